@@ -1,6 +1,9 @@
 //! Benchmark for UniversalHash algorithm
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::num::NonZeroUsize;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use uhash::pool::{Share, ShareJob, ShareValidator};
 use uhash::UniversalHash;
 
 fn bench_hash(c: &mut Criterion) {
@@ -25,5 +28,71 @@ fn bench_hash_varying_input(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_hash, bench_hash_varying_input);
+/// Shares a pool can check per second: fresh hashers against the pooled,
+/// batched validator, and garbage the validator refuses without hashing
+fn bench_share_validation(c: &mut Criterion) {
+    const BATCH: u64 = 64;
+    let job = ShareJob {
+        seed: [3u8; 32],
+        address: "bostrom1pool".to_string(),
+        timestamp: 1_700_000_000,
+        network_difficulty: 20,
+    };
+    let input = |nonce: u64| {
+        let mut input = job.seed.to_vec();
+        input.extend_from_slice(job.address.as_bytes());
+        input.extend_from_slice(&job.timestamp.to_le_bytes());
+        input.extend_from_slice(&nonce.to_le_bytes());
+        input
+    };
+    let mut hasher = UniversalHash::new();
+    let shares: Vec<Share> = (0..BATCH)
+        .map(|nonce| Share {
+            nonce,
+            hash: hex::encode(hasher.hash(&input(nonce))),
+            difficulty: 0,
+        })
+        .collect();
+    let garbage: Vec<Share> = (0..BATCH)
+        .map(|nonce| Share {
+            nonce,
+            hash: "ff".repeat(32),
+            difficulty: 8,
+        })
+        .collect();
+    let threads = std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+    let validator = ShareValidator::new(threads);
+
+    let mut group = c.benchmark_group("share_validation");
+    group.throughput(Throughput::Elements(BATCH));
+    group.sample_size(10);
+    group.bench_function("fresh_hasher_each", |b| {
+        b.iter(|| {
+            for share in &shares {
+                black_box(UniversalHash::new().hash(&input(share.nonce)));
+            }
+        })
+    });
+    group.bench_function("pooled_single", |b| {
+        b.iter(|| {
+            for share in &shares {
+                black_box(validator.verify(&job, share)).unwrap();
+            }
+        })
+    });
+    group.bench_function("pooled_batch", |b| {
+        b.iter(|| black_box(validator.verify_batch(&job, &shares)))
+    });
+    group.bench_function("rejected_unhashed", |b| {
+        b.iter(|| black_box(validator.verify_batch(&job, &garbage)))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_hash,
+    bench_hash_varying_input,
+    bench_share_validation
+);
 criterion_main!(benches);
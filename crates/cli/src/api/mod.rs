@@ -0,0 +1,204 @@
+//! Remote management API of a running miner
+//!
+//! `mine --api <addr>` serves a small authenticated HTTP API so fleet tooling
+//! can watch and steer many miners:
+//!
+//! - `GET /status` — the miner's [`MinerStatus`], the same document as the
+//!   status file and the dashboard
+//! - `GET /proofs` — the recent [`ProofLogEntry`] log
+//! - `POST /pause`, `POST /resume` — stop hashing without exiting, and go on
+//! - `POST /threads` with `{"threads": n}` — change the thread count
+//!
+//! Every request needs `Authorization: Bearer <token>`. Changes reach the
+//! mining loop through a shared [`MinerControl`] and apply from the next round,
+//! which the miner starts at once.
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+
+pub use crate::miner::MinerControl;
+use crate::server;
+use crate::status::{MinerStatus, ProofLogEntry};
+
+/// Most threads `POST /threads` accepts
+pub const MAX_THREADS: usize = 1024;
+
+/// Body of `POST /threads`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadsRequest {
+    pub threads: usize,
+}
+
+/// The API server for one miner
+#[derive(Debug, Clone)]
+pub struct Api {
+    control: Arc<MinerControl>,
+    status: Arc<Mutex<MinerStatus>>,
+    token: Arc<str>,
+}
+
+impl Api {
+    /// Serve `status` and steer `control`, accepting requests that carry `token`
+    pub fn new(control: Arc<MinerControl>, status: Arc<Mutex<MinerStatus>>, token: &str) -> Self {
+        Self {
+            control,
+            status,
+            token: token.into(),
+        }
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/status", get(status))
+            .route("/proofs", get(proofs))
+            .route("/pause", post(pause))
+            .route("/resume", post(resume))
+            .route("/threads", post(threads))
+            .with_state(self.clone())
+    }
+
+    /// Serve the control API on an already bound listener until the server fails
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
+        server::serve_router(listener, self.router()).await
+    }
+
+    /// Serve the control API on `addr` in a background task, returning the bound address
+    pub fn spawn(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        server::spawn_server(addr, self.router())
+    }
+
+    /// The 401 response for a request without the token, `None` if it has it
+    fn unauthorized(&self, headers: &HeaderMap) -> Option<Response> {
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or("");
+        if constant_time_eq(presented.as_bytes(), self.token.as_bytes()) {
+            return None;
+        }
+        Some(error(
+            StatusCode::UNAUTHORIZED,
+            "missing or wrong bearer token",
+        ))
+    }
+
+    fn snapshot(&self) -> MinerStatus {
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+/// Compare without an early exit, so response timing does not leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn status(State(api): State<Api>, headers: HeaderMap) -> Response {
+    if let Some(denied) = api.unauthorized(&headers) {
+        return denied;
+    }
+    Json(api.snapshot()).into_response()
+}
+
+async fn proofs(State(api): State<Api>, headers: HeaderMap) -> Response {
+    if let Some(denied) = api.unauthorized(&headers) {
+        return denied;
+    }
+    let proofs: Vec<ProofLogEntry> = api.snapshot().proofs.into();
+    Json(proofs).into_response()
+}
+
+async fn pause(State(api): State<Api>, headers: HeaderMap) -> Response {
+    if let Some(denied) = api.unauthorized(&headers) {
+        return denied;
+    }
+    api.control.pause();
+    Json(json!({ "paused": true })).into_response()
+}
+
+async fn resume(State(api): State<Api>, headers: HeaderMap) -> Response {
+    if let Some(denied) = api.unauthorized(&headers) {
+        return denied;
+    }
+    api.control.resume();
+    Json(json!({ "paused": false })).into_response()
+}
+
+async fn threads(
+    State(api): State<Api>,
+    headers: HeaderMap,
+    Json(request): Json<ThreadsRequest>,
+) -> Response {
+    if let Some(denied) = api.unauthorized(&headers) {
+        return denied;
+    }
+    if request.threads == 0 || request.threads > MAX_THREADS {
+        return error(
+            StatusCode::BAD_REQUEST,
+            &format!("threads must be between 1 and {}", MAX_THREADS),
+        );
+    }
+    api.control.set_threads(request.threads);
+    Json(request).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_restarts_only_on_change() {
+        let control = MinerControl::new(4);
+        assert!(!control.take_restart());
+        control.set_threads(4);
+        assert!(!control.take_restart());
+        control.set_threads(2);
+        assert_eq!(control.threads(), 2);
+        assert!(control.take_restart());
+        assert!(!control.take_restart());
+
+        control.pause();
+        assert!(control.is_paused());
+        assert!(control.take_restart());
+        control.resume();
+        assert!(!control.is_paused());
+    }
+
+    #[test]
+    fn test_hold_outlasts_resume() {
+        let control = MinerControl::new(4);
+        control.hold(true);
+        assert!(control.is_paused());
+        assert!(control.take_restart());
+        control.hold(true);
+        assert!(!control.take_restart());
+
+        // Resuming through the API does not override an idle hold
+        control.resume();
+        assert!(control.is_paused());
+        control.hold(false);
+        assert!(!control.is_paused());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+}
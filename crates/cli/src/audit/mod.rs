@@ -0,0 +1,343 @@
+//! Re-verification of proofs the contract accepted
+//!
+//! `uhash audit` replays the contract's `submit_proof` transactions and checks
+//! every proof again with the core algorithm: its hash must be the U-Hash of
+//! an epoch seed, the miner address and worker tag, the timestamp and the
+//! nonce (see [`crate::proof::proof_input`]), it must
+//! meet the difficulty it was accepted at, it must not have been accepted
+//! before, and its timestamp must not be ahead of its block.
+//!
+//! The contract only reports the seed and difficulty as of a block, so
+//! [`History`] reads them from an archive LCD at the proof's height and the
+//! one before, and walks back to the seed that preceded them: a proof mined
+//! just before a rotation is still accepted on the previous seed.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::proof::proof_input;
+use crate::rpc::{self, DifficultyResponse, QueryMsg, RpcClient, SeedResponse, SubmittedProof};
+use crate::{meets_difficulty, UniversalHash};
+
+/// How far a proof timestamp may run ahead of its block, in seconds
+pub const FUTURE_TOLERANCE_SECS: u64 = 60;
+
+/// Blocks stepped back per query while looking for the previous seed
+const SEED_LOOKBACK_BLOCKS: u64 = 50;
+
+/// Steps taken before giving up on finding the previous seed
+const SEED_LOOKBACK_STEPS: u64 = 20;
+
+/// Why an accepted proof does not check out
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    #[error("hash is not the U-Hash of the proof inputs under any seed active at the time")]
+    HashMismatch,
+
+    #[error("hash does not meet difficulty {difficulty}")]
+    BelowDifficulty { difficulty: u32 },
+
+    #[error("hash was already accepted in {first_tx}")]
+    Duplicate { first_tx: String },
+
+    #[error("timestamp {timestamp} is ahead of its block time {block_time}")]
+    FutureTimestamp { timestamp: u64, block_time: u64 },
+}
+
+/// Checks proofs in chain order, remembering the hashes already accepted
+pub struct Auditor {
+    hasher: UniversalHash,
+    /// Lowercase hex hash → transaction that first carried it
+    accepted: HashMap<String, String>,
+}
+
+impl Default for Auditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Auditor {
+    pub fn new() -> Self {
+        Self {
+            hasher: UniversalHash::new(),
+            accepted: HashMap::new(),
+        }
+    }
+
+    /// Check `proof` against the candidate `seeds` and the `difficulty` it
+    /// was accepted at
+    pub fn check(
+        &mut self,
+        proof: &SubmittedProof,
+        seeds: &[[u8; 32]],
+        difficulty: u32,
+    ) -> Result<(), Discrepancy> {
+        let hash_hex = proof.hash.to_ascii_lowercase();
+        if let Some(first_tx) = self.accepted.get(&hash_hex) {
+            return Err(Discrepancy::Duplicate {
+                first_tx: first_tx.clone(),
+            });
+        }
+        self.accepted
+            .insert(hash_hex.clone(), proof.tx_hash.clone());
+
+        let tag = proof
+            .worker_tag
+            .as_deref()
+            .map_or(Ok(Vec::new()), hex::decode)
+            .map_err(|_| Discrepancy::HashMismatch)?;
+        let hash = seeds
+            .iter()
+            .map(|seed| {
+                let input = proof_input(seed, &proof.miner, &tag, proof.timestamp, proof.nonce);
+                self.hasher.hash(&input)
+            })
+            .find(|hash| hex::encode(hash) == hash_hex)
+            .ok_or(Discrepancy::HashMismatch)?;
+        if !meets_difficulty(&hash, difficulty) {
+            return Err(Discrepancy::BelowDifficulty { difficulty });
+        }
+        if proof.timestamp > proof.time + FUTURE_TOLERANCE_SECS {
+            return Err(Discrepancy::FutureTimestamp {
+                timestamp: proof.timestamp,
+                block_time: proof.time,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Seeds and difficulties at past heights, cached as they are fetched
+#[derive(Debug, Default)]
+pub struct History {
+    seeds: HashMap<u64, [u8; 32]>,
+    difficulties: HashMap<u64, u32>,
+    /// Seed → the seed active before it, if one was found
+    previous: HashMap<[u8; 32], Option<[u8; 32]>>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The seed as of block `height`
+    pub async fn seed_at(&mut self, client: &RpcClient, height: u64) -> rpc::Result<[u8; 32]> {
+        if let Some(seed) = self.seeds.get(&height) {
+            return Ok(*seed);
+        }
+        let resp: SeedResponse = client.query_smart_at(&QueryMsg::Seed {}, height).await?;
+        let seed = hex::decode(&resp.seed)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                rpc::RpcError::InvalidResponse(format!(
+                    "seed '{}' at height {} is not 32 hex-encoded bytes",
+                    resp.seed, height
+                ))
+            })?;
+        self.seeds.insert(height, seed);
+        Ok(seed)
+    }
+
+    /// Seeds a proof committed in block `height` may have been mined on
+    ///
+    /// The seed after the block (the contract may rotate while executing it),
+    /// the seed before it, and the seed that preceded that one.
+    pub async fn candidate_seeds(
+        &mut self,
+        client: &RpcClient,
+        height: u64,
+    ) -> rpc::Result<Vec<[u8; 32]>> {
+        let mut seeds = vec![self.seed_at(client, height).await?];
+        let before = self
+            .seed_at(client, height.saturating_sub(1).max(1))
+            .await?;
+        if !seeds.contains(&before) {
+            seeds.push(before);
+        }
+        if let Some(previous) = self.previous_seed(client, before, height).await? {
+            if !seeds.contains(&previous) {
+                seeds.push(previous);
+            }
+        }
+        Ok(seeds)
+    }
+
+    /// The seed active before `seed`, looking back from block `height`
+    async fn previous_seed(
+        &mut self,
+        client: &RpcClient,
+        seed: [u8; 32],
+        height: u64,
+    ) -> rpc::Result<Option<[u8; 32]>> {
+        if let Some(previous) = self.previous.get(&seed) {
+            return Ok(*previous);
+        }
+        let mut previous = None;
+        let mut lookback = height;
+        for _ in 0..SEED_LOOKBACK_STEPS {
+            if lookback <= 1 {
+                break;
+            }
+            lookback = lookback.saturating_sub(SEED_LOOKBACK_BLOCKS).max(1);
+            let earlier = self.seed_at(client, lookback).await?;
+            if earlier != seed {
+                previous = Some(earlier);
+                break;
+            }
+        }
+        self.previous.insert(seed, previous);
+        Ok(previous)
+    }
+
+    /// The difficulty as of block `height`
+    pub async fn difficulty_at(&mut self, client: &RpcClient, height: u64) -> rpc::Result<u32> {
+        if let Some(difficulty) = self.difficulties.get(&height) {
+            return Ok(*difficulty);
+        }
+        let resp: DifficultyResponse = client
+            .query_smart_at(&QueryMsg::Difficulty {}, height)
+            .await?;
+        self.difficulties.insert(height, resp.current);
+        Ok(resp.current)
+    }
+
+    /// The difficulty a proof in block `height` was held to
+    ///
+    /// The lower of the difficulties before and after the block, unless the
+    /// contract reported it with the proof, so a retarget during the block
+    /// is never held against the proof.
+    pub async fn proof_difficulty(
+        &mut self,
+        client: &RpcClient,
+        proof: &SubmittedProof,
+    ) -> rpc::Result<u32> {
+        if let Some(difficulty) = proof.difficulty {
+            return Ok(difficulty);
+        }
+        let after = self.difficulty_at(client, proof.height).await?;
+        let before = self
+            .difficulty_at(client, proof.height.saturating_sub(1).max(1))
+            .await?;
+        Ok(after.min(before))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: [u8; 32] = [7; 32];
+
+    fn mined(difficulty: u32) -> SubmittedProof {
+        mined_with_tag(difficulty, None)
+    }
+
+    fn mined_with_tag(difficulty: u32, worker_tag: Option<&[u8]>) -> SubmittedProof {
+        let mut hasher = UniversalHash::new();
+        let tag = worker_tag.unwrap_or_default();
+        let mut proof = SubmittedProof {
+            tx_hash: "TX1".to_string(),
+            height: 10,
+            time: 1_700_000_000,
+            miner: "bostrom1miner".to_string(),
+            hash: String::new(),
+            nonce: 0,
+            timestamp: 1_700_000_000,
+            difficulty: Some(difficulty),
+            worker_tag: worker_tag.map(hex::encode),
+        };
+        for nonce in 0u64.. {
+            let hash = hasher.hash(&proof_input(
+                &SEED,
+                &proof.miner,
+                tag,
+                proof.timestamp,
+                nonce,
+            ));
+            if meets_difficulty(&hash, difficulty) {
+                proof.hash = hex::encode(hash);
+                proof.nonce = nonce;
+                return proof;
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_valid_proof_checks_out_once() {
+        let proof = mined(2);
+        let mut auditor = Auditor::new();
+        assert_eq!(auditor.check(&proof, &[[0; 32], SEED], 2), Ok(()));
+
+        let mut again = proof.clone();
+        again.tx_hash = "TX-REPLAY".to_string();
+        again.hash = proof.hash.to_ascii_uppercase();
+        assert_eq!(
+            auditor.check(&again, &[SEED], 2),
+            Err(Discrepancy::Duplicate {
+                first_tx: proof.tx_hash.clone()
+            })
+        );
+    }
+
+    #[test]
+    fn test_tagged_proof_checks_out() {
+        let proof = mined_with_tag(2, Some(b"rig-7"));
+        assert_eq!(Auditor::new().check(&proof, &[SEED], 2), Ok(()));
+
+        // The tag is part of the input: without it, or with another, the hash is wrong
+        let untagged = SubmittedProof {
+            worker_tag: None,
+            ..proof.clone()
+        };
+        assert_eq!(
+            Auditor::new().check(&untagged, &[SEED], 2),
+            Err(Discrepancy::HashMismatch)
+        );
+        let retagged = SubmittedProof {
+            worker_tag: Some(hex::encode(b"rig-8")),
+            ..proof
+        };
+        assert_eq!(
+            Auditor::new().check(&retagged, &[SEED], 2),
+            Err(Discrepancy::HashMismatch)
+        );
+    }
+
+    #[test]
+    fn test_discrepancies() {
+        let proof = mined(2);
+        let check = |proof: &SubmittedProof, seeds: &[[u8; 32]], difficulty| {
+            Auditor::new().check(proof, seeds, difficulty)
+        };
+
+        assert_eq!(check(&proof, &[[0; 32]], 2), Err(Discrepancy::HashMismatch));
+        let mut forged = proof.clone();
+        forged.nonce += 1;
+        assert_eq!(check(&forged, &[SEED], 2), Err(Discrepancy::HashMismatch));
+
+        // Claimed at a difficulty the hash falls short of
+        let leading_zeros = hex::decode(&proof.hash)
+            .unwrap()
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap() as u32
+            * 8;
+        assert!(matches!(
+            check(&proof, &[SEED], leading_zeros + 9),
+            Err(Discrepancy::BelowDifficulty { .. })
+        ));
+
+        let mut early = proof.clone();
+        early.time = proof.timestamp - FUTURE_TOLERANCE_SECS - 1;
+        assert!(matches!(
+            check(&early, &[SEED], 2),
+            Err(Discrepancy::FutureTimestamp { .. })
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! Worker authentication and rate limits
+//!
+//! A controller or pool listening on a public address would otherwise take
+//! work from anyone, and every share it is sent costs it a full hash to
+//! check. An [`Authenticator`] admits only workers that present one of its
+//! tokens, or that prove they hold an allowed key by signing a fresh
+//! challenge (ADR-036, see [`challenge_data`]). A [`RateLimit`] then bounds
+//! the messages each admitted worker may send.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::wallet::{verify_arbitrary, StdSignature};
+
+/// Messages per second a worker may send by default
+pub const DEFAULT_RATE_LIMIT: f64 = 20.0;
+
+/// Messages a worker may send at once above the rate, by default
+pub const DEFAULT_RATE_BURST: u32 = 100;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("authentication required (pass --token, or --key with an allowed wallet)")]
+    Required,
+
+    #[error("invalid token")]
+    InvalidToken,
+
+    #[error("key {0} is not allowed")]
+    KeyNotAllowed(String),
+
+    #[error("challenge signature is invalid")]
+    InvalidSignature,
+}
+
+/// Who may connect; with no tokens and no keys, anyone
+#[derive(Debug, Clone, Default)]
+pub struct Authenticator {
+    /// SHA-256 of each token, so tokens are compared in constant time
+    tokens: Vec<[u8; 32]>,
+    keys: HashSet<String>,
+}
+
+impl Authenticator {
+    /// An authenticator that admits everyone, until tokens or keys are added
+    pub fn open() -> Self {
+        Self::default()
+    }
+
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.tokens.push(Sha256::digest(token.as_bytes()).into());
+        self
+    }
+
+    /// Admit the holder of the key of `address`
+    pub fn with_key(mut self, address: &str) -> Self {
+        self.keys.insert(address.to_string());
+        self
+    }
+
+    /// Whether everyone is admitted
+    pub fn is_open(&self) -> bool {
+        self.tokens.is_empty() && self.keys.is_empty()
+    }
+
+    /// Check a worker's token, if it sent one
+    pub fn check_token(&self, token: Option<&str>) -> Result<(), AuthError> {
+        if self.is_open() {
+            return Ok(());
+        }
+        let token = token.ok_or(AuthError::Required)?;
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        // Compare every byte of every token, whatever matches first
+        let matched = self.tokens.iter().fold(false, |matched, known| {
+            let diff = known.iter().zip(&digest).fold(0u8, |d, (a, b)| d | (a ^ b));
+            matched | (diff == 0)
+        });
+        if matched {
+            Ok(())
+        } else {
+            Err(AuthError::InvalidToken)
+        }
+    }
+
+    /// Whether `address` may sign in with its key
+    pub fn allows_key(&self, address: &str) -> Result<(), AuthError> {
+        if self.keys.contains(address) {
+            Ok(())
+        } else {
+            Err(AuthError::KeyNotAllowed(address.to_string()))
+        }
+    }
+
+    /// Check that `signature` is `address`'s over `challenge`
+    pub fn verify_key(
+        &self,
+        address: &str,
+        challenge: &str,
+        signature: &StdSignature,
+    ) -> Result<(), AuthError> {
+        self.allows_key(address)?;
+        match verify_arbitrary(address, &challenge_data(challenge), signature) {
+            Ok(true) => Ok(()),
+            _ => Err(AuthError::InvalidSignature),
+        }
+    }
+}
+
+/// A random challenge for a worker to sign
+pub fn new_challenge() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("OS random number generator");
+    hex::encode(bytes)
+}
+
+/// Bytes a worker signs to answer `challenge`
+///
+/// The prefix keeps the signature from passing for any other ADR-036 message.
+pub fn challenge_data(challenge: &str) -> Vec<u8> {
+    format!("uhash worker sign-in: {}", challenge).into_bytes()
+}
+
+/// Token bucket bounding how often one worker may send
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    per_second: f64,
+    burst: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl RateLimit {
+    /// `per_second` messages on average, `burst` at once
+    pub fn new(per_second: f64, burst: u32, now: Instant) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            per_second,
+            burst,
+            available: burst,
+            updated: now,
+        }
+    }
+
+    /// Take one message's allowance, `false` if there is none left
+    pub fn allow(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.updated = now;
+        self.available = (self.available + elapsed.as_secs_f64() * self.per_second).min(self.burst);
+        if self.available < 1.0 {
+            return false;
+        }
+        self.available -= 1.0;
+        true
+    }
+
+    /// Time until the next message is allowed
+    pub fn retry_after(&self) -> Duration {
+        if self.available >= 1.0 || self.per_second <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((1.0 - self.available) / self.per_second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn test_tokens() {
+        assert!(Authenticator::open().check_token(None).is_ok());
+        let auth = Authenticator::open()
+            .with_token("s3cret")
+            .with_token("other");
+        assert_eq!(auth.check_token(None), Err(AuthError::Required));
+        assert_eq!(
+            auth.check_token(Some("s3cre")),
+            Err(AuthError::InvalidToken)
+        );
+        assert_eq!(auth.check_token(Some("s3cret")), Ok(()));
+        assert_eq!(auth.check_token(Some("other")), Ok(()));
+    }
+
+    #[test]
+    fn test_key_challenge() {
+        let worker = Wallet::new().unwrap();
+        let stranger = Wallet::new().unwrap();
+        let auth = Authenticator::open().with_key(&worker.address_str());
+        let challenge = new_challenge();
+        assert_ne!(challenge, new_challenge());
+
+        let signature = worker.sign_arbitrary(&challenge_data(&challenge)).unwrap();
+        assert_eq!(
+            auth.verify_key(&worker.address_str(), &challenge, &signature),
+            Ok(())
+        );
+        // A signature over another challenge is a replay
+        assert_eq!(
+            auth.verify_key(&worker.address_str(), &new_challenge(), &signature),
+            Err(AuthError::InvalidSignature)
+        );
+        let forged = stranger
+            .sign_arbitrary(&challenge_data(&challenge))
+            .unwrap();
+        assert_eq!(
+            auth.verify_key(&stranger.address_str(), &challenge, &forged),
+            Err(AuthError::KeyNotAllowed(stranger.address_str()))
+        );
+        assert_eq!(
+            auth.verify_key(&worker.address_str(), &challenge, &forged),
+            Err(AuthError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let start = Instant::now();
+        let mut limit = RateLimit::new(2.0, 3, start);
+        assert!((0..3).all(|_| limit.allow(start)));
+        assert!(!limit.allow(start));
+        assert_eq!(limit.retry_after(), Duration::from_millis(500));
+        assert!(limit.allow(start + Duration::from_millis(500)));
+        assert!(!limit.allow(start + Duration::from_millis(600)));
+        // Idle time refills up to the burst, no further
+        let later = start + Duration::from_secs(60);
+        assert!((0..3).all(|_| limit.allow(later)));
+        assert!(!limit.allow(later));
+    }
+}
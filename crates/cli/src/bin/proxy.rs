@@ -0,0 +1,389 @@
+//! UniversalHash aggregation proxy
+//!
+//! Accepts the phones and LAN devices of one farm over the pool protocol and
+//! mines for them upstream as one: a single pool connection with `--pool`,
+//! or one RPC client and wallet submitting to the chain otherwise. See
+//! [`uhash::proxy`] for how shares are checked and counted.
+
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use uhash::config::{default_config_path, Config};
+use uhash::pool::VardiffConfig;
+use uhash::proxy::{PoolUpstream, Proxy, ProxyEvent, DEFAULT_START_DIFFICULTY};
+use uhash::rpc::{
+    lcd_url_for_rpc, validate_endpoint, ProofSubmission, RpcClient, RpcConfig, SeedInfo,
+};
+use uhash::sink::{self, Delivery};
+use uhash::wallet::{default_wallet_path, validate_address_with_prefix, Wallet};
+use uhash_pool_proto::{Job, ServerMessage};
+
+#[derive(Parser)]
+#[command(name = "uhash-proxy")]
+#[command(author = "Cyberia")]
+#[command(version)]
+#[command(about = "Mine for many local workers through one pool connection or account")]
+struct Args {
+    /// Address to accept workers on
+    #[arg(long, default_value = "0.0.0.0:3334")]
+    listen: SocketAddr,
+
+    /// Pool to mine for, speaking the uhash pool protocol over TCP
+    /// (default: submit proofs to the chain)
+    #[arg(long, value_name = "tcp://HOST:PORT")]
+    pool: Option<String>,
+
+    /// Address the pool pays (default: the wallet's)
+    #[arg(long, requires = "pool")]
+    address: Option<String>,
+
+    /// Name the proxy signs in to the pool with
+    #[arg(long, requires = "pool")]
+    worker: Option<String>,
+
+    /// Plaintext wallet file of the account proofs are mined for
+    #[arg(long)]
+    wallet: Option<PathBuf>,
+
+    /// Account index derived from the wallet mnemonic
+    #[arg(long, default_value = "0")]
+    account: u32,
+
+    /// Config file with the chain and network settings (default: ~/.uhash/config.toml)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Custom RPC endpoint(s), comma-separated; later ones are failover targets
+    #[arg(long, value_delimiter = ',')]
+    rpc: Vec<String>,
+
+    /// LCD (REST) endpoint(s), comma-separated (default: derived from --rpc)
+    #[arg(long, value_delimiter = ',')]
+    lcd: Vec<String>,
+
+    /// Custom contract address (default: production contract)
+    #[arg(long)]
+    contract: Option<String>,
+
+    /// Share difficulty new workers start at
+    #[arg(long, default_value_t = DEFAULT_START_DIFFICULTY)]
+    start_difficulty: u32,
+
+    /// Seconds between seed and difficulty checks on the chain
+    #[arg(long, default_value = "30")]
+    refresh: u64,
+
+    /// Seconds between hashrate reports
+    #[arg(long, default_value = "60")]
+    report: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> anyhow::Result<()> {
+    let config_path = args.config.clone().unwrap_or_else(default_config_path);
+    let settings = Config::load(&config_path)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let (proxy, events) = Proxy::new(u32::MAX, VardiffConfig::default());
+        let proxy = proxy.with_start_difficulty(args.start_difficulty);
+        let addr = proxy.spawn(args.listen)?;
+        println!("Accepting workers on tcp://{}", addr);
+        match &args.pool {
+            Some(pool) => mine_for_pool(args, &settings, pool, proxy, events).await,
+            None => mine_for_chain(args, &settings, proxy, events).await,
+        }
+    })
+}
+
+type Events = tokio::sync::mpsc::UnboundedReceiver<ProxyEvent>;
+
+/// Totals for the periodic report
+#[derive(Default)]
+struct Tally {
+    forwarded: u64,
+    accepted: u64,
+    rejected: u64,
+}
+
+async fn mine_for_pool(
+    args: &Args,
+    settings: &Config,
+    pool: &str,
+    proxy: Proxy,
+    mut events: Events,
+) -> anyhow::Result<()> {
+    let host = pool
+        .strip_prefix("tcp://")
+        .ok_or_else(|| anyhow::anyhow!("Pool URL must be tcp://HOST:PORT, got {}", pool))?;
+    let address = match &args.address {
+        Some(address) => {
+            validate_address_with_prefix(address, &settings.chain.bech32_prefix)?;
+            address.clone()
+        }
+        None => load_wallet(args, settings)?.address().to_string(),
+    };
+    let mut upstream = PoolUpstream::connect(host, &address, args.worker.clone()).await?;
+    println!("Mining for the pool at {} as {}", pool, address);
+    println!("\nPress Ctrl-C to stop.");
+
+    let mut tally = Tally::default();
+    let mut report = tokio::time::interval(Duration::from_secs(args.report.max(1)));
+    report.tick().await;
+    loop {
+        tokio::select! {
+            message = upstream.recv() => match message? {
+                Some(ServerMessage::Welcome { difficulty, .. })
+                | Some(ServerMessage::SetDifficulty { difficulty }) => {
+                    proxy.set_upstream_difficulty(difficulty);
+                }
+                Some(ServerMessage::Job(job)) => proxy.set_job(job),
+                Some(ServerMessage::JobUpdate(update)) => {
+                    if let Some(job) = proxy.job() {
+                        proxy.set_job(job.apply(&update));
+                    }
+                }
+                Some(ServerMessage::Accepted { proof, .. }) => {
+                    tally.accepted += 1;
+                    if proof {
+                        println!("The pool found a proof with one of our shares");
+                    }
+                }
+                Some(ServerMessage::Rejected { reason, .. }) => {
+                    tally.rejected += 1;
+                    eprintln!("Warning: the pool rejected a share: {:?}", reason);
+                }
+                Some(_) => {}
+                None => anyhow::bail!("The pool closed the connection"),
+            },
+            Some(event) = events.recv() => {
+                if let ProxyEvent::Share(share) = log_event(event) {
+                    upstream.share(&share).await?;
+                    tally.forwarded += 1;
+                }
+            }
+            _ = report.tick() => {
+                print_report(&proxy, &tally);
+                upstream.keepalive().await?;
+            }
+        }
+    }
+}
+
+async fn mine_for_chain(
+    args: &Args,
+    settings: &Config,
+    proxy: Proxy,
+    mut events: Events,
+) -> anyhow::Result<()> {
+    let wallet = load_wallet(args, settings)?;
+    let address = wallet.address().to_string();
+    let signing_key =
+        cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key()?.to_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?;
+    let client = RpcClient::try_with_config(rpc_config(args, settings)?)?
+        .on_warning(|warning| eprintln!("Warning: {}", warning));
+    let submit = &settings.submit;
+    let sink = sink::from_settings(
+        submit.sink,
+        &submit.url,
+        &submit.path,
+        &client,
+        Some(signing_key),
+    )?;
+    let max_proof_age = client.get_config().await.ok().map(|c| c.max_proof_age);
+
+    let mut chain = ChainJobs::default();
+    chain.refresh(&client).await?;
+    proxy.set_upstream_difficulty(chain.difficulty);
+    proxy.set_job(chain.next_job(&address));
+    println!("Mining for {} on {}", address, sink.destination());
+    println!("\nPress Ctrl-C to stop.");
+
+    let mut tally = Tally::default();
+    let mut refresh = tokio::time::interval(Duration::from_secs(args.refresh.max(1)));
+    let mut report = tokio::time::interval(Duration::from_secs(args.report.max(1)));
+    refresh.tick().await;
+    report.tick().await;
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                let seed = chain.seed.seed;
+                let difficulty = chain.difficulty;
+                if let Err(e) = chain.refresh(&client).await {
+                    eprintln!("Warning: could not refresh the job: {}", e);
+                    continue;
+                }
+                // Proofs must land before the contract thinks them too old
+                let aged = max_proof_age
+                    .is_some_and(|age| unix_now().saturating_sub(chain.timestamp) >= age / 2);
+                if seed != chain.seed.seed || difficulty != chain.difficulty || aged {
+                    proxy.set_upstream_difficulty(chain.difficulty);
+                    proxy.set_job(chain.next_job(&address));
+                }
+            }
+            Some(event) = events.recv() => {
+                let ProxyEvent::Share(share) = log_event(event) else {
+                    continue;
+                };
+                tally.forwarded += 1;
+                let Some(job) = proxy.job().filter(|job| job.job_id == share.job_id) else {
+                    continue;
+                };
+                let proof = ProofSubmission {
+                    hash: hex::encode(share.hash),
+                    nonce: share.nonce,
+                    timestamp: job.timestamp,
+                    miner_address: address.clone(),
+                    worker_tag: None,
+                };
+                println!("{} found a proof, submitting to {}", share.worker, sink.destination());
+                match sink.submit(&proof).await {
+                    Ok(Delivery::Committed { tx_hash, .. }) | Ok(Delivery::Relayed { tx_hash }) => {
+                        tally.accepted += 1;
+                        println!("Proof accepted! TX: {}", tx_hash);
+                    }
+                    Ok(_) => tally.accepted += 1,
+                    Err(e) => {
+                        tally.rejected += 1;
+                        eprintln!("Warning: proof submission failed: {}", e);
+                    }
+                }
+                // A fresh timestamp, so workers stop hashing toward the same proof
+                proxy.set_job(chain.next_job(&address));
+            }
+            _ = report.tick() => print_report(&proxy, &tally),
+        }
+    }
+}
+
+/// The chain state jobs are made from
+struct ChainJobs {
+    seed: SeedInfo,
+    difficulty: u32,
+    /// Timestamp of the last job
+    timestamp: u64,
+    next_id: u64,
+}
+
+impl Default for ChainJobs {
+    fn default() -> Self {
+        Self {
+            seed: SeedInfo::new([0u8; 32], 0),
+            difficulty: 0,
+            timestamp: 0,
+            next_id: 1,
+        }
+    }
+}
+
+impl ChainJobs {
+    async fn refresh(&mut self, client: &RpcClient) -> anyhow::Result<()> {
+        let (seed, difficulty) = tokio::try_join!(client.get_seed(), client.get_difficulty())?;
+        self.seed = seed;
+        self.difficulty = difficulty;
+        Ok(())
+    }
+
+    fn next_job(&mut self, address: &str) -> Job {
+        self.timestamp = unix_now();
+        let job = Job {
+            job_id: format!("{:x}", self.next_id),
+            seed: hex::encode(self.seed.seed),
+            address: address.to_string(),
+            timestamp: self.timestamp,
+            network_difficulty: self.difficulty,
+        };
+        self.next_id += 1;
+        job
+    }
+}
+
+/// Print worker comings and goings; the event is handed back
+fn log_event(event: ProxyEvent) -> ProxyEvent {
+    match &event {
+        ProxyEvent::Connected(worker) => {
+            println!("Worker {} connected from {}", worker.name, worker.peer)
+        }
+        ProxyEvent::Disconnected(worker) => {
+            println!("Worker {} left after {} shares", worker.name, worker.shares)
+        }
+        ProxyEvent::Share(_) | ProxyEvent::Rejected { .. } => {}
+    }
+    event
+}
+
+fn print_report(proxy: &Proxy, tally: &Tally) {
+    let workers = proxy.workers();
+    let shares: u64 = workers.iter().map(|w| w.shares).sum();
+    println!(
+        "{} workers, {:.1} H/s, {} shares; upstream {} sent, {} accepted, {} rejected",
+        workers.len(),
+        proxy.hashrate(),
+        shares,
+        tally.forwarded,
+        tally.accepted,
+        tally.rejected
+    );
+}
+
+fn load_wallet(args: &Args, settings: &Config) -> anyhow::Result<Wallet> {
+    let wallet_path = args.wallet.clone().unwrap_or_else(default_wallet_path);
+    Wallet::load_from_file(&wallet_path)
+        .and_then(|wallet| wallet.for_chain(&settings.chain))
+        .and_then(|wallet| wallet.derive_account(args.account))
+        .map_err(|e| anyhow::anyhow!("Cannot load {}: {}", wallet_path.display(), e))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn rpc_config(args: &Args, settings: &Config) -> anyhow::Result<RpcConfig> {
+    let mut config = RpcConfig::from_profile(&settings.chain);
+    let (rpc_endpoints, lcd_endpoints) = if args.rpc.is_empty() && args.lcd.is_empty() {
+        (&settings.network.rpc, &settings.network.lcd)
+    } else {
+        (&args.rpc, &args.lcd)
+    };
+    for url in rpc_endpoints.iter().chain(lcd_endpoints) {
+        validate_endpoint(url)?;
+    }
+    if let Some(primary) = rpc_endpoints.first() {
+        config.rpc_url = primary.to_string();
+    }
+    let lcd_endpoints = if lcd_endpoints.is_empty() {
+        rpc_endpoints
+            .iter()
+            .map(|url| lcd_url_for_rpc(url))
+            .collect()
+    } else {
+        lcd_endpoints.clone()
+    };
+    if let Some((primary, fallbacks)) = lcd_endpoints.split_first() {
+        config.lcd_url = primary.to_string();
+        config.fallback_lcd_urls = fallbacks.to_vec();
+    }
+    if let Some(addr) = &args.contract {
+        validate_address_with_prefix(addr, &settings.chain.bech32_prefix)?;
+        config.contract_address = addr.to_string();
+    }
+    config.proxy = Some(settings.network.proxy.clone()).filter(|p| !p.is_empty());
+    config.ca_certs = settings.network.ca_certs.clone();
+    config.custom_roots_only = settings.network.custom_roots_only;
+    config.connect_timeout_secs = settings.network.connect_timeout;
+    config.request_timeout_secs = settings.network.request_timeout;
+    config.cache_ttl_secs = settings.network.cache_ttl;
+    Ok(config)
+}
@@ -0,0 +1,156 @@
+//! UniversalHash proof relay
+//!
+//! Accepts proofs from miners that hold no funded account (phones, browsers)
+//! over HTTP, checks them locally, and submits them from the relayer's own
+//! account. See [`uhash::relay`] for the protocol.
+
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use uhash::config::{default_config_path, Config};
+use uhash::relay::Relay;
+use uhash::rpc::{
+    lcd_url_for_rpc, parse_gas, parse_gas_price, validate_endpoint, BroadcastMode, RpcClient,
+    RpcConfig,
+};
+use uhash::wallet::{default_wallet_path, validate_address_with_prefix, Wallet};
+
+#[derive(Parser)]
+#[command(name = "uhash-relay")]
+#[command(author = "Cyberia")]
+#[command(version)]
+#[command(about = "Submit UniversalHash proofs on behalf of miners without a funded account")]
+struct Args {
+    /// Address to serve the relay on
+    #[arg(long, default_value = "127.0.0.1:8788")]
+    listen: SocketAddr,
+
+    /// Plaintext wallet file of the account that pays for relayed proofs
+    #[arg(long)]
+    wallet: Option<PathBuf>,
+
+    /// Account index derived from the wallet mnemonic
+    #[arg(long, default_value = "0")]
+    account: u32,
+
+    /// Config file with the chain and network settings (default: ~/.uhash/config.toml)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Custom RPC endpoint(s), comma-separated; later ones are failover targets
+    #[arg(long, value_delimiter = ',')]
+    rpc: Vec<String>,
+
+    /// LCD (REST) endpoint(s), comma-separated (default: derived from --rpc)
+    #[arg(long, value_delimiter = ',')]
+    lcd: Vec<String>,
+
+    /// Custom contract address (default: production contract)
+    #[arg(long)]
+    contract: Option<String>,
+
+    /// Transaction fee in uboot
+    #[arg(long, default_value = "0")]
+    fee: u128,
+
+    /// Gas limit per transaction, or `auto` to simulate first
+    #[arg(long, default_value = "1600000")]
+    gas: String,
+
+    /// Gas price such as `0.01boot`; the fee becomes gas * price (overrides --fee)
+    #[arg(long)]
+    gas_prices: Option<String>,
+
+    /// Refuse proofs without the miner's signed authorization (`uhash mine --submit-via`)
+    #[arg(long)]
+    require_authorization: bool,
+
+    /// Broadcast mode: sync, async, or block (wait for the commit, so the
+    /// miner learns whether the contract accepted the proof)
+    #[arg(long, default_value = "sync")]
+    broadcast_mode: BroadcastMode,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> anyhow::Result<()> {
+    let config_path = args.config.clone().unwrap_or_else(default_config_path);
+    let settings = Config::load(&config_path)?;
+    let prefix = &settings.chain.bech32_prefix;
+
+    let wallet_path = args.wallet.clone().unwrap_or_else(default_wallet_path);
+    let wallet = Wallet::load_from_file(&wallet_path)
+        .and_then(|wallet| wallet.for_chain(&settings.chain))
+        .and_then(|wallet| wallet.derive_account(args.account))
+        .map_err(|e| anyhow::anyhow!("Cannot load {}: {}", wallet_path.display(), e))?;
+    let signing_key =
+        cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key()?.to_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?;
+
+    let client = RpcClient::try_with_config(rpc_config(args, &settings)?)?
+        .on_warning(|warning| eprintln!("Warning: {}", warning));
+    let relay =
+        Relay::new(client, signing_key, prefix)?.require_authorization(args.require_authorization);
+
+    let listener = std::net::TcpListener::bind(args.listen)?;
+    println!("Relaying proofs on http://{}/relay", listener.local_addr()?);
+    println!("Fees paid by {}", relay.address());
+    println!("\nPress Ctrl-C to stop.");
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(relay.serve(listener))
+}
+
+fn rpc_config(args: &Args, settings: &Config) -> anyhow::Result<RpcConfig> {
+    let mut config = RpcConfig::from_profile(&settings.chain);
+    let prefix = &settings.chain.bech32_prefix;
+    let (rpc_endpoints, lcd_endpoints) = if args.rpc.is_empty() && args.lcd.is_empty() {
+        (&settings.network.rpc, &settings.network.lcd)
+    } else {
+        (&args.rpc, &args.lcd)
+    };
+    for url in rpc_endpoints.iter().chain(lcd_endpoints) {
+        validate_endpoint(url)?;
+    }
+    if let Some(primary) = rpc_endpoints.first() {
+        config.rpc_url = primary.to_string();
+    }
+    let lcd_endpoints = if lcd_endpoints.is_empty() {
+        rpc_endpoints
+            .iter()
+            .map(|url| lcd_url_for_rpc(url))
+            .collect()
+    } else {
+        lcd_endpoints.clone()
+    };
+    if let Some((primary, fallbacks)) = lcd_endpoints.split_first() {
+        config.lcd_url = primary.to_string();
+        config.fallback_lcd_urls = fallbacks.to_vec();
+    }
+    if let Some(addr) = &args.contract {
+        validate_address_with_prefix(addr, prefix)?;
+        config.contract_address = addr.to_string();
+    }
+    config.proxy = Some(settings.network.proxy.clone()).filter(|p| !p.is_empty());
+    config.ca_certs = settings.network.ca_certs.clone();
+    config.custom_roots_only = settings.network.custom_roots_only;
+    config.connect_timeout_secs = settings.network.connect_timeout;
+    config.request_timeout_secs = settings.network.request_timeout;
+    config.cache_ttl_secs = settings.network.cache_ttl;
+    config.fee_amount = args.fee;
+    config.gas_limit = parse_gas(&args.gas)?;
+    config.gas_price = args
+        .gas_prices
+        .as_deref()
+        .map(|price| parse_gas_price(price, &config.fee_denom))
+        .transpose()?;
+    config.broadcast_mode = args.broadcast_mode;
+    Ok(config)
+}
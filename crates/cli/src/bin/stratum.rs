@@ -0,0 +1,48 @@
+//! UniversalHash Stratum V1 shim
+//!
+//! Lets Stratum V1 miners, proxies and dashboards work with a uhash pool by
+//! translating their JSON-RPC to the pool protocol. See [`uhash::stratum`]
+//! for the mapping.
+
+use clap::Parser;
+use std::net::SocketAddr;
+
+use uhash::stratum::{parse_pool_url, serve};
+
+#[derive(Parser)]
+#[command(name = "uhash-stratum")]
+#[command(author = "Cyberia")]
+#[command(version)]
+#[command(about = "Serve Stratum V1 clients from a UniversalHash pool")]
+struct Args {
+    /// Address to accept Stratum clients on
+    #[arg(long, default_value = "0.0.0.0:3333")]
+    listen: SocketAddr,
+
+    /// Pool to forward to, speaking the uhash pool protocol over TCP
+    #[arg(long, value_name = "tcp://HOST:PORT")]
+    pool: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> anyhow::Result<()> {
+    let pool = parse_pool_url(&args.pool)?;
+    let listener = std::net::TcpListener::bind(args.listen)?;
+    println!(
+        "Serving Stratum on stratum+tcp://{}",
+        listener.local_addr()?
+    );
+    println!("Forwarding to the pool at {}", args.pool);
+    println!("\nPress Ctrl-C to stop.");
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(serve(listener, pool))?;
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! Chain profiles
+//!
+//! Everything that ties the miner to a particular CosmWasm chain — bech32
+//! prefix, chain ID, fee denom, HD coin type, endpoints and the verifier
+//! contract — lives in a [`ChainProfile`]. Bostrom mainnet is the default; other
+//! deployments are configured through the `[chain]` section of the config file.
+
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::{CONTRACT_ADDRESS, DEFAULT_LCD, DEFAULT_RPC, FEE_DENOM};
+
+/// Relay service that submits first proofs for Bostrom accounts not yet on chain
+pub const BOSTROM_RELAY_URL: &str = "https://bostrom.cybernode.ai/relay";
+
+/// Bostrom transaction explorer (`{hash}` is replaced by the tx hash)
+pub const BOSTROM_EXPLORER_TX_URL: &str = "https://cyb.ai/network/bostrom/tx/{hash}";
+
+/// SLIP-44 coin type used by Cosmos Hub-derived chains
+pub const COSMOS_COIN_TYPE: u32 = 118;
+
+/// Parameters of the chain the miner submits proofs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainProfile {
+    /// Chain ID used in sign docs
+    pub chain_id: String,
+    /// Bech32 prefix of account addresses
+    pub bech32_prefix: String,
+    /// Denom transaction fees are paid in
+    pub fee_denom: String,
+    /// SLIP-44 coin type in the HD path `m/44'/<coin_type>'/<account>'/0/0`
+    pub coin_type: u32,
+    /// Default RPC endpoint
+    pub rpc_url: String,
+    /// Default LCD/REST endpoint
+    pub lcd_url: String,
+    /// UniversalHash verifier contract
+    pub contract_address: String,
+    /// Relay service for an account's first proof (empty: none)
+    pub relay_url: String,
+    /// Faucet that funds new accounts so they can sign transactions (empty: none)
+    pub faucet_url: String,
+    /// Explorer transaction URL with a `{hash}` placeholder (empty: none)
+    pub explorer_tx_url: String,
+}
+
+impl ChainProfile {
+    /// Bostrom mainnet
+    pub fn bostrom() -> Self {
+        Self {
+            chain_id: "bostrom".to_string(),
+            bech32_prefix: "bostrom".to_string(),
+            fee_denom: FEE_DENOM.to_string(),
+            coin_type: COSMOS_COIN_TYPE,
+            rpc_url: DEFAULT_RPC.to_string(),
+            lcd_url: DEFAULT_LCD.to_string(),
+            contract_address: CONTRACT_ADDRESS.to_string(),
+            relay_url: BOSTROM_RELAY_URL.to_string(),
+            faucet_url: String::new(),
+            explorer_tx_url: BOSTROM_EXPLORER_TX_URL.to_string(),
+        }
+    }
+
+    /// HD derivation path for the first account
+    pub fn derivation_path(&self) -> String {
+        self.account_path(0)
+    }
+
+    /// HD derivation path for an account, as `--account` in `cyber keys add`
+    pub fn account_path(&self, account: u32) -> String {
+        format!("m/44'/{}'/{}'/0/0", self.coin_type, account)
+    }
+}
+
+impl Default for ChainProfile {
+    fn default() -> Self {
+        Self::bostrom()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcConfig;
+
+    #[test]
+    fn test_partial_profile_falls_back_to_bostrom() {
+        let profile: ChainProfile = toml::from_str(
+            r#"
+            chain_id = "uhash-devnet-1"
+            bech32_prefix = "cosmos"
+            faucet_url = "http://localhost:4500/credit"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(profile.chain_id, "uhash-devnet-1");
+        assert_eq!(profile.bech32_prefix, "cosmos");
+        assert_eq!(profile.fee_denom, "boot");
+        assert_eq!(profile.derivation_path(), "m/44'/118'/0'/0/0");
+        assert_eq!(
+            RpcConfig::from_profile(&profile).faucet_url,
+            "http://localhost:4500/credit"
+        );
+        assert!(ChainProfile::bostrom().faucet_url.is_empty());
+    }
+
+    #[test]
+    fn test_explorer_link() {
+        let mut profile = ChainProfile::bostrom();
+        assert_eq!(
+            RpcConfig::from_profile(&profile)
+                .explorer_tx_link("ABC")
+                .as_deref(),
+            Some("https://cyb.ai/network/bostrom/tx/ABC")
+        );
+        profile.explorer_tx_url.clear();
+        assert_eq!(
+            RpcConfig::from_profile(&profile).explorer_tx_link("ABC"),
+            None
+        );
+    }
+}
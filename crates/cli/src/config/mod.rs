@@ -0,0 +1,380 @@
+//! Persistent miner configuration
+//!
+//! Settings are stored as TOML in `~/.uhash/config.toml` and edited with
+//! `uhash config set <key> <value>`, where `key` is a dotted path such as
+//! `wallet.backend`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::chain::ChainProfile;
+use crate::rpc::{
+    Transport, DEFAULT_CACHE_TTL_SECS, DEFAULT_CONNECT_TIMEOUT_SECS,
+    DEFAULT_POOL_IDLE_TIMEOUT_SECS, DEFAULT_REQUEST_TIMEOUT_SECS, DEFAULT_TCP_KEEPALIVE_SECS,
+};
+use crate::sink::SinkKind;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("File I/O error: {0}")]
+    FileError(#[from] std::io::Error),
+
+    #[error("Invalid config file: {0}")]
+    Parse(String),
+
+    #[error("Unknown config key: {0}")]
+    UnknownKey(String),
+
+    #[error("Invalid value for {key}: {reason}")]
+    InvalidValue { key: String, reason: String },
+}
+
+/// Where the wallet mnemonic is persisted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletBackend {
+    /// Plaintext mnemonic file (`~/.uhash/wallet.txt`)
+    #[default]
+    File,
+    /// OS credential store (macOS Keychain, Windows Credential Manager, Linux keyutils)
+    Keychain,
+    /// Passphrase-encrypted file (`~/.uhash/wallet.enc`)
+    Encrypted,
+}
+
+/// Wallet-related settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalletConfig {
+    /// Storage backend for the mnemonic
+    pub backend: WalletBackend,
+}
+
+/// Defaults of `mine`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MineConfig {
+    /// Threads to mine with when `--threads` is not given (0: one per CPU core)
+    pub threads: usize,
+    /// Tag hashed after the address to name this machine in its proofs, if
+    /// the contract accepts worker tags (empty: none)
+    pub worker_tag: String,
+}
+
+/// Local mining stats database (used when built with the `stats` feature)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Record rounds and proofs while mining
+    pub enabled: bool,
+    /// Database file (empty: `~/.uhash/stats.db`)
+    pub path: String,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: String::new(),
+        }
+    }
+}
+
+/// Management API of `mine` (see `uhash::api`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Address to serve on (empty: disabled unless `mine --api` is given)
+    pub listen: String,
+    /// Bearer token requests must carry (overridden by `UHASH_API_TOKEN`)
+    pub token: String,
+}
+
+/// Where found proofs go (see `uhash::sink`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubmitConfig {
+    pub sink: SinkKind,
+    /// Endpoint of the `pool` and `webhook` sinks, or base URL of the `relay` sink
+    pub url: String,
+    /// File of the `file` sink
+    pub path: String,
+    /// Proofs submitted together in one transaction when several are ready,
+    /// by the controller or from the pending queue (0 or 1: one per transaction)
+    pub batch_size: usize,
+}
+
+/// Opt-in benchmark sharing (see `uhash::telemetry`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Community endpoint for `benchmark --share` and `devices` (empty: none)
+    pub url: String,
+}
+
+/// Chain endpoint settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// RPC endpoints in priority order; later ones are failover targets
+    /// (empty: built-in default)
+    pub rpc: Vec<String>,
+    /// LCD (REST) endpoints in priority order (empty: derived from `rpc`)
+    pub lcd: Vec<String>,
+    /// Protocol for account queries, contract queries and broadcast
+    pub transport: Transport,
+    /// gRPC endpoint, e.g. `http://localhost:9090` (used when `transport = "grpc"`)
+    pub grpc: String,
+    /// Proxy for all HTTP traffic, e.g. `socks5h://127.0.0.1:9050` (empty: none)
+    pub proxy: String,
+    /// PEM files with additional trusted root certificates
+    pub ca_certs: Vec<PathBuf>,
+    /// Trust only `ca_certs`, not the system roots
+    pub custom_roots_only: bool,
+    /// Seconds allowed to establish a connection (0: no limit)
+    pub connect_timeout: u64,
+    /// Seconds allowed for a whole request (0: no limit)
+    pub request_timeout: u64,
+    /// Seconds an idle connection stays in the pool (0: no limit)
+    pub pool_idle_timeout: u64,
+    /// Seconds between TCP keep-alive probes (0: disabled)
+    pub tcp_keepalive: u64,
+    /// Seconds contract status queries are cached (0: disabled)
+    pub cache_ttl: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            rpc: Vec::new(),
+            lcd: Vec::new(),
+            transport: Transport::default(),
+            grpc: String::new(),
+            proxy: String::new(),
+            ca_certs: Vec::new(),
+            custom_roots_only: false,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT_SECS,
+            pool_idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT_SECS,
+            tcp_keepalive: DEFAULT_TCP_KEEPALIVE_SECS,
+            cache_ttl: DEFAULT_CACHE_TTL_SECS,
+        }
+    }
+}
+
+/// Top-level configuration file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub wallet: WalletConfig,
+    pub network: NetworkConfig,
+    pub mine: MineConfig,
+    pub stats: StatsConfig,
+    pub api: ApiConfig,
+    pub submit: SubmitConfig,
+    pub telemetry: TelemetryConfig,
+    /// Chain to mine on (Bostrom mainnet unless overridden)
+    pub chain: ChainProfile,
+}
+
+impl Config {
+    /// Load configuration from a file, falling back to defaults if it does not exist
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+
+    /// Save configuration to a file, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            toml::to_string_pretty(self).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        // Write a sibling and rename, so a running `mine` never reads half a file
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Get a setting by dotted key (e.g. `wallet.backend`)
+    pub fn get(&self, key: &str) -> Result<String, ConfigError> {
+        let root = toml::Value::try_from(self).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        let mut value = &root;
+        for part in key.split('.') {
+            value = value
+                .get(part)
+                .ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+        }
+        Ok(match value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+
+    /// Set a setting by dotted key, validating the result against the schema
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let mut root =
+            toml::Value::try_from(&*self).map_err(|e| ConfigError::Parse(e.to_string()))?;
+
+        let (parents, leaf) = match key.rsplit_once('.') {
+            Some((parents, leaf)) => (Some(parents), leaf),
+            None => (None, key),
+        };
+        let mut table = root
+            .as_table_mut()
+            .ok_or_else(|| ConfigError::Parse("config root is not a table".to_string()))?;
+        for part in parents.into_iter().flat_map(|p| p.split('.')) {
+            table = table
+                .get_mut(part)
+                .and_then(toml::Value::as_table_mut)
+                .ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+        }
+        let current = table
+            .get(leaf)
+            .ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+        let parsed = parse_like(current, value).ok_or_else(|| ConfigError::InvalidValue {
+            key: key.to_string(),
+            reason: format!("expected {}", current.type_str()),
+        })?;
+        table.insert(leaf.to_string(), parsed);
+
+        *self = root
+            .try_into()
+            .map_err(|e: toml::de::Error| ConfigError::InvalidValue {
+                key: key.to_string(),
+                reason: e.message().to_string(),
+            })?;
+        Ok(())
+    }
+}
+
+/// Parse a command-line string into a TOML value of the same type as `current`
+fn parse_like(current: &toml::Value, value: &str) -> Option<toml::Value> {
+    Some(match current {
+        toml::Value::String(_) => toml::Value::String(value.to_string()),
+        toml::Value::Integer(_) => toml::Value::Integer(value.parse().ok()?),
+        toml::Value::Float(_) => toml::Value::Float(value.parse().ok()?),
+        toml::Value::Boolean(_) => toml::Value::Boolean(value.parse().ok()?),
+        toml::Value::Array(_) => toml::Value::Array(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| toml::Value::String(s.to_string()))
+                .collect(),
+        ),
+        _ => return None,
+    })
+}
+
+#[cfg(feature = "cli")]
+impl StatsConfig {
+    /// The configured database file, or `~/.uhash/stats.db`
+    pub fn db_path(&self) -> PathBuf {
+        if self.path.is_empty() {
+            default_config_path().with_file_name("stats.db")
+        } else {
+            PathBuf::from(&self.path)
+        }
+    }
+}
+
+/// Get the default config file path
+#[cfg(feature = "cli")]
+pub fn default_config_path() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".uhash").join("config.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get_wallet_backend() {
+        let mut config = Config::default();
+        assert_eq!(config.get("wallet.backend").unwrap(), "file");
+
+        config.set("wallet.backend", "keychain").unwrap();
+        assert_eq!(config.wallet.backend, WalletBackend::Keychain);
+        assert_eq!(config.get("wallet.backend").unwrap(), "keychain");
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_key_and_bad_value() {
+        let mut config = Config::default();
+        assert!(matches!(
+            config.set("wallet.colour", "blue"),
+            Err(ConfigError::UnknownKey(_))
+        ));
+        assert!(matches!(
+            config.set("wallet.backend", "usb-stick"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert_eq!(config.wallet.backend, WalletBackend::File);
+    }
+
+    #[test]
+    fn test_set_endpoint_list() {
+        let mut config = Config::default();
+        config
+            .set(
+                "network.rpc",
+                "https://rpc.a.example, https://rpc.b.example",
+            )
+            .unwrap();
+        assert_eq!(
+            config.network.rpc,
+            vec!["https://rpc.a.example", "https://rpc.b.example"]
+        );
+        config.set("network.lcd", "http://10.0.0.5:1317").unwrap();
+        assert_eq!(config.network.lcd, vec!["http://10.0.0.5:1317"]);
+    }
+
+    #[test]
+    fn test_set_transport() {
+        let mut config = Config::default();
+        config.set("network.transport", "grpc").unwrap();
+        assert_eq!(config.network.transport, Transport::Grpc);
+        assert!(matches!(
+            config.set("network.transport", "websocket"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_set_proof_sink() {
+        let mut config = Config::default();
+        assert_eq!(config.submit.sink, SinkKind::Contract);
+        config.set("submit.sink", "webhook").unwrap();
+        config
+            .set("submit.url", "https://hooks.example.com/uhash")
+            .unwrap();
+        assert_eq!(config.submit.sink, SinkKind::Webhook);
+        assert!(matches!(
+            config.set("submit.sink", "carrier-pigeon"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_roundtrip_file() {
+        let dir = std::env::temp_dir().join(format!("uhash-config-{}", std::process::id()));
+        let path = dir.join("config.toml");
+
+        let mut config = Config::default();
+        config.set("wallet.backend", "keychain").unwrap();
+        config.save(&path).unwrap();
+
+        assert_eq!(Config::load(&path).unwrap(), config);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
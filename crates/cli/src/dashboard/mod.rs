@@ -0,0 +1,195 @@
+//! Browser dashboard for a running miner
+//!
+//! `uhash dashboard` serves one bundled page and `GET /api/status`, which
+//! returns the miner's status file (see [`crate::status`]) together with the
+//! server's clock, so the page can tell a live miner from one that stopped or
+//! died. `GET /metrics` exposes the same figures, per-thread hashrates
+//! included, in the Prometheus text format. The dashboard only reads the
+//! file: it can run, restart or be left out independently of the miner.
+
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::json;
+use std::fmt::Write;
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::server;
+use crate::status::MinerStatus;
+
+const INDEX_HTML: &str = include_str!("index.html");
+
+/// Seconds without a status update after which a miner counts as not running
+pub const STALE_AFTER_SECS: u64 = 15;
+
+/// Body of `GET /api/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardStatus {
+    /// Server time (Unix seconds)
+    pub now: u64,
+    /// Whether the miner updated its status recently and has not exited
+    pub running: bool,
+    /// `None` until a miner has run with this config directory
+    pub status: Option<MinerStatus>,
+}
+
+impl DashboardStatus {
+    pub fn new(status: Option<MinerStatus>, now: u64) -> Self {
+        let running = status.as_ref().is_some_and(|s| {
+            s.stopped_at.is_none() && now.saturating_sub(s.updated_at) <= STALE_AFTER_SECS
+        });
+        Self {
+            now,
+            running,
+            status,
+        }
+    }
+
+    /// The status in the Prometheus text exposition format
+    pub fn metrics(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, f64)]| {
+            let _ = writeln!(out, "# HELP uhash_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE uhash_{} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "uhash_{}{} {}", name, labels, value);
+            }
+        };
+        let plain = |value: f64| vec![(String::new(), value)];
+        metric(
+            "up",
+            "gauge",
+            "Whether the miner is running",
+            &plain(if self.running { 1.0 } else { 0.0 }),
+        );
+        let Some(s) = &self.status else {
+            return out;
+        };
+        metric(
+            "hashrate",
+            "gauge",
+            "Hashes per second over the current round",
+            &plain(s.hashrate),
+        );
+        let threads: Vec<(String, f64)> = s
+            .thread_hashrates
+            .iter()
+            .enumerate()
+            .map(|(thread, &rate)| (format!("{{thread=\"{}\"}}", thread), rate))
+            .collect();
+        metric(
+            "thread_hashrate",
+            "gauge",
+            "Hashes per second of each mining thread over the current round",
+            &threads,
+        );
+        metric(
+            "threads",
+            "gauge",
+            "Mining threads",
+            &plain(s.threads as f64),
+        );
+        metric(
+            "difficulty",
+            "gauge",
+            "Difficulty in bits",
+            &plain(s.difficulty as f64),
+        );
+        metric(
+            "hashes",
+            "counter",
+            "Hashes computed since the miner started",
+            &plain((s.total_hashes + s.round_hashes) as f64),
+        );
+        metric(
+            "proofs_found",
+            "counter",
+            "Proofs found since the miner started",
+            &plain(s.proofs_found as f64),
+        );
+        metric(
+            "proofs_submitted",
+            "counter",
+            "Proofs submitted since the miner started",
+            &plain(s.proofs_submitted as f64),
+        );
+        out
+    }
+}
+
+/// Web UI over a miner status file
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    status_path: Arc<PathBuf>,
+}
+
+impl Dashboard {
+    pub fn new(status_path: impl Into<PathBuf>) -> Self {
+        Self {
+            status_path: Arc::new(status_path.into()),
+        }
+    }
+
+    pub fn status_path(&self) -> &Path {
+        &self.status_path
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", get(index))
+            .route("/api/status", get(status))
+            .route("/metrics", get(metrics))
+            .with_state(self.clone())
+    }
+
+    /// Serve the dashboard on an already bound listener until the server fails
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
+        server::serve_router(listener, self.router()).await
+    }
+
+    /// Serve the dashboard on `addr` in a background task, returning the bound address
+    pub fn spawn(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        server::spawn_server(addr, self.router())
+    }
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn status(State(dashboard): State<Dashboard>) -> Response {
+    match MinerStatus::read(&dashboard.status_path) {
+        Ok(status) => Json(DashboardStatus::new(status, unix_now())).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("could not read miner status: {}", e) })),
+        )
+            .into_response(),
+    }
+}
+
+async fn metrics(State(dashboard): State<Dashboard>) -> Response {
+    match MinerStatus::read(&dashboard.status_path) {
+        Ok(status) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            DashboardStatus::new(status, unix_now()).metrics(),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("could not read miner status: {}\n", e),
+        )
+            .into_response(),
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
@@ -0,0 +1,831 @@
+//! State machine of the mock chain and verifier contract
+//!
+//! Kept free of HTTP so the acceptance rules can be exercised directly.
+
+use cosmrs::cosmwasm::MsgExecuteContract;
+use cosmrs::tx::{Msg, Tx};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::algorithm::{meets_difficulty, UniversalHash};
+use crate::proof::proof_input;
+use crate::rpc::{
+    ConfigResponse, DifficultyResponse, EventAttribute, ExecuteMsg, MinerStatsResponse, QueryMsg,
+    SeedResponse, TxEvent, CONTRACT_ADDRESS, SEQUENCE_MISMATCH_CODE,
+};
+#[cfg(feature = "contract-v2")]
+use crate::rpc::{LeaderboardEntry, LeaderboardResponse};
+use crate::sim::retarget;
+
+/// ABCI code of a failed contract execution (`wasm` module error 5)
+pub const EXECUTE_FAILED_CODE: u32 = 5;
+
+/// Gas reported for every simulated or executed transaction
+pub const MOCK_GAS_USED: u64 = 120_000;
+
+/// Parameters of the simulated contract
+#[derive(Debug, Clone)]
+pub struct DevnetParams {
+    pub chain_id: String,
+    pub contract_address: String,
+    /// Starting difficulty in leading zero bits
+    pub difficulty: u32,
+    /// Difficulty never retargets below this (also reported as `min_profitable`)
+    pub min_difficulty: u32,
+    /// How often the seed rotates and difficulty retargets
+    pub seed_interval: Duration,
+    /// Accepted proofs per seed interval the retarget aims for
+    pub target_proofs_per_window: u64,
+    /// Reward paid per accepted proof, in the smallest LI unit
+    pub base_reward: u128,
+    /// Oldest proof timestamp accepted, in seconds
+    pub max_proof_age: u64,
+    /// Longest worker tag accepted after the miner address, `None` for none
+    pub max_worker_tag_len: Option<u32>,
+}
+
+impl Default for DevnetParams {
+    fn default() -> Self {
+        Self {
+            chain_id: "uhash-devnet-1".to_string(),
+            contract_address: CONTRACT_ADDRESS.to_string(),
+            difficulty: 8,
+            min_difficulty: 1,
+            seed_interval: Duration::from_secs(600),
+            target_proofs_per_window: 10,
+            base_reward: 1_000_000,
+            max_proof_age: 3600,
+            max_worker_tag_len: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MinerStats {
+    proofs: u64,
+    last_proof_time: u64,
+    rewards: u128,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Account {
+    number: u64,
+    sequence: u64,
+}
+
+/// A transaction as recorded in a block
+#[derive(Debug, Clone)]
+pub struct TxRecord {
+    pub height: u64,
+    /// Unix time of the block
+    pub time: u64,
+    /// Body messages as the LCD decodes them
+    pub messages: Vec<serde_json::Value>,
+    pub code: u32,
+    pub raw_log: String,
+    pub events: Vec<TxEvent>,
+}
+
+/// CheckTx outcome of a broadcast
+#[derive(Debug, Clone)]
+pub struct CheckTx {
+    pub tx_hash: String,
+    pub code: u32,
+    pub raw_log: String,
+}
+
+/// Seed and difficulty from a block on, for queries at past heights
+#[derive(Debug, Clone, Copy)]
+struct Round {
+    /// First block that sees this round
+    height: u64,
+    seed: [u8; 32],
+    difficulty: u32,
+    started: u64,
+}
+
+/// Mock chain with a single verifier contract
+#[derive(Debug)]
+pub struct MockChain {
+    params: DevnetParams,
+    height: u64,
+    seed: [u8; 32],
+    previous_seed: Option<[u8; 32]>,
+    /// Unix time the current seed became active
+    round_started: u64,
+    round: u64,
+    difficulty: u32,
+    proofs_this_round: u64,
+    proofs_accepted: u64,
+    accounts: HashMap<String, Account>,
+    miners: HashMap<String, MinerStats>,
+    seen_hashes: HashSet<String>,
+    txs: HashMap<String, TxRecord>,
+    rounds: Vec<Round>,
+}
+
+impl MockChain {
+    pub fn new(params: DevnetParams, now: u64) -> Self {
+        let seed = Sha256::digest(params.chain_id.as_bytes()).into();
+        Self {
+            seed,
+            previous_seed: None,
+            difficulty: params.difficulty,
+            rounds: vec![Round {
+                height: 0,
+                seed,
+                difficulty: params.difficulty,
+                started: now,
+            }],
+            params,
+            height: 1,
+            round_started: now,
+            round: 0,
+            proofs_this_round: 0,
+            proofs_accepted: 0,
+            accounts: HashMap::new(),
+            miners: HashMap::new(),
+            seen_hashes: HashSet::new(),
+            txs: HashMap::new(),
+        }
+    }
+
+    pub fn params(&self) -> &DevnetParams {
+        &self.params
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    pub fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+
+    /// Proofs accepted since the chain started
+    pub fn proofs_accepted(&self) -> u64 {
+        self.proofs_accepted
+    }
+
+    /// Rotate the seed and retarget difficulty for every interval elapsed by `now`
+    pub fn tick(&mut self, now: u64) {
+        let interval = self.params.seed_interval.as_secs().max(1);
+        while now >= self.round_started + interval {
+            self.difficulty = retarget(
+                self.difficulty,
+                self.proofs_this_round,
+                self.params.target_proofs_per_window,
+                self.params.min_difficulty,
+            );
+            self.proofs_this_round = 0;
+
+            self.round += 1;
+            self.previous_seed = Some(self.seed);
+            let mut hasher = Sha256::new();
+            hasher.update(self.seed);
+            hasher.update(self.round.to_le_bytes());
+            self.seed = hasher.finalize().into();
+            self.round_started += interval;
+            self.rounds.push(Round {
+                height: self.height + 1,
+                seed: self.seed,
+                difficulty: self.difficulty,
+                started: self.round_started,
+            });
+        }
+    }
+
+    /// Account number and sequence, creating the account on first sight
+    pub fn account(&mut self, address: &str) -> (u64, u64) {
+        let next_number = self.accounts.len() as u64 + 1;
+        let account = self.accounts.entry(address.to_string()).or_insert(Account {
+            number: next_number,
+            sequence: 0,
+        });
+        (account.number, account.sequence)
+    }
+
+    /// Consume the account's next sequence, as another transaction from it would
+    pub fn advance_sequence(&mut self, address: &str) {
+        self.account(address);
+        if let Some(account) = self.accounts.get_mut(address) {
+            account.sequence += 1;
+        }
+    }
+
+    /// Answer a smart query
+    pub fn query(&self, query: &QueryMsg) -> serde_json::Value {
+        self.query_at(query, u64::MAX)
+    }
+
+    /// Answer a smart query against the state as of block `height`
+    ///
+    /// Only the seed and difficulty are kept per block; other queries answer
+    /// with the current state.
+    pub fn query_at(&self, query: &QueryMsg, height: u64) -> serde_json::Value {
+        let round = self
+            .rounds
+            .iter()
+            .rev()
+            .find(|round| round.height <= height)
+            .unwrap_or(&self.rounds[0]);
+        let result = match query {
+            QueryMsg::Config {} => serde_json::to_value(ConfigResponse {
+                token_denom: format!("factory/{}/li", self.params.contract_address),
+                difficulty: self.difficulty,
+                base_reward: self.params.base_reward.to_string(),
+                max_proof_age: self.params.max_proof_age,
+                period_duration: self.params.seed_interval.as_secs(),
+                target_proofs_per_window: Some(self.params.target_proofs_per_window),
+                max_worker_tag_len: self.params.max_worker_tag_len,
+                admin: self.params.contract_address.clone(),
+                paused: false,
+            }),
+            QueryMsg::Seed {} => serde_json::to_value(SeedResponse {
+                seed: hex::encode(round.seed),
+                seed_interval: self.params.seed_interval.as_secs(),
+                created_at: Some(round.started),
+            }),
+            QueryMsg::Difficulty {} => serde_json::to_value(DifficultyResponse {
+                current: round.difficulty,
+                min_profitable: self.params.min_difficulty,
+            }),
+            QueryMsg::MinerStats { address } => {
+                let stats = self.miners.get(address).copied().unwrap_or_default();
+                serde_json::to_value(MinerStatsResponse {
+                    address: address.clone(),
+                    proofs_submitted: stats.proofs,
+                    last_proof_time: (stats.proofs > 0).then_some(stats.last_proof_time),
+                    total_rewards: stats.rewards.to_string(),
+                })
+            }
+            #[cfg(feature = "contract-v2")]
+            QueryMsg::Leaderboard { limit, start_after } => {
+                let mut ranked: Vec<_> = self.miners.iter().collect();
+                ranked.sort_by(|(a, x), (b, y)| y.proofs.cmp(&x.proofs).then(a.cmp(b)));
+                let skip = start_after
+                    .as_ref()
+                    .and_then(|after| ranked.iter().position(|(address, _)| *address == after))
+                    .map_or(0, |i| i + 1);
+                let entries = ranked
+                    .into_iter()
+                    .skip(skip)
+                    .take(limit.unwrap_or(10) as usize)
+                    .map(|(address, stats)| LeaderboardEntry {
+                        address: address.clone(),
+                        proofs_submitted: stats.proofs,
+                        total_rewards: stats.rewards.to_string(),
+                    })
+                    .collect();
+                serde_json::to_value(LeaderboardResponse { entries })
+            }
+        };
+        result.expect("contract responses serialize")
+    }
+
+    /// Check a proof against the contract rules and pay the reward
+    pub fn execute_proof(
+        &mut self,
+        miner: &str,
+        worker_tag: &[u8],
+        hash_hex: &str,
+        nonce: u64,
+        timestamp: u64,
+        now: u64,
+    ) -> Result<u128, String> {
+        let hash_hex = self.check_proof(miner, worker_tag, hash_hex, nonce, timestamp, now)?;
+        self.seen_hashes.insert(hash_hex);
+        self.proofs_this_round += 1;
+        self.proofs_accepted += 1;
+        let stats = self.miners.entry(miner.to_string()).or_default();
+        stats.proofs += 1;
+        stats.last_proof_time = now;
+        stats.rewards += self.params.base_reward;
+        Ok(self.params.base_reward)
+    }
+
+    /// Check a proof against the contract rules, returning its normalized hash
+    fn check_proof(
+        &self,
+        miner: &str,
+        worker_tag: &[u8],
+        hash_hex: &str,
+        nonce: u64,
+        timestamp: u64,
+        now: u64,
+    ) -> Result<String, String> {
+        let max_tag_len = self.params.max_worker_tag_len.unwrap_or(0) as usize;
+        if worker_tag.len() > max_tag_len {
+            return Err(format!("worker tag is longer than {} bytes", max_tag_len));
+        }
+        if timestamp > now + 60 {
+            return Err("proof timestamp is in the future".to_string());
+        }
+        if now.saturating_sub(timestamp) > self.params.max_proof_age {
+            return Err("proof expired".to_string());
+        }
+        let hash_hex = hash_hex.to_ascii_lowercase();
+        if self.seen_hashes.contains(&hash_hex) {
+            return Err("duplicate proof".to_string());
+        }
+
+        // The miner may have started on the seed that was just rotated out
+        let mut hasher = UniversalHash::new();
+        let valid_hash = [Some(self.seed), self.previous_seed]
+            .into_iter()
+            .flatten()
+            .map(|seed| hasher.hash(&proof_input(&seed, miner, worker_tag, timestamp, nonce)))
+            .find(|hash| hex::encode(hash) == hash_hex);
+        let Some(hash) = valid_hash else {
+            return Err("hash does not match proof inputs".to_string());
+        };
+        if !meets_difficulty(&hash, self.difficulty) {
+            return Err(format!("hash does not meet difficulty {}", self.difficulty));
+        }
+        Ok(hash_hex)
+    }
+
+    /// Run a transaction against the current state without committing it,
+    /// returning the gas used or why the contract would fail it
+    pub fn simulate(&self, tx_bytes: &[u8], now: u64) -> Result<u64, String> {
+        let tx = Tx::from_bytes(tx_bytes).map_err(|e| format!("tx parse error: {}", e))?;
+        let msgs =
+            execute_messages(&tx).ok_or_else(|| "expected a MsgExecuteContract".to_string())?;
+        // Simulation runs the ante handler, sequence check included
+        if let Some(signer) = tx.auth_info.signer_infos.first() {
+            let sender = msgs[0].sender.to_string();
+            let expected = self.accounts.get(&sender).map_or(0, |a| a.sequence);
+            if signer.sequence != expected {
+                return Err(format!(
+                    "account sequence mismatch, expected {}, got {}: incorrect account sequence",
+                    expected, signer.sequence
+                ));
+            }
+        }
+        for msg in &msgs {
+            if msg.contract.as_ref() != self.params.contract_address {
+                return Err(format!("contract {} not found", msg.contract));
+            }
+            let execute: ExecuteMsg = serde_json::from_slice(&msg.msg)
+                .map_err(|e| format!("Error parsing into type ExecuteMsg: {}", e))?;
+            match execute {
+                ExecuteMsg::SubmitProof {
+                    hash,
+                    nonce,
+                    timestamp,
+                    miner_address,
+                    worker_tag,
+                } => {
+                    let miner = miner_address.unwrap_or_else(|| msg.sender.to_string());
+                    let worker_tag = decode_worker_tag(worker_tag)?;
+                    self.check_proof(&miner, &worker_tag, &hash, nonce, timestamp, now)
+                        .map_err(|e| format!("execute wasm contract failed: {}", e))?;
+                }
+                #[cfg(feature = "contract-v2")]
+                other => return Err(unsupported_v2_execute(&other)),
+            }
+        }
+        Ok(MOCK_GAS_USED * msgs.len() as u64)
+    }
+
+    /// Run CheckTx and, if it passes, commit the transaction in a new block
+    pub fn broadcast(&mut self, tx_bytes: &[u8], now: u64) -> CheckTx {
+        let tx_hash = Self::tx_hash(tx_bytes);
+        let reject = |code: u32, raw_log: String| CheckTx {
+            tx_hash: tx_hash.clone(),
+            code,
+            raw_log,
+        };
+
+        let tx = match Tx::from_bytes(tx_bytes) {
+            Ok(tx) => tx,
+            Err(e) => return reject(2, format!("tx parse error: {}", e)),
+        };
+        let Some(msgs) = execute_messages(&tx) else {
+            return reject(2, "expected a MsgExecuteContract".to_string());
+        };
+        let Some(signer) = tx.auth_info.signer_infos.first() else {
+            return reject(4, "no signatures supplied".to_string());
+        };
+
+        let sender = msgs[0].sender.to_string();
+        let (_, expected) = self.account(&sender);
+        if signer.sequence != expected {
+            return reject(
+                SEQUENCE_MISMATCH_CODE,
+                format!(
+                    "account sequence mismatch, expected {}, got {}: incorrect account sequence",
+                    expected, signer.sequence
+                ),
+            );
+        }
+        if let Some(account) = self.accounts.get_mut(&sender) {
+            account.sequence += 1;
+        }
+
+        let result = self.execute_all(&msgs, now);
+        let messages = msgs
+            .iter()
+            .map(|msg| {
+                serde_json::json!({
+                    "@type": "/cosmwasm.wasm.v1.MsgExecuteContract",
+                    "sender": msg.sender.to_string(),
+                    "contract": msg.contract.to_string(),
+                    "msg": serde_json::from_slice::<serde_json::Value>(&msg.msg).unwrap_or_default(),
+                    "funds": [],
+                })
+            })
+            .collect();
+        self.commit(&tx_hash, result, messages, now);
+        reject(0, String::new())
+    }
+
+    /// Hash a transaction is known by
+    pub fn tx_hash(tx_bytes: &[u8]) -> String {
+        hex::encode_upper(Sha256::digest(tx_bytes))
+    }
+
+    /// Signer of a transaction's contract message, if it decodes
+    pub fn tx_sender(tx_bytes: &[u8]) -> Option<String> {
+        let tx = Tx::from_bytes(tx_bytes).ok()?;
+        let msg = MsgExecuteContract::from_any(tx.body.messages.first()?).ok()?;
+        Some(msg.sender.to_string())
+    }
+
+    /// Submit a proof on behalf of a miner, as the relay service does
+    pub fn relay(
+        &mut self,
+        miner: &str,
+        hash_hex: &str,
+        nonce: u64,
+        timestamp: u64,
+        now: u64,
+    ) -> Result<String, String> {
+        self.account(miner);
+        let reward = self.execute_proof(miner, &[], hash_hex, nonce, timestamp, now)?;
+        let tx_hash = hex::encode_upper(Sha256::digest(
+            format!("relay:{}:{}", miner, hash_hex).as_bytes(),
+        ));
+        // The relay service signs with its own key
+        let message = serde_json::json!({
+            "@type": "/cosmwasm.wasm.v1.MsgExecuteContract",
+            "sender": self.params.contract_address,
+            "contract": self.params.contract_address,
+            "msg": ExecuteMsg::SubmitProof {
+                hash: hash_hex.to_string(),
+                nonce,
+                timestamp,
+                miner_address: Some(miner.to_string()),
+                worker_tag: None,
+            },
+            "funds": [],
+        });
+        self.commit(
+            &tx_hash,
+            Ok(self.reward_events(miner, reward)),
+            vec![message],
+            now,
+        );
+        Ok(tx_hash)
+    }
+
+    pub fn tx(&self, tx_hash: &str) -> Option<&TxRecord> {
+        self.txs.get(&tx_hash.to_ascii_uppercase())
+    }
+
+    /// Committed transactions with their hashes, newest first
+    pub fn txs(&self) -> Vec<(&str, &TxRecord)> {
+        let mut txs: Vec<_> = self
+            .txs
+            .iter()
+            .map(|(hash, record)| (hash.as_str(), record))
+            .collect();
+        txs.sort_by_key(|(_, record)| std::cmp::Reverse(record.height));
+        txs
+    }
+
+    /// Execute every message of a transaction, undoing them all if one fails
+    fn execute_all(
+        &mut self,
+        msgs: &[MsgExecuteContract],
+        now: u64,
+    ) -> Result<Vec<TxEvent>, String> {
+        let saved = (
+            self.seen_hashes.clone(),
+            self.miners.clone(),
+            self.proofs_this_round,
+            self.proofs_accepted,
+        );
+        let mut events = Vec::new();
+        for msg in msgs {
+            match self.execute(msg, now) {
+                Ok(msg_events) => events.extend(msg_events),
+                Err(e) => {
+                    (
+                        self.seen_hashes,
+                        self.miners,
+                        self.proofs_this_round,
+                        self.proofs_accepted,
+                    ) = saved;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    fn execute(&mut self, msg: &MsgExecuteContract, now: u64) -> Result<Vec<TxEvent>, String> {
+        if msg.contract.as_ref() != self.params.contract_address {
+            return Err(format!("contract {} not found", msg.contract));
+        }
+        let execute: ExecuteMsg = serde_json::from_slice(&msg.msg)
+            .map_err(|e| format!("Error parsing into type ExecuteMsg: {}", e))?;
+        match execute {
+            ExecuteMsg::SubmitProof {
+                hash,
+                nonce,
+                timestamp,
+                miner_address,
+                worker_tag,
+            } => {
+                let miner = miner_address.unwrap_or_else(|| msg.sender.to_string());
+                let worker_tag = decode_worker_tag(worker_tag)?;
+                let reward =
+                    self.execute_proof(&miner, &worker_tag, &hash, nonce, timestamp, now)?;
+                Ok(self.reward_events(&miner, reward))
+            }
+            #[cfg(feature = "contract-v2")]
+            other => Err(unsupported_v2_execute(&other)),
+        }
+    }
+
+    fn reward_events(&self, miner: &str, reward: u128) -> Vec<TxEvent> {
+        let attribute = |key: &str, value: String| EventAttribute {
+            key: key.to_string(),
+            value,
+        };
+        vec![TxEvent {
+            kind: "wasm".to_string(),
+            attributes: vec![
+                attribute("_contract_address", self.params.contract_address.clone()),
+                attribute("action", "submit_proof".to_string()),
+                attribute("miner", miner.to_string()),
+                attribute("difficulty", self.difficulty.to_string()),
+                attribute("reward", reward.to_string()),
+            ],
+        }]
+    }
+
+    fn commit(
+        &mut self,
+        tx_hash: &str,
+        result: Result<Vec<TxEvent>, String>,
+        messages: Vec<serde_json::Value>,
+        now: u64,
+    ) {
+        self.height += 1;
+        let record = match result {
+            Ok(events) => TxRecord {
+                height: self.height,
+                time: now,
+                messages,
+                code: 0,
+                raw_log: String::new(),
+                events,
+            },
+            Err(error) => TxRecord {
+                height: self.height,
+                time: now,
+                messages,
+                code: EXECUTE_FAILED_CODE,
+                raw_log: format!(
+                    "failed to execute message; message index: 0: {}: execute wasm contract failed",
+                    error
+                ),
+                events: Vec::new(),
+            },
+        };
+        self.txs.insert(tx_hash.to_string(), record);
+    }
+}
+
+/// The contract messages of a transaction, `None` if it has none or another kind
+fn execute_messages(tx: &Tx) -> Option<Vec<MsgExecuteContract>> {
+    if tx.body.messages.is_empty() {
+        return None;
+    }
+    tx.body
+        .messages
+        .iter()
+        .map(|any| MsgExecuteContract::from_any(any).ok())
+        .collect()
+}
+
+/// A `submit_proof` message's hex worker tag as bytes, empty without one
+fn decode_worker_tag(worker_tag: Option<String>) -> Result<Vec<u8>, String> {
+    worker_tag.map_or(Ok(Vec::new()), |tag| {
+        hex::decode(tag).map_err(|e| format!("invalid worker tag: {}", e))
+    })
+}
+
+/// The devnet runs the v1 contract, which pays rewards on submission and
+/// knows no workers
+#[cfg(feature = "contract-v2")]
+fn unsupported_v2_execute(msg: &ExecuteMsg) -> String {
+    let name = match msg {
+        ExecuteMsg::SubmitProof { .. } => "submit_proof",
+        ExecuteMsg::ClaimRewards {} => "claim_rewards",
+        ExecuteMsg::RegisterWorker { .. } => "register_worker",
+    };
+    format!(
+        "Error parsing into type ExecuteMsg: unknown variant `{}`, expected `submit_proof`",
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{sign_proof_tx, ProofSubmission, RpcConfig};
+    use crate::wallet::Wallet;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn mine(chain: &MockChain, miner: &str) -> (String, u64) {
+        mine_tagged(chain, miner, &[])
+    }
+
+    fn mine_tagged(chain: &MockChain, miner: &str, worker_tag: &[u8]) -> (String, u64) {
+        let mut hasher = UniversalHash::new();
+        for nonce in 0u64.. {
+            let hash = hasher.hash(&proof_input(&chain.seed(), miner, worker_tag, NOW, nonce));
+            if meets_difficulty(&hash, chain.difficulty()) {
+                return (hex::encode(hash), nonce);
+            }
+        }
+        unreachable!()
+    }
+
+    fn params() -> DevnetParams {
+        DevnetParams {
+            difficulty: 2,
+            ..DevnetParams::default()
+        }
+    }
+
+    #[test]
+    fn test_proof_rules() {
+        let mut chain = MockChain::new(params(), NOW);
+        let miner = "bostrom1miner";
+        let (hash, nonce) = mine(&chain, miner);
+
+        assert!(chain
+            .execute_proof("bostrom1other", &[], &hash, nonce, NOW, NOW)
+            .is_err());
+        assert!(chain
+            .execute_proof(miner, &[], &hash, nonce, NOW, NOW + 7200)
+            .is_err());
+        assert_eq!(
+            chain.execute_proof(miner, &[], &hash, nonce, NOW, NOW),
+            Ok(1_000_000)
+        );
+        assert_eq!(
+            chain.execute_proof(miner, &[], &hash, nonce, NOW, NOW),
+            Err("duplicate proof".to_string())
+        );
+        assert_eq!(chain.proofs_accepted(), 1);
+
+        let stats = chain.query(&QueryMsg::MinerStats {
+            address: miner.to_string(),
+        });
+        assert_eq!(stats["proofs_submitted"], 1);
+        assert_eq!(stats["last_proof_time"], NOW);
+        assert_eq!(stats["total_rewards"], "1000000");
+    }
+
+    #[cfg(feature = "contract-v2")]
+    #[test]
+    fn test_contract_v2_messages() {
+        let params = DevnetParams {
+            max_worker_tag_len: Some(8),
+            ..params()
+        };
+        let mut chain = MockChain::new(params, NOW);
+        for (miner, tag) in [
+            ("bostrom1a", &b""[..]),
+            ("bostrom1b", b""),
+            ("bostrom1b", b"rig-1"),
+        ] {
+            let (hash, nonce) = mine_tagged(&chain, miner, tag);
+            chain
+                .execute_proof(miner, tag, &hash, nonce, NOW, NOW)
+                .unwrap();
+        }
+
+        let board = chain.query(&QueryMsg::Leaderboard {
+            limit: Some(1),
+            start_after: None,
+        });
+        assert_eq!(board["entries"][0]["address"], "bostrom1b");
+        assert_eq!(board["entries"][0]["proofs_submitted"], 2);
+        assert_eq!(board["entries"].as_array().unwrap().len(), 1);
+        let board = chain.query(&QueryMsg::Leaderboard {
+            limit: None,
+            start_after: Some("bostrom1b".to_string()),
+        });
+        assert_eq!(board["entries"][0]["address"], "bostrom1a");
+
+        let claim = serde_json::to_string(&ExecuteMsg::ClaimRewards {}).unwrap();
+        assert_eq!(claim, r#"{"claim_rewards":{}}"#);
+        assert!(unsupported_v2_execute(&ExecuteMsg::ClaimRewards {}).contains("claim_rewards"));
+    }
+
+    #[test]
+    fn test_worker_tags() {
+        let miner = "bostrom1miner";
+        let mut chain = MockChain::new(params(), NOW);
+        let (hash, nonce) = mine_tagged(&chain, miner, b"rig-1");
+        assert_eq!(
+            chain.execute_proof(miner, b"rig-1", &hash, nonce, NOW, NOW),
+            Err("worker tag is longer than 0 bytes".to_string())
+        );
+
+        let mut chain = MockChain::new(
+            DevnetParams {
+                max_worker_tag_len: Some(8),
+                ..params()
+            },
+            NOW,
+        );
+        assert_eq!(chain.query(&QueryMsg::Config {})["max_worker_tag_len"], 8);
+        // The tag is part of the hashed input
+        assert!(chain
+            .execute_proof(miner, b"rig-2", &hash, nonce, NOW, NOW)
+            .is_err());
+        assert!(chain
+            .execute_proof(miner, b"rig-1", &hash, nonce, NOW, NOW)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_seed_rotation_and_retarget() {
+        let mut chain = MockChain::new(params(), NOW);
+        let seed = chain.seed();
+        chain.proofs_this_round = 100;
+        chain.tick(NOW + 600);
+        assert_ne!(chain.seed(), seed);
+        assert_eq!(chain.difficulty(), 3);
+        assert_eq!(chain.query(&QueryMsg::Seed {})["created_at"], NOW + 600);
+        // Blocks before the rotation still answer with the old round
+        assert_eq!(
+            chain.query_at(&QueryMsg::Seed {}, 1)["seed"],
+            hex::encode(seed)
+        );
+        assert_eq!(chain.query_at(&QueryMsg::Difficulty {}, 1)["current"], 2);
+        assert_eq!(
+            chain.query_at(&QueryMsg::Seed {}, 2)["seed"],
+            hex::encode(chain.seed())
+        );
+
+        // Two idle intervals walk the difficulty back down
+        chain.tick(NOW + 1800);
+        assert_eq!(chain.difficulty(), 1);
+    }
+
+    #[test]
+    fn test_broadcast_checks_sequence() {
+        let mut chain = MockChain::new(params(), NOW);
+        let wallet = Wallet::generate(12, bip39::Language::English).unwrap();
+        let miner = wallet.address_str();
+        let (hash, nonce) = mine(&chain, &miner);
+        let key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let proof = ProofSubmission {
+            hash,
+            nonce,
+            timestamp: NOW,
+            miner_address: miner.clone(),
+            worker_tag: None,
+        };
+        let config = RpcConfig::default();
+        let (number, sequence) = chain.account(&miner);
+
+        let stale = sign_proof_tx(&config, &proof, &key, number, sequence + 1).unwrap();
+        let check = chain.broadcast(&stale.decode_tx_bytes().unwrap(), NOW);
+        assert_eq!(check.code, SEQUENCE_MISMATCH_CODE);
+
+        let signed = sign_proof_tx(&config, &proof, &key, number, sequence).unwrap();
+        let check = chain.broadcast(&signed.decode_tx_bytes().unwrap(), NOW);
+        assert_eq!(check.code, 0);
+        let record = chain.tx(&check.tx_hash).unwrap();
+        assert_eq!(record.code, 0);
+        assert_eq!(chain.account(&miner), (number, sequence + 1));
+    }
+}
@@ -0,0 +1,584 @@
+//! Local mock of the chain and verifier contract
+//!
+//! `uhash devnet` serves the subset of the LCD API the miner uses — smart
+//! queries, account queries, simulate, broadcast, transaction lookup and search,
+//! the latest block — plus the first-proof relay, the Tendermint websocket
+//! subscription to committed proofs and the benchmark sharing endpoint,
+//! backed by an in-process [`MockChain`]. Seeds rotate and difficulty retargets on a timer, and proofs
+//! are checked with the same rules as the real contract, so the whole
+//! mine → submit pipeline can run without a chain.
+
+mod chain;
+
+pub use chain::{CheckTx, DevnetParams, MockChain, TxRecord, EXECUTE_FAILED_CODE, MOCK_GAS_USED};
+
+/// A failure injected into the next broadcast
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Reply with HTTP 503, as an overloaded node does
+    Unavailable,
+    /// Reply with HTTP 429 and `Retry-After: 1`
+    RateLimited,
+    /// Let another transaction from the same account take the sequence first;
+    /// a gas simulation coming before the broadcast sees it instead
+    SequenceMismatch,
+    /// Accept the transaction, then drop it from the mempool
+    Evicted,
+}
+
+use axum::extract::ws::{self, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::broadcast;
+
+use crate::rpc::{QueryMsg, BLOCK_HEIGHT_HEADER};
+use crate::server;
+use crate::telemetry::{self, BenchmarkReport, DeviceTable};
+
+type Reply = (StatusCode, Json<Value>);
+
+/// Committed transactions buffered for slow websocket subscribers
+const TX_NOTIFICATION_BUFFER: usize = 64;
+
+/// Handle to a mock chain that can be served over HTTP and inspected
+#[derive(Debug, Clone)]
+pub struct Devnet {
+    chain: Arc<Mutex<MockChain>>,
+    faults: Arc<Mutex<VecDeque<Fault>>>,
+    /// `result` of a Tendermint `Tx` notification for each committed proof
+    txs: broadcast::Sender<Value>,
+    /// Reports shared with `benchmark --share`
+    benchmarks: Arc<Mutex<Vec<BenchmarkReport>>>,
+}
+
+impl Devnet {
+    pub fn new(params: DevnetParams) -> Self {
+        Self {
+            chain: Arc::new(Mutex::new(MockChain::new(params, unix_now()))),
+            faults: Arc::default(),
+            txs: broadcast::channel(TX_NOTIFICATION_BUFFER).0,
+            benchmarks: Arc::default(),
+        }
+    }
+
+    /// Apply `fault` to the next broadcast (faults queue up in order)
+    pub fn inject(&self, fault: Fault) {
+        self.faults
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push_back(fault);
+    }
+
+    fn next_fault(&self) -> Option<Fault> {
+        self.faults
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+    }
+
+    /// The next fault, if it is `fault`
+    fn next_fault_if(&self, fault: Fault) -> Option<Fault> {
+        let mut faults = self.faults.lock().unwrap_or_else(|e| e.into_inner());
+        if faults.front() == Some(&fault) {
+            faults.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Lock the chain state, applying any seed rotations that are due
+    pub fn chain(&self) -> MutexGuard<'_, MockChain> {
+        let mut chain = self.chain.lock().unwrap_or_else(|e| e.into_inner());
+        chain.tick(unix_now());
+        chain
+    }
+
+    /// Benchmark reports shared so far
+    pub fn benchmarks(&self) -> Vec<BenchmarkReport> {
+        self.benchmarks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Notify websocket subscribers of a transaction if it committed successfully
+    fn publish(&self, tx_hash: &str) {
+        let chain = self.chain();
+        let Some(record) = chain.tx(tx_hash).filter(|record| record.code == 0) else {
+            return;
+        };
+        let notification = json!({
+            "query": "tm.event='Tx'",
+            "data": {
+                "type": "tendermint/event/Tx",
+                "value": { "TxResult": {
+                    "height": record.height.to_string(),
+                    "index": 0,
+                    "result": { "events": record.events },
+                }},
+            },
+            "events": {
+                "tx.hash": [tx_hash.to_ascii_uppercase()],
+                "tx.height": [record.height.to_string()],
+            },
+        });
+        // No subscribers is not an error
+        let _ = self.txs.send(notification);
+    }
+
+    /// LCD routes backed by this devnet
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route(
+                "/cosmwasm/wasm/v1/contract/:contract/smart/:query",
+                get(smart_query),
+            )
+            .route("/cosmos/auth/v1beta1/accounts/:address", get(account))
+            .route("/cosmos/tx/v1beta1/simulate", post(simulate))
+            .route("/cosmos/tx/v1beta1/txs", post(broadcast).get(search_txs))
+            .route("/cosmos/tx/v1beta1/txs/:hash", get(tx))
+            .route(
+                "/cosmos/base/tendermint/v1beta1/blocks/latest",
+                get(latest_block),
+            )
+            .route("/relay", post(relay))
+            .route("/websocket", get(websocket))
+            .route("/benchmarks", post(share_benchmark))
+            .route("/devices", get(devices))
+            .with_state(self.clone())
+    }
+
+    /// Serve the mock chain on an already bound listener until the server fails
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
+        server::serve_router(listener, self.router()).await
+    }
+
+    /// Serve the mock chain on `addr` in a background task, returning the bound address
+    pub fn spawn(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        server::spawn_server(addr, self.router())
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cosmos SDK gRPC-gateway error body
+fn error(status: StatusCode, code: u32, message: impl Into<String>) -> Reply {
+    (
+        status,
+        Json(json!({ "code": code, "message": message.into(), "details": [] })),
+    )
+}
+
+async fn smart_query(
+    State(devnet): State<Devnet>,
+    Path((contract, query)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Reply {
+    let chain = devnet.chain();
+    if contract != chain.params().contract_address {
+        return error(
+            StatusCode::NOT_FOUND,
+            5,
+            format!("contract {} not found", contract),
+        );
+    }
+    let query = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &query)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<QueryMsg>(&bytes).ok());
+    let height = headers
+        .get(BLOCK_HEIGHT_HEADER)
+        .and_then(|h| h.to_str().ok()?.parse().ok());
+    match query {
+        Some(query) => {
+            let data = match height {
+                Some(height) => chain.query_at(&query, height),
+                None => chain.query(&query),
+            };
+            (StatusCode::OK, Json(json!({ "data": data })))
+        }
+        None => error(
+            StatusCode::BAD_REQUEST,
+            3,
+            "Error parsing into type QueryMsg",
+        ),
+    }
+}
+
+async fn account(State(devnet): State<Devnet>, Path(address): Path<String>) -> Reply {
+    let (number, sequence) = devnet.chain().account(&address);
+    (
+        StatusCode::OK,
+        Json(json!({
+            "account": {
+                "@type": "/cosmos.auth.v1beta1.BaseAccount",
+                "address": address,
+                "pub_key": null,
+                "account_number": number.to_string(),
+                "sequence": sequence.to_string(),
+            }
+        })),
+    )
+}
+
+#[derive(Deserialize)]
+struct TxBody {
+    tx_bytes: String,
+}
+
+fn decode_tx(body: &TxBody) -> Result<Vec<u8>, Reply> {
+    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &body.tx_bytes).map_err(
+        |e| {
+            error(
+                StatusCode::BAD_REQUEST,
+                3,
+                format!("invalid tx_bytes: {}", e),
+            )
+        },
+    )
+}
+
+async fn simulate(State(devnet): State<Devnet>, Json(body): Json<TxBody>) -> Reply {
+    let tx_bytes = match decode_tx(&body) {
+        Ok(bytes) => bytes,
+        Err(reply) => return reply,
+    };
+    if devnet.next_fault_if(Fault::SequenceMismatch).is_some() {
+        if let Some(sender) = MockChain::tx_sender(&tx_bytes) {
+            devnet.chain().advance_sequence(&sender);
+        }
+    }
+    match devnet.chain().simulate(&tx_bytes, unix_now()) {
+        Ok(gas_used) => (
+            StatusCode::OK,
+            Json(json!({
+                "gas_info": { "gas_wanted": "0", "gas_used": gas_used.to_string() }
+            })),
+        ),
+        Err(message) => error(StatusCode::BAD_REQUEST, 2, message),
+    }
+}
+
+async fn broadcast(State(devnet): State<Devnet>, Json(body): Json<TxBody>) -> Response {
+    let tx_bytes = match decode_tx(&body) {
+        Ok(bytes) => bytes,
+        Err(reply) => return reply.into_response(),
+    };
+    match devnet.next_fault() {
+        Some(Fault::Unavailable) => {
+            return error(StatusCode::SERVICE_UNAVAILABLE, 14, "service unavailable")
+                .into_response()
+        }
+        Some(Fault::RateLimited) => {
+            let (status, body) = error(StatusCode::TOO_MANY_REQUESTS, 8, "rate limit exceeded");
+            return (status, [(header::RETRY_AFTER, "1")], body).into_response();
+        }
+        Some(Fault::SequenceMismatch) => {
+            if let Some(sender) = MockChain::tx_sender(&tx_bytes) {
+                devnet.chain().advance_sequence(&sender);
+            }
+        }
+        Some(Fault::Evicted) => {
+            let check = CheckTx {
+                tx_hash: MockChain::tx_hash(&tx_bytes),
+                code: 0,
+                raw_log: String::new(),
+            };
+            return check_tx_reply(&check);
+        }
+        None => {}
+    }
+    let check = devnet.chain().broadcast(&tx_bytes, unix_now());
+    if check.code == 0 {
+        devnet.publish(&check.tx_hash);
+    }
+    check_tx_reply(&check)
+}
+
+/// The LCD's reply to a `sync` broadcast
+fn check_tx_reply(check: &CheckTx) -> Response {
+    (
+        StatusCode::OK,
+        Json(json!({
+            "tx_response": {
+                "height": "0",
+                "txhash": check.tx_hash,
+                "code": check.code,
+                "raw_log": check.raw_log,
+            }
+        })),
+    )
+        .into_response()
+}
+
+async fn tx(State(devnet): State<Devnet>, Path(hash): Path<String>) -> Reply {
+    let chain = devnet.chain();
+    let Some(record) = chain.tx(&hash) else {
+        return error(StatusCode::NOT_FOUND, 5, format!("tx not found: {}", hash));
+    };
+    (
+        StatusCode::OK,
+        Json(json!({ "tx_response": tx_response(&hash, record) })),
+    )
+}
+
+/// Transaction search by `events` conditions (Cosmos SDK 0.47) or an
+/// `AND`-joined `query` (0.50) of `type.key='value'` and `tx.height` terms,
+/// newest first unless `order_by` is `ORDER_BY_ASC`
+async fn search_txs(
+    State(devnet): State<Devnet>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Reply {
+    let mut conditions = Vec::new();
+    let (mut page, mut limit, mut ascending) = (1usize, 100usize, false);
+    for (key, value) in &params {
+        match key.as_str() {
+            "events" => conditions.push(value.as_str()),
+            "query" => conditions.extend(value.split(" AND ")),
+            "page" => page = value.parse().unwrap_or(1).max(1),
+            "limit" => limit = value.parse().unwrap_or(100).max(1),
+            "order_by" => ascending = value == "ORDER_BY_ASC",
+            _ => {}
+        }
+    }
+    if conditions.is_empty() {
+        return error(
+            StatusCode::BAD_REQUEST,
+            3,
+            "must declare at least one event to search",
+        );
+    }
+    let mut terms = Vec::new();
+    for condition in conditions {
+        let Some(term) = parse_condition(condition) else {
+            return error(
+                StatusCode::BAD_REQUEST,
+                3,
+                format!("invalid event condition: {}", condition),
+            );
+        };
+        terms.push(term);
+    }
+
+    let chain = devnet.chain();
+    let mut matching: Vec<_> = chain
+        .txs()
+        .into_iter()
+        .filter(|(_, record)| terms.iter().all(|term| term.matches(record)))
+        .collect();
+    if ascending {
+        matching.reverse();
+    }
+    let total = matching.len();
+    let tx_responses: Vec<Value> = matching
+        .into_iter()
+        .skip((page - 1) * limit)
+        .take(limit)
+        .map(|(hash, record)| tx_response(hash, record))
+        .collect();
+    (
+        StatusCode::OK,
+        Json(json!({ "txs": [], "tx_responses": tx_responses, "total": total.to_string() })),
+    )
+}
+
+/// A term of a transaction search
+enum Term<'a> {
+    /// `type.key='value'`: an event of `type` has the attribute
+    Attribute(&'a str, &'a str, &'a str),
+    /// `tx.height>=N`
+    MinHeight(u64),
+    /// `tx.height<=N`
+    MaxHeight(u64),
+}
+
+impl Term<'_> {
+    fn matches(&self, record: &TxRecord) -> bool {
+        match *self {
+            Term::Attribute(kind, key, value) => record
+                .events
+                .iter()
+                .any(|e| e.kind == kind && e.attribute(key) == Some(value)),
+            Term::MinHeight(height) => record.height >= height,
+            Term::MaxHeight(height) => record.height <= height,
+        }
+    }
+}
+
+fn parse_condition(condition: &str) -> Option<Term<'_>> {
+    let condition = condition.trim();
+    if let Some(height) = condition.strip_prefix("tx.height>=") {
+        return height.parse().ok().map(Term::MinHeight);
+    }
+    if let Some(height) = condition.strip_prefix("tx.height<=") {
+        return height.parse().ok().map(Term::MaxHeight);
+    }
+    let (name, value) = condition.split_once('=')?;
+    let (kind, key) = name.split_once('.')?;
+    let value = value.strip_prefix('\'')?.strip_suffix('\'')?;
+    Some(Term::Attribute(kind, key, value))
+}
+
+fn tx_response(hash: &str, record: &TxRecord) -> Value {
+    json!({
+        "height": record.height.to_string(),
+        "txhash": hash.to_ascii_uppercase(),
+        "code": record.code,
+        "raw_log": record.raw_log,
+        "gas_wanted": MOCK_GAS_USED.to_string(),
+        "gas_used": MOCK_GAS_USED.to_string(),
+        "events": record.events,
+        "timestamp": block_time(record.time),
+        "tx": {
+            "@type": "/cosmos.tx.v1beta1.Tx",
+            "body": { "messages": record.messages },
+        },
+    })
+}
+
+/// RFC 3339 UTC timestamp of a Unix time, as block times are reported
+fn block_time(unix: u64) -> String {
+    // Howard Hinnant's civil_from_days
+    let days = (unix / 86_400) as i64 + 719_468;
+    let seconds = unix % 86_400;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+async fn latest_block(State(devnet): State<Devnet>) -> Reply {
+    let chain = devnet.chain();
+    (
+        StatusCode::OK,
+        Json(json!({
+            "block": {
+                "header": {
+                    "chain_id": chain.params().chain_id,
+                    "height": chain.height().to_string(),
+                }
+            }
+        })),
+    )
+}
+
+#[derive(Deserialize)]
+struct RelayBody {
+    hash: String,
+    nonce: u64,
+    timestamp: u64,
+    miner_address: String,
+}
+
+async fn relay(State(devnet): State<Devnet>, Json(body): Json<RelayBody>) -> Reply {
+    let result = devnet.chain().relay(
+        &body.miner_address,
+        &body.hash,
+        body.nonce,
+        body.timestamp,
+        unix_now(),
+    );
+    let reply = match result {
+        Ok(tx_hash) => {
+            devnet.publish(&tx_hash);
+            json!({ "ok": true, "tx_hash": tx_hash })
+        }
+        Err(error) => json!({ "ok": false, "error": error }),
+    };
+    (StatusCode::OK, Json(reply))
+}
+
+async fn share_benchmark(
+    State(devnet): State<Devnet>,
+    Json(report): Json<BenchmarkReport>,
+) -> StatusCode {
+    devnet
+        .benchmarks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(report);
+    StatusCode::NO_CONTENT
+}
+
+async fn devices(State(devnet): State<Devnet>) -> Json<DeviceTable> {
+    Json(telemetry::aggregate(&devnet.benchmarks()))
+}
+
+async fn websocket(State(devnet): State<Devnet>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| subscription(socket, devnet))
+}
+
+/// Serve one subscriber: acknowledge its `subscribe` request, then forward every
+/// committed proof transaction (the query itself is not evaluated)
+async fn subscription(mut socket: WebSocket, devnet: Devnet) {
+    let id = loop {
+        match socket.recv().await {
+            Some(Ok(ws::Message::Text(text))) => {
+                let request: Value = serde_json::from_str(&text).unwrap_or_default();
+                if request["method"] == "subscribe" {
+                    break request["id"].clone();
+                }
+            }
+            Some(Ok(_)) => continue,
+            _ => return,
+        }
+    };
+
+    let mut txs = devnet.txs.subscribe();
+    let ack = json!({ "jsonrpc": "2.0", "id": id, "result": {} });
+    if socket
+        .send(ws::Message::Text(ack.to_string()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        tokio::select! {
+            tx = txs.recv() => match tx {
+                Ok(result) => {
+                    let notification = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                    if socket.send(ws::Message::Text(notification.to_string())).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(ws::Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+        }
+    }
+}
@@ -0,0 +1,602 @@
+//! Controller side: serve workers, hand out jobs and check their proofs
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+use super::{
+    hash_input, ControllerMessage, FleetError, Job, WorkerMessage, NONCE_RANGE, PROTOCOL_VERSION,
+};
+use crate::auth::{
+    new_challenge, AuthError, Authenticator, RateLimit, DEFAULT_RATE_BURST, DEFAULT_RATE_LIMIT,
+};
+use crate::pool::HasherPool;
+use crate::server;
+use crate::{meets_difficulty, UniversalHash};
+
+/// A connected worker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkerInfo {
+    pub id: u64,
+    /// Name the worker gave, or its address
+    pub name: String,
+    pub peer: SocketAddr,
+    pub threads: usize,
+    /// Hashes per second last reported
+    pub hashrate: f64,
+    /// Valid proofs sent
+    pub proofs: u64,
+}
+
+/// A checked proof from a worker, ready to submit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerProof {
+    pub worker: String,
+    pub job_id: u64,
+    pub hash: [u8; 32],
+    pub nonce: u64,
+    pub timestamp: u64,
+    /// Hex worker tag of the job, as [`crate::rpc::ProofSubmission`] takes it
+    pub worker_tag: Option<String>,
+}
+
+/// What happened on the controller, in order
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControllerEvent {
+    Connected(WorkerInfo),
+    Disconnected(WorkerInfo),
+    Proof(WorkerProof),
+    /// A worker sent a proof that is stale, a duplicate or wrong, or went
+    /// over its rate limit and was disconnected
+    Rejected {
+        worker: String,
+        reason: String,
+    },
+    /// A connection failed to sign in
+    Refused {
+        peer: SocketAddr,
+        reason: String,
+    },
+}
+
+/// The round being mined, before it is split into nonce ranges
+#[derive(Debug, Clone)]
+struct Round {
+    id: u64,
+    seed: [u8; 32],
+    address: String,
+    timestamp: u64,
+    difficulty: u32,
+    worker_tag: Vec<u8>,
+    next_range: Arc<AtomicU64>,
+    /// Nonces already proven, so a proof is submitted once
+    proven: Arc<Mutex<HashSet<u64>>>,
+}
+
+impl Round {
+    /// The next unused nonce range, as a job for one worker
+    fn assign(&self) -> Job {
+        let nonce_start = self.next_range.fetch_add(NONCE_RANGE, Ordering::SeqCst);
+        Job {
+            id: self.id,
+            seed: hex::encode(self.seed),
+            address: self.address.clone(),
+            timestamp: self.timestamp,
+            difficulty: self.difficulty,
+            nonce_start,
+            nonce_end: nonce_start + NONCE_RANGE,
+            worker_tag: self.hex_worker_tag(),
+        }
+    }
+
+    fn hex_worker_tag(&self) -> Option<String> {
+        (!self.worker_tag.is_empty()).then(|| hex::encode(&self.worker_tag))
+    }
+
+    /// Check a worker's proof against this round
+    fn check(
+        &self,
+        hasher: &mut UniversalHash,
+        job_id: u64,
+        nonce: u64,
+        hash_hex: &str,
+    ) -> Result<[u8; 32], String> {
+        if job_id != self.id {
+            return Err(format!("stale job {} (current: {})", job_id, self.id));
+        }
+        let hash = hash_input(
+            hasher,
+            &self.seed,
+            &self.address,
+            &self.worker_tag,
+            self.timestamp,
+            nonce,
+        );
+        if !hash_hex.eq_ignore_ascii_case(&hex::encode(hash)) {
+            return Err(format!("hash does not match nonce {}", nonce));
+        }
+        if !meets_difficulty(&hash, self.difficulty) {
+            return Err(format!("hash does not meet difficulty {}", self.difficulty));
+        }
+        if !self
+            .proven
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(nonce)
+        {
+            return Err(format!("duplicate proof for nonce {}", nonce));
+        }
+        Ok(hash)
+    }
+}
+
+/// Handle to a controller that workers connect to
+#[derive(Debug, Clone)]
+pub struct Controller {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    round: watch::Sender<Option<Round>>,
+    next_job_id: AtomicU64,
+    next_worker_id: AtomicU64,
+    workers: Mutex<BTreeMap<u64, WorkerInfo>>,
+    events: mpsc::Sender<ControllerEvent>,
+    auth: Authenticator,
+    /// Messages per second and burst each worker may send
+    rate_limit: (f64, u32),
+    /// Tag hashed after the address in every job
+    worker_tag: Vec<u8>,
+    /// Hashers for checking proofs, so each check skips allocating one
+    hashers: HasherPool,
+}
+
+impl Controller {
+    /// A controller with no job yet, and the receiver of its events
+    pub fn new() -> (Self, mpsc::Receiver<ControllerEvent>) {
+        let (events, receiver) = mpsc::channel();
+        let controller = Self {
+            shared: Arc::new(Shared {
+                round: watch::channel(None).0,
+                next_job_id: AtomicU64::new(1),
+                next_worker_id: AtomicU64::new(1),
+                workers: Mutex::default(),
+                events,
+                auth: Authenticator::open(),
+                rate_limit: (DEFAULT_RATE_LIMIT, DEFAULT_RATE_BURST),
+                worker_tag: Vec::new(),
+                hashers: HasherPool::new(
+                    std::thread::available_parallelism().map_or(1, |n| n.get()),
+                ),
+            }),
+        };
+        (controller, receiver)
+    }
+
+    /// Admit only the workers `auth` lets in; set before serving
+    pub fn with_auth(mut self, auth: Authenticator) -> Self {
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            shared.auth = auth;
+        }
+        self
+    }
+
+    /// Let each worker send `per_second` messages, `burst` at once; set
+    /// before serving
+    pub fn with_rate_limit(mut self, per_second: f64, burst: u32) -> Self {
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            shared.rate_limit = (per_second, burst);
+        }
+        self
+    }
+
+    /// Have workers hash `tag` after the address, for a contract that
+    /// accepts worker tags; set before serving
+    pub fn with_worker_tag(mut self, tag: impl Into<Vec<u8>>) -> Self {
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            shared.worker_tag = tag.into();
+        }
+        self
+    }
+
+    /// Replace the job every worker mines, returning its id
+    pub fn set_job(&self, seed: [u8; 32], address: &str, timestamp: u64, difficulty: u32) -> u64 {
+        let id = self.shared.next_job_id.fetch_add(1, Ordering::SeqCst);
+        self.shared.round.send_replace(Some(Round {
+            id,
+            seed,
+            address: address.to_string(),
+            timestamp,
+            difficulty,
+            worker_tag: self.shared.worker_tag.clone(),
+            next_range: Arc::new(AtomicU64::new(0)),
+            proven: Arc::default(),
+        }));
+        id
+    }
+
+    /// Tell every worker to stop hashing
+    pub fn idle(&self) {
+        self.shared.round.send_replace(None);
+    }
+
+    /// Workers connected now
+    pub fn workers(&self) -> Vec<WorkerInfo> {
+        self.shared.workers().values().cloned().collect()
+    }
+
+    /// Serve workers on an already bound listener until accepting fails
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
+        server::accept_loop(listener, |stream, peer| {
+            let shared = Arc::clone(&self.shared);
+            async move { shared.handle(stream, peer).await }
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Serve workers on `addr` in a background task, returning the bound address
+    pub fn spawn(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        let controller = self.clone();
+        Ok(server::spawn_listener(addr, |listener| async move {
+            controller.serve(listener).await
+        })?)
+    }
+}
+
+impl Shared {
+    fn workers(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, WorkerInfo>> {
+        self.workers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn emit(&self, event: ControllerEvent) {
+        // Nobody listening is not the workers' problem
+        let _ = self.events.send(event);
+    }
+
+    async fn handle(&self, stream: TcpStream, peer: SocketAddr) -> Result<(), FleetError> {
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let Some(line) = lines.next_line().await? else {
+            return Ok(());
+        };
+        let (name, threads, token, key) = match serde_json::from_str(&line)? {
+            WorkerMessage::Hello {
+                protocol: PROTOCOL_VERSION,
+                name,
+                threads,
+                token,
+                key,
+            } => (name, threads, token, key),
+            WorkerMessage::Hello { protocol, .. } => {
+                let message = format!(
+                    "worker speaks protocol {}, controller speaks {}",
+                    protocol, PROTOCOL_VERSION
+                );
+                return send(&mut write, &ControllerMessage::Error { message }).await;
+            }
+            _ => {
+                let message = "expected hello".to_string();
+                return send(&mut write, &ControllerMessage::Error { message }).await;
+            }
+        };
+
+        let signed_in = self
+            .authenticate(token.as_deref(), key.as_deref(), &mut lines, &mut write)
+            .await?;
+        if let Err(e) = signed_in {
+            self.emit(ControllerEvent::Refused {
+                peer,
+                reason: e.to_string(),
+            });
+            let message = format!("sign-in failed: {}", e);
+            return send(&mut write, &ControllerMessage::Error { message }).await;
+        }
+
+        let id = self.next_worker_id.fetch_add(1, Ordering::SeqCst);
+        let info = WorkerInfo {
+            id,
+            name: name.unwrap_or_else(|| peer.to_string()),
+            peer,
+            threads,
+            hashrate: 0.0,
+            proofs: 0,
+        };
+        self.workers().insert(id, info.clone());
+        self.emit(ControllerEvent::Connected(info));
+
+        let result = self.serve_worker(id, &mut lines, &mut write).await;
+
+        if let Some(info) = self.workers().remove(&id) {
+            self.emit(ControllerEvent::Disconnected(info));
+        }
+        result
+    }
+
+    /// Check the sign-in of `hello`'s `token` or `key`, challenging a key
+    async fn authenticate(
+        &self,
+        token: Option<&str>,
+        key: Option<&str>,
+        lines: &mut Lines<BufReader<OwnedReadHalf>>,
+        write: &mut OwnedWriteHalf,
+    ) -> Result<Result<(), AuthError>, FleetError> {
+        let Some(address) = key.filter(|_| !self.auth.is_open()) else {
+            return Ok(self.auth.check_token(token));
+        };
+        if let Err(e) = self.auth.allows_key(address) {
+            return Ok(Err(e));
+        }
+        let challenge = new_challenge();
+        let message = ControllerMessage::Challenge {
+            challenge: challenge.clone(),
+        };
+        send(write, &message).await?;
+        let Some(line) = lines.next_line().await? else {
+            return Ok(Err(AuthError::InvalidSignature));
+        };
+        Ok(match serde_json::from_str(&line)? {
+            WorkerMessage::Auth { signature } => {
+                self.auth.verify_key(address, &challenge, &signature)
+            }
+            _ => Err(AuthError::InvalidSignature),
+        })
+    }
+
+    /// Send jobs as they change and take in the worker's reports
+    async fn serve_worker(
+        &self,
+        id: u64,
+        lines: &mut Lines<BufReader<OwnedReadHalf>>,
+        write: &mut OwnedWriteHalf,
+    ) -> Result<(), FleetError> {
+        let (per_second, burst) = self.rate_limit;
+        let mut limit = RateLimit::new(per_second, burst, Instant::now());
+        let mut rounds = self.round.subscribe();
+        loop {
+            let message = match rounds.borrow_and_update().as_ref() {
+                Some(round) => ControllerMessage::Job(round.assign()),
+                None => ControllerMessage::Idle,
+            };
+            send(write, &message).await?;
+
+            loop {
+                tokio::select! {
+                    changed = rounds.changed() => {
+                        if changed.is_err() {
+                            return Ok(());
+                        }
+                        break;
+                    }
+                    line = lines.next_line() => match line? {
+                        // Dropped before a proof costs a hash to check
+                        Some(_) if !limit.allow(Instant::now()) => {
+                            return self.over_limit(id, write).await;
+                        }
+                        Some(line) => self.receive(id, serde_json::from_str(&line)?),
+                        None => return Ok(()),
+                    },
+                }
+            }
+        }
+    }
+
+    /// Disconnect a worker that went over its rate limit
+    async fn over_limit(&self, id: u64, write: &mut OwnedWriteHalf) -> Result<(), FleetError> {
+        let reason = "rate limit exceeded".to_string();
+        if let Some(worker) = self.workers().get(&id) {
+            self.emit(ControllerEvent::Rejected {
+                worker: worker.name.clone(),
+                reason: reason.clone(),
+            });
+        }
+        send(write, &ControllerMessage::Error { message: reason }).await
+    }
+
+    fn receive(&self, id: u64, message: WorkerMessage) {
+        match message {
+            WorkerMessage::Hello { .. } | WorkerMessage::Auth { .. } => {}
+            WorkerMessage::Progress { hashrate, .. } => {
+                if let Some(worker) = self.workers().get_mut(&id) {
+                    worker.hashrate = hashrate;
+                }
+            }
+            WorkerMessage::Proof {
+                job_id,
+                nonce,
+                hash,
+            } => {
+                let checked = match self.round.borrow().as_ref() {
+                    Some(round) => round
+                        .check(&mut self.hashers.get(), job_id, nonce, &hash)
+                        .map(|hash| (hash, round.timestamp, round.hex_worker_tag())),
+                    None => Err("no job is being mined".to_string()),
+                };
+                let worker = {
+                    let mut workers = self.workers();
+                    let Some(worker) = workers.get_mut(&id) else {
+                        return;
+                    };
+                    if checked.is_ok() {
+                        worker.proofs += 1;
+                    }
+                    worker.name.clone()
+                };
+                self.emit(match checked {
+                    Ok((hash, timestamp, worker_tag)) => ControllerEvent::Proof(WorkerProof {
+                        worker,
+                        job_id,
+                        hash,
+                        nonce,
+                        timestamp,
+                        worker_tag,
+                    }),
+                    Err(reason) => ControllerEvent::Rejected { worker, reason },
+                });
+            }
+        }
+    }
+}
+
+async fn send(write: &mut OwnedWriteHalf, message: &ControllerMessage) -> Result<(), FleetError> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    write.write_all(&line).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fleet::WorkerConnection;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    #[test]
+    fn test_controller_checks_worker_proofs() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (controller, events) = Controller::new();
+        let addr = {
+            let _guard = runtime.enter();
+            controller.spawn("127.0.0.1:0".parse().unwrap()).unwrap()
+        };
+        let url = format!("tcp://{}", addr);
+        let next_event = || events.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        let mut first = WorkerConnection::connect(&url, Some("rig-1"), 2).unwrap();
+        let mut second = WorkerConnection::connect(&url, None, 1).unwrap();
+        assert!(matches!(next_event(), ControllerEvent::Connected(_)));
+        assert!(matches!(next_event(), ControllerEvent::Connected(_)));
+        assert_eq!(first.recv().unwrap(), Some(ControllerMessage::Idle));
+        assert_eq!(second.recv().unwrap(), Some(ControllerMessage::Idle));
+
+        let job_id = controller.set_job([7u8; 32], "bostrom1controller", 1_700_000_000, 1);
+        let Some(ControllerMessage::Job(job)) = first.recv().unwrap() else {
+            panic!("expected a job");
+        };
+        let Some(ControllerMessage::Job(other)) = second.recv().unwrap() else {
+            panic!("expected a job");
+        };
+        assert_eq!(job.id, job_id);
+        assert_eq!(job.address, "bostrom1controller");
+        assert!(job.nonce_end <= other.nonce_start || other.nonce_end <= job.nonce_start);
+
+        let stop = AtomicBool::new(false);
+        let mut found = None;
+        job.search(0, 1, &stop, &AtomicU64::new(0), |nonce, hash| {
+            found = Some((nonce, hash));
+            stop.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+        let (nonce, hash) = found.expect("difficulty 1 is found within the range");
+        let proof = WorkerMessage::Proof {
+            job_id,
+            nonce,
+            hash: hex::encode(hash),
+        };
+        let sender = first.sender().unwrap();
+        sender.send(&proof).unwrap();
+        let ControllerEvent::Proof(accepted) = next_event() else {
+            panic!("expected the proof");
+        };
+        assert_eq!(accepted.worker, "rig-1");
+        assert_eq!((accepted.nonce, accepted.hash), (nonce, hash));
+        assert_eq!(accepted.timestamp, 1_700_000_000);
+
+        sender.send(&proof).unwrap();
+        let ControllerEvent::Rejected { reason, .. } = next_event() else {
+            panic!("a duplicate is rejected");
+        };
+        assert!(reason.contains("duplicate"), "{}", reason);
+        sender
+            .send(&WorkerMessage::Proof {
+                job_id,
+                nonce: nonce + 1,
+                hash: hex::encode(hash),
+            })
+            .unwrap();
+        assert!(matches!(next_event(), ControllerEvent::Rejected { .. }));
+
+        let workers = controller.workers();
+        assert_eq!(workers.len(), 2);
+        let rig = workers.iter().find(|w| w.name == "rig-1").unwrap();
+        assert_eq!((rig.threads, rig.proofs), (2, 1));
+        let unnamed = second.local_addr().unwrap().to_string();
+        assert!(workers.iter().any(|w| w.name == unnamed && w.proofs == 0));
+
+        drop((sender, first));
+        let ControllerEvent::Disconnected(gone) = next_event() else {
+            panic!("expected a disconnect");
+        };
+        assert_eq!(gone.name, "rig-1");
+    }
+
+    #[test]
+    fn test_controller_signs_workers_in() {
+        use crate::fleet::WorkerAuth;
+        use crate::wallet::Wallet;
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let allowed = Wallet::new().unwrap();
+        let auth = Authenticator::open()
+            .with_token("s3cret")
+            .with_key(&allowed.address_str());
+        let (controller, events) = Controller::new();
+        let controller = controller.with_auth(auth).with_rate_limit(1.0, 5);
+        let addr = {
+            let _guard = runtime.enter();
+            controller.spawn("127.0.0.1:0".parse().unwrap()).unwrap()
+        };
+        let url = format!("tcp://{}", addr);
+        let next_event = || events.recv_timeout(Duration::from_secs(10)).unwrap();
+        let connect = |auth| WorkerConnection::connect_with(&url, None, 1, &auth);
+
+        // Missing and wrong tokens, and keys not allowed, are refused
+        for auth in [
+            WorkerAuth::None,
+            WorkerAuth::Token("guess".to_string()),
+            WorkerAuth::Key(Box::new(Wallet::new().unwrap())),
+        ] {
+            let refused = connect(auth).and_then(|mut c| c.recv());
+            assert!(
+                matches!(refused, Err(FleetError::Refused(_))),
+                "{:?}",
+                refused
+            );
+            assert!(matches!(next_event(), ControllerEvent::Refused { .. }));
+        }
+
+        let mut by_token = connect(WorkerAuth::Token("s3cret".to_string())).unwrap();
+        assert!(matches!(next_event(), ControllerEvent::Connected(_)));
+        assert_eq!(by_token.recv().unwrap(), Some(ControllerMessage::Idle));
+        let mut by_key = connect(WorkerAuth::Key(Box::new(allowed))).unwrap();
+        assert!(matches!(next_event(), ControllerEvent::Connected(_)));
+        assert_eq!(by_key.recv().unwrap(), Some(ControllerMessage::Idle));
+
+        // A flood past the burst disconnects the worker
+        let sender = by_token.sender().unwrap();
+        let progress = WorkerMessage::Progress {
+            job_id: 0,
+            hashrate: 1.0,
+        };
+        for _ in 0..6 {
+            sender.send(&progress).unwrap();
+        }
+        let ControllerEvent::Rejected { reason, .. } = next_event() else {
+            panic!("expected the flood to be rejected");
+        };
+        assert_eq!(reason, "rate limit exceeded");
+        assert!(matches!(by_token.recv(), Err(FleetError::Refused(_))));
+        assert!(matches!(next_event(), ControllerEvent::Disconnected(_)));
+        assert_eq!(controller.workers().len(), 1);
+    }
+}
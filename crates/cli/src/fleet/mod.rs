@@ -0,0 +1,329 @@
+//! Fleet mining: one controller, many workers
+//!
+//! `uhash controller` holds the wallet and talks to the chain; `uhash worker`
+//! only hashes. Workers connect over TCP and exchange newline-delimited JSON:
+//!
+//! - worker → controller: [`WorkerMessage`] — `hello` first, then `progress`
+//!   reports and found `proof`s
+//! - controller → worker: [`ControllerMessage`] — a `job` whenever the seed,
+//!   difficulty or proof timestamp changes, `idle` when there is nothing to mine
+//!
+//! A controller can require workers to sign in (see [`crate::auth`]): with a
+//! token in `hello`, or with a key named in `hello`, whose holder answers the
+//! controller's `challenge` with a signed `auth`. Each worker's messages are
+//! rate limited, and a worker over its limit is disconnected.
+//!
+//! Every worker gets its own nonce range of a job, so no two workers hash the
+//! same input. Proofs are for the controller's address and the controller
+//! checks each one before submitting it, so a worker needs no keys, no RPC
+//! access and no trust.
+
+mod controller;
+mod worker;
+
+pub use controller::{Controller, ControllerEvent, WorkerInfo, WorkerProof};
+pub use worker::{WorkerAuth, WorkerConnection, WorkerSender};
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use thiserror::Error;
+
+use crate::proof::proof_input;
+use crate::wallet::StdSignature;
+use crate::{meets_difficulty, UniversalHash};
+
+/// Version of the worker protocol; the controller refuses other versions
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Nonces handed to each worker per job
+///
+/// At the hashrates U-Hash allows, a worker never exhausts its range before
+/// the next job arrives. Ranges start at zero and stay far below 2^53, so
+/// nonces survive JSON number handling.
+pub const NONCE_RANGE: u64 = 1 << 32;
+
+#[derive(Error, Debug)]
+pub enum FleetError {
+    #[error("Invalid controller URL '{0}' (expected tcp://host:port)")]
+    InvalidUrl(String),
+
+    #[error("Connection error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid message: {0}")]
+    Protocol(#[from] serde_json::Error),
+
+    #[error("Invalid job seed '{0}'")]
+    InvalidSeed(String),
+
+    #[error("Invalid job worker tag '{0}'")]
+    InvalidWorkerTag(String),
+
+    #[error("Controller refused the worker: {0}")]
+    Refused(String),
+}
+
+/// Messages a worker sends to its controller
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerMessage {
+    /// First message on a connection
+    Hello {
+        protocol: u32,
+        /// Display name; the controller falls back to the peer address
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        threads: usize,
+        /// Sign-in token, for controllers that require one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+        /// Address whose key the worker signs in with
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<String>,
+    },
+    /// Signature over the controller's challenge, by `hello`'s key
+    Auth { signature: StdSignature },
+    /// Hashes per second on the current job
+    Progress { job_id: u64, hashrate: f64 },
+    /// A hash of the job's input that meets its difficulty
+    Proof {
+        job_id: u64,
+        nonce: u64,
+        /// Hex hash, checked by the controller
+        hash: String,
+    },
+}
+
+/// Messages a controller sends to its workers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControllerMessage {
+    /// Mine this job, dropping any previous one
+    Job(Job),
+    /// Sign `challenge` with the key named in `hello` (see
+    /// [`crate::auth::challenge_data`])
+    Challenge { challenge: String },
+    /// Stop hashing until the next job
+    Idle,
+    /// The connection is about to close
+    Error { message: String },
+}
+
+/// A worker's share of a mining round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    /// Hex epoch seed
+    pub seed: String,
+    /// Address the proofs are for (the controller's)
+    pub address: String,
+    /// Proof timestamp (Unix seconds)
+    pub timestamp: u64,
+    pub difficulty: u32,
+    /// First nonce of this worker's range
+    pub nonce_start: u64,
+    /// End of the range (exclusive)
+    pub nonce_end: u64,
+    /// Hex bytes hashed after the address, naming the controller's machine
+    /// in its proofs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_tag: Option<String>,
+}
+
+impl Job {
+    /// The decoded epoch seed
+    pub fn seed(&self) -> Result<[u8; 32], FleetError> {
+        hex::decode(&self.seed)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| FleetError::InvalidSeed(self.seed.clone()))
+    }
+
+    /// The decoded worker tag, empty without one
+    pub fn worker_tag(&self) -> Result<Vec<u8>, FleetError> {
+        self.worker_tag
+            .as_deref()
+            .map_or(Ok(Vec::new()), hex::decode)
+            .map_err(|_| FleetError::InvalidWorkerTag(self.worker_tag.clone().unwrap_or_default()))
+    }
+
+    /// Hash the proof input for `nonce`
+    pub fn hash(&self, hasher: &mut UniversalHash, nonce: u64) -> Result<[u8; 32], FleetError> {
+        Ok(hash_input(
+            hasher,
+            &self.seed()?,
+            &self.address,
+            &self.worker_tag()?,
+            self.timestamp,
+            nonce,
+        ))
+    }
+
+    /// Search thread `thread_id` of `threads`' interleaved share of the range
+    ///
+    /// Calls `found` with each nonce and hash that meets the difficulty, and
+    /// returns when the range is exhausted or `stop` is set. `hashes` counts
+    /// the hashes computed.
+    pub fn search(
+        &self,
+        thread_id: usize,
+        threads: usize,
+        stop: &AtomicBool,
+        hashes: &AtomicU64,
+        mut found: impl FnMut(u64, [u8; 32]),
+    ) -> Result<(), FleetError> {
+        let seed = self.seed()?;
+        let tag = self.worker_tag()?;
+        let mut hasher = UniversalHash::new();
+        let mut nonce = self.nonce_start + thread_id as u64;
+        while nonce < self.nonce_end && !stop.load(Ordering::Relaxed) {
+            let hash = hash_input(
+                &mut hasher,
+                &seed,
+                &self.address,
+                &tag,
+                self.timestamp,
+                nonce,
+            );
+            hashes.fetch_add(1, Ordering::Relaxed);
+            if meets_difficulty(&hash, self.difficulty) {
+                found(nonce, hash);
+            }
+            nonce += threads as u64;
+        }
+        Ok(())
+    }
+}
+
+fn hash_input(
+    hasher: &mut UniversalHash,
+    seed: &[u8; 32],
+    address: &str,
+    worker_tag: &[u8],
+    timestamp: u64,
+    nonce: u64,
+) -> [u8; 32] {
+    hasher.hash(&proof_input(seed, address, worker_tag, timestamp, nonce))
+}
+
+/// Parse `tcp://host:port` into `host:port`
+pub fn parse_controller_url(url: &str) -> Result<String, FleetError> {
+    url.strip_prefix("tcp://")
+        .filter(|addr| {
+            addr.rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+        })
+        .map(str::to_string)
+        .ok_or_else(|| FleetError::InvalidUrl(url.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> Job {
+        Job {
+            id: 1,
+            seed: "11".repeat(32),
+            address: "bostrom1worker".to_string(),
+            timestamp: 1_700_000_000,
+            difficulty: 2,
+            nonce_start: NONCE_RANGE,
+            nonce_end: NONCE_RANGE + 64,
+            worker_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_search_stays_in_range_and_finds_valid_proofs() {
+        let job = job();
+        let stop = AtomicBool::new(false);
+        let hashes = AtomicU64::new(0);
+        let mut found = Vec::new();
+        job.search(1, 2, &stop, &hashes, |nonce, hash| {
+            found.push((nonce, hash))
+        })
+        .unwrap();
+
+        assert_eq!(hashes.load(Ordering::Relaxed), 32);
+        assert!(!found.is_empty());
+        let mut hasher = UniversalHash::new();
+        for (nonce, hash) in found {
+            assert!((job.nonce_start..job.nonce_end).contains(&nonce));
+            assert_eq!(nonce % 2, 1);
+            assert_eq!(job.hash(&mut hasher, nonce).unwrap(), hash);
+            assert!(meets_difficulty(&hash, job.difficulty));
+        }
+    }
+
+    #[test]
+    fn test_tagged_job_hashes_the_tag() {
+        let tagged = Job {
+            worker_tag: Some(hex::encode(b"rig-7")),
+            ..job()
+        };
+        let mut hasher = UniversalHash::new();
+        let seed = tagged.seed().unwrap();
+        assert_eq!(
+            tagged.hash(&mut hasher, 5).unwrap(),
+            hasher.hash(&proof_input(
+                &seed,
+                &tagged.address,
+                b"rig-7",
+                tagged.timestamp,
+                5
+            ))
+        );
+        assert_ne!(
+            tagged.hash(&mut hasher, 5).unwrap(),
+            job().hash(&mut hasher, 5).unwrap()
+        );
+        let garbled = Job {
+            worker_tag: Some("zz".to_string()),
+            ..job()
+        };
+        assert!(matches!(
+            garbled.hash(&mut hasher, 5),
+            Err(FleetError::InvalidWorkerTag(_))
+        ));
+    }
+
+    #[test]
+    fn test_messages_roundtrip() {
+        let message = ControllerMessage::Job(job());
+        let text = serde_json::to_string(&message).unwrap();
+        assert!(text.starts_with(r#"{"type":"job","id":1,"#), "{}", text);
+        assert_eq!(
+            serde_json::from_str::<ControllerMessage>(&text).unwrap(),
+            message
+        );
+
+        let hello: WorkerMessage =
+            serde_json::from_str(r#"{"type":"hello","protocol":1,"threads":4}"#).unwrap();
+        assert_eq!(
+            hello,
+            WorkerMessage::Hello {
+                protocol: 1,
+                name: None,
+                threads: 4,
+                token: None,
+                key: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_controller_url() {
+        assert_eq!(
+            parse_controller_url("tcp://10.0.0.2:7070").unwrap(),
+            "10.0.0.2:7070"
+        );
+        assert_eq!(
+            parse_controller_url("tcp://farm.local:7070").unwrap(),
+            "farm.local:7070"
+        );
+        assert!(parse_controller_url("10.0.0.2:7070").is_err());
+        assert!(parse_controller_url("tcp://10.0.0.2").is_err());
+        assert!(parse_controller_url("http://10.0.0.2:7070").is_err());
+    }
+}
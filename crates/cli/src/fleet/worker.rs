@@ -0,0 +1,125 @@
+//! Worker side: a blocking connection to the controller
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+
+use super::{parse_controller_url, ControllerMessage, FleetError, WorkerMessage, PROTOCOL_VERSION};
+use crate::auth::challenge_data;
+use crate::wallet::Wallet;
+
+/// How a worker signs in to a controller that requires it
+#[derive(Default)]
+pub enum WorkerAuth {
+    #[default]
+    None,
+    Token(String),
+    /// Answer the controller's challenge with this wallet's key
+    Key(Box<Wallet>),
+}
+
+/// A worker's connection to its controller
+#[derive(Debug)]
+pub struct WorkerConnection {
+    reader: BufReader<TcpStream>,
+    /// A message read while signing in, for the next [`recv`](Self::recv)
+    pending: Option<ControllerMessage>,
+}
+
+/// Sends messages on a [`WorkerConnection`], from any thread
+#[derive(Debug)]
+pub struct WorkerSender {
+    stream: TcpStream,
+}
+
+impl WorkerConnection {
+    /// Connect to `tcp://host:port` and introduce the worker
+    pub fn connect(url: &str, name: Option<&str>, threads: usize) -> Result<Self, FleetError> {
+        Self::connect_with(url, name, threads, &WorkerAuth::None)
+    }
+
+    /// [`connect`](Self::connect), signing in with `auth`
+    pub fn connect_with(
+        url: &str,
+        name: Option<&str>,
+        threads: usize,
+        auth: &WorkerAuth,
+    ) -> Result<Self, FleetError> {
+        let stream = TcpStream::connect(parse_controller_url(url)?)?;
+        stream.set_nodelay(true)?;
+        let mut connection = Self {
+            reader: BufReader::new(stream),
+            pending: None,
+        };
+        let (token, key) = match auth {
+            WorkerAuth::None => (None, None),
+            WorkerAuth::Token(token) => (Some(token.clone()), None),
+            WorkerAuth::Key(wallet) => (None, Some(wallet.address_str())),
+        };
+        let sender = connection.sender()?;
+        sender.send(&WorkerMessage::Hello {
+            protocol: PROTOCOL_VERSION,
+            name: name.map(str::to_string),
+            threads,
+            token,
+            key,
+        })?;
+
+        if let WorkerAuth::Key(wallet) = auth {
+            // A controller that does not check keys goes straight to work
+            match connection.recv()? {
+                Some(ControllerMessage::Challenge { challenge }) => {
+                    let signature = wallet
+                        .sign_arbitrary(&challenge_data(&challenge))
+                        .map_err(|e| FleetError::Refused(e.to_string()))?;
+                    sender.send(&WorkerMessage::Auth { signature })?;
+                }
+                message => connection.pending = message,
+            }
+        }
+        Ok(connection)
+    }
+
+    /// A handle for sending while another thread waits in [`recv`](Self::recv)
+    pub fn sender(&self) -> Result<WorkerSender, FleetError> {
+        Ok(WorkerSender {
+            stream: self.reader.get_ref().try_clone()?,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, FleetError> {
+        Ok(self.reader.get_ref().local_addr()?)
+    }
+
+    /// Wait for the next message, `None` once the controller hangs up
+    ///
+    /// A controller that refuses the worker is reported as
+    /// [`FleetError::Refused`].
+    pub fn recv(&mut self) -> Result<Option<ControllerMessage>, FleetError> {
+        if let Some(message) = self.pending.take() {
+            return Ok(Some(message));
+        }
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match serde_json::from_str(&line)? {
+            ControllerMessage::Error { message } => Err(FleetError::Refused(message)),
+            message => Ok(Some(message)),
+        }
+    }
+}
+
+impl WorkerSender {
+    pub fn send(&self, message: &WorkerMessage) -> Result<(), FleetError> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        (&self.stream).write_all(&line)?;
+        Ok(())
+    }
+
+    /// Close the connection, waking a thread blocked in
+    /// [`WorkerConnection::recv`]
+    pub fn close(&self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
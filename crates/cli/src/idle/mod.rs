@@ -0,0 +1,191 @@
+//! Idle detection for `mine --when-idle`
+//!
+//! The desktop miner only hashes while nobody uses the machine: once there has
+//! been no keyboard or mouse input for a while, or as soon as the screen is
+//! locked. It stops again when input comes back.
+//!
+//! There is no portable API for this, so [`probe`] asks the OS tools that
+//! already track it:
+//!
+//! - Linux: `xprintidle` (X11 input idle time) and `loginctl` (the session's
+//!   idle and lock hints, also set by Wayland desktops)
+//! - macOS: `ioreg` (`HIDIdleTime` of the HID system)
+//!
+//! Other platforms report [`IdleError::Unsupported`].
+
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use thiserror::Error;
+
+/// How often a running miner checks whether the machine is idle
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Errors detecting idleness
+#[derive(Debug, Error)]
+pub enum IdleError {
+    #[error("idle detection is not supported on this platform")]
+    Unsupported,
+
+    #[error("no idle source answered (install xprintidle, or run under systemd-logind)")]
+    NoSource,
+}
+
+/// What the OS reports about the user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdleState {
+    /// Time since the last input, if known
+    pub idle_for: Option<Duration>,
+    /// The screen is locked
+    pub locked: bool,
+}
+
+impl IdleState {
+    /// Whether to mine, idle meaning locked or `idle_after` without input
+    pub fn is_idle(&self, idle_after: Duration) -> bool {
+        self.locked || self.idle_for.is_some_and(|idle| idle >= idle_after)
+    }
+}
+
+/// Ask the OS how long the user has been away
+pub fn probe() -> Result<IdleState, IdleError> {
+    if cfg!(target_os = "macos") {
+        let idle_for = run("ioreg", &["-c", "IOHIDSystem", "-d", "4"])
+            .and_then(|out| parse_ioreg(&out))
+            .ok_or(IdleError::NoSource)?;
+        return Ok(IdleState {
+            idle_for: Some(idle_for),
+            locked: false,
+        });
+    }
+    if !cfg!(target_os = "linux") {
+        return Err(IdleError::Unsupported);
+    }
+
+    let input = std::env::var_os("DISPLAY")
+        .and_then(|_| run("xprintidle", &[]))
+        .and_then(|out| parse_xprintidle(&out));
+    let session = std::env::var("XDG_SESSION_ID")
+        .ok()
+        .and_then(|id| {
+            run(
+                "loginctl",
+                &[
+                    "show-session",
+                    &id,
+                    "-p",
+                    "IdleHint",
+                    "-p",
+                    "IdleSinceHint",
+                    "-p",
+                    "LockedHint",
+                ],
+            )
+        })
+        .map(|out| parse_loginctl(&out, unix_micros()));
+    match (input, session) {
+        (None, None) => Err(IdleError::NoSource),
+        // Input idle time is the more precise of the two
+        (Some(idle_for), session) => Ok(IdleState {
+            idle_for: Some(idle_for),
+            locked: session.is_some_and(|s| s.locked),
+        }),
+        (None, Some(session)) => Ok(session),
+    }
+}
+
+/// Standard output of `program`, `None` if it could not run or failed
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn unix_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+/// Milliseconds printed by `xprintidle`
+pub fn parse_xprintidle(output: &str) -> Option<Duration> {
+    output.trim().parse().ok().map(Duration::from_millis)
+}
+
+/// `Key=value` lines of `loginctl show-session`, at `now_us` (Unix microseconds)
+///
+/// `IdleSinceHint` is when the session went idle, so the idle time is only
+/// known while `IdleHint` is set; otherwise the user is active.
+pub fn parse_loginctl(output: &str, now_us: u64) -> IdleState {
+    let value = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::trim)
+    };
+    let idle = value("IdleHint") == Some("yes");
+    let since = value("IdleSinceHint").and_then(|v| v.parse::<u64>().ok());
+    IdleState {
+        idle_for: Some(match (idle, since) {
+            (true, Some(since)) if since > 0 => Duration::from_micros(now_us.saturating_sub(since)),
+            // Idle since an unknown time: count it as long enough
+            (true, _) => Duration::MAX,
+            (false, _) => Duration::ZERO,
+        }),
+        locked: value("LockedHint") == Some("yes"),
+    }
+}
+
+/// `"HIDIdleTime" = <nanoseconds>` in `ioreg -c IOHIDSystem` output
+pub fn parse_ioreg(output: &str) -> Option<Duration> {
+    output.lines().find_map(|line| {
+        let (_, value) = line.split_once("\"HIDIdleTime\" = ")?;
+        value.trim().parse().ok().map(Duration::from_nanos)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xprintidle() {
+        assert_eq!(
+            parse_xprintidle("1500\n"),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(parse_xprintidle("couldn't open display"), None);
+    }
+
+    #[test]
+    fn test_parse_loginctl() {
+        let now = 1_700_000_600_000_000;
+        let idle = parse_loginctl(
+            "IdleHint=yes\nIdleSinceHint=1700000000000000\nLockedHint=no\n",
+            now,
+        );
+        assert_eq!(idle.idle_for, Some(Duration::from_secs(600)));
+        assert!(!idle.locked);
+        assert!(idle.is_idle(Duration::from_secs(300)));
+        assert!(!idle.is_idle(Duration::from_secs(900)));
+
+        let active = parse_loginctl("IdleHint=no\nIdleSinceHint=0\nLockedHint=no\n", now);
+        assert_eq!(active.idle_for, Some(Duration::ZERO));
+        assert!(!active.is_idle(Duration::from_secs(60)));
+
+        // A locked screen counts as idle at once
+        let locked = parse_loginctl("IdleHint=no\nLockedHint=yes\n", now);
+        assert!(locked.is_idle(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_parse_ioreg() {
+        let output = r#"    | |   "HIDIdleTime" = 123000000000
+    | |   "HIDParameters" = {}"#;
+        assert_eq!(parse_ioreg(output), Some(Duration::from_secs(123)));
+        assert_eq!(parse_ioreg("nothing here"), None);
+    }
+}
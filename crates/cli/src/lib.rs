@@ -34,7 +34,47 @@
 // Re-export the core algorithm
 pub use uhash_core as algorithm;
 
+#[cfg(feature = "api")]
+pub mod api;
+pub mod audit;
+pub mod auth;
+pub mod chain;
+pub mod config;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+#[cfg(feature = "devnet")]
+pub mod devnet;
+#[cfg(feature = "fleet")]
+pub mod fleet;
+pub mod idle;
+pub mod miner;
+pub mod network;
+pub mod pending;
+pub mod pool;
+pub mod profit;
+pub mod proof;
+#[cfg(feature = "proxy")]
+pub mod proxy;
+#[cfg(feature = "relay")]
+pub mod relay;
+pub mod resume;
 pub mod rpc;
+#[cfg(any(
+    feature = "api",
+    feature = "dashboard",
+    feature = "devnet",
+    feature = "fleet",
+    feature = "proxy",
+    feature = "relay"
+))]
+mod server;
+pub mod sim;
+pub mod sink;
+pub mod stats;
+pub mod status;
+#[cfg(feature = "stratum")]
+pub mod stratum;
+pub mod telemetry;
 pub mod wallet;
 
 // Convenience re-exports
@@ -8,19 +8,87 @@
 //! - `send` - Submit a proof to the chain
 //! - `import-mnemonic` - Import a wallet from mnemonic phrase
 //! - `export-mnemonic` - Export the wallet mnemonic
-//! - `benchmark` - Run performance benchmark
+//! - `sign-message` / `verify-message` - ADR-036 proof of address ownership
+//! - `import-key` / `export-key` - Move keys to and from Cosmos SDK armored keystores
+//! - `benchmark` - Run performance benchmark (`--share` uploads the result)
+//! - `devices` - Compare hashrates that other users shared
+//! - `simulate-difficulty` - Model difficulty retargeting for a hashrate scenario
+//! - `profit` - Estimate daily earnings and break-even for this device
 //! - `status` - Query contract state (seed, difficulty, config)
+//! - `report` - Summarize locally recorded mining history (`stats` feature)
+//! - `dashboard` - Serve a web UI showing the running miner
+//! - `controller` / `worker` - Mine as a fleet: workers hash, one controller submits
+//! - `config` - Read and write persistent settings
 
 use clap::{Parser, Subcommand};
 use serde::Serialize;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::io::{IsTerminal, Read};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
-use uhash::rpc::{ProofSubmission, RpcClient};
-use uhash::wallet::{default_wallet_path, ensure_wallet_dir, Wallet};
-use uhash::{meets_difficulty, UniversalHash};
+use uhash::algorithm::difficulty::Luck;
+use uhash::algorithm::HashTiming;
+use uhash::api::{Api, MAX_THREADS};
+use uhash::audit::{Auditor, Discrepancy, History};
+use uhash::auth::{Authenticator, DEFAULT_RATE_BURST, DEFAULT_RATE_LIMIT};
+use uhash::chain::ChainProfile;
+use uhash::config::{
+    default_config_path, Config, ConfigError, StatsConfig, SubmitConfig, WalletBackend,
+};
+use uhash::devnet::{Devnet, DevnetParams};
+use uhash::fleet::{
+    parse_controller_url, Controller, ControllerEvent, ControllerMessage, FleetError, Job,
+    WorkerAuth, WorkerConnection, WorkerInfo, WorkerMessage, WorkerSender, NONCE_RANGE,
+};
+use uhash::idle::{self, IDLE_POLL_INTERVAL};
+use uhash::miner::{
+    self, available_memory, check_worker_tag, fit_threads_to_memory, resumable_round, AddressBook,
+    AddressStats, DeliveryError, DeliveryEvent, Due, MinerControl, NonceRange, Restart, ResumeNote,
+    RoundParams, Schedule, Session, SessionConfig, SessionEnd, SessionEvent, SessionHost,
+    Submitter, MONITOR_INTERVAL, PROGRESS_INTERVAL, PROOF_AGE_MARGIN_SECS,
+};
+use uhash::network::{HashrateEstimate, ProofSample};
+use uhash::pending::{PendingQueue, PENDING_FILE_NAME};
+use uhash::profit::{Prices, ProfitEstimate, ProfitParams};
+use uhash::proof::Proof;
+use uhash::resume::ROUND_STATE_FILE_NAME;
+use uhash::rpc::{
+    fee_for_gas, lcd_url_for_rpc, parse_gas, parse_gas_price, preflight, proof_reward,
+    sign_proof_tx, validate_endpoint, BroadcastMode, ChainClient, CheckStatus, ContractSchema,
+    EndpointStatus, PreflightCheck, ProofEvent, ProofSimulation, ProofSubmission, RpcClient,
+    RpcConfig, RpcError, SeedInfo, SignedTx, SubmitResult, SubmittedProof, Transport, TxInfo,
+    TxStatus, DEFAULT_CONFIRM_TIMEOUT_SECS, DEFAULT_FINALITY_DEPTH, MAX_MEMO_LEN, SUBMIT_PROOF_GAS,
+};
+use uhash::sim::{Scenario, SimParams, SimReport};
+use uhash::sink::{self, Delivery, SinkKind};
+use uhash::stats::{ProofRecord, RoundOutcome, RoundRecord};
+use uhash::status::{MinerStatus, STATUS_FILE_NAME};
+use uhash::telemetry::{BenchmarkReport, DeviceClass, DeviceTable};
+use uhash::wallet::{
+    default_wallet_path, parse_language, read_header, validate_address,
+    validate_address_with_prefix, verify_arbitrary, write_private_file, EncryptedFileStore,
+    FileStore, KeychainStore, StdPubKey, StdSignature, Wallet, WalletError, WalletStore,
+};
+use uhash::UniversalHash;
+use zeroize::Zeroizing;
+
+/// How often `--json` mining emits a `hashrate` event
+const HASHRATE_EVENT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Threads up to which the progress line lists every thread's hashrate
+const PROGRESS_THREAD_RATES: usize = 8;
+
+/// Seconds `mine --addresses` mines for one address before the next one
+const DEFAULT_ADDRESS_SLICE_SECS: u64 = 60;
+
+/// How long a worker waits before reconnecting to its controller
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Seconds to wait for a faucet-funded account to appear on chain
+const FAUCET_WAIT_SECS: u64 = 60;
 
 // ── JSON output structs ──
 
@@ -32,6 +100,50 @@ struct JsonProofFound {
     timestamp: u64,
     hashes_computed: u64,
     hashrate: f64,
+    /// H/s of each mining thread over the round
+    thread_hashrates: Vec<f64>,
+    /// Hashes since the last proof, in expected proofs
+    effort: f64,
+    /// Expected over actual hashes: above 1 is lucky
+    luck: f64,
+    /// Chance of a proof taking at least this many hashes
+    luck_probability: f64,
+}
+
+/// Periodic hashrate report of `mine` and `worker`
+#[derive(Serialize)]
+struct JsonHashrate {
+    event: &'static str,
+    hashrate: f64,
+    /// H/s of each mining thread, to spot a throttled or mis-pinned core
+    thread_hashrates: Vec<f64>,
+    hashes: u64,
+    elapsed_s: f64,
+}
+
+/// A mining round of `mine` starting: one seed and timestamp
+#[derive(Serialize)]
+struct JsonRoundStarted {
+    event: &'static str,
+    /// Reward address the round mines for
+    address: String,
+    seed: String,
+    difficulty: u32,
+    /// Unix seconds in the proof input
+    timestamp: u64,
+    threads: usize,
+}
+
+/// A mining round of `mine` ending, however it ended
+#[derive(Serialize)]
+struct JsonRoundEnded {
+    event: &'static str,
+    seed: String,
+    difficulty: u32,
+    timestamp: u64,
+    outcome: RoundOutcome,
+    duration_s: f64,
+    hashes: u64,
 }
 
 #[derive(Serialize)]
@@ -40,6 +152,100 @@ struct JsonProofSubmitted {
     tx_hash: String,
     success: bool,
     proofs_submitted: u64,
+    /// Amount paid for the proof; this and the fields below are known only
+    /// once the transaction is confirmed (`--broadcast-mode block-equivalent`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reward: Option<String>,
+    /// Denom the reward was paid in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reward_denom: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_used: Option<u64>,
+    /// Block the transaction was committed at
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u64>,
+    /// Sink the proof went to (`contract`, a URL or a path)
+    destination: String,
+}
+
+/// A sent proof transaction reached a block, became final or was dropped
+#[derive(Serialize)]
+struct JsonTxStatus {
+    event: &'static str,
+    tx_hash: String,
+    #[serde(flatten)]
+    status: TxStatus,
+    /// Hashes of the proofs the transaction carries
+    proofs: Vec<String>,
+    /// How many of the proofs of a dropped transaction were sent again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resubmitted: Option<usize>,
+}
+
+/// `mine --when-idle` saw the machine go idle or come back into use
+#[derive(Serialize)]
+struct JsonIdleChanged {
+    event: &'static str,
+    /// Mining goes on while idle and waits otherwise
+    idle: bool,
+}
+
+/// `mine` re-read its config file
+#[derive(Serialize)]
+struct JsonConfigReloaded {
+    event: &'static str,
+    /// Settings applied, e.g. `threads=8`
+    changed: Vec<String>,
+    /// Why the file could not be applied; the miner keeps its settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A proof saved to the pending queue when `mine` was stopped before sending it
+#[derive(Serialize)]
+struct JsonProofPending {
+    event: &'static str,
+    hash: String,
+    nonce: u64,
+    timestamp: u64,
+    path: String,
+}
+
+/// Summary `mine` prints when it stops
+#[derive(Serialize)]
+struct JsonMineStopped {
+    event: &'static str,
+    /// `signal`, `max_proofs`, `no_submit` or `stopped`
+    reason: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signal: Option<&'static str>,
+    proofs_found: u64,
+    proofs_submitted: u64,
+    proofs_unsigned: u64,
+    /// Saved to the pending queue for the next start
+    proofs_pending: u64,
+    /// The interrupted round was saved for `mine --resume`
+    round_saved: bool,
+    hashes: u64,
+    elapsed_s: f64,
+    /// Work per reward address when mining with `--addresses`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    addresses: Vec<AddressStats>,
+}
+
+/// A proof a watch-only wallet found, to be signed offline with `sign-proof`
+#[derive(Serialize)]
+struct JsonProofUnsigned {
+    event: &'static str,
+    hash: String,
+    nonce: u64,
+    timestamp: u64,
+    /// Base64 binary proof for `sign-proof --proof`
+    proof: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence: Option<u64>,
 }
 
 #[derive(Serialize)]
@@ -47,16 +253,108 @@ struct JsonMineStarted {
     event: &'static str,
     contract: String,
     address: String,
+    /// Reward addresses from `--addresses`, mined for in turn
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    addresses: Vec<String>,
     difficulty: u32,
     threads: usize,
     seed: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contract_schema: Option<ContractSchema>,
     auto_submit: bool,
+    watch_only: bool,
+    account: u32,
+    /// Tag hashed after the address in every proof
+    #[serde(skip_serializing_if = "Option::is_none")]
+    worker_tag: Option<String>,
+    /// Management API URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api: Option<String>,
+    /// Timestamp of the saved round continued with `--resume`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resumed: Option<u64>,
+    /// Endpoint, chain, contract and account checks run before mining
+    preflight: Vec<PreflightCheck>,
+}
+
+#[derive(Serialize)]
+struct JsonControllerStarted {
+    event: &'static str,
+    listen: String,
+    contract: String,
+    address: String,
+    difficulty: u32,
+    seed: String,
+    watch_only: bool,
+}
+
+/// A worker connecting to or leaving the controller
+#[derive(Serialize)]
+struct JsonWorker {
+    event: &'static str,
+    #[serde(flatten)]
+    worker: WorkerInfo,
+}
+
+#[derive(Serialize)]
+struct JsonWorkerProof {
+    event: &'static str,
+    worker: String,
+    hash: String,
+    nonce: u64,
+    timestamp: u64,
+}
+
+/// Outcome of `send --dry-run`
+#[derive(Serialize)]
+struct JsonSendSimulation {
+    event: &'static str,
+    accepted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 #[derive(Serialize)]
 struct JsonSendResult {
     tx_hash: String,
     success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reward: Option<String>,
+}
+
+/// Outcome of `uhash init`
+#[derive(Serialize)]
+struct JsonInit {
+    address: String,
+    /// `created`, `imported` or `existing`
+    wallet: &'static str,
+    rpc: Vec<String>,
+    lcd: Vec<String>,
+    /// Whether any LCD endpoint answered
+    reachable: bool,
+    threads: usize,
+    /// H/s measured with `threads` threads
+    hashrate: f64,
+    config: String,
+    dry_run: JsonDryRun,
+}
+
+/// One hash of a real proof input, computed at the end of `uhash init`
+#[derive(Serialize)]
+struct JsonDryRun {
+    hash: String,
+    elapsed_ms: f64,
+    /// Contract difficulty, if the chain answered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected_secs_per_proof: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -65,6 +363,19 @@ struct JsonBenchmark {
     elapsed_s: f64,
     hashrate: f64,
     params: JsonAlgoParams,
+    /// Average time per hash in each phase, with `--detailed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phases: Option<JsonPhaseTiming>,
+    /// What `--share` uploaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shared: Option<BenchmarkReport>,
+}
+
+#[derive(Serialize)]
+struct JsonPhaseTiming {
+    scratchpad_init_us: f64,
+    rounds_us: f64,
+    finalize_us: f64,
 }
 
 #[derive(Serialize)]
@@ -82,10 +393,83 @@ struct JsonWallet {
     path: Option<String>,
 }
 
+#[cfg(feature = "stats")]
+#[derive(Serialize)]
+struct JsonExport {
+    format: &'static str,
+    files: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct JsonSimulation {
+    params: SimParams,
+    scenario: Scenario,
+    #[serde(flatten)]
+    report: SimReport,
+}
+
+#[derive(Serialize)]
+struct JsonProfit {
+    params: ProfitParams,
+    /// Hashrate came from a benchmark rather than `--hashrate`
+    measured: bool,
+    #[serde(flatten)]
+    estimate: ProfitEstimate,
+}
+
+#[derive(Serialize)]
+struct JsonDiscrepancy<'a> {
+    event: &'static str,
+    #[serde(flatten)]
+    proof: &'a SubmittedProof,
+    discrepancy: &'a Discrepancy,
+    reason: String,
+}
+
+#[derive(Serialize)]
+struct JsonAuditFinished {
+    event: &'static str,
+    proofs: u64,
+    verified: u64,
+    discrepancies: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_height: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct JsonNetworkHashrate {
+    #[serde(flatten)]
+    estimate: HashrateEstimate,
+    /// Current difficulty
+    difficulty: u32,
+    from_height: u64,
+    to_height: u64,
+}
+
+#[derive(Serialize)]
+struct JsonAddress {
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    png: Option<String>,
+}
+
+/// `validate-address` output; an invalid address is reported as an error
+#[derive(Serialize)]
+struct JsonValidAddress<'a> {
+    address: &'a str,
+    valid: bool,
+    /// `account` or `contract`
+    kind: &'static str,
+}
+
 #[derive(Serialize)]
 struct JsonStatus {
     contract: String,
     seed: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed_expires_at: Option<u64>,
     difficulty: u32,
     min_profitable_difficulty: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -96,6 +480,40 @@ struct JsonStatus {
     paused: Option<bool>,
 }
 
+#[derive(Serialize)]
+struct JsonSubscribed {
+    event: &'static str,
+    endpoint: String,
+    contract: String,
+}
+
+#[derive(Serialize)]
+struct JsonProofAccepted {
+    event: &'static str,
+    #[serde(flatten)]
+    proof: ProofEvent,
+}
+
+#[derive(Serialize)]
+struct JsonTx {
+    #[serde(flatten)]
+    tx: TxInfo,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reward: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonFaucet {
+    address: String,
+    /// Whether funds were requested (false: the account already existed)
+    requested: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence: Option<u64>,
+}
+
 #[derive(Serialize)]
 struct JsonError {
     error: String,
@@ -110,9 +528,13 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Custom RPC endpoint
-    #[arg(long, global = true)]
-    rpc: Option<String>,
+    /// Custom RPC endpoint(s), comma-separated; later ones are failover targets
+    #[arg(long, global = true, value_delimiter = ',')]
+    rpc: Vec<String>,
+
+    /// LCD (REST) endpoint(s), comma-separated (default: derived from --rpc)
+    #[arg(long, global = true, value_delimiter = ',')]
+    lcd: Vec<String>,
 
     /// Custom contract address (default: production contract)
     #[arg(long, global = true)]
@@ -122,10 +544,69 @@ struct Cli {
     #[arg(long, global = true, default_value = "0")]
     fee: u128,
 
+    /// Gas limit per transaction, or `auto` to simulate first
+    #[arg(long, global = true, default_value = "1600000")]
+    gas: String,
+
+    /// Multiplier applied to simulated gas (with `--gas auto`)
+    #[arg(long, global = true, default_value = "1.3")]
+    gas_adjustment: f64,
+
+    /// Gas price such as `0.01boot`; the fee becomes gas * price (overrides --fee)
+    #[arg(long, global = true)]
+    gas_prices: Option<String>,
+
+    /// Broadcast mode: sync, async, or block-equivalent (sync + wait for commit)
+    #[arg(long, global = true, default_value = "sync")]
+    broadcast_mode: BroadcastMode,
+
+    /// Seconds to wait for a commit in block-equivalent mode, and for a sync or
+    /// async proof transaction to reach a block before it is sent again
+    #[arg(long, global = true, default_value_t = DEFAULT_CONFIRM_TIMEOUT_SECS)]
+    confirm_timeout: u64,
+
+    /// Blocks on top of a proof transaction's block before it counts as final
+    #[arg(long, global = true, default_value_t = DEFAULT_FINALITY_DEPTH)]
+    finality_depth: u64,
+
+    /// Account whose fee grant (x/feegrant) pays transaction fees
+    #[arg(long, global = true)]
+    fee_granter: Option<String>,
+
+    /// Account that pays transaction fees; it must be the signing wallet
+    /// (use --fee-granter to have another account pay)
+    #[arg(long, global = true)]
+    fee_payer: Option<String>,
+
+    /// Proxy for all HTTP traffic (http://, https://, socks5:// or socks5h://)
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+
+    /// Seconds allowed per chain request (0 = no limit)
+    #[arg(long, global = true)]
+    timeout: Option<u64>,
+
+    /// Log each chain request's status, latency and truncated bodies to stderr
+    #[arg(long, global = true)]
+    rpc_debug: bool,
+
     /// Custom wallet file path
     #[arg(long, global = true)]
     wallet: Option<PathBuf>,
 
+    /// Load wallet files even if other users can read them
+    #[arg(long, global = true)]
+    insecure_ok: bool,
+
+    /// Account derived from the wallet mnemonic (HD path m/44'/<coin type>'/N'/0/0)
+    /// [default: the account the wallet was saved for, 0 for new wallets]
+    #[arg(long, global = true)]
+    account: Option<u32>,
+
+    /// Custom config file path (default: ~/.uhash/config.toml)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
     /// Output in JSON format (machine-readable, for agent/script integration)
     #[arg(long, global = true)]
     json: bool,
@@ -133,9 +614,16 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Set up a wallet, endpoints and threads, then check with a dry-run hash
+    Init {
+        /// Accept every default without asking (creates a wallet if none exists)
+        #[arg(long)]
+        yes: bool,
+    },
+
     /// Start mining LI (Lithium) tokens
     Mine {
-        /// Number of threads to use (default: number of CPU cores)
+        /// Number of threads to use (default: mine.threads, else number of CPU cores)
         #[arg(short, long)]
         threads: Option<usize>,
 
@@ -146,6 +634,132 @@ enum Commands {
         /// Disable auto-submit (just print found proofs)
         #[arg(long)]
         no_submit: bool,
+
+        /// Stop after this many proofs have been submitted
+        #[arg(long, conflicts_with = "no_submit")]
+        max_proofs: Option<u64>,
+
+        /// Transaction memo, e.g. a worker name (max 256 characters)
+        #[arg(long, default_value = "")]
+        memo: String,
+
+        /// Serve the management API on this address (token: UHASH_API_TOKEN or api.token)
+        #[arg(long)]
+        api: Option<SocketAddr>,
+
+        /// Sign each proof and hand it to this `uhash-relay`, which pays for the
+        /// transaction (overrides submit.sink)
+        #[arg(long, value_name = "URL", conflicts_with = "no_submit")]
+        submit_via: Option<String>,
+
+        /// Submit up to N proofs saved by an earlier run in one transaction
+        /// (default: submit.batch_size)
+        #[arg(long, value_name = "N", conflicts_with = "no_submit")]
+        batch_size: Option<usize>,
+
+        /// Continue the round saved when mine was last stopped, if its seed is
+        /// still current, instead of hashing its nonces again
+        #[arg(long)]
+        resume: bool,
+
+        /// First nonce to hash
+        #[arg(long, default_value_t = 0)]
+        nonce_start: u64,
+
+        /// Hash at most this many nonces per round, then start a round with a
+        /// fresh timestamp (with --worker-count: the size of each worker's
+        /// range, default 2^32)
+        #[arg(long)]
+        nonce_range: Option<u64>,
+
+        /// This machine's range when several mine for one address without a
+        /// controller, from 0 to --worker-count - 1
+        #[arg(long, requires = "worker_count")]
+        worker_index: Option<u64>,
+
+        /// Number of machines splitting the nonce space with --worker-index
+        #[arg(long, requires = "worker_index")]
+        worker_count: Option<u64>,
+
+        /// Start each round at a random nonce, so miners sharing an address
+        /// without splitting the nonce space rarely hash the same nonces
+        #[arg(long, conflicts_with_all = ["nonce_start", "worker_index"])]
+        nonce_random: bool,
+
+        /// Only mine while the machine is idle: no input for MINUTES (5 if
+        /// omitted) or the screen locked; stop when the user returns
+        #[arg(long, value_name = "MINUTES", num_args = 0..=1, default_missing_value = "5")]
+        when_idle: Option<u64>,
+
+        /// Mine for the reward addresses in FILE (one `ADDRESS [WEIGHT]` per
+        /// line) in turn, sharing out hashes by weight; the wallet still signs
+        #[arg(long, value_name = "FILE")]
+        addresses: Option<PathBuf>,
+
+        /// Hash TAG after the address so proofs name this machine, if the
+        /// contract accepts worker tags (default: mine.worker_tag)
+        #[arg(long, value_name = "TAG")]
+        worker_tag: Option<String>,
+
+        /// With --addresses, move on to the next address after this many seconds
+        #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_ADDRESS_SLICE_SECS, requires = "addresses")]
+        address_slice: u64,
+    },
+
+    /// Hand out work to `uhash worker`s and submit their proofs with this wallet
+    Controller {
+        /// Address to accept workers on
+        #[arg(long, default_value = "0.0.0.0:7070")]
+        listen: SocketAddr,
+
+        /// Stop after this many proofs have been submitted
+        #[arg(long)]
+        max_proofs: Option<u64>,
+
+        /// Transaction memo, e.g. a farm name (max 256 characters)
+        #[arg(long, default_value = "")]
+        memo: String,
+
+        /// Admit workers that send this token (repeatable)
+        #[arg(long, value_name = "TOKEN")]
+        worker_token: Vec<String>,
+
+        /// Admit workers that sign in with this address's key (repeatable)
+        #[arg(long, value_name = "ADDRESS")]
+        allow_worker: Vec<String>,
+
+        /// Messages per second each worker may send before it is disconnected
+        #[arg(long, default_value_t = DEFAULT_RATE_LIMIT)]
+        rate_limit: f64,
+
+        /// Collect up to N proofs and submit them in one transaction
+        /// (default: submit.batch_size)
+        #[arg(long, value_name = "N")]
+        batch_size: Option<usize>,
+    },
+
+    /// Hash for a `uhash controller` (needs no chain access, and no wallet
+    /// unless signing in with --key)
+    Worker {
+        /// Controller to work for
+        #[arg(long, value_name = "tcp://HOST:PORT")]
+        controller: String,
+
+        /// Number of threads to use (default: number of CPU cores)
+        #[arg(short, long)]
+        threads: Option<usize>,
+
+        /// Name shown on the controller (default: this worker's address)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Sign in to the controller with this token
+        #[arg(long, conflicts_with = "key")]
+        token: Option<String>,
+
+        /// Sign in to the controller with the wallet's key
+        #[arg(long)]
+        key: bool,
     },
 
     /// Submit a proof to the chain
@@ -161,716 +775,4927 @@ enum Commands {
         /// The timestamp when mining started (unix seconds)
         #[arg(long)]
         timestamp: u64,
-    },
 
-    /// Import a wallet from mnemonic phrase
-    ImportMnemonic {
-        /// The mnemonic phrase (will prompt if not provided)
+        /// The worker tag the proof was mined with
+        #[arg(long, value_name = "TAG")]
+        worker_tag: Option<String>,
+
+        /// Transaction memo, e.g. a worker name (max 256 characters)
+        #[arg(long, default_value = "")]
+        memo: String,
+
+        /// Sign and simulate the transaction instead of broadcasting it,
+        /// reporting the gas it would use and whether the contract accepts it
         #[arg(long)]
-        phrase: Option<String>,
+        dry_run: bool,
     },
 
-    /// Export the wallet mnemonic phrase
-    ExportMnemonic,
+    /// Sign a proof transaction offline and write it to a file for `broadcast`
+    SignProof {
+        /// The proof from `mine`'s hand-off, base64 or JSON (instead of
+        /// --hash, --nonce and --timestamp)
+        #[arg(long, conflicts_with_all = ["hash", "nonce", "timestamp"])]
+        proof: Option<String>,
 
-    /// Generate a new wallet
-    NewWallet,
+        /// The hash to submit
+        #[arg(long, required_unless_present = "proof")]
+        hash: Option<String>,
 
-    /// Show wallet address
-    Address,
+        /// The nonce used
+        #[arg(long, required_unless_present = "proof")]
+        nonce: Option<u64>,
 
-    /// Run performance benchmark
-    Benchmark {
-        /// Number of hashes to compute
-        #[arg(short, long, default_value = "100")]
-        count: u32,
-    },
+        /// The timestamp when mining started (unix seconds)
+        #[arg(long, required_unless_present = "proof")]
+        timestamp: Option<u64>,
+
+        /// The worker tag the proof was mined with (carried by --proof)
+        #[arg(long, value_name = "TAG", conflicts_with = "proof")]
+        worker_tag: Option<String>,
+
+        /// Transaction memo, e.g. a worker name (max 256 characters)
+        #[arg(long, default_value = "")]
+        memo: String,
+
+        /// Signer account number (fetched from the chain if omitted)
+        #[arg(long, requires = "sequence")]
+        account_number: Option<u64>,
+
+        /// Signer sequence (fetched from the chain if omitted)
+        #[arg(long, requires = "account_number")]
+        sequence: Option<u64>,
+
+        /// Write the signed transaction here (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Broadcast a transaction signed with `sign-proof` (no wallet needed)
+    Broadcast {
+        /// Signed transaction file (`-` for stdin)
+        file: PathBuf,
+    },
+
+    /// Look up a transaction by hash (height, result, gas, reward)
+    Tx {
+        /// Transaction hash
+        hash: String,
+    },
+
+    /// Import a wallet from mnemonic phrase
+    ImportMnemonic {
+        /// The mnemonic phrase; it stays in shell history, so prefer the
+        /// hidden prompt or --stdin (will prompt if not provided)
+        #[arg(long, conflicts_with = "stdin")]
+        phrase: Option<String>,
+
+        /// Read the phrase from standard input, e.g. piped from a password manager
+        #[arg(long)]
+        stdin: bool,
+
+        /// Mnemonic wordlist (default: auto-detect)
+        #[arg(long)]
+        language: Option<String>,
+
+        /// Print the imported phrase back so it can be checked
+        #[arg(long)]
+        confirm_display: bool,
+    },
+
+    /// Export the wallet mnemonic phrase
+    ExportMnemonic,
+
+    /// Store a watch-only wallet: mine to an address without holding its key
+    ImportAddress {
+        /// Account address to mine for
+        address: String,
+    },
+
+    /// Import a private key exported with `cyber keys export`
+    ImportKey {
+        /// Armored key file
+        file: PathBuf,
+
+        /// Passphrase protecting the key (will prompt if not provided)
+        #[arg(long)]
+        passphrase: Option<String>,
+    },
+
+    /// Export the private key for `cyber keys import` (or raw hex with --hex)
+    ExportKey {
+        /// Write the key to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Passphrase to encrypt the key with (will prompt if not provided)
+        #[arg(long, conflicts_with = "hex")]
+        passphrase: Option<String>,
+
+        /// Print the unencrypted secp256k1 private key as hex
+        #[arg(long)]
+        hex: bool,
+
+        /// Skip the confirmation prompt for --hex
+        #[arg(long, requires = "hex")]
+        yes: bool,
+    },
+
+    /// Generate a new wallet
+    NewWallet {
+        /// Number of mnemonic words (12, 15, 18, 21 or 24)
+        #[arg(long, default_value = "24")]
+        words: usize,
+
+        /// Mnemonic wordlist (english, japanese, spanish, chinese-simplified, ...)
+        #[arg(long, default_value = "english")]
+        language: String,
+    },
+
+    /// Show wallet address
+    Address {
+        /// Also print the address as a QR code in the terminal
+        #[arg(long)]
+        qr: bool,
+
+        /// Write the address QR code to a PNG file
+        #[arg(long, value_name = "PATH")]
+        png: Option<PathBuf>,
+    },
+
+    /// Sign a message with the wallet key (ADR-036) to prove address ownership
+    SignMessage {
+        /// The message to sign
+        message: String,
+    },
+
+    /// Verify an ADR-036 message signature
+    VerifyMessage {
+        /// The signed message
+        message: String,
+
+        /// Address that claims to have signed
+        #[arg(long)]
+        signer: String,
+
+        /// Base64 signature
+        #[arg(long)]
+        signature: String,
+
+        /// Base64 compressed secp256k1 public key
+        #[arg(long)]
+        pubkey: String,
+    },
+
+    /// Check that an address is a well-formed bech32 address with the expected prefix
+    ValidateAddress {
+        /// Address to check
+        address: String,
+
+        /// Expected bech32 prefix (default: the configured chain's)
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+
+    /// Run performance benchmark
+    Benchmark {
+        /// Number of hashes to compute
+        #[arg(short, long, default_value = "100")]
+        count: u32,
+
+        /// Upload device class, CPU model, thread count and hashrate to telemetry.url
+        #[arg(long)]
+        share: bool,
+
+        /// Device class to report: phone, laptop, desktop or server (default: detected)
+        #[arg(long, requires = "share")]
+        device_class: Option<DeviceClass>,
+
+        /// Also time each phase of the hash: scratchpad init, rounds, finalize
+        #[arg(long)]
+        detailed: bool,
+    },
+
+    /// Show the hashrates other users shared, by device (from telemetry.url)
+    Devices,
+
+    /// Estimate daily earnings and break-even from hashrate, difficulty and power cost
+    Profit {
+        /// Power the device draws while mining, in watts
+        #[arg(long)]
+        power_watts: f64,
+
+        /// Electricity price per kWh
+        #[arg(long, default_value = "0")]
+        electricity: f64,
+
+        /// Hashrate in H/s (default: measured with a short benchmark)
+        #[arg(long)]
+        hashrate: Option<f64>,
+
+        /// Threads to measure with (default: number of CPU cores)
+        #[arg(short, long, conflicts_with = "hashrate")]
+        threads: Option<usize>,
+
+        /// Difficulty override (default: fetched from contract)
+        #[arg(short, long)]
+        difficulty: Option<u32>,
+
+        /// Price of one LI, in the electricity currency (overrides the price feed)
+        #[arg(long)]
+        li_price: Option<f64>,
+
+        /// Price of one BOOT, in the electricity currency (overrides the price feed)
+        #[arg(long)]
+        boot_price: Option<f64>,
+
+        /// URL answering `{"li": <price>, "boot": <price>}`
+        #[arg(long)]
+        price_feed: Option<String>,
+    },
+
+    /// Model the contract's difficulty retarget against a network hashrate scenario
+    SimulateDifficulty {
+        /// Network hashrate phases in H/s, e.g. `2k,20k,5k` (suffixes k, M, G)
+        #[arg(long, default_value = "10k")]
+        hashrate: String,
+
+        /// Move linearly between the phases instead of stepping
+        #[arg(long)]
+        ramp: bool,
+
+        /// Retarget windows to simulate
+        #[arg(long, default_value = "144")]
+        windows: usize,
+
+        /// Accepted proofs per window the retarget aims for
+        #[arg(long, default_value = "10")]
+        target_proofs: u64,
+
+        /// Seconds between seed rotations (and retargets)
+        #[arg(long, default_value = "600")]
+        seed_interval: u64,
+
+        /// Difficulty of the first window in bits
+        #[arg(long, default_value = "8")]
+        difficulty: u32,
+
+        /// Difficulty never retargets below this
+        #[arg(long, default_value = "1")]
+        min_difficulty: u32,
+
+        /// Simulated runs to average over
+        #[arg(long, default_value = "100")]
+        runs: u32,
+
+        /// Random seed, for repeatable results
+        #[arg(long, default_value = "1")]
+        seed: u64,
+    },
 
     /// Query contract status (seed, difficulty, config)
     Status,
+
+    /// Re-verify every proof the contract accepted from a block height on
+    Audit {
+        /// First block height to replay
+        #[arg(long)]
+        from_height: u64,
+
+        /// Last block height to replay (default: up to the latest block)
+        #[arg(long)]
+        to_height: Option<u64>,
+    },
+
+    /// Estimate the network hashrate from recently accepted proofs
+    NetworkHashrate {
+        /// Recent proofs to sample
+        #[arg(long, default_value = "100")]
+        samples: usize,
+
+        /// Confidence level of the reported interval
+        #[arg(long, default_value = "0.95")]
+        confidence: f64,
+    },
+
+    /// Query proofs submitted and rewards earned by an address
+    Stats {
+        /// Address to look up
+        #[arg(required_unless_present_any = ["mine", "follow"], conflicts_with = "mine")]
+        address: Option<String>,
+
+        /// Look up the wallet's own address
+        #[arg(long)]
+        mine: bool,
+
+        /// Stream proofs accepted from every miner as they are committed
+        #[arg(long, conflicts_with_all = ["address", "mine"])]
+        follow: bool,
+    },
+
+    /// Summarize mining history recorded by `mine` (requires the `stats` feature)
+    Report {
+        /// Period to summarize, e.g. 12h, 7d or 4w
+        #[arg(long, default_value = "7d")]
+        last: String,
+
+        /// Write the period's rounds and proofs as datasets instead (csv or parquet)
+        #[arg(long)]
+        export: Option<String>,
+
+        /// Directory for `rounds.<ext>` and `proofs.<ext>`
+        #[arg(long, default_value = ".", requires = "export")]
+        output: PathBuf,
+    },
+
+    /// Health-check the configured endpoints (latency, block height)
+    Endpoints,
+
+    /// Request tokens from the chain's faucet (`chain.faucet_url`) for a new account
+    Faucet {
+        /// Address to fund (default: the wallet's)
+        address: Option<String>,
+
+        /// Seconds to wait for the account to appear on chain (0: don't wait)
+        #[arg(long, default_value_t = FAUCET_WAIT_SECS)]
+        wait: u64,
+    },
+
+    /// Serve a web dashboard of the miner running with this config directory
+    Dashboard {
+        /// Address to serve the dashboard on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+    },
+
+    /// Run a local mock chain and contract for testing the miner
+    Devnet {
+        /// Address to serve the mock LCD on
+        #[arg(long, default_value = "127.0.0.1:1317")]
+        listen: SocketAddr,
+
+        /// Starting difficulty in bits
+        #[arg(long, default_value = "8")]
+        difficulty: u32,
+
+        /// Seconds between seed rotations (and difficulty retargets)
+        #[arg(long, default_value = "600")]
+        seed_interval: u64,
+
+        /// Accepted proofs per seed interval the retarget aims for
+        #[arg(long, default_value = "10")]
+        target_proofs: u64,
+    },
+
+    /// Read and write persistent settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print a setting (e.g. `wallet.backend`)
+    Get { key: String },
+
+    /// Change a setting (e.g. `wallet.backend keychain`)
+    Set { key: String, value: String },
+
+    /// Print the config file path
+    Path,
 }
 
 fn main() {
     let cli = Cli::parse();
 
     let json = cli.json;
-    let rpc_config = build_rpc_config(cli.rpc.as_deref(), cli.contract.as_deref(), cli.fee);
+    if cli.rpc_debug {
+        init_rpc_debug().unwrap_or_else(|e| exit_with_error(e, json));
+    }
+    let config_path = cli.config.clone().unwrap_or_else(default_config_path);
+    let config = Config::load(&config_path).unwrap_or_else(|e| exit_with_error(e.into(), json));
+    let rpc_config = build_rpc_config(&cli, &config).unwrap_or_else(|e| exit_with_error(e, json));
+    let wallet_opts = WalletOpts {
+        path: cli.wallet,
+        backend: config.wallet.backend,
+        insecure_ok: cli.insecure_ok,
+        chain: config.chain.clone(),
+        account: cli.account,
+        json,
+    };
+
+    // Endpoints given on the command line are not reloaded from the config
+    let endpoints_pinned = !cli.rpc.is_empty() || !cli.lcd.is_empty();
 
     let result = match cli.command {
+        Commands::Init { yes } => {
+            cmd_init(yes, &config_path, config, &rpc_config, &wallet_opts, json)
+        }
         Commands::Mine {
             threads,
             difficulty,
             no_submit,
-        } => cmd_mine(threads, difficulty, no_submit, &rpc_config, json),
+            max_proofs,
+            memo,
+            api,
+            submit_via,
+            batch_size,
+            resume,
+            nonce_start,
+            nonce_range,
+            worker_index,
+            worker_count,
+            nonce_random,
+            when_idle,
+            addresses,
+            worker_tag,
+            address_slice,
+        } => {
+            let reload = ConfigReload::new(
+                &config_path,
+                threads.is_none(),
+                submit_via.is_none() && batch_size.is_none(),
+                !endpoints_pinned,
+            );
+            let mut submit = match submit_via {
+                Some(url) => SubmitConfig {
+                    sink: SinkKind::Relay,
+                    url,
+                    ..config.submit.clone()
+                },
+                None => config.submit.clone(),
+            };
+            submit.batch_size = batch_size.unwrap_or(submit.batch_size);
+            let threads = threads.or(Some(config.mine.threads).filter(|&t| t > 0));
+            let worker_tag = worker_tag.unwrap_or_else(|| config.mine.worker_tag.clone());
+            let nonces = mine_nonces(nonce_start, nonce_range, worker_index.zip(worker_count));
+            nonces.and_then(|nonces| {
+                let monitoring = mine_monitoring(&config, &config_path, api, json)?;
+                let addresses = addresses
+                    .map(|path| AddressBook::load(&path, &config.chain.bech32_prefix))
+                    .transpose()?;
+                let args = MineArgs {
+                    threads,
+                    difficulty,
+                    no_submit,
+                    max_proofs,
+                    resume,
+                    nonces,
+                    nonce_random,
+                    idle_after: when_idle.map(|minutes| Duration::from_secs(minutes * 60)),
+                    addresses,
+                    address_slice: Duration::from_secs(address_slice.max(1)),
+                    worker_tag: worker_tag.into_bytes(),
+                };
+                cmd_mine(
+                    args,
+                    &wallet_opts,
+                    &RpcConfig { memo, ..rpc_config },
+                    &submit,
+                    monitoring,
+                    reload,
+                    json,
+                )
+            })
+        }
+        Commands::Controller {
+            listen,
+            max_proofs,
+            memo,
+            worker_token,
+            allow_worker,
+            rate_limit,
+            batch_size,
+        } => cmd_controller(
+            listen,
+            max_proofs,
+            &worker_token,
+            &allow_worker,
+            rate_limit,
+            &wallet_opts,
+            &RpcConfig { memo, ..rpc_config },
+            &SubmitConfig {
+                batch_size: batch_size.unwrap_or(config.submit.batch_size),
+                ..config.submit.clone()
+            },
+            json,
+        ),
+        Commands::Worker {
+            controller,
+            threads,
+            name,
+            token,
+            key,
+        } => cmd_worker(
+            &controller,
+            name.as_deref(),
+            threads,
+            token,
+            key.then_some(&wallet_opts),
+            json,
+        ),
         Commands::Send {
             hash,
             nonce,
             timestamp,
-        } => cmd_send(&hash, nonce, timestamp, &rpc_config, json),
-        Commands::ImportMnemonic { phrase } => cmd_import_mnemonic(phrase, cli.wallet, json),
-        Commands::ExportMnemonic => cmd_export_mnemonic(cli.wallet, json),
-        Commands::NewWallet => cmd_new_wallet(cli.wallet, json),
-        Commands::Address => cmd_address(cli.wallet, json),
-        Commands::Benchmark { count } => cmd_benchmark(count, json),
+            worker_tag,
+            memo,
+            dry_run,
+        } => cmd_send(
+            &hash,
+            nonce,
+            timestamp,
+            worker_tag.as_deref().unwrap_or_default().as_bytes(),
+            dry_run,
+            &wallet_opts,
+            &RpcConfig { memo, ..rpc_config },
+            json,
+        ),
+        Commands::SignProof {
+            proof,
+            hash,
+            nonce,
+            timestamp,
+            worker_tag,
+            memo,
+            account_number,
+            sequence,
+            output,
+        } => cmd_sign_proof(
+            proof.as_deref(),
+            hash.zip(nonce).zip(timestamp),
+            worker_tag.as_deref().unwrap_or_default().as_bytes(),
+            account_number.zip(sequence),
+            output.as_deref(),
+            &wallet_opts,
+            &RpcConfig { memo, ..rpc_config },
+            json,
+        ),
+        Commands::Broadcast { file } => cmd_broadcast(&file, &rpc_config, json),
+        Commands::Tx { hash } => cmd_tx(&hash, &rpc_config, json),
+        Commands::ImportMnemonic {
+            phrase,
+            stdin,
+            language,
+            confirm_display,
+        } => cmd_import_mnemonic(
+            phrase,
+            stdin,
+            language.as_deref(),
+            confirm_display,
+            &wallet_opts,
+            json,
+        ),
+        Commands::ExportMnemonic => cmd_export_mnemonic(&wallet_opts, json),
+        Commands::ImportAddress { address } => cmd_import_address(&address, &wallet_opts, json),
+        Commands::ImportKey { file, passphrase } => {
+            cmd_import_key(&file, passphrase, &wallet_opts, json)
+        }
+        Commands::ExportKey {
+            output,
+            passphrase,
+            hex,
+            yes,
+        } => {
+            if hex {
+                cmd_export_key_hex(output.as_deref(), yes, &wallet_opts, json)
+            } else {
+                cmd_export_key(output.as_deref(), passphrase, &wallet_opts, json)
+            }
+        }
+        Commands::NewWallet { words, language } => {
+            cmd_new_wallet(words, &language, &wallet_opts, json)
+        }
+        Commands::Address { qr, png } => cmd_address(qr, png.as_deref(), &wallet_opts, json),
+        Commands::SignMessage { message } => cmd_sign_message(&message, &wallet_opts, json),
+        Commands::VerifyMessage {
+            message,
+            signer,
+            signature,
+            pubkey,
+        } => cmd_verify_message(&message, &signer, signature, pubkey, json),
+        Commands::ValidateAddress { address, prefix } => {
+            let prefix = prefix.unwrap_or_else(|| config.chain.bech32_prefix.clone());
+            cmd_validate_address(&address, &prefix, json)
+        }
+        Commands::Benchmark {
+            count,
+            share,
+            device_class,
+            detailed,
+        } => {
+            let share = share.then(|| device_class.unwrap_or_else(DeviceClass::detect));
+            cmd_benchmark(count, share, detailed, &config.telemetry.url, json)
+        }
+        Commands::Devices => cmd_devices(&config.telemetry.url, json),
+        Commands::Profit {
+            power_watts,
+            electricity,
+            hashrate,
+            threads,
+            difficulty,
+            li_price,
+            boot_price,
+            price_feed,
+        } => cmd_profit(
+            ProfitArgs {
+                power_watts,
+                electricity,
+                hashrate,
+                threads,
+                difficulty,
+                prices: Prices {
+                    li_price,
+                    boot_price,
+                },
+                price_feed,
+            },
+            &rpc_config,
+            json,
+        ),
+        Commands::SimulateDifficulty {
+            hashrate,
+            ramp,
+            windows,
+            target_proofs,
+            seed_interval,
+            difficulty,
+            min_difficulty,
+            runs,
+            seed,
+        } => Scenario::parse(&hashrate, windows, ramp)
+            .map_err(anyhow::Error::from)
+            .and_then(|scenario| {
+                let params = SimParams {
+                    start_difficulty: difficulty,
+                    min_difficulty,
+                    target_proofs_per_window: target_proofs,
+                    window_secs: seed_interval,
+                    runs,
+                    seed,
+                };
+                cmd_simulate_difficulty(params, scenario, json)
+            }),
         Commands::Status => cmd_status(&rpc_config, json),
+        Commands::Audit {
+            from_height,
+            to_height,
+        } => cmd_audit(&rpc_config, from_height, to_height, json),
+        Commands::NetworkHashrate {
+            samples,
+            confidence,
+        } => cmd_network_hashrate(&rpc_config, samples, confidence, json),
+        Commands::Stats { follow: true, .. } => cmd_follow_proofs(&rpc_config, json),
+        Commands::Stats { address, .. } => cmd_stats(address, &wallet_opts, &rpc_config, json),
+        Commands::Report {
+            last,
+            export,
+            output,
+        } => cmd_report(&last, export.as_deref(), &output, &config.stats, json),
+        Commands::Endpoints => cmd_endpoints(&rpc_config, json),
+        Commands::Faucet { address, wait } => {
+            cmd_faucet(address, wait, &wallet_opts, &rpc_config, json)
+        }
+        Commands::Dashboard { listen } => {
+            cmd_dashboard(listen, &config_path.with_file_name(STATUS_FILE_NAME), json)
+        }
+        Commands::Devnet {
+            listen,
+            difficulty,
+            seed_interval,
+            target_proofs,
+        } => {
+            let params = DevnetParams {
+                chain_id: rpc_config.chain_id.clone(),
+                contract_address: rpc_config.contract_address.clone(),
+                difficulty,
+                seed_interval: Duration::from_secs(seed_interval),
+                target_proofs_per_window: target_proofs,
+                ..DevnetParams::default()
+            };
+            cmd_devnet(listen, params, json)
+        }
+        Commands::Config { action } => cmd_config(action, &config_path, config, json),
+    };
+
+    if let Err(e) = result {
+        if let Some(Interrupted(signal)) = e.downcast_ref() {
+            std::process::exit(128 + signal);
+        }
+        exit_with_error(e, json);
+    }
+}
+
+/// Print request logs from the rpc module to stderr
+#[cfg(feature = "debug")]
+fn init_rpc_debug() -> anyhow::Result<()> {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let filter = tracing_subscriber::filter::Targets::new()
+        .with_target(uhash::rpc::LOG_TARGET, tracing::Level::DEBUG);
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(filter)
+        .try_init()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "debug"))]
+fn init_rpc_debug() -> anyhow::Result<()> {
+    anyhow::bail!("RPC debug logging is not available: rebuild with `--features debug`")
+}
+
+/// Report an error (as JSON in `--json` mode) and exit with status 1
+fn exit_with_error(e: anyhow::Error, json: bool) -> ! {
+    if json {
+        let err = JsonError {
+            error: e.to_string(),
+        };
+        println!("{}", serde_json::to_string(&err).unwrap());
+    } else {
+        eprintln!("Error: {}", e);
+    }
+    std::process::exit(1);
+}
+
+/// LCD endpoints to use: the `lcd` list, else one derived from each RPC endpoint
+fn lcd_endpoints_for(rpc: &[String], lcd: &[String]) -> Vec<String> {
+    if lcd.is_empty() {
+        rpc.iter().map(|url| lcd_url_for_rpc(url)).collect()
+    } else {
+        lcd.to_vec()
+    }
+}
+
+/// Build RPC config from CLI args
+fn build_rpc_config(cli: &Cli, settings: &Config) -> anyhow::Result<RpcConfig> {
+    let mut config = RpcConfig::from_profile(&settings.chain);
+    let prefix = &settings.chain.bech32_prefix;
+    // `--rpc`/`--lcd` together override the config file's endpoint lists
+    let (rpc_endpoints, lcd_endpoints) = if cli.rpc.is_empty() && cli.lcd.is_empty() {
+        (&settings.network.rpc, &settings.network.lcd)
+    } else {
+        (&cli.rpc, &cli.lcd)
     };
+    for url in rpc_endpoints.iter().chain(lcd_endpoints) {
+        validate_endpoint(url)?;
+    }
+    if let Some(primary) = rpc_endpoints.first() {
+        config.rpc_url = primary.to_string();
+    }
+    let lcd_endpoints = lcd_endpoints_for(rpc_endpoints, lcd_endpoints);
+    if let Some((primary, fallbacks)) = lcd_endpoints.split_first() {
+        config.lcd_url = primary.to_string();
+        config.fallback_lcd_urls = fallbacks.to_vec();
+    }
+    if let Some(addr) = &cli.contract {
+        validate_address_with_prefix(addr, prefix)?;
+        config.contract_address = addr.to_string();
+    }
+    config.transport = settings.network.transport;
+    config.grpc_url = settings.network.grpc.clone();
+    if config.transport == Transport::Grpc {
+        if !config.transport.is_supported() {
+            anyhow::bail!("gRPC transport is not available: rebuild with `--features grpc`");
+        }
+        if config.grpc_url.is_empty() {
+            anyhow::bail!("gRPC transport needs an endpoint: uhash config set network.grpc <URL>");
+        }
+    }
+    config.proxy = cli
+        .proxy
+        .clone()
+        .or_else(|| Some(settings.network.proxy.clone()).filter(|p| !p.is_empty()));
+    config.ca_certs = settings.network.ca_certs.clone();
+    config.custom_roots_only = settings.network.custom_roots_only;
+    config.connect_timeout_secs = settings.network.connect_timeout;
+    config.request_timeout_secs = cli.timeout.unwrap_or(settings.network.request_timeout);
+    config.pool_idle_timeout_secs = settings.network.pool_idle_timeout;
+    config.tcp_keepalive_secs = settings.network.tcp_keepalive;
+    config.cache_ttl_secs = settings.network.cache_ttl;
+    config.fee_amount = cli.fee;
+    config.gas_limit = parse_gas(&cli.gas)?;
+    config.gas_adjustment = cli.gas_adjustment;
+    config.gas_price = cli
+        .gas_prices
+        .as_deref()
+        .map(|price| parse_gas_price(price, &config.fee_denom))
+        .transpose()?;
+    config.broadcast_mode = cli.broadcast_mode;
+    config.confirm_timeout_secs = cli.confirm_timeout;
+    config.finality_depth = cli.finality_depth;
+    for addr in [&cli.fee_granter, &cli.fee_payer].into_iter().flatten() {
+        validate_address_with_prefix(addr, prefix)?;
+    }
+    config.fee_granter = cli.fee_granter.clone();
+    config.fee_payer = cli.fee_payer.clone();
+    Ok(config)
+}
+
+/// RPC client that reports backoff retries on stderr (outside `--json` mode)
+fn rpc_client(rpc_config: &RpcConfig, json: bool) -> anyhow::Result<RpcClient> {
+    let client = RpcClient::try_with_config(rpc_config.clone())?;
+    if json {
+        return Ok(client);
+    }
+    Ok(client.on_warning(|warning| eprintln!("\nWarning: {}", warning)))
+}
+
+/// Wallet location and loading policy from global flags and config
+struct WalletOpts {
+    /// `--wallet` override for the file and encrypted backends
+    path: Option<PathBuf>,
+    backend: WalletBackend,
+    /// `--insecure-ok`: warn instead of failing on world-readable wallet files
+    insecure_ok: bool,
+    /// Chain whose coin type and address prefix the wallet is derived for
+    chain: ChainProfile,
+    /// `--account`: account index derived from the mnemonic, overriding
+    /// the one the wallet was saved for
+    account: Option<u32>,
+    /// Passphrases cannot be prompted for in JSON mode
+    json: bool,
+}
+
+impl WalletOpts {
+    fn file_path(&self) -> PathBuf {
+        match (&self.path, self.backend) {
+            (Some(path), _) => path.clone(),
+            (None, WalletBackend::Encrypted) => default_wallet_path().with_file_name("wallet.enc"),
+            (None, _) => default_wallet_path(),
+        }
+    }
+
+    /// The configured backend, prompting for the passphrase of an encrypted wallet
+    fn wallet_store(&self) -> anyhow::Result<Box<dyn WalletStore>> {
+        Ok(match self.backend {
+            WalletBackend::File => Box::new(FileStore::new(self.file_path())),
+            WalletBackend::Keychain => Box::new(KeychainStore),
+            WalletBackend::Encrypted => Box::new(EncryptedFileStore::new(
+                self.file_path(),
+                wallet_passphrase(self.json)?,
+            )),
+        })
+    }
+
+    /// Load the wallet from the configured backend
+    fn load(&self) -> anyhow::Result<Wallet> {
+        let wallet = match self.wallet_store()?.load() {
+            Err(e @ WalletError::InsecurePermissions { .. }) if self.insecure_ok => {
+                eprintln!("Warning: {}", e);
+                FileStore::new(self.file_path())
+                    .allow_insecure(true)
+                    .load()?
+            }
+            result => result?,
+        };
+        let wallet = wallet.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No wallet found. Create one with 'uhash new-wallet' or 'uhash import-mnemonic'"
+            )
+        })?;
+        self.derive(wallet)
+    }
+
+    /// Derive a loaded or new wallet for the configured chain and account
+    fn derive(&self, wallet: Wallet) -> anyhow::Result<Wallet> {
+        let wallet = wallet.for_chain(&self.chain)?;
+        match self.account {
+            Some(account) if account != wallet.account() => Ok(wallet.derive_account(account)?),
+            _ => Ok(wallet),
+        }
+    }
+
+    /// Address of the stored wallet, read from the encrypted file's header
+    /// when it was saved for this chain, so no passphrase is needed
+    fn address(&self) -> anyhow::Result<String> {
+        if self.backend == WalletBackend::Encrypted {
+            if let Some(header) = read_header(&self.file_path())? {
+                let prefix = format!("{}1", self.chain.bech32_prefix);
+                if header.backend == WalletBackend::Encrypted
+                    && self.account.is_none_or(|account| account == header.account)
+                    && header.address.starts_with(&prefix)
+                {
+                    return Ok(header.address);
+                }
+            }
+        }
+        Ok(self.load()?.address_str())
+    }
+
+    /// Check whether a wallet is already stored in the configured backend
+    fn exists(&self) -> anyhow::Result<bool> {
+        Ok(match self.backend {
+            // No passphrase is needed to see that the file is there
+            WalletBackend::File | WalletBackend::Encrypted => self.file_path().exists(),
+            WalletBackend::Keychain => KeychainStore.exists()?,
+        })
+    }
+
+    /// Persist the wallet to the configured backend, returning a description of where it went
+    fn store(&self, wallet: &Wallet) -> anyhow::Result<String> {
+        let store = self.wallet_store()?;
+        store.save(wallet)?;
+        Ok(store.location())
+    }
+}
+
+/// Environment variable holding the passphrase of an encrypted wallet
+const WALLET_PASSPHRASE_ENV: &str = "UHASH_WALLET_PASSPHRASE";
+
+/// Environment variable holding the management API token
+const API_TOKEN_ENV: &str = "UHASH_API_TOKEN";
+
+/// Passphrase of an encrypted wallet, from the environment or a prompt
+fn wallet_passphrase(json: bool) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var(WALLET_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    if json {
+        anyhow::bail!(
+            "{} must be set to unlock an encrypted wallet when using --json",
+            WALLET_PASSPHRASE_ENV
+        );
+    }
+    read_passphrase(None, "Enter the wallet passphrase:", false)
+}
+
+/// Records rounds and proofs in the local stats database while mining
+///
+/// Does nothing unless built with the `stats` feature and `stats.enabled` is
+/// set. A database that cannot be opened or written is reported once and
+/// mining carries on without it.
+struct StatsRecorder {
+    #[cfg(feature = "stats")]
+    db: Option<uhash::stats::StatsDb>,
+    #[cfg(feature = "stats")]
+    json: bool,
+}
+
+#[cfg(feature = "stats")]
+impl StatsRecorder {
+    fn open(settings: &StatsConfig, json: bool) -> Self {
+        let mut recorder = Self { db: None, json };
+        if settings.enabled {
+            recorder.db = recorder.check(uhash::stats::StatsDb::open(&settings.db_path()));
+        }
+        recorder
+    }
+
+    fn round(&mut self, round: &RoundRecord) -> Option<i64> {
+        let result = self.db.as_ref()?.record_round(round);
+        self.check(result)
+    }
+
+    fn proof(&mut self, proof: &ProofRecord) -> Option<i64> {
+        let result = self.db.as_ref()?.record_proof(proof);
+        self.check(result)
+    }
+
+    fn submission(&mut self, proof_id: Option<i64>, tx_hash: &str, reward: Option<&str>) {
+        if let (Some(db), Some(id)) = (&self.db, proof_id) {
+            let result = db.record_submission(id, tx_hash, reward);
+            self.check(result);
+        }
+    }
+
+    /// Keep the value of a database call, or warn and stop recording
+    fn check<T>(&mut self, result: Result<T, uhash::stats::StatsError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                if !self.json {
+                    eprintln!("Warning: Not recording mining stats ({})", e);
+                }
+                self.db = None;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+impl StatsRecorder {
+    fn open(_settings: &StatsConfig, _json: bool) -> Self {
+        Self {}
+    }
+
+    fn round(&mut self, _round: &RoundRecord) -> Option<i64> {
+        None
+    }
+
+    fn proof(&mut self, _proof: &ProofRecord) -> Option<i64> {
+        None
+    }
+
+    fn submission(&mut self, _proof_id: Option<i64>, _tx_hash: &str, _reward: Option<&str>) {}
+}
+
+/// Where `mine` reports progress besides stdout
+struct Monitoring {
+    stats: StatsRecorder,
+    status_path: PathBuf,
+    /// Where proofs go when `mine` is stopped before sending them
+    pending: PendingQueue,
+    /// Where an interrupted round is saved for `--resume`
+    round_path: PathBuf,
+    /// Management API address and bearer token
+    api: Option<(SocketAddr, String)>,
+}
+
+/// Resolve the stats database, status file and management API for `mine`
+fn mine_monitoring(
+    config: &Config,
+    config_path: &Path,
+    api: Option<SocketAddr>,
+    json: bool,
+) -> anyhow::Result<Monitoring> {
+    let listen =
+        match api {
+            Some(addr) => Some(addr),
+            None if config.api.listen.is_empty() => None,
+            None => Some(config.api.listen.parse().map_err(|e| {
+                anyhow::anyhow!("Invalid api.listen '{}': {}", config.api.listen, e)
+            })?),
+        };
+    let api = match listen {
+        Some(addr) => {
+            let token = std::env::var(API_TOKEN_ENV)
+                .ok()
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| config.api.token.clone());
+            if token.is_empty() {
+                anyhow::bail!(
+                    "The management API needs a token: set {} or `uhash config set api.token`",
+                    API_TOKEN_ENV
+                );
+            }
+            Some((addr, token))
+        }
+        None => None,
+    };
+    Ok(Monitoring {
+        stats: StatsRecorder::open(&config.stats, json),
+        status_path: config_path.with_file_name(STATUS_FILE_NAME),
+        pending: PendingQueue::new(config_path.with_file_name(PENDING_FILE_NAME)),
+        round_path: config_path.with_file_name(ROUND_STATE_FILE_NAME),
+        api,
+    })
+}
+
+/// `mine` arguments
+struct MineArgs {
+    threads: Option<usize>,
+    /// Mine at this difficulty instead of the contract's
+    difficulty: Option<u32>,
+    no_submit: bool,
+    max_proofs: Option<u64>,
+    resume: bool,
+    nonces: NonceRange,
+    nonce_random: bool,
+    /// Mine only after the machine was idle this long
+    idle_after: Option<Duration>,
+    addresses: Option<AddressBook>,
+    address_slice: Duration,
+    worker_tag: Vec<u8>,
+}
+
+fn cmd_mine(
+    args: MineArgs,
+    wallet_opts: &WalletOpts,
+    rpc_config: &RpcConfig,
+    submit: &SubmitConfig,
+    monitoring: Monitoring,
+    reload: ConfigReload,
+    json: bool,
+) -> anyhow::Result<()> {
+    let MineArgs {
+        threads,
+        difficulty: difficulty_override,
+        no_submit,
+        max_proofs,
+        resume,
+        nonces,
+        nonce_random,
+        idle_after,
+        addresses,
+        address_slice,
+        worker_tag,
+    } = args;
+    let worker_tag = worker_tag.as_slice();
+    let Monitoring {
+        stats,
+        status_path,
+        pending,
+        round_path,
+        api,
+    } = monitoring;
+    // Fail before mining rather than on the first submission
+    if rpc_config.memo.chars().count() > MAX_MEMO_LEN {
+        anyhow::bail!("Memo is longer than {} characters", MAX_MEMO_LEN);
+    }
+    if idle_after.is_some() {
+        idle::probe().map_err(|e| anyhow::anyhow!("Could not detect idleness: {}", e))?;
+    }
+
+    let wallet = wallet_opts.load()?;
+    // Rounds mine for the wallet's address, or take turns at an address book's
+    let shared = addresses.is_some();
+    let book = addresses.unwrap_or_else(|| AddressBook::single(wallet.address_str()));
+    let address = book.next().to_string();
+
+    // Create RPC client
+    let client = rpc_client(rpc_config, json)?;
+
+    let rt = tokio::runtime::Runtime::new()?;
+
+    // Check the endpoint, chain, contract and paying account up front,
+    // starting on the fastest healthy endpoint
+    let signs_proofs = !no_submit && submit.sink == SinkKind::Contract;
+    let wallet_address = wallet.address_str();
+    // A new account funded by the chain's faucet can sign its own proofs
+    if signs_proofs
+        && !rpc_config.faucet_url.is_empty()
+        && matches!(rt.block_on(client.get_account(&wallet_address)), Ok(None))
+    {
+        rt.block_on(request_faucet(
+            &client,
+            &wallet_address,
+            FAUCET_WAIT_SECS,
+            json,
+        ))?;
+    }
+    let report = rt.block_on(preflight(
+        &client,
+        signs_proofs.then_some(wallet_address.as_str()),
+    ));
+    if !report.is_ok() && !no_submit {
+        let failures = report
+            .failures()
+            .map(|check| format!("\n  - {}", check.detail))
+            .collect::<String>();
+        anyhow::bail!("Preflight checks failed:{}", failures);
+    }
+    if !json {
+        for check in &report.checks {
+            if check.status != CheckStatus::Passed {
+                eprintln!("Warning: {}", check.detail);
+            }
+        }
+        if report.endpoints.len() > 1 && report.endpoints.iter().any(|s| s.is_healthy()) {
+            println!("Using endpoint: {}", client.active_endpoint());
+        }
+    }
+
+    // Detect the contract's response shapes before relying on them
+    let contract_schema = match rt.block_on(client.contract_schema()) {
+        Ok(schema) => Some(schema),
+        Err(e) => {
+            if !json {
+                eprintln!("Warning: Could not detect contract schema ({})", e);
+            }
+            None
+        }
+    };
+
+    // Fetch difficulty from contract (unless overridden)
+    let difficulty = if let Some(d) = difficulty_override {
+        if !json {
+            println!("Using difficulty override: {} bits", d);
+        }
+        d
+    } else {
+        if !json {
+            println!("Fetching difficulty from contract...");
+        }
+        match rt.block_on(client.get_difficulty()) {
+            Ok(d) => {
+                if !json {
+                    println!("Contract difficulty: {} bits", d);
+                }
+                d
+            }
+            Err(e) => {
+                if !json {
+                    eprintln!(
+                        "Warning: Could not fetch difficulty ({}), using default 16",
+                        e
+                    );
+                }
+                16
+            }
+        }
+    };
+
+    // Query mining seed from contract
+    if !json {
+        println!("Fetching seed from contract...");
+    }
+    let seed_info = rt.block_on(client.get_seed()).unwrap_or_else(|e| {
+        if !json {
+            eprintln!("Warning: Could not fetch seed ({}), using zeros", e);
+        }
+        SeedInfo::new([0u8; 32], 0)
+    });
+
+    let num_threads = fit_mining_threads(threads.unwrap_or_else(num_cpus::get), 0, json)?;
+
+    // Rounds are restarted with a fresh timestamp before their proofs get too old
+    let contract_config = rt.block_on(client.get_config());
+    let max_proof_age = contract_config
+        .as_ref()
+        .ok()
+        .map(|config| config.max_proof_age);
+    let schedule = Schedule::new(max_proof_age);
+
+    // A tag the contract does not take would waste every proof
+    if !worker_tag.is_empty() {
+        let config = contract_config.map_err(|e| {
+            anyhow::anyhow!("Could not check the worker tag against the contract: {}", e)
+        })?;
+        check_worker_tag(worker_tag, config.max_worker_tag_len)?;
+        if wallet.is_watch_only() && submit.sink == SinkKind::Contract && !no_submit {
+            anyhow::bail!("Proofs handed off for offline signing cannot carry a worker tag");
+        }
+    }
+
+    // A saved round is used once: it is stale as soon as this run moves on
+    let resumed_round = if resume {
+        resumable_round(
+            &round_path,
+            &address,
+            &seed_info,
+            difficulty,
+            nonces,
+            max_proof_age,
+            |note| {
+                if json {
+                    return;
+                }
+                match note {
+                    ResumeNote::Unreadable { .. } | ResumeNote::NotRemoved { .. } => {
+                        eprintln!("Warning: {}", note)
+                    }
+                    note => eprintln!("{}, starting a fresh one", note),
+                }
+            },
+        )
+    } else {
+        None
+    };
+
+    let status = Arc::new(std::sync::Mutex::new(MinerStatus {
+        pid: std::process::id(),
+        started_at: unix_now(),
+        address: address.clone(),
+        contract: rpc_config.contract_address.clone(),
+        difficulty,
+        threads: num_threads,
+        ..MinerStatus::default()
+    }));
+
+    // Remote control through the management API
+    let control = Arc::new(MinerControl::new(num_threads));
+    let api_url = match api {
+        Some((addr, token)) => {
+            let api = Api::new(Arc::clone(&control), Arc::clone(&status), &token);
+            let _guard = rt.enter();
+            Some(format!("http://{}", api.spawn(addr)?))
+        }
+        None => None,
+    };
+    if json {
+        let started = JsonMineStarted {
+            event: "mine_started",
+            contract: rpc_config.contract_address.clone(),
+            address: wallet.address_str(),
+            addresses: if shared {
+                book.entries().iter().map(|e| e.address.clone()).collect()
+            } else {
+                Vec::new()
+            },
+            difficulty,
+            threads: num_threads,
+            seed: hex::encode(seed_info.seed),
+            contract_schema,
+            auto_submit: !no_submit,
+            watch_only: wallet.is_watch_only(),
+            account: wallet.account(),
+            worker_tag: (!worker_tag.is_empty())
+                .then(|| String::from_utf8_lossy(worker_tag).into_owned()),
+            api: api_url.clone(),
+            resumed: resumed_round.as_ref().map(|state| state.timestamp),
+            preflight: report.checks.clone(),
+        };
+        println!("{}", serde_json::to_string(&started)?);
+    } else {
+        println!("\n=== UniversalHash Miner ===");
+        println!("Contract: {}", rpc_config.contract_address);
+        if let Some(schema) = contract_schema {
+            println!("Contract schema: {}", schema);
+        }
+        println!("Address:  {}", wallet.address_str());
+        if shared {
+            for entry in book.entries() {
+                println!("Mining for: {} (weight {})", entry.address, entry.weight);
+            }
+        }
+        if wallet.account() > 0 {
+            println!("Account:  {}", wallet.account());
+        }
+        println!("Difficulty: {} bits", difficulty);
+        println!("Threads: {}", num_threads);
+        println!("Seed: {}", hex::encode(seed_info.seed));
+        if let Some(expires_in) = seed_info.expires_in(unix_now()) {
+            println!("Seed rotates in: {}s", expires_in.as_secs());
+        }
+        if !worker_tag.is_empty() {
+            println!("Worker tag: {}", String::from_utf8_lossy(worker_tag));
+        }
+        println!("Auto-submit: {}", if no_submit { "off" } else { "on" });
+        if wallet.is_watch_only() && !no_submit {
+            println!("Watch-only: proofs are relayed or handed off for offline signing");
+        }
+        if let Some(url) = &api_url {
+            println!("Management API: {}", url);
+        }
+        if let Some(state) = &resumed_round {
+            println!(
+                "Resuming round from {} at nonce {}",
+                state.timestamp,
+                state.cursors.iter().min().unwrap_or(&0)
+            );
+        }
+        println!("===========================\n");
+    }
+
+    // Watch-only wallets have no signing key: the contract sink hands proofs off instead
+    let sink = sink::from_settings(
+        submit.sink,
+        &submit.url,
+        &submit.path,
+        &client,
+        proof_signing_key(&wallet)?,
+    )?;
+    let submitter = Submitter::new(
+        &rt,
+        &client,
+        sink,
+        Duration::from_secs(rpc_config.confirm_timeout_secs),
+        rpc_config.finality_depth,
+        max_proof_age,
+    );
+    let config = SessionConfig {
+        difficulty,
+        nonces,
+        nonce_random,
+        worker_tag: worker_tag.to_vec(),
+        address_slice,
+        no_submit,
+        max_proofs,
+        batch_size: submit.batch_size,
+        schedule,
+        pending: pending.clone(),
+        round_path,
+        status_path,
+    };
+    let mut session = Session::new(
+        config,
+        &rt,
+        submitter,
+        Arc::clone(&control),
+        status,
+        book,
+        seed_info,
+    );
+    if let Some(state) = resumed_round {
+        session = session.resume(state);
+    }
+
+    // From here on a signal stops mining cleanly instead of killing the process
+    let shutdown = Shutdown::install(&rt)?;
+    reload.watch_hangup(&rt)?;
+    if let Some(idle_after) = idle_after {
+        watch_idle(Arc::clone(&control), idle_after, json)?;
+    }
+
+    let mut output = MineOutput {
+        rpc_config,
+        wallet: &wallet,
+        control,
+        reload,
+        submit: submit.clone(),
+        stats,
+        shutdown: shutdown.clone(),
+        no_submit,
+        worker_tag,
+        difficulty,
+        round_id: None,
+        submitting: None,
+        last_hashrate: Instant::now(),
+        error: None,
+        json,
+    };
+    let summary = session.run(&mut output)?;
+    if let Some(e) = output.error {
+        return Err(e);
+    }
+
+    let summary = JsonMineStopped {
+        event: "mine_stopped",
+        reason: match summary.end {
+            SessionEnd::Finished => "stopped",
+            SessionEnd::Stopped => "signal",
+            SessionEnd::NoSubmit => "no_submit",
+            SessionEnd::MaxProofs => "max_proofs",
+        },
+        signal: shutdown.signal().map(signal_name),
+        proofs_found: summary.proofs_found,
+        proofs_submitted: summary.proofs_submitted,
+        proofs_unsigned: summary.proofs_unsigned,
+        proofs_pending: summary.proofs_pending,
+        round_saved: summary.round_saved,
+        hashes: summary.hashes,
+        elapsed_s: summary.elapsed.as_secs_f64(),
+        addresses: if shared {
+            summary.addresses
+        } else {
+            Vec::new()
+        },
+    };
+    if json {
+        println!("{}", serde_json::to_string(&summary)?);
+    } else {
+        println!("\n\n=== Mining stopped ===");
+        if let Some(signal) = summary.signal {
+            println!("Stopped by:  {}", signal);
+        }
+        println!("Mined for:   {:.0}s", summary.elapsed_s);
+        println!("Hashes:      {}", summary.hashes);
+        println!(
+            "Proofs:      {} found, {} sent",
+            summary.proofs_found, summary.proofs_submitted
+        );
+        if summary.proofs_unsigned > 0 {
+            println!(
+                "Unsigned:    {} (sign them with sign-proof)",
+                summary.proofs_unsigned
+            );
+        }
+        if summary.proofs_pending > 0 {
+            println!(
+                "Pending:     {} saved to {}, submitted on the next start",
+                summary.proofs_pending,
+                pending.path().display()
+            );
+        }
+        if summary.round_saved {
+            println!("Round saved: continue it with `uhash mine --resume`");
+        }
+        for entry in &summary.addresses {
+            println!(
+                "{}: {} hashes in {} rounds, {} proofs found, {} sent",
+                entry.address,
+                entry.hashes,
+                entry.rounds,
+                entry.proofs_found,
+                entry.proofs_submitted
+            );
+        }
+    }
+
+    match shutdown.signal() {
+        Some(signal) => Err(Interrupted(signal).into()),
+        None => Ok(()),
+    }
+}
+
+/// Prints what a mining session does and records it in the stats database
+struct MineOutput<'w> {
+    rpc_config: &'w RpcConfig,
+    wallet: &'w Wallet,
+    control: Arc<MinerControl>,
+    reload: ConfigReload,
+    /// Submit settings in effect, for config reloads to compare against
+    submit: SubmitConfig,
+    stats: StatsRecorder,
+    shutdown: Shutdown,
+    no_submit: bool,
+    worker_tag: &'w [u8],
+    difficulty: u32,
+    /// Database id of the last round
+    round_id: Option<i64>,
+    /// Hash and database id of the proof being submitted
+    submitting: Option<(String, Option<i64>)>,
+    last_hashrate: Instant,
+    /// The first event that could not be printed
+    error: Option<anyhow::Error>,
+    json: bool,
+}
+
+impl<'a> SessionHost<'a> for MineOutput<'_> {
+    fn event(&mut self, event: SessionEvent) {
+        if let Err(e) = self.report(event) {
+            self.error.get_or_insert(e);
+        }
+    }
+
+    fn stop_requested(&self) -> bool {
+        self.shutdown.requested()
+    }
+
+    fn poll(&mut self, submitter: &mut Submitter<'a>) {
+        if let Some(config) = self.reload.poll() {
+            let result = config.map_err(anyhow::Error::from).and_then(|config| {
+                self.reload.apply(
+                    &config,
+                    &self.control,
+                    &mut self.submit,
+                    submitter,
+                    self.wallet,
+                )
+            });
+            if let Err(e) = report_config_reload(result, self.json) {
+                self.error.get_or_insert(e);
+            }
+        }
+    }
+}
+
+impl MineOutput<'_> {
+    fn report(&mut self, event: SessionEvent) -> anyhow::Result<()> {
+        let json = self.json;
+        match event {
+            SessionEvent::Paused { held } => {
+                if !json && held {
+                    println!("\nWaiting until the machine is idle");
+                } else if !json {
+                    println!("\nPaused through the management API");
+                }
+            }
+            SessionEvent::Resumed => {
+                if !json {
+                    println!("Resumed\n");
+                }
+            }
+            SessionEvent::ThreadsCapped {
+                threads,
+                requested,
+                available,
+            } => report_threads_capped(threads, requested, available, json),
+            SessionEvent::RoundStarted {
+                address,
+                seed,
+                difficulty,
+                timestamp,
+                threads,
+            } => {
+                self.last_hashrate = Instant::now();
+                if json {
+                    let event = JsonRoundStarted {
+                        event: "round_started",
+                        address,
+                        seed: hex::encode(seed),
+                        difficulty,
+                        timestamp,
+                        threads,
+                    };
+                    println!("{}", serde_json::to_string(&event)?);
+                }
+            }
+            SessionEvent::Hashrate {
+                hashrate,
+                thread_hashrates,
+                hashes,
+                elapsed,
+                proofs_submitted,
+            } => {
+                let elapsed = elapsed.as_secs_f64();
+                if json {
+                    if self.last_hashrate.elapsed() >= HASHRATE_EVENT_INTERVAL {
+                        self.last_hashrate = Instant::now();
+                        let event = JsonHashrate {
+                            event: "hashrate",
+                            hashrate,
+                            thread_hashrates,
+                            hashes,
+                            elapsed_s: elapsed,
+                        };
+                        println!("{}", serde_json::to_string(&event)?);
+                    }
+                } else {
+                    print!(
+                        "\rHashrate: {:.0} H/s | {} | Hashes: {} | Time: {:.0}s | Proofs sent: {}",
+                        hashrate,
+                        thread_rates(&thread_hashrates),
+                        hashes,
+                        elapsed,
+                        proofs_submitted
+                    );
+                    use std::io::Write;
+                    std::io::stdout().flush().ok();
+                }
+            }
+            SessionEvent::RoundEnded { seed, round, .. } => {
+                if json {
+                    let event = JsonRoundEnded {
+                        event: "round_ended",
+                        seed: hex::encode(seed),
+                        difficulty: round.difficulty,
+                        timestamp: round.started_at,
+                        outcome: round.outcome,
+                        duration_s: round.duration_secs,
+                        hashes: round.hashes,
+                    };
+                    println!("{}", serde_json::to_string(&event)?);
+                }
+                self.difficulty = round.difficulty;
+                self.round_id = self.stats.round(&round);
+            }
+            SessionEvent::RoundNotSaved { path, error } => {
+                if !json {
+                    eprintln!(
+                        "Warning: Could not save round to {}: {}",
+                        path.display(),
+                        error
+                    );
+                }
+            }
+            SessionEvent::ProofFound {
+                proof,
+                thread_hashes,
+                elapsed,
+                luck,
+            } => {
+                let proof_id = self.stats.proof(&ProofRecord {
+                    round_id: self.round_id,
+                    found_at: unix_now(),
+                    address: proof.miner_address.clone(),
+                    hash: proof.hash.clone(),
+                    nonce: proof.nonce,
+                    timestamp: proof.timestamp,
+                    difficulty: self.difficulty,
+                });
+                self.submitting = Some((proof.hash.clone(), proof_id));
+                let hashes: u64 = thread_hashes.iter().sum();
+                let elapsed = elapsed.as_secs_f64();
+                if json {
+                    let event = JsonProofFound {
+                        event: "proof_found",
+                        hash: proof.hash,
+                        nonce: proof.nonce,
+                        timestamp: proof.timestamp,
+                        hashes_computed: hashes,
+                        hashrate: hashes as f64 / elapsed,
+                        thread_hashrates: thread_hashes
+                            .iter()
+                            .map(|&h| h as f64 / elapsed)
+                            .collect(),
+                        effort: luck.effort(),
+                        luck: luck.luck(),
+                        luck_probability: luck.probability(),
+                    };
+                    println!("{}", serde_json::to_string(&event)?);
+                    return Ok(());
+                }
+                println!("\n\nFound valid proof!");
+                println!("  Hash:      {}", proof.hash);
+                println!("  Nonce:     {}", proof.nonce);
+                println!("  Timestamp: {}", proof.timestamp);
+                println!(
+                    "  Hashes:    {} ({:.0} H/s)",
+                    hashes,
+                    hashes as f64 / elapsed
+                );
+                println!("  Luck:      {}", describe_luck(&luck));
+                if self.no_submit {
+                    println!("\nTo submit this proof, run:");
+                    let tag = match self.worker_tag {
+                        [] => String::new(),
+                        tag => format!(" --worker-tag {}", String::from_utf8_lossy(tag)),
+                    };
+                    println!(
+                        "  uhash send --hash {} --nonce {} --timestamp {}{}",
+                        proof.hash, proof.nonce, proof.timestamp, tag
+                    );
+                }
+            }
+            SessionEvent::Delivery(event) => {
+                if let DeliveryEvent::Delivered {
+                    proof, delivery, ..
+                } = &event
+                {
+                    let sent = match delivery {
+                        Delivery::Committed {
+                            tx_hash, reward, ..
+                        } => Some((tx_hash, reward.as_deref())),
+                        Delivery::Relayed { tx_hash } => Some((tx_hash, None)),
+                        _ => None,
+                    };
+                    let found = self.submitting.take_if(|(hash, _)| *hash == proof.hash);
+                    if let (Some((tx_hash, reward)), Some((_, proof_id))) = (sent, found) {
+                        self.stats.submission(proof_id, tx_hash, reward);
+                    }
+                }
+                print_delivery(event, self.rpc_config, json)?;
+            }
+            SessionEvent::ProofPending { proof, path } => {
+                if json {
+                    let event = JsonProofPending {
+                        event: "proof_pending",
+                        hash: proof.hash,
+                        nonce: proof.nonce,
+                        timestamp: proof.timestamp,
+                        path: path.display().to_string(),
+                    };
+                    println!("{}", serde_json::to_string(&event)?);
+                } else {
+                    println!("\nStopping: proof saved to {}", path.display());
+                }
+            }
+            SessionEvent::Continuing => {
+                if !json {
+                    println!("\nContinuing to mine...\n");
+                }
+            }
+            SessionEvent::SeedRotated { seed } => {
+                if !json {
+                    println!("New seed: {}", hex::encode(seed));
+                }
+            }
+            SessionEvent::Restarting(restart) => {
+                if !json {
+                    report_restart(restart);
+                }
+            }
+            SessionEvent::SeedUnreadable(e) => {
+                if !json {
+                    eprintln!("\nWarning: Could not refresh seed ({})", e);
+                }
+            }
+            SessionEvent::StatusNotWritten { path, error } => {
+                if !json {
+                    eprintln!(
+                        "Warning: Could not write miner status to {} ({})",
+                        path.display(),
+                        error
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Say why a round ended early, in text mode
+fn report_restart(restart: Restart) {
+    match restart {
+        Restart::SeedRotated { seed } => {
+            println!("\n\nSeed rotated, restarting round...");
+            println!("New seed: {}", hex::encode(seed));
+        }
+        Restart::Reconfigured { threads, paused } => {
+            if !paused {
+                println!("\n\nRestarting round with {} threads...", threads);
+            }
+        }
+        Restart::Expired => {
+            println!("\n\nRound is getting too old to submit proofs for, starting a fresh one...")
+        }
+        Restart::SliceEnded { next } => println!("\n\nMoving on to {}...", next),
+        Restart::NoncesExhausted => {
+            println!("\n\nNonce range exhausted, starting a round with a fresh timestamp...")
+        }
+    }
+}
+
+/// Per-thread hashrates for the progress line: every thread's rate when there
+/// are few, the slowest and fastest thread otherwise
+fn thread_rates(rates: &[f64]) -> String {
+    if rates.len() <= PROGRESS_THREAD_RATES {
+        let rates: Vec<String> = rates.iter().map(|r| format!("{:.0}", r)).collect();
+        return format!("Threads: {}", rates.join(" "));
+    }
+    let by_rate = |a: &(usize, &f64), b: &(usize, &f64)| a.1.total_cmp(b.1);
+    let slowest = rates.iter().enumerate().min_by(by_rate);
+    let fastest = rates.iter().enumerate().max_by(by_rate);
+    match slowest.zip(fastest) {
+        Some(((slow, slow_rate), (fast, fast_rate))) => format!(
+            "Slowest: #{} {:.0} H/s, fastest: #{} {:.0} H/s",
+            slow, slow_rate, fast, fast_rate
+        ),
+        None => String::new(),
+    }
+}
+
+/// `mine` stopped on a signal: the process exits with 128 + the signal number
+#[derive(Debug)]
+struct Interrupted(i32);
+
+impl std::fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Stopped by {}", signal_name(self.0))
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        SIGINT => "SIGINT",
+        SIGTERM => "SIGTERM",
+        _ => "signal",
+    }
+}
+
+/// `requested` threads, or as many as free memory holds with `running` running
+fn fit_mining_threads(requested: usize, running: usize, json: bool) -> anyhow::Result<usize> {
+    let threads = fit_threads_to_memory(requested, running)?;
+    if threads < requested {
+        report_threads_capped(threads, requested, available_memory(), json);
+    }
+    Ok(threads)
+}
+
+fn report_threads_capped(threads: usize, requested: usize, available: Option<u64>, json: bool) {
+    if !json {
+        eprintln!(
+            "Warning: Only {} of {} threads fit in free memory ({} MB available), mining on {}",
+            threads,
+            requested,
+            available.unwrap_or(0) / (1024 * 1024),
+            threads
+        );
+    }
+}
+
+/// Hold mining back while the machine is in use, checking in the background
+fn watch_idle(control: Arc<MinerControl>, idle_after: Duration, json: bool) -> anyhow::Result<()> {
+    let state = idle::probe().map_err(|e| anyhow::anyhow!("Could not detect idleness: {}", e))?;
+    let mut was_idle = state.is_idle(idle_after);
+    control.hold(!was_idle);
+    report_idle(was_idle, json)?;
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_POLL_INTERVAL);
+        // A failed check keeps the last state
+        let Ok(state) = idle::probe() else {
+            continue;
+        };
+        let idle = state.is_idle(idle_after);
+        if idle != was_idle {
+            was_idle = idle;
+            control.hold(!idle);
+            let _ = report_idle(idle, json);
+        }
+    });
+    Ok(())
+}
+
+/// Emit `idle_changed`; in text mode the mining loop reports the wait itself
+fn report_idle(idle: bool, json: bool) -> anyhow::Result<()> {
+    if json {
+        let event = JsonIdleChanged {
+            event: "idle_changed",
+            idle,
+        };
+        println!("{}", serde_json::to_string(&event)?);
+    }
+    Ok(())
+}
+
+/// Report a config reload; a file that cannot be applied is only a warning
+fn report_config_reload(result: anyhow::Result<Vec<String>>, json: bool) -> anyhow::Result<()> {
+    if json {
+        let (changed, error) = match result {
+            Ok(changed) => (changed, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        let event = JsonConfigReloaded {
+            event: "config_reloaded",
+            changed,
+            error,
+        };
+        println!("{}", serde_json::to_string(&event)?);
+    } else {
+        match result {
+            Ok(changed) if changed.is_empty() => println!("\nConfig reloaded: no changes"),
+            Ok(changed) => println!("\nConfig reloaded: {}", changed.join(", ")),
+            Err(e) => eprintln!("\nWarning: Could not reload config: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Settings `mine` picks up from its config file while it runs
+///
+/// The file is re-read when its modification time changes and, on Unix, on
+/// SIGHUP. Settings given on the command line are left alone.
+struct ConfigReload {
+    path: PathBuf,
+    /// `mine.threads` applies (no `--threads`)
+    threads: bool,
+    /// `submit.*` applies (no `--submit-via`)
+    submit: bool,
+    /// `network.rpc` and `network.lcd` apply (no `--rpc`/`--lcd`)
+    endpoints: bool,
+    modified: Option<SystemTime>,
+    hangup: Arc<AtomicBool>,
+}
+
+impl ConfigReload {
+    fn new(path: &Path, threads: bool, submit: bool, endpoints: bool) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            threads,
+            submit,
+            endpoints,
+            modified: modified_time(path),
+            hangup: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Re-read the file on SIGHUP too (Unix only)
+    fn watch_hangup(&self, rt: &tokio::runtime::Runtime) -> anyhow::Result<()> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut hangup = rt.block_on(async { signal(SignalKind::hangup()) })?;
+            let flag = Arc::clone(&self.hangup);
+            rt.spawn(async move {
+                while hangup.recv().await.is_some() {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        let _ = rt;
+        Ok(())
+    }
+
+    /// The config, if the file changed or SIGHUP arrived since the last call
+    fn poll(&mut self) -> Option<Result<Config, ConfigError>> {
+        let modified = modified_time(&self.path);
+        if !self.hangup.swap(false, Ordering::SeqCst) && modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(Config::load(&self.path))
+    }
+
+    /// Apply the settings of `config` that are not pinned, describing changes
+    fn apply(
+        &self,
+        config: &Config,
+        control: &MinerControl,
+        submit: &mut SubmitConfig,
+        submitter: &mut Submitter,
+        wallet: &Wallet,
+    ) -> anyhow::Result<Vec<String>> {
+        let client = submitter.client();
+        let mut changed = Vec::new();
+        if self.endpoints {
+            let mut lcd = lcd_endpoints_for(&config.network.rpc, &config.network.lcd);
+            if lcd.is_empty() {
+                let profile = RpcConfig::from_profile(&config.chain);
+                lcd = std::iter::once(profile.lcd_url)
+                    .chain(profile.fallback_lcd_urls)
+                    .collect();
+            }
+            let lcd: Vec<String> = lcd
+                .iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect();
+            if lcd != client.lcd_endpoints() {
+                client.set_lcd_endpoints(lcd.clone())?;
+                changed.push(format!("lcd={}", lcd.join(",")));
+            }
+        }
+        if self.submit && config.submit != *submit {
+            submitter.set_sink(sink::from_settings(
+                config.submit.sink,
+                &config.submit.url,
+                &config.submit.path,
+                client,
+                proof_signing_key(wallet)?,
+            )?);
+            *submit = config.submit.clone();
+            changed.push(format!("sink={:?}", submit.sink).to_lowercase());
+        }
+        if self.threads {
+            let threads = match config.mine.threads {
+                0 => num_cpus::get(),
+                threads => threads.min(MAX_THREADS),
+            };
+            if threads != control.threads() {
+                // Takes effect with the next round
+                control.set_threads(threads);
+                changed.push(format!("threads={}", threads));
+            }
+        }
+        Ok(changed)
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The signal that asked the miner to stop, if one arrived
+#[derive(Clone)]
+struct Shutdown(Arc<AtomicI32>);
+
+impl Shutdown {
+    /// Catch SIGINT and SIGTERM (Ctrl-C on Windows) on `rt`
+    ///
+    /// The first signal only sets the flag, so the miner can stop its threads
+    /// and save what it holds; a second one exits at once.
+    fn install(rt: &tokio::runtime::Runtime) -> anyhow::Result<Self> {
+        let received = Arc::new(AtomicI32::new(0));
+        let flag = Arc::clone(&received);
+        let on_signal = move |signal: i32| {
+            if flag.swap(signal, Ordering::SeqCst) != 0 {
+                std::process::exit(128 + signal);
+            }
+        };
+
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            // Registered before returning, so no signal slips through
+            let (mut interrupt, mut terminate) = rt.block_on(async {
+                Ok::<_, std::io::Error>((
+                    signal(SignalKind::interrupt())?,
+                    signal(SignalKind::terminate())?,
+                ))
+            })?;
+            rt.spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = interrupt.recv() => on_signal(SIGINT),
+                        _ = terminate.recv() => on_signal(SIGTERM),
+                    }
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        rt.spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                on_signal(SIGINT);
+            }
+        });
+
+        Ok(Self(received))
+    }
+
+    fn signal(&self) -> Option<i32> {
+        Some(self.0.load(Ordering::SeqCst)).filter(|&signal| signal != 0)
+    }
+
+    fn requested(&self) -> bool {
+        self.signal().is_some()
+    }
+}
+
+/// The nonces `mine` hashes, from its partitioning options
+fn mine_nonces(
+    start: u64,
+    range: Option<u64>,
+    worker: Option<(u64, u64)>,
+) -> anyhow::Result<NonceRange> {
+    if range == Some(0) {
+        anyhow::bail!("--nonce-range must be at least 1");
+    }
+    let Some((index, count)) = worker else {
+        return Ok(NonceRange {
+            start,
+            end: range.map(|range| start.saturating_add(range)),
+        });
+    };
+    if index >= count {
+        anyhow::bail!(
+            "--worker-index must be below --worker-count ({}), got {}",
+            count,
+            index
+        );
+    }
+    NonceRange::partition(start, range.unwrap_or(NONCE_RANGE), index, count)
+        .ok_or_else(|| anyhow::anyhow!("Worker {}'s nonce range is past the last nonce", index))
+}
+
+/// The key that signs proof transactions, `None` for a watch-only wallet
+fn proof_signing_key(
+    wallet: &Wallet,
+) -> anyhow::Result<Option<cosmrs::crypto::secp256k1::SigningKey>> {
+    match wallet.signing_key() {
+        Ok(key) => Ok(Some(
+            cosmrs::crypto::secp256k1::SigningKey::from_slice(&key.to_bytes())
+                .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?,
+        )),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Print what became of proofs handed to the sink, for `mine` and `controller`
+fn print_delivery(event: DeliveryEvent, rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    let submitted = |tx_hash: &str, success, proofs_submitted, destination| JsonProofSubmitted {
+        event: "proof_submitted",
+        tx_hash: tx_hash.to_string(),
+        success,
+        proofs_submitted,
+        reward: None,
+        reward_denom: None,
+        gas_used: None,
+        height: None,
+        destination,
+    };
+
+    match event {
+        DeliveryEvent::Submitting {
+            proofs,
+            destination,
+        } => {
+            if !json {
+                if proofs == 1 {
+                    println!("\nSubmitting proof to {}...", destination);
+                } else {
+                    println!("\nSubmitting {} proofs to {}...", proofs, destination);
+                }
+            }
+        }
+        DeliveryEvent::Delivered {
+            delivery:
+                Delivery::Committed {
+                    tx_hash,
+                    reward,
+                    reward_denom,
+                    gas_used,
+                    height,
+                },
+            destination,
+            proofs_submitted,
+            ..
+        } => {
+            if json {
+                let event = JsonProofSubmitted {
+                    reward,
+                    reward_denom,
+                    gas_used,
+                    height,
+                    ..submitted(&tx_hash, true, proofs_submitted, destination)
+                };
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                println!("Proof accepted! TX: {}", tx_hash);
+                if let Some(reward) = &reward {
+                    println!("Reward: {} uLI", reward);
+                }
+                if let (Some(height), Some(gas_used)) = (height, gas_used) {
+                    println!("Block: {} (gas used: {})", height, gas_used);
+                }
+                if let Some(link) = rpc_config.explorer_tx_link(&tx_hash) {
+                    println!("View: {}", link);
+                }
+            }
+        }
+        DeliveryEvent::Delivered {
+            delivery: Delivery::Relayed { tx_hash },
+            destination,
+            proofs_submitted,
+            ..
+        } => {
+            if json {
+                let event = submitted(&tx_hash, true, proofs_submitted, destination);
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                println!("Proof relayed! TX: {}", tx_hash);
+                if let Some(link) = rpc_config.explorer_tx_link(&tx_hash) {
+                    println!("View: {}", link);
+                }
+            }
+        }
+        DeliveryEvent::Delivered {
+            delivery,
+            destination,
+            proofs_submitted,
+            ..
+        } => {
+            if json {
+                let event = submitted("", true, proofs_submitted, destination);
+                println!("{}", serde_json::to_string(&event)?);
+            } else if let Delivery::Forwarded { destination } = delivery {
+                println!("Proof sent to {}", destination);
+            }
+        }
+        DeliveryEvent::Failed {
+            error,
+            destination,
+            proofs_submitted,
+            ..
+        } => {
+            if json {
+                let event = submitted("", false, proofs_submitted, destination);
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                eprintln!("Submit failed: {}. Continuing to mine...", error);
+            }
+        }
+        DeliveryEvent::Unsigned {
+            proof,
+            encoded,
+            account,
+        } => print_unsigned(&proof, encoded, account, json)?,
+        DeliveryEvent::TxStatus {
+            update,
+            expired,
+            resubmitted,
+        } => {
+            if json {
+                let event = JsonTxStatus {
+                    event: "tx_status",
+                    tx_hash: update.tx_hash,
+                    status: update.status,
+                    proofs: update.proofs.into_iter().map(|p| p.hash).collect(),
+                    resubmitted,
+                };
+                println!("{}", serde_json::to_string(&event)?);
+                return Ok(());
+            }
+            match &update.status {
+                TxStatus::Included { .. } => {}
+                TxStatus::Finalized {
+                    height,
+                    success: true,
+                } => println!(
+                    "\nTransaction {} is final at block {}",
+                    update.tx_hash, height
+                ),
+                TxStatus::Finalized {
+                    height,
+                    success: false,
+                } => eprintln!(
+                    "\nWarning: Transaction {} failed in block {}",
+                    update.tx_hash, height
+                ),
+                TxStatus::Evicted => eprintln!(
+                    "\nWarning: Transaction {} did not reach a block in {}s",
+                    update.tx_hash, rpc_config.confirm_timeout_secs
+                ),
+                TxStatus::Reorged { height } => eprintln!(
+                    "\nWarning: Block {} with transaction {} was reorganized away",
+                    height, update.tx_hash
+                ),
+            }
+            for proof in &expired {
+                eprintln!("Dropping expired proof {}", proof.hash);
+            }
+        }
+        DeliveryEvent::TrackingFailed(e) => {
+            if !json {
+                eprintln!("\nWarning: Could not look up sent proofs ({})", e);
+            }
+        }
+        DeliveryEvent::Unsettled(txs) => {
+            if !json {
+                eprintln!("\nWarning: {} proof transaction(s) not yet final", txs);
+            }
+        }
+        DeliveryEvent::PendingFound => {
+            if !json {
+                println!("Submitting proofs saved by an earlier run...");
+            }
+        }
+        DeliveryEvent::PendingExpired(proof) => {
+            if !json {
+                println!("Dropping expired proof {}", proof.hash);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run `call`, printing the delivery events it reports as they come
+fn print_deliveries<T>(
+    rpc_config: &RpcConfig,
+    json: bool,
+    call: impl FnOnce(&mut dyn FnMut(DeliveryEvent)) -> Result<T, DeliveryError>,
+) -> anyhow::Result<T> {
+    let mut error = None;
+    let value = call(&mut |event| {
+        if let Err(e) = print_delivery(event, rpc_config, json) {
+            error.get_or_insert(e);
+        }
+    })?;
+    error.map_or(Ok(value), Err)
+}
+
+/// Print a proof a watch-only wallet cannot sign, with what `sign-proof` needs
+///
+/// Proofs signed at the same sequence replace each other, so broadcast each
+/// signed proof before signing the next.
+fn print_unsigned(
+    proof: &ProofSubmission,
+    encoded: String,
+    account: Result<(u64, u64), RpcError>,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        let (account_number, sequence) = account.ok().unzip();
+        let event = JsonProofUnsigned {
+            event: "proof_unsigned",
+            hash: proof.hash.clone(),
+            nonce: proof.nonce,
+            timestamp: proof.timestamp,
+            proof: encoded,
+            account_number,
+            sequence,
+        };
+        println!("{}", serde_json::to_string(&event)?);
+        return Ok(());
+    }
+
+    let account_args = match account {
+        Ok((account_number, sequence)) => format!(
+            " --account-number {} --sequence {}",
+            account_number, sequence
+        ),
+        Err(e) => {
+            eprintln!("Warning: Could not fetch account info ({})", e);
+            String::new()
+        }
+    };
+    println!("\nWatch-only wallet: sign this proof where the key is kept:");
+    println!(
+        "  uhash sign-proof --proof {}{} -o proof.json",
+        encoded, account_args
+    );
+    println!("then broadcast it with: uhash broadcast proof.json");
+    Ok(())
+}
+
+/// Read the seed again, keeping the old one if the chain cannot be read;
+/// whether it changed
+fn refresh_seed(
+    rt: &tokio::runtime::Runtime,
+    client: &RpcClient,
+    seed_info: &mut SeedInfo,
+    json: bool,
+) -> bool {
+    miner::refresh_seed(rt, client, seed_info).unwrap_or_else(|e| {
+        if !json {
+            eprintln!("\nWarning: Could not refresh seed ({})", e);
+        }
+        false
+    })
+}
+
+/// `142% (0.70x the expected hashes; 50% of proofs take longer)`
+fn describe_luck(luck: &Luck) -> String {
+    format!(
+        "{:.0}% ({:.2}x the expected hashes; {:.0}% of proofs take longer)",
+        luck.luck() * 100.0,
+        luck.effort(),
+        luck.probability() * 100.0
+    )
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Who `uhash controller` admits, from its flags
+fn worker_auth(tokens: &[String], addresses: &[String]) -> anyhow::Result<Authenticator> {
+    let mut auth = Authenticator::open();
+    for token in tokens {
+        if token.is_empty() {
+            anyhow::bail!("Worker token must not be empty");
+        }
+        auth = auth.with_token(token);
+    }
+    for address in addresses {
+        validate_address(address)?;
+        auth = auth.with_key(address);
+    }
+    Ok(auth)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_controller(
+    listen: SocketAddr,
+    max_proofs: Option<u64>,
+    worker_tokens: &[String],
+    allowed_workers: &[String],
+    rate_limit: f64,
+    wallet_opts: &WalletOpts,
+    rpc_config: &RpcConfig,
+    submit: &SubmitConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    if rpc_config.memo.chars().count() > MAX_MEMO_LEN {
+        anyhow::bail!("Memo is longer than {} characters", MAX_MEMO_LEN);
+    }
+
+    let wallet = wallet_opts.load()?;
+    let address = wallet.address_str();
+    let client = rpc_client(rpc_config, json)?;
+    let sink = sink::from_settings(
+        submit.sink,
+        &submit.url,
+        &submit.path,
+        &client,
+        proof_signing_key(&wallet)?,
+    )?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let statuses = rt.block_on(client.select_fastest_endpoint());
+    if statuses.iter().all(|s| !s.is_healthy()) {
+        anyhow::bail!("No LCD endpoint is reachable");
+    }
+    let difficulty = rt
+        .block_on(client.get_difficulty())
+        .map_err(|e| anyhow::anyhow!("Could not fetch difficulty: {}", e))?;
+    let mut seed_info = rt
+        .block_on(client.get_seed())
+        .map_err(|e| anyhow::anyhow!("Could not fetch seed: {}", e))?;
+    let max_proof_age = rt
+        .block_on(client.get_config())
+        .ok()
+        .map(|config| config.max_proof_age);
+    let schedule = Schedule::new(max_proof_age);
+
+    if rate_limit.is_nan() || rate_limit <= 0.0 {
+        anyhow::bail!("Rate limit must be above 0");
+    }
+    let (controller, events) = Controller::new();
+    let controller = controller
+        .with_auth(worker_auth(worker_tokens, allowed_workers)?)
+        .with_rate_limit(rate_limit, DEFAULT_RATE_BURST);
+    let local_addr = {
+        let _guard = rt.enter();
+        controller.spawn(listen)?
+    };
+
+    if json {
+        let started = JsonControllerStarted {
+            event: "controller_started",
+            listen: local_addr.to_string(),
+            contract: rpc_config.contract_address.clone(),
+            address: address.clone(),
+            difficulty,
+            seed: hex::encode(seed_info.seed),
+            watch_only: wallet.is_watch_only(),
+        };
+        println!("{}", serde_json::to_string(&started)?);
+    } else {
+        println!("\n=== UniversalHash Controller ===");
+        println!("Contract: {}", rpc_config.contract_address);
+        println!("Address:  {}", address);
+        println!("Difficulty: {} bits", difficulty);
+        println!("Seed: {}", hex::encode(seed_info.seed));
+        println!(
+            "Workers connect with: uhash worker --controller tcp://{}",
+            local_addr
+        );
+        println!("================================\n");
+    }
+
+    let mut submitter = Submitter::new(
+        &rt,
+        &client,
+        sink,
+        Duration::from_secs(rpc_config.confirm_timeout_secs),
+        rpc_config.finality_depth,
+        max_proof_age,
+    );
+    // Proofs waiting to fill a transaction, oldest first
+    let mut batch: Vec<ProofSubmission> = Vec::new();
+    let batch_size = submit.batch_size.max(1);
+    // Unix time the oldest proof waiting must be sent by
+    let batch_deadline = |batch: &[ProofSubmission]| {
+        max_proof_age
+            .zip(batch.first())
+            .map(|(age, oldest)| oldest.timestamp + age.saturating_sub(PROOF_AGE_MARGIN_SECS))
+    };
+
+    loop {
+        // A job at the timestamp of a proof still waiting would find it again
+        let now = unix_now();
+        let params = RoundParams {
+            seed: seed_info.seed,
+            address: address.clone(),
+            timestamp: batch
+                .last()
+                .map_or(now, |proof| now.max(proof.timestamp + 1)),
+            difficulty,
+            nonce_end: None,
+            worker_tag: Vec::new(),
+        };
+        let mut timer = schedule.round(&params, &seed_info, now);
+        controller.set_job(params.seed, &address, params.timestamp, difficulty);
+
+        // Serve workers until one of them finds a proof, the seed rotates or
+        // the job's proofs would get too old
+        let mut last_report = Instant::now();
+        let mut expired = false;
+        let found = loop {
+            if batch_deadline(&batch).is_some_and(|at| unix_now() >= at) {
+                submit_controller_batch(&mut submitter, &mut batch, rpc_config, json)?;
+            }
+            print_deliveries(rpc_config, json, |events| submitter.follow(events))?;
+            match timer.due(unix_now()) {
+                Some(Due::RefreshSeed) => {
+                    if refresh_seed(&rt, &client, &mut seed_info, json) {
+                        break false;
+                    }
+                    timer.refresh_at = schedule.next_refresh(&seed_info, unix_now());
+                }
+                Some(Due::Restart) => {
+                    expired = true;
+                    break false;
+                }
+                None => {}
+            }
+            match events.recv_timeout(MONITOR_INTERVAL).ok() {
+                Some(ControllerEvent::Connected(worker)) => {
+                    if json {
+                        let event = JsonWorker {
+                            event: "worker_connected",
+                            worker,
+                        };
+                        println!("{}", serde_json::to_string(&event)?);
+                    } else {
+                        println!(
+                            "\nWorker {} connected from {} ({} threads)",
+                            worker.name, worker.peer, worker.threads
+                        );
+                    }
+                }
+                Some(ControllerEvent::Disconnected(worker)) => {
+                    if json {
+                        let event = JsonWorker {
+                            event: "worker_disconnected",
+                            worker,
+                        };
+                        println!("{}", serde_json::to_string(&event)?);
+                    } else {
+                        println!("\nWorker {} disconnected", worker.name);
+                    }
+                }
+                Some(ControllerEvent::Rejected { worker, reason }) => {
+                    if json {
+                        let event = serde_json::json!({
+                            "event": "proof_rejected",
+                            "worker": worker,
+                            "reason": reason,
+                        });
+                        println!("{}", event);
+                    } else {
+                        eprintln!("\nWarning: Rejected a proof from {} ({})", worker, reason);
+                    }
+                }
+                Some(ControllerEvent::Refused { peer, reason }) => {
+                    if json {
+                        let event = serde_json::json!({
+                            "event": "worker_refused",
+                            "peer": peer,
+                            "reason": reason,
+                        });
+                        println!("{}", event);
+                    } else {
+                        eprintln!("\nWarning: Refused a worker from {} ({})", peer, reason);
+                    }
+                }
+                Some(ControllerEvent::Proof(proof)) => {
+                    if json {
+                        let event = JsonWorkerProof {
+                            event: "proof_found",
+                            worker: proof.worker.clone(),
+                            hash: hex::encode(proof.hash),
+                            nonce: proof.nonce,
+                            timestamp: proof.timestamp,
+                        };
+                        println!("{}", serde_json::to_string(&event)?);
+                    } else {
+                        println!("\n\nWorker {} found a valid proof!", proof.worker);
+                        println!("  Hash:      {}", hex::encode(proof.hash));
+                        println!("  Nonce:     {}", proof.nonce);
+                        println!("  Timestamp: {}", proof.timestamp);
+                    }
+                    batch.push(ProofSubmission {
+                        hash: hex::encode(proof.hash),
+                        nonce: proof.nonce,
+                        timestamp: proof.timestamp,
+                        miner_address: address.clone(),
+                        worker_tag: proof.worker_tag.clone(),
+                    });
+                    break true;
+                }
+                None => {}
+            }
+
+            if !json && last_report.elapsed() >= PROGRESS_INTERVAL {
+                last_report = Instant::now();
+                let workers = controller.workers();
+                let hashrate: f64 = workers.iter().map(|w| w.hashrate).sum();
+                print!(
+                    "\rWorkers: {} | Hashrate: {:.0} H/s | Proofs sent: {}",
+                    workers.len(),
+                    hashrate,
+                    submitter.proofs_submitted()
+                );
+                use std::io::Write;
+                std::io::stdout().flush().ok();
+            }
+        };
+
+        let sent = submitter.proofs_submitted() + submitter.proofs_unsigned();
+        let done = max_proofs.is_some_and(|max| sent + batch.len() as u64 >= max);
+        // Proofs of a rotated seed are sent before the contract stops taking them
+        if batch.len() >= batch_size || !found || done {
+            submit_controller_batch(&mut submitter, &mut batch, rpc_config, json)?;
+        }
+        if done {
+            print_deliveries(rpc_config, json, |events| submitter.settle(events))?;
+            break;
+        }
+        if expired {
+            if !json {
+                println!(
+                    "\n\nJob is getting too old to submit proofs for, handing out new work..."
+                );
+            }
+        } else if !found {
+            if !json {
+                println!("\n\nSeed rotated, handing out new work...");
+                println!("New seed: {}", hex::encode(seed_info.seed));
+            }
+        } else if refresh_seed(&rt, &client, &mut seed_info, json) && !json {
+            println!("New seed: {}", hex::encode(seed_info.seed));
+        }
+    }
+
+    controller.idle();
+    Ok(())
+}
+
+/// Submit the proofs the controller collected, emptying `batch`
+fn submit_controller_batch(
+    submitter: &mut Submitter,
+    batch: &mut Vec<ProofSubmission>,
+    rpc_config: &RpcConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    print_deliveries(rpc_config, json, |events| submitter.submit(batch, events))?;
+    batch.clear();
+    Ok(())
+}
+
+fn cmd_worker(
+    controller: &str,
+    name: Option<&str>,
+    threads: Option<usize>,
+    token: Option<String>,
+    key: Option<&WalletOpts>,
+    json: bool,
+) -> anyhow::Result<()> {
+    parse_controller_url(controller)?;
+    let auth = match (token, key) {
+        (Some(token), _) => WorkerAuth::Token(token),
+        (None, Some(wallet_opts)) => WorkerAuth::Key(Box::new(wallet_opts.load()?)),
+        (None, None) => WorkerAuth::None,
+    };
+    let num_threads = fit_mining_threads(threads.unwrap_or_else(num_cpus::get), 0, json)?;
+
+    loop {
+        let result = WorkerConnection::connect_with(controller, name, num_threads, &auth).and_then(
+            |connection| {
+                if json {
+                    let event = serde_json::json!({
+                        "event": "worker_connected",
+                        "controller": controller,
+                        "threads": num_threads,
+                    });
+                    println!("{}", event);
+                } else {
+                    println!(
+                        "Connected to controller {} ({} threads)",
+                        controller, num_threads
+                    );
+                }
+                work_for_controller(connection, num_threads, json)
+            },
+        );
+        let reason = match result {
+            Ok(()) => "the controller closed the connection".to_string(),
+            Err(FleetError::Refused(message)) => {
+                anyhow::bail!("Controller refused the worker: {}", message)
+            }
+            Err(e) => e.to_string(),
+        };
+        if json {
+            let event = serde_json::json!({
+                "event": "worker_disconnected",
+                "controller": controller,
+                "error": reason,
+            });
+            println!("{}", event);
+        } else {
+            eprintln!(
+                "\nWarning: No connection to controller {} ({}), retrying in {}s",
+                controller,
+                reason,
+                RECONNECT_DELAY.as_secs()
+            );
+        }
+        std::thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+/// Mine the controller's jobs until the connection ends
+fn work_for_controller(
+    mut connection: WorkerConnection,
+    threads: usize,
+    json: bool,
+) -> Result<(), FleetError> {
+    let sender = connection.sender()?;
+    let (messages_tx, messages) = std::sync::mpsc::channel();
+    std::thread::spawn(move || loop {
+        let message = connection.recv();
+        let last = !matches!(message, Ok(Some(_)));
+        if messages_tx.send(message).is_err() || last {
+            break;
+        }
+    });
+
+    let mut next = messages.recv().unwrap_or(Ok(None));
+    let result = loop {
+        match next {
+            Ok(Some(ControllerMessage::Job(job))) => {
+                next = mine_job(&job, threads, &sender, &messages, json);
+            }
+            Ok(Some(ControllerMessage::Idle)) => {
+                if !json {
+                    println!("\nWaiting for work...");
+                }
+                next = messages.recv().unwrap_or(Ok(None));
+            }
+            Ok(Some(ControllerMessage::Error { message })) => {
+                break Err(FleetError::Refused(message))
+            }
+            // Only sent while signing in
+            Ok(Some(ControllerMessage::Challenge { .. })) => {
+                next = messages.recv().unwrap_or(Ok(None));
+            }
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+    // Wakes the reader thread if it is still waiting
+    sender.close();
+    result
+}
+
+/// Hash one job until the controller sends its next message, which is returned
+fn mine_job(
+    job: &Job,
+    threads: usize,
+    sender: &WorkerSender,
+    messages: &std::sync::mpsc::Receiver<Result<Option<ControllerMessage>, FleetError>>,
+    json: bool,
+) -> Result<Option<ControllerMessage>, FleetError> {
+    job.seed()?;
+    if json {
+        let event = serde_json::json!({
+            "event": "job",
+            "id": job.id,
+            "difficulty": job.difficulty,
+            "nonce_start": job.nonce_start,
+            "nonce_end": job.nonce_end,
+        });
+        println!("{}", event);
+    } else {
+        println!(
+            "\nJob {}: difficulty {} bits, nonces {}..{}",
+            job.id, job.difficulty, job.nonce_start, job.nonce_end
+        );
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_hashes: Arc<Vec<AtomicU64>> =
+        Arc::new((0..threads).map(|_| AtomicU64::new(0)).collect());
+    let (found_tx, found) = std::sync::mpsc::channel();
+    let handles: Vec<_> = (0..threads)
+        .map(|thread_id| {
+            let job = job.clone();
+            let stop = Arc::clone(&stop);
+            let thread_hashes = Arc::clone(&thread_hashes);
+            let found_tx = found_tx.clone();
+            std::thread::spawn(move || {
+                job.search(
+                    thread_id,
+                    threads,
+                    &stop,
+                    &thread_hashes[thread_id],
+                    |nonce, hash| {
+                        let _ = found_tx.send((nonce, hash));
+                    },
+                )
+            })
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut last_report = Instant::now();
+    let mut last_event = Instant::now();
+    let mut proofs_found: u64 = 0;
+    // Hashes of the job when its last proof was found
+    let mut hashes_at_proof: u64 = 0;
+    let next = 'job: loop {
+        match messages.recv_timeout(MONITOR_INTERVAL) {
+            Ok(message) => break message,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break Ok(None),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+        }
+
+        let counts: Vec<u64> = thread_hashes
+            .iter()
+            .map(|h| h.load(Ordering::Relaxed))
+            .collect();
+        let hashes: u64 = counts.iter().sum();
+        let elapsed = start.elapsed().as_secs_f64();
+        let hashrate = hashes as f64 / elapsed;
+        let thread_hashrates: Vec<f64> = counts.iter().map(|&h| h as f64 / elapsed).collect();
+        for (nonce, hash) in found.try_iter() {
+            proofs_found += 1;
+            let mut luck = Luck::default();
+            luck.add(hashes.saturating_sub(hashes_at_proof), job.difficulty);
+            hashes_at_proof = hashes;
+            if json {
+                let event = JsonProofFound {
+                    event: "proof_found",
+                    hash: hex::encode(hash),
+                    nonce,
+                    timestamp: job.timestamp,
+                    hashes_computed: hashes,
+                    hashrate,
+                    thread_hashrates: thread_hashrates.clone(),
+                    effort: luck.effort(),
+                    luck: luck.luck(),
+                    luck_probability: luck.probability(),
+                };
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                println!(
+                    "\nFound valid proof (nonce {}, luck {}), sending to the controller",
+                    nonce,
+                    describe_luck(&luck)
+                );
+            }
+            let proof = WorkerMessage::Proof {
+                job_id: job.id,
+                nonce,
+                hash: hex::encode(hash),
+            };
+            if let Err(e) = sender.send(&proof) {
+                break 'job Err(e);
+            }
+        }
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            let progress = WorkerMessage::Progress {
+                job_id: job.id,
+                hashrate,
+            };
+            if let Err(e) = sender.send(&progress) {
+                break Err(e);
+            }
+            if json {
+                if last_event.elapsed() >= HASHRATE_EVENT_INTERVAL {
+                    last_event = Instant::now();
+                    let event = JsonHashrate {
+                        event: "hashrate",
+                        hashrate,
+                        thread_hashrates,
+                        hashes,
+                        elapsed_s: elapsed,
+                    };
+                    println!("{}", serde_json::to_string(&event)?);
+                }
+            } else {
+                print!(
+                    "\rHashrate: {:.0} H/s | {} | Hashes: {} | Time: {:.0}s | Proofs found: {}",
+                    hashrate,
+                    thread_rates(&thread_hashrates),
+                    hashes,
+                    elapsed,
+                    proofs_found
+                );
+                use std::io::Write;
+                std::io::stdout().flush().ok();
+            }
+        }
+    };
+
+    stop.store(true, Ordering::SeqCst);
+    for handle in handles {
+        let _ = handle.join();
+    }
+    next
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_send(
+    hash_hex: &str,
+    nonce: u64,
+    timestamp: u64,
+    worker_tag: &[u8],
+    dry_run: bool,
+    wallet_opts: &WalletOpts,
+    rpc_config: &RpcConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    let wallet = wallet_opts.load()?;
+
+    if !json {
+        if dry_run {
+            println!("Simulating proof submission...");
+        } else {
+            println!("Submitting proof to contract...");
+        }
+        println!("Contract: {}", rpc_config.contract_address);
+        println!("From: {}", wallet.address_str());
+        println!("Hash: {}", hash_hex);
+        println!("Nonce: {}", nonce);
+        println!("Timestamp: {}", timestamp);
+        if !worker_tag.is_empty() {
+            println!("Worker tag: {}", String::from_utf8_lossy(worker_tag));
+        }
+    }
+
+    // Create RPC client
+    let client = rpc_client(rpc_config, json)?;
+
+    // Build proof submission
+    let proof = ProofSubmission {
+        hash: hash_hex.to_string(),
+        nonce,
+        timestamp,
+        miner_address: wallet.address_str(),
+        worker_tag: None,
+    }
+    .with_worker_tag(worker_tag);
+
+    // Get signing key from wallet
+    let signing_key =
+        cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key()?.to_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?;
+
+    // Submit using tokio runtime
+    let rt = tokio::runtime::Runtime::new()?;
+    if dry_run {
+        let simulation = rt.block_on(client.simulate_proof(&proof, &signing_key))?;
+        return print_simulation(&simulation, rpc_config, json);
+    }
+    let result = rt.block_on(send_proof(&client, proof, &signing_key))?;
+
+    if json {
+        let out = JsonSendResult {
+            tx_hash: result.tx_hash,
+            success: true,
+            reward: result.reward,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("\nProof submitted successfully!");
+        println!("Transaction hash: {}", result.tx_hash);
+        if let Some(height) = result.height {
+            println!("Committed at height: {}", height);
+        }
+        if let Some(reward) = &result.reward {
+            println!("Reward: {} uLI", reward);
+        }
+        if let Some(link) = rpc_config.explorer_tx_link(&result.tx_hash) {
+            println!("\nView on explorer: {}", link);
+        }
+    }
+
+    Ok(())
+}
+
+/// Report a `send --dry-run`, failing when the contract would reject the proof
+fn print_simulation(
+    simulation: &ProofSimulation,
+    rpc_config: &RpcConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    let fee = simulation.gas_limit.map(|gas_limit| {
+        format!(
+            "{}{}",
+            fee_for_gas(rpc_config, gas_limit),
+            rpc_config.fee_denom
+        )
+    });
+    if json {
+        let out = JsonSendSimulation {
+            event: "simulation",
+            accepted: simulation.accepted,
+            gas_used: simulation.gas_used,
+            gas_limit: simulation.gas_limit,
+            fee,
+            error: simulation.error.clone(),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else if simulation.accepted {
+        println!("\nThe contract would accept this proof (nothing was broadcast)");
+        if let Some(gas_used) = simulation.gas_used {
+            println!("Gas used: {}", gas_used);
+        }
+        if let Some(gas_limit) = simulation.gas_limit {
+            println!("Gas limit: {}", gas_limit);
+        }
+        if let Some(fee) = &fee {
+            println!("Fee: {}", fee);
+        }
+    }
+    if !simulation.accepted {
+        anyhow::bail!(
+            "The contract would reject this proof: {}",
+            simulation.error.as_deref().unwrap_or("simulation failed")
+        );
+    }
+    Ok(())
+}
+
+/// Submit one proof, treating a committed-but-failed transaction as an error
+async fn send_proof(
+    client: &impl ChainClient,
+    proof: ProofSubmission,
+    signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+) -> anyhow::Result<SubmitResult> {
+    rejected_as_error(client.submit_proof(proof, signing_key).await?)
+}
+
+/// Turn a committed-but-failed transaction into an error
+fn rejected_as_error(result: SubmitResult) -> anyhow::Result<SubmitResult> {
+    if result.accepted {
+        return Ok(result);
+    }
+    anyhow::bail!(
+        "{} (tx {})",
+        result.error.as_deref().unwrap_or("Transaction rejected"),
+        result.tx_hash
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_sign_proof(
+    encoded: Option<&str>,
+    parts: Option<((String, u64), u64)>,
+    worker_tag: &[u8],
+    account: Option<(u64, u64)>,
+    output: Option<&Path>,
+    wallet_opts: &WalletOpts,
+    rpc_config: &RpcConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    let wallet = wallet_opts.load()?;
+
+    let proof = match (encoded, parts) {
+        (Some(encoded), _) => {
+            let proof = Proof::decode(encoded)?;
+            if proof.miner_address != wallet.address_str() {
+                anyhow::bail!(
+                    "The proof is for {}, but the wallet is {}",
+                    proof.miner_address,
+                    wallet.address_str()
+                );
+            }
+            proof.submission()
+        }
+        (None, Some(((hash, nonce), timestamp))) => ProofSubmission {
+            hash,
+            nonce,
+            timestamp,
+            miner_address: wallet.address_str(),
+            worker_tag: None,
+        }
+        .with_worker_tag(worker_tag),
+        // clap requires one or the other
+        (None, None) => anyhow::bail!("Pass --proof, or --hash, --nonce and --timestamp"),
+    };
+
+    // Both values are needed for a fully offline signature; otherwise ask the chain
+    let (account_number, sequence) = match account {
+        Some(account) => account,
+        None => {
+            if !json {
+                eprintln!("Fetching account number and sequence (pass --account-number and --sequence to stay offline)...");
+            }
+            let client = rpc_client(rpc_config, json)?;
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(client.get_account_info(&proof.miner_address))?
+        }
+    };
+
+    let signing_key =
+        cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key()?.to_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?;
+    let signed = sign_proof_tx(rpc_config, &proof, &signing_key, account_number, sequence)?;
+    let content = serde_json::to_string_pretty(&signed)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, format!("{}\n", content))?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "path": path.display().to_string(), "sequence": sequence })
+                );
+            } else {
+                println!("Signed transaction written to: {}", path.display());
+                println!("Account number: {}, sequence: {}", account_number, sequence);
+                println!(
+                    "Broadcast it from an online machine with: uhash broadcast {}",
+                    path.display()
+                );
+            }
+        }
+        None => println!("{}", content),
+    }
+
+    Ok(())
+}
+
+fn cmd_broadcast(path: &Path, rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    let content = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(path)?
+    };
+    let signed: SignedTx = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid signed transaction file: {}", e))?;
+
+    if !json {
+        println!("Broadcasting proof from {}...", signed.proof.miner_address);
+        println!("Contract: {}", signed.contract_address);
+        println!("Sequence: {}", signed.sequence);
+    }
+
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let result = rejected_as_error(rt.block_on(client.broadcast_signed(&signed))?)?;
+
+    if json {
+        let out = JsonSendResult {
+            tx_hash: result.tx_hash,
+            success: true,
+            reward: result.reward,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("\nTransaction broadcast successfully!");
+        println!("Transaction hash: {}", result.tx_hash);
+        if let Some(height) = result.height {
+            println!("Committed at height: {}", height);
+        }
+        if let Some(reward) = &result.reward {
+            println!("Reward: {} uLI", reward);
+        }
+        if let Some(link) = rpc_config.explorer_tx_link(&result.tx_hash) {
+            println!("\nView on explorer: {}", link);
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_tx(hash: &str, rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let tx = rt.block_on(client.get_tx(hash))?;
+    let reward = proof_reward(&tx.events, &rpc_config.contract_address);
+
+    if json {
+        let out = JsonTx {
+            success: tx.is_success(),
+            tx,
+            reward,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("Transaction: {}", tx.tx_hash);
+        println!("Height:      {}", tx.height);
+        if tx.is_success() {
+            println!("Result:      success");
+        } else {
+            println!("Result:      failed (code {})", tx.code);
+            println!("Log:         {}", tx.raw_log);
+        }
+        println!(
+            "Gas:         {} used / {} wanted",
+            tx.gas_used, tx.gas_wanted
+        );
+        if let Some(reward) = &reward {
+            println!("Reward:      {} uLI", reward);
+        }
+        if let Some(link) = rpc_config.explorer_tx_link(&tx.tx_hash) {
+            println!("\nView on explorer: {}", link);
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_import_mnemonic(
+    phrase: Option<String>,
+    stdin: bool,
+    language: Option<&str>,
+    confirm_display: bool,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    let phrase = match phrase {
+        Some(p) => Zeroizing::new(p),
+        None if stdin => {
+            let mut input = Zeroizing::new(String::new());
+            std::io::stdin().read_to_string(&mut input)?;
+            Zeroizing::new(input.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+        None => {
+            if json && std::io::stdin().is_terminal() {
+                anyhow::bail!("--phrase or --stdin is required when using --json");
+            }
+            read_mnemonic()?
+        }
+    };
+
+    let wallet = match language {
+        Some(name) => Wallet::from_phrase_in(parse_language(name)?, &phrase)?,
+        None => Wallet::from_phrase(&phrase)?,
+    };
+    let wallet = wallet_opts.derive(wallet)?;
+    let location = wallet_opts.store(&wallet)?;
+
+    if json {
+        #[derive(Serialize)]
+        struct JsonImport<'a> {
+            address: String,
+            path: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            mnemonic: Option<&'a str>,
+        }
+        let out = JsonImport {
+            address: wallet.address_str(),
+            path: location,
+            mnemonic: confirm_display.then_some(phrase.as_str()),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("Wallet imported successfully!");
+        println!("Address: {}", wallet.address_str());
+        println!("Saved to: {}", location);
+        if confirm_display {
+            println!("\nImported phrase (clear your terminal afterwards):");
+            println!("{}", phrase.as_str());
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a mnemonic without echoing it
+///
+/// On a terminal the phrase is typed at a hidden prompt, so it never shows on
+/// screen or in scrollback; piped input is read as one line.
+fn read_mnemonic() -> anyhow::Result<Zeroizing<String>> {
+    let input = if std::io::stdin().is_terminal() {
+        Zeroizing::new(rpassword::prompt_password(
+            "Enter your mnemonic phrase (12-24 words, input is hidden): ",
+        )?)
+    } else {
+        let mut input = Zeroizing::new(String::new());
+        std::io::stdin().read_line(&mut input)?;
+        input
+    };
+    Ok(Zeroizing::new(
+        input.split_whitespace().collect::<Vec<_>>().join(" "),
+    ))
+}
+
+fn cmd_export_mnemonic(wallet_opts: &WalletOpts, json: bool) -> anyhow::Result<()> {
+    let wallet = wallet_opts.load()?;
+    wallet.signing_key()?;
+    let mnemonic = wallet.mnemonic().ok_or_else(|| {
+        anyhow::anyhow!(
+            "This wallet was imported from a private key and has no mnemonic. Use 'uhash export-key' instead."
+        )
+    })?;
+
+    if json {
+        #[derive(Serialize)]
+        struct JsonMnemonic<'a> {
+            mnemonic: &'a str,
+            address: String,
+        }
+        let out = JsonMnemonic {
+            mnemonic: &mnemonic,
+            address: wallet.address_str(),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("WARNING: Keep this mnemonic phrase secret and secure!");
+        println!("\n{}\n", mnemonic.as_str());
+    }
+
+    Ok(())
+}
+
+/// Use the passphrase given on the command line, or prompt for it
+fn read_passphrase(provided: Option<String>, prompt: &str, json: bool) -> anyhow::Result<String> {
+    match provided {
+        Some(p) => Ok(p),
+        None => {
+            if json {
+                anyhow::bail!("--passphrase is required when using --json");
+            }
+            println!("{}", prompt);
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            Ok(input.trim_end_matches(['\r', '\n']).to_string())
+        }
+    }
+}
+
+fn cmd_import_address(address: &str, wallet_opts: &WalletOpts, json: bool) -> anyhow::Result<()> {
+    let wallet = wallet_opts.derive(Wallet::watch_only(address)?)?;
+    if wallet.address().prefix() != wallet_opts.chain.bech32_prefix {
+        anyhow::bail!(
+            "Address prefix '{}' does not match the configured chain (expected '{}')",
+            wallet.address().prefix(),
+            wallet_opts.chain.bech32_prefix
+        );
+    }
+    let location = wallet_opts.store(&wallet)?;
+
+    if json {
+        let out = JsonWallet {
+            address: wallet.address_str(),
+            path: Some(location),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("Watch-only wallet saved!");
+        println!("Address: {}", wallet.address_str());
+        println!("Saved to: {}", location);
+        println!("\nProofs will need signing elsewhere with 'uhash sign-proof'.");
+    }
+
+    Ok(())
+}
+
+fn cmd_import_key(
+    file: &Path,
+    passphrase: Option<String>,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    let armored = std::fs::read_to_string(file)?;
+    let passphrase = read_passphrase(passphrase, "Enter the key passphrase:", json)?;
+
+    let wallet = wallet_opts.derive(Wallet::from_armored(&armored, &passphrase)?)?;
+    let location = wallet_opts.store(&wallet)?;
+
+    if json {
+        let out = JsonWallet {
+            address: wallet.address_str(),
+            path: Some(location),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("Key imported successfully!");
+        println!("Address: {}", wallet.address_str());
+        println!("Saved to: {}", location);
+    }
+
+    Ok(())
+}
+
+fn cmd_export_key(
+    output: Option<&Path>,
+    passphrase: Option<String>,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    let wallet = wallet_opts.load()?;
+    // Fail before prompting if there is no key to export
+    wallet.signing_key()?;
+    let passphrase = read_passphrase(passphrase, "Enter a passphrase to encrypt the key:", json)?;
+    if passphrase.is_empty() {
+        anyhow::bail!("Passphrase must not be empty");
+    }
+
+    let armored = wallet.to_armored(&passphrase)?;
+
+    match output {
+        Some(path) => {
+            write_private_file(path, armored.as_bytes())?;
+            if json {
+                let out = JsonWallet {
+                    address: wallet.address_str(),
+                    path: Some(path.display().to_string()),
+                };
+                println!("{}", serde_json::to_string(&out)?);
+            } else {
+                println!("Key exported to {}", path.display());
+                println!("Import with: cyber keys import <name> {}", path.display());
+            }
+        }
+        None => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "address": wallet.address_str(), "armor": armored })
+                );
+            } else {
+                print!("{}", armored);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_export_key_hex(
+    output: Option<&Path>,
+    yes: bool,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    let wallet = wallet_opts.load()?;
+    let key_hex = wallet.private_key_hex()?;
+
+    if !yes {
+        if json {
+            anyhow::bail!("--yes is required with --hex when using --json");
+        }
+        println!(
+            "WARNING: The raw private key gives full control of {}.",
+            wallet.address_str()
+        );
+        println!("Anyone who sees it can spend your funds. It is NOT encrypted.");
+        println!("Type 'yes' to continue:");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if input.trim() != "yes" {
+            anyhow::bail!("Export cancelled");
+        }
+    }
+
+    match output {
+        Some(path) => {
+            write_private_file(path, key_hex.as_bytes())?;
+            if json {
+                let out = JsonWallet {
+                    address: wallet.address_str(),
+                    path: Some(path.display().to_string()),
+                };
+                println!("{}", serde_json::to_string(&out)?);
+            } else {
+                println!("Private key written to {}", path.display());
+            }
+        }
+        None => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "address": wallet.address_str(), "private_key": key_hex.as_str() })
+                );
+            } else {
+                println!("\n{}\n", key_hex.as_str());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_new_wallet(
+    words: usize,
+    language: &str,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    if wallet_opts.exists()? {
+        anyhow::bail!(
+            "Wallet already exists. Use 'uhash export-mnemonic' to backup, then delete it to create a new one."
+        );
+    }
+
+    let wallet = wallet_opts.derive(Wallet::generate(words, parse_language(language)?)?)?;
+    let location = wallet_opts.store(&wallet)?;
+
+    if json {
+        let out = JsonWallet {
+            address: wallet.address_str(),
+            path: Some(location),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("New wallet created!");
+        println!("Address: {}", wallet.address_str());
+        println!("Saved to: {}", location);
+        println!("\nIMPORTANT: Backup your mnemonic phrase with 'uhash export-mnemonic'");
+    }
+
+    Ok(())
+}
+
+fn cmd_address(
+    qr: bool,
+    png: Option<&Path>,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    let address = wallet_opts.address()?;
+
+    let code = if qr || png.is_some() {
+        Some(qrcode::QrCode::new(address.as_bytes())?)
+    } else {
+        None
+    };
+    if let (Some(code), Some(path)) = (&code, png) {
+        write_qr_png(code, path)?;
+    }
+
+    if json {
+        let out = JsonAddress {
+            address,
+            png: png.map(|p| p.display().to_string()),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+        return Ok(());
+    }
+
+    if let (Some(code), true) = (&code, qr) {
+        // Light modules are drawn as blocks so the code scans on dark terminals
+        use qrcode::render::unicode::Dense1x2;
+        let art = code
+            .render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .quiet_zone(true)
+            .build();
+        println!("{}", art);
+    }
+    println!("{}", address);
+    if let Some(path) = png {
+        println!("QR code saved to: {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Render a QR code as a black-on-white grayscale PNG
+fn write_qr_png(code: &qrcode::QrCode, path: &Path) -> anyhow::Result<()> {
+    const MODULE_PX: usize = 8;
+    const QUIET_ZONE: usize = 4;
+
+    let modules = code.width();
+    let side = (modules + 2 * QUIET_ZONE) * MODULE_PX;
+    let colors = code.to_colors();
+    let mut pixels = vec![0xFFu8; side * side];
+    for y in 0..side {
+        for x in 0..side {
+            let mx = (x / MODULE_PX).wrapping_sub(QUIET_ZONE);
+            let my = (y / MODULE_PX).wrapping_sub(QUIET_ZONE);
+            if mx < modules && my < modules && colors[my * modules + mx] == qrcode::Color::Dark {
+                pixels[y * side + x] = 0x00;
+            }
+        }
+    }
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, side as u32, side as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)?;
+    Ok(())
+}
+
+fn cmd_sign_message(message: &str, wallet_opts: &WalletOpts, json: bool) -> anyhow::Result<()> {
+    let wallet = wallet_opts.load()?;
+    let sig = wallet.sign_arbitrary(message.as_bytes())?;
+
+    if json {
+        #[derive(Serialize)]
+        struct JsonSignedMessage {
+            signer: String,
+            #[serde(flatten)]
+            signature: StdSignature,
+        }
+        let out = JsonSignedMessage {
+            signer: wallet.address_str(),
+            signature: sig,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("Signer:     {}", wallet.address_str());
+        println!("Public key: {}", sig.pub_key.value);
+        println!("Signature:  {}", sig.signature);
+        println!("\nVerify with:");
+        println!(
+            "  uhash verify-message --signer {} --pubkey {} --signature {} '<message>'",
+            wallet.address_str(),
+            sig.pub_key.value,
+            sig.signature
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_verify_message(
+    message: &str,
+    signer: &str,
+    signature: String,
+    pubkey: String,
+    json: bool,
+) -> anyhow::Result<()> {
+    let sig = StdSignature {
+        pub_key: StdPubKey {
+            key_type: uhash::wallet::PUBKEY_TYPE.to_string(),
+            value: pubkey,
+        },
+        signature,
+    };
+    let valid = verify_arbitrary(signer, message.as_bytes(), &sig)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "signer": signer, "valid": valid })
+        );
+    } else if valid {
+        println!("Valid signature from {}", signer);
+    }
+    if !valid {
+        anyhow::bail!("Signature is not valid for {}", signer);
+    }
+
+    Ok(())
+}
+
+fn cmd_validate_address(address: &str, prefix: &str, json: bool) -> anyhow::Result<()> {
+    let (_, kind) = validate_address_with_prefix(address, prefix)?;
+
+    if json {
+        let out = JsonValidAddress {
+            address,
+            valid: true,
+            kind: kind.as_str(),
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("Valid {} address: {}", kind.as_str(), address);
+    }
+
+    Ok(())
+}
+
+fn cmd_benchmark(
+    count: u32,
+    share: Option<DeviceClass>,
+    detailed: bool,
+    telemetry_url: &str,
+    json: bool,
+) -> anyhow::Result<()> {
+    if !json {
+        println!("Running benchmark with {} hashes...", count);
+    }
+
+    let mut hasher = UniversalHash::new();
+    let input = b"benchmark input data for UniversalHash v4";
+    let mut timing = HashTiming::default();
+
+    let start = Instant::now();
+
+    for i in 0..count {
+        let mut data = input.to_vec();
+        data.extend_from_slice(&i.to_le_bytes());
+        if detailed {
+            timing += hasher.hash_timed(&data).1;
+        } else {
+            let _ = hasher.hash(&data);
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let hashrate = count as f64 / elapsed.as_secs_f64();
+    // Average microseconds per hash in each phase
+    let phases = (detailed && count > 0).then(|| {
+        let per_hash = |phase: Duration| phase.as_secs_f64() * 1e6 / count as f64;
+        JsonPhaseTiming {
+            scratchpad_init_us: per_hash(timing.scratchpad_init),
+            rounds_us: per_hash(timing.rounds),
+            finalize_us: per_hash(timing.finalize),
+        }
+    });
+
+    // Opt-in: nothing leaves the machine without --share
+    let shared = match share {
+        Some(device_class) => {
+            let report = BenchmarkReport::new(device_class, hashrate);
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(uhash::telemetry::share(telemetry_url, &report))?;
+            Some(report)
+        }
+        None => None,
+    };
+
+    if json {
+        let out = JsonBenchmark {
+            total_hashes: count,
+            elapsed_s: elapsed.as_secs_f64(),
+            hashrate,
+            params: JsonAlgoParams {
+                chains: uhash_core::CHAINS,
+                scratchpad_kb: uhash_core::SCRATCHPAD_SIZE / 1024,
+                total_mb: uhash_core::TOTAL_MEMORY / (1024 * 1024),
+                rounds: uhash_core::ROUNDS,
+            },
+            phases,
+            shared,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+    } else {
+        println!("\nResults:");
+        println!("  Total hashes: {}", count);
+        println!("  Time elapsed: {:.2}s", elapsed.as_secs_f64());
+        println!("  Hashrate: {:.2} H/s", hashrate);
+
+        println!("\nAlgorithm parameters:");
+        println!("  Chains: {}", uhash_core::CHAINS);
+        println!(
+            "  Memory per chain: {} KB",
+            uhash_core::SCRATCHPAD_SIZE / 1024
+        );
+        println!(
+            "  Total memory: {} MB",
+            uhash_core::TOTAL_MEMORY / (1024 * 1024)
+        );
+        println!("  Rounds: {}", uhash_core::ROUNDS);
 
-    if let Err(e) = result {
-        if json {
-            let err = JsonError {
-                error: e.to_string(),
-            };
-            println!("{}", serde_json::to_string(&err).unwrap());
-        } else {
-            eprintln!("Error: {}", e);
+        if let Some(phases) = &phases {
+            let total = phases.scratchpad_init_us + phases.rounds_us + phases.finalize_us;
+            let share = |us: f64| us * 100.0 / total;
+            println!("\nPhases (average per hash):");
+            for (name, us) in [
+                ("Scratchpad init", phases.scratchpad_init_us),
+                ("Rounds", phases.rounds_us),
+                ("Finalize", phases.finalize_us),
+            ] {
+                println!(
+                    "  {:<16} {:>10.1} us ({:.1}%)",
+                    format!("{}:", name),
+                    us,
+                    share(us)
+                );
+            }
+        }
+
+        if let Some(report) = &shared {
+            println!("\nShared with {}:", telemetry_url);
+            println!("  Device class: {}", report.device_class);
+            println!("  CPU: {}", report.cpu_model);
+            println!("  Threads: {}", report.threads);
+            println!("  OS: {} ({})", report.os, report.arch);
         }
-        std::process::exit(1);
     }
+
+    Ok(())
 }
 
-/// Build RPC config from CLI args
-fn build_rpc_config(
-    rpc_url: Option<&str>,
-    contract: Option<&str>,
-    fee: u128,
-) -> uhash::rpc::RpcConfig {
-    let mut config = uhash::rpc::RpcConfig::default();
-    if let Some(url) = rpc_url {
-        config.rpc_url = url.to_string();
-        config.lcd_url = url.replace("rpc", "lcd");
+fn cmd_devices(telemetry_url: &str, json: bool) -> anyhow::Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let table: DeviceTable = rt.block_on(uhash::telemetry::fetch_devices(telemetry_url))?;
+
+    if json {
+        println!("{}", serde_json::to_string(&table)?);
+        return Ok(());
     }
-    if let Some(addr) = contract {
-        config.contract_address = addr.to_string();
+    if table.devices.is_empty() {
+        println!("No benchmarks shared yet. Add yours with: uhash benchmark --share");
+        return Ok(());
     }
-    config.fee_amount = fee;
-    config
-}
 
-/// A valid proof found by a mining thread
-struct FoundProof {
-    hash: Vec<u8>,
-    nonce: u64,
-    timestamp: u64,
-}
-
-fn cmd_mine(
-    threads: Option<usize>,
-    difficulty_override: Option<u32>,
-    no_submit: bool,
-    rpc_config: &uhash::rpc::RpcConfig,
-    json: bool,
-) -> anyhow::Result<()> {
-    let wallet_path = default_wallet_path();
+    println!(
+        "{:<8} {:<44} {:>7} {:>8} {:>10}",
+        "Class", "CPU", "Threads", "Samples", "H/s"
+    );
+    for device in &table.devices {
+        println!(
+            "{:<8} {:<44} {:>7} {:>8} {:>10.1}",
+            device.device_class.to_string(),
+            device.cpu_model,
+            device.threads,
+            device.samples,
+            device.median_hashrate
+        );
+    }
 
-    if !wallet_path.exists() {
-        anyhow::bail!(
-            "No wallet found. Create one with 'uhash new-wallet' or 'uhash import-mnemonic'"
+    println!("\nMedian single-thread hashrate by class:");
+    for class in &table.classes {
+        let ratio = table
+            .ratio_to_desktop(class.device_class)
+            .map(|r| format!("  ({:.2}x desktop)", r))
+            .unwrap_or_default();
+        println!(
+            "  {:<8} {:>10.1} H/s from {} reports{}",
+            class.device_class.to_string(),
+            class.median_hashrate,
+            class.samples,
+            ratio
         );
     }
+    Ok(())
+}
 
-    let wallet = Wallet::load_from_file(&wallet_path)?;
-    let address = wallet.address_str();
+/// How long `profit` benchmarks the device when no hashrate is given
+const PROFIT_BENCHMARK_TIME: Duration = Duration::from_secs(5);
 
-    // Create RPC client
-    let client = RpcClient::with_config(rpc_config.clone());
+/// `profit` arguments
+struct ProfitArgs {
+    power_watts: f64,
+    electricity: f64,
+    hashrate: Option<f64>,
+    threads: Option<usize>,
+    difficulty: Option<u32>,
+    /// From the command line; the price feed fills in the rest
+    prices: Prices,
+    price_feed: Option<String>,
+}
 
+fn cmd_profit(args: ProfitArgs, rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    let client = rpc_client(rpc_config, json)?;
     let rt = tokio::runtime::Runtime::new()?;
 
-    // Fetch difficulty from contract (unless overridden)
-    let difficulty = if let Some(d) = difficulty_override {
-        if !json {
-            println!("Using difficulty override: {} bits", d);
-        }
-        d
-    } else {
-        if !json {
-            println!("Fetching difficulty from contract...");
-        }
-        match rt.block_on(client.get_difficulty()) {
-            Ok(d) => {
-                if !json {
-                    println!("Contract difficulty: {} bits", d);
-                }
-                d
-            }
-            Err(e) => {
-                if !json {
-                    eprintln!(
-                        "Warning: Could not fetch difficulty ({}), using default 16",
-                        e
-                    );
-                }
-                16
+    let difficulty = match args.difficulty {
+        Some(difficulty) => difficulty,
+        None => rt.block_on(client.get_difficulty())?,
+    };
+    let base_reward = uhash::profit::parse_reward(&rt.block_on(client.get_config())?.base_reward)?;
+    let prices = match &args.price_feed {
+        Some(url) => args
+            .prices
+            .or(rt.block_on(uhash::profit::fetch_prices(url))?),
+        None => args.prices,
+    };
+    let hashrate = match args.hashrate {
+        Some(hashrate) => hashrate,
+        None => {
+            let threads = args.threads.unwrap_or_else(num_cpus::get);
+            if !json {
+                println!(
+                    "Measuring hashrate on {} threads for {}s...",
+                    threads,
+                    PROFIT_BENCHMARK_TIME.as_secs()
+                );
             }
+            measure_hashrate(threads, PROFIT_BENCHMARK_TIME)
         }
     };
+    let gas_limit = rpc_config.gas_limit.unwrap_or(SUBMIT_PROOF_GAS);
 
-    // Query mining seed from contract
-    if !json {
-        println!("Fetching seed from contract...");
-    }
-    let epoch_seed = rt.block_on(client.get_seed()).unwrap_or_else(|e| {
-        if !json {
-            eprintln!("Warning: Could not fetch seed ({}), using zeros", e);
-        }
-        [0u8; 32]
-    });
-
-    let num_threads = threads.unwrap_or_else(num_cpus::get);
+    let params = ProfitParams {
+        hashrate,
+        difficulty,
+        base_reward,
+        power_watts: args.power_watts,
+        electricity_price: args.electricity,
+        fee_per_proof: fee_for_gas(rpc_config, gas_limit) as f64,
+        prices,
+    };
+    let estimate = uhash::profit::estimate(&params)?;
 
     if json {
-        let started = JsonMineStarted {
-            event: "mine_started",
-            contract: rpc_config.contract_address.clone(),
-            address: address.clone(),
-            difficulty,
-            threads: num_threads,
-            seed: hex::encode(epoch_seed),
-            auto_submit: !no_submit,
+        let out = JsonProfit {
+            params,
+            measured: args.hashrate.is_none(),
+            estimate,
         };
-        println!("{}", serde_json::to_string(&started)?);
-    } else {
-        println!("\n=== UniversalHash Miner ===");
-        println!("Contract: {}", rpc_config.contract_address);
-        println!("Address:  {}", address);
-        println!("Difficulty: {} bits", difficulty);
-        println!("Threads: {}", num_threads);
-        println!("Seed: {}", hex::encode(epoch_seed));
-        println!("Auto-submit: {}", if no_submit { "off" } else { "on" });
-        println!("===========================\n");
+        println!("{}", serde_json::to_string(&out)?);
+        return Ok(());
     }
 
-    // Shared state for threads
-    let total_hashes = Arc::new(AtomicU64::new(0));
-    let found = Arc::new(std::sync::Mutex::new(None::<FoundProof>));
-    let stop = Arc::new(AtomicBool::new(false));
-
-    // Get signing key for auto-submit
-    let signing_key =
-        cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key().to_bytes())
-            .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?;
+    let price = |price: Option<f64>| price.map_or("-".to_string(), |p| format!("{}", p));
+    println!("\nHashrate:    {:.1} H/s", params.hashrate);
+    println!("Difficulty:  {} bits", params.difficulty);
+    println!(
+        "Reward:      {} LI per proof",
+        params.base_reward as f64 / uhash::profit::ULI_PER_LI
+    );
+    println!(
+        "Power:       {} W at {} per kWh",
+        params.power_watts, params.electricity_price
+    );
+    println!(
+        "Prices:      LI {}, BOOT {}",
+        price(prices.li_price),
+        price(prices.boot_price)
+    );
 
-    let mut proofs_submitted: u64 = 0;
+    let amount = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.4}", v));
+    println!(
+        "\n{:<6} {:>10} {:>12} {:>12} {:>10} {:>12} {:>12}",
+        "", "Proofs", "LI", "Revenue", "Power", "Fees (BOOT)", "Profit"
+    );
+    for (period, days) in [("Day", 1.0), ("Week", 7.0), ("Month", 30.0)] {
+        println!(
+            "{:<6} {:>10.2} {:>12.4} {:>12} {:>10.4} {:>12.2} {:>12}",
+            period,
+            estimate.proofs_per_day * days,
+            estimate.li_per_day * days,
+            amount(estimate.revenue_per_day.map(|v| v * days)),
+            estimate.power_cost_per_day * days,
+            estimate.fees_per_day * days,
+            amount(estimate.profit_per_day.map(|v| v * days)),
+        );
+    }
 
-    loop {
-        // Reset for new round
-        stop.store(false, Ordering::SeqCst);
-        *found.lock().unwrap() = None;
-        total_hashes.store(0, Ordering::Relaxed);
-
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        let start = Instant::now();
-
-        // Spawn mining threads
-        let mut handles = Vec::with_capacity(num_threads);
-        for thread_id in 0..num_threads {
-            let address = address.clone();
-            let total_hashes = Arc::clone(&total_hashes);
-            let found = Arc::clone(&found);
-            let stop = Arc::clone(&stop);
+    println!();
+    match estimate.break_even_li_price {
+        Some(li_price) => println!("Break-even:  LI price {:.6}", li_price),
+        None => println!("Break-even:  needs a BOOT price to count fees (--boot-price)"),
+    }
+    if let Some(difficulty) = estimate.break_even_difficulty {
+        println!(
+            "             difficulty {:.1} bits at this LI price",
+            difficulty
+        );
+    }
+    Ok(())
+}
 
-            // Each thread uses interleaved nonces: thread_id, thread_id + N, thread_id + 2N, ...
-            // This keeps all nonces small and avoids JSON precision issues with u64 > 2^53
-            let handle = std::thread::spawn(move || {
+/// Hashes per second of `threads` threads hashing for `duration`
+fn measure_hashrate(threads: usize, duration: Duration) -> f64 {
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads.max(1))
+        .map(|thread_id| {
+            std::thread::spawn(move || {
                 let mut hasher = UniversalHash::new();
-                let mut nonce = thread_id as u64;
-
-                while !stop.load(Ordering::Relaxed) {
-                    let mut input = Vec::with_capacity(128);
-                    input.extend_from_slice(&epoch_seed);
-                    input.extend_from_slice(address.as_bytes());
-                    input.extend_from_slice(&timestamp.to_le_bytes());
-                    input.extend_from_slice(&nonce.to_le_bytes());
-
-                    let result = hasher.hash(&input);
-                    total_hashes.fetch_add(1, Ordering::Relaxed);
-
-                    if meets_difficulty(&result, difficulty) {
-                        let mut guard = found.lock().unwrap();
-                        if guard.is_none() {
-                            *guard = Some(FoundProof {
-                                hash: result.to_vec(),
-                                nonce,
-                                timestamp,
-                            });
-                            stop.store(true, Ordering::SeqCst);
-                        }
-                        return;
-                    }
-
-                    nonce += num_threads as u64;
+                let mut count: u64 = 0;
+                while start.elapsed() < duration {
+                    let mut input = b"profit benchmark".to_vec();
+                    input.extend_from_slice(&(thread_id as u64).to_le_bytes());
+                    input.extend_from_slice(&count.to_le_bytes());
+                    let _ = hasher.hash(&input);
+                    count += 1;
                 }
-            });
-            handles.push(handle);
-        }
-
-        // Monitor progress while threads work
-        if !json {
-            loop {
-                std::thread::sleep(Duration::from_secs(2));
-
-                let hashes = total_hashes.load(Ordering::Relaxed);
-                let elapsed = start.elapsed().as_secs_f64();
-                let hashrate = if elapsed > 0.0 {
-                    hashes as f64 / elapsed
-                } else {
-                    0.0
-                };
+                count
+            })
+        })
+        .collect();
+    let total: u64 = handles.into_iter().map(|h| h.join().unwrap_or(0)).sum();
+    total as f64 / start.elapsed().as_secs_f64()
+}
 
-                if stop.load(Ordering::Relaxed) {
-                    break;
-                }
+/// Rows the simulation table shows at most; `--json` has every window
+const SIMULATION_ROWS: usize = 24;
 
-                print!(
-                    "\rHashrate: {:.0} H/s | Hashes: {} | Time: {:.0}s | Proofs sent: {}",
-                    hashrate, hashes, elapsed, proofs_submitted
-                );
-                use std::io::Write;
-                std::io::stdout().flush().ok();
-            }
-        }
+fn cmd_simulate_difficulty(
+    params: SimParams,
+    scenario: Scenario,
+    json: bool,
+) -> anyhow::Result<()> {
+    let report = uhash::sim::simulate(&params, &scenario)?;
 
-        // Wait for all threads to finish
-        for handle in handles {
-            let _ = handle.join();
-        }
+    if json {
+        let out = JsonSimulation {
+            params,
+            scenario,
+            report,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+        return Ok(());
+    }
 
-        // Process found proof
-        let proof_data = found.lock().unwrap().take();
-        if let Some(proof) = proof_data {
-            let hashes = total_hashes.load(Ordering::Relaxed);
-            let elapsed = start.elapsed().as_secs_f64();
+    let target = params.target_proofs_per_window;
+    println!(
+        "Retarget every {}s aiming for {} proofs ({} runs, seed {})\n",
+        params.window_secs, target, params.runs, params.seed
+    );
+    println!(
+        "{:>7} {:>8} {:>12} {:>16} {:>8}",
+        "Window", "Time", "Hashrate", "Difficulty", "Proofs"
+    );
+    let step = report.windows.len().div_ceil(SIMULATION_ROWS);
+    let last = report.windows.len() - 1;
+    for stats in report
+        .windows
+        .iter()
+        .filter(|w| w.window % step == 0 || w.window == last)
+    {
+        println!(
+            "{:>7} {:>7.1}h {:>8.0} H/s {:>6.1} ({:>2}-{:>2}) {:>8.1}",
+            stats.window,
+            (stats.window as u64 * params.window_secs) as f64 / 3600.0,
+            stats.hashrate,
+            stats.difficulty_mean,
+            stats.difficulty_min,
+            stats.difficulty_max,
+            stats.proofs_mean
+        );
+    }
+    if step > 1 {
+        println!("(one row per {} windows; --json lists all)", step);
+    }
 
-            if json {
-                let event = JsonProofFound {
-                    event: "proof_found",
-                    hash: hex::encode(&proof.hash),
-                    nonce: proof.nonce,
-                    timestamp: proof.timestamp,
-                    hashes_computed: hashes,
-                    hashrate: hashes as f64 / elapsed,
-                };
-                println!("{}", serde_json::to_string(&event)?);
-            } else {
-                println!("\n\nFound valid proof!");
-                println!("  Hash:      {}", hex::encode(&proof.hash));
-                println!("  Nonce:     {}", proof.nonce);
-                println!("  Timestamp: {}", proof.timestamp);
-                println!(
-                    "  Hashes:    {} ({:.0} H/s)",
-                    hashes,
-                    hashes as f64 / elapsed
-                );
-            }
+    println!(
+        "\nProofs per window: {:.1} (target {})",
+        report.proofs_mean, target
+    );
+    println!(
+        "Windows between {} and {} proofs: {:.0}%",
+        target / 2,
+        target,
+        report.on_target_fraction * 100.0
+    );
+    println!(
+        "Windows that moved difficulty: {:.0}%",
+        report.retarget_fraction * 100.0
+    );
+    let final_hashrate = report.windows[last].hashrate;
+    println!(
+        "Equilibrium at the final hashrate: {} bits ({:.1} proofs per window expected)",
+        report.final_equilibrium,
+        uhash::sim::expected_proofs(final_hashrate, params.window_secs, report.final_equilibrium)
+    );
+    Ok(())
+}
 
-            if no_submit {
-                if !json {
-                    println!("\nTo submit this proof, run:");
-                    println!(
-                        "  uhash send --hash {} --nonce {} --timestamp {}",
-                        hex::encode(&proof.hash),
-                        proof.nonce,
-                        proof.timestamp
-                    );
-                }
-                // In no-submit mode, exit after first proof
-                break;
-            }
+fn cmd_audit(
+    rpc_config: &RpcConfig,
+    from_height: u64,
+    to_height: Option<u64>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let mut auditor = Auditor::new();
+    let mut history = History::new();
+    let mut summary = JsonAuditFinished {
+        event: "audit_finished",
+        proofs: 0,
+        verified: 0,
+        discrepancies: 0,
+        first_height: None,
+        last_height: None,
+    };
 
-            // Auto-submit
-            let submission = ProofSubmission {
-                hash: hex::encode(&proof.hash),
-                nonce: proof.nonce,
-                timestamp: proof.timestamp,
-                miner_address: address.clone(),
-            };
+    if !json {
+        println!(
+            "Auditing proofs accepted by {} from height {}...",
+            rpc_config.contract_address, from_height
+        );
+    }
+    let mut page = 1;
+    loop {
+        let batch = rt.block_on(client.proof_submissions(from_height, to_height, page))?;
+        for proof in &batch.proofs {
+            let (seeds, difficulty) = rt
+                .block_on(async {
+                    let seeds = history.candidate_seeds(&client, proof.height).await?;
+                    let difficulty = history.proof_difficulty(&client, proof).await?;
+                    Ok::<_, RpcError>((seeds, difficulty))
+                })
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Could not read the contract state at height {} (auditing needs an archive LCD): {}",
+                        proof.height,
+                        e
+                    )
+                })?;
 
-            // Check if account exists; if not, relay the proof instead of direct submit
-            let is_new_account = !rt.block_on(client.account_exists(&address));
-            if is_new_account {
-                if !json {
-                    println!("\nNew account — relaying first proof via relay service...");
-                }
-                match rt.block_on(client.relay_proof(&submission)) {
-                    Ok(tx_hash) => {
-                        proofs_submitted += 1;
-                        if json {
-                            let event = JsonProofSubmitted {
-                                event: "proof_submitted",
-                                tx_hash: tx_hash.clone(),
-                                success: true,
-                                proofs_submitted,
-                            };
-                            println!("{}", serde_json::to_string(&event)?);
-                        } else {
-                            println!("Proof relayed! TX: {}", tx_hash);
-                            println!("View: https://cyb.ai/network/bostrom/tx/{}", tx_hash);
-                            println!("Waiting for account creation...");
-                        }
-                        // Wait for the relay TX to be included so account exists for next proof
-                        std::thread::sleep(Duration::from_secs(7));
-                    }
-                    Err(e) => {
-                        if json {
-                            let event = JsonProofSubmitted {
-                                event: "proof_submitted",
-                                tx_hash: String::new(),
-                                success: false,
-                                proofs_submitted,
-                            };
-                            println!("{}", serde_json::to_string(&event)?);
-                        } else {
-                            eprintln!("Relay failed: {}. Continuing to mine...", e);
-                        }
-                    }
-                }
-            } else {
-                // Normal direct submit
-                if !json {
-                    println!("\nSubmitting proof to contract...");
-                }
-                match rt.block_on(client.submit_proof(submission, &signing_key)) {
-                    Ok(result) => {
-                        proofs_submitted += 1;
-                        if json {
-                            let event = JsonProofSubmitted {
-                                event: "proof_submitted",
-                                tx_hash: result.tx_hash,
-                                success: true,
-                                proofs_submitted,
-                            };
-                            println!("{}", serde_json::to_string(&event)?);
-                        } else {
-                            println!("Proof accepted! TX: {}", result.tx_hash);
-                            println!("View: https://cyb.ai/network/bostrom/tx/{}", result.tx_hash);
-                        }
-                    }
-                    Err(e) => {
-                        if json {
-                            let event = JsonProofSubmitted {
-                                event: "proof_submitted",
-                                tx_hash: String::new(),
-                                success: false,
-                                proofs_submitted,
-                            };
-                            println!("{}", serde_json::to_string(&event)?);
-                        } else {
-                            eprintln!("Submit failed: {}. Continuing to mine...", e);
-                        }
+            summary.proofs += 1;
+            summary.first_height.get_or_insert(proof.height);
+            summary.last_height = Some(proof.height);
+            match auditor.check(proof, &seeds, difficulty) {
+                Ok(()) => summary.verified += 1,
+                Err(discrepancy) => {
+                    summary.discrepancies += 1;
+                    if json {
+                        let out = JsonDiscrepancy {
+                            event: "discrepancy",
+                            proof,
+                            discrepancy: &discrepancy,
+                            reason: discrepancy.to_string(),
+                        };
+                        println!("{}", serde_json::to_string(&out)?);
+                    } else {
+                        println!(
+                            "\rHeight {} tx {} ({}): {}",
+                            proof.height, proof.tx_hash, proof.miner, discrepancy
+                        );
                     }
                 }
             }
-
             if !json {
-                println!("\nContinuing to mine...\n");
+                print!(
+                    "\rChecked {} proofs (height {})",
+                    summary.proofs, proof.height
+                );
+                let _ = std::io::Write::flush(&mut std::io::stdout());
             }
-            // Loop continues — mine next proof
-        } else {
-            // Interrupted without finding proof
+        }
+        if !batch.more {
             break;
         }
+        page += 1;
     }
 
+    if json {
+        println!("{}", serde_json::to_string(&summary)?);
+    } else {
+        match (summary.first_height, summary.last_height) {
+            (Some(first), Some(last)) => println!(
+                "\rChecked {} proofs from heights {}-{}: {} verified, {} discrepancies",
+                summary.proofs, first, last, summary.verified, summary.discrepancies
+            ),
+            _ => println!("No proofs accepted from height {}", from_height),
+        }
+    }
+    if summary.discrepancies > 0 {
+        anyhow::bail!(
+            "{} of {} proofs failed re-verification",
+            summary.discrepancies,
+            summary.proofs
+        );
+    }
     Ok(())
 }
 
-fn cmd_send(
-    hash_hex: &str,
-    nonce: u64,
-    timestamp: u64,
-    rpc_config: &uhash::rpc::RpcConfig,
+fn cmd_network_hashrate(
+    rpc_config: &RpcConfig,
+    samples: usize,
+    confidence: f64,
     json: bool,
 ) -> anyhow::Result<()> {
-    let wallet_path = default_wallet_path();
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
 
-    if !wallet_path.exists() {
-        anyhow::bail!("No wallet found. Create one with 'uhash new-wallet'");
+    if !json {
+        println!("Sampling the last {} accepted proofs...", samples);
     }
+    let (proofs, difficulty) = rt.block_on(async {
+        tokio::try_join!(client.recent_proofs(samples), client.get_difficulty())
+    })?;
+    // Contracts that do not report a proof's difficulty get the current one
+    let sample: Vec<ProofSample> = proofs
+        .iter()
+        .map(|proof| ProofSample {
+            time: proof.time,
+            difficulty: proof.event.difficulty.unwrap_or(difficulty),
+        })
+        .collect();
+    let estimate = uhash::network::estimate_hashrate(&sample, confidence)?;
+    let heights = proofs.iter().map(|proof| proof.event.height);
+    let (from_height, to_height) = (
+        heights.clone().min().unwrap_or(0),
+        heights.max().unwrap_or(0),
+    );
 
-    let wallet = Wallet::load_from_file(&wallet_path)?;
+    if json {
+        let out = JsonNetworkHashrate {
+            estimate,
+            difficulty,
+            from_height,
+            to_height,
+        };
+        println!("{}", serde_json::to_string(&out)?);
+        return Ok(());
+    }
 
-    if !json {
-        println!("Submitting proof to contract...");
-        println!("Contract: {}", rpc_config.contract_address);
-        println!("From: {}", wallet.address_str());
-        println!("Hash: {}", hash_hex);
-        println!("Nonce: {}", nonce);
-        println!("Timestamp: {}", timestamp);
+    println!(
+        "\nNetwork hashrate: {:.0} H/s ({:.0}% confidence: {:.0}-{:.0} H/s)",
+        estimate.hashrate,
+        estimate.confidence * 100.0,
+        estimate.low,
+        estimate.high
+    );
+    println!(
+        "Sampled:          {} proofs over {}s (heights {}-{})",
+        estimate.proofs, estimate.span_secs, from_height, to_height
+    );
+    println!(
+        "Difficulty:       {} bits now, {:.1} on average over the sample",
+        difficulty, estimate.difficulty_mean
+    );
+    Ok(())
+}
+
+fn cmd_endpoints(rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let statuses = rt.block_on(client.select_fastest_endpoint());
+
+    if json {
+        println!("{}", serde_json::to_string(&statuses)?);
+    } else {
+        print_endpoint_statuses(&statuses);
     }
 
-    // Create RPC client
-    let client = RpcClient::with_config(rpc_config.clone());
+    if statuses.iter().all(|s| !s.is_healthy()) {
+        anyhow::bail!("No healthy endpoints");
+    }
+    Ok(())
+}
 
-    // Build proof submission
-    let proof = ProofSubmission {
-        hash: hash_hex.to_string(),
-        nonce,
-        timestamp,
-        miner_address: wallet.address_str(),
-    };
+fn print_endpoint_statuses(statuses: &[EndpointStatus]) {
+    for status in statuses {
+        let marker = if status.active { "*" } else { " " };
+        match (&status.error, status.latency) {
+            (None, Some(latency)) => println!(
+                "{} {}  ok  {:>6.0} ms  height {}",
+                marker,
+                status.url,
+                latency.as_secs_f64() * 1000.0,
+                status.height.map_or("?".to_string(), |h| h.to_string())
+            ),
+            (error, _) => println!(
+                "{} {}  DOWN  {}",
+                marker,
+                status.url,
+                error.as_deref().unwrap_or("no response")
+            ),
+        }
+    }
+    println!("\n* = endpoint that would be used (fastest healthy)");
+}
+
+fn cmd_devnet(listen: SocketAddr, params: DevnetParams, json: bool) -> anyhow::Result<()> {
+    let listener = std::net::TcpListener::bind(listen)?;
+    let url = format!("http://{}", listener.local_addr()?);
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "devnet_started",
+                "url": url,
+                "contract": params.contract_address,
+                "difficulty": params.difficulty,
+                "seed_interval": params.seed_interval.as_secs(),
+            })
+        );
+    } else {
+        println!("Mock chain listening on {}", url);
+        println!("Contract:      {}", params.contract_address);
+        println!("Difficulty:    {} bits", params.difficulty);
+        println!("Seed interval: {}s", params.seed_interval.as_secs());
+        println!("\nMine against it with:");
+        println!("  uhash --rpc {} mine", url);
+        println!("\nPress Ctrl-C to stop.");
+    }
+
+    let devnet = Devnet::new(params);
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(devnet.serve(listener))
+}
+
+fn cmd_dashboard(listen: SocketAddr, status_path: &Path, json: bool) -> anyhow::Result<()> {
+    let listener = std::net::TcpListener::bind(listen)?;
+    let url = format!("http://{}", listener.local_addr()?);
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "event": "dashboard_started",
+                "url": url,
+                "status_file": status_path.display().to_string(),
+            })
+        );
+    } else {
+        println!("Dashboard listening on {}", url);
+        println!("Miner status: {}", status_path.display());
+        println!("\nPress Ctrl-C to stop.");
+    }
 
-    // Get signing key from wallet
-    let signing_key =
-        cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key().to_bytes())
-            .map_err(|e| anyhow::anyhow!("Invalid signing key: {}", e))?;
+    let dashboard = uhash::dashboard::Dashboard::new(status_path);
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(dashboard.serve(listener))
+}
 
-    // Submit using tokio runtime
+fn cmd_status(rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    let client = rpc_client(rpc_config, json)?;
     let rt = tokio::runtime::Runtime::new()?;
-    let result = rt.block_on(client.submit_proof(proof, &signing_key))?;
+
+    if !json {
+        println!("Querying contract status...");
+        println!("Contract: {}", rpc_config.contract_address);
+    }
+
+    let out = rt.block_on(query_status(&client, &rpc_config.contract_address))?;
 
     if json {
-        let out = JsonSendResult {
-            tx_hash: result.tx_hash,
-            success: true,
-        };
         println!("{}", serde_json::to_string(&out)?);
     } else {
-        println!("\nProof submitted successfully!");
-        println!("Transaction hash: {}", result.tx_hash);
-        println!(
-            "\nView on explorer: https://cyb.ai/network/bostrom/tx/{}",
-            result.tx_hash
-        );
+        println!("\n=== Contract Status ===");
+        println!("Seed:       {}", out.seed);
+        if let Some(expires_at) = out.seed_expires_at {
+            println!(
+                "Seed rotates in: {}s",
+                expires_at.saturating_sub(unix_now())
+            );
+        }
+        println!("Difficulty: {} bits", out.difficulty);
+        println!("Min profitable: {} bits", out.min_profitable_difficulty);
+        if let Some(base_reward) = &out.base_reward {
+            println!("Base reward:    {} uLI", base_reward);
+        }
+        if let Some(period_duration) = out.period_duration {
+            println!("Period duration: {}s", period_duration);
+        }
+        if let Some(paused) = out.paused {
+            println!("Paused: {}", paused);
+        }
+        println!("=======================");
     }
 
     Ok(())
 }
 
-fn cmd_import_mnemonic(
-    phrase: Option<String>,
-    wallet_path: Option<PathBuf>,
+/// Query seed, difficulty and (best-effort) contract configuration
+async fn query_status(client: &impl ChainClient, contract: &str) -> anyhow::Result<JsonStatus> {
+    let seed = client.get_seed().await?;
+    let difficulty = client.get_difficulty().await?;
+    let min_profitable = client.get_min_profitable_difficulty().await.unwrap_or(0);
+
+    // Try to query full config for extra fields
+    let config_resp = client.get_config().await.ok();
+
+    Ok(JsonStatus {
+        contract: contract.to_string(),
+        seed: hex::encode(seed.seed),
+        seed_expires_at: seed.expires_at,
+        difficulty,
+        min_profitable_difficulty: min_profitable,
+        base_reward: config_resp.as_ref().map(|c| c.base_reward.clone()),
+        period_duration: config_resp.as_ref().map(|c| c.period_duration),
+        paused: config_resp.as_ref().map(|c| c.paused),
+    })
+}
+
+fn cmd_faucet(
+    address: Option<String>,
+    wait: u64,
+    wallet_opts: &WalletOpts,
+    rpc_config: &RpcConfig,
     json: bool,
 ) -> anyhow::Result<()> {
-    let phrase = match phrase {
-        Some(p) => p,
-        None => {
-            if json {
-                anyhow::bail!("--phrase is required when using --json");
-            }
-            println!("Enter your 24-word mnemonic phrase:");
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            input.trim().to_string()
-        }
+    let address = match address {
+        Some(address) => address,
+        None => wallet_opts.address()?,
     };
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    if let Some((account_number, sequence)) = rt.block_on(client.get_account(&address))? {
+        if json {
+            let out = JsonFaucet {
+                address,
+                requested: false,
+                account_number: Some(account_number),
+                sequence: Some(sequence),
+            };
+            println!("{}", serde_json::to_string(&out)?);
+        } else {
+            println!("Account {} already exists on chain", address);
+        }
+        return Ok(());
+    }
 
-    let wallet = Wallet::from_phrase(&phrase)?;
-    let path = wallet_path
-        .unwrap_or_else(|| ensure_wallet_dir().expect("Failed to create wallet directory"));
-
-    wallet.save_to_file(&path)?;
-
+    let account = rt.block_on(request_faucet(&client, &address, wait, json))?;
     if json {
-        let out = JsonWallet {
-            address: wallet.address_str(),
-            path: Some(path.display().to_string()),
+        let (account_number, sequence) = account.unzip();
+        let out = JsonFaucet {
+            address,
+            requested: true,
+            account_number,
+            sequence,
         };
         println!("{}", serde_json::to_string(&out)?);
+    } else if account.is_some() {
+        println!("Account {} is on chain and can sign transactions", address);
     } else {
-        println!("Wallet imported successfully!");
-        println!("Address: {}", wallet.address_str());
-        println!("Saved to: {}", path.display());
+        println!(
+            "Requested funds for {}; the account appears once the transfer is committed",
+            address
+        );
     }
-
     Ok(())
 }
 
-fn cmd_export_mnemonic(wallet_path: Option<PathBuf>, json: bool) -> anyhow::Result<()> {
-    let path = wallet_path.unwrap_or_else(default_wallet_path);
-
-    if !path.exists() {
-        anyhow::bail!("No wallet found at {}", path.display());
+/// Ask the faucet to fund `address` and wait up to `wait` seconds for the
+/// account (`None` without waiting)
+async fn request_faucet(
+    client: &RpcClient,
+    address: &str,
+    wait: u64,
+    json: bool,
+) -> anyhow::Result<Option<(u64, u64)>> {
+    if !json {
+        println!("Requesting funds for {} from the faucet...", address);
     }
+    client.request_faucet(address).await?;
+    if wait == 0 {
+        return Ok(None);
+    }
+    let account = client
+        .wait_for_account(address, Duration::from_secs(wait))
+        .await?;
+    Ok(Some(account))
+}
 
-    let wallet = Wallet::load_from_file(&path)?;
+fn cmd_stats(
+    address: Option<String>,
+    wallet_opts: &WalletOpts,
+    rpc_config: &RpcConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    let address = match address {
+        Some(address) => address,
+        None => wallet_opts.address()?,
+    };
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let stats = match rt.block_on(client.get_miner_stats(&address)) {
+        Ok(stats) => stats,
+        Err(e @ RpcError::ContractQuery(_)) => {
+            anyhow::bail!("The contract does not expose miner stats ({})", e)
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     if json {
-        #[derive(Serialize)]
-        struct JsonMnemonic {
-            mnemonic: String,
-            address: String,
-        }
-        let out = JsonMnemonic {
-            mnemonic: wallet.mnemonic(),
-            address: wallet.address_str(),
-        };
-        println!("{}", serde_json::to_string(&out)?);
+        println!("{}", serde_json::to_string(&stats)?);
     } else {
-        println!("WARNING: Keep this mnemonic phrase secret and secure!");
-        println!("\n{}\n", wallet.mnemonic());
+        println!("Address:          {}", stats.address);
+        println!("Proofs submitted: {}", stats.proofs_submitted);
+        match stats.last_proof_time {
+            Some(time) => {
+                println!(
+                    "Last proof:       {} ({}s ago)",
+                    time,
+                    unix_now().saturating_sub(time)
+                );
+            }
+            None => println!("Last proof:       never"),
+        }
+        println!("Total rewards:    {} uLI", stats.total_rewards);
     }
 
     Ok(())
 }
 
-fn cmd_new_wallet(wallet_path: Option<PathBuf>, json: bool) -> anyhow::Result<()> {
-    let path = wallet_path
-        .unwrap_or_else(|| ensure_wallet_dir().expect("Failed to create wallet directory"));
+/// Summarize the stats database over the last `period`, or export it to `output`
+#[cfg(feature = "stats")]
+fn cmd_report(
+    period: &str,
+    export: Option<&str>,
+    output: &Path,
+    settings: &StatsConfig,
+    json: bool,
+) -> anyhow::Result<()> {
+    use uhash::stats::ExportFormat;
 
-    if path.exists() {
+    let period_secs = uhash::stats::parse_period(period)?;
+    let format = export.map(str::parse::<ExportFormat>).transpose()?;
+    if format.is_some_and(|f| !f.is_supported()) {
+        anyhow::bail!("Parquet export is not available: rebuild with `--features parquet`");
+    }
+    let path = settings.db_path();
+    if !path.exists() {
         anyhow::bail!(
-            "Wallet already exists at {}. Use 'uhash export-mnemonic' to backup, then delete the file to create a new one.",
+            "No mining stats recorded yet ({} does not exist)",
             path.display()
         );
     }
+    let db = uhash::stats::StatsDb::open(&path)?;
+    let since = unix_now().saturating_sub(period_secs);
+
+    if let Some(format) = format {
+        let files = uhash::stats::export(&db, since, format, output)?;
+        if json {
+            let files = files.iter().map(|f| f.display().to_string()).collect();
+            println!(
+                "{}",
+                serde_json::to_string(&JsonExport {
+                    format: format.extension(),
+                    files,
+                })?
+            );
+        } else {
+            for file in files {
+                println!("Wrote {}", file.display());
+            }
+        }
+        return Ok(());
+    }
 
-    let wallet = Wallet::new()?;
-    wallet.save_to_file(&path)?;
+    let report = db.report(since)?;
 
     if json {
-        let out = JsonWallet {
-            address: wallet.address_str(),
-            path: Some(path.display().to_string()),
-        };
-        println!("{}", serde_json::to_string(&out)?);
-    } else {
-        println!("New wallet created!");
-        println!("Address: {}", wallet.address_str());
-        println!("Saved to: {}", path.display());
-        println!("\nIMPORTANT: Backup your mnemonic phrase with 'uhash export-mnemonic'");
+        println!("{}", serde_json::to_string(&report)?);
+        return Ok(());
     }
 
+    println!("Mining report for the last {}", period);
+    println!("Rounds:           {}", report.rounds);
+    println!("Mining time:      {:.0}s", report.mining_secs);
+    println!("Hashes:           {}", report.hashes);
+    println!("Average hashrate: {:.0} H/s", report.hashrate);
+    println!("Proofs found:     {}", report.proofs_found);
+    println!("Proofs submitted: {}", report.proofs_submitted);
+    if let Some(per_proof) = report.hashes_per_proof {
+        println!("Hashes per proof: {:.0}", per_proof);
+    }
+    if report.unknown_rewards > 0 {
+        // Sync broadcasts return before the reward is known
+        println!(
+            "Rewards:          {} uLI (+ {} proofs with unknown rewards)",
+            report.rewards, report.unknown_rewards
+        );
+    } else {
+        println!("Rewards:          {} uLI", report.rewards);
+    }
+    if let Some(first) = db.first_round_at()? {
+        if first > report.since {
+            println!(
+                "Note: history only goes back {}s",
+                unix_now().saturating_sub(first)
+            );
+        }
+    }
     Ok(())
 }
 
-fn cmd_address(wallet_path: Option<PathBuf>, json: bool) -> anyhow::Result<()> {
-    let path = wallet_path.unwrap_or_else(default_wallet_path);
+#[cfg(not(feature = "stats"))]
+fn cmd_report(
+    _period: &str,
+    _export: Option<&str>,
+    _output: &Path,
+    _settings: &StatsConfig,
+    _json: bool,
+) -> anyhow::Result<()> {
+    anyhow::bail!("Mining stats are not available: rebuild with `--features stats`")
+}
 
-    if !path.exists() {
-        anyhow::bail!("No wallet found. Create one with 'uhash new-wallet'");
-    }
+fn cmd_follow_proofs(rpc_config: &RpcConfig, json: bool) -> anyhow::Result<()> {
+    use futures_util::StreamExt;
 
-    let wallet = Wallet::load_from_file(&path)?;
+    let client = rpc_client(rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let mut proofs = client.subscribe_proofs().await?;
+        if json {
+            println!(
+                "{}",
+                serde_json::to_string(&JsonSubscribed {
+                    event: "subscribed",
+                    endpoint: rpc_config.rpc_url.clone(),
+                    contract: rpc_config.contract_address.clone(),
+                })?
+            );
+        } else {
+            println!(
+                "Following proofs accepted by {} (Ctrl+C to stop)",
+                rpc_config.contract_address
+            );
+        }
 
-    if json {
-        let out = JsonWallet {
-            address: wallet.address_str(),
-            path: None,
-        };
-        println!("{}", serde_json::to_string(&out)?);
+        while let Some(proof) = proofs.next().await {
+            let proof = proof?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonProofAccepted {
+                        event: "proof_accepted",
+                        proof,
+                    })?
+                );
+            } else {
+                let difficulty = proof
+                    .difficulty
+                    .map(|d| format!("difficulty {}", d))
+                    .unwrap_or_else(|| "difficulty ?".to_string());
+                let reward = proof
+                    .reward
+                    .map(|r| format!("{} uLI", r))
+                    .unwrap_or_else(|| "? uLI".to_string());
+                println!(
+                    "#{}  {}  {}  {}  {}",
+                    proof.height, proof.miner, difficulty, reward, proof.tx_hash
+                );
+            }
+        }
+        anyhow::bail!("{} closed the subscription", rpc_config.rpc_url)
+    })
+}
+
+/// How long `init` measures each thread count
+const CALIBRATION_TIME: Duration = Duration::from_secs(1);
+
+/// `init` recommends the fewest threads reaching this share of the best hashrate
+const CALIBRATION_SHARE: f64 = 0.95;
+
+/// Ask on the terminal, returning `default` for an empty answer
+fn prompt(question: &str, default: &str) -> std::io::Result<String> {
+    use std::io::Write;
+    if default.is_empty() {
+        print!("{}: ", question);
     } else {
-        println!("{}", wallet.address_str());
+        print!("{} [{}]: ", question, default);
     }
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let answer = input.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
 
-    Ok(())
+/// A rough duration such as `40s`, `12 min`, `5.2 h` or `3.1 days`
+fn format_duration(secs: f64) -> String {
+    if secs < 120.0 {
+        format!("{:.0}s", secs)
+    } else if secs < 7_200.0 {
+        format!("{:.0} min", secs / 60.0)
+    } else if secs < 172_800.0 {
+        format!("{:.1} h", secs / 3_600.0)
+    } else {
+        format!("{:.1} days", secs / 86_400.0)
+    }
 }
 
-fn cmd_benchmark(count: u32, json: bool) -> anyhow::Result<()> {
+/// Hashrate at 1, 2, 4, ... threads up to `max`
+fn calibrate_threads(max: usize) -> Vec<(usize, f64)> {
+    let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |n| n.checked_mul(2))
+        .take_while(|&n| n < max)
+        .collect();
+    counts.push(max.max(1));
+    counts
+        .into_iter()
+        .map(|threads| (threads, measure_hashrate(threads, CALIBRATION_TIME)))
+        .collect()
+}
+
+/// First-run setup: wallet, endpoints, threads, config file and a dry-run hash
+fn cmd_init(
+    yes: bool,
+    config_path: &Path,
+    mut config: Config,
+    rpc_config: &RpcConfig,
+    wallet_opts: &WalletOpts,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json && !yes {
+        anyhow::bail!("--yes is required when using --json");
+    }
+    let ask = |question: &str, default: &str| -> std::io::Result<String> {
+        if yes {
+            Ok(default.to_string())
+        } else {
+            prompt(question, default)
+        }
+    };
+
     if !json {
-        println!("Running benchmark with {} hashes...", count);
+        println!("=== UniversalHash Setup ===\n");
+        println!("[1/4] Wallet");
     }
+    let (address, wallet_state) = if wallet_opts.exists()? {
+        let address = wallet_opts.address()?;
+        if !json {
+            println!("Using the existing wallet: {}", address);
+        }
+        (address, "existing")
+    } else {
+        let choice = ask(
+            "No wallet yet. Create a new one or import a mnemonic? (new/import)",
+            "new",
+        )?;
+        let (wallet, state) = match choice.as_str() {
+            "new" | "n" => (Wallet::generate(24, parse_language("english")?)?, "created"),
+            "import" | "i" => (Wallet::from_phrase(&read_mnemonic()?)?, "imported"),
+            other => anyhow::bail!("Unknown choice '{}': answer new or import", other),
+        };
+        let wallet = wallet_opts.derive(wallet)?;
+        let location = wallet_opts.store(&wallet)?;
+        if !json {
+            println!("Address:  {}", wallet.address_str());
+            println!("Saved to: {}", location);
+            if state == "created" {
+                println!("IMPORTANT: Backup your mnemonic phrase with 'uhash export-mnemonic'");
+            }
+        }
+        (wallet.address_str(), state)
+    };
 
-    let mut hasher = UniversalHash::new();
-    let input = b"benchmark input data for UniversalHash v4";
+    if !json {
+        println!("\n[2/4] Endpoints");
+    }
+    let list = |answer: String| -> Vec<String> {
+        answer
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let lcd_default = std::iter::once(&rpc_config.lcd_url)
+        .chain(&rpc_config.fallback_lcd_urls)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(",");
+    let rpc = list(ask("RPC endpoints (comma-separated)", &rpc_config.rpc_url)?);
+    let lcd = list(ask("LCD endpoints (comma-separated)", &lcd_default)?);
+    let (Some(primary_rpc), Some((primary_lcd, fallback_lcd))) = (rpc.first(), lcd.split_first())
+    else {
+        anyhow::bail!("At least one RPC and one LCD endpoint are needed");
+    };
+    for url in rpc.iter().chain(&lcd) {
+        validate_endpoint(url)?;
+    }
+    let rpc_config = RpcConfig {
+        rpc_url: primary_rpc.clone(),
+        lcd_url: primary_lcd.clone(),
+        fallback_lcd_urls: fallback_lcd.to_vec(),
+        ..rpc_config.clone()
+    };
+    let client = rpc_client(&rpc_config, json)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let statuses = rt.block_on(client.select_fastest_endpoint());
+    let reachable = statuses.iter().any(|s| s.is_healthy());
+    if !json {
+        print_endpoint_statuses(&statuses);
+        if !reachable {
+            eprintln!("Warning: No LCD endpoint is reachable; mining will fail until one is");
+        }
+    }
 
-    let start = Instant::now();
+    if !json {
+        println!("\n[3/4] Threads");
+        println!("Measuring hashrate...");
+    }
+    let calibration = calibrate_threads(num_cpus::get());
+    let best = calibration
+        .iter()
+        .map(|&(_, rate)| rate)
+        .fold(0.0, f64::max);
+    let recommended = calibration
+        .iter()
+        .find(|&&(_, rate)| rate >= best * CALIBRATION_SHARE)
+        .map_or(1, |&(threads, _)| threads);
+    if !json {
+        for (threads, rate) in &calibration {
+            let plural = if *threads == 1 { "" } else { "s" };
+            println!("  {:>3} thread{}: {:.1} H/s", threads, plural, rate);
+        }
+    }
+    let threads: usize = ask("Threads to mine with", &recommended.to_string())?
+        .parse()
+        .ok()
+        .filter(|&t| t > 0)
+        .ok_or_else(|| anyhow::anyhow!("Threads must be a positive number"))?;
+    let hashrate = match calibration.iter().find(|&&(t, _)| t == threads) {
+        Some(&(_, rate)) => rate,
+        None => measure_hashrate(threads, CALIBRATION_TIME),
+    };
 
-    for i in 0..count {
-        let mut data = input.to_vec();
-        data.extend_from_slice(&i.to_le_bytes());
-        let _ = hasher.hash(&data);
+    if !json {
+        println!("\n[4/4] Config");
+    }
+    // Leave the chain defaults implicit so they can change with new releases
+    let is_default = |configured: &[String], chosen: &[String], default: &str| -> bool {
+        configured.is_empty() && chosen == [default]
+    };
+    if !is_default(&config.network.rpc, &rpc, &config.chain.rpc_url) {
+        config.network.rpc = rpc.clone();
+    }
+    if !is_default(&config.network.lcd, &lcd, &config.chain.lcd_url) {
+        config.network.lcd = lcd.clone();
+    }
+    config.mine.threads = threads;
+    config.save(config_path)?;
+    if !json {
+        println!("Config written to {}", config_path.display());
     }
 
-    let elapsed = start.elapsed();
-    let hashrate = count as f64 / elapsed.as_secs_f64();
+    // One hash of a real proof input shows the whole pipeline works
+    let seed = rt.block_on(client.get_seed()).map(|info| info.seed);
+    let difficulty = rt.block_on(client.get_difficulty()).ok();
+    let seed = seed.unwrap_or_else(|e| {
+        if !json {
+            eprintln!("Warning: Could not fetch seed ({}), hashing with zeros", e);
+        }
+        [0u8; 32]
+    });
+    let mut input = seed.to_vec();
+    input.extend_from_slice(address.as_bytes());
+    input.extend_from_slice(&unix_now().to_le_bytes());
+    input.extend_from_slice(&0u64.to_le_bytes());
+    let start = Instant::now();
+    let hash = UniversalHash::new().hash(&input);
+    let dry_run = JsonDryRun {
+        hash: hex::encode(hash),
+        elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        difficulty,
+        expected_secs_per_proof: difficulty.map(|d| 2f64.powi(d as i32) / hashrate),
+    };
 
     if json {
-        let out = JsonBenchmark {
-            total_hashes: count,
-            elapsed_s: elapsed.as_secs_f64(),
+        let out = JsonInit {
+            address,
+            wallet: wallet_state,
+            rpc,
+            lcd,
+            reachable,
+            threads,
             hashrate,
-            params: JsonAlgoParams {
-                chains: uhash_core::CHAINS,
-                scratchpad_kb: uhash_core::SCRATCHPAD_SIZE / 1024,
-                total_mb: uhash_core::TOTAL_MEMORY / (1024 * 1024),
-                rounds: uhash_core::ROUNDS,
-            },
+            config: config_path.display().to_string(),
+            dry_run,
         };
         println!("{}", serde_json::to_string(&out)?);
     } else {
-        println!("\nResults:");
-        println!("  Total hashes: {}", count);
-        println!("  Time elapsed: {:.2}s", elapsed.as_secs_f64());
-        println!("  Hashrate: {:.2} H/s", hashrate);
-
-        println!("\nAlgorithm parameters:");
-        println!("  Chains: {}", uhash_core::CHAINS);
-        println!(
-            "  Memory per chain: {} KB",
-            uhash_core::SCRATCHPAD_SIZE / 1024
-        );
         println!(
-            "  Total memory: {} MB",
-            uhash_core::TOTAL_MEMORY / (1024 * 1024)
+            "\nDry-run hash: {} ({:.1} ms)",
+            dry_run.hash, dry_run.elapsed_ms
         );
-        println!("  Rounds: {}", uhash_core::ROUNDS);
+        if let (Some(difficulty), Some(secs)) = (difficulty, dry_run.expected_secs_per_proof) {
+            println!(
+                "At {} bits and {:.1} H/s, expect a proof every {}",
+                difficulty,
+                hashrate,
+                format_duration(secs)
+            );
+        }
+        println!("\nSetup complete. Start mining with: uhash mine");
     }
-
     Ok(())
 }
 
-fn cmd_status(rpc_config: &uhash::rpc::RpcConfig, json: bool) -> anyhow::Result<()> {
-    let client = RpcClient::with_config(rpc_config.clone());
-    let rt = tokio::runtime::Runtime::new()?;
-
-    if !json {
-        println!("Querying contract status...");
-        println!("Contract: {}", rpc_config.contract_address);
+fn cmd_config(
+    action: ConfigAction,
+    config_path: &Path,
+    mut config: Config,
+    json: bool,
+) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    struct JsonConfigValue {
+        key: String,
+        value: String,
     }
 
-    // Query seed
-    let seed = rt.block_on(client.get_seed())?;
-    let seed_hex = hex::encode(seed);
-
-    // Query difficulty
-    let difficulty = rt.block_on(client.get_difficulty())?;
-    let min_profitable = rt
-        .block_on(client.get_min_profitable_difficulty())
-        .unwrap_or(0);
-
-    // Try to query full config for extra fields
-    let config_resp: Option<uhash::rpc::ConfigResponse> = rt.block_on(async {
-        let query = uhash::rpc::QueryMsg::Config {};
-        let query_b64 = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            serde_json::to_vec(&query).ok()?,
-        );
-        let url = format!(
-            "{}/cosmwasm/wasm/v1/contract/{}/smart/{}",
-            rpc_config.lcd_url, rpc_config.contract_address, query_b64
-        );
-        let http = reqwest::Client::new();
-        let r = http.get(&url).send().await.ok()?;
-        let v: serde_json::Value = r.json().await.ok()?;
-        serde_json::from_value(v["data"].clone()).ok()
-    });
-
-    if json {
-        let out = JsonStatus {
-            contract: rpc_config.contract_address.clone(),
-            seed: seed_hex,
-            difficulty,
-            min_profitable_difficulty: min_profitable,
-            base_reward: config_resp.as_ref().map(|c| c.base_reward.clone()),
-            period_duration: config_resp.as_ref().map(|c| c.period_duration),
-            paused: config_resp.as_ref().map(|c| c.paused),
-        };
-        println!("{}", serde_json::to_string(&out)?);
-    } else {
-        println!("\n=== Contract Status ===");
-        println!("Seed:       {}", seed_hex);
-        println!("Difficulty: {} bits", difficulty);
-        println!("Min profitable: {} bits", min_profitable);
-        if let Some(ref config) = config_resp {
-            println!("Base reward:    {} uLI", config.base_reward);
-            println!("Period duration: {}s", config.period_duration);
-            println!("Paused: {}", config.paused);
+    match action {
+        ConfigAction::Get { key } => {
+            let value = config.get(&key)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonConfigValue { key, value })?
+                );
+            } else {
+                println!("{}", value);
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            config.set(&key, &value)?;
+            config.save(config_path)?;
+            let value = config.get(&key)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonConfigValue { key, value })?
+                );
+            } else {
+                println!("{} = {}", key, value);
+            }
+        }
+        ConfigAction::Path => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "path": config_path.display().to_string() })
+                );
+            } else {
+                println!("{}", config_path.display());
+            }
         }
-        println!("=======================");
     }
 
     Ok(())
@@ -0,0 +1,233 @@
+//! Mining for several reward addresses from one machine
+//!
+//! An [`AddressBook`] lists reward addresses with a weight each, e.g. the
+//! addresses of a family sharing one mining machine. Each round mines for the
+//! address furthest behind its share of the hashes, so equal weights take
+//! turns and an address of weight 2 gets twice the hashes of one of weight 1.
+//!
+//! The file has one address per line, optionally followed by its weight;
+//! blank lines and `#` comments are skipped:
+//!
+//! ```text
+//! # Shared wallet split
+//! bostrom1...alice 2
+//! bostrom1...bob
+//! ```
+
+use serde::Serialize;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::wallet::validate_address_with_prefix;
+
+/// Errors reading an address book
+#[derive(Error, Debug)]
+pub enum AddressBookError {
+    #[error("Could not read {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Line {line}: {reason}")]
+    Invalid { line: usize, reason: String },
+    #[error("The address book lists no addresses")]
+    Empty,
+}
+
+/// One reward address and the work done for it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AddressStats {
+    pub address: String,
+    pub weight: f64,
+    pub rounds: u64,
+    pub hashes: u64,
+    pub proofs_found: u64,
+    pub proofs_submitted: u64,
+}
+
+impl AddressStats {
+    fn new(address: String, weight: f64) -> Self {
+        Self {
+            address,
+            weight,
+            rounds: 0,
+            hashes: 0,
+            proofs_found: 0,
+            proofs_submitted: 0,
+        }
+    }
+}
+
+/// Reward addresses that take turns at mining rounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressBook {
+    entries: Vec<AddressStats>,
+}
+
+impl AddressBook {
+    /// A book of one address, which gets every round
+    pub fn single(address: impl Into<String>) -> Self {
+        Self {
+            entries: vec![AddressStats::new(address.into(), 1.0)],
+        }
+    }
+
+    /// Read an address book file, checking each address has `prefix`
+    pub fn load(path: &Path, prefix: &str) -> Result<Self, AddressBookError> {
+        let text = std::fs::read_to_string(path).map_err(|source| AddressBookError::Read {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::parse(&text, prefix)
+    }
+
+    /// Parse `ADDRESS [WEIGHT]` lines, checking each address has `prefix`
+    pub fn parse(text: &str, prefix: &str) -> Result<Self, AddressBookError> {
+        let mut entries: Vec<AddressStats> = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let invalid = |reason: String| AddressBookError::Invalid {
+                line: index + 1,
+                reason,
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut fields = line.split_whitespace();
+            let Some(address) = fields.next() else {
+                continue;
+            };
+            validate_address_with_prefix(address, prefix).map_err(|e| invalid(e.to_string()))?;
+            let address = address.to_lowercase();
+            let weight = match fields.next() {
+                Some(weight) => weight
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|w| w.is_finite() && *w > 0.0)
+                    .ok_or_else(|| {
+                        invalid(format!("weight '{}' is not a positive number", weight))
+                    })?,
+                None => 1.0,
+            };
+            if let Some(extra) = fields.next() {
+                return Err(invalid(format!("unexpected '{}' after the weight", extra)));
+            }
+            if entries.iter().any(|entry| entry.address == address) {
+                return Err(invalid(format!("{} is listed twice", address)));
+            }
+            entries.push(AddressStats::new(address, weight));
+        }
+        if entries.is_empty() {
+            return Err(AddressBookError::Empty);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every address with its stats, in file order
+    pub fn entries(&self) -> &[AddressStats] {
+        &self.entries
+    }
+
+    /// The address to mine the next round for: the one with the fewest
+    /// hashes for its weight, the first listed on a tie
+    pub fn next(&self) -> &str {
+        let share = |entry: &AddressStats| entry.hashes as f64 / entry.weight;
+        self.entries
+            .iter()
+            .reduce(|best, entry| {
+                if share(entry) < share(best) {
+                    entry
+                } else {
+                    best
+                }
+            })
+            .map(|entry| entry.address.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Count a round of `hashes` mined for `address`
+    pub fn record_round(&mut self, address: &str, hashes: u64) {
+        if let Some(entry) = self.entry_mut(address) {
+            entry.rounds += 1;
+            entry.hashes += hashes;
+        }
+    }
+
+    /// Count a proof found for `address`
+    pub fn record_proof(&mut self, address: &str) {
+        if let Some(entry) = self.entry_mut(address) {
+            entry.proofs_found += 1;
+        }
+    }
+
+    /// Count a proof for `address` that reached the chain
+    pub fn record_submitted(&mut self, address: &str) {
+        if let Some(entry) = self.entry_mut(address) {
+            entry.proofs_submitted += 1;
+        }
+    }
+
+    fn entry_mut(&mut self, address: &str) -> Option<&mut AddressStats> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.address == address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "bostrom1qyqszqgpqyqszqgpqyqszqgpqyqszqgp3q4dnm";
+    const BOB: &str = "bostrom1qgpqyqszqgpqyqszqgpqyqszqgpqyqszqyngcd";
+
+    fn book() -> AddressBook {
+        let text = format!("# family split\n{} 2\n\n{}  # the laptop\n", ALICE, BOB);
+        AddressBook::parse(&text, "bostrom").unwrap()
+    }
+
+    #[test]
+    fn test_parse_address_book() {
+        let book = book();
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.entries()[0].address, ALICE);
+        assert_eq!(book.entries()[0].weight, 2.0);
+        assert_eq!(book.entries()[1].weight, 1.0);
+
+        let parse = |text: &str| AddressBook::parse(text, "bostrom").unwrap_err().to_string();
+        assert_eq!(parse("# nothing\n"), "The address book lists no addresses");
+        assert!(parse("bostrom1typo").starts_with("Line 1:"));
+        assert!(parse(&format!("{}\n{} 0", ALICE, BOB)).contains("weight '0'"));
+        assert!(parse(&format!("{}\n{}", ALICE, ALICE.to_uppercase())).contains("twice"));
+        assert!(AddressBook::parse(ALICE, "cosmos").is_err());
+    }
+
+    #[test]
+    fn test_rounds_follow_weights() {
+        let mut book = book();
+        let mut rounds = Vec::new();
+        for _ in 0..6 {
+            let address = book.next().to_string();
+            book.record_round(&address, 100);
+            rounds.push(address);
+        }
+        assert_eq!(rounds.iter().filter(|a| *a == ALICE).count(), 4);
+        assert_eq!(book.entries()[0].hashes, 400);
+        assert_eq!(book.entries()[1].rounds, 2);
+
+        book.record_proof(BOB);
+        book.record_submitted(BOB);
+        book.record_proof("bostrom1unknown");
+        assert_eq!(book.entries()[1].proofs_found, 1);
+        assert_eq!(book.entries()[1].proofs_submitted, 1);
+        assert_eq!(book.entries()[0].proofs_found, 0);
+
+        let single = AddressBook::single(ALICE);
+        assert_eq!(single.next(), ALICE);
+    }
+}
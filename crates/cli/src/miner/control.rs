@@ -0,0 +1,69 @@
+//! Settings a running miner takes from outside its mining loop
+//!
+//! The management API, config reloads and `--when-idle` change these from
+//! other threads; a [`Session`](super::Session) reads them between rounds and
+//! restarts the running round when [`MinerControl::take_restart`] says so.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Settings changed remotely, read by the mining loop
+#[derive(Debug)]
+pub struct MinerControl {
+    paused: AtomicBool,
+    /// Held back by `--when-idle` while the machine is in use
+    held: AtomicBool,
+    threads: AtomicUsize,
+    /// The current round should end so a change takes effect
+    restart: AtomicBool,
+}
+
+impl MinerControl {
+    pub fn new(threads: usize) -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            held: AtomicBool::new(false),
+            threads: AtomicUsize::new(threads),
+            restart: AtomicBool::new(false),
+        }
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        self.restart.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether mining waits, paused through the API or held
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst) || self.is_held()
+    }
+
+    /// Hold mining back while `held`, independently of pause and resume
+    pub fn hold(&self, held: bool) {
+        if !self.held.swap(held, Ordering::SeqCst) && held {
+            self.restart.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held.load(Ordering::SeqCst)
+    }
+
+    pub fn set_threads(&self, threads: usize) {
+        if self.threads.swap(threads, Ordering::SeqCst) != threads {
+            self.restart.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads.load(Ordering::SeqCst)
+    }
+
+    /// Whether a change since the last call needs the round restarted
+    pub fn take_restart(&self) -> bool {
+        self.restart.swap(false, Ordering::SeqCst)
+    }
+}
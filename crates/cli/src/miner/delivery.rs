@@ -0,0 +1,438 @@
+//! Handing found proofs to a sink and following them on chain
+//!
+//! A [`Submitter`] hands proofs to a [`ProofSink`], follows the transactions
+//! it broadcast until they are final, and sends the proofs of dropped ones
+//! again while the contract still takes them. It also submits the proofs an
+//! earlier run saved to its [`PendingQueue`]. What it does is reported as
+//! [`DeliveryEvent`]s; printing them is up to the caller.
+
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+use crate::pending::PendingQueue;
+use crate::proof::{Proof, ProofError};
+use crate::rpc::{ProofSubmission, RpcClient, RpcError, TxTracker, TxUpdate, TRACK_POLL_INTERVAL};
+use crate::sink::{Delivery, ProofSink, SinkError};
+
+#[derive(Error, Debug)]
+pub enum DeliveryError {
+    #[error("Could not update the pending proofs: {0}")]
+    Pending(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Proof(#[from] ProofError),
+}
+
+/// What became of a proof handed to the sink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Submitted {
+    /// Relayed or accepted by the contract
+    Sent {
+        tx_hash: String,
+        reward: Option<String>,
+        /// Broadcast by this miner but not yet seen in a block
+        unconfirmed: bool,
+    },
+    /// Handed to an off-chain sink
+    Forwarded,
+    /// Submitting failed; mining carries on
+    Failed(String),
+    /// Handed off for offline signing (watch-only wallet)
+    Unsigned,
+}
+
+/// What the [`Submitter`] did, in order
+#[derive(Debug)]
+pub enum DeliveryEvent {
+    /// Proofs are being handed to the sink
+    Submitting { proofs: usize, destination: String },
+    /// The sink took a proof
+    Delivered {
+        proof: ProofSubmission,
+        delivery: Delivery,
+        destination: String,
+        /// Proofs sent so far, this one included
+        proofs_submitted: u64,
+    },
+    /// The sink could not take a proof
+    Failed {
+        proof: ProofSubmission,
+        error: SinkError,
+        destination: String,
+        proofs_submitted: u64,
+    },
+    /// A watch-only wallet's proof, encoded for `sign-proof`, with the
+    /// account number and sequence to sign it at
+    Unsigned {
+        proof: ProofSubmission,
+        encoded: String,
+        account: Result<(u64, u64), RpcError>,
+    },
+    /// A followed transaction reached a block, became final or was dropped
+    TxStatus {
+        update: TxUpdate,
+        /// Proofs of a dropped transaction too old to send again
+        expired: Vec<ProofSubmission>,
+        /// Proofs of a dropped transaction sent again
+        resubmitted: Option<usize>,
+    },
+    /// The followed transactions could not be looked up
+    TrackingFailed(RpcError),
+    /// Transactions still not final when settling gave up
+    Unsettled(usize),
+    /// Proofs an earlier run saved are being submitted
+    PendingFound,
+    /// A saved proof was too old to submit and was dropped
+    PendingExpired(ProofSubmission),
+}
+
+/// Hands found proofs to a sink and follows the transactions it sent
+pub struct Submitter<'a> {
+    rt: &'a Runtime,
+    client: &'a RpcClient,
+    sink: Box<dyn ProofSink + 'a>,
+    /// Sent proofs not yet in a final block
+    tracker: TxTracker,
+    last_track: Instant,
+    /// Oldest proof the contract accepts, in seconds
+    max_proof_age: Option<u64>,
+    proofs_submitted: u64,
+    proofs_unsigned: u64,
+}
+
+impl<'a> Submitter<'a> {
+    /// Submit through `sink`, evicting sent transactions not in a block
+    /// after `evict_after` and following them `finality_depth` blocks deep
+    pub fn new(
+        rt: &'a Runtime,
+        client: &'a RpcClient,
+        sink: Box<dyn ProofSink + 'a>,
+        evict_after: Duration,
+        finality_depth: u64,
+        max_proof_age: Option<u64>,
+    ) -> Self {
+        Self {
+            rt,
+            client,
+            sink,
+            tracker: TxTracker::new(evict_after, finality_depth),
+            last_track: Instant::now(),
+            max_proof_age,
+            proofs_submitted: 0,
+            proofs_unsigned: 0,
+        }
+    }
+
+    /// Submit through `sink` from now on
+    pub fn set_sink(&mut self, sink: Box<dyn ProofSink + 'a>) {
+        self.sink = sink;
+    }
+
+    pub fn client(&self) -> &'a RpcClient {
+        self.client
+    }
+
+    /// Proofs the sink took, less those of transactions that were dropped
+    pub fn proofs_submitted(&self) -> u64 {
+        self.proofs_submitted
+    }
+
+    /// Proofs handed off for offline signing
+    pub fn proofs_unsigned(&self) -> u64 {
+        self.proofs_unsigned
+    }
+
+    /// Hand `proofs` to the sink, all at once (one transaction for the
+    /// contract sink), and follow the transactions that carry them
+    pub fn submit(
+        &mut self,
+        proofs: &[ProofSubmission],
+        events: &mut dyn FnMut(DeliveryEvent),
+    ) -> Result<Vec<Submitted>, DeliveryError> {
+        if proofs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let destination = self.sink.destination();
+        events(DeliveryEvent::Submitting {
+            proofs: proofs.len(),
+            destination: destination.clone(),
+        });
+        let deliveries = match proofs {
+            [proof] => vec![self.rt.block_on(self.sink.submit(proof))],
+            _ => self.rt.block_on(self.sink.submit_batch(proofs)),
+        };
+        let submitted = proofs
+            .iter()
+            .zip(deliveries)
+            .map(|(proof, delivery)| self.report(proof, delivery, &destination, events))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.track(proofs, &submitted);
+        Ok(submitted)
+    }
+
+    /// Submit the proofs for `address` an earlier run saved to `pending`, up
+    /// to `batch_size` per transaction
+    ///
+    /// Proofs of other accounts stay queued; expired ones are dropped, and
+    /// ones that fail to submit are kept for the next start.
+    pub fn submit_pending(
+        &mut self,
+        pending: &PendingQueue,
+        address: &str,
+        batch_size: usize,
+        events: &mut dyn FnMut(DeliveryEvent),
+    ) -> Result<(), DeliveryError> {
+        let queued = pending.load()?;
+        if !queued.iter().any(|proof| proof.miner_address == address) {
+            return Ok(());
+        }
+        events(DeliveryEvent::PendingFound);
+        let mut kept = Vec::new();
+        let mut ready = Vec::new();
+        for proof in queued {
+            if proof.miner_address != address {
+                kept.push(proof);
+            } else if self.is_expired(&proof) {
+                events(DeliveryEvent::PendingExpired(proof));
+            } else {
+                ready.push(proof);
+            }
+        }
+        for batch in ready.chunks(batch_size.max(1)) {
+            let submitted = self.submit(batch, events)?;
+            for (proof, submitted) in batch.iter().zip(submitted) {
+                if matches!(submitted, Submitted::Failed(_)) {
+                    kept.push(proof.clone());
+                }
+            }
+        }
+        pending.replace(&kept)?;
+        Ok(())
+    }
+
+    /// Look up the followed transactions if [`TRACK_POLL_INTERVAL`] passed
+    /// since the last look
+    pub fn follow(&mut self, events: &mut dyn FnMut(DeliveryEvent)) -> Result<(), DeliveryError> {
+        if self.tracker.is_empty() || self.last_track.elapsed() < TRACK_POLL_INTERVAL {
+            return Ok(());
+        }
+        self.last_track = Instant::now();
+        self.poll_tracked(events)
+    }
+
+    /// Follow sent proofs until their transactions are final, sending
+    /// dropped ones again, or until they should have been
+    pub fn settle(&mut self, events: &mut dyn FnMut(DeliveryEvent)) -> Result<(), DeliveryError> {
+        loop {
+            self.poll_tracked(events)?;
+            let Some(settle_by) = self.tracker.settle_by() else {
+                return Ok(());
+            };
+            if Instant::now() >= settle_by {
+                events(DeliveryEvent::Unsettled(self.tracker.len()));
+                return Ok(());
+            }
+            std::thread::sleep(TRACK_POLL_INTERVAL);
+        }
+    }
+
+    /// Report the followed transactions that reached a block or became
+    /// final, and send again the proofs of ones that were evicted or reorged
+    /// away while the contract still takes them
+    fn poll_tracked(&mut self, events: &mut dyn FnMut(DeliveryEvent)) -> Result<(), DeliveryError> {
+        let updates = match self.rt.block_on(self.tracker.poll(self.client)) {
+            Ok(updates) => updates,
+            Err(e) => {
+                events(DeliveryEvent::TrackingFailed(e));
+                return Ok(());
+            }
+        };
+        for update in updates {
+            let dropped = update.status.is_dropped();
+            let (live, expired): (Vec<_>, Vec<_>) = update
+                .proofs
+                .iter()
+                .cloned()
+                .partition(|proof| !dropped || !self.is_expired(proof));
+            if dropped {
+                // The proofs never made it on chain
+                self.client.forget_submission(&update.proofs);
+                self.proofs_submitted = self
+                    .proofs_submitted
+                    .saturating_sub(update.proofs.len() as u64);
+            }
+            events(DeliveryEvent::TxStatus {
+                update,
+                expired,
+                resubmitted: dropped.then_some(live.len()),
+            });
+            if dropped {
+                self.submit(&live, events)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Count what became of a proof handed to the sink and report it
+    fn report(
+        &mut self,
+        proof: &ProofSubmission,
+        delivery: Result<Delivery, SinkError>,
+        destination: &str,
+        events: &mut dyn FnMut(DeliveryEvent),
+    ) -> Result<Submitted, DeliveryError> {
+        let submitted = match &delivery {
+            Ok(Delivery::Unsigned) => {
+                self.proofs_unsigned += 1;
+                return self.hand_off_unsigned(proof, events);
+            }
+            Ok(Delivery::Committed {
+                tx_hash,
+                reward,
+                height,
+                ..
+            }) => Submitted::Sent {
+                tx_hash: tx_hash.clone(),
+                reward: reward.clone(),
+                unconfirmed: height.is_none(),
+            },
+            Ok(Delivery::Relayed { tx_hash }) => Submitted::Sent {
+                tx_hash: tx_hash.clone(),
+                reward: None,
+                unconfirmed: false,
+            },
+            Ok(Delivery::Forwarded { .. }) => Submitted::Forwarded,
+            Err(e) => Submitted::Failed(format!("Submit failed: {}", e)),
+        };
+        if delivery.is_ok() {
+            self.proofs_submitted += 1;
+        }
+        events(match delivery {
+            Ok(delivery) => DeliveryEvent::Delivered {
+                proof: proof.clone(),
+                delivery,
+                destination: destination.to_string(),
+                proofs_submitted: self.proofs_submitted,
+            },
+            Err(error) => DeliveryEvent::Failed {
+                proof: proof.clone(),
+                error,
+                destination: destination.to_string(),
+                proofs_submitted: self.proofs_submitted,
+            },
+        });
+        Ok(submitted)
+    }
+
+    /// Report a proof a watch-only wallet cannot sign, with what `sign-proof`
+    /// needs
+    ///
+    /// The account number and sequence are looked up here so the signing
+    /// machine can stay offline. Proofs signed at the same sequence replace
+    /// each other, so each signed proof is broadcast before the next is signed.
+    fn hand_off_unsigned(
+        &self,
+        proof: &ProofSubmission,
+        events: &mut dyn FnMut(DeliveryEvent),
+    ) -> Result<Submitted, DeliveryError> {
+        let account = self
+            .rt
+            .block_on(self.client.get_account_info(&proof.miner_address));
+        events(DeliveryEvent::Unsigned {
+            proof: proof.clone(),
+            encoded: Proof::try_from(proof)?.to_base64(),
+            account,
+        });
+        Ok(Submitted::Unsigned)
+    }
+
+    /// Follow the transactions of proofs that were sent but not yet seen in
+    /// a block
+    fn track(&mut self, proofs: &[ProofSubmission], submitted: &[Submitted]) {
+        let mut txs: Vec<(&str, Vec<ProofSubmission>)> = Vec::new();
+        for (proof, submitted) in proofs.iter().zip(submitted) {
+            let Submitted::Sent {
+                tx_hash,
+                unconfirmed: true,
+                ..
+            } = submitted
+            else {
+                continue;
+            };
+            match txs.iter_mut().find(|(hash, _)| hash == tx_hash) {
+                Some((_, batch)) => batch.push(proof.clone()),
+                None => txs.push((tx_hash, vec![proof.clone()])),
+            }
+        }
+        for (tx_hash, proofs) in txs {
+            self.tracker.track(tx_hash, proofs);
+        }
+    }
+
+    /// The contract would no longer take `proof`
+    fn is_expired(&self, proof: &ProofSubmission) -> bool {
+        self.max_proof_age
+            .is_some_and(|max| super::unix_now().saturating_sub(proof.timestamp) > max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::{self, SinkKind};
+
+    fn proof(address: &str, nonce: u64, timestamp: u64) -> ProofSubmission {
+        ProofSubmission {
+            hash: format!("{:064x}", nonce),
+            nonce,
+            timestamp,
+            miner_address: address.to_string(),
+            worker_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_submit_pending_keeps_other_accounts() {
+        let dir = std::env::temp_dir().join(format!("uhash-delivery-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pending = PendingQueue::new(dir.join("pending.jsonl"));
+        let now = crate::miner::unix_now();
+        pending.push(&proof("bostrom1other", 1, now)).unwrap();
+        pending.push(&proof("bostrom1miner", 2, 0)).unwrap();
+        pending.push(&proof("bostrom1miner", 3, now)).unwrap();
+
+        let rt = Runtime::new().unwrap();
+        let client = RpcClient::new();
+        let out = dir.join("proofs.jsonl");
+        let sink =
+            sink::from_settings(SinkKind::File, "", out.to_str().unwrap(), &client, None).unwrap();
+        let mut submitter =
+            Submitter::new(&rt, &client, sink, Duration::from_secs(60), 1, Some(600));
+        let mut events = Vec::new();
+        submitter
+            .submit_pending(&pending, "bostrom1miner", 10, &mut |event| {
+                events.push(event)
+            })
+            .unwrap();
+
+        assert!(matches!(events[0], DeliveryEvent::PendingFound));
+        assert!(matches!(&events[1], DeliveryEvent::PendingExpired(p) if p.nonce == 2));
+        assert!(matches!(
+            &events[2],
+            DeliveryEvent::Submitting { proofs: 1, .. }
+        ));
+        assert!(matches!(
+            &events[3],
+            DeliveryEvent::Delivered {
+                delivery: Delivery::Forwarded { .. },
+                proofs_submitted: 1,
+                ..
+            }
+        ));
+        assert_eq!(submitter.proofs_submitted(), 1);
+        let kept: Vec<u64> = pending.load().unwrap().iter().map(|p| p.nonce).collect();
+        assert_eq!(kept, vec![1]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,106 @@
+//! Keeping mining threads within the memory the system has free
+//!
+//! Every thread holds its own 2 MB of scratchpads. On a phone, or any machine
+//! short on RAM, one thread per core can push the system into swap or get the
+//! miner killed, so thread counts are capped to what free memory holds. Free
+//! memory is `MemAvailable` of `/proc/meminfo` (Linux and Android); elsewhere
+//! it is unknown and thread counts are left alone.
+
+use thiserror::Error;
+
+use crate::algorithm::TOTAL_MEMORY;
+
+/// Memory one mining thread needs: its scratchpads plus stack and buffers
+pub const THREAD_MEMORY: u64 = TOTAL_MEMORY as u64 + 256 * 1024;
+
+/// Free memory left to the rest of the system
+pub const MEMORY_RESERVE: u64 = 64 * 1024 * 1024;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MemoryError {
+    #[error(
+        "Not enough free memory to mine: {} MB available, {} MB needed for one thread",
+        .available / (1024 * 1024),
+        (THREAD_MEMORY + MEMORY_RESERVE) / (1024 * 1024)
+    )]
+    Insufficient { available: u64 },
+}
+
+/// Free memory in bytes, if the platform reports it
+pub fn available_memory() -> Option<u64> {
+    if !cfg!(any(target_os = "linux", target_os = "android")) {
+        return None;
+    }
+    parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+/// `MemAvailable` of `/proc/meminfo`, in bytes
+pub fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib: u64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Threads to run when `running` are already running and `requested` are
+/// wanted, with `available` bytes free
+///
+/// Only threads beyond the running ones need free memory. The count is
+/// lowered to what fits, and not even one thread fitting is an error.
+pub fn fit_threads(
+    requested: usize,
+    running: usize,
+    available: Option<u64>,
+) -> Result<usize, MemoryError> {
+    let Some(available) = available else {
+        return Ok(requested);
+    };
+    if requested <= running {
+        return Ok(requested);
+    }
+    let extra = available.saturating_sub(MEMORY_RESERVE) / THREAD_MEMORY;
+    let threads = requested.min(running.saturating_add(extra as usize));
+    if threads == 0 {
+        return Err(MemoryError::Insufficient { available });
+    }
+    Ok(threads)
+}
+
+/// [`fit_threads`] with the memory free right now
+pub fn fit_threads_to_memory(requested: usize, running: usize) -> Result<usize, MemoryError> {
+    fit_threads(requested, running, available_memory())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:        3884700 kB\nMemFree:          181212 kB\nMemAvailable:    1048576 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(1024 * MB));
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_fit_threads() {
+        // Unknown free memory leaves the count alone
+        assert_eq!(fit_threads(64, 0, None), Ok(64));
+        assert_eq!(fit_threads(8, 0, Some(4096 * MB)), Ok(8));
+
+        // 64 MB reserve plus 4 threads of 2.25 MB, and a little spare
+        let available = MEMORY_RESERVE + 4 * THREAD_MEMORY + MB;
+        assert_eq!(fit_threads(8, 0, Some(available)), Ok(4));
+        // Running threads already hold their memory
+        assert_eq!(fit_threads(8, 2, Some(available)), Ok(6));
+        assert_eq!(fit_threads(2, 6, Some(0)), Ok(2));
+
+        assert_eq!(
+            fit_threads(1, 0, Some(32 * MB)),
+            Err(MemoryError::Insufficient { available: 32 * MB })
+        );
+    }
+}
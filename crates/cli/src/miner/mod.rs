@@ -0,0 +1,891 @@
+//! Mining engine for the CLI and embedding applications
+//!
+//! A [`Round`] hashes one seed and timestamp on a set of threads until one of
+//! them finds a proof or the round is stopped. A [`Miner`] drives rounds
+//! against a [`ChainClient`]: it reads the seed and difficulty, submits each
+//! proof it finds, starts a fresh round when the seed rotates or the round's
+//! timestamp nears the contract's maximum proof age, and reports what it
+//! does as [`MiningEvent`]s. When it reads the chain again is up to its
+//! [`Schedule`]. GUIs and other hosts start and stop it
+//! and read its [`MinerStatus`] instead of running their own mining loop.
+//! `uhash mine` runs a [`Session`] instead, which mines on the calling thread
+//! for an [`AddressBook`], delivers proofs through a [`Submitter`] and keeps
+//! what it holds when stopped.
+//!
+//! Events go to the channel returned by [`Miner::new`] and to every
+//! subscriber: [`Miner::subscribe`] hands out a `tokio` broadcast receiver and
+//! [`Miner::stream`] a [`Stream`], so async hosts await events rather than
+//! poll the status.
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use uhash::miner::{Miner, MinerConfig, MiningEvent};
+//! use uhash::rpc::RpcClient;
+//!
+//! # fn run(signing_key: cosmrs::crypto::secp256k1::SigningKey) -> anyhow::Result<()> {
+//! let config = MinerConfig {
+//!     address: "bostrom1...".to_string(),
+//!     ..MinerConfig::default()
+//! };
+//! let (miner, events) = Miner::new(config, Arc::new(RpcClient::new()), Some(signing_key));
+//! miner.start()?;
+//! for event in events {
+//!     if let MiningEvent::ProofSubmitted { result, .. } = event {
+//!         println!("Submitted in {}", result.tx_hash);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod addresses;
+mod control;
+mod delivery;
+mod memory;
+mod round;
+mod schedule;
+mod session;
+
+pub use addresses::{AddressBook, AddressBookError, AddressStats};
+pub use control::MinerControl;
+pub use delivery::{DeliveryError, DeliveryEvent, Submitted, Submitter};
+pub use memory::{
+    available_memory, fit_threads, fit_threads_to_memory, MemoryError, MEMORY_RESERVE,
+    THREAD_MEMORY,
+};
+pub use round::{
+    check_worker_tag, FoundProof, NonceRange, Round, RoundOutcome, RoundParams, WorkerTagError,
+    PROOF_AGE_MARGIN_SECS,
+};
+pub use schedule::{Due, RoundTimer, Schedule, DEFAULT_REFRESH_JITTER, SEED_POLL_INTERVAL};
+pub use session::{
+    refresh_seed, resumable_round, Restart, ResumeNote, Session, SessionConfig, SessionEnd,
+    SessionError, SessionEvent, SessionHost, SessionSummary,
+};
+
+use cosmrs::crypto::secp256k1::SigningKey;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use crate::rpc::{ChainClient, ProofSubmission, RpcError, SeedInfo, SubmitResult};
+use crate::status::{MinerStatus, ProofLogEntry};
+
+/// How often a running round checks for a stop, a seed rotation or a proof
+pub const MONITOR_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How often a running round reports its hashrate
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Events a subscriber may fall behind by before it misses the oldest
+pub const EVENT_CAPACITY: usize = 256;
+
+#[derive(Error, Debug)]
+pub enum MinerError {
+    #[error("The miner is already running")]
+    AlreadyRunning,
+
+    #[error("Could not start the miner runtime: {0}")]
+    Runtime(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+
+    #[error(transparent)]
+    WorkerTag(#[from] WorkerTagError),
+
+    #[error("The mining thread exited before it started")]
+    Exited,
+}
+
+/// What to mine
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MinerConfig {
+    /// Address the proofs are mined for
+    pub address: String,
+    /// Hashing threads (0: one per CPU core), capped to what free memory holds
+    pub threads: usize,
+    /// Difficulty to mine at instead of the contract's
+    pub difficulty: Option<u32>,
+    /// Stop after this many proofs: submitted ones with a signing key, found
+    /// ones without
+    pub max_proofs: Option<u64>,
+    /// Spread of seed reads after a rotation (default: [`DEFAULT_REFRESH_JITTER`])
+    pub refresh_jitter: Option<Duration>,
+    /// Bytes hashed after the address to name this machine in its proofs,
+    /// if the contract accepts worker tags (empty: no tag)
+    pub worker_tag: Vec<u8>,
+}
+
+/// Why the miner stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// [`Miner::stop`] was called
+    Stopped,
+    /// `max_proofs` were mined
+    MaxProofs,
+}
+
+/// What the miner did, in order
+#[derive(Debug, Clone)]
+pub enum MiningEvent {
+    /// A round started hashing
+    RoundStarted {
+        seed: [u8; 32],
+        timestamp: u64,
+        difficulty: u32,
+        threads: usize,
+    },
+    /// Progress of the running round, every [`PROGRESS_INTERVAL`]
+    Hashrate {
+        hashrate: f64,
+        thread_hashrates: Vec<f64>,
+        hashes: u64,
+        elapsed: Duration,
+    },
+    ProofFound {
+        proof: ProofSubmission,
+        hashes: u64,
+        elapsed: Duration,
+    },
+    ProofSubmitted {
+        proof: ProofSubmission,
+        result: SubmitResult,
+    },
+    /// Submitting failed, or the chain rejected the proof
+    SubmitFailed {
+        proof: ProofSubmission,
+        error: String,
+    },
+    /// The seed changed; the next round mines the new one
+    SeedRotated {
+        seed: [u8; 32],
+    },
+    Stopped {
+        reason: StopReason,
+    },
+}
+
+/// What a run reads from the chain when it starts
+struct RunParams {
+    seed: SeedInfo,
+    difficulty: u32,
+    threads: usize,
+    /// Oldest proof the contract accepts, in seconds
+    max_proof_age: Option<u64>,
+}
+
+/// A mining engine that runs in the background between [`Miner::start`] and
+/// [`Miner::stop`]
+pub struct Miner {
+    shared: Arc<Shared>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// State the mining thread shares with the [`Miner`]
+struct Shared {
+    config: MinerConfig,
+    client: Arc<dyn ChainClient>,
+    /// Without a key proofs are reported but not submitted
+    signing_key: Option<SigningKey>,
+    stop: AtomicBool,
+    status: Mutex<MinerStatus>,
+    events: Mutex<mpsc::Sender<MiningEvent>>,
+    subscribers: broadcast::Sender<MiningEvent>,
+}
+
+impl Miner {
+    /// A stopped miner, and the receiver of its events
+    pub fn new(
+        config: MinerConfig,
+        client: Arc<dyn ChainClient>,
+        signing_key: Option<SigningKey>,
+    ) -> (Self, mpsc::Receiver<MiningEvent>) {
+        let (events, receiver) = mpsc::channel();
+        let status = MinerStatus {
+            pid: std::process::id(),
+            address: config.address.clone(),
+            ..MinerStatus::default()
+        };
+        let miner = Self {
+            shared: Arc::new(Shared {
+                config,
+                client,
+                signing_key,
+                stop: AtomicBool::new(false),
+                status: Mutex::new(status),
+                events: Mutex::new(events),
+                subscribers: broadcast::channel(EVENT_CAPACITY).0,
+            }),
+            handle: Mutex::new(None),
+        };
+        (miner, receiver)
+    }
+
+    /// Read the difficulty and seed, then mine in a background thread
+    ///
+    /// Fails if the chain cannot be reached; a miner that has stopped can be
+    /// started again. The chain is queried on the mining thread, so this can
+    /// be called from inside an async runtime.
+    pub fn start(&self) -> Result<(), MinerError> {
+        let mut handle = self.handle.lock().unwrap_or_else(|e| e.into_inner());
+        if handle.as_ref().is_some_and(|h| !h.is_finished()) {
+            return Err(MinerError::AlreadyRunning);
+        }
+        self.shared.stop.store(false, Ordering::SeqCst);
+        let (ready_tx, ready) = mpsc::channel();
+        let shared = Arc::clone(&self.shared);
+        *handle = Some(std::thread::spawn(move || match shared.prepare() {
+            Ok((rt, params)) => {
+                let _ = ready_tx.send(Ok(()));
+                shared.run(&rt, params);
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+            }
+        }));
+        let started = ready.recv().unwrap_or(Err(MinerError::Exited));
+        if started.is_err() {
+            if let Some(handle) = handle.take() {
+                let _ = handle.join();
+            }
+        }
+        started
+    }
+
+    /// A receiver of every event from now on
+    ///
+    /// A receiver more than [`EVENT_CAPACITY`] events behind misses the
+    /// oldest and gets `RecvError::Lagged` once.
+    pub fn subscribe(&self) -> broadcast::Receiver<MiningEvent> {
+        self.shared.subscribers.subscribe()
+    }
+
+    /// Every event from now on as an async stream, skipping any missed by
+    /// falling more than [`EVENT_CAPACITY`] behind
+    pub fn stream(&self) -> impl Stream<Item = MiningEvent> + Send + Unpin + 'static {
+        BroadcastStream::new(self.subscribe()).filter_map(Result::ok)
+    }
+
+    /// Stop mining and wait for the current round to wind down
+    pub fn stop(&self) {
+        self.shared.stop.store(true, Ordering::SeqCst);
+        self.wait();
+    }
+
+    /// Wait until the miner stops on its own (e.g. after `max_proofs`)
+    pub fn wait(&self) {
+        let handle = self.handle.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(handle) = handle {
+            let _ = handle.join();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(|h| !h.is_finished())
+    }
+
+    /// Snapshot of the hashrate, round and proofs
+    pub fn status(&self) -> MinerStatus {
+        self.shared.status().clone()
+    }
+}
+
+impl Drop for Miner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Shared {
+    fn status(&self) -> MutexGuard<'_, MinerStatus> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update_status(&self, change: impl FnOnce(&mut MinerStatus)) {
+        let mut status = self.status();
+        change(&mut status);
+        status.updated_at = unix_now();
+    }
+
+    fn emit(&self, event: MiningEvent) {
+        // Nobody listening does not stop the miner
+        let _ = self.subscribers.send(event.clone());
+        let _ = self
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .send(event);
+    }
+
+    /// Runtime and chain parameters for a run
+    fn prepare(&self) -> Result<(tokio::runtime::Runtime, RunParams), MinerError> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let difficulty = match self.config.difficulty {
+            Some(difficulty) => difficulty,
+            None => rt.block_on(self.client.get_difficulty())?,
+        };
+        let seed = rt.block_on(self.client.get_seed())?;
+        let config = match rt.block_on(self.client.get_config()) {
+            Ok(config) => Some(config),
+            // Without it rounds only end on a proof or a seed rotation
+            Err(_) if self.config.worker_tag.is_empty() => None,
+            // A tag the contract does not take would waste every proof
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(config) = &config {
+            check_worker_tag(&self.config.worker_tag, config.max_worker_tag_len)?;
+        }
+        let max_proof_age = config.map(|config| config.max_proof_age);
+        let threads = match self.config.threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        };
+        // More threads than free memory holds would swap or get the host killed
+        let threads = fit_threads_to_memory(threads, 0)?;
+        *self.status() = MinerStatus {
+            pid: std::process::id(),
+            started_at: unix_now(),
+            address: self.config.address.clone(),
+            difficulty,
+            threads,
+            ..MinerStatus::default()
+        };
+        let params = RunParams {
+            seed,
+            difficulty,
+            threads,
+            max_proof_age,
+        };
+        Ok((rt, params))
+    }
+
+    /// Mine rounds until stopped or `max_proofs` are reached
+    fn run(&self, rt: &tokio::runtime::Runtime, params: RunParams) {
+        let RunParams {
+            mut seed,
+            difficulty,
+            threads,
+            max_proof_age,
+        } = params;
+        let address = &self.config.address;
+        let mut proofs: u64 = 0;
+        let mut starts: Vec<u64> = Vec::new();
+        let mut last_timestamp = 0;
+        let schedule = Schedule::new(max_proof_age)
+            .with_jitter(self.config.refresh_jitter.unwrap_or(DEFAULT_REFRESH_JITTER));
+        let reason = loop {
+            if self.stop.load(Ordering::SeqCst) {
+                break StopReason::Stopped;
+            }
+            let params = RoundParams {
+                seed: seed.seed,
+                address: address.clone(),
+                timestamp: unix_now(),
+                difficulty,
+                nonce_end: None,
+                worker_tag: self.config.worker_tag.clone(),
+            };
+            // A round in the same second as the last one would find its proof
+            // again, so it carries on from where that one stopped
+            if params.timestamp != last_timestamp {
+                starts = (0..threads as u64).collect();
+            }
+            let mut timer = schedule.round(&params, &seed, unix_now());
+            self.update_status(|s| {
+                s.seed = hex::encode(params.seed);
+                s.seed_expires_at = seed.expires_at;
+                s.start_round(params.timestamp);
+            });
+            self.emit(MiningEvent::RoundStarted {
+                seed: params.seed,
+                timestamp: params.timestamp,
+                difficulty,
+                threads,
+            });
+
+            let round = Round::start(&params, &starts);
+            let mut last_report = Instant::now();
+            while !round.is_finished() {
+                std::thread::sleep(MONITOR_INTERVAL);
+                if self.stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                match timer.due(unix_now()) {
+                    // The threads keep hashing while the seed is read
+                    Some(Due::RefreshSeed) => {
+                        if self.refresh_seed(rt, &mut seed) {
+                            break;
+                        }
+                        timer.refresh_at = schedule.next_refresh(&seed, unix_now());
+                    }
+                    // A fresh timestamp keeps the round's proofs submittable
+                    Some(Due::Restart) => break,
+                    None => {}
+                }
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    let elapsed = round.elapsed();
+                    let counts = round.hash_counts();
+                    let (hashrate, thread_hashrates) = {
+                        let mut status = self.status();
+                        status.update_hashes(&counts, elapsed.as_secs_f64());
+                        status.updated_at = unix_now();
+                        (status.hashrate, status.thread_hashrates.clone())
+                    };
+                    self.emit(MiningEvent::Hashrate {
+                        hashrate,
+                        thread_hashrates,
+                        hashes: counts.iter().sum(),
+                        elapsed,
+                    });
+                }
+            }
+            let outcome = round.finish();
+            self.update_status(|s| s.update_hashes(&outcome.hashes, outcome.elapsed.as_secs_f64()));
+            starts = outcome.cursors(&starts);
+            last_timestamp = params.timestamp;
+
+            match &outcome.proof {
+                Some(found) => {
+                    let proof = found
+                        .submission(address)
+                        .with_worker_tag(&params.worker_tag);
+                    self.update_status(|s| {
+                        s.push_proof(ProofLogEntry {
+                            found_at: unix_now(),
+                            hash: proof.hash.clone(),
+                            nonce: proof.nonce,
+                            tx_hash: None,
+                            reward: None,
+                            error: None,
+                        })
+                    });
+                    self.emit(MiningEvent::ProofFound {
+                        proof: proof.clone(),
+                        hashes: outcome.total_hashes(),
+                        elapsed: outcome.elapsed,
+                    });
+                    match &self.signing_key {
+                        Some(key) => {
+                            if self.submit(rt, proof, key) {
+                                proofs += 1;
+                            }
+                        }
+                        None => proofs += 1,
+                    }
+                    if self.config.max_proofs.is_some_and(|max| proofs >= max) {
+                        break StopReason::MaxProofs;
+                    }
+                    self.refresh_seed(rt, &mut seed);
+                }
+                None if self.stop.load(Ordering::SeqCst) => break StopReason::Stopped,
+                None => {}
+            }
+        };
+        self.update_status(|s| s.stopped_at = Some(unix_now()));
+        self.emit(MiningEvent::Stopped { reason });
+    }
+
+    /// Submit `proof`, returning whether the chain accepted it
+    fn submit(
+        &self,
+        rt: &tokio::runtime::Runtime,
+        proof: ProofSubmission,
+        key: &SigningKey,
+    ) -> bool {
+        let error = match rt.block_on(self.client.submit_proof(proof.clone(), key)) {
+            Ok(result) if result.accepted => {
+                self.update_status(|s| {
+                    s.proof_submitted(&result.tx_hash, result.reward.as_deref())
+                });
+                self.emit(MiningEvent::ProofSubmitted { proof, result });
+                return true;
+            }
+            Ok(result) => result
+                .error
+                .unwrap_or_else(|| format!("Transaction {} rejected", result.tx_hash)),
+            Err(e) => e.to_string(),
+        };
+        self.update_status(|s| s.proof_failed(unix_now(), error.clone()));
+        self.emit(MiningEvent::SubmitFailed { proof, error });
+        false
+    }
+
+    /// Pick up a seed rotation, keeping the old seed if the chain cannot be
+    /// read; whether the seed changed
+    fn refresh_seed(&self, rt: &tokio::runtime::Runtime, seed: &mut SeedInfo) -> bool {
+        match rt.block_on(self.client.get_seed()) {
+            Ok(info) => {
+                let rotated = info.seed != seed.seed;
+                if rotated {
+                    self.emit(MiningEvent::SeedRotated { seed: info.seed });
+                }
+                *seed = info;
+                rotated
+            }
+            Err(e) => {
+                self.update_status(|s| {
+                    s.push_error(unix_now(), format!("Could not refresh seed: {}", e))
+                });
+                false
+            }
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::MockChainClient;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    fn config(max_proofs: Option<u64>) -> MinerConfig {
+        MinerConfig {
+            address: "bostrom1miner".to_string(),
+            threads: 2,
+            difficulty: None,
+            max_proofs,
+            refresh_jitter: Some(Duration::ZERO),
+            worker_tag: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_round_finds_valid_proof() {
+        let params = RoundParams {
+            seed: [5u8; 32],
+            address: "bostrom1miner".to_string(),
+            timestamp: 1_700_000_000,
+            difficulty: 2,
+            nonce_end: None,
+            worker_tag: Vec::new(),
+        };
+        let round = Round::start(&params, &[0, 1]);
+        while !round.is_finished() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let outcome = round.finish();
+        let proof = outcome.proof.clone().unwrap();
+        assert!(outcome.total_hashes() > proof.nonce / 2);
+
+        let mut input = params.seed.to_vec();
+        input.extend_from_slice(params.address.as_bytes());
+        input.extend_from_slice(&params.timestamp.to_le_bytes());
+        input.extend_from_slice(&proof.nonce.to_le_bytes());
+        assert_eq!(crate::hash(&input), proof.hash);
+        assert!(crate::meets_difficulty(&proof.hash, 2));
+        assert_eq!(
+            params.deadline(3600),
+            1_700_000_000 + 3600 - PROOF_AGE_MARGIN_SECS
+        );
+    }
+
+    #[test]
+    fn test_round_ends_with_its_nonce_range() {
+        let range = NonceRange::partition(100, 6, 1, 3).unwrap();
+        assert_eq!(range.start, 106);
+        assert_eq!(range.end, Some(112));
+        assert!(range.contains(111) && !range.contains(112) && !range.contains(105));
+        assert_eq!(NonceRange::partition(0, 6, 3, 3), None);
+        assert_eq!(NonceRange::partition(u64::MAX - 6, 6, 1, 3), None);
+
+        let params = RoundParams {
+            seed: [7u8; 32],
+            address: "bostrom1miner".to_string(),
+            timestamp: 1_700_000_000,
+            // Never found
+            difficulty: 60,
+            nonce_end: range.end,
+            worker_tag: Vec::new(),
+        };
+        let round = Round::start(&params, &range.starts(2));
+        while !round.is_finished() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let outcome = round.finish();
+        assert!(outcome.exhausted);
+        assert_eq!(outcome.proof, None);
+        assert_eq!(outcome.hashes, vec![3, 3]);
+        assert_eq!(outcome.cursors(&range.starts(2)), vec![112, 113]);
+    }
+
+    #[test]
+    fn test_random_starts_stay_in_range() {
+        let starts = NonceRange::default().random_starts(4).unwrap();
+        assert!(starts.windows(2).all(|pair| pair[1] == pair[0] + 1));
+        assert!(starts[3] < 1 << 53);
+
+        let range = NonceRange::partition(1000, 10, 0, 1).unwrap();
+        for _ in 0..20 {
+            let starts = range.random_starts(2).unwrap();
+            assert!(starts.iter().all(|&nonce| range.contains(nonce)));
+        }
+        // Too small to start anywhere but the beginning
+        let tiny = NonceRange::partition(1000, 2, 0, 1).unwrap();
+        assert_eq!(tiny.random_starts(2).unwrap(), vec![1000, 1001]);
+    }
+
+    #[test]
+    fn test_continued_round_finds_the_next_proof() {
+        let params = RoundParams {
+            seed: [6u8; 32],
+            address: "bostrom1miner".to_string(),
+            timestamp: 1_700_000_000,
+            difficulty: 2,
+            nonce_end: None,
+            worker_tag: Vec::new(),
+        };
+        let found = |starts: &[u64]| {
+            let round = Round::start(&params, starts);
+            while !round.is_finished() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            round.finish()
+        };
+        let first = found(&[0, 1]);
+        let starts = first.cursors(&[0, 1]);
+        let second = found(&starts).proof.unwrap();
+        // The same seed and timestamp, but the first proof is not found again
+        assert!(second.nonce >= *starts.iter().min().unwrap());
+        assert_ne!(first.proof.unwrap().nonce, second.nonce);
+    }
+
+    #[test]
+    fn test_miner_submits_until_max_proofs() {
+        let client = Arc::new(MockChainClient::new().with_difficulty(1));
+        let (miner, events) = Miner::new(config(Some(2)), client.clone(), Some(signing_key()));
+        miner.start().unwrap();
+        assert!(matches!(miner.start(), Err(MinerError::AlreadyRunning)));
+        miner.wait();
+
+        let events: Vec<MiningEvent> = events.try_iter().collect();
+        let submitted = events
+            .iter()
+            .filter(|e| matches!(e, MiningEvent::ProofSubmitted { .. }))
+            .count();
+        assert_eq!(submitted, 2);
+        assert!(matches!(
+            events.last(),
+            Some(MiningEvent::Stopped {
+                reason: StopReason::MaxProofs
+            })
+        ));
+        assert_eq!(client.submissions().len(), 2);
+        assert!(client
+            .submissions()
+            .iter()
+            .all(|p| p.miner_address == "bostrom1miner"));
+
+        let status = miner.status();
+        assert_eq!(status.difficulty, 1);
+        assert_eq!(status.proofs_found, 2);
+        assert_eq!(status.proofs_submitted, 2);
+        assert!(status.stopped_at.is_some());
+        assert!(!miner.is_running());
+    }
+
+    #[test]
+    fn test_miner_mines_with_worker_tag() {
+        let tagged = MinerConfig {
+            worker_tag: b"rig-1".to_vec(),
+            ..config(Some(1))
+        };
+        let client = Arc::new(MockChainClient::new().with_difficulty(1));
+        let (miner, _events) = Miner::new(tagged.clone(), client, Some(signing_key()));
+        assert!(matches!(
+            miner.start(),
+            Err(MinerError::WorkerTag(WorkerTagError::Unsupported))
+        ));
+
+        let contract = crate::rpc::ConfigResponse {
+            token_denom: crate::rpc::LI_DENOM.to_string(),
+            difficulty: 1,
+            base_reward: "1000000".to_string(),
+            max_proof_age: 3600,
+            period_duration: 600,
+            target_proofs_per_window: None,
+            max_worker_tag_len: Some(8),
+            admin: String::new(),
+            paused: false,
+        };
+        let client = Arc::new(
+            MockChainClient::new()
+                .with_difficulty(1)
+                .with_config(contract),
+        );
+        let (miner, _events) = Miner::new(tagged, client.clone(), Some(signing_key()));
+        miner.start().unwrap();
+        miner.wait();
+        let proof = &client.submissions()[0];
+        assert_eq!(
+            proof.worker_tag.as_deref(),
+            Some(hex::encode("rig-1").as_str())
+        );
+
+        let mut input = [0u8; 32].to_vec();
+        input.extend_from_slice(b"bostrom1minerrig-1");
+        input.extend_from_slice(&proof.timestamp.to_le_bytes());
+        input.extend_from_slice(&proof.nonce.to_le_bytes());
+        assert_eq!(hex::encode(crate::hash(&input)), proof.hash);
+
+        assert_eq!(check_worker_tag(b"", None), Ok(()));
+        assert_eq!(
+            check_worker_tag(b"rig-1", Some(4)),
+            Err(WorkerTagError::TooLong { len: 5, max: 4 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_miner_streams_events_to_async_hosts() {
+        let client = Arc::new(MockChainClient::new().with_difficulty(1));
+        let (miner, _events) = Miner::new(config(Some(1)), client, Some(signing_key()));
+        let mut stream = miner.stream();
+        let mut receiver = miner.subscribe();
+        // Starting from inside a runtime must not nest runtimes
+        miner.start().unwrap();
+
+        let mut seen = Vec::new();
+        while let Some(event) = stream.next().await {
+            let stopped = matches!(event, MiningEvent::Stopped { .. });
+            seen.push(event);
+            if stopped {
+                break;
+            }
+        }
+        assert!(matches!(seen[0], MiningEvent::RoundStarted { .. }));
+        assert!(seen
+            .iter()
+            .any(|e| matches!(e, MiningEvent::ProofSubmitted { .. })));
+        assert!(matches!(
+            seen.last(),
+            Some(MiningEvent::Stopped {
+                reason: StopReason::MaxProofs
+            })
+        ));
+        // Every subscriber gets every event
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            MiningEvent::RoundStarted { .. }
+        ));
+        miner.wait();
+    }
+
+    #[test]
+    fn test_miner_refreshes_timestamp_before_proofs_get_too_old() {
+        let client = MockChainClient::new();
+        let contract = crate::rpc::ConfigResponse {
+            token_denom: crate::rpc::LI_DENOM.to_string(),
+            difficulty: 60,
+            base_reward: "1000000".to_string(),
+            // Rounds get a second before their deadline
+            max_proof_age: PROOF_AGE_MARGIN_SECS + 1,
+            period_duration: 600,
+            target_proofs_per_window: None,
+            max_worker_tag_len: None,
+            admin: String::new(),
+            paused: false,
+        };
+        let config = MinerConfig {
+            difficulty: Some(60),
+            ..config(None)
+        };
+        let (miner, events) = Miner::new(config, Arc::new(client.with_config(contract)), None);
+        miner.start().unwrap();
+        let timestamps: Vec<u64> = events
+            .iter()
+            .filter_map(|event| match event {
+                MiningEvent::RoundStarted { timestamp, .. } => Some(timestamp),
+                _ => None,
+            })
+            .take(2)
+            .collect();
+        miner.stop();
+        assert!(timestamps[1] > timestamps[0], "{:?}", timestamps);
+    }
+
+    #[test]
+    fn test_miner_picks_up_seed_rotation_mid_round() {
+        // An interval but no rotation time: the seed is read every second
+        let client = Arc::new(MockChainClient::new().with_seed(SeedInfo::new([1u8; 32], 1)));
+        let config = MinerConfig {
+            difficulty: Some(60),
+            ..config(None)
+        };
+        let (miner, events) = Miner::new(config, client.clone(), None);
+        miner.start().unwrap();
+        assert!(matches!(
+            events.recv().unwrap(),
+            MiningEvent::RoundStarted { seed, .. } if seed == [1u8; 32]
+        ));
+        // Reads of the same seed leave the round running
+        std::thread::sleep(Duration::from_millis(2500));
+        client.set_seed(SeedInfo::new([2u8; 32], 1));
+        let events: Vec<MiningEvent> = events
+            .iter()
+            .filter(|e| !matches!(e, MiningEvent::Hashrate { .. }))
+            .take(2)
+            .collect();
+        miner.stop();
+        assert!(matches!(
+            events[0],
+            MiningEvent::SeedRotated { seed } if seed == [2u8; 32]
+        ));
+        assert!(matches!(
+            events[1],
+            MiningEvent::RoundStarted { seed, .. } if seed == [2u8; 32]
+        ));
+    }
+
+    #[test]
+    fn test_miner_reports_failures_and_stops() {
+        let client = Arc::new(MockChainClient::new());
+        client.fail_next(RpcError::Network("unreachable".to_string()));
+        let (miner, _events) = Miner::new(config(None), client.clone(), None);
+        assert!(matches!(miner.start(), Err(MinerError::Rpc(_))));
+        assert!(!miner.is_running());
+
+        // Too hard to finish a round before it is stopped
+        let config = MinerConfig {
+            difficulty: Some(60),
+            ..config(None)
+        };
+        let (miner, events) = Miner::new(config, client, None);
+        miner.start().unwrap();
+        assert!(matches!(
+            events.recv().unwrap(),
+            MiningEvent::RoundStarted { difficulty: 60, .. }
+        ));
+        miner.stop();
+        assert!(matches!(
+            events.recv().unwrap(),
+            MiningEvent::Stopped {
+                reason: StopReason::Stopped
+            }
+        ));
+        assert!(miner.status().round_hashes > 0);
+    }
+}
@@ -0,0 +1,320 @@
+//! One mining round: a seed and timestamp hashed on a set of threads
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::proof::proof_input;
+use crate::rpc::ProofSubmission;
+use crate::{meets_difficulty, UniversalHash};
+
+/// A round is restarted with a fresh timestamp this long before its proofs
+/// get too old for the contract, leaving time to submit a late find
+pub const PROOF_AGE_MARGIN_SECS: u64 = 60;
+
+/// Random starting nonces stay below 2^53, so they survive JSON number handling
+const MAX_RANDOM_NONCE: u64 = 1 << 53;
+
+/// What a round hashes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundParams {
+    pub seed: [u8; 32],
+    /// Miner address, part of every proof input
+    pub address: String,
+    /// Unix seconds in the proof input, fixed for the whole round
+    pub timestamp: u64,
+    pub difficulty: u32,
+    /// End of the nonces to hash (exclusive), unbounded if `None`
+    pub nonce_end: Option<u64>,
+    /// Bytes hashed after the address, naming this machine in its proofs
+    /// (empty: no tag)
+    pub worker_tag: Vec<u8>,
+}
+
+impl RoundParams {
+    /// Mine with `tag` after the address, for a contract that accepts it
+    pub fn with_worker_tag(mut self, tag: impl Into<Vec<u8>>) -> Self {
+        self.worker_tag = tag.into();
+        self
+    }
+
+    /// Unix time at which the round should be restarted, for a contract that
+    /// accepts proofs up to `max_proof_age` seconds old
+    pub fn deadline(&self, max_proof_age: u64) -> u64 {
+        self.timestamp + max_proof_age.saturating_sub(PROOF_AGE_MARGIN_SECS)
+    }
+}
+
+/// A worker tag the contract would reject
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerTagError {
+    #[error("The contract does not accept worker tags")]
+    Unsupported,
+    #[error("The worker tag is {len} bytes, the contract accepts at most {max}")]
+    TooLong { len: usize, max: usize },
+}
+
+/// Check `tag` against a contract accepting tags of up to `max_len` bytes
+/// (`None`: no tags); an empty tag always passes
+pub fn check_worker_tag(tag: &[u8], max_len: Option<u32>) -> Result<(), WorkerTagError> {
+    match max_len {
+        _ if tag.is_empty() => Ok(()),
+        None => Err(WorkerTagError::Unsupported),
+        Some(max) if tag.len() > max as usize => Err(WorkerTagError::TooLong {
+            len: tag.len(),
+            max: max as usize,
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+/// The nonces a miner hashes each round
+///
+/// Independent machines mining for one address split the nonce space into
+/// equal ranges, one per worker, so no two of them hash the same input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NonceRange {
+    pub start: u64,
+    /// End of the range (exclusive), unbounded if `None`
+    pub end: Option<u64>,
+}
+
+impl NonceRange {
+    /// Range `index` of `count` consecutive ranges of `size` nonces from `start`
+    pub fn partition(start: u64, size: u64, index: u64, count: u64) -> Option<Self> {
+        if size == 0 || index >= count {
+            return None;
+        }
+        let start = start.checked_add(size.checked_mul(index)?)?;
+        Some(Self {
+            start,
+            end: Some(start.checked_add(size)?),
+        })
+    }
+
+    pub fn contains(&self, nonce: u64) -> bool {
+        nonce >= self.start && self.end.is_none_or(|end| nonce < end)
+    }
+
+    /// First nonce of each of `threads` threads interleaved over the range
+    pub fn starts(&self, threads: usize) -> Vec<u64> {
+        interleaved(self.start, threads)
+    }
+
+    /// Like [`NonceRange::starts`], from a random point of the range
+    ///
+    /// Miners that share an address without splitting the nonce space
+    /// rarely hash the same nonces this way, where all of them starting at
+    /// the same nonce would.
+    pub fn random_starts(&self, threads: usize) -> Result<Vec<u64>, getrandom::Error> {
+        let span = self
+            .end
+            .unwrap_or(MAX_RANDOM_NONCE)
+            .saturating_sub(self.start)
+            .saturating_sub(threads as u64);
+        if span == 0 {
+            return Ok(self.starts(threads));
+        }
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(interleaved(
+            self.start + u64::from_le_bytes(bytes) % span,
+            threads,
+        ))
+    }
+}
+
+/// One starting nonce per thread, consecutive from `first`
+fn interleaved(first: u64, threads: usize) -> Vec<u64> {
+    (0..threads as u64).map(|thread| first + thread).collect()
+}
+
+/// A valid proof found by a mining thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundProof {
+    pub hash: [u8; 32],
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+impl FoundProof {
+    /// The proof as submitted to the contract for `miner_address`
+    pub fn submission(&self, miner_address: &str) -> ProofSubmission {
+        ProofSubmission {
+            hash: hex::encode(self.hash),
+            nonce: self.nonce,
+            timestamp: self.timestamp,
+            miner_address: miner_address.to_string(),
+            worker_tag: None,
+        }
+    }
+}
+
+/// How a round ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundOutcome {
+    /// The proof, unless the round was stopped first
+    pub proof: Option<FoundProof>,
+    /// Nonces each thread hashed
+    pub hashes: Vec<u64>,
+    pub elapsed: Duration,
+    /// Every thread reached the end of the nonce range without a proof
+    pub exhausted: bool,
+}
+
+impl RoundOutcome {
+    pub fn total_hashes(&self) -> u64 {
+        self.hashes.iter().sum()
+    }
+
+    /// Next nonce of each thread of a round started from `starts`
+    pub fn cursors(&self, starts: &[u64]) -> Vec<u64> {
+        let step = starts.len() as u64;
+        starts
+            .iter()
+            .zip(&self.hashes)
+            .map(|(start, hashes)| start + hashes * step)
+            .collect()
+    }
+
+    /// Average hashrate of each thread over the round
+    pub fn thread_hashrates(&self) -> Vec<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        self.hashes.iter().map(|&h| h as f64 / secs).collect()
+    }
+}
+
+/// A round being hashed in the background
+///
+/// Thread `i` of `n` hashes nonces `starts[i]`, `starts[i] + n`, ... which
+/// keeps all nonces small and avoids JSON precision issues with u64 > 2^53.
+/// The first proof found stops every thread, as does every thread reaching
+/// [`RoundParams::nonce_end`].
+#[derive(Debug)]
+pub struct Round {
+    stop: Arc<AtomicBool>,
+    /// Threads that reached the end of the nonce range
+    exhausted: Arc<AtomicUsize>,
+    hashes: Arc<Vec<AtomicU64>>,
+    found: Arc<Mutex<Option<FoundProof>>>,
+    handles: Vec<JoinHandle<()>>,
+    started: Instant,
+}
+
+impl Round {
+    /// Start one thread per entry of `starts`, each from its own first nonce
+    pub fn start(params: &RoundParams, starts: &[u64]) -> Self {
+        let threads = starts.len() as u64;
+        let stop = Arc::new(AtomicBool::new(false));
+        let hashes: Arc<Vec<AtomicU64>> =
+            Arc::new(starts.iter().map(|_| AtomicU64::new(0)).collect());
+        let found = Arc::new(Mutex::new(None::<FoundProof>));
+        let exhausted = Arc::new(AtomicUsize::new(0));
+        let handles = starts
+            .iter()
+            .enumerate()
+            .map(|(thread_id, &first_nonce)| {
+                let params = params.clone();
+                let stop = Arc::clone(&stop);
+                let hashes = Arc::clone(&hashes);
+                let found = Arc::clone(&found);
+                let exhausted = Arc::clone(&exhausted);
+                std::thread::spawn(move || {
+                    let mut hasher = UniversalHash::new();
+                    let mut nonce = first_nonce;
+                    while !stop.load(Ordering::Relaxed) {
+                        if params.nonce_end.is_some_and(|end| nonce >= end) {
+                            // The last thread to run out ends the round
+                            if exhausted.fetch_add(1, Ordering::SeqCst) + 1 == threads as usize {
+                                stop.store(true, Ordering::SeqCst);
+                            }
+                            return;
+                        }
+                        let input = proof_input(
+                            &params.seed,
+                            &params.address,
+                            &params.worker_tag,
+                            params.timestamp,
+                            nonce,
+                        );
+
+                        let result = hasher.hash(&input);
+                        hashes[thread_id].fetch_add(1, Ordering::Relaxed);
+
+                        if meets_difficulty(&result, params.difficulty) {
+                            let mut guard = found.lock().unwrap_or_else(|e| e.into_inner());
+                            if guard.is_none() {
+                                *guard = Some(FoundProof {
+                                    hash: result,
+                                    nonce,
+                                    timestamp: params.timestamp,
+                                });
+                                stop.store(true, Ordering::SeqCst);
+                            }
+                            return;
+                        }
+
+                        nonce += threads;
+                    }
+                })
+            })
+            .collect();
+        Self {
+            stop,
+            exhausted,
+            hashes,
+            found,
+            handles,
+            started: Instant::now(),
+        }
+    }
+
+    /// Start `threads` threads from nonces `0..threads`
+    pub fn fresh(params: &RoundParams, threads: usize) -> Self {
+        Self::start(params, &(0..threads as u64).collect::<Vec<_>>())
+    }
+
+    /// Whether a proof was found, the nonces ran out or the round was stopped
+    pub fn is_finished(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Ask every thread to stop after its current hash
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    /// Nonces each thread has hashed so far
+    pub fn hash_counts(&self) -> Vec<u64> {
+        self.hashes
+            .iter()
+            .map(|h| h.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Stop the threads and wait for them
+    pub fn finish(self) -> RoundOutcome {
+        self.stop();
+        let threads = self.handles.len();
+        for handle in self.handles {
+            let _ = handle.join();
+        }
+        let proof = self.found.lock().unwrap_or_else(|e| e.into_inner()).take();
+        RoundOutcome {
+            exhausted: proof.is_none() && self.exhausted.load(Ordering::SeqCst) == threads,
+            proof,
+            hashes: self
+                .hashes
+                .iter()
+                .map(|h| h.load(Ordering::Relaxed))
+                .collect(),
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! When a mining run looks at the chain again and restarts its round
+//!
+//! Two clocks bound a round: the seed rotates every `seed_interval`, and a
+//! proof is only accepted up to `max_proof_age` seconds after its timestamp.
+//! A [`Schedule`] turns the seed's metadata into the time to read the seed
+//! again and the round's timestamp into the time to restart it. Seed reads
+//! are spread over a few random seconds, so a fleet of miners does not hit
+//! the RPC nodes in the same second every rotation.
+//!
+//! The chain rotates its seed lazily, so a read at the rotation time may
+//! still return the old seed: an overdue rotation is read again every
+//! [`SEED_POLL_INTERVAL`] until the new seed shows up, and a read that
+//! returns the same seed leaves the round running.
+
+use std::time::Duration;
+
+use super::round::RoundParams;
+use crate::rpc::{jitter, SeedInfo};
+
+/// Seed reads are spread over up to this long by default
+pub const DEFAULT_REFRESH_JITTER: Duration = Duration::from_secs(5);
+
+/// How often an overdue or unknown seed rotation is read again
+pub const SEED_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Timing of the rounds of one mining run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    /// Oldest proof the contract accepts, in seconds
+    max_proof_age: Option<u64>,
+    jitter: Duration,
+}
+
+/// What a running round has to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due {
+    /// Read the seed; the round ends only if it changed
+    RefreshSeed,
+    /// Start a round with a fresh timestamp, as this one's proofs would
+    /// soon be too old to submit
+    Restart,
+}
+
+/// The times a running round is waiting for, in unix seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTimer {
+    pub refresh_at: Option<u64>,
+    pub deadline: Option<u64>,
+}
+
+impl Schedule {
+    /// A schedule for a contract accepting proofs up to `max_proof_age`
+    /// seconds old, unlimited if `None`
+    pub fn new(max_proof_age: Option<u64>) -> Self {
+        Self {
+            max_proof_age,
+            jitter: DEFAULT_REFRESH_JITTER,
+        }
+    }
+
+    /// Spread seed reads over up to `jitter` (zero reads on the second)
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Timer of a round of `params`, started at `now` on `seed`
+    pub fn round(&self, params: &RoundParams, seed: &SeedInfo, now: u64) -> RoundTimer {
+        RoundTimer {
+            refresh_at: self.next_refresh(seed, now),
+            deadline: self.max_proof_age.map(|age| params.deadline(age)),
+        }
+    }
+
+    /// Unix time to read `seed` again, as read at `now`
+    ///
+    /// Just after its rotation if that is known and ahead, a poll if it is
+    /// overdue or only the interval is known, and never (until the next
+    /// proof) without either.
+    pub fn next_refresh(&self, seed: &SeedInfo, now: u64) -> Option<u64> {
+        let poll = SEED_POLL_INTERVAL.as_secs();
+        let at = match seed.expires_at {
+            Some(at) if at > now => at,
+            Some(_) => now + poll,
+            None if seed.seed_interval > 0 => now + seed.seed_interval.min(poll),
+            None => return None,
+        };
+        Some(at + self.jitter_secs())
+    }
+
+    fn jitter_secs(&self) -> u64 {
+        (self.jitter.as_secs_f64() * jitter()).round() as u64
+    }
+}
+
+impl RoundTimer {
+    /// What is due at `now`, a seed read first
+    pub fn due(&self, now: u64) -> Option<Due> {
+        if self.refresh_at.is_some_and(|at| now >= at) {
+            Some(Due::RefreshSeed)
+        } else if self.deadline.is_some_and(|at| now >= at) {
+            Some(Due::Restart)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miner::PROOF_AGE_MARGIN_SECS;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn params() -> RoundParams {
+        RoundParams {
+            seed: [1u8; 32],
+            address: "bostrom1miner".to_string(),
+            timestamp: NOW,
+            difficulty: 8,
+            nonce_end: None,
+            worker_tag: Vec::new(),
+        }
+    }
+
+    fn seed(expires_at: Option<u64>, seed_interval: u64) -> SeedInfo {
+        SeedInfo {
+            expires_at,
+            ..SeedInfo::new([1u8; 32], seed_interval)
+        }
+    }
+
+    #[test]
+    fn test_round_timer() {
+        let schedule = Schedule::new(Some(600)).with_jitter(Duration::ZERO);
+        let timer = schedule.round(&params(), &seed(Some(NOW + 100), 3600), NOW);
+        assert_eq!(timer.refresh_at, Some(NOW + 100));
+        assert_eq!(timer.deadline, Some(NOW + 600 - PROOF_AGE_MARGIN_SECS));
+        assert_eq!(timer.due(NOW + 99), None);
+        assert_eq!(timer.due(NOW + 100), Some(Due::RefreshSeed));
+
+        let later = RoundTimer {
+            refresh_at: Some(NOW + 1000),
+            ..timer
+        };
+        assert_eq!(later.due(NOW + 540), Some(Due::Restart));
+        // Without a proof age rounds only end on a seed change
+        let unlimited = Schedule::new(None).round(&params(), &seed(None, 0), NOW);
+        assert_eq!(unlimited.due(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_next_refresh() {
+        let schedule = Schedule::new(None).with_jitter(Duration::ZERO);
+        let poll = SEED_POLL_INTERVAL.as_secs();
+        assert_eq!(
+            schedule.next_refresh(&seed(Some(NOW + 30), 3600), NOW),
+            Some(NOW + 30)
+        );
+        // Overdue: the chain has not rotated yet
+        assert_eq!(
+            schedule.next_refresh(&seed(Some(NOW), 3600), NOW),
+            Some(NOW + poll)
+        );
+        // Only the interval is known
+        assert_eq!(
+            schedule.next_refresh(&seed(None, 3600), NOW),
+            Some(NOW + poll)
+        );
+        assert_eq!(schedule.next_refresh(&seed(None, 20), NOW), Some(NOW + 20));
+        assert_eq!(schedule.next_refresh(&seed(None, 0), NOW), None);
+
+        let jittered = schedule.with_jitter(Duration::from_secs(10));
+        for _ in 0..20 {
+            let at = jittered
+                .next_refresh(&seed(Some(NOW + 30), 3600), NOW)
+                .unwrap();
+            assert!((NOW + 30..=NOW + 40).contains(&at), "{}", at);
+        }
+    }
+}
@@ -0,0 +1,825 @@
+//! The mining loop of `uhash mine`
+//!
+//! A [`Session`] mines round after round on the calling thread: it takes
+//! turns at the addresses of an [`AddressBook`], hands each proof it finds to
+//! a [`Submitter`], restarts the round when the seed rotates, the round grows
+//! too old or a [`MinerControl`] change asks for it, and waits while mining is
+//! paused. Unlike a [`Miner`](super::Miner) it keeps what it holds when it is
+//! stopped: a proof goes to the [`PendingQueue`] and an unfinished round to a
+//! [`RoundState`] file for `--resume`.
+//!
+//! The host, usually the CLI, sees what happens as [`SessionEvent`]s and
+//! decides when to stop through [`SessionHost`].
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+use super::addresses::{AddressBook, AddressStats};
+use super::control::MinerControl;
+use super::delivery::{DeliveryError, DeliveryEvent, Submitted, Submitter};
+use super::memory::{available_memory, fit_threads_to_memory, MemoryError};
+use super::round::{NonceRange, Round, RoundParams};
+use super::schedule::{Due, Schedule};
+use super::{unix_now, MONITOR_INTERVAL, PROGRESS_INTERVAL};
+use crate::algorithm::difficulty::Luck;
+use crate::pending::PendingQueue;
+use crate::resume::RoundState;
+use crate::rpc::{ProofSubmission, RpcClient, RpcError, SeedInfo};
+use crate::stats::{RoundOutcome, RoundRecord};
+use crate::status::{MinerStatus, ProofLogEntry};
+
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error(transparent)]
+    Delivery(#[from] DeliveryError),
+
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+
+    #[error("Could not pick a random nonce: {0}")]
+    RandomNonce(getrandom::Error),
+
+    #[error(
+        "Could not save proof to {}: {source} (submit it with: uhash send --hash {} --nonce {} --timestamp {})",
+        .path.display(), .proof.hash, .proof.nonce, .proof.timestamp
+    )]
+    NotSaved {
+        path: PathBuf,
+        proof: ProofSubmission,
+        source: std::io::Error,
+    },
+}
+
+/// What a session mines and where it keeps what it holds when stopped
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    pub difficulty: u32,
+    pub nonces: NonceRange,
+    /// Start each round's threads at random nonces of the range
+    pub nonce_random: bool,
+    /// Bytes hashed after the address to name this machine (empty: no tag)
+    pub worker_tag: Vec<u8>,
+    /// How long each address of a shared book mines before the next one
+    pub address_slice: Duration,
+    /// Stop at the first proof instead of submitting it
+    pub no_submit: bool,
+    /// Stop once this many proofs were sent or handed off for signing
+    pub max_proofs: Option<u64>,
+    /// Proofs per transaction when submitting those an earlier run saved
+    pub batch_size: usize,
+    pub schedule: Schedule,
+    /// Where a proof found while stopping is kept for the next start
+    pub pending: PendingQueue,
+    /// Where a round stopped in the middle is saved for `--resume`
+    pub round_path: PathBuf,
+    /// Rewritten with the miner's status on every change
+    pub status_path: PathBuf,
+}
+
+/// Why a session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEnd {
+    /// A round ended without a proof and nothing asked for another
+    Finished,
+    /// [`SessionHost::stop_requested`] said so
+    Stopped,
+    /// A proof was found with `no_submit` set
+    NoSubmit,
+    /// `max_proofs` proofs were sent or handed off
+    MaxProofs,
+}
+
+/// Why a round ended without a proof and a fresh one starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Restart {
+    SeedRotated {
+        seed: [u8; 32],
+    },
+    /// The thread count changed or mining was paused through [`MinerControl`]
+    Reconfigured {
+        threads: usize,
+        paused: bool,
+    },
+    /// The round's proofs would soon be too old to submit
+    Expired,
+    /// The address book moves on to `next`
+    SliceEnded {
+        next: String,
+    },
+    /// Every nonce of the range was hashed at this timestamp
+    NoncesExhausted,
+}
+
+/// What the session did, in order
+#[derive(Debug)]
+pub enum SessionEvent {
+    /// Mining waits, paused through [`MinerControl`] or `held` while the
+    /// machine is in use
+    Paused {
+        held: bool,
+    },
+    Resumed,
+    /// Only `threads` of `requested` fit in the `available` bytes of memory
+    ThreadsCapped {
+        threads: usize,
+        requested: usize,
+        available: Option<u64>,
+    },
+    RoundStarted {
+        address: String,
+        seed: [u8; 32],
+        difficulty: u32,
+        timestamp: u64,
+        threads: usize,
+    },
+    /// Progress of the running round, every [`PROGRESS_INTERVAL`]
+    Hashrate {
+        hashrate: f64,
+        thread_hashrates: Vec<f64>,
+        hashes: u64,
+        elapsed: Duration,
+        proofs_submitted: u64,
+    },
+    RoundEnded {
+        address: String,
+        seed: [u8; 32],
+        round: RoundRecord,
+    },
+    /// The stopped round could not be saved for `--resume`
+    RoundNotSaved {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// A proof, with the work it took since the last one
+    ProofFound {
+        proof: ProofSubmission,
+        /// Nonces each thread hashed in the round
+        thread_hashes: Vec<u64>,
+        elapsed: Duration,
+        luck: Luck,
+    },
+    Delivery(DeliveryEvent),
+    /// A proof found while stopping was saved for the next start
+    ProofPending {
+        proof: ProofSubmission,
+        path: PathBuf,
+    },
+    /// Mining goes on after a proof
+    Continuing,
+    /// The seed read after a proof is a new one
+    SeedRotated {
+        seed: [u8; 32],
+    },
+    Restarting(Restart),
+    /// The seed could not be read; mining carries on with the last one
+    SeedUnreadable(RpcError),
+    /// The status file could not be written; reported once
+    StatusNotWritten {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+/// What a session reports to and asks of its host
+pub trait SessionHost<'a> {
+    fn event(&mut self, event: SessionEvent);
+
+    /// Whether to stop, asked every [`MONITOR_INTERVAL`]
+    fn stop_requested(&self) -> bool;
+
+    /// Apply outside changes, such as a reloaded config, every
+    /// [`MONITOR_INTERVAL`] while a round runs
+    fn poll(&mut self, _submitter: &mut Submitter<'a>) {}
+}
+
+/// How a session went
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub end: SessionEnd,
+    pub proofs_found: u64,
+    pub proofs_submitted: u64,
+    pub proofs_unsigned: u64,
+    /// Proofs saved to the pending queue for the next start
+    pub proofs_pending: u64,
+    /// The last round was saved for `--resume`
+    pub round_saved: bool,
+    pub hashes: u64,
+    pub elapsed: Duration,
+    pub addresses: Vec<AddressStats>,
+}
+
+/// Mining for an address book until the host stops it
+pub struct Session<'a> {
+    config: SessionConfig,
+    rt: &'a Runtime,
+    submitter: Submitter<'a>,
+    control: Arc<MinerControl>,
+    status: Arc<Mutex<MinerStatus>>,
+    /// A failed status write has been reported; later failures stay quiet
+    status_warned: bool,
+    book: AddressBook,
+    seed_info: SeedInfo,
+    threads: usize,
+    /// A saved round to continue first
+    resumed: Option<RoundState>,
+}
+
+impl<'a> Session<'a> {
+    /// A session mining on `control`'s thread count, sharing its status
+    /// with `status` (the management API reads it too)
+    pub fn new(
+        config: SessionConfig,
+        rt: &'a Runtime,
+        submitter: Submitter<'a>,
+        control: Arc<MinerControl>,
+        status: Arc<Mutex<MinerStatus>>,
+        book: AddressBook,
+        seed_info: SeedInfo,
+    ) -> Self {
+        let threads = control.threads();
+        Self {
+            config,
+            rt,
+            submitter,
+            control,
+            status,
+            status_warned: false,
+            book,
+            seed_info,
+            threads,
+            resumed: None,
+        }
+    }
+
+    /// Continue `state` as the first round, if it still fits
+    pub fn resume(mut self, state: RoundState) -> Self {
+        self.resumed = Some(state);
+        self
+    }
+
+    /// Submit the proofs earlier runs saved, then mine until a proof with
+    /// `no_submit`, `max_proofs`, or the host's stop
+    pub fn run(mut self, host: &mut impl SessionHost<'a>) -> Result<SessionSummary, SessionError> {
+        let started = Instant::now();
+        if !self.config.no_submit {
+            let addresses: Vec<String> = self
+                .book
+                .entries()
+                .iter()
+                .map(|entry| entry.address.clone())
+                .collect();
+            for address in addresses {
+                self.submitter.submit_pending(
+                    &self.config.pending,
+                    &address,
+                    self.config.batch_size,
+                    &mut |event| host.event(SessionEvent::Delivery(event)),
+                )?;
+            }
+        }
+
+        let mut summary = SessionSummary {
+            end: SessionEnd::Finished,
+            proofs_found: 0,
+            proofs_submitted: 0,
+            proofs_unsigned: 0,
+            proofs_pending: 0,
+            round_saved: false,
+            hashes: 0,
+            elapsed: Duration::ZERO,
+            addresses: Vec::new(),
+        };
+        // Work since the last proof, across rounds
+        let mut luck = Luck::default();
+        let mut last_round: Option<RoundState> = None;
+        let difficulty = self.config.difficulty;
+        let nonces = self.config.nonces;
+
+        loop {
+            if self.control.is_paused() {
+                self.update_status(host, |s| s.paused = true);
+                host.event(SessionEvent::Paused {
+                    held: self.control.is_held(),
+                });
+                while self.control.is_paused() && !host.stop_requested() {
+                    std::thread::sleep(MONITOR_INTERVAL);
+                }
+                self.control.take_restart();
+                self.update_status(host, |s| s.paused = false);
+                host.event(SessionEvent::Resumed);
+            }
+            if host.stop_requested() {
+                summary.end = SessionEnd::Stopped;
+                break;
+            }
+            let requested = self.control.threads();
+            self.threads = self.fit_threads(requested, host)?;
+            if self.threads != requested {
+                // Keep the API and config reloads from asking again every round
+                self.control.set_threads(self.threads);
+                self.control.take_restart();
+            }
+
+            let epoch_seed = self.seed_info.seed;
+            let address = self.book.next().to_string();
+            let now = unix_now();
+            // Continue a saved round on the same seed, or start a fresh one. A
+            // round in the same second as the last one would find its proof
+            // again, so it carries on from where that one stopped.
+            let previous = self
+                .resumed
+                .take()
+                .or_else(|| last_round.take().filter(|state| state.timestamp == now));
+            let (timestamp, start_nonces) = match previous {
+                Some(state)
+                    if state.matches(&address, &epoch_seed, difficulty)
+                        && state.cursors.iter().all(|&nonce| nonces.contains(nonce)) =>
+                {
+                    (state.timestamp, state.start_nonces(self.threads))
+                }
+                _ if self.config.nonce_random => {
+                    let starts = nonces
+                        .random_starts(self.threads)
+                        .map_err(SessionError::RandomNonce)?;
+                    (now, starts)
+                }
+                _ => (now, nonces.starts(self.threads)),
+            };
+            let params = RoundParams {
+                seed: epoch_seed,
+                address: address.clone(),
+                timestamp,
+                difficulty,
+                nonce_end: nonces.end,
+                worker_tag: self.config.worker_tag.clone(),
+            };
+            let mut timer = self
+                .config
+                .schedule
+                .round(&params, &self.seed_info, unix_now());
+            let (seed_expires_at, threads) = (self.seed_info.expires_at, self.threads);
+            self.update_status(host, |s| {
+                s.address = address.clone();
+                s.seed = hex::encode(epoch_seed);
+                s.seed_expires_at = seed_expires_at;
+                s.threads = threads;
+                s.start_round(timestamp);
+            });
+            host.event(SessionEvent::RoundStarted {
+                address: address.clone(),
+                seed: epoch_seed,
+                difficulty,
+                timestamp,
+                threads,
+            });
+
+            let round = Round::start(&params, &start_nonces);
+
+            // Watch progress, seed rotation and outside changes while threads work
+            let mut restart = None;
+            let mut last_report = Instant::now();
+            while !round.is_finished() {
+                std::thread::sleep(MONITOR_INTERVAL);
+
+                if host.stop_requested() {
+                    break;
+                }
+
+                // The threads keep hashing while the seed is read
+                if timer.due(unix_now()) == Some(Due::RefreshSeed) {
+                    if self.refresh_seed(host) {
+                        restart = Some(Restart::SeedRotated {
+                            seed: self.seed_info.seed,
+                        });
+                        break;
+                    }
+                    timer.refresh_at = self
+                        .config
+                        .schedule
+                        .next_refresh(&self.seed_info, unix_now());
+                }
+
+                host.poll(&mut self.submitter);
+
+                if self.control.take_restart() {
+                    restart = Some(Restart::Reconfigured {
+                        threads: self.control.threads(),
+                        paused: self.control.is_paused(),
+                    });
+                    break;
+                }
+
+                if timer.due(unix_now()) == Some(Due::Restart) {
+                    restart = Some(Restart::Expired);
+                    break;
+                }
+
+                self.submitter
+                    .follow(&mut |event| host.event(SessionEvent::Delivery(event)))?;
+
+                // Hand the machine to the next address
+                if self.book.len() > 1 && round.elapsed() >= self.config.address_slice {
+                    // Named once the round is counted in the book
+                    restart = Some(Restart::SliceEnded {
+                        next: String::new(),
+                    });
+                    break;
+                }
+
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    let elapsed = round.elapsed();
+                    let (hashrate, hashes, thread_hashrates) = self.update_status(host, |s| {
+                        s.update_hashes(&round.hash_counts(), elapsed.as_secs_f64());
+                        (s.hashrate, s.round_hashes, s.thread_hashrates.clone())
+                    });
+                    host.event(SessionEvent::Hashrate {
+                        hashrate,
+                        thread_hashrates,
+                        hashes,
+                        elapsed,
+                        proofs_submitted: self.submitter.proofs_submitted(),
+                    });
+                }
+            }
+
+            // Wait for all threads to finish
+            let outcome = round.finish();
+            let hashes = outcome.total_hashes();
+            summary.hashes += hashes;
+            self.book.record_round(&address, hashes);
+            luck.add(hashes, difficulty);
+            let elapsed = outcome.elapsed.as_secs_f64();
+            self.update_status(host, |s| s.update_hashes(&outcome.hashes, elapsed));
+            let state = RoundState::new(
+                &address,
+                &epoch_seed,
+                timestamp,
+                difficulty,
+                &start_nonces,
+                &outcome.hashes,
+            );
+            if outcome.proof.is_none() && host.stop_requested() {
+                match state.write(&self.config.round_path) {
+                    Ok(()) => summary.round_saved = true,
+                    Err(error) => host.event(SessionEvent::RoundNotSaved {
+                        path: self.config.round_path.clone(),
+                        error,
+                    }),
+                }
+            }
+            last_round = Some(state);
+            let round_outcome = if outcome.proof.is_some() {
+                RoundOutcome::Proof
+            } else if matches!(restart, Some(Restart::SeedRotated { .. })) {
+                RoundOutcome::SeedRotated
+            } else if outcome.exhausted {
+                RoundOutcome::Exhausted
+            } else {
+                RoundOutcome::Interrupted
+            };
+            host.event(SessionEvent::RoundEnded {
+                address: address.clone(),
+                seed: epoch_seed,
+                round: RoundRecord {
+                    started_at: timestamp,
+                    duration_secs: elapsed,
+                    hashes,
+                    threads,
+                    difficulty,
+                    outcome: round_outcome,
+                },
+            });
+
+            let Some(found) = outcome.proof else {
+                if let Some(mut restart) = restart {
+                    if let Restart::SliceEnded { next } = &mut restart {
+                        *next = self.book.next().to_string();
+                    }
+                    host.event(SessionEvent::Restarting(restart));
+                    continue;
+                }
+                if outcome.exhausted && !host.stop_requested() {
+                    host.event(SessionEvent::Restarting(Restart::NoncesExhausted));
+                    // The same timestamp would hash the same nonces again
+                    while unix_now() == timestamp {
+                        std::thread::sleep(MONITOR_INTERVAL);
+                    }
+                    continue;
+                }
+                if host.stop_requested() {
+                    summary.end = SessionEnd::Stopped;
+                }
+                break;
+            };
+
+            summary.proofs_found += 1;
+            self.book.record_proof(&address);
+            self.update_status(host, |s| {
+                s.push_proof(ProofLogEntry {
+                    found_at: unix_now(),
+                    hash: hex::encode(found.hash),
+                    nonce: found.nonce,
+                    tx_hash: None,
+                    reward: None,
+                    error: None,
+                })
+            });
+            let proof = found
+                .submission(&address)
+                .with_worker_tag(&self.config.worker_tag);
+            host.event(SessionEvent::ProofFound {
+                proof: proof.clone(),
+                thread_hashes: outcome.hashes,
+                elapsed: outcome.elapsed,
+                luck,
+            });
+            luck = Luck::default();
+
+            if self.config.no_submit {
+                summary.end = SessionEnd::NoSubmit;
+                break;
+            }
+            if host.stop_requested() {
+                // Keep the proof for the next start rather than delay stopping
+                let pending = &self.config.pending;
+                pending
+                    .push(&proof)
+                    .map_err(|source| SessionError::NotSaved {
+                        path: pending.path().to_path_buf(),
+                        proof: proof.clone(),
+                        source,
+                    })?;
+                host.event(SessionEvent::ProofPending {
+                    proof,
+                    path: pending.path().to_path_buf(),
+                });
+                summary.proofs_pending += 1;
+                summary.end = SessionEnd::Stopped;
+                break;
+            }
+
+            let submitted = self
+                .submitter
+                .submit(std::slice::from_ref(&proof), &mut |event| {
+                    host.event(SessionEvent::Delivery(event))
+                })?;
+            match submitted.into_iter().next() {
+                Some(Submitted::Sent {
+                    tx_hash, reward, ..
+                }) => {
+                    self.book.record_submitted(&address);
+                    self.update_status(host, |s| s.proof_submitted(&tx_hash, reward.as_deref()));
+                }
+                Some(Submitted::Forwarded) => self.book.record_submitted(&address),
+                Some(Submitted::Failed(error)) => {
+                    self.update_status(host, |s| s.proof_failed(unix_now(), error));
+                }
+                Some(Submitted::Unsigned) | None => {}
+            }
+
+            let sent = self.submitter.proofs_submitted() + self.submitter.proofs_unsigned();
+            if self.config.max_proofs.is_some_and(|max| sent >= max) {
+                summary.end = SessionEnd::MaxProofs;
+                break;
+            }
+            if host.stop_requested() {
+                summary.end = SessionEnd::Stopped;
+                break;
+            }
+            host.event(SessionEvent::Continuing);
+            // Mine the next proof on the new seed if it rotated meanwhile
+            if self.refresh_seed(host) {
+                host.event(SessionEvent::SeedRotated {
+                    seed: self.seed_info.seed,
+                });
+            }
+        }
+
+        // Stopping at max_proofs counts proofs on chain, so see them into a block
+        if summary.end == SessionEnd::MaxProofs {
+            self.submitter
+                .settle(&mut |event| host.event(SessionEvent::Delivery(event)))?;
+        }
+
+        summary.proofs_submitted = self.submitter.proofs_submitted();
+        summary.proofs_unsigned = self.submitter.proofs_unsigned();
+        summary.elapsed = started.elapsed();
+        summary.addresses = self.book.entries().to_vec();
+        self.update_status(host, |s| s.stopped_at = Some(unix_now()));
+        Ok(summary)
+    }
+
+    /// `requested` threads, or as many as free memory holds
+    fn fit_threads(
+        &self,
+        requested: usize,
+        host: &mut impl SessionHost<'a>,
+    ) -> Result<usize, SessionError> {
+        let threads = fit_threads_to_memory(requested, self.threads)?;
+        if threads < requested {
+            host.event(SessionEvent::ThreadsCapped {
+                threads,
+                requested,
+                available: available_memory(),
+            });
+        }
+        Ok(threads)
+    }
+
+    /// Read the seed again, reporting a failure; whether it changed
+    fn refresh_seed(&mut self, host: &mut impl SessionHost<'a>) -> bool {
+        refresh_seed(self.rt, self.submitter.client(), &mut self.seed_info).unwrap_or_else(|e| {
+            host.event(SessionEvent::SeedUnreadable(e));
+            false
+        })
+    }
+
+    /// Apply `change` to the status and write the status file
+    fn update_status<R>(
+        &mut self,
+        host: &mut impl SessionHost<'a>,
+        change: impl FnOnce(&mut MinerStatus) -> R,
+    ) -> R {
+        let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        let result = change(&mut status);
+        status.updated_at = unix_now();
+        if let Err(error) = status.write(&self.config.status_path) {
+            if !self.status_warned {
+                host.event(SessionEvent::StatusNotWritten {
+                    path: self.config.status_path.clone(),
+                    error,
+                });
+            }
+            self.status_warned = true;
+        }
+        result
+    }
+}
+
+/// Read the seed again, keeping the old one if the chain cannot be read;
+/// whether it changed
+pub fn refresh_seed(
+    rt: &Runtime,
+    client: &RpcClient,
+    seed_info: &mut SeedInfo,
+) -> Result<bool, RpcError> {
+    client.invalidate_cache();
+    let info = rt.block_on(client.get_seed())?;
+    let rotated = info.seed != seed_info.seed;
+    *seed_info = info;
+    Ok(rotated)
+}
+
+/// Why a saved round is not resumed, or what went wrong with its file
+#[derive(Error, Debug)]
+pub enum ResumeNote {
+    #[error("No saved round to resume")]
+    NoSavedRound,
+
+    #[error("Could not read {}: {source}", .path.display())]
+    Unreadable {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// The round is still resumed, but may be resumed again next time
+    #[error("Could not remove {}: {source}", .path.display())]
+    NotRemoved {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Saved round was for another seed or difficulty")]
+    OtherRound,
+
+    #[error("Saved round was for another nonce range")]
+    OtherNonceRange,
+
+    #[error("Saved round is too old to submit proofs for")]
+    TooOld,
+}
+
+/// The round saved at `path` that `mine --resume` can continue
+///
+/// The round is dropped when it was mined for another address, seed or
+/// difficulty, or when its proofs are about to exceed the contract's
+/// `max_proof_age`. Either way the file is removed: a round is resumed once.
+/// Why it was dropped goes to `note`.
+pub fn resumable_round(
+    path: &Path,
+    address: &str,
+    seed_info: &SeedInfo,
+    difficulty: u32,
+    nonces: NonceRange,
+    max_proof_age: Option<u64>,
+    mut note: impl FnMut(ResumeNote),
+) -> Option<RoundState> {
+    let state = match RoundState::read(path) {
+        Ok(Some(state)) => state,
+        Ok(None) => {
+            note(ResumeNote::NoSavedRound);
+            return None;
+        }
+        Err(source) => {
+            note(ResumeNote::Unreadable {
+                path: path.to_path_buf(),
+                source,
+            });
+            return None;
+        }
+    };
+    if let Err(source) = RoundState::remove(path) {
+        note(ResumeNote::NotRemoved {
+            path: path.to_path_buf(),
+            source,
+        });
+    }
+    if !state.matches(address, &seed_info.seed, difficulty) {
+        note(ResumeNote::OtherRound);
+        return None;
+    }
+    if !state.cursors.iter().all(|&nonce| nonces.contains(nonce)) {
+        note(ResumeNote::OtherNonceRange);
+        return None;
+    }
+    // Leave time to submit a proof found just before the deadline
+    let params = RoundParams {
+        seed: seed_info.seed,
+        address: address.to_string(),
+        timestamp: state.timestamp,
+        difficulty,
+        nonce_end: nonces.end,
+        worker_tag: Vec::new(),
+    };
+    if max_proof_age.is_some_and(|age| unix_now() >= params.deadline(age)) {
+        note(ResumeNote::TooOld);
+        return None;
+    }
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "bostrom1miner";
+
+    #[test]
+    fn test_resumable_round_is_used_once() {
+        let dir = std::env::temp_dir().join(format!("uhash-session-{}", std::process::id()));
+        let path = dir.join("round-state.json");
+        let seed = SeedInfo::new([7u8; 32], 0);
+        let nonces = NonceRange::default();
+        RoundState::new(ADDRESS, &seed.seed, unix_now(), 8, &[0, 1], &[10, 10])
+            .write(&path)
+            .unwrap();
+
+        let mut notes = Vec::new();
+        let state = resumable_round(&path, ADDRESS, &seed, 8, nonces, Some(600), |note| {
+            notes.push(note)
+        });
+        assert_eq!(state.unwrap().cursors, vec![20, 21]);
+        assert!(notes.is_empty());
+
+        let state = resumable_round(&path, ADDRESS, &seed, 8, nonces, Some(600), |note| {
+            notes.push(note)
+        });
+        assert!(state.is_none());
+        assert!(matches!(notes[..], [ResumeNote::NoSavedRound]));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_resumable_round_drops_stale_rounds() {
+        let dir = std::env::temp_dir().join(format!("uhash-session-stale-{}", std::process::id()));
+        let path = dir.join("round-state.json");
+        let seed = SeedInfo::new([7u8; 32], 0);
+        let nonces = NonceRange::default();
+        let resume = |difficulty, timestamp| {
+            RoundState::new(ADDRESS, &seed.seed, timestamp, 8, &[0], &[5])
+                .write(&path)
+                .unwrap();
+            let mut notes = Vec::new();
+            let state = resumable_round(
+                &path,
+                ADDRESS,
+                &seed,
+                difficulty,
+                nonces,
+                Some(600),
+                |note| notes.push(note),
+            );
+            assert!(state.is_none());
+            assert!(!path.exists());
+            notes.pop().unwrap()
+        };
+
+        assert!(matches!(resume(9, unix_now()), ResumeNote::OtherRound));
+        assert!(matches!(resume(8, 1), ResumeNote::TooOld));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
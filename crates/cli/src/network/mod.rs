@@ -0,0 +1,179 @@
+//! Network hashrate estimation from accepted proofs
+//!
+//! A proof at difficulty `d` takes 2^d hashes on average, and the network's
+//! proofs arrive as a Poisson process. Counting the proofs accepted between
+//! the first and last of a sample of recent ones gives the proof rate, and
+//! the rate times the work per proof gives the hashrate. The confidence
+//! interval is the exact (Garwood) Poisson interval on the proof count,
+//! scaled the same way, so a handful of proofs yields a wide interval.
+
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum NetworkError {
+    #[error("At least two accepted proofs are needed, found {0}")]
+    NotEnoughProofs(usize),
+
+    #[error("All {0} sampled proofs were accepted in the same second")]
+    NoTimeSpan(usize),
+
+    #[error("Confidence must be between 0 and 1, got {0}")]
+    InvalidConfidence(f64),
+}
+
+/// An accepted proof, as far as the estimate is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofSample {
+    /// Unix time of the block that accepted it
+    pub time: u64,
+    /// Difficulty it was accepted at
+    pub difficulty: u32,
+}
+
+/// Estimated network hashrate
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HashrateEstimate {
+    /// H/s
+    pub hashrate: f64,
+    /// Lower bound of the confidence interval (H/s)
+    pub low: f64,
+    /// Upper bound of the confidence interval (H/s)
+    pub high: f64,
+    pub confidence: f64,
+    /// Proofs sampled
+    pub proofs: usize,
+    /// Seconds between the first and last sampled proof
+    pub span_secs: u64,
+    /// Mean difficulty of the sampled proofs
+    pub difficulty_mean: f64,
+}
+
+/// Estimate the hashrate behind `samples` with a `confidence` interval
+///
+/// The first proof only starts the clock: `n` proofs over a span give
+/// `n - 1` arrivals.
+pub fn estimate_hashrate(
+    samples: &[ProofSample],
+    confidence: f64,
+) -> Result<HashrateEstimate, NetworkError> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(NetworkError::InvalidConfidence(confidence));
+    }
+    if samples.len() < 2 {
+        return Err(NetworkError::NotEnoughProofs(samples.len()));
+    }
+
+    let mut samples = samples.to_vec();
+    samples.sort_by_key(|s| s.time);
+    let span_secs = samples[samples.len() - 1].time - samples[0].time;
+    if span_secs == 0 {
+        return Err(NetworkError::NoTimeSpan(samples.len()));
+    }
+
+    let arrivals = &samples[1..];
+    let count = arrivals.len() as f64;
+    let work: f64 = arrivals
+        .iter()
+        .map(|s| 2f64.powi(s.difficulty as i32))
+        .sum();
+    let work_per_proof = work / count;
+    let span = span_secs as f64;
+
+    let alpha = 1.0 - confidence;
+    let low_count = chi_square_quantile(alpha / 2.0, 2.0 * count) / 2.0;
+    let high_count = chi_square_quantile(1.0 - alpha / 2.0, 2.0 * count + 2.0) / 2.0;
+
+    Ok(HashrateEstimate {
+        hashrate: work / span,
+        low: low_count * work_per_proof / span,
+        high: high_count * work_per_proof / span,
+        confidence,
+        proofs: samples.len(),
+        span_secs,
+        difficulty_mean: samples.iter().map(|s| s.difficulty as f64).sum::<f64>()
+            / samples.len() as f64,
+    })
+}
+
+/// Chi-square quantile by the Wilson–Hilferty approximation
+fn chi_square_quantile(p: f64, degrees: f64) -> f64 {
+    let scale = 2.0 / (9.0 * degrees);
+    let cube = 1.0 - scale + normal_quantile(p) * scale.sqrt();
+    (degrees * cube.powi(3)).max(0.0)
+}
+
+/// Standard normal quantile (Abramowitz & Stegun 26.2.23, error below 5e-4)
+fn normal_quantile(p: f64) -> f64 {
+    if p > 0.5 {
+        return -normal_quantile(1.0 - p);
+    }
+    let t = (-2.0 * p.ln()).sqrt();
+    let numerator = 2.515517 + 0.802853 * t + 0.010328 * t * t;
+    let denominator = 1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t;
+    -(t - numerator / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(count: u64, interval: u64, difficulty: u32) -> Vec<ProofSample> {
+        (0..count)
+            .map(|i| ProofSample {
+                time: 1_700_000_000 + i * interval,
+                difficulty,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_from_steady_proofs() {
+        // A proof every 60 s at 2^-16 is 65536 / 60 H/s
+        let estimate = estimate_hashrate(&samples(101, 60, 16), 0.95).unwrap();
+        assert_eq!(estimate.proofs, 101);
+        assert_eq!(estimate.span_secs, 6000);
+        assert_eq!(estimate.difficulty_mean, 16.0);
+        assert!((estimate.hashrate - 65536.0 / 60.0).abs() < 1e-6);
+
+        // 100 arrivals: the exact 95% interval is about 81.4..121.6
+        let ratio = |bound: f64| bound / estimate.hashrate;
+        assert!((ratio(estimate.low) - 0.814).abs() < 0.01, "{:?}", estimate);
+        assert!(
+            (ratio(estimate.high) - 1.216).abs() < 0.01,
+            "{:?}",
+            estimate
+        );
+
+        // Fewer proofs, wider interval
+        let few = estimate_hashrate(&samples(5, 60, 16), 0.95).unwrap();
+        assert!((few.hashrate - estimate.hashrate).abs() < 1e-6);
+        assert!(few.low < estimate.low && few.high > estimate.high);
+    }
+
+    #[test]
+    fn test_estimate_weights_proofs_by_difficulty() {
+        let mut mixed = samples(3, 100, 10);
+        mixed[2].difficulty = 12;
+        mixed.reverse();
+        let estimate = estimate_hashrate(&mixed, 0.9).unwrap();
+        assert!((estimate.hashrate - (1024.0 + 4096.0) / 200.0).abs() < 1e-9);
+        assert!(estimate.low < estimate.hashrate && estimate.hashrate < estimate.high);
+    }
+
+    #[test]
+    fn test_estimate_needs_a_time_span() {
+        assert!(matches!(
+            estimate_hashrate(&samples(1, 60, 8), 0.95),
+            Err(NetworkError::NotEnoughProofs(1))
+        ));
+        assert!(matches!(
+            estimate_hashrate(&samples(3, 0, 8), 0.95),
+            Err(NetworkError::NoTimeSpan(3))
+        ));
+        assert!(matches!(
+            estimate_hashrate(&samples(3, 60, 8), 1.0),
+            Err(NetworkError::InvalidConfidence(_))
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+//! Proofs found but not yet submitted
+//!
+//! When `mine` is stopped by a signal while holding a proof it has not sent
+//! yet, it appends the proof to a JSON-lines file next to the config
+//! (`~/.uhash/pending-proofs.jsonl` by default) instead of dropping it. The
+//! next `mine` submits the queued proofs before it starts hashing, keeping
+//! any that still cannot be sent. Lines are the canonical proof JSON (see
+//! [`crate::proof`]).
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::proof::Proof;
+use crate::rpc::ProofSubmission;
+
+/// File name of the pending queue, in the config directory
+pub const PENDING_FILE_NAME: &str = "pending-proofs.jsonl";
+
+/// Queue of unsent proofs backed by a JSON-lines file
+#[derive(Debug, Clone)]
+pub struct PendingQueue {
+    path: PathBuf,
+}
+
+impl PendingQueue {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `proof`, creating the file if needed
+    pub fn push(&self, proof: &ProofSubmission) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let line = canonical_line(proof)?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_all()
+    }
+
+    /// Queued proofs, oldest first; lines that do not parse are skipped
+    pub fn load(&self) -> std::io::Result<Vec<ProofSubmission>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| Proof::from_json(line).ok())
+            .map(|proof| proof.submission())
+            .collect())
+    }
+
+    /// Replace the queue with `proofs`, removing the file when empty
+    pub fn replace(&self, proofs: &[ProofSubmission]) -> std::io::Result<()> {
+        if proofs.is_empty() {
+            return match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let mut content = Vec::new();
+        for proof in proofs {
+            content.extend(canonical_line(proof)?);
+        }
+        // Write a sibling and rename, so a crash never truncates the queue
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// `proof` as a line of canonical JSON
+fn canonical_line(proof: &ProofSubmission) -> std::io::Result<Vec<u8>> {
+    let proof = Proof::try_from(proof)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let mut line = proof.to_json().into_bytes();
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof(nonce: u64) -> ProofSubmission {
+        ProofSubmission {
+            hash: "00ab".repeat(16),
+            nonce,
+            timestamp: 1_700_000_000,
+            miner_address: "bostrom1miner".to_string(),
+            worker_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_push_load_replace() {
+        let dir = std::env::temp_dir().join(format!("uhash-pending-{}", std::process::id()));
+        let queue = PendingQueue::new(dir.join(PENDING_FILE_NAME));
+        assert!(queue.load().unwrap().is_empty());
+
+        queue.push(&proof(1)).unwrap();
+        queue.push(&proof(2)).unwrap();
+        let nonces: Vec<u64> = queue.load().unwrap().iter().map(|p| p.nonce).collect();
+        assert_eq!(nonces, vec![1, 2]);
+        let first = fs::read_to_string(queue.path()).unwrap();
+        assert!(first.starts_with(r#"{"hash":"00ab"#));
+        assert!(first
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(r#""nonce":1,"timestamp":1700000000}"#));
+
+        queue.replace(&[proof(2)]).unwrap();
+        assert_eq!(queue.load().unwrap().len(), 1);
+        queue.replace(&[]).unwrap();
+        assert!(!queue.path().exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_worker_tag_survives_queue() {
+        let dir = std::env::temp_dir().join(format!("uhash-pending-tag-{}", std::process::id()));
+        let queue = PendingQueue::new(dir.join(PENDING_FILE_NAME));
+        let tagged = proof(1).with_worker_tag(b"rig-7");
+        queue.push(&tagged).unwrap();
+        queue.push(&proof(2)).unwrap();
+        let loaded = queue.load().unwrap();
+        assert_eq!(loaded[0].worker_tag_bytes().unwrap(), b"rig-7");
+        assert_eq!(loaded[1].worker_tag, None);
+
+        queue.replace(&loaded).unwrap();
+        assert_eq!(queue.load().unwrap()[0].worker_tag, tagged.worker_tag);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,20 @@
+//! Pool-side share handling
+//!
+//! The logic a mining pool runs for every connected worker, speaking the
+//! `uhash-pool-proto` messages: [`Vardiff`] retargets each worker's share
+//! difficulty so it sends shares at a steady rate whatever its hashrate,
+//! [`ShareValidator`] checks the shares, and [`split_reward`] shares out what
+//! a pooled proof earns.
+
+mod payout;
+mod shares;
+mod vardiff;
+
+pub use payout::{
+    bank_sends, split_reward, Payout, PayoutError, PayoutReport, PayoutScheme, ShareRecord,
+    DEFAULT_PPLNS_WINDOW,
+};
+pub use shares::{HasherPool, PooledHasher, Share, ShareJob, ShareValidator, ValidShare};
+pub use vardiff::{
+    Vardiff, VardiffConfig, DEFAULT_RETARGET_INTERVAL, DEFAULT_SHARES_PER_MINUTE, DEFAULT_TOLERANCE,
+};
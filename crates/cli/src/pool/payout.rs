@@ -0,0 +1,290 @@
+//! Splitting a pooled proof's reward among the workers
+//!
+//! Every valid share is recorded as a [`ShareRecord`]. When a share is also a
+//! proof and the chain pays the pool for it, the reward minus the pool fee is
+//! split over a window of shares by their work, `2^difficulty` each:
+//!
+//! - [`PayoutScheme::Pplns`]: the last N shares up to the proof, so a worker
+//!   gains nothing by hopping between pools
+//! - [`PayoutScheme::Proportional`]: every share since the previous payout
+//!
+//! [`split_reward`] produces the [`PayoutReport`], and [`bank_sends`] the
+//! `MsgSend` batch that pays it out. The stats database keeps the shares and
+//! the payouts (`StatsDb::record_share`, `StatsDb::share_window` and
+//! `StatsDb::record_payout`).
+
+use std::collections::BTreeMap;
+
+use cosmrs::bank::MsgSend;
+use cosmrs::{AccountId, Coin};
+use serde::Serialize;
+use thiserror::Error;
+
+/// PPLNS window by default, in shares
+pub const DEFAULT_PPLNS_WINDOW: usize = 10_000;
+
+/// Difficulties a share may be below the window's hardest and still weigh
+/// its full work; easier ones weigh as if this many bits easier
+const MAX_WEIGHT_BITS: u32 = 32;
+
+/// One basis point is 0.01%
+const BPS: u128 = 10_000;
+
+#[derive(Error, Debug)]
+pub enum PayoutError {
+    #[error("Pool fee of {0} basis points is above 100%")]
+    InvalidFee(u16),
+
+    #[error("No shares to split the reward over")]
+    NoShares,
+
+    #[error("Invalid payout address or denom: {0}")]
+    InvalidMsg(String),
+}
+
+/// How the shares a reward is split over are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "scheme")]
+pub enum PayoutScheme {
+    /// Pay per last N shares
+    Pplns { window: usize },
+    /// Every share since the previous payout
+    Proportional,
+}
+
+impl Default for PayoutScheme {
+    fn default() -> Self {
+        PayoutScheme::Pplns {
+            window: DEFAULT_PPLNS_WINDOW,
+        }
+    }
+}
+
+/// A valid share as the pool accepted it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareRecord {
+    /// Id in the stats database, `None` before it is stored
+    pub id: Option<i64>,
+    /// Unix seconds
+    pub found_at: u64,
+    /// Payout address of the worker
+    pub address: String,
+    /// Share difficulty it met
+    pub difficulty: u32,
+}
+
+/// One worker's part of a reward
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Payout {
+    pub address: String,
+    /// Shares of the worker in the window
+    pub shares: u64,
+    /// uLI, or whatever the reward denom's unit is
+    pub amount: u128,
+}
+
+/// How a proof's reward was split
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PayoutReport {
+    /// Hash of the pooled proof
+    pub proof_hash: String,
+    pub reward: u128,
+    #[serde(flatten)]
+    pub scheme: PayoutScheme,
+    /// What the pool keeps: its fee and the remainder of rounding down
+    pub fee: u128,
+    /// Largest payout first
+    pub payouts: Vec<Payout>,
+    /// Last share the window covered, to start the next proportional window after
+    pub last_share_id: Option<i64>,
+}
+
+/// Split `reward` for `proof_hash` over `shares` (the window `scheme` chose),
+/// keeping `fee_bps` basis points for the pool
+pub fn split_reward(
+    proof_hash: &str,
+    reward: u128,
+    scheme: PayoutScheme,
+    fee_bps: u16,
+    shares: &[ShareRecord],
+) -> Result<PayoutReport, PayoutError> {
+    if u128::from(fee_bps) > BPS {
+        return Err(PayoutError::InvalidFee(fee_bps));
+    }
+    let hardest = shares
+        .iter()
+        .map(|s| s.difficulty)
+        .max()
+        .ok_or(PayoutError::NoShares)?;
+
+    // Work relative to the easiest weighed share keeps the sums in integers
+    let floor = hardest.saturating_sub(MAX_WEIGHT_BITS);
+    let mut workers: BTreeMap<&str, (u64, u128)> = BTreeMap::new();
+    for share in shares {
+        let weight = 1u128 << (share.difficulty.max(floor) - floor);
+        let worker = workers.entry(&share.address).or_default();
+        worker.0 += 1;
+        worker.1 += weight;
+    }
+    let total: u128 = workers.values().map(|(_, weight)| weight).sum();
+
+    let distributable = reward - mul_div(reward, u128::from(fee_bps), BPS);
+    let mut payouts: Vec<Payout> = workers
+        .into_iter()
+        .map(|(address, (shares, weight))| Payout {
+            address: address.to_string(),
+            shares,
+            amount: mul_div(distributable, weight, total),
+        })
+        .filter(|p| p.amount > 0)
+        .collect();
+    payouts.sort_by(|a, b| b.amount.cmp(&a.amount).then(a.address.cmp(&b.address)));
+    let paid: u128 = payouts.iter().map(|p| p.amount).sum();
+
+    Ok(PayoutReport {
+        proof_hash: proof_hash.to_string(),
+        reward,
+        scheme,
+        fee: reward - paid,
+        payouts,
+        last_share_id: shares.iter().filter_map(|s| s.id).max(),
+    })
+}
+
+/// `value * numerator / denominator` rounded down, for `numerator <= denominator`
+fn mul_div(value: u128, numerator: u128, denominator: u128) -> u128 {
+    value / denominator * numerator + value % denominator * numerator / denominator
+}
+
+/// `MsgSend`s from the pool account `from` paying out `report` in `denom`
+pub fn bank_sends(
+    report: &PayoutReport,
+    from: &str,
+    denom: &str,
+) -> Result<Vec<MsgSend>, PayoutError> {
+    let invalid = |e: cosmrs::ErrorReport| PayoutError::InvalidMsg(e.to_string());
+    let from: AccountId = from.parse().map_err(invalid)?;
+    let denom: cosmrs::Denom = denom.parse().map_err(invalid)?;
+    report
+        .payouts
+        .iter()
+        .map(|payout| {
+            Ok(MsgSend {
+                from_address: from.clone(),
+                to_address: payout.address.parse().map_err(invalid)?,
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount: payout.amount,
+                }],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(id: i64, address: &str, difficulty: u32) -> ShareRecord {
+        ShareRecord {
+            id: Some(id),
+            found_at: 1_700_000_000 + id as u64,
+            address: address.to_string(),
+            difficulty,
+        }
+    }
+
+    #[test]
+    fn test_split_by_work() {
+        // b's difficulty-12 share is worth a's two difficulty-11 ones
+        let shares = [
+            share(1, "a", 11),
+            share(2, "a", 11),
+            share(3, "b", 12),
+            share(4, "c", 10),
+        ];
+        let report =
+            split_reward("ff", 1_000_000, PayoutScheme::Proportional, 100, &shares).unwrap();
+        // 990,000 over 9 units of difficulty-10 work
+        assert_eq!(
+            report.payouts,
+            vec![
+                Payout {
+                    address: "a".to_string(),
+                    shares: 2,
+                    amount: 440_000,
+                },
+                Payout {
+                    address: "b".to_string(),
+                    shares: 1,
+                    amount: 440_000,
+                },
+                Payout {
+                    address: "c".to_string(),
+                    shares: 1,
+                    amount: 110_000,
+                },
+            ]
+        );
+        assert_eq!(report.fee, 10_000);
+        assert_eq!(report.last_share_id, Some(4));
+
+        // Rounding down leaves the remainder to the pool, never more than the reward
+        let report = split_reward("ff", 10, PayoutScheme::default(), 0, &shares).unwrap();
+        let paid: u128 = report.payouts.iter().map(|p| p.amount).sum();
+        assert_eq!(paid + report.fee, 10);
+    }
+
+    #[test]
+    fn test_split_edge_cases() {
+        assert!(matches!(
+            split_reward("ff", 1, PayoutScheme::Proportional, 0, &[]),
+            Err(PayoutError::NoShares)
+        ));
+        assert!(matches!(
+            split_reward(
+                "ff",
+                1,
+                PayoutScheme::Proportional,
+                10_001,
+                &[share(1, "a", 1)]
+            ),
+            Err(PayoutError::InvalidFee(10_001))
+        ));
+        // Huge difficulty spreads and rewards neither overflow nor lose the total
+        let shares = [share(1, "a", 250), share(2, "b", 1)];
+        let report = split_reward(
+            "ff",
+            u128::MAX / 2,
+            PayoutScheme::Proportional,
+            250,
+            &shares,
+        )
+        .unwrap();
+        let paid: u128 = report.payouts.iter().map(|p| p.amount).sum();
+        assert_eq!(paid + report.fee, u128::MAX / 2);
+        assert_eq!(report.payouts[0].address, "a");
+    }
+
+    #[test]
+    fn test_bank_sends() {
+        let pool = crate::wallet::Wallet::new().unwrap().address_str();
+        let miner = crate::wallet::Wallet::new().unwrap().address_str();
+        let report = split_reward(
+            "ff",
+            500,
+            PayoutScheme::Proportional,
+            0,
+            &[share(1, &miner, 4)],
+        )
+        .unwrap();
+        let sends = bank_sends(&report, &pool, crate::rpc::LI_DENOM).unwrap();
+        assert_eq!(sends.len(), 1);
+        assert_eq!(sends[0].to_address.as_ref(), miner);
+        assert_eq!(sends[0].amount[0].amount, 500);
+        assert!(matches!(
+            bank_sends(&report, "nonsense", crate::rpc::LI_DENOM),
+            Err(PayoutError::InvalidMsg(_))
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+//! Checking shares fast
+//!
+//! A share costs a full hash to check, so a pool's share throughput is its
+//! cores times the hashrate of one: a [`ShareValidator`] spreads a batch of
+//! shares over threads, each hashing with a hasher from a [`HasherPool`]
+//! rather than allocating 2 MB of scratchpads per share. Shares that cannot
+//! be valid, with a malformed hash or one that does not meet the share
+//! difficulty by its own claim, are rejected before any hashing, so a worker
+//! sending garbage costs the pool next to nothing. Vardiff keeps the rest at
+//! a few shares per worker a minute.
+
+use std::num::NonZeroUsize;
+
+use uhash_pool_proto::RejectReason;
+
+use crate::meets_difficulty;
+use crate::proof::proof_input;
+
+pub use crate::algorithm::verify::{HasherPool, PooledHasher};
+
+/// The hash input of a pool job, less the nonce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareJob {
+    pub seed: [u8; 32],
+    /// Pool address in the hash input
+    pub address: String,
+    pub timestamp: u64,
+    /// Difficulty at which a share is also a proof
+    pub network_difficulty: u32,
+}
+
+impl ShareJob {
+    /// The proof input of `nonce`; pool jobs carry no worker tag
+    fn input(&self, nonce: u64) -> Vec<u8> {
+        proof_input(&self.seed, &self.address, &[], self.timestamp, nonce)
+    }
+}
+
+/// A share as a worker sent it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub nonce: u64,
+    /// Hex hash the worker claims
+    pub hash: String,
+    /// Share difficulty of the worker when it was sent
+    pub difficulty: u32,
+}
+
+/// A share that checked out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidShare {
+    pub hash: [u8; 32],
+    /// It also meets the network difficulty
+    pub proof: bool,
+}
+
+/// Checks shares with pooled hashers, a batch at a time on several threads
+#[derive(Debug)]
+pub struct ShareValidator {
+    hashers: HasherPool,
+    threads: usize,
+}
+
+impl ShareValidator {
+    /// A validator hashing on up to `threads` threads at once
+    pub fn new(threads: NonZeroUsize) -> Self {
+        Self {
+            hashers: HasherPool::new(threads.get()),
+            threads: threads.get(),
+        }
+    }
+
+    /// The validator's hashers
+    pub fn hashers(&self) -> &HasherPool {
+        &self.hashers
+    }
+
+    /// Check one share of `job`
+    pub fn verify(&self, job: &ShareJob, share: &Share) -> Result<ValidShare, RejectReason> {
+        let claimed = precheck(share)?;
+        let hash = self.hashers.get().hash(&job.input(share.nonce));
+        if hash != claimed {
+            return Err(RejectReason::InvalidHash);
+        }
+        Ok(ValidShare {
+            hash,
+            proof: meets_difficulty(&hash, job.network_difficulty),
+        })
+    }
+
+    /// Check `shares` of `job`, with one verdict per share in order
+    ///
+    /// Shares failing the checks that need no hashing are answered at once;
+    /// the rest are split over the threads.
+    pub fn verify_batch(
+        &self,
+        job: &ShareJob,
+        shares: &[Share],
+    ) -> Vec<Result<ValidShare, RejectReason>> {
+        let mut verdicts: Vec<Option<Result<ValidShare, RejectReason>>> = shares
+            .iter()
+            .map(|share| precheck(share).err().map(Err))
+            .collect();
+        let to_hash: Vec<usize> = (0..shares.len())
+            .filter(|&i| verdicts[i].is_none())
+            .collect();
+
+        let chunk = to_hash.len().div_ceil(self.threads).max(1);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = to_hash
+                .chunks(chunk)
+                .map(|indices| {
+                    scope.spawn(move || {
+                        indices
+                            .iter()
+                            .map(|&i| (i, self.verify(job, &shares[i])))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            for worker in workers {
+                for (i, verdict) in worker.join().expect("share check panicked") {
+                    verdicts[i] = Some(verdict);
+                }
+            }
+        });
+        verdicts
+            .into_iter()
+            .map(|verdict| verdict.expect("every share is checked"))
+            .collect()
+    }
+}
+
+/// The claimed hash, if the share can be valid at all
+fn precheck(share: &Share) -> Result<[u8; 32], RejectReason> {
+    let mut claimed = [0u8; 32];
+    hex::decode_to_slice(&share.hash, &mut claimed).map_err(|_| RejectReason::InvalidHash)?;
+    if !meets_difficulty(&claimed, share.difficulty) {
+        return Err(RejectReason::LowDifficulty);
+    }
+    Ok(claimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UniversalHash;
+
+    fn job() -> ShareJob {
+        ShareJob {
+            seed: [3u8; 32],
+            address: "bostrom1pool".to_string(),
+            timestamp: 1_700_000_000,
+            network_difficulty: 255,
+        }
+    }
+
+    /// A share of `job` at difficulty 0, which every hash meets
+    fn share(job: &ShareJob, nonce: u64) -> Share {
+        Share {
+            nonce,
+            hash: hex::encode(UniversalHash::new().hash(&job.input(nonce))),
+            difficulty: 0,
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let validator = ShareValidator::new(NonZeroUsize::new(2).unwrap());
+        let mut job = job();
+        let valid = share(&job, 7);
+        let verdict = validator.verify(&job, &valid).unwrap();
+        assert_eq!(hex::encode(verdict.hash), valid.hash);
+        assert!(!verdict.proof);
+        job.network_difficulty = 0;
+        assert!(validator.verify(&job, &valid).unwrap().proof);
+
+        let wrong_nonce = Share {
+            nonce: 8,
+            ..valid.clone()
+        };
+        assert_eq!(
+            validator.verify(&job, &wrong_nonce),
+            Err(RejectReason::InvalidHash)
+        );
+        let malformed = Share {
+            hash: "zz".to_string(),
+            ..valid.clone()
+        };
+        assert_eq!(
+            validator.verify(&job, &malformed),
+            Err(RejectReason::InvalidHash)
+        );
+        // A claimed hash below the share difficulty is refused unhashed
+        let easy = Share {
+            hash: "ff".repeat(32),
+            difficulty: 1,
+            ..valid
+        };
+        assert_eq!(
+            validator.verify(&job, &easy),
+            Err(RejectReason::LowDifficulty)
+        );
+        assert_eq!(validator.hashers().idle_count(), 1);
+    }
+
+    #[test]
+    fn test_verify_batch_keeps_order() {
+        let validator = ShareValidator::new(NonZeroUsize::new(3).unwrap());
+        let job = job();
+        let mut shares: Vec<Share> = (0..8).map(|nonce| share(&job, nonce)).collect();
+        shares[2].nonce = 100;
+        shares[5].hash = "ff".repeat(32);
+        shares[5].difficulty = 4;
+
+        let verdicts = validator.verify_batch(&job, &shares);
+        assert_eq!(verdicts.len(), shares.len());
+        for (i, (verdict, share)) in verdicts.iter().zip(&shares).enumerate() {
+            match i {
+                2 => assert_eq!(*verdict, Err(RejectReason::InvalidHash)),
+                5 => assert_eq!(*verdict, Err(RejectReason::LowDifficulty)),
+                _ => assert_eq!(hex::encode(verdict.unwrap().hash), share.hash),
+            }
+        }
+        // Hashers are kept for the next batch, no more than the threads
+        assert!((1..=3).contains(&validator.hashers().idle_count()));
+        assert!(validator.verify_batch(&job, &[]).is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+//! Variable share difficulty
+//!
+//! A phone and a server on the same pool need share difficulties bits apart:
+//! a difficulty the server finds a few times a minute would leave the phone
+//! without a share for an hour, and the phone's would flood the pool with the
+//! server's shares. Each worker's difficulty is therefore retargeted from the
+//! shares it sends. Every [`VardiffConfig::retarget_interval`] the share rate
+//! of the window is compared with the target; a rate off by more than
+//! [`VardiffConfig::tolerance`] gives the difficulty at which the hashrate
+//! it implies finds the target rate. A worker flooding the pool is retargeted
+//! before the interval ends.
+
+use std::time::{Duration, Instant};
+
+use crate::algorithm::difficulty::{difficulty_for_rate, expected_hashes, MAX_DIFFICULTY};
+
+/// Shares per minute a worker is retargeted to by default
+pub const DEFAULT_SHARES_PER_MINUTE: f64 = 6.0;
+
+/// Time between retargets by default
+pub const DEFAULT_RETARGET_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Factor a share rate may be off target by before a retarget, by default
+///
+/// Difficulties are whole bits, so even the best one leaves the rate up to
+/// √2 off target; the tolerance leaves room for that and for luck.
+pub const DEFAULT_TOLERANCE: f64 = 2.0;
+
+/// Times the expected shares of a whole interval that retarget at once
+const FLOOD_FACTOR: f64 = 4.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VardiffConfig {
+    /// Share rate to aim each worker at
+    pub shares_per_minute: f64,
+    pub retarget_interval: Duration,
+    pub tolerance: f64,
+    pub min_difficulty: u32,
+    pub max_difficulty: u32,
+}
+
+impl Default for VardiffConfig {
+    fn default() -> Self {
+        Self {
+            shares_per_minute: DEFAULT_SHARES_PER_MINUTE,
+            retarget_interval: DEFAULT_RETARGET_INTERVAL,
+            tolerance: DEFAULT_TOLERANCE,
+            min_difficulty: 1,
+            max_difficulty: MAX_DIFFICULTY,
+        }
+    }
+}
+
+/// Share difficulty of one worker
+#[derive(Debug, Clone)]
+pub struct Vardiff {
+    config: VardiffConfig,
+    difficulty: u32,
+    window_start: Instant,
+    shares: u64,
+}
+
+impl Vardiff {
+    /// Start at `difficulty`, with the first window opening at `now`
+    pub fn new(config: VardiffConfig, difficulty: u32, now: Instant) -> Self {
+        let difficulty = difficulty.clamp(config.min_difficulty, config.max_difficulty);
+        Self {
+            config,
+            difficulty,
+            window_start: now,
+            shares: 0,
+        }
+    }
+
+    /// Current share difficulty
+    pub fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+
+    /// Count a valid share found at the current difficulty; the new
+    /// difficulty if it retargets
+    pub fn record_share(&mut self, now: Instant) -> Option<u32> {
+        self.shares += 1;
+        self.retarget(now)
+    }
+
+    /// Retarget if the window is over; the new difficulty if it changed
+    ///
+    /// Call it periodically as well as on shares, so a worker whose
+    /// difficulty is too high for it to find any share is still lowered.
+    pub fn retarget(&mut self, now: Instant) -> Option<u32> {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        let target = self.config.shares_per_minute;
+        let interval_minutes = self.config.retarget_interval.as_secs_f64() / 60.0;
+        let flooded = self.shares as f64 > target * interval_minutes * FLOOD_FACTOR;
+        if elapsed.is_zero() || (elapsed < self.config.retarget_interval && !flooded) {
+            return None;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        let rate = self.shares as f64 * 60.0 / seconds;
+        let shares = self.shares;
+        self.window_start = now;
+        self.shares = 0;
+        if rate <= target * self.config.tolerance && rate * self.config.tolerance >= target {
+            return None;
+        }
+
+        // No share at all: the hashrate is at most about one share's worth
+        let hashrate = shares.max(1) as f64 * expected_hashes(self.difficulty) / seconds;
+        let mut next = difficulty_for_rate(hashrate, target);
+        if shares == 0 {
+            next = next.min(self.difficulty.saturating_sub(1));
+        }
+        let next = next.clamp(self.config.min_difficulty, self.config.max_difficulty);
+        if next == self.difficulty {
+            return None;
+        }
+        self.difficulty = next;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::Rng;
+
+    /// Run a worker of `hashrate` H/s for `duration`, with share arrivals
+    /// drawn from the exponential distribution its difficulty implies;
+    /// the difficulty at each share
+    fn simulate(
+        vardiff: &mut Vardiff,
+        rng: &mut Rng,
+        start: Instant,
+        hashrate: f64,
+        duration: Duration,
+    ) -> Vec<u32> {
+        let mut now = start;
+        let end = start + duration;
+        let mut seen = Vec::new();
+        // Retarget checks between shares, as a pool's timer would
+        let tick = Duration::from_secs(10);
+        loop {
+            let mean = expected_hashes(vardiff.difficulty()) / hashrate;
+            let wait = Duration::from_secs_f64(-rng.next_f64().ln() * mean);
+            let mut share_at = now + wait;
+            while now + tick < share_at.min(end) {
+                now += tick;
+                if vardiff.retarget(now).is_some() {
+                    // The worker starts over at the new difficulty
+                    let mean = expected_hashes(vardiff.difficulty()) / hashrate;
+                    share_at = now + Duration::from_secs_f64(-rng.next_f64().ln() * mean);
+                }
+            }
+            if share_at >= end {
+                return seen;
+            }
+            now = share_at;
+            seen.push(vardiff.difficulty());
+            vardiff.record_share(now);
+        }
+    }
+
+    #[test]
+    fn test_converges_on_simulated_hashrates() {
+        let mut rng = Rng::new(7);
+        let start = Instant::now();
+        // Phone, desktop and server starting from the same difficulty
+        for (hashrate, equilibrium) in [(50.0, 9), (1_000.0, 13), (200_000.0, 21)] {
+            let mut vardiff = Vardiff::new(VardiffConfig::default(), 8, start);
+            let hour = Duration::from_secs(3600);
+            simulate(&mut vardiff, &mut rng, start, hashrate, hour);
+            assert!(
+                vardiff.difficulty().abs_diff(equilibrium) <= 1,
+                "{} H/s settled at {}",
+                hashrate,
+                vardiff.difficulty()
+            );
+
+            // Then it holds, sending shares near the target rate
+            let shares = simulate(&mut vardiff, &mut rng, start + hour, hashrate, 2 * hour);
+            assert!(shares.iter().all(|d| d.abs_diff(equilibrium) <= 1));
+            let per_minute = shares.len() as f64 / 120.0;
+            assert!(
+                (3.0..=12.0).contains(&per_minute),
+                "{} H/s sent {} shares/min",
+                hashrate,
+                per_minute
+            );
+        }
+    }
+
+    #[test]
+    fn test_follows_a_hashrate_drop() {
+        let mut rng = Rng::new(11);
+        let start = Instant::now();
+        let mut vardiff = Vardiff::new(VardiffConfig::default(), 13, start);
+        let hour = Duration::from_secs(3600);
+        // A desktop throttles down to phone speed
+        simulate(&mut vardiff, &mut rng, start, 1_000.0, hour);
+        simulate(&mut vardiff, &mut rng, start + hour, 20.0, hour);
+        assert!(
+            vardiff.difficulty().abs_diff(8) <= 1,
+            "{}",
+            vardiff.difficulty()
+        );
+    }
+
+    #[test]
+    fn test_retarget_rules() {
+        let start = Instant::now();
+        let config = VardiffConfig {
+            min_difficulty: 4,
+            max_difficulty: 16,
+            ..VardiffConfig::default()
+        };
+        let mut vardiff = Vardiff::new(config.clone(), 1, start);
+        assert_eq!(vardiff.difficulty(), 4);
+
+        // Nothing before the interval, unless flooded
+        assert_eq!(vardiff.retarget(start + Duration::from_secs(60)), None);
+        for i in 0..48 {
+            assert_eq!(vardiff.record_share(start + Duration::from_millis(i)), None);
+        }
+        assert_eq!(
+            vardiff.record_share(start + Duration::from_millis(48)),
+            Some(16)
+        );
+
+        // A silent worker steps down, and never below the minimum
+        let mut now = start + Duration::from_millis(48);
+        let mut previous = vardiff.difficulty();
+        loop {
+            now += DEFAULT_RETARGET_INTERVAL;
+            let Some(next) = vardiff.retarget(now) else {
+                break;
+            };
+            assert!(next < previous);
+            previous = next;
+        }
+        assert_eq!(vardiff.difficulty(), 4);
+
+        // On target within tolerance: no change
+        let mut vardiff = Vardiff::new(config, 10, start);
+        for i in 1..=12 {
+            vardiff.record_share(start + Duration::from_secs(i * 10));
+        }
+        assert_eq!(vardiff.retarget(start + DEFAULT_RETARGET_INTERVAL), None);
+        assert_eq!(vardiff.difficulty(), 10);
+    }
+}
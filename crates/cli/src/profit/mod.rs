@@ -0,0 +1,259 @@
+//! Mining profitability estimate
+//!
+//! A proof at difficulty `d` takes 2^d hashes on average, so a hashrate `h`
+//! finds `h * 86400 / 2^d` proofs a day, each paid the contract's base
+//! reward. Against that stand the electricity the device draws and the
+//! transaction fee of each proof. Prices come from the command line or a
+//! price feed; without an LI price the estimate still gives the daily LI and
+//! the LI price at which mining breaks even.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Smallest LI unit per LI
+pub const ULI_PER_LI: f64 = 1_000_000.0;
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Limit on a price feed request
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum ProfitError {
+    #[error("Hashrate must be positive, got {0}")]
+    InvalidHashrate(f64),
+
+    #[error("Invalid base reward '{0}'")]
+    InvalidReward(String),
+
+    #[error("Price feed {url} failed: {reason}")]
+    PriceFeed { url: String, reason: String },
+}
+
+/// What the estimate is computed from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfitParams {
+    /// H/s of the whole device
+    pub hashrate: f64,
+    /// Contract difficulty in bits
+    pub difficulty: u32,
+    /// uLI paid per proof
+    pub base_reward: u128,
+    /// Device power draw while mining
+    pub power_watts: f64,
+    /// Electricity price per kWh
+    pub electricity_price: f64,
+    /// Transaction fee per proof in BOOT
+    pub fee_per_proof: f64,
+    #[serde(flatten)]
+    pub prices: Prices,
+}
+
+impl ProfitParams {
+    pub fn validate(&self) -> Result<(), ProfitError> {
+        if !(self.hashrate.is_finite() && self.hashrate > 0.0) {
+            return Err(ProfitError::InvalidHashrate(self.hashrate));
+        }
+        Ok(())
+    }
+}
+
+/// Token prices in the currency electricity is paid in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prices {
+    /// Price of one LI
+    #[serde(default, rename = "li", skip_serializing_if = "Option::is_none")]
+    pub li_price: Option<f64>,
+    /// Price of one BOOT
+    #[serde(default, rename = "boot", skip_serializing_if = "Option::is_none")]
+    pub boot_price: Option<f64>,
+}
+
+impl Prices {
+    /// Prices from `self`, falling back to `other` where missing
+    pub fn or(self, other: Prices) -> Prices {
+        Prices {
+            li_price: self.li_price.or(other.li_price),
+            boot_price: self.boot_price.or(other.boot_price),
+        }
+    }
+}
+
+/// Expected earnings and costs per day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfitEstimate {
+    pub proofs_per_day: f64,
+    pub li_per_day: f64,
+    pub kwh_per_day: f64,
+    pub power_cost_per_day: f64,
+    /// BOOT spent on transaction fees
+    pub fees_per_day: f64,
+    /// Value of the LI mined (with an LI price)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revenue_per_day: Option<f64>,
+    /// Revenue minus power and fees (with an LI price, and a BOOT price if
+    /// fees are charged)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profit_per_day: Option<f64>,
+    /// LI price at which revenue covers power and fees
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_even_li_price: Option<f64>,
+    /// Highest difficulty at which mining still pays (with an LI price)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_even_difficulty: Option<f64>,
+}
+
+/// Estimate daily earnings and the break-even point for `params`
+pub fn estimate(params: &ProfitParams) -> Result<ProfitEstimate, ProfitError> {
+    params.validate()?;
+    let work_per_proof = 2f64.powi(params.difficulty as i32);
+    let proofs_per_day = params.hashrate * SECS_PER_DAY / work_per_proof;
+    let reward_li = params.base_reward as f64 / ULI_PER_LI;
+    let li_per_day = proofs_per_day * reward_li;
+    let kwh_per_day = params.power_watts * 24.0 / 1000.0;
+    let power_cost_per_day = kwh_per_day * params.electricity_price;
+    let fees_per_day = proofs_per_day * params.fee_per_proof;
+
+    // Fees only need a BOOT price if there are any
+    let fee_cost_per_proof = match params.prices.boot_price {
+        Some(price) => Some(params.fee_per_proof * price),
+        None if params.fee_per_proof == 0.0 => Some(0.0),
+        None => None,
+    };
+    let fee_cost_per_day = fee_cost_per_proof.map(|cost| cost * proofs_per_day);
+    let revenue_per_day = params.prices.li_price.map(|price| li_per_day * price);
+    let profit_per_day = revenue_per_day
+        .zip(fee_cost_per_day)
+        .map(|(revenue, fees)| revenue - power_cost_per_day - fees);
+    let break_even_li_price = fee_cost_per_day
+        .filter(|_| li_per_day > 0.0)
+        .map(|fees| (power_cost_per_day + fees) / li_per_day);
+
+    // The margin per proof and the power bill do not depend on difficulty:
+    // mining pays while proofs_per_day * margin covers the power
+    let break_even_difficulty = params
+        .prices
+        .li_price
+        .zip(fee_cost_per_proof)
+        .map(|(li_price, fee_cost)| reward_li * li_price - fee_cost)
+        .filter(|&margin| margin > 0.0 && power_cost_per_day > 0.0)
+        .map(|margin| (params.hashrate * SECS_PER_DAY * margin / power_cost_per_day).log2());
+
+    Ok(ProfitEstimate {
+        proofs_per_day,
+        li_per_day,
+        kwh_per_day,
+        power_cost_per_day,
+        fees_per_day,
+        revenue_per_day,
+        profit_per_day,
+        break_even_li_price,
+        break_even_difficulty,
+    })
+}
+
+/// Parse a contract base reward (uLI as a decimal string)
+pub fn parse_reward(base_reward: &str) -> Result<u128, ProfitError> {
+    base_reward
+        .parse()
+        .map_err(|_| ProfitError::InvalidReward(base_reward.to_string()))
+}
+
+/// Fetch prices from a feed answering `{"li": <price>, "boot": <price>}`
+pub async fn fetch_prices(url: &str) -> Result<Prices, ProfitError> {
+    let failed = |reason: String| ProfitError::PriceFeed {
+        url: url.to_string(),
+        reason,
+    };
+    let resp = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| failed(e.to_string()))?
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(failed(format!("HTTP {}", resp.status())));
+    }
+    resp.json()
+        .await
+        .map_err(|e| failed(format!("invalid reply: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> ProfitParams {
+        ProfitParams {
+            hashrate: 1000.0,
+            difficulty: 20,
+            base_reward: 1_000_000,
+            power_watts: 15.0,
+            electricity_price: 0.20,
+            fee_per_proof: 0.0,
+            prices: Prices::default(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_without_prices() {
+        let estimate = estimate(&params()).unwrap();
+        // 1000 H/s * 86400 s / 2^20
+        assert!((estimate.proofs_per_day - 82.397).abs() < 0.001);
+        assert_eq!(estimate.li_per_day, estimate.proofs_per_day);
+        assert!((estimate.kwh_per_day - 0.36).abs() < 1e-9);
+        assert!((estimate.power_cost_per_day - 0.072).abs() < 1e-9);
+        assert_eq!(estimate.revenue_per_day, None);
+        assert_eq!(estimate.profit_per_day, None);
+        let break_even = estimate.break_even_li_price.unwrap();
+        assert!((break_even * estimate.li_per_day - 0.072).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_with_prices_and_fees() {
+        let params = ProfitParams {
+            fee_per_proof: 2.0,
+            prices: Prices {
+                li_price: Some(0.01),
+                boot_price: Some(0.0001),
+            },
+            ..params()
+        };
+        let estimate = estimate(&params).unwrap();
+        let revenue = estimate.li_per_day * 0.01;
+        let fees = estimate.proofs_per_day * 2.0 * 0.0001;
+        assert_eq!(estimate.revenue_per_day, Some(revenue));
+        assert!((estimate.profit_per_day.unwrap() - (revenue - 0.072 - fees)).abs() < 1e-9);
+
+        // At the break-even difficulty, profit is zero
+        let difficulty = estimate.break_even_difficulty.unwrap();
+        let proofs = 1000.0 * SECS_PER_DAY / 2f64.powf(difficulty);
+        assert!((proofs * (0.01 - 2.0 * 0.0001) - 0.072).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fees_need_a_boot_price() {
+        let params = ProfitParams {
+            fee_per_proof: 2.0,
+            prices: Prices {
+                li_price: Some(0.01),
+                boot_price: None,
+            },
+            ..params()
+        };
+        let estimate = estimate(&params).unwrap();
+        assert!(estimate.revenue_per_day.is_some());
+        assert_eq!(estimate.profit_per_day, None);
+        assert_eq!(estimate.break_even_li_price, None);
+        assert!(matches!(
+            super::estimate(&ProfitParams {
+                hashrate: 0.0,
+                ..params
+            }),
+            Err(ProfitError::InvalidHashrate(_))
+        ));
+    }
+}
@@ -0,0 +1,493 @@
+//! The canonical proof and its encodings
+//!
+//! A [`Proof`] is what every component passes around: the pending queue, the
+//! offline signing hand-off, the relay and pool protocols. It has two stable
+//! encodings:
+//!
+//! - JSON: compact with sorted keys and a lowercase hex hash,
+//!   `{"hash":"00ab…","miner_address":"bostrom1…","nonce":42,"timestamp":1700000000}`,
+//!   plus `"worker_tag"` (lowercase hex) for a tagged proof. The same bytes
+//!   are what a miner signs ([`Proof::sign`]) so a relay or pool can check
+//!   the proof is the miner's before spending gas on it.
+//! - Binary: version byte (2), hash (32 bytes), nonce and timestamp (u64
+//!   little-endian), address length (1 byte) and address, worker tag length
+//!   (1 byte, 0 without one) and tag, then the first 4 bytes of the SHA-256
+//!   of everything before as a checksum. Base64 of it makes a short string to
+//!   copy between machines. Version 1, without the tag, still decodes.
+
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::rpc::ProofSubmission;
+use crate::wallet::{verify_arbitrary, StdSignature, Wallet};
+
+/// Version byte of the binary encoding
+pub const PROOF_ENCODING_VERSION: u8 = 2;
+
+/// Version byte of the binary encoding before worker tags
+const UNTAGGED_ENCODING_VERSION: u8 = 1;
+
+/// Length of the binary checksum
+const CHECKSUM_LEN: usize = 4;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    #[error("Hash must be 64 hex characters")]
+    InvalidHash,
+
+    #[error("Miner address must be 1 to 255 bytes")]
+    InvalidAddress,
+
+    #[error("Worker tag must be 1 to 255 bytes of hex")]
+    InvalidWorkerTag,
+
+    #[error("Encoded proof is truncated")]
+    Truncated,
+
+    #[error("Encoded proof has {0} bytes after the checksum")]
+    TrailingBytes(usize),
+
+    #[error("Unknown proof encoding version {0}")]
+    UnknownVersion(u8),
+
+    #[error("Proof checksum does not match; the encoding was altered or cut")]
+    Checksum,
+
+    #[error("Invalid proof: {0}")]
+    Decode(String),
+
+    #[error("The proof is for {miner_address}, but the wallet is {signer}")]
+    WrongSigner {
+        signer: String,
+        miner_address: String,
+    },
+
+    #[error("Signature error: {0}")]
+    Signature(String),
+}
+
+/// A found proof, ready to submit for `miner_address`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Proof {
+    pub hash: [u8; 32],
+    pub nonce: u64,
+    /// Unix seconds in the proof input
+    pub timestamp: u64,
+    pub miner_address: String,
+    /// Lowercase hex bytes mined after the miner address, as in
+    /// [`ProofSubmission::worker_tag`]
+    pub worker_tag: Option<String>,
+}
+
+/// Field order of the canonical JSON: sorted keys
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProofJson {
+    hash: String,
+    miner_address: String,
+    nonce: u64,
+    timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    worker_tag: Option<String>,
+}
+
+impl Proof {
+    /// Canonical JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.as_json()).expect("proof JSON is infallible")
+    }
+
+    /// Parse JSON, accepting the keys in any order
+    pub fn from_json(json: &str) -> Result<Self, ProofError> {
+        let parsed: ProofJson =
+            serde_json::from_str(json).map_err(|e| ProofError::Decode(e.to_string()))?;
+        Self::from_parts(
+            &parsed.hash,
+            parsed.nonce,
+            parsed.timestamp,
+            parsed.miner_address,
+            parsed.worker_tag.as_deref(),
+        )
+    }
+
+    /// Compact binary encoding with a checksum
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let address = self.miner_address.as_bytes();
+        let tag = self.worker_tag_bytes();
+        let mut bytes =
+            Vec::with_capacity(1 + 32 + 16 + 2 + address.len() + tag.len() + CHECKSUM_LEN);
+        bytes.push(PROOF_ENCODING_VERSION);
+        bytes.extend_from_slice(&self.hash);
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        // Addresses and tags are checked to fit when the proof is built
+        bytes.push(address.len() as u8);
+        bytes.extend_from_slice(address);
+        bytes.push(tag.len() as u8);
+        bytes.extend_from_slice(&tag);
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bytes
+    }
+
+    /// Decode [`Proof::to_bytes`], checking the version and checksum
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let (&version, rest) = bytes.split_first().ok_or(ProofError::Truncated)?;
+        let tagged = match version {
+            PROOF_ENCODING_VERSION => true,
+            UNTAGGED_ENCODING_VERSION => false,
+            _ => return Err(ProofError::UnknownVersion(version)),
+        };
+        let fixed = 32 + 8 + 8 + 1;
+        if rest.len() < fixed {
+            return Err(ProofError::Truncated);
+        }
+        let address_len = rest[fixed - 1] as usize;
+        let mut body_len = 1 + fixed + address_len;
+        let mut tag_len = 0;
+        if tagged {
+            tag_len = *rest.get(fixed + address_len).ok_or(ProofError::Truncated)? as usize;
+            body_len += 1 + tag_len;
+        }
+        if bytes.len() < body_len + CHECKSUM_LEN {
+            return Err(ProofError::Truncated);
+        }
+        if bytes.len() > body_len + CHECKSUM_LEN {
+            return Err(ProofError::TrailingBytes(
+                bytes.len() - body_len - CHECKSUM_LEN,
+            ));
+        }
+        let (body, sum) = bytes.split_at(body_len);
+        if checksum(body) != sum {
+            return Err(ProofError::Checksum);
+        }
+        let u64_at = |at: usize| u64::from_le_bytes(rest[at..at + 8].try_into().unwrap());
+        let miner_address = std::str::from_utf8(&rest[fixed..fixed + address_len])
+            .map_err(|_| ProofError::InvalidAddress)?
+            .to_string();
+        let tag_start = fixed + address_len + 1;
+        let worker_tag = (tag_len > 0).then(|| hex::encode(&rest[tag_start..tag_start + tag_len]));
+        Ok(Self {
+            hash: rest[..32].try_into().unwrap(),
+            nonce: u64_at(32),
+            timestamp: u64_at(40),
+            miner_address,
+            worker_tag,
+        })
+    }
+
+    /// Base64 of [`Proof::to_bytes`]
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self, ProofError> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| ProofError::Decode(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parse either encoding: JSON or base64 binary
+    pub fn decode(encoded: &str) -> Result<Self, ProofError> {
+        if encoded.trim_start().starts_with('{') {
+            Self::from_json(encoded)
+        } else {
+            Self::from_base64(encoded)
+        }
+    }
+
+    /// Detached ADR-036 signature over the canonical JSON by `wallet`, which
+    /// must hold the miner address's key
+    pub fn sign(&self, wallet: &Wallet) -> Result<StdSignature, ProofError> {
+        let signer = wallet.address_str();
+        if signer != self.miner_address {
+            return Err(ProofError::WrongSigner {
+                signer,
+                miner_address: self.miner_address.clone(),
+            });
+        }
+        wallet
+            .sign_arbitrary(self.to_json().as_bytes())
+            .map_err(|e| ProofError::Signature(e.to_string()))
+    }
+
+    /// Whether `signature` is the miner address's over this proof
+    ///
+    /// `Ok(false)` is a well-formed signature by another key or over another
+    /// proof; a malformed signature is an error.
+    pub fn verify_signature(&self, signature: &StdSignature) -> Result<bool, ProofError> {
+        verify_arbitrary(&self.miner_address, self.to_json().as_bytes(), signature)
+            .map_err(|e| ProofError::Signature(e.to_string()))
+    }
+
+    /// The proof as the RPC client submits it
+    pub fn submission(&self) -> ProofSubmission {
+        ProofSubmission {
+            hash: hex::encode(self.hash),
+            nonce: self.nonce,
+            timestamp: self.timestamp,
+            miner_address: self.miner_address.clone(),
+            worker_tag: self.worker_tag.clone(),
+        }
+    }
+
+    /// The worker tag's bytes, empty without one
+    pub fn worker_tag_bytes(&self) -> Vec<u8> {
+        // Tags are checked to be hex when the proof is built
+        self.worker_tag
+            .as_deref()
+            .map_or_else(Vec::new, |tag| hex::decode(tag).unwrap_or_default())
+    }
+
+    fn from_parts(
+        hash: &str,
+        nonce: u64,
+        timestamp: u64,
+        miner_address: String,
+        worker_tag: Option<&str>,
+    ) -> Result<Self, ProofError> {
+        let hash = hex::decode(hash)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ProofError::InvalidHash)?;
+        if miner_address.is_empty() || miner_address.len() > u8::MAX as usize {
+            return Err(ProofError::InvalidAddress);
+        }
+        let worker_tag = worker_tag
+            .map(|tag| match hex::decode(tag) {
+                Ok(bytes) if !bytes.is_empty() && bytes.len() <= u8::MAX as usize => {
+                    Ok(hex::encode(bytes))
+                }
+                _ => Err(ProofError::InvalidWorkerTag),
+            })
+            .transpose()?;
+        Ok(Self {
+            hash,
+            nonce,
+            timestamp,
+            miner_address,
+            worker_tag,
+        })
+    }
+
+    fn as_json(&self) -> ProofJson {
+        ProofJson {
+            hash: hex::encode(self.hash),
+            miner_address: self.miner_address.clone(),
+            nonce: self.nonce,
+            timestamp: self.timestamp,
+            worker_tag: self.worker_tag.clone(),
+        }
+    }
+}
+
+impl TryFrom<&ProofSubmission> for Proof {
+    type Error = ProofError;
+
+    fn try_from(proof: &ProofSubmission) -> Result<Self, ProofError> {
+        Self::from_parts(
+            &proof.hash,
+            proof.nonce,
+            proof.timestamp,
+            proof.miner_address.clone(),
+            proof.worker_tag.as_deref(),
+        )
+    }
+}
+
+impl From<&Proof> for ProofSubmission {
+    fn from(proof: &Proof) -> Self {
+        proof.submission()
+    }
+}
+
+impl Serialize for Proof {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Proof {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parsed = ProofJson::deserialize(deserializer)?;
+        Self::from_parts(
+            &parsed.hash,
+            parsed.nonce,
+            parsed.timestamp,
+            parsed.miner_address,
+            parsed.worker_tag.as_deref(),
+        )
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The hashed input of a proof: `seed ‖ address ‖ worker_tag ‖ timestamp ‖ nonce`,
+/// the last two little-endian; an empty tag adds nothing
+pub fn proof_input(
+    seed: &[u8; 32],
+    address: &str,
+    worker_tag: &[u8],
+    timestamp: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut input = Vec::with_capacity(128);
+    input.extend_from_slice(seed);
+    input.extend_from_slice(address.as_bytes());
+    input.extend_from_slice(worker_tag);
+    input.extend_from_slice(&timestamp.to_le_bytes());
+    input.extend_from_slice(&nonce.to_le_bytes());
+    input
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(bytes);
+    digest[..CHECKSUM_LEN].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof() -> Proof {
+        Proof {
+            hash: [0xab; 32],
+            nonce: 42,
+            timestamp: 1_700_000_000,
+            miner_address: "bostrom1miner".to_string(),
+            worker_tag: None,
+        }
+    }
+
+    fn tagged() -> Proof {
+        Proof {
+            worker_tag: Some("72696730".to_string()),
+            ..proof()
+        }
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let json = proof().to_json();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"hash":"{}","miner_address":"bostrom1miner","nonce":42,"timestamp":1700000000}}"#,
+                "ab".repeat(32)
+            )
+        );
+        assert_eq!(Proof::from_json(&json), Ok(proof()));
+        // Any key order and hex case parse to the same proof
+        let shuffled = format!(
+            r#"{{"nonce":42,"timestamp":1700000000,"miner_address":"bostrom1miner","hash":"{}"}}"#,
+            "AB".repeat(32)
+        );
+        assert_eq!(Proof::from_json(&shuffled), Ok(proof()));
+        assert_eq!(
+            Proof::from_json(r#"{"hash":"00","miner_address":"a","nonce":1,"timestamp":1}"#),
+            Err(ProofError::InvalidHash)
+        );
+    }
+
+    #[test]
+    fn test_binary_round_trip_and_checksum() {
+        let bytes = proof().to_bytes();
+        assert_eq!(bytes.len(), 1 + 32 + 16 + 2 + 13 + 4);
+        assert_eq!(Proof::from_bytes(&bytes), Ok(proof()));
+        assert_eq!(Proof::decode(&proof().to_base64()), Ok(proof()));
+        assert_eq!(Proof::decode(&proof().to_json()), Ok(proof()));
+
+        let mut flipped = bytes.clone();
+        flipped[40] ^= 1;
+        assert_eq!(Proof::from_bytes(&flipped), Err(ProofError::Checksum));
+        assert_eq!(
+            Proof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::Truncated)
+        );
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(
+            Proof::from_bytes(&longer),
+            Err(ProofError::TrailingBytes(1))
+        );
+        let mut future = bytes;
+        future[0] = 3;
+        assert_eq!(
+            Proof::from_bytes(&future),
+            Err(ProofError::UnknownVersion(3))
+        );
+    }
+
+    #[test]
+    fn test_worker_tag_encodings() {
+        let json = tagged().to_json();
+        assert!(json.ends_with(r#""timestamp":1700000000,"worker_tag":"72696730"}"#));
+        assert_eq!(Proof::from_json(&json), Ok(tagged()));
+        assert_eq!(
+            Proof::from_json(&json.replace("7269", "7A69"))
+                .unwrap()
+                .worker_tag,
+            Some("7a696730".to_string())
+        );
+
+        let bytes = tagged().to_bytes();
+        assert_eq!(bytes.len(), 1 + 32 + 16 + 2 + 13 + 4 + 4);
+        assert_eq!(Proof::from_bytes(&bytes), Ok(tagged()));
+        assert_eq!(Proof::decode(&tagged().to_base64()), Ok(tagged()));
+        assert_eq!(tagged().submission().worker_tag_bytes().unwrap(), b"rig0");
+
+        // Version 1 has no tag byte
+        let mut v1 = vec![1];
+        v1.extend_from_slice(&proof().to_bytes()[1..1 + 32 + 16 + 1 + 13]);
+        v1.extend_from_slice(&checksum(&v1));
+        assert_eq!(Proof::from_bytes(&v1), Ok(proof()));
+
+        let bad = ProofSubmission {
+            worker_tag: Some("zz".to_string()),
+            ..proof().submission()
+        };
+        assert_eq!(Proof::try_from(&bad), Err(ProofError::InvalidWorkerTag));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let wallet = Wallet::new().unwrap();
+        let proof = Proof {
+            miner_address: wallet.address_str(),
+            ..tagged()
+        };
+        let signature = proof.sign(&wallet).unwrap();
+        assert_eq!(proof.verify_signature(&signature), Ok(true));
+
+        let altered = Proof {
+            nonce: 43,
+            ..proof.clone()
+        };
+        assert_eq!(altered.verify_signature(&signature), Ok(false));
+        let retagged = Proof {
+            worker_tag: None,
+            ..proof.clone()
+        };
+        assert_eq!(retagged.verify_signature(&signature), Ok(false));
+        let other = Wallet::new().unwrap();
+        assert!(matches!(
+            proof.sign(&other),
+            Err(ProofError::WrongSigner { .. })
+        ));
+        let mut garbled = signature;
+        garbled.signature = "!".to_string();
+        assert!(matches!(
+            proof.verify_signature(&garbled),
+            Err(ProofError::Signature(_))
+        ));
+    }
+
+    #[test]
+    fn test_submission_conversion() {
+        let submission = proof().submission();
+        assert_eq!(submission.hash, "ab".repeat(32));
+        assert_eq!(Proof::try_from(&submission), Ok(proof()));
+    }
+}
@@ -0,0 +1,735 @@
+//! Local aggregation proxy
+//!
+//! `uhash-proxy` serves the LAN devices of one farm over the pool protocol
+//! and stands for all of them upstream: one connection to a pool, or one
+//! RPC client and wallet for the chain, however many phones are hashing.
+//!
+//! A [`Proxy`] is a small pool server. Each worker gets its own vardiff
+//! share difficulty, and its shares are checked here, so garbage never goes
+//! upstream and the proxy knows every worker's hashrate from the work its
+//! shares show. Shares that also meet the upstream difficulty (the pool
+//! session's, or the network's when mining for the chain) come out as
+//! [`ProxyEvent::Share`] for the upstream side to send on.
+//!
+//! Jobs carry no nonce ranges in the pool protocol; workers start at random
+//! nonces, which keeps them from hashing the same inputs.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::net::{SocketAddr, TcpListener};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use uhash_pool_proto::{
+    negotiate, ClientMessage, Job, ProtoError, RejectReason, ServerMessage, SUPPORTED_VERSIONS,
+};
+
+use crate::algorithm::difficulty::expected_hashes;
+use crate::pool::{Share, ShareJob, ShareValidator, Vardiff, VardiffConfig};
+use crate::server;
+
+/// Share difficulty a new worker starts at by default
+pub const DEFAULT_START_DIFFICULTY: u32 = 8;
+
+/// How often idle workers are retargeted
+const RETARGET_CHECK: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum ProxyError {
+    #[error("Invalid message: {0}")]
+    Proto(#[from] ProtoError),
+
+    #[error("Pool refused the proxy: {0}")]
+    Refused(String),
+
+    #[error("Connection failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A worker connected to the proxy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProxyWorker {
+    pub id: u64,
+    /// Worker name from `hello`, or its address
+    pub name: String,
+    pub peer: SocketAddr,
+    /// Current share difficulty
+    pub difficulty: u32,
+    /// Valid shares sent
+    pub shares: u64,
+    /// Hashes per second its shares show since it connected
+    pub hashrate: f64,
+}
+
+/// A share that meets the upstream difficulty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundShare {
+    pub worker: String,
+    pub job_id: String,
+    pub nonce: u64,
+    pub hash: [u8; 32],
+    /// It also meets the network difficulty
+    pub proof: bool,
+}
+
+/// What happened on the proxy, in order
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProxyEvent {
+    Connected(ProxyWorker),
+    Disconnected(ProxyWorker),
+    Share(FoundShare),
+    Rejected {
+        worker: String,
+        reason: RejectReason,
+    },
+}
+
+/// Handle to a proxy that workers connect to
+#[derive(Debug, Clone)]
+pub struct Proxy {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    job: watch::Sender<Option<Job>>,
+    /// Nonces shared in the current job
+    shared_nonces: Mutex<HashSet<u64>>,
+    upstream_difficulty: AtomicU32,
+    start_difficulty: u32,
+    vardiff: VardiffConfig,
+    validator: ShareValidator,
+    next_worker_id: AtomicU64,
+    workers: Mutex<BTreeMap<u64, WorkerState>>,
+    events: mpsc::UnboundedSender<ProxyEvent>,
+}
+
+#[derive(Debug, Clone)]
+struct WorkerState {
+    info: ProxyWorker,
+    connected_at: Instant,
+    /// Expected hashes behind its valid shares
+    work: f64,
+}
+
+impl Proxy {
+    /// A proxy with no job yet, forwarding shares that meet
+    /// `upstream_difficulty`, and the receiver of its events
+    pub fn new(
+        upstream_difficulty: u32,
+        vardiff: VardiffConfig,
+    ) -> (Self, mpsc::UnboundedReceiver<ProxyEvent>) {
+        let (events, receiver) = mpsc::unbounded_channel();
+        let threads = std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+        let proxy = Self {
+            shared: Arc::new(Shared {
+                job: watch::channel(None).0,
+                shared_nonces: Mutex::default(),
+                upstream_difficulty: AtomicU32::new(upstream_difficulty),
+                start_difficulty: DEFAULT_START_DIFFICULTY,
+                vardiff,
+                validator: ShareValidator::new(threads),
+                next_worker_id: AtomicU64::new(1),
+                workers: Mutex::default(),
+                events,
+            }),
+        };
+        (proxy, receiver)
+    }
+
+    /// Start new workers at `difficulty`; set before serving
+    pub fn with_start_difficulty(mut self, difficulty: u32) -> Self {
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            shared.start_difficulty = difficulty;
+        }
+        self
+    }
+
+    /// Replace the job every worker mines
+    pub fn set_job(&self, job: Job) {
+        self.shared.nonces().clear();
+        self.shared.job.send_replace(Some(job));
+    }
+
+    /// The job being mined
+    pub fn job(&self) -> Option<Job> {
+        self.shared.job.borrow().clone()
+    }
+
+    /// Forward shares meeting `difficulty` from now on
+    pub fn set_upstream_difficulty(&self, difficulty: u32) {
+        self.shared
+            .upstream_difficulty
+            .store(difficulty, Ordering::Relaxed);
+    }
+
+    /// Workers connected now
+    pub fn workers(&self) -> Vec<ProxyWorker> {
+        self.shared
+            .workers()
+            .values()
+            .map(|w| w.info.clone())
+            .collect()
+    }
+
+    /// Hashrate of all workers together
+    pub fn hashrate(&self) -> f64 {
+        self.workers().iter().map(|w| w.hashrate).sum()
+    }
+
+    /// Serve workers on an already bound listener until accepting fails
+    pub async fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        server::accept_loop(listener, |stream, peer| {
+            Arc::clone(&self.shared).handle(stream, peer)
+        })
+        .await
+    }
+
+    /// Serve workers on `addr` in a background task, returning the bound address
+    pub fn spawn(&self, addr: SocketAddr) -> std::io::Result<SocketAddr> {
+        let proxy = self.clone();
+        server::spawn_listener(addr, |listener| async move { proxy.serve(listener).await })
+    }
+}
+
+impl Shared {
+    fn workers(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, WorkerState>> {
+        self.workers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn nonces(&self) -> std::sync::MutexGuard<'_, HashSet<u64>> {
+        self.shared_nonces.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn emit(&self, event: ProxyEvent) {
+        // Nobody listening is not the workers' problem
+        let _ = self.events.send(event);
+    }
+
+    async fn handle(self: Arc<Self>, stream: TcpStream, peer: SocketAddr) {
+        // A worker that drops or garbles the connection is just gone
+        let _ = Arc::clone(&self).session(stream, peer).await;
+    }
+
+    async fn session(
+        self: Arc<Self>,
+        stream: TcpStream,
+        peer: SocketAddr,
+    ) -> Result<(), ProxyError> {
+        stream.set_nodelay(true)?;
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let Some(line) = lines.next_line().await? else {
+            return Ok(());
+        };
+        let (version, name) = match ClientMessage::from_frame(&line)? {
+            ClientMessage::Hello {
+                versions,
+                address,
+                worker,
+                ..
+            } => match negotiate(&versions) {
+                Ok(version) => (version, worker.unwrap_or(address)),
+                Err(e) => {
+                    let message = e.to_string();
+                    return send(&mut write, &ServerMessage::Error { message }).await;
+                }
+            },
+            _ => {
+                let message = "expected hello".to_string();
+                return send(&mut write, &ServerMessage::Error { message }).await;
+            }
+        };
+
+        let id = self.next_worker_id.fetch_add(1, Ordering::SeqCst);
+        let now = Instant::now();
+        let vardiff = Vardiff::new(self.vardiff.clone(), self.start_difficulty, now);
+        let info = ProxyWorker {
+            id,
+            name,
+            peer,
+            difficulty: vardiff.difficulty(),
+            shares: 0,
+            hashrate: 0.0,
+        };
+        self.workers().insert(
+            id,
+            WorkerState {
+                info: info.clone(),
+                connected_at: now,
+                work: 0.0,
+            },
+        );
+        self.emit(ProxyEvent::Connected(info));
+
+        let welcome = ServerMessage::Welcome {
+            version,
+            session: format!("{:08x}", id),
+            difficulty: vardiff.difficulty(),
+            binary: false,
+        };
+        let result = match send(&mut write, &welcome).await {
+            Ok(()) => {
+                Arc::clone(&self)
+                    .serve_worker(id, version, vardiff, &mut lines, &mut write)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        if let Some(worker) = self.workers().remove(&id) {
+            self.emit(ProxyEvent::Disconnected(worker.info));
+        }
+        result
+    }
+
+    /// Send jobs as they change and check the worker's shares
+    async fn serve_worker(
+        self: Arc<Self>,
+        id: u64,
+        version: u32,
+        mut vardiff: Vardiff,
+        lines: &mut Lines<BufReader<OwnedReadHalf>>,
+        write: &mut OwnedWriteHalf,
+    ) -> Result<(), ProxyError> {
+        let mut jobs = self.job.subscribe();
+        let mut last: Option<Job> = None;
+        let mut retarget = tokio::time::interval(RETARGET_CHECK);
+        jobs.mark_changed();
+        loop {
+            tokio::select! {
+                changed = jobs.changed() => {
+                    if changed.is_err() {
+                        return Ok(());
+                    }
+                    let job = jobs.borrow_and_update().clone();
+                    if let Some(job) = job {
+                        send(write, &job.announce(last.as_ref(), version)).await?;
+                        last = Some(job);
+                    }
+                }
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        return Ok(());
+                    };
+                    let ClientMessage::Share { job_id, nonce, hash } = ClientMessage::from_frame(&line)? else {
+                        continue;
+                    };
+                    let difficulty = vardiff.difficulty();
+                    let verdict = Arc::clone(&self).check(id, &job_id, nonce, hash, difficulty).await;
+                    let answer = match verdict {
+                        Ok(proof) => ServerMessage::Accepted { job_id, nonce, proof },
+                        Err(reason) => ServerMessage::Rejected { job_id, nonce, reason },
+                    };
+                    send(write, &answer).await?;
+                    if matches!(answer, ServerMessage::Accepted { .. }) {
+                        if let Some(difficulty) = vardiff.record_share(Instant::now()) {
+                            self.set_difficulty(id, difficulty, write).await?;
+                        }
+                    }
+                }
+                _ = retarget.tick() => {
+                    if let Some(difficulty) = vardiff.retarget(Instant::now()) {
+                        self.set_difficulty(id, difficulty, write).await?;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn set_difficulty(
+        &self,
+        id: u64,
+        difficulty: u32,
+        write: &mut OwnedWriteHalf,
+    ) -> Result<(), ProxyError> {
+        if let Some(worker) = self.workers().get_mut(&id) {
+            worker.info.difficulty = difficulty;
+        }
+        send(write, &ServerMessage::SetDifficulty { difficulty }).await
+    }
+
+    /// Check a share of worker `id`; whether it is also a proof
+    async fn check(
+        self: Arc<Self>,
+        id: u64,
+        job_id: &str,
+        nonce: u64,
+        hash: String,
+        difficulty: u32,
+    ) -> Result<bool, RejectReason> {
+        let job = match self.job.borrow().as_ref() {
+            Some(job) if job.job_id == job_id => job.clone(),
+            Some(_) => return Err(RejectReason::StaleJob),
+            None => return Err(RejectReason::UnknownJob),
+        };
+        let mut seed = [0u8; 32];
+        hex::decode_to_slice(&job.seed, &mut seed).map_err(|_| RejectReason::UnknownJob)?;
+        let share_job = ShareJob {
+            seed,
+            address: job.address.clone(),
+            timestamp: job.timestamp,
+            network_difficulty: job.network_difficulty,
+        };
+        let share = Share {
+            nonce,
+            hash,
+            difficulty,
+        };
+
+        // Hashing blocks, so it runs off the connection tasks
+        let shared = Arc::clone(&self);
+        let verdict =
+            tokio::task::spawn_blocking(move || shared.validator.verify(&share_job, &share))
+                .await
+                .unwrap_or(Err(RejectReason::Other));
+        let verdict = verdict.and_then(|valid| {
+            if self.nonces().insert(nonce) {
+                Ok(valid)
+            } else {
+                Err(RejectReason::Duplicate)
+            }
+        });
+
+        let mut workers = self.workers();
+        let Some(worker) = workers.get_mut(&id) else {
+            return verdict.map(|valid| valid.proof);
+        };
+        let name = worker.info.name.clone();
+        let valid = match verdict {
+            Ok(valid) => valid,
+            Err(reason) => {
+                drop(workers);
+                self.emit(ProxyEvent::Rejected {
+                    worker: name,
+                    reason,
+                });
+                return Err(reason);
+            }
+        };
+        worker.work += expected_hashes(difficulty);
+        worker.info.shares += 1;
+        let elapsed = worker.connected_at.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            worker.info.hashrate = worker.work / elapsed;
+        }
+        drop(workers);
+
+        let upstream = self.upstream_difficulty.load(Ordering::Relaxed);
+        if valid.proof || crate::meets_difficulty(&valid.hash, upstream) {
+            self.emit(ProxyEvent::Share(FoundShare {
+                worker: name,
+                job_id: job.job_id,
+                nonce,
+                hash: valid.hash,
+                proof: valid.proof,
+            }));
+        }
+        Ok(valid.proof)
+    }
+}
+
+async fn send(write: &mut OwnedWriteHalf, message: &ServerMessage) -> Result<(), ProxyError> {
+    let mut frame = message.to_frame();
+    frame.push('\n');
+    write.write_all(frame.as_bytes()).await?;
+    Ok(())
+}
+
+/// The proxy's one connection to an upstream pool
+#[derive(Debug)]
+pub struct PoolUpstream {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    write: OwnedWriteHalf,
+}
+
+impl PoolUpstream {
+    /// Connect to the pool at `host:port` and sign in as `address`
+    pub async fn connect(
+        pool: &str,
+        address: &str,
+        worker: Option<String>,
+    ) -> Result<Self, ProxyError> {
+        let stream = TcpStream::connect(pool).await?;
+        stream.set_nodelay(true)?;
+        let (read, write) = stream.into_split();
+        let mut upstream = Self {
+            lines: BufReader::new(read).lines(),
+            write,
+        };
+        upstream
+            .send(&ClientMessage::Hello {
+                versions: SUPPORTED_VERSIONS.to_vec(),
+                address: address.to_string(),
+                worker,
+                agent: Some(format!("uhash-proxy/{}", env!("CARGO_PKG_VERSION"))),
+                binary: false,
+            })
+            .await?;
+        Ok(upstream)
+    }
+
+    /// The pool's next message, `None` once it hangs up
+    ///
+    /// An `error` from the pool is reported as [`ProxyError::Refused`].
+    pub async fn recv(&mut self) -> Result<Option<ServerMessage>, ProxyError> {
+        let Some(line) = self.lines.next_line().await? else {
+            return Ok(None);
+        };
+        match ServerMessage::from_frame(&line)? {
+            ServerMessage::Error { message } => Err(ProxyError::Refused(message)),
+            message => Ok(Some(message)),
+        }
+    }
+
+    /// Send a share on to the pool
+    pub async fn share(&mut self, share: &FoundShare) -> Result<(), ProxyError> {
+        self.send(&ClientMessage::Share {
+            job_id: share.job_id.clone(),
+            nonce: share.nonce,
+            hash: hex::encode(share.hash),
+        })
+        .await
+    }
+
+    pub async fn keepalive(&mut self) -> Result<(), ProxyError> {
+        self.send(&ClientMessage::Keepalive).await
+    }
+
+    async fn send(&mut self, message: &ClientMessage) -> Result<(), ProxyError> {
+        let mut frame = message.to_frame();
+        frame.push('\n');
+        self.write.write_all(frame.as_bytes()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UniversalHash;
+
+    fn job(id: &str, network_difficulty: u32) -> Job {
+        Job {
+            job_id: id.to_string(),
+            seed: "22".repeat(32),
+            address: "bostrom1farm".to_string(),
+            timestamp: 1_700_000_000,
+            network_difficulty,
+        }
+    }
+
+    fn hash(job: &Job, nonce: u64) -> [u8; 32] {
+        let mut input = [0x22u8; 32].to_vec();
+        input.extend_from_slice(job.address.as_bytes());
+        input.extend_from_slice(&job.timestamp.to_le_bytes());
+        input.extend_from_slice(&nonce.to_le_bytes());
+        UniversalHash::new().hash(&input)
+    }
+
+    struct TestWorker {
+        lines: Lines<BufReader<OwnedReadHalf>>,
+        write: OwnedWriteHalf,
+    }
+
+    impl TestWorker {
+        async fn connect(addr: SocketAddr, name: &str) -> Self {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let (read, write) = stream.into_split();
+            let mut worker = Self {
+                lines: BufReader::new(read).lines(),
+                write,
+            };
+            worker
+                .send(ClientMessage::hello(
+                    "bostrom1miner",
+                    Some(name.to_string()),
+                ))
+                .await;
+            worker
+        }
+
+        async fn send(&mut self, message: ClientMessage) {
+            let frame = format!("{}\n", message.to_frame());
+            self.write.write_all(frame.as_bytes()).await.unwrap();
+        }
+
+        async fn recv(&mut self) -> ServerMessage {
+            let line = self.lines.next_line().await.unwrap().unwrap();
+            ServerMessage::from_frame(&line).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_proxy_checks_and_forwards_shares() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let config = VardiffConfig {
+                min_difficulty: 0,
+                ..VardiffConfig::default()
+            };
+            let (proxy, mut events) = Proxy::new(0, config);
+            let proxy = proxy.with_start_difficulty(0);
+            let addr = proxy.spawn("127.0.0.1:0".parse().unwrap()).unwrap();
+
+            let mut worker = TestWorker::connect(addr, "phone-1").await;
+            assert!(matches!(
+                worker.recv().await,
+                ServerMessage::Welcome {
+                    version: 2,
+                    difficulty: 0,
+                    ..
+                }
+            ));
+            assert!(matches!(
+                events.recv().await,
+                Some(ProxyEvent::Connected(_))
+            ));
+
+            let first = job("j1", 255);
+            proxy.set_job(first.clone());
+            assert_eq!(worker.recv().await, ServerMessage::Job(first.clone()));
+
+            // Every hash meets difficulty 0, so the share goes upstream
+            let valid = hash(&first, 5);
+            let share = ClientMessage::Share {
+                job_id: "j1".to_string(),
+                nonce: 5,
+                hash: hex::encode(valid),
+            };
+            worker.send(share.clone()).await;
+            assert!(matches!(
+                worker.recv().await,
+                ServerMessage::Accepted {
+                    nonce: 5,
+                    proof: false,
+                    ..
+                }
+            ));
+            let Some(ProxyEvent::Share(found)) = events.recv().await else {
+                panic!("expected the share upstream");
+            };
+            assert_eq!(
+                (found.worker.as_str(), found.nonce, found.hash),
+                ("phone-1", 5, valid)
+            );
+
+            // Duplicates, wrong hashes and stale jobs stop at the proxy
+            worker.send(share).await;
+            assert!(matches!(
+                worker.recv().await,
+                ServerMessage::Rejected {
+                    reason: RejectReason::Duplicate,
+                    ..
+                }
+            ));
+            worker
+                .send(ClientMessage::Share {
+                    job_id: "j1".to_string(),
+                    nonce: 6,
+                    hash: hex::encode(valid),
+                })
+                .await;
+            assert!(matches!(
+                worker.recv().await,
+                ServerMessage::Rejected {
+                    reason: RejectReason::InvalidHash,
+                    ..
+                }
+            ));
+
+            // The next job reaches the worker as an update
+            let second = job("j2", 0);
+            proxy.set_job(second.clone());
+            let ServerMessage::JobUpdate(update) = worker.recv().await else {
+                panic!("expected a job update");
+            };
+            assert_eq!(first.apply(&update), second);
+            worker
+                .send(ClientMessage::Share {
+                    job_id: "j1".to_string(),
+                    nonce: 7,
+                    hash: hex::encode(hash(&first, 7)),
+                })
+                .await;
+            assert!(matches!(
+                worker.recv().await,
+                ServerMessage::Rejected {
+                    reason: RejectReason::StaleJob,
+                    ..
+                }
+            ));
+            worker
+                .send(ClientMessage::Share {
+                    job_id: "j2".to_string(),
+                    nonce: 5,
+                    hash: hex::encode(hash(&second, 5)),
+                })
+                .await;
+            assert!(matches!(
+                worker.recv().await,
+                ServerMessage::Accepted { proof: true, .. }
+            ));
+
+            let workers = proxy.workers();
+            assert_eq!(workers.len(), 1);
+            assert_eq!(
+                (workers[0].shares, workers[0].name.as_str()),
+                (2, "phone-1")
+            );
+            assert!(proxy.hashrate() > 0.0);
+        });
+    }
+
+    #[test]
+    fn test_pool_upstream() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let pool = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = pool.local_addr().unwrap().to_string();
+            let server = tokio::spawn(async move {
+                let (stream, _) = pool.accept().await.unwrap();
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let hello = ClientMessage::from_frame(&lines.next_line().await.unwrap().unwrap());
+                let share = ClientMessage::from_frame(&lines.next_line().await.unwrap().unwrap());
+                let refusal = ServerMessage::Error {
+                    message: "banned".to_string(),
+                };
+                let frame = format!("{}\n", refusal.to_frame());
+                write.write_all(frame.as_bytes()).await.unwrap();
+                (hello.unwrap(), share.unwrap())
+            });
+
+            let mut upstream = PoolUpstream::connect(&addr, "bostrom1farm", None)
+                .await
+                .unwrap();
+            let found = FoundShare {
+                worker: "phone-1".to_string(),
+                job_id: "j1".to_string(),
+                nonce: 9,
+                hash: [0xab; 32],
+                proof: false,
+            };
+            upstream.share(&found).await.unwrap();
+            assert!(matches!(upstream.recv().await, Err(ProxyError::Refused(m)) if m == "banned"));
+
+            let (hello, share) = server.await.unwrap();
+            assert!(matches!(hello, ClientMessage::Hello { address, agent: Some(_), .. } if address == "bostrom1farm"));
+            assert_eq!(
+                share,
+                ClientMessage::Share {
+                    job_id: "j1".to_string(),
+                    nonce: 9,
+                    hash: "ab".repeat(32),
+                }
+            );
+        });
+    }
+}
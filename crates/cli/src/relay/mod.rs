@@ -0,0 +1,507 @@
+//! Proof relay for miners without a funded account
+//!
+//! `uhash-relay` takes proofs from lightweight miners (phones, browsers) over
+//! HTTP, checks them the way the contract will, and submits them from its own
+//! fee-paying account with `miner_address` set, so the reward still goes to
+//! the miner. It speaks the relay format the miner already uses for first
+//! proofs and for the `pool` sink:
+//!
+//! - `POST /relay` with `{hash, nonce, timestamp, miner_address}` and an
+//!   optional `authorization` (see [`RelayRequest`]) —
+//!   `{"ok": true, "tx_hash": ...}` or `{"ok": false, "error": ...}`
+//! - `GET /health` — the relayer's address and counters
+//!
+//! A proof needs no other signature: the miner address is part of the hashed
+//! input, so a relayed proof cannot be redirected to someone else. An
+//! authorization, the miner's ADR-036 signature over the proof, shows the
+//! miner asked for the relay; it is checked when present and can be required
+//! with [`Relay::require_authorization`]. The local
+//! checks keep the relayer from paying for transactions the contract would
+//! reject. Submissions go out one at a time, since they share the relayer's
+//! account sequence.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use cosmrs::crypto::secp256k1::SigningKey;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::miner::check_worker_tag;
+use crate::proof::{proof_input, Proof, ProofError};
+use crate::rpc::{signer_address, ProofSubmission, RpcClient, RpcError};
+use crate::server;
+use crate::sink::{authorization_data, RelayRequest};
+use crate::wallet::{validate_address_with_prefix, verify_arbitrary};
+use crate::{meets_difficulty, UniversalHash};
+
+/// How far a proof timestamp may run ahead of the relay's clock, in seconds
+pub const MAX_CLOCK_SKEW_SECS: u64 = 60;
+
+#[derive(Error, Debug)]
+pub enum RelayError {
+    #[error("Invalid miner address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Hash must be 64 hex characters")]
+    InvalidHash,
+
+    #[error("Invalid worker tag: {0}")]
+    InvalidWorkerTag(String),
+
+    #[error("Proof must carry the miner's authorization")]
+    MissingAuthorization,
+
+    #[error("Authorization is not the miner's signature over the proof")]
+    InvalidAuthorization,
+
+    #[error("Timestamp {timestamp} is ahead of the relay's clock ({now})")]
+    FutureTimestamp { timestamp: u64, now: u64 },
+
+    #[error("Proof is {age}s old, the contract accepts at most {max}s")]
+    Expired { age: u64, max: u64 },
+
+    #[error("Hash is not the U-Hash of the proof inputs under the current or previous seed")]
+    HashMismatch,
+
+    #[error("Hash does not meet difficulty {0}")]
+    BelowDifficulty(u32),
+
+    #[error("Proof was already relayed")]
+    Duplicate,
+
+    #[error("Mining is paused on the contract")]
+    Paused,
+
+    #[error("Contract rejected the proof: {0}")]
+    Rejected(String),
+
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+}
+
+impl RelayError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::MissingAuthorization | Self::InvalidAuthorization => StatusCode::UNAUTHORIZED,
+            Self::Duplicate => StatusCode::CONFLICT,
+            Self::Paused => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Rpc(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+/// Body of `GET /health`
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayHealth {
+    /// Account that signs and pays for relayed proofs
+    pub relayer: String,
+    pub relayed: u64,
+    pub refused: u64,
+}
+
+/// The relay server
+#[derive(Clone)]
+pub struct Relay {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: RpcClient,
+    signing_key: SigningKey,
+    address: String,
+    prefix: String,
+    require_authorization: bool,
+    /// Current and previous seed, updated as the contract rotates
+    seeds: Mutex<Seeds>,
+    /// Lowercase hex hash → proof timestamp, pruned once too old to submit
+    relayed_hashes: Mutex<HashMap<String, u64>>,
+    /// Held while a submission is in flight
+    submitting: tokio::sync::Mutex<()>,
+    relayed: AtomicU64,
+    refused: AtomicU64,
+}
+
+#[derive(Default)]
+struct Seeds {
+    current: Option<[u8; 32]>,
+    previous: Option<[u8; 32]>,
+}
+
+impl Relay {
+    /// Relay proofs through `client`, paying with `signing_key`'s account
+    ///
+    /// Miner addresses must carry `prefix`, as the relayer's own does.
+    pub fn new(
+        client: RpcClient,
+        signing_key: SigningKey,
+        prefix: &str,
+    ) -> Result<Self, RelayError> {
+        let address = signer_address(&signing_key, prefix)?.to_string();
+        Ok(Self {
+            inner: Arc::new(Inner {
+                client,
+                signing_key,
+                address,
+                prefix: prefix.to_string(),
+                require_authorization: false,
+                seeds: Mutex::new(Seeds::default()),
+                relayed_hashes: Mutex::new(HashMap::new()),
+                submitting: tokio::sync::Mutex::new(()),
+                relayed: AtomicU64::new(0),
+                refused: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// Refuse proofs that carry no authorization
+    ///
+    /// Call before serving; has no effect once the relay has been cloned.
+    pub fn require_authorization(mut self, require: bool) -> Self {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.require_authorization = require;
+        }
+        self
+    }
+
+    /// Account that signs and pays for relayed proofs
+    pub fn address(&self) -> &str {
+        &self.inner.address
+    }
+
+    pub fn health(&self) -> RelayHealth {
+        RelayHealth {
+            relayer: self.inner.address.clone(),
+            relayed: self.inner.relayed.load(Ordering::Relaxed),
+            refused: self.inner.refused.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/relay", post(relay))
+            .route("/health", get(health))
+            .with_state(self.clone())
+    }
+
+    /// Serve relay requests on an already bound listener until the server fails
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
+        server::serve_router(listener, self.router()).await
+    }
+
+    /// Serve relay requests on `addr` in a background task, returning the bound address
+    pub fn spawn(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        server::spawn_server(addr, self.router())
+    }
+
+    /// Check `request` and submit its proof, returning the transaction hash
+    pub async fn submit(&self, request: RelayRequest, now: u64) -> Result<String, RelayError> {
+        let result = self.check_and_submit(request, now).await;
+        let counter = match result {
+            Ok(_) => &self.inner.relayed,
+            Err(_) => &self.inner.refused,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    async fn check_and_submit(
+        &self,
+        request: RelayRequest,
+        now: u64,
+    ) -> Result<String, RelayError> {
+        let RelayRequest {
+            mut proof,
+            authorization,
+        } = request;
+        validate_address_with_prefix(&proof.miner_address, &self.inner.prefix)
+            .map_err(|e| RelayError::InvalidAddress(e.to_string()))?;
+        let canonical = Proof::try_from(&proof).map_err(|e| match e {
+            ProofError::InvalidHash => RelayError::InvalidHash,
+            e => RelayError::InvalidAddress(e.to_string()),
+        })?;
+        match &authorization {
+            Some(signature) => {
+                // Over the canonical proof, or over the proof as the miner sent
+                // it for clients that sign an uppercase hash
+                let signed = canonical.verify_signature(signature).unwrap_or(false)
+                    || verify_arbitrary(
+                        &proof.miner_address,
+                        &authorization_data(&proof),
+                        signature,
+                    )
+                    .unwrap_or(false);
+                if !signed {
+                    return Err(RelayError::InvalidAuthorization);
+                }
+            }
+            None if self.inner.require_authorization => {
+                return Err(RelayError::MissingAuthorization)
+            }
+            None => {}
+        }
+        let worker_tag = proof
+            .worker_tag_bytes()
+            .map_err(|e| RelayError::InvalidWorkerTag(e.to_string()))?;
+        let claimed = canonical.hash;
+        proof = canonical.submission().with_worker_tag(&worker_tag);
+        if proof.timestamp > now + MAX_CLOCK_SKEW_SECS {
+            return Err(RelayError::FutureTimestamp {
+                timestamp: proof.timestamp,
+                now,
+            });
+        }
+
+        let client = &self.inner.client;
+        let config = client.get_config().await?;
+        if config.paused {
+            return Err(RelayError::Paused);
+        }
+        check_worker_tag(&worker_tag, config.max_worker_tag_len)
+            .map_err(|e| RelayError::InvalidWorkerTag(e.to_string()))?;
+        let age = now.saturating_sub(proof.timestamp);
+        if age > config.max_proof_age {
+            return Err(RelayError::Expired {
+                age,
+                max: config.max_proof_age,
+            });
+        }
+        if self.already_relayed(&proof.hash) {
+            return Err(RelayError::Duplicate);
+        }
+
+        let seeds = self.seeds().await?;
+        let check = proof.clone();
+        let matched = tokio::task::spawn_blocking(move || {
+            let mut hasher = UniversalHash::new();
+            seeds
+                .iter()
+                .any(|seed| hasher.hash(&submission_input(seed, &check)) == claimed)
+        })
+        .await
+        .map_err(|e| RpcError::InvalidResponse(format!("hashing task failed: {}", e)))?;
+        if !matched {
+            return Err(RelayError::HashMismatch);
+        }
+        let difficulty = client.get_difficulty().await?;
+        if !meets_difficulty(&claimed, difficulty) {
+            return Err(RelayError::BelowDifficulty(difficulty));
+        }
+
+        let _submitting = self.inner.submitting.lock().await;
+        if !self.mark_relayed(&proof.hash, proof.timestamp, now, config.max_proof_age) {
+            return Err(RelayError::Duplicate);
+        }
+        let hash = proof.hash.clone();
+        let result = match client.submit_proof(proof, &self.inner.signing_key).await {
+            Ok(result) => result,
+            Err(e) => {
+                // Not submitted (as far as we know): let the miner retry
+                self.forget(&hash);
+                return Err(e.into());
+            }
+        };
+        if !result.accepted {
+            return Err(RelayError::Rejected(
+                result
+                    .error
+                    .unwrap_or_else(|| "Transaction rejected".to_string()),
+            ));
+        }
+        Ok(result.tx_hash)
+    }
+
+    /// The seeds a proof may have been mined on: the current one and the one
+    /// it replaced, if the relay saw the rotation
+    async fn seeds(&self) -> Result<Vec<[u8; 32]>, RelayError> {
+        let current = self.inner.client.get_seed().await?.seed;
+        let mut seeds = self.inner.seeds.lock().unwrap_or_else(|e| e.into_inner());
+        if seeds.current != Some(current) {
+            seeds.previous = seeds.current.replace(current);
+        }
+        Ok([seeds.current, seeds.previous]
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    fn already_relayed(&self, hash: &str) -> bool {
+        self.relayed_hashes().contains_key(hash)
+    }
+
+    /// Record `hash` as relayed, `false` if it already was
+    fn mark_relayed(&self, hash: &str, timestamp: u64, now: u64, max_proof_age: u64) -> bool {
+        let mut hashes = self.relayed_hashes();
+        hashes.retain(|_, timestamp| now.saturating_sub(*timestamp) <= max_proof_age);
+        hashes.insert(hash.to_string(), timestamp).is_none()
+    }
+
+    fn forget(&self, hash: &str) {
+        self.relayed_hashes().remove(hash);
+    }
+
+    fn relayed_hashes(&self) -> std::sync::MutexGuard<'_, HashMap<String, u64>> {
+        self.inner
+            .relayed_hashes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The hashed input of a submitted proof
+fn submission_input(seed: &[u8; 32], proof: &ProofSubmission) -> Vec<u8> {
+    proof_input(
+        seed,
+        &proof.miner_address,
+        // Checked to decode before the hash is
+        &proof.worker_tag_bytes().unwrap_or_default(),
+        proof.timestamp,
+        proof.nonce,
+    )
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+async fn relay(State(relay): State<Relay>, Json(request): Json<RelayRequest>) -> Response {
+    match relay.submit(request, unix_now()).await {
+        Ok(tx_hash) => Json(json!({ "ok": true, "tx_hash": tx_hash })).into_response(),
+        Err(e) => (
+            e.status(),
+            Json(json!({ "ok": false, "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+async fn health(State(relay): State<Relay>) -> Json<RelayHealth> {
+    Json(relay.health())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    fn relay() -> Relay {
+        let wallet = Wallet::new().unwrap();
+        let signing_key =
+            SigningKey::from_slice(&wallet.signing_key().unwrap().to_bytes()).unwrap();
+        Relay::new(RpcClient::new(), signing_key, "bostrom").unwrap()
+    }
+
+    fn unsigned(proof: ProofSubmission) -> RelayRequest {
+        RelayRequest {
+            proof,
+            authorization: None,
+        }
+    }
+
+    #[test]
+    fn test_malformed_proofs_are_refused_before_the_chain() {
+        let relay = relay();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let now = 1_700_000_000;
+        let proof = ProofSubmission {
+            hash: "00".repeat(32),
+            nonce: 1,
+            timestamp: now,
+            miner_address: Wallet::new().unwrap().address_str(),
+            worker_tag: None,
+        };
+
+        let mut foreign = proof.clone();
+        foreign.miner_address = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu".to_string();
+        assert!(matches!(
+            rt.block_on(relay.submit(unsigned(foreign), now)),
+            Err(RelayError::InvalidAddress(_))
+        ));
+        let mut short = proof.clone();
+        short.hash = "00ab".to_string();
+        assert!(matches!(
+            rt.block_on(relay.submit(unsigned(short), now)),
+            Err(RelayError::InvalidHash)
+        ));
+        assert!(matches!(
+            rt.block_on(relay.submit(unsigned(proof), now - MAX_CLOCK_SKEW_SECS - 1)),
+            Err(RelayError::FutureTimestamp { .. })
+        ));
+        assert_eq!(relay.health().refused, 3);
+    }
+
+    #[test]
+    fn test_authorization_must_be_the_miners() {
+        let relay = relay().require_authorization(true);
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let miner = Wallet::new().unwrap();
+        let proof = ProofSubmission {
+            hash: "00".repeat(32),
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            miner_address: miner.address_str(),
+            worker_tag: None,
+        };
+
+        assert!(matches!(
+            rt.block_on(relay.submit(unsigned(proof.clone()), proof.timestamp)),
+            Err(RelayError::MissingAuthorization)
+        ));
+        // Signed by someone else, or over a different proof
+        for (signer, nonce) in [(Wallet::new().unwrap(), 1), (miner, 2)] {
+            let signed = ProofSubmission {
+                nonce,
+                ..proof.clone()
+            };
+            let request = RelayRequest {
+                proof: proof.clone(),
+                authorization: Some(signer.sign_arbitrary(&authorization_data(&signed)).unwrap()),
+            };
+            assert!(matches!(
+                rt.block_on(relay.submit(request, proof.timestamp)),
+                Err(RelayError::InvalidAuthorization)
+            ));
+        }
+        let other = Wallet::new().unwrap();
+        let forged = Proof::try_from(&ProofSubmission {
+            miner_address: other.address_str(),
+            ..proof.clone()
+        })
+        .unwrap();
+        let request = RelayRequest {
+            proof: proof.clone(),
+            authorization: Some(forged.sign(&other).unwrap()),
+        };
+        assert!(matches!(
+            rt.block_on(relay.submit(request, proof.timestamp)),
+            Err(RelayError::InvalidAuthorization)
+        ));
+    }
+
+    #[test]
+    fn test_relayed_hashes_expire_with_the_proof_age() {
+        let relay = relay();
+        assert!(relay.mark_relayed("aa", 1000, 1000, 3600));
+        assert!(!relay.mark_relayed("aa", 1000, 1000, 3600));
+        assert!(relay.already_relayed("aa"));
+        assert!(relay.mark_relayed("bb", 5000, 5000, 3600));
+        assert!(!relay.already_relayed("aa"));
+    }
+}
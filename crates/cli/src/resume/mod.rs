@@ -0,0 +1,145 @@
+//! Mining round state kept across restarts
+//!
+//! A round hashes one seed and timestamp, each of its `n` threads walking its
+//! own nonce sequence (`start`, `start + n`, ...). When `mine` is stopped in
+//! the middle of a round it writes the seed, the timestamp and each thread's
+//! next nonce to a small JSON file next to the config
+//! (`~/.uhash/round-state.json` by default). `mine --resume` picks the round
+//! up where it stopped if the seed is still current, so a short restart does
+//! not hash the same nonces again.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File name of the saved round, in the config directory
+pub const ROUND_STATE_FILE_NAME: &str = "round-state.json";
+
+/// A round interrupted before it found a proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundState {
+    pub address: String,
+    /// Hex seed the round hashed against
+    pub seed: String,
+    /// Unix seconds in the proof input
+    pub timestamp: u64,
+    pub difficulty: u32,
+    /// Next nonce of each thread
+    pub cursors: Vec<u64>,
+}
+
+impl RoundState {
+    /// State of a round whose threads started at `starts` and hashed
+    /// `hashes` nonces each, stepping by the thread count
+    pub fn new(
+        address: &str,
+        seed: &[u8],
+        timestamp: u64,
+        difficulty: u32,
+        starts: &[u64],
+        hashes: &[u64],
+    ) -> Self {
+        let step = starts.len() as u64;
+        Self {
+            address: address.to_string(),
+            seed: hex::encode(seed),
+            timestamp,
+            difficulty,
+            cursors: starts
+                .iter()
+                .zip(hashes)
+                .map(|(start, hashes)| start + hashes * step)
+                .collect(),
+        }
+    }
+
+    /// Whether the round can continue mining for `address` on `seed`
+    pub fn matches(&self, address: &str, seed: &[u8], difficulty: u32) -> bool {
+        self.address == address && self.seed == hex::encode(seed) && self.difficulty == difficulty
+    }
+
+    /// First nonce of each of `threads` threads continuing the round
+    ///
+    /// With the thread count unchanged every thread picks up its own cursor.
+    /// Otherwise the new threads start from the lowest cursor: every nonce
+    /// below it was hashed, so nothing is skipped and little is repeated.
+    pub fn start_nonces(&self, threads: usize) -> Vec<u64> {
+        if self.cursors.len() == threads {
+            return self.cursors.clone();
+        }
+        let base = self.cursors.iter().copied().min().unwrap_or(0);
+        (0..threads as u64).map(|thread| base + thread).collect()
+    }
+
+    /// Read the saved round, `None` if there is none
+    pub fn read(path: &Path) -> std::io::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the round atomically
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_vec(self)?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Remove the saved round, if any
+    pub fn remove(path: &Path) -> std::io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_nonces_cover_searched_range() {
+        // Three threads started at 0, 1, 2 and hashed 5, 4 and 7 nonces
+        let state = RoundState::new(
+            "bostrom1miner",
+            &[7; 32],
+            1_700_000_000,
+            16,
+            &[0, 1, 2],
+            &[5, 4, 7],
+        );
+        assert_eq!(state.cursors, vec![15, 13, 23]);
+        assert!(state.matches("bostrom1miner", &[7; 32], 16));
+        assert!(!state.matches("bostrom1miner", &[8; 32], 16));
+        assert!(!state.matches("bostrom1miner", &[7; 32], 17));
+
+        assert_eq!(state.start_nonces(3), vec![15, 13, 23]);
+        // Everything below the lowest cursor was hashed
+        assert_eq!(state.start_nonces(2), vec![13, 14]);
+    }
+
+    #[test]
+    fn test_read_write_remove() {
+        let dir = std::env::temp_dir().join(format!("uhash-round-{}", std::process::id()));
+        let path = dir.join(ROUND_STATE_FILE_NAME);
+        assert_eq!(RoundState::read(&path).unwrap(), None);
+
+        let state = RoundState::new("bostrom1miner", &[1; 32], 42, 8, &[0, 1], &[3, 3]);
+        state.write(&path).unwrap();
+        assert_eq!(RoundState::read(&path).unwrap(), Some(state));
+        RoundState::remove(&path).unwrap();
+        RoundState::remove(&path).unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,212 @@
+//! Backoff for rate-limited and overloaded endpoints
+//!
+//! Public LCD providers answer bursts with HTTP 429 and shed load with 5xx.
+//! Such replies are retried on the same endpoint after a jittered exponential
+//! delay (or the server's `Retry-After`), up to [`RATE_LIMIT_RETRIES`] per
+//! request, before the request fails over to the next endpoint. All requests
+//! of a client also share a [`RetryBudget`] per endpoint, so many concurrent
+//! requests cannot multiply the retries a struggling endpoint receives.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Retries of a rate-limited request against one endpoint before failing over
+pub const RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Retries one endpoint may receive at once, across all requests of a client
+pub const RETRY_BUDGET_BURST: u32 = 10;
+
+/// Time for an endpoint's retry budget to regain one retry
+const RETRY_BUDGET_REFILL: Duration = Duration::from_secs(2);
+
+/// Delay before the first retry
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on any single delay, including `Retry-After`
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// An endpoint replied with 429 Too Many Requests or a 5xx server error
+#[derive(Debug, Clone, Error)]
+#[error("{url} returned HTTP {status}")]
+pub struct HttpStatusError {
+    pub url: String,
+    pub status: u16,
+    /// Delay requested by the server's `Retry-After` header
+    pub retry_after: Option<Duration>,
+}
+
+impl HttpStatusError {
+    /// Map a response to an error if its status warrants backing off
+    pub(crate) fn check(resp: reqwest::Response) -> Result<reqwest::Response, Self> {
+        let status = resp.status();
+        if status != reqwest::StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+            return Ok(resp);
+        }
+        let retry_after = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+        Err(Self {
+            url: resp.url().to_string(),
+            status: status.as_u16(),
+            retry_after,
+        })
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+    }
+}
+
+/// A request is being retried after a rate-limit or server error
+#[derive(Debug, Clone)]
+pub struct BackoffWarning {
+    /// Endpoint that refused the request
+    pub endpoint: String,
+    pub status: u16,
+    /// Retry number against this endpoint (1-based)
+    pub attempt: u32,
+    pub delay: Duration,
+}
+
+impl fmt::Display for BackoffWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = if self.status == 429 {
+            "rate-limited"
+        } else {
+            "unavailable"
+        };
+        write!(
+            f,
+            "{} {} (HTTP {}), retry {}/{} in {:.1}s",
+            self.endpoint,
+            reason,
+            self.status,
+            self.attempt,
+            RATE_LIMIT_RETRIES,
+            self.delay.as_secs_f64()
+        )
+    }
+}
+
+/// Delay before retry number `attempt` (0-based)
+///
+/// Honors `Retry-After` when present; otherwise doubles from [`BASE_DELAY`] and
+/// picks a random point in the upper half so that concurrent miners spread out.
+pub(crate) fn delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(MAX_DELAY);
+    }
+    let ceiling = BASE_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_DELAY);
+    let half = ceiling / 2;
+    half + half.mul_f64(jitter())
+}
+
+/// Token buckets of retries, one per endpoint URL
+///
+/// Each endpoint starts with [`RETRY_BUDGET_BURST`] retries and regains one
+/// every [`RETRY_BUDGET_REFILL`]. A request that finds the budget spent
+/// fails over at once instead of retrying.
+#[derive(Debug, Default)]
+pub(crate) struct RetryBudget {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RetryBudget {
+    /// Take one retry against `endpoint`, or `false` if its budget is spent
+    pub(crate) fn try_take(&self, endpoint: &str) -> bool {
+        self.try_take_at(endpoint, Instant::now())
+    }
+
+    fn try_take_at(&self, endpoint: &str, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets
+            .entry(endpoint.to_string())
+            .or_insert_with(|| Bucket {
+                tokens: f64::from(RETRY_BUDGET_BURST),
+                updated: now,
+            });
+        let refilled = now.saturating_duration_since(bucket.updated).as_secs_f64()
+            / RETRY_BUDGET_REFILL.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refilled).min(f64::from(RETRY_BUDGET_BURST));
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+/// Uniform random fraction in `[0, 1)`
+pub(crate) fn jitter() -> f64 {
+    let mut bytes = [0u8; 8];
+    if getrandom::getrandom(&mut bytes).is_err() {
+        return 0.5;
+    }
+    (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_and_is_capped() {
+        for attempt in 0..4 {
+            let ceiling = BASE_DELAY * (1 << attempt);
+            let d = delay(attempt, None);
+            assert!(
+                d >= ceiling / 2 && d <= ceiling,
+                "attempt {}: {:?}",
+                attempt,
+                d
+            );
+        }
+        assert!(delay(40, None) <= MAX_DELAY);
+    }
+
+    #[test]
+    fn test_delay_honors_retry_after() {
+        assert_eq!(
+            delay(0, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(delay(0, Some(Duration::from_secs(3600))), MAX_DELAY);
+    }
+
+    #[test]
+    fn test_retry_budget_is_shared_per_endpoint() {
+        let budget = RetryBudget::default();
+        let start = Instant::now();
+        for _ in 0..RETRY_BUDGET_BURST {
+            assert!(budget.try_take_at("https://a", start));
+        }
+        assert!(!budget.try_take_at("https://a", start));
+        // Other endpoints keep their own budget
+        assert!(budget.try_take_at("https://b", start));
+
+        // One retry comes back per refill period, never more than the burst
+        assert!(!budget.try_take_at("https://a", start + RETRY_BUDGET_REFILL / 2));
+        assert!(budget.try_take_at("https://a", start + RETRY_BUDGET_REFILL));
+        assert!(!budget.try_take_at("https://a", start + RETRY_BUDGET_REFILL));
+        let later = start + RETRY_BUDGET_REFILL * 100;
+        for _ in 0..RETRY_BUDGET_BURST {
+            assert!(budget.try_take_at("https://a", later));
+        }
+        assert!(!budget.try_take_at("https://a", later));
+    }
+}
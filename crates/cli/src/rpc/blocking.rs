@@ -0,0 +1,204 @@
+//! Blocking wrapper around [`RpcClient`](super::RpcClient)
+//!
+//! For synchronous hosts that would otherwise build a tokio runtime per call.
+//! Like `reqwest::blocking`, the client drives the async client on a private
+//! single-threaded runtime, so failover, backoff, caching and sequence
+//! tracking behave exactly as in the async client. Its methods must not be
+//! called from within an async runtime.
+
+use cosmrs::crypto::secp256k1::SigningKey;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+
+use super::{
+    BackoffWarning, ConfigResponse, EndpointStatus, MinerStatsResponse, ProofSubmission, Result,
+    RpcConfig, RpcError, SeedInfo, SignedTx, SubmitResult, TxInfo,
+};
+
+/// Synchronous RPC client
+pub struct RpcClient {
+    inner: super::RpcClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl RpcClient {
+    /// Create a new RPC client with default config
+    pub fn new() -> Result<Self> {
+        Self::try_with_config(RpcConfig::default())
+    }
+
+    /// Create a new RPC client, failing on an invalid proxy URL or unreadable certificate
+    pub fn try_with_config(config: RpcConfig) -> Result<Self> {
+        Self::from_async(super::RpcClient::try_with_config(config)?)
+    }
+
+    /// Wrap an already configured async client
+    pub fn from_async(inner: super::RpcClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| RpcError::Config(format!("failed to start runtime: {}", e)))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Report retries of rate-limited or failing requests to `handler`
+    pub fn on_warning(self, handler: impl Fn(&BackoffWarning) + Send + Sync + 'static) -> Self {
+        Self {
+            inner: self.inner.on_warning(handler),
+            runtime: self.runtime,
+        }
+    }
+
+    /// The wrapped async client
+    pub fn inner(&self) -> &super::RpcClient {
+        &self.inner
+    }
+
+    /// Get the current configuration
+    pub fn config(&self) -> &RpcConfig {
+        self.inner.config()
+    }
+
+    /// LCD endpoint requests are currently sent to
+    pub fn active_endpoint(&self) -> String {
+        self.inner.active_endpoint()
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Health-check every configured endpoint
+    pub fn probe_endpoints(&self) -> Vec<EndpointStatus> {
+        self.block_on(self.inner.probe_endpoints())
+    }
+
+    /// Probe every endpoint and switch to the fastest healthy one
+    pub fn select_fastest_endpoint(&self) -> Vec<EndpointStatus> {
+        self.block_on(self.inner.select_fastest_endpoint())
+    }
+
+    /// Forget cached contract query responses so the next queries hit the chain
+    pub fn invalidate_cache(&self) {
+        self.inner.invalidate_cache();
+    }
+
+    /// Run a smart query against the configured contract and parse its reply
+    pub fn query_smart<T: DeserializeOwned>(&self, msg: &impl Serialize) -> Result<T> {
+        self.block_on(self.inner.query_smart(msg))
+    }
+
+    /// Read a key of the configured contract's storage, `None` if it is unset
+    pub fn query_raw(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.block_on(self.inner.query_raw(key))
+    }
+
+    /// Query the current mining seed and when it rotates
+    pub fn get_seed(&self) -> Result<SeedInfo> {
+        self.block_on(self.inner.get_seed())
+    }
+
+    /// Query the current difficulty target
+    pub fn get_difficulty(&self) -> Result<u32> {
+        self.block_on(self.inner.get_difficulty())
+    }
+
+    /// Query the minimum profitable difficulty
+    pub fn get_min_profitable_difficulty(&self) -> Result<u32> {
+        self.block_on(self.inner.get_min_profitable_difficulty())
+    }
+
+    /// Query the contract configuration
+    pub fn get_config(&self) -> Result<ConfigResponse> {
+        self.block_on(self.inner.get_config())
+    }
+
+    /// Query proofs submitted and rewards earned by `address`
+    pub fn get_miner_stats(&self, address: &str) -> Result<MinerStatsResponse> {
+        self.block_on(self.inner.get_miner_stats(address))
+    }
+
+    /// Query account info (account number and sequence)
+    pub fn get_account_info(&self, address: &str) -> Result<(u64, u64)> {
+        self.block_on(self.inner.get_account_info(address))
+    }
+
+    /// Check if an account exists on-chain
+    pub fn account_exists(&self, address: &str) -> bool {
+        self.block_on(self.inner.account_exists(address))
+    }
+
+    /// Submit a proof to the chain
+    pub fn submit_proof(
+        &self,
+        proof: ProofSubmission,
+        signing_key: &SigningKey,
+    ) -> Result<SubmitResult> {
+        self.block_on(self.inner.submit_proof(proof, signing_key))
+    }
+
+    /// Relay a proof via the relay service, returning the transaction hash
+    pub fn relay_proof(&self, proof: &ProofSubmission) -> Result<String> {
+        self.block_on(self.inner.relay_proof(proof))
+    }
+
+    /// Broadcast a signed transaction
+    pub fn broadcast_tx(&self, tx_bytes: Vec<u8>) -> Result<String> {
+        self.block_on(self.inner.broadcast_tx(tx_bytes))
+    }
+
+    /// Broadcast a transaction produced offline by [`sign_proof_tx`](super::sign_proof_tx)
+    pub fn broadcast_signed(&self, signed: &SignedTx) -> Result<SubmitResult> {
+        self.block_on(self.inner.broadcast_signed(signed))
+    }
+
+    /// Simulate a signed transaction and return the gas it would use
+    pub fn simulate_gas(&self, tx_bytes: &[u8]) -> Result<u64> {
+        self.block_on(self.inner.simulate_gas(tx_bytes))
+    }
+
+    /// Fetch a committed transaction by hash
+    pub fn get_tx(&self, tx_hash: &str) -> Result<TxInfo> {
+        self.block_on(self.inner.get_tx(tx_hash))
+    }
+
+    /// Poll until a transaction is committed or `confirm_timeout_secs` elapses
+    pub fn wait_for_tx(&self, tx_hash: &str) -> Result<TxInfo> {
+        self.block_on(self.inner.wait_for_tx(tx_hash))
+    }
+}
+
+#[cfg(all(test, feature = "devnet"))]
+mod tests {
+    use super::*;
+    use crate::devnet::{Devnet, DevnetParams};
+
+    #[test]
+    fn test_blocking_queries_devnet() {
+        let devnet = Devnet::new(DevnetParams::default());
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = devnet.clone();
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(server.serve(listener))
+        });
+
+        let client = RpcClient::try_with_config(RpcConfig {
+            lcd_url: format!("http://{}", addr),
+            ..RpcConfig::default()
+        })
+        .unwrap();
+        assert_eq!(client.get_seed().unwrap().seed, devnet.chain().seed());
+        assert_eq!(client.get_difficulty().unwrap(), 8);
+        assert_eq!(client.get_config().unwrap().base_reward, "1000000");
+        assert!(matches!(
+            client.get_tx(&"00".repeat(32)),
+            Err(RpcError::TxNotFound(_))
+        ));
+    }
+}
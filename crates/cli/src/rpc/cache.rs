@@ -0,0 +1,110 @@
+//! Short-lived cache of contract status queries
+//!
+//! Seed, difficulty and config change at most once per seed interval, yet the
+//! miner, status commands and exporters poll them constantly. Responses are kept
+//! for a few seconds; when a newly fetched seed differs from the cached one, the
+//! other entries are dropped since the contract may have retargeted with it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default lifetime of a cached contract query
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 10;
+
+#[derive(Debug)]
+struct Entry {
+    fetched: Instant,
+    value: serde_json::Value,
+}
+
+/// TTL cache of smart query responses keyed by the serialized query
+#[derive(Debug)]
+pub(crate) struct QueryCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl QueryCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        // Entries are independent values, so a poisoned map is still usable
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Cached response for `key`, if younger than the TTL
+    pub(crate) fn get(&self, key: &str) -> Option<serde_json::Value> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        self.lock()
+            .get(key)
+            .filter(|entry| entry.fetched.elapsed() < self.ttl)
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Store a fresh response, returning the one it replaced
+    pub(crate) fn insert(&self, key: &str, value: serde_json::Value) -> Option<serde_json::Value> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        self.lock()
+            .insert(
+                key.to_string(),
+                Entry {
+                    fetched: Instant::now(),
+                    value,
+                },
+            )
+            .map(|entry| entry.value)
+    }
+
+    /// Drop every entry except `keep`
+    pub(crate) fn retain_only(&self, keep: &str) {
+        self.lock().retain(|key, _| key == keep);
+    }
+
+    /// Drop every entry
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_expiry_and_invalidation() {
+        let cache = QueryCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get("seed"), None);
+
+        cache.insert("seed", serde_json::json!(1));
+        cache.insert("difficulty", serde_json::json!(8));
+        assert_eq!(cache.get("seed"), Some(serde_json::json!(1)));
+        assert_eq!(
+            cache.insert("seed", serde_json::json!(2)),
+            Some(serde_json::json!(1))
+        );
+
+        cache.retain_only("seed");
+        assert_eq!(cache.get("difficulty"), None);
+        assert_eq!(cache.get("seed"), Some(serde_json::json!(2)));
+
+        cache.clear();
+        assert_eq!(cache.get("seed"), None);
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_cache() {
+        let cache = QueryCache::new(Duration::ZERO);
+        cache.insert("seed", serde_json::json!(1));
+        assert_eq!(cache.get("seed"), None);
+    }
+}
@@ -0,0 +1,67 @@
+//! Chain access behind a trait
+//!
+//! Command functions and servers take a [`ChainClient`] rather than an
+//! [`RpcClient`] so they can run against [`MockChainClient`](super::MockChainClient)
+//! in tests.
+
+use async_trait::async_trait;
+use cosmrs::crypto::secp256k1::SigningKey;
+
+use super::{ConfigResponse, ProofSubmission, Result, RpcClient, SeedInfo, SubmitResult};
+
+/// The contract queries and transactions a miner needs
+#[async_trait]
+pub trait ChainClient: Send + Sync {
+    /// Current mining seed and when it rotates
+    async fn get_seed(&self) -> Result<SeedInfo>;
+
+    /// Current difficulty target in leading zero bits
+    async fn get_difficulty(&self) -> Result<u32>;
+
+    /// Lowest difficulty at which a proof still earns more than its fee
+    async fn get_min_profitable_difficulty(&self) -> Result<u32>;
+
+    /// Contract configuration
+    async fn get_config(&self) -> Result<ConfigResponse>;
+
+    /// Sign and broadcast a proof from the key's account
+    async fn submit_proof(
+        &self,
+        proof: ProofSubmission,
+        signing_key: &SigningKey,
+    ) -> Result<SubmitResult>;
+
+    /// Broadcast signed transaction bytes, returning the transaction hash
+    async fn broadcast(&self, tx_bytes: Vec<u8>) -> Result<String>;
+}
+
+#[async_trait]
+impl ChainClient for RpcClient {
+    async fn get_seed(&self) -> Result<SeedInfo> {
+        RpcClient::get_seed(self).await
+    }
+
+    async fn get_difficulty(&self) -> Result<u32> {
+        RpcClient::get_difficulty(self).await
+    }
+
+    async fn get_min_profitable_difficulty(&self) -> Result<u32> {
+        RpcClient::get_min_profitable_difficulty(self).await
+    }
+
+    async fn get_config(&self) -> Result<ConfigResponse> {
+        RpcClient::get_config(self).await
+    }
+
+    async fn submit_proof(
+        &self,
+        proof: ProofSubmission,
+        signing_key: &SigningKey,
+    ) -> Result<SubmitResult> {
+        RpcClient::submit_proof(self, proof, signing_key).await
+    }
+
+    async fn broadcast(&self, tx_bytes: Vec<u8>) -> Result<String> {
+        self.broadcast_tx(tx_bytes).await
+    }
+}
@@ -0,0 +1,108 @@
+//! Request/response logging for diagnosing endpoint-specific failures
+//!
+//! Every LCD, relay and health-check request is sent through [`send`]. With the
+//! `debug` feature, and a `tracing` subscriber enabled for [`LOG_TARGET`] at debug
+//! level, each exchange is logged with its method, URL, status, latency and
+//! the first [`BODY_LOG_LIMIT`] bytes of both bodies. Otherwise it is a plain
+//! `send()` and response bodies are left unread.
+
+/// `tracing` target of request logs
+pub const LOG_TARGET: &str = "uhash::rpc";
+
+/// Bytes of each request and response body included in a log event
+pub const BODY_LOG_LIMIT: usize = 512;
+
+/// Send `request`
+#[cfg(not(feature = "debug"))]
+pub(crate) async fn send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    request.send().await
+}
+
+/// Send `request`, logging the exchange if debug logging is enabled
+#[cfg(feature = "debug")]
+pub(crate) async fn send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    use reqwest::ResponseBuilderExt;
+
+    if !tracing::enabled!(target: LOG_TARGET, tracing::Level::DEBUG) {
+        return request.send().await;
+    }
+    let (client, request) = request.build_split();
+    let request = request?;
+    let method = request.method().clone();
+    let url = request.url().clone();
+    let request_body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(truncate)
+        .unwrap_or_default();
+
+    let started = std::time::Instant::now();
+    let resp = match client.execute(request).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            tracing::debug!(
+                target: LOG_TARGET,
+                %method,
+                %url,
+                latency_ms = started.elapsed().as_millis() as u64,
+                request = %request_body,
+                error = %e,
+                "request failed",
+            );
+            return Err(e);
+        }
+    };
+
+    // The body is read here to log it, then handed back in a rebuilt response
+    let status = resp.status();
+    let version = resp.version();
+    let headers = resp.headers().clone();
+    let body = resp.bytes().await?;
+    tracing::debug!(
+        target: LOG_TARGET,
+        %method,
+        %url,
+        status = status.as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        request = %request_body,
+        response = %truncate(&body),
+        "request",
+    );
+
+    let mut builder = http::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    if let Some(builder_headers) = builder.headers_mut() {
+        *builder_headers = headers;
+    }
+    Ok(builder
+        .body(body)
+        .expect("status and headers come from a valid response")
+        .into())
+}
+
+/// Body as text, cut to [`BODY_LOG_LIMIT`] bytes
+#[cfg(feature = "debug")]
+fn truncate(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(&body[..body.len().min(BODY_LOG_LIMIT)]);
+    if body.len() > BODY_LOG_LIMIT {
+        format!("{}... ({} bytes)", text, body.len())
+    } else {
+        text.into_owned()
+    }
+}
+
+#[cfg(all(test, feature = "debug"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_long_bodies() {
+        assert_eq!(truncate(b"{\"ok\":true}"), "{\"ok\":true}");
+        let long = vec![b'a'; BODY_LOG_LIMIT + 10];
+        let logged = truncate(&long);
+        assert!(logged.starts_with(&"a".repeat(BODY_LOG_LIMIT)));
+        assert!(logged.ends_with(&format!("... ({} bytes)", BODY_LOG_LIMIT + 10)));
+    }
+}
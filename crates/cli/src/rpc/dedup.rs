@@ -0,0 +1,173 @@
+//! Proofs this client has already submitted
+//!
+//! Sending a proof twice, say when a retry races the first attempt or two
+//! workers report the same find, burns a transaction fee and a sequence slot
+//! on a submission the contract is bound to reject. The client remembers the
+//! most recent proofs it sent and refuses to send one of them again.
+//!
+//! Proofs are keyed by miner address, nonce, timestamp and hash; the hash
+//! stands in for the seed, which the submission does not carry but which
+//! gives the same inputs another hash.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use super::ProofSubmission;
+
+/// Proofs remembered before the least recently seen is forgotten
+pub const SUBMITTED_PROOFS_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ProofKey {
+    miner_address: String,
+    nonce: u64,
+    timestamp: u64,
+    hash: String,
+}
+
+impl From<&ProofSubmission> for ProofKey {
+    fn from(proof: &ProofSubmission) -> Self {
+        Self {
+            miner_address: proof.miner_address.clone(),
+            nonce: proof.nonce,
+            timestamp: proof.timestamp,
+            hash: proof.hash.to_ascii_lowercase(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Transaction of each proof, `None` while it is being sent
+    tx_hashes: HashMap<ProofKey, Option<String>>,
+    /// Least recently seen first
+    order: VecDeque<ProofKey>,
+}
+
+impl Entries {
+    fn touch(&mut self, key: &ProofKey) {
+        if let Some(i) = self.order.iter().position(|k| k == key) {
+            self.order.remove(i);
+        }
+        self.order.push_back(key.clone());
+    }
+}
+
+/// LRU set of submitted proofs
+#[derive(Debug)]
+pub(crate) struct SubmittedProofs {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl SubmittedProofs {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // Entries are independent, so a poisoned set is still usable
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Claim `proof` for sending
+    ///
+    /// A proof claimed before fails with that claim's transaction, `None`
+    /// while it is still being sent.
+    pub(crate) fn claim(&self, proof: &ProofSubmission) -> Result<(), Option<String>> {
+        let key = ProofKey::from(proof);
+        let mut entries = self.lock();
+        if let Some(tx_hash) = entries.tx_hashes.get(&key).cloned() {
+            entries.touch(&key);
+            return Err(tx_hash);
+        }
+        if entries.order.len() >= self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.tx_hashes.remove(&oldest);
+            }
+        }
+        entries.tx_hashes.insert(key.clone(), None);
+        entries.order.push_back(key);
+        Ok(())
+    }
+
+    /// Record the transaction a claimed proof went out in
+    pub(crate) fn sent(&self, proof: &ProofSubmission, tx_hash: &str) {
+        let key = ProofKey::from(proof);
+        if let Some(entry) = self.lock().tx_hashes.get_mut(&key) {
+            *entry = Some(tx_hash.to_string());
+        }
+    }
+
+    /// Release a claim that never went out in a transaction, so the proof
+    /// can be retried; a sent proof stays claimed
+    pub(crate) fn release(&self, proof: &ProofSubmission) {
+        let key = ProofKey::from(proof);
+        let mut entries = self.lock();
+        if entries.tx_hashes.get(&key) == Some(&None) {
+            entries.tx_hashes.remove(&key);
+            entries.order.retain(|k| k != &key);
+        }
+    }
+
+    /// Forget `proof` whether or not it was sent, for a transaction that
+    /// never made it into the chain
+    pub(crate) fn forget(&self, proof: &ProofSubmission) {
+        let key = ProofKey::from(proof);
+        let mut entries = self.lock();
+        if entries.tx_hashes.remove(&key).is_some() {
+            entries.order.retain(|k| k != &key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof(nonce: u64) -> ProofSubmission {
+        ProofSubmission {
+            hash: "00AB".repeat(16),
+            nonce,
+            timestamp: 1_700_000_000,
+            miner_address: "bostrom1miner".to_string(),
+            worker_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_claim_send_release() {
+        let proofs = SubmittedProofs::new(8);
+        assert_eq!(proofs.claim(&proof(1)), Ok(()));
+        // In flight
+        assert_eq!(proofs.claim(&proof(1)), Err(None));
+        proofs.sent(&proof(1), "TX1");
+        assert_eq!(proofs.claim(&proof(1)), Err(Some("TX1".to_string())));
+        // Waiting for the commit failed, but the transaction is out
+        proofs.release(&proof(1));
+        assert!(proofs.claim(&proof(1)).is_err());
+        // Case does not make another proof
+        let mut lower = proof(1);
+        lower.hash = lower.hash.to_ascii_lowercase();
+        assert!(proofs.claim(&lower).is_err());
+
+        assert_eq!(proofs.claim(&proof(2)), Ok(()));
+        proofs.release(&proof(2));
+        assert_eq!(proofs.claim(&proof(2)), Ok(()));
+    }
+
+    #[test]
+    fn test_least_recently_seen_is_forgotten() {
+        let proofs = SubmittedProofs::new(2);
+        proofs.claim(&proof(1)).unwrap();
+        proofs.claim(&proof(2)).unwrap();
+        // Seeing 1 again keeps it over 2
+        assert!(proofs.claim(&proof(1)).is_err());
+        proofs.claim(&proof(3)).unwrap();
+        assert!(proofs.claim(&proof(1)).is_err());
+        assert_eq!(proofs.claim(&proof(2)), Ok(()));
+    }
+}
@@ -0,0 +1,278 @@
+//! LCD endpoint pool with health checks and failover
+//!
+//! Requests go to the active endpoint; a transport failure (connection error,
+//! timeout, non-JSON reply) moves the pool to the next endpoint and the request
+//! is retried there, so a single dead node does not stall the miner.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use super::{Result, RpcError};
+
+/// Latest-block endpoint used as a cheap health check
+pub(crate) const HEALTH_PATH: &str = "/cosmos/base/tendermint/v1beta1/blocks/latest";
+
+/// Result of probing a single endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    /// Endpoint base URL
+    pub url: String,
+    /// Round-trip time of the health check, if it succeeded
+    #[serde(serialize_with = "serialize_latency_ms", rename = "latency_ms")]
+    pub latency: Option<Duration>,
+    /// Latest block height reported by the endpoint
+    pub height: Option<u64>,
+    /// Chain ID in the latest block header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    /// Why the endpoint is unhealthy
+    pub error: Option<String>,
+    /// Whether this is the endpoint currently in use
+    pub active: bool,
+}
+
+impl EndpointStatus {
+    /// Whether the health check succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+fn serialize_latency_ms<S: serde::Serializer>(
+    latency: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match latency {
+        Some(latency) => serializer.serialize_some(&(latency.as_secs_f64() * 1000.0)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Ordered list of endpoints with the index of the one in use
+#[derive(Debug)]
+pub(crate) struct EndpointPool {
+    urls: RwLock<Vec<String>>,
+    active: AtomicUsize,
+}
+
+impl EndpointPool {
+    pub(crate) fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls: RwLock::new(urls),
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub(crate) fn urls(&self) -> Vec<String> {
+        self.read().clone()
+    }
+
+    /// Swap in a new list, starting over at its first endpoint
+    pub(crate) fn replace(&self, urls: Vec<String>) {
+        *self.urls.write().unwrap_or_else(|e| e.into_inner()) = urls;
+        self.active.store(0, Ordering::Relaxed);
+    }
+
+    /// Index and URL of the endpoint in use
+    pub(crate) fn active(&self) -> (usize, String) {
+        let urls = self.read();
+        let index = self.active.load(Ordering::Relaxed) % urls.len();
+        (index, urls[index].clone())
+    }
+
+    pub(crate) fn set_active(&self, index: usize) {
+        self.active.store(index % self.len(), Ordering::Relaxed);
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<String>> {
+        self.urls.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Move past `failed`, unless another request already did
+    pub(crate) fn fail_over(&self, failed: usize) {
+        let next = (failed + 1) % self.len();
+        let _ = self
+            .active
+            .compare_exchange(failed, next, Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+/// Probe one endpoint's health check, timing the round trip
+pub(crate) async fn probe(http_client: &reqwest::Client, url: &str) -> EndpointStatus {
+    let started = Instant::now();
+    let result = async {
+        let resp: serde_json::Value =
+            super::debug::send(http_client.get(format!("{}{}", url, HEALTH_PATH)))
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+        let header = &resp["block"]["header"];
+        Ok::<_, reqwest::Error>((
+            header["height"].as_str().and_then(|h| h.parse().ok()),
+            header["chain_id"].as_str().map(str::to_string),
+        ))
+    }
+    .await;
+
+    match result {
+        Ok((height, chain_id)) => EndpointStatus {
+            url: url.to_string(),
+            latency: Some(started.elapsed()),
+            height,
+            chain_id,
+            error: None,
+            active: false,
+        },
+        Err(e) => EndpointStatus {
+            url: url.to_string(),
+            latency: None,
+            height: None,
+            chain_id: None,
+            error: Some(e.to_string()),
+            active: false,
+        },
+    }
+}
+
+/// Check that `url` is an absolute http(s) URL with a host
+pub fn validate_endpoint(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| RpcError::Config(format!("invalid endpoint '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(RpcError::Config(format!(
+            "invalid endpoint '{}': expected an http:// or https:// URL",
+            url
+        )));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(RpcError::Config(format!(
+            "invalid endpoint '{}': missing host",
+            url
+        )));
+    }
+    Ok(())
+}
+
+/// Best guess at the LCD URL of a node given its Tendermint RPC URL
+///
+/// Follows the two common conventions: an `rpc.` host label becomes `lcd.`
+/// (`rpc.bostrom.cybernode.ai` → `lcd.bostrom.cybernode.ai`) and the default
+/// RPC port 26657 becomes the default LCD port 1317. Anything else is
+/// returned unchanged; nodes that follow neither need an explicit LCD URL.
+pub fn lcd_url_for_rpc(rpc_url: &str) -> String {
+    let Ok(mut url) = reqwest::Url::parse(rpc_url) else {
+        return rpc_url.to_string();
+    };
+    if let Some(rest) = url.host_str().and_then(|h| h.strip_prefix("rpc.")) {
+        let host = format!("lcd.{}", rest);
+        if url.set_host(Some(&host)).is_err() {
+            return rpc_url.to_string();
+        }
+    }
+    if url.port() == Some(26657) && url.set_port(Some(1317)).is_err() {
+        return rpc_url.to_string();
+    }
+    let derived = url.to_string();
+    // `Url` adds a trailing slash to bare hosts
+    if rpc_url.ends_with('/') {
+        derived
+    } else {
+        derived.trim_end_matches('/').to_string()
+    }
+}
+
+/// Index of the healthy endpoint with the lowest latency
+pub(crate) fn fastest(statuses: &[EndpointStatus]) -> Option<usize> {
+    statuses
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.latency.filter(|_| s.is_healthy()).map(|l| (i, l)))
+        .min_by_key(|(_, latency)| *latency)
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(latency_ms: Option<u64>) -> EndpointStatus {
+        EndpointStatus {
+            url: String::new(),
+            latency: latency_ms.map(Duration::from_millis),
+            height: None,
+            chain_id: None,
+            error: latency_ms.is_none().then(|| "down".to_string()),
+            active: false,
+        }
+    }
+
+    #[test]
+    fn test_fastest_skips_unhealthy() {
+        let statuses = [status(None), status(Some(80)), status(Some(30))];
+        assert_eq!(fastest(&statuses), Some(2));
+        assert_eq!(fastest(&[status(None)]), None);
+    }
+
+    #[test]
+    fn test_lcd_url_for_rpc() {
+        assert_eq!(
+            lcd_url_for_rpc("https://rpc.bostrom.cybernode.ai"),
+            "https://lcd.bostrom.cybernode.ai"
+        );
+        assert_eq!(
+            lcd_url_for_rpc("http://10.0.0.5:26657"),
+            "http://10.0.0.5:1317"
+        );
+        // The old string replacement turned this into "lcd-provider.example/lcd"
+        assert_eq!(
+            lcd_url_for_rpc("https://rpc-provider.example/rpc"),
+            "https://rpc-provider.example/rpc"
+        );
+        assert_eq!(
+            lcd_url_for_rpc("http://127.0.0.1:1317"),
+            "http://127.0.0.1:1317"
+        );
+    }
+
+    #[test]
+    fn test_validate_endpoint() {
+        assert!(validate_endpoint("https://lcd.bostrom.cybernode.ai").is_ok());
+        assert!(validate_endpoint("http://127.0.0.1:1317").is_ok());
+        assert!(validate_endpoint("lcd.bostrom.cybernode.ai").is_err());
+        assert!(validate_endpoint("ftp://lcd.example").is_err());
+    }
+
+    #[test]
+    fn test_fail_over_rotates_once() {
+        let pool = EndpointPool::new(vec!["a".into(), "b".into(), "c".into()]);
+        assert_eq!(pool.active(), (0, "a".to_string()));
+
+        pool.fail_over(0);
+        // A second request that also saw endpoint 0 fail does not skip "b"
+        pool.fail_over(0);
+        assert_eq!(pool.active(), (1, "b".to_string()));
+
+        pool.fail_over(1);
+        pool.fail_over(2);
+        assert_eq!(pool.active(), (0, "a".to_string()));
+    }
+
+    #[test]
+    fn test_replace_starts_on_first() {
+        let pool = EndpointPool::new(vec!["a".into(), "b".into()]);
+        pool.fail_over(0);
+        pool.replace(vec!["c".into()]);
+        assert_eq!(pool.active(), (0, "c".to_string()));
+        assert_eq!(pool.urls(), vec!["c".to_string()]);
+        // Failing over within a one-endpoint pool stays on it
+        pool.fail_over(0);
+        assert_eq!(pool.active(), (0, "c".to_string()));
+    }
+}
@@ -0,0 +1,157 @@
+//! Errors returned by the RPC client
+
+use thiserror::Error;
+
+use super::{HttpStatusError, SEQUENCE_MISMATCH_CODE};
+
+/// Result type of the RPC client
+pub type Result<T, E = RpcError> = std::result::Result<T, E>;
+
+#[derive(Error, Debug)]
+pub enum RpcError {
+    /// Connection failure or timeout
+    #[error("Network error: {0}")]
+    Network(String),
+
+    /// HTTP 429 or 5xx that persisted through backoff
+    #[error(transparent)]
+    HttpStatus(#[from] HttpStatusError),
+
+    /// A reply that is not the JSON (or protobuf) it should be
+    #[error("Failed to decode response: {0}")]
+    Decode(String),
+
+    /// The node rejected the transaction (CheckTx) or it failed on chain
+    #[error("Transaction failed with code {code}: {raw_log}")]
+    TxRejected { code: u32, raw_log: String },
+
+    /// The transaction is not (or not yet) indexed by the node
+    #[error("Transaction {0} not found")]
+    TxNotFound(String),
+
+    /// The contract returned an error for a smart query
+    #[error("Contract query failed: {0}")]
+    ContractQuery(String),
+
+    /// A well-formed reply missing the expected fields
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// The transaction could not be built or signed
+    #[error("Invalid transaction: {0}")]
+    InvalidTx(String),
+
+    /// Invalid client settings (URLs, certificates, flags)
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    #[error("Transaction {tx_hash} was not committed within {timeout_secs}s")]
+    ConfirmTimeout { tx_hash: String, timeout_secs: u64 },
+
+    #[error("Relay failed: {0}")]
+    Relay(String),
+
+    /// The account has never received tokens, so it cannot sign transactions
+    #[error(
+        "Account {address} does not exist on {chain_id} yet; accounts are created by their first \
+         incoming transfer, so send it some tokens (or run `uhash faucet` if the chain has one)"
+    )]
+    AccountNotFound { address: String, chain_id: String },
+
+    #[error("Faucet request failed: {0}")]
+    Faucet(String),
+
+    /// This client already sent the proof, in `tx_hash` if it went out
+    #[error(
+        "Proof was already submitted{}",
+        tx_hash.as_ref().map(|tx| format!(" in {}", tx)).unwrap_or_default()
+    )]
+    DuplicateProof { tx_hash: Option<String> },
+
+    /// Every endpoint failed with a transport error; `last` is the final one
+    #[error("No LCD endpoint could serve the request (last error: {last})")]
+    EndpointsExhausted { last: Box<RpcError> },
+}
+
+impl RpcError {
+    /// Whether the node rejected the transaction for using a stale sequence
+    pub fn is_sequence_mismatch(&self) -> bool {
+        match self {
+            RpcError::TxRejected { code, raw_log } => {
+                *code == SEQUENCE_MISMATCH_CODE || raw_log.contains("account sequence mismatch")
+            }
+            _ => false,
+        }
+    }
+
+    /// The sequence the node expected, parsed from
+    /// `account sequence mismatch, expected 5, got 4: incorrect account sequence`
+    pub fn expected_sequence(&self) -> Option<u64> {
+        let RpcError::TxRejected { raw_log, .. } = self else {
+            return None;
+        };
+        let (_, rest) = raw_log.split_once("expected ")?;
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    }
+
+    /// Whether the endpoint rather than the request is at fault, so another
+    /// endpoint (or a later attempt) may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RpcError::Network(_) | RpcError::HttpStatus(_) | RpcError::Decode(_) => true,
+            RpcError::EndpointsExhausted { last } => last.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for RpcError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            RpcError::Decode(e.to_string())
+        } else {
+            RpcError::Network(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for RpcError {
+    fn from(e: serde_json::Error) -> Self {
+        RpcError::Decode(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_mismatch_detection() {
+        let rejected = RpcError::TxRejected {
+            code: 32,
+            raw_log: "account sequence mismatch, expected 12, got 11: incorrect account sequence"
+                .to_string(),
+        };
+        assert!(rejected.is_sequence_mismatch());
+        assert_eq!(rejected.expected_sequence(), Some(12));
+        assert!(!rejected.is_transient());
+
+        let out_of_gas = RpcError::TxRejected {
+            code: 11,
+            raw_log: "out of gas in location: wasm contract".to_string(),
+        };
+        assert!(!out_of_gas.is_sequence_mismatch());
+        assert_eq!(out_of_gas.expected_sequence(), None);
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(RpcError::Network("connection refused".to_string()).is_transient());
+        let exhausted = RpcError::EndpointsExhausted {
+            last: Box::new(RpcError::Decode("expected value".to_string())),
+        };
+        assert!(exhausted.is_transient());
+        assert!(!RpcError::ContractQuery("paused".to_string()).is_transient());
+    }
+}
@@ -0,0 +1,299 @@
+//! Transaction events emitted by the verifier contract
+//!
+//! CosmWasm emits a `wasm` event (plus `wasm-<custom>` events) whose attributes
+//! carry the contract's response; the reward paid for a proof is read from there.
+//! Each `submit_proof` execution also yields a [`ProofEvent`], which is how
+//! other miners' proofs are observed.
+
+use serde::{Deserialize, Serialize};
+
+/// Attribute CosmWasm adds to every `wasm` event with the emitting contract
+const CONTRACT_ADDRESS_KEY: &str = "_contract_address";
+
+/// Attribute keys the contract may use for the paid reward, in priority order
+const REWARD_KEYS: [&str; 3] = ["reward", "reward_amount", "amount"];
+
+/// Attribute keys the contract may use for the denom of the reward
+const DENOM_KEYS: [&str; 2] = ["reward_denom", "denom"];
+
+/// `action` attribute of a proof submission
+const SUBMIT_PROOF_ACTION: &str = "submit_proof";
+
+/// A key/value event attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventAttribute {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// An ABCI event from a transaction result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub attributes: Vec<EventAttribute>,
+}
+
+impl TxEvent {
+    /// Value of the first attribute with the given key
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.as_str())
+    }
+
+    /// Whether this is a CosmWasm event emitted by `contract`
+    fn is_wasm_event_from(&self, contract: &str) -> bool {
+        (self.kind == "wasm" || self.kind.starts_with("wasm-"))
+            && self.attribute(CONTRACT_ADDRESS_KEY) == Some(contract)
+    }
+}
+
+/// Reward paid by `contract` according to its wasm events, if any
+pub fn proof_reward(events: &[TxEvent], contract: &str) -> Option<String> {
+    let wasm_events: Vec<&TxEvent> = events
+        .iter()
+        .filter(|e| e.is_wasm_event_from(contract))
+        .collect();
+    REWARD_KEYS.iter().find_map(|key| {
+        wasm_events
+            .iter()
+            .find_map(|e| e.attribute(key))
+            .map(str::to_string)
+    })
+}
+
+/// Denom of the reward paid by `contract`, if its wasm events name one
+pub fn proof_reward_denom(events: &[TxEvent], contract: &str) -> Option<String> {
+    let wasm_events: Vec<&TxEvent> = events
+        .iter()
+        .filter(|e| e.is_wasm_event_from(contract))
+        .collect();
+    DENOM_KEYS.iter().find_map(|key| {
+        wasm_events
+            .iter()
+            .find_map(|e| e.attribute(key))
+            .map(str::to_string)
+    })
+}
+
+/// Reward paid by `contract` for all the proofs of a transaction
+///
+/// A transaction carrying several `submit_proof` messages has a wasm event
+/// per proof; their rewards are added up. Otherwise this is [`proof_reward`].
+pub fn batch_reward(events: &[TxEvent], contract: &str) -> Option<String> {
+    let rewards: Vec<u128> = proof_events(events, contract, "", 0)
+        .iter()
+        .filter_map(|proof| proof.reward.as_deref()?.parse().ok())
+        .collect();
+    if rewards.len() > 1 {
+        return Some(rewards.iter().sum::<u128>().to_string());
+    }
+    proof_reward(events, contract)
+}
+
+/// A proof accepted by the contract, from any miner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEvent {
+    pub tx_hash: String,
+    pub height: u64,
+    pub miner: String,
+    /// Difficulty the proof was accepted at, if the contract reports it
+    pub difficulty: Option<u32>,
+    /// Reward paid in uLI, if the contract reports it
+    pub reward: Option<String>,
+}
+
+/// Proofs accepted by `contract` in a committed transaction
+pub fn proof_events(
+    events: &[TxEvent],
+    contract: &str,
+    tx_hash: &str,
+    height: u64,
+) -> Vec<ProofEvent> {
+    events
+        .iter()
+        .filter(|e| e.is_wasm_event_from(contract))
+        .filter(|e| e.attribute("action") == Some(SUBMIT_PROOF_ACTION))
+        .filter_map(|e| {
+            Some(ProofEvent {
+                tx_hash: tx_hash.to_string(),
+                height,
+                miner: e.attribute("miner")?.to_string(),
+                difficulty: e.attribute("difficulty").and_then(|d| d.parse().ok()),
+                reward: REWARD_KEYS
+                    .iter()
+                    .find_map(|key| e.attribute(key))
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// A proof accepted by the contract, with the time of its block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptedProof {
+    #[serde(flatten)]
+    pub event: ProofEvent,
+    /// Unix time of the block
+    pub time: u64,
+}
+
+/// Unix time of an RFC 3339 block timestamp such as `2024-05-01T12:34:56.789Z`
+///
+/// Fractional seconds are dropped; only UTC (`Z`) timestamps, which is
+/// what Cosmos SDK nodes report, are accepted.
+pub(crate) fn parse_block_time(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let time = time.split('.').next()?;
+    let mut time = time.splitn(3, ':').map(str::parse::<i64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    // Days since the epoch of a proleptic Gregorian date (Howard Hinnant's days_from_civil)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    u64::try_from(days * 86_400 + hour * 3600 + minute * 60 + second).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "bostrom1qwys5wj3r4lry7dl74ukn5unhdpa6t397h097q36dqvrp5qgvjxqverdlf";
+
+    #[test]
+    fn test_proof_reward_from_wasm_event() {
+        let events: Vec<TxEvent> = serde_json::from_value(serde_json::json!([
+            { "type": "message", "attributes": [{ "key": "action", "value": "/cosmwasm.wasm.v1.MsgExecuteContract" }] },
+            { "type": "wasm", "attributes": [
+                { "key": "_contract_address", "value": CONTRACT, "index": true },
+                { "key": "action", "value": "submit_proof" },
+                { "key": "reward", "value": "1250000" }
+            ]},
+            { "type": "transfer", "attributes": [{ "key": "amount", "value": "5boot" }] }
+        ]))
+        .unwrap();
+
+        assert_eq!(proof_reward(&events, CONTRACT).as_deref(), Some("1250000"));
+        assert_eq!(proof_reward(&events, "bostrom1other"), None);
+        assert_eq!(proof_reward_denom(&events, CONTRACT), None);
+    }
+
+    #[test]
+    fn test_proof_reward_denom_from_wasm_event() {
+        let events: Vec<TxEvent> = serde_json::from_value(serde_json::json!([
+            { "type": "wasm", "attributes": [
+                { "key": "_contract_address", "value": CONTRACT },
+                { "key": "reward_amount", "value": "7" },
+                { "key": "reward_denom", "value": "factory/bostrom1x/li" }
+            ]}
+        ]))
+        .unwrap();
+
+        assert_eq!(proof_reward(&events, CONTRACT).as_deref(), Some("7"));
+        assert_eq!(
+            proof_reward_denom(&events, CONTRACT).as_deref(),
+            Some("factory/bostrom1x/li")
+        );
+    }
+
+    #[test]
+    fn test_proof_reward_ignores_other_contracts() {
+        let events = vec![TxEvent {
+            kind: "wasm-mint".to_string(),
+            attributes: vec![
+                EventAttribute {
+                    key: "_contract_address".to_string(),
+                    value: "bostrom1other".to_string(),
+                },
+                EventAttribute {
+                    key: "amount".to_string(),
+                    value: "99".to_string(),
+                },
+            ],
+        }];
+        assert_eq!(proof_reward(&events, CONTRACT), None);
+    }
+
+    #[test]
+    fn test_proof_events_one_per_submission() {
+        let events: Vec<TxEvent> = serde_json::from_value(serde_json::json!([
+            { "type": "wasm", "attributes": [
+                { "key": "_contract_address", "value": CONTRACT },
+                { "key": "action", "value": "submit_proof" },
+                { "key": "miner", "value": "bostrom1alice" },
+                { "key": "difficulty", "value": "18" },
+                { "key": "reward", "value": "1000000" }
+            ]},
+            { "type": "wasm", "attributes": [
+                { "key": "_contract_address", "value": CONTRACT },
+                { "key": "action", "value": "submit_proof" },
+                { "key": "miner", "value": "bostrom1bob" }
+            ]},
+            { "type": "wasm", "attributes": [
+                { "key": "_contract_address", "value": CONTRACT },
+                { "key": "action", "value": "update_config" }
+            ]}
+        ]))
+        .unwrap();
+
+        let proofs = proof_events(&events, CONTRACT, "ABCD", 42);
+        assert_eq!(proofs.len(), 2);
+        assert_eq!(proofs[0].miner, "bostrom1alice");
+        assert_eq!(proofs[0].difficulty, Some(18));
+        assert_eq!(proofs[0].reward.as_deref(), Some("1000000"));
+        assert_eq!(proofs[0].height, 42);
+        assert_eq!(proofs[1].miner, "bostrom1bob");
+        assert_eq!(proofs[1].difficulty, None);
+        assert_eq!(proofs[1].reward, None);
+        assert!(proof_events(&events, "bostrom1other", "ABCD", 42).is_empty());
+    }
+
+    #[test]
+    fn test_batch_reward_adds_up_proofs() {
+        let submission = |reward: &str| {
+            serde_json::json!({ "type": "wasm", "attributes": [
+                { "key": "_contract_address", "value": CONTRACT },
+                { "key": "action", "value": "submit_proof" },
+                { "key": "miner", "value": "bostrom1alice" },
+                { "key": "reward", "value": reward }
+            ]})
+        };
+        let batch: Vec<TxEvent> =
+            serde_json::from_value(serde_json::json!([submission("1000"), submission("250")]))
+                .unwrap();
+        assert_eq!(batch_reward(&batch, CONTRACT).as_deref(), Some("1250"));
+
+        let single: Vec<TxEvent> =
+            serde_json::from_value(serde_json::json!([submission("1000")])).unwrap();
+        assert_eq!(batch_reward(&single, CONTRACT).as_deref(), Some("1000"));
+    }
+
+    #[test]
+    fn test_parse_block_time() {
+        assert_eq!(parse_block_time("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_block_time("2023-11-14T22:13:20Z"),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            parse_block_time("2024-02-29T12:00:00.123456789Z"),
+            Some(1_709_208_000)
+        );
+        assert_eq!(parse_block_time("2024-02-29T12:00:00+02:00"), None);
+        assert_eq!(parse_block_time("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_block_time("yesterday"), None);
+    }
+}
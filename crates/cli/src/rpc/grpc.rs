@@ -0,0 +1,160 @@
+//! gRPC transport for account queries, contract queries and broadcast
+//!
+//! Public LCD endpoints are often rate-limited or lag behind the chain head; a
+//! node's gRPC port answers the same queries directly. The connection is opened
+//! on first use so that building an [`RpcClient`](super::RpcClient) stays
+//! synchronous.
+
+use cosmrs::proto::cosmos::auth::v1beta1::{
+    query_client::QueryClient as AuthQueryClient, BaseAccount, QueryAccountRequest,
+};
+use cosmrs::proto::cosmos::tx::v1beta1::{
+    service_client::ServiceClient, BroadcastMode as ProtoBroadcastMode, BroadcastTxRequest,
+};
+use cosmrs::proto::cosmwasm::wasm::v1::{
+    query_client::QueryClient as WasmQueryClient, QueryRawContractStateRequest,
+    QuerySmartContractStateRequest,
+};
+use cosmrs::proto::prost::Message;
+use tokio::sync::OnceCell;
+use tonic::transport::{Channel, Endpoint};
+
+use super::{BroadcastMode, Result, RpcError};
+
+/// Type URL of the only account type proofs can be signed for
+const BASE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.BaseAccount";
+
+/// Lazily connected gRPC channel to a single node
+#[derive(Debug)]
+pub(crate) struct GrpcClient {
+    url: String,
+    channel: OnceCell<Channel>,
+}
+
+impl GrpcClient {
+    pub(crate) fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            channel: OnceCell::new(),
+        }
+    }
+
+    async fn channel(&self) -> Result<Channel> {
+        let channel = self
+            .channel
+            .get_or_try_init(|| async {
+                Endpoint::from_shared(self.url.clone())
+                    .map_err(|e| {
+                        RpcError::Config(format!("Invalid gRPC URL '{}': {}", self.url, e))
+                    })?
+                    .connect()
+                    .await
+                    .map_err(|e| {
+                        RpcError::Network(format!("Failed to connect to {}: {}", self.url, e))
+                    })
+            })
+            .await?;
+        Ok(channel.clone())
+    }
+
+    /// Account number and sequence, or `None` if the account does not exist
+    pub(crate) async fn account_info(&self, address: &str) -> Result<Option<(u64, u64)>> {
+        let mut client = AuthQueryClient::new(self.channel().await?);
+        let response = match client
+            .account(QueryAccountRequest {
+                address: address.to_string(),
+            })
+            .await
+        {
+            Ok(response) => response.into_inner(),
+            Err(status) if status.code() == tonic::Code::NotFound => return Ok(None),
+            Err(status) => return Err(status_error(status)),
+        };
+
+        let Some(any) = response.account else {
+            return Ok(None);
+        };
+        if any.type_url != BASE_ACCOUNT_TYPE_URL {
+            return Err(RpcError::InvalidResponse(format!(
+                "Unsupported account type '{}'",
+                any.type_url
+            )));
+        }
+        let account = BaseAccount::decode(any.value.as_slice())
+            .map_err(|e| RpcError::Decode(e.to_string()))?;
+        Ok(Some((account.account_number, account.sequence)))
+    }
+
+    /// Run a smart query and parse the contract's JSON reply
+    pub(crate) async fn smart_query(
+        &self,
+        contract: &str,
+        query: &[u8],
+    ) -> Result<serde_json::Value> {
+        let mut client = WasmQueryClient::new(self.channel().await?);
+        let response = client
+            .smart_contract_state(QuerySmartContractStateRequest {
+                address: contract.to_string(),
+                query_data: query.to_vec(),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded => status_error(status),
+                _ => RpcError::ContractQuery(status.message().to_string()),
+            })?
+            .into_inner();
+        Ok(serde_json::from_slice(&response.data)?)
+    }
+
+    /// Read a key of contract storage (empty if unset)
+    pub(crate) async fn raw_query(&self, contract: &str, key: &[u8]) -> Result<Vec<u8>> {
+        let mut client = WasmQueryClient::new(self.channel().await?);
+        let response = client
+            .raw_contract_state(QueryRawContractStateRequest {
+                address: contract.to_string(),
+                query_data: key.to_vec(),
+            })
+            .await
+            .map_err(status_error)?
+            .into_inner();
+        Ok(response.data)
+    }
+
+    /// Broadcast a signed transaction and return its hash
+    pub(crate) async fn broadcast(&self, tx_bytes: Vec<u8>, mode: BroadcastMode) -> Result<String> {
+        let mode = match mode {
+            BroadcastMode::Sync | BroadcastMode::Block => ProtoBroadcastMode::Sync,
+            BroadcastMode::Async => ProtoBroadcastMode::Async,
+        };
+        let mut client = ServiceClient::new(self.channel().await?);
+        let response = client
+            .broadcast_tx(BroadcastTxRequest {
+                tx_bytes,
+                mode: mode as i32,
+            })
+            .await
+            .map_err(status_error)?
+            .into_inner();
+
+        let tx_response = response.tx_response.ok_or_else(|| {
+            RpcError::InvalidResponse("broadcast response is missing tx_response".to_string())
+        })?;
+        if tx_response.code != 0 {
+            return Err(RpcError::TxRejected {
+                code: tx_response.code,
+                raw_log: tx_response.raw_log,
+            });
+        }
+        Ok(tx_response.txhash)
+    }
+}
+
+/// Classify a gRPC status: transport-level codes are network errors
+fn status_error(status: tonic::Status) -> RpcError {
+    match status.code() {
+        tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::Unknown => {
+            RpcError::Network(status.message().to_string())
+        }
+        _ => RpcError::InvalidResponse(status.message().to_string()),
+    }
+}
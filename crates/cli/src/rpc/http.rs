@@ -0,0 +1,123 @@
+//! HTTP client construction
+//!
+//! All LCD and relay traffic goes through one `reqwest::Client`, built here from
+//! the network settings in [`RpcConfig`]. Without explicit timeouts a dead LCD
+//! would stall the mining loop forever, so every request is bounded by default.
+
+use std::time::Duration;
+
+use super::{Result, RpcConfig, RpcError};
+
+/// `Some(duration)` for a positive number of seconds, `None` for 0 (disabled)
+fn seconds(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Build the HTTP client for `config`'s timeout, pooling, proxy and TLS settings
+pub(crate) fn build_client(config: &RpcConfig) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .pool_idle_timeout(seconds(config.pool_idle_timeout_secs))
+        .tcp_keepalive(seconds(config.tcp_keepalive_secs));
+    if let Some(timeout) = seconds(config.connect_timeout_secs) {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = seconds(config.request_timeout_secs) {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(max) = config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+
+    if let Some(proxy) = &config.proxy {
+        // `reqwest::Proxy::all` accepts http://, https://, socks5:// and socks5h:// URLs
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| RpcError::Config(format!("Invalid proxy URL '{}': {}", proxy, e)))?;
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &config.ca_certs {
+        let pem = std::fs::read(path).map_err(|e| {
+            RpcError::Config(format!(
+                "Failed to read CA certificate {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            RpcError::Config(format!(
+                "Invalid PEM certificate in {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if certs.is_empty() {
+            return Err(RpcError::Config(format!(
+                "No certificates found in {}",
+                path.display()
+            )));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if config.custom_roots_only {
+        if config.ca_certs.is_empty() {
+            return Err(RpcError::Config(
+                "custom_roots_only requires at least one CA certificate".to_string(),
+            ));
+        }
+        builder = builder.tls_built_in_root_certs(false);
+    }
+
+    builder
+        .build()
+        .map_err(|e| RpcError::Config(format!("Failed to build HTTP client: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_schemes() {
+        for proxy in [
+            "http://proxy.local:3128",
+            "https://proxy.local:3128",
+            "socks5h://127.0.0.1:9050",
+        ] {
+            let config = RpcConfig {
+                proxy: Some(proxy.to_string()),
+                ..RpcConfig::default()
+            };
+            assert!(build_client(&config).is_ok(), "{}", proxy);
+        }
+    }
+
+    #[test]
+    fn test_zero_disables_timeout() {
+        assert_eq!(seconds(0), None);
+        assert_eq!(seconds(5), Some(Duration::from_secs(5)));
+        let config = RpcConfig {
+            connect_timeout_secs: 0,
+            request_timeout_secs: 0,
+            tcp_keepalive_secs: 0,
+            ..RpcConfig::default()
+        };
+        assert!(build_client(&config).is_ok());
+    }
+
+    #[test]
+    fn test_pinning_requires_certificate() {
+        let config = RpcConfig {
+            custom_roots_only: true,
+            ..RpcConfig::default()
+        };
+        assert!(build_client(&config).is_err());
+
+        let config = RpcConfig {
+            ca_certs: vec!["/nonexistent/ca.pem".into()],
+            ..RpcConfig::default()
+        };
+        assert!(build_client(&config).is_err());
+    }
+}
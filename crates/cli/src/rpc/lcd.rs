@@ -0,0 +1,212 @@
+//! Typed LCD (REST) replies
+//!
+//! Account, broadcast and simulate replies are parsed into these structs
+//! rather than picked apart as `serde_json::Value` with defaults, so an
+//! endpoint that is not a Cosmos LCD (an RPC port, a proxy page, another
+//! service's API) fails at the request that hit it, with the path and the
+//! start of the body in the error, instead of as a zero sequence or an empty
+//! transaction hash further on.
+
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Result, RpcError};
+
+/// Characters of an unexpected body quoted in errors
+pub const BODY_SNIPPET_LIMIT: usize = 200;
+
+/// gRPC `NOT_FOUND`, returned by the LCD for accounts with no transactions yet
+const NOT_FOUND_CODE: u32 = 5;
+
+/// The error body the LCD returns instead of a reply
+#[derive(Debug, Deserialize)]
+pub(crate) struct LcdError {
+    #[serde(default)]
+    pub code: u32,
+    pub message: String,
+}
+
+/// `GET /cosmos/auth/v1beta1/accounts/{address}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct AccountReply {
+    pub account: Account,
+}
+
+/// A `BaseAccount`, or an account type that wraps one (module and vesting
+/// accounts nest it as `base_account` / `base_vesting_account.base_account`)
+#[derive(Debug, Deserialize)]
+pub(crate) struct Account {
+    #[serde(default, deserialize_with = "opt_u64_string")]
+    account_number: Option<u64>,
+    #[serde(default, deserialize_with = "opt_u64_string")]
+    sequence: Option<u64>,
+    #[serde(default)]
+    base_account: Option<Box<Account>>,
+    #[serde(default)]
+    base_vesting_account: Option<Box<Account>>,
+}
+
+impl Account {
+    /// `(account_number, sequence)` of the innermost base account
+    pub fn numbers(&self) -> Option<(u64, u64)> {
+        if let (Some(number), Some(sequence)) = (self.account_number, self.sequence) {
+            return Some((number, sequence));
+        }
+        self.base_account
+            .as_deref()
+            .or(self.base_vesting_account.as_deref())
+            .and_then(Account::numbers)
+    }
+}
+
+/// `GET /cosmos/bank/v1beta1/balances/{address}/by_denom`
+#[derive(Debug, Deserialize)]
+pub(crate) struct BalanceReply {
+    pub balance: Coin,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Coin {
+    pub amount: String,
+}
+
+/// `GET /cosmos/feegrant/v1beta1/allowance/{granter}/{grantee}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct AllowanceReply {
+    pub allowance: Value,
+}
+
+/// `POST /cosmos/tx/v1beta1/txs`
+#[derive(Debug, Deserialize)]
+pub(crate) struct BroadcastReply {
+    pub tx_response: BroadcastTxResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BroadcastTxResponse {
+    pub txhash: String,
+    #[serde(default)]
+    pub code: u32,
+    #[serde(default)]
+    pub raw_log: String,
+}
+
+/// `POST /cosmos/tx/v1beta1/simulate`
+#[derive(Debug, Deserialize)]
+pub(crate) struct SimulateReply {
+    pub gas_info: GasInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GasInfo {
+    #[serde(deserialize_with = "u64_string")]
+    pub gas_used: u64,
+}
+
+/// A reply in the expected shape, or the LCD's error body in its place
+#[derive(Debug)]
+pub(crate) enum Reply<T> {
+    Ok(T),
+    Error(LcdError),
+}
+
+/// Parse the reply to `path`, telling an LCD error body from a malformed reply
+pub(crate) fn parse<T: DeserializeOwned>(path: &str, body: Value) -> Result<Reply<T>> {
+    match T::deserialize(&body) {
+        Ok(reply) => Ok(Reply::Ok(reply)),
+        Err(e) => match LcdError::deserialize(&body) {
+            Ok(error) => Ok(Reply::Error(error)),
+            Err(_) => Err(unexpected(path, &e, &body)),
+        },
+    }
+}
+
+/// An [`RpcError::InvalidResponse`] quoting the start of `body`
+pub(crate) fn unexpected(path: &str, error: &dyn std::fmt::Display, body: &Value) -> RpcError {
+    RpcError::InvalidResponse(format!(
+        "unexpected reply from {} ({}); is the LCD URL right? Body: {}",
+        path,
+        error,
+        snippet(body)
+    ))
+}
+
+/// `body` as compact JSON, cut to [`BODY_SNIPPET_LIMIT`] characters
+pub fn snippet(body: &Value) -> String {
+    let text = body.to_string();
+    match text.char_indices().nth(BODY_SNIPPET_LIMIT) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+impl LcdError {
+    /// Whether the LCD reports the queried object as missing
+    pub fn is_not_found(&self) -> bool {
+        self.code == NOT_FOUND_CODE || self.message.contains("not found")
+    }
+}
+
+/// A `u64` the LCD encodes as a string (`"42"`); plain numbers are accepted
+fn u64_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse().map_err(de::Error::custom),
+        Value::Number(n) => n
+            .as_u64()
+            .ok_or_else(|| de::Error::custom(format!("{} is not a u64", n))),
+        other => Err(de::Error::custom(format!(
+            "expected a number string, got {}",
+            other
+        ))),
+    }
+}
+
+fn opt_u64_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    u64_string(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_account_shapes() {
+        let base = json!({"account": {
+            "@type": "/cosmos.auth.v1beta1.BaseAccount",
+            "account_number": "7",
+            "sequence": "3",
+        }});
+        let vesting = json!({"account": {
+            "@type": "/cosmos.vesting.v1beta1.ContinuousVestingAccount",
+            "base_vesting_account": {"base_account": {"account_number": "8", "sequence": "1"}},
+        }});
+        for (body, numbers) in [(base, (7, 3)), (vesting, (8, 1))] {
+            let Reply::Ok(reply) = parse::<AccountReply>("/accounts", body).unwrap() else {
+                panic!("expected an account");
+            };
+            assert_eq!(reply.account.numbers(), Some(numbers));
+        }
+
+        let missing = json!({"code": 5, "message": "rpc error: account bostrom1x not found"});
+        let Reply::Error(error) = parse::<AccountReply>("/accounts", missing).unwrap() else {
+            panic!("expected an LCD error");
+        };
+        assert!(error.is_not_found());
+    }
+
+    #[test]
+    fn test_malformed_reply_quotes_body() {
+        let body = json!({"jsonrpc": "2.0", "result": {"node_info": "x".repeat(500)}});
+        let err = parse::<BroadcastReply>("/cosmos/tx/v1beta1/txs", body).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("/cosmos/tx/v1beta1/txs"));
+        assert!(message.contains("missing field `tx_response`"));
+        assert!(message.contains(r#"{"jsonrpc":"2.0""#));
+        assert!(message.ends_with("..."));
+
+        let body = json!({"gas_info": {"gas_used": "lots"}});
+        assert!(parse::<SimulateReply>("/simulate", body).is_err());
+    }
+}
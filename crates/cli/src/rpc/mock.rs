@@ -0,0 +1,232 @@
+//! In-memory [`ChainClient`] for tests
+//!
+//! Answers queries from fixed state, records every submission and broadcast,
+//! and fails calls with queued errors. Unlike the devnet it checks nothing:
+//! every proof is accepted unless an error was queued.
+
+use async_trait::async_trait;
+use cosmrs::crypto::secp256k1::SigningKey;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+use super::{
+    ChainClient, ConfigResponse, ProofSubmission, Result, RpcError, SeedInfo, SubmitResult,
+};
+
+#[derive(Debug)]
+struct MockState {
+    seed: SeedInfo,
+    difficulty: u32,
+    min_profitable_difficulty: u32,
+    config: ConfigResponse,
+    reward: Option<String>,
+    failures: VecDeque<RpcError>,
+    submissions: Vec<ProofSubmission>,
+    broadcasts: Vec<Vec<u8>>,
+}
+
+/// A [`ChainClient`] that never touches the network
+#[derive(Debug)]
+pub struct MockChainClient {
+    state: Mutex<MockState>,
+}
+
+impl MockChainClient {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                seed: SeedInfo::new([0u8; 32], 600),
+                difficulty: 8,
+                min_profitable_difficulty: 0,
+                config: ConfigResponse {
+                    token_denom: super::LI_DENOM.to_string(),
+                    difficulty: 8,
+                    base_reward: "1000000".to_string(),
+                    max_proof_age: 3600,
+                    period_duration: 600,
+                    target_proofs_per_window: None,
+                    max_worker_tag_len: None,
+                    admin: String::new(),
+                    paused: false,
+                },
+                reward: Some("1000000".to_string()),
+                failures: VecDeque::new(),
+                submissions: Vec::new(),
+                broadcasts: Vec::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn with_seed(self, seed: SeedInfo) -> Self {
+        self.state().seed = seed;
+        self
+    }
+
+    pub fn with_difficulty(self, difficulty: u32) -> Self {
+        {
+            let mut state = self.state();
+            state.difficulty = difficulty;
+            state.config.difficulty = difficulty;
+        }
+        self
+    }
+
+    pub fn with_min_profitable_difficulty(self, difficulty: u32) -> Self {
+        self.state().min_profitable_difficulty = difficulty;
+        self
+    }
+
+    pub fn with_config(self, config: ConfigResponse) -> Self {
+        self.state().config = config;
+        self
+    }
+
+    /// Reward reported for each accepted proof (`None` as in `sync` mode)
+    pub fn with_reward(self, reward: Option<String>) -> Self {
+        self.state().reward = reward;
+        self
+    }
+
+    /// Fail the next call with `error` (errors queue up in order)
+    pub fn fail_next(&self, error: RpcError) {
+        self.state().failures.push_back(error);
+    }
+
+    /// Change the seed, as a rotation on chain would
+    pub fn set_seed(&self, seed: SeedInfo) {
+        self.state().seed = seed;
+    }
+
+    /// Proofs accepted by [`ChainClient::submit_proof`] so far
+    pub fn submissions(&self) -> Vec<ProofSubmission> {
+        self.state().submissions.clone()
+    }
+
+    /// Transactions accepted by [`ChainClient::broadcast`] so far
+    pub fn broadcasts(&self) -> Vec<Vec<u8>> {
+        self.state().broadcasts.clone()
+    }
+
+    fn take_failure(&self) -> Result<MutexGuard<'_, MockState>> {
+        let mut state = self.state();
+        match state.failures.pop_front() {
+            Some(error) => Err(error),
+            None => Ok(state),
+        }
+    }
+}
+
+impl Default for MockChainClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn tx_hash(bytes: &[u8]) -> String {
+    hex::encode_upper(Sha256::digest(bytes))
+}
+
+#[async_trait]
+impl ChainClient for MockChainClient {
+    async fn get_seed(&self) -> Result<SeedInfo> {
+        Ok(self.take_failure()?.seed)
+    }
+
+    async fn get_difficulty(&self) -> Result<u32> {
+        Ok(self.take_failure()?.difficulty)
+    }
+
+    async fn get_min_profitable_difficulty(&self) -> Result<u32> {
+        Ok(self.take_failure()?.min_profitable_difficulty)
+    }
+
+    async fn get_config(&self) -> Result<ConfigResponse> {
+        Ok(self.take_failure()?.config.clone())
+    }
+
+    async fn submit_proof(
+        &self,
+        proof: ProofSubmission,
+        _signing_key: &SigningKey,
+    ) -> Result<SubmitResult> {
+        let mut state = self.take_failure()?;
+        let tx_hash = tx_hash(format!("{}:{}", proof.hash, proof.nonce).as_bytes());
+        state.submissions.push(proof);
+        Ok(SubmitResult {
+            tx_hash,
+            accepted: true,
+            reward: state.reward.clone(),
+            error: None,
+            height: None,
+            reward_denom: None,
+            gas_used: None,
+        })
+    }
+
+    async fn broadcast(&self, tx_bytes: Vec<u8>) -> Result<String> {
+        let mut state = self.take_failure()?;
+        let tx_hash = tx_hash(&tx_bytes);
+        state.broadcasts.push(tx_bytes);
+        Ok(tx_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    fn proof(nonce: u64) -> ProofSubmission {
+        ProofSubmission {
+            hash: "00".repeat(32),
+            nonce,
+            timestamp: 1_700_000_000,
+            miner_address: "bostrom1miner".to_string(),
+            worker_tag: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_records_submissions() {
+        let client = MockChainClient::new()
+            .with_seed(SeedInfo::new([3u8; 32], 600))
+            .with_difficulty(12);
+        assert_eq!(client.get_seed().await.unwrap().seed, [3u8; 32]);
+        assert_eq!(client.get_difficulty().await.unwrap(), 12);
+        assert_eq!(client.get_config().await.unwrap().difficulty, 12);
+
+        let result = client.submit_proof(proof(1), &signing_key()).await.unwrap();
+        assert!(result.accepted);
+        assert_eq!(result.reward.as_deref(), Some("1000000"));
+        assert_eq!(client.submissions().len(), 1);
+        assert_eq!(client.submissions()[0].nonce, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_fails_queued_calls_in_order() {
+        let client = MockChainClient::new();
+        client.fail_next(RpcError::Network("connection refused".to_string()));
+        client.fail_next(RpcError::TxRejected {
+            code: 32,
+            raw_log: "account sequence mismatch, expected 2, got 1".to_string(),
+        });
+
+        let err = client.get_seed().await.unwrap_err();
+        assert!(matches!(err, RpcError::Network(_)));
+        let err = client.broadcast(vec![1, 2, 3]).await.unwrap_err();
+        assert!(err.is_sequence_mismatch());
+        assert!(client.broadcasts().is_empty());
+
+        let hash = client.broadcast(vec![1, 2, 3]).await.unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(client.broadcasts(), vec![vec![1, 2, 3]]);
+    }
+}
@@ -1,9 +1,117 @@
 //! RPC client for Bostrom blockchain interaction
 //!
 //! Handles submitting proofs and querying chain state.
-
-use anyhow::Result;
+//!
+//! # Example
+//!
+//! Mine one proof and submit it:
+//!
+//! ```rust,no_run
+//! use uhash::rpc::{ProofSubmission, RpcClient};
+//! use uhash::wallet::Wallet;
+//! use uhash::{meets_difficulty, UniversalHash};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let wallet = Wallet::from_phrase("word1 word2 ... word24")?;
+//! let signing_key =
+//!     cosmrs::crypto::secp256k1::SigningKey::from_slice(&wallet.signing_key()?.to_bytes())
+//!         .map_err(|e| anyhow::anyhow!("invalid signing key: {}", e))?;
+//! let client = RpcClient::new();
+//! let seed = client.get_seed().await?.seed;
+//! let difficulty = client.get_difficulty().await?;
+//!
+//! // Proof input: seed || address || timestamp || nonce
+//! let address = wallet.address_str();
+//! let timestamp = std::time::SystemTime::now()
+//!     .duration_since(std::time::UNIX_EPOCH)?
+//!     .as_secs();
+//! let mut hasher = UniversalHash::new();
+//! let (hash, nonce) = (0u64..)
+//!     .map(|nonce| {
+//!         let mut input = seed.to_vec();
+//!         input.extend_from_slice(address.as_bytes());
+//!         input.extend_from_slice(&timestamp.to_le_bytes());
+//!         input.extend_from_slice(&nonce.to_le_bytes());
+//!         (hasher.hash(&input), nonce)
+//!     })
+//!     .find(|(hash, _)| meets_difficulty(hash, difficulty))
+//!     .unwrap();
+//!
+//! let proof = ProofSubmission {
+//!     hash: hex::encode(hash),
+//!     nonce,
+//!     timestamp,
+//!     miner_address: address,
+//!     worker_tag: None,
+//! };
+//! let result = client.submit_proof(proof, &signing_key).await?;
+//! println!("{} accepted: {}", result.tx_hash, result.accepted);
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::chain::ChainProfile;
+use cache::QueryCache;
+use dedup::SubmittedProofs;
+use endpoints::{EndpointPool, HEALTH_PATH};
+
+mod backoff;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
+mod client;
+mod debug;
+mod dedup;
+mod endpoints;
+mod error;
+mod events;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod http;
+mod lcd;
+mod mock;
+mod preflight;
+mod schema;
+mod sequence;
+#[cfg(feature = "websocket")]
+mod subscribe;
+mod tracker;
+mod tx;
+
+pub(crate) use backoff::jitter;
+use backoff::RetryBudget;
+pub use backoff::{BackoffWarning, HttpStatusError, RATE_LIMIT_RETRIES, RETRY_BUDGET_BURST};
+pub use cache::DEFAULT_CACHE_TTL_SECS;
+pub use client::ChainClient;
+pub use debug::{BODY_LOG_LIMIT, LOG_TARGET};
+pub use dedup::SUBMITTED_PROOFS_CAPACITY;
+pub use endpoints::{lcd_url_for_rpc, validate_endpoint, EndpointStatus};
+pub use error::{Result, RpcError};
+pub use events::{
+    batch_reward, proof_events, proof_reward, proof_reward_denom, AcceptedProof, EventAttribute,
+    ProofEvent, TxEvent,
+};
+pub use lcd::BODY_SNIPPET_LIMIT;
+pub use mock::MockChainClient;
+pub use preflight::{preflight, CheckStatus, Preflight, PreflightCheck};
+pub use schema::ContractSchema;
+pub use sequence::SequenceManager;
+#[cfg(feature = "websocket")]
+pub use subscribe::{websocket_url, ProofSubscription};
+pub use tracker::{TxStatus, TxTracker, TxUpdate, DEFAULT_FINALITY_DEPTH, TRACK_POLL_INTERVAL};
+pub use tx::{
+    batch_gas_limit, fee_for_gas, parse_gas, parse_gas_price, sign_batch_tx_with_gas,
+    sign_proof_tx, sign_proof_tx_with_gas, signer_address, SignedTx, DEFAULT_GAS_ADJUSTMENT,
+    FEE_DENOM, MAX_MEMO_LEN, SEQUENCE_MISMATCH_CODE, SUBMIT_PROOF_GAS,
+};
+
+/// How many times a proof is re-signed after an account sequence mismatch
+pub const MAX_SEQUENCE_RETRIES: u32 = 3;
 
 /// Default RPC endpoint for Bostrom
 pub const DEFAULT_RPC: &str = "https://rpc.bostrom.cybernode.ai";
@@ -19,6 +127,104 @@ pub const CONTRACT_ADDRESS: &str =
 pub const LI_DENOM: &str =
     "factory/bostrom1qwys5wj3r4lry7dl74ukn5unhdpa6t397h097q36dqvrp5qgvjxqverdlf/li";
 
+/// Transactions fetched per transaction search request
+pub const TX_SEARCH_PAGE: usize = 100;
+
+/// Header selecting the block height an LCD query runs against
+pub const BLOCK_HEIGHT_HEADER: &str = "x-cosmos-block-height";
+
+/// How long to wait between confirmation polls
+const CONFIRM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Default time to wait for a transaction to be committed in `block` mode
+pub const DEFAULT_CONFIRM_TIMEOUT_SECS: u64 = 60;
+
+/// Default time allowed to establish a connection
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Default time allowed for a whole request, from connecting to reading the body
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Default time an idle pooled connection is kept open
+pub const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+/// Default interval of TCP keep-alive probes on open connections
+pub const DEFAULT_TCP_KEEPALIVE_SECS: u64 = 60;
+
+/// How to broadcast transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BroadcastMode {
+    /// Return after CheckTx (mempool admission)
+    #[default]
+    Sync,
+    /// Return immediately without waiting for CheckTx
+    Async,
+    /// Broadcast in sync mode, then poll until the transaction is committed
+    Block,
+}
+
+impl BroadcastMode {
+    fn as_proto(&self) -> &'static str {
+        match self {
+            BroadcastMode::Sync | BroadcastMode::Block => "BROADCAST_MODE_SYNC",
+            BroadcastMode::Async => "BROADCAST_MODE_ASYNC",
+        }
+    }
+}
+
+impl std::str::FromStr for BroadcastMode {
+    type Err = RpcError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sync" => Ok(BroadcastMode::Sync),
+            "async" => Ok(BroadcastMode::Async),
+            "block" | "block-equivalent" => Ok(BroadcastMode::Block),
+            _ => Err(RpcError::Config(format!(
+                "invalid broadcast mode '{}': expected sync, async or block-equivalent",
+                s
+            ))),
+        }
+    }
+}
+
+/// Protocol used for account queries, contract queries and broadcast
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// REST via the LCD endpoints
+    #[default]
+    Lcd,
+    /// gRPC via `grpc_url` (requires the `grpc` feature)
+    Grpc,
+}
+
+impl Transport {
+    /// Whether this build can use the transport
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Transport::Lcd => true,
+            Transport::Grpc => cfg!(feature = "grpc"),
+        }
+    }
+}
+
+impl std::str::FromStr for Transport {
+    type Err = RpcError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "lcd" | "rest" => Ok(Transport::Lcd),
+            "grpc" => Ok(Transport::Grpc),
+            _ => Err(RpcError::Config(format!(
+                "invalid transport '{}': expected lcd or grpc",
+                s
+            ))),
+        }
+    }
+}
+
 /// RPC client configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RpcConfig {
@@ -26,24 +232,186 @@ pub struct RpcConfig {
     pub rpc_url: String,
     /// LCD/REST endpoint URL
     pub lcd_url: String,
+    /// Additional LCD endpoints, tried in order when the active one fails
+    #[serde(default)]
+    pub fallback_lcd_urls: Vec<String>,
+    /// Transport for account queries, contract queries and broadcast
+    #[serde(default)]
+    pub transport: Transport,
+    /// gRPC endpoint URL (used with [`Transport::Grpc`])
+    #[serde(default)]
+    pub grpc_url: String,
+    /// Proxy for all HTTP traffic (`http://`, `https://`, `socks5://` or `socks5h://`)
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// PEM files with additional trusted root certificates
+    #[serde(default)]
+    pub ca_certs: Vec<PathBuf>,
+    /// Trust only `ca_certs`, not the system roots (certificate pinning)
+    #[serde(default)]
+    pub custom_roots_only: bool,
+    /// Seconds allowed to establish a connection (0: no limit)
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Seconds allowed for a whole request (0: no limit)
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Seconds an idle pooled connection is kept open (0: never closed for idleness)
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    /// Maximum idle connections kept per host (`None`: no limit)
+    #[serde(default)]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Seconds between TCP keep-alive probes (0: disabled)
+    #[serde(default = "default_tcp_keepalive_secs")]
+    pub tcp_keepalive_secs: u64,
+    /// Seconds seed, difficulty and config query results are reused (0: no caching)
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
     /// Chain ID
     pub chain_id: String,
     /// Contract address for UniversalHash verifier
     pub contract_address: String,
+    /// Response shapes of the contract (`None`: detected from its config)
+    #[serde(default)]
+    pub contract_schema: Option<ContractSchema>,
+    /// Denom transaction fees are paid in
+    #[serde(default = "default_fee_denom")]
+    pub fee_denom: String,
+    /// Relay service for an account's first proof (empty: none)
+    #[serde(default)]
+    pub relay_url: String,
+    /// Faucet that funds new accounts (empty: none), see [`RpcClient::request_faucet`]
+    #[serde(default)]
+    pub faucet_url: String,
+    /// Explorer transaction URL with a `{hash}` placeholder (empty: none)
+    #[serde(default)]
+    pub explorer_tx_url: String,
     /// Fee amount in uboot (default: 0 for zero-fee Bostrom transactions)
     pub fee_amount: u128,
+    /// Account whose `x/feegrant` allowance pays the fee instead of the signer
+    #[serde(default)]
+    pub fee_granter: Option<String>,
+    /// Account that pays the fee; transactions are signed by one key, so
+    /// this must be the signer (other accounts pay through `fee_granter`)
+    #[serde(default)]
+    pub fee_payer: Option<String>,
+    /// Memo attached to every transaction (e.g. a worker name)
+    #[serde(default)]
+    pub memo: String,
+    /// Gas limit per transaction; `None` simulates each transaction first
+    #[serde(default = "default_gas_limit")]
+    pub gas_limit: Option<u64>,
+    /// Multiplier applied to simulated gas usage
+    #[serde(default = "default_gas_adjustment")]
+    pub gas_adjustment: f64,
+    /// Gas price in boot; when set the fee is `gas_limit * gas_price` instead of `fee_amount`
+    #[serde(default)]
+    pub gas_price: Option<f64>,
+    /// Broadcast mode for submitted transactions
+    #[serde(default)]
+    pub broadcast_mode: BroadcastMode,
+    /// Seconds to wait for a transaction to be committed in `block` mode, and
+    /// before a `sync`/`async` one not in a block counts as evicted
+    #[serde(default = "default_confirm_timeout_secs")]
+    pub confirm_timeout_secs: u64,
+    /// Blocks on top of a proof transaction's block before it counts as final
+    #[serde(default)]
+    pub finality_depth: u64,
+}
+
+fn default_confirm_timeout_secs() -> u64 {
+    DEFAULT_CONFIRM_TIMEOUT_SECS
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT_SECS
+}
+
+fn default_request_timeout_secs() -> u64 {
+    DEFAULT_REQUEST_TIMEOUT_SECS
+}
+
+fn default_pool_idle_timeout_secs() -> u64 {
+    DEFAULT_POOL_IDLE_TIMEOUT_SECS
+}
+
+fn default_tcp_keepalive_secs() -> u64 {
+    DEFAULT_TCP_KEEPALIVE_SECS
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    DEFAULT_CACHE_TTL_SECS
+}
+
+fn default_fee_denom() -> String {
+    FEE_DENOM.to_string()
+}
+
+fn default_gas_limit() -> Option<u64> {
+    Some(SUBMIT_PROOF_GAS)
+}
+
+fn default_gas_adjustment() -> f64 {
+    DEFAULT_GAS_ADJUSTMENT
 }
 
 impl Default for RpcConfig {
     fn default() -> Self {
+        Self::from_profile(&ChainProfile::bostrom())
+    }
+}
+
+impl RpcConfig {
+    /// Default client settings for a chain
+    pub fn from_profile(profile: &ChainProfile) -> Self {
         Self {
-            rpc_url: DEFAULT_RPC.to_string(),
-            lcd_url: DEFAULT_LCD.to_string(),
-            chain_id: "bostrom".to_string(),
-            contract_address: CONTRACT_ADDRESS.to_string(),
+            rpc_url: profile.rpc_url.clone(),
+            lcd_url: profile.lcd_url.clone(),
+            fallback_lcd_urls: Vec::new(),
+            transport: Transport::default(),
+            grpc_url: String::new(),
+            proxy: None,
+            ca_certs: Vec::new(),
+            custom_roots_only: false,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            pool_idle_timeout_secs: DEFAULT_POOL_IDLE_TIMEOUT_SECS,
+            pool_max_idle_per_host: None,
+            tcp_keepalive_secs: DEFAULT_TCP_KEEPALIVE_SECS,
+            cache_ttl_secs: DEFAULT_CACHE_TTL_SECS,
+            chain_id: profile.chain_id.clone(),
+            contract_address: profile.contract_address.clone(),
+            contract_schema: None,
+            fee_denom: profile.fee_denom.clone(),
+            relay_url: profile.relay_url.clone(),
+            faucet_url: profile.faucet_url.clone(),
+            explorer_tx_url: profile.explorer_tx_url.clone(),
             fee_amount: 0,
+            fee_granter: None,
+            fee_payer: None,
+            memo: String::new(),
+            gas_limit: default_gas_limit(),
+            gas_adjustment: DEFAULT_GAS_ADJUSTMENT,
+            gas_price: None,
+            broadcast_mode: BroadcastMode::default(),
+            confirm_timeout_secs: DEFAULT_CONFIRM_TIMEOUT_SECS,
+            finality_depth: DEFAULT_FINALITY_DEPTH,
         }
     }
+
+    /// Explorer link for a transaction, if an explorer is configured
+    pub fn explorer_tx_link(&self, tx_hash: &str) -> Option<String> {
+        (!self.explorer_tx_url.is_empty()).then(|| self.explorer_tx_url.replace("{hash}", tx_hash))
+    }
+
+    /// Primary and fallback LCD endpoints, in priority order
+    pub fn lcd_urls(&self) -> Vec<String> {
+        std::iter::once(&self.lcd_url)
+            .chain(&self.fallback_lcd_urls)
+            .map(|url| url.trim_end_matches('/').to_string())
+            .collect()
+    }
 }
 
 /// Proof submission message
@@ -57,6 +425,25 @@ pub struct ProofSubmission {
     pub timestamp: u64,
     /// Miner's address
     pub miner_address: String,
+    /// Hex bytes mined after the miner address, naming the machine that
+    /// found the proof (only if the contract accepts worker tags)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_tag: Option<String>,
+}
+
+impl ProofSubmission {
+    /// Attach the worker tag the proof was mined with (none if empty)
+    pub fn with_worker_tag(mut self, tag: &[u8]) -> Self {
+        self.worker_tag = (!tag.is_empty()).then(|| hex::encode(tag));
+        self
+    }
+
+    /// The worker tag's bytes, empty without one
+    pub fn worker_tag_bytes(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        self.worker_tag
+            .as_deref()
+            .map_or(Ok(Vec::new()), hex::decode)
+    }
 }
 
 /// Result of submitting a proof
@@ -70,6 +457,64 @@ pub struct SubmitResult {
     pub reward: Option<String>,
     /// Error message if rejected
     pub error: Option<String>,
+    /// Block height the transaction was committed at (`block` mode only)
+    #[serde(default)]
+    pub height: Option<u64>,
+    /// Denom of the reward (`block` mode only)
+    #[serde(default)]
+    pub reward_denom: Option<String>,
+    /// Gas consumed by the transaction (`block` mode only)
+    #[serde(default)]
+    pub gas_used: Option<u64>,
+}
+
+/// Outcome of simulating a proof transaction instead of broadcasting it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofSimulation {
+    /// Whether the contract would accept the proof
+    pub accepted: bool,
+    /// Gas the simulated transaction used
+    pub gas_used: Option<u64>,
+    /// Gas limit a real submission would set (fixed `gas_limit`, or the
+    /// simulated usage times `gas_adjustment`)
+    pub gas_limit: Option<u64>,
+    /// Why the simulation failed
+    pub error: Option<String>,
+}
+
+/// The `tx_responses` of a transaction search reply
+fn tx_responses(json: &serde_json::Value) -> Result<&Vec<serde_json::Value>> {
+    json["tx_responses"].as_array().ok_or_else(|| {
+        let message = json["message"].as_str().unwrap_or("Unknown error");
+        RpcError::InvalidResponse(format!("failed to search transactions: {}", message))
+    })
+}
+
+/// Execution result of a committed transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxInfo {
+    /// Transaction hash
+    pub tx_hash: String,
+    /// Block height
+    pub height: u64,
+    /// Result code (0 = success)
+    pub code: u32,
+    /// Raw execution log (error message when `code != 0`)
+    pub raw_log: String,
+    /// Gas limit of the transaction
+    pub gas_wanted: u64,
+    /// Gas consumed during execution
+    pub gas_used: u64,
+    /// Events emitted during execution
+    #[serde(default)]
+    pub events: Vec<TxEvent>,
+}
+
+impl TxInfo {
+    /// Whether the transaction executed successfully
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
 }
 
 /// Contract execute message for submitting proofs
@@ -82,6 +527,20 @@ pub enum ExecuteMsg {
         timestamp: u64,
         #[serde(skip_serializing_if = "Option::is_none")]
         miner_address: Option<String>,
+        /// Hex worker tag appended to the miner address in the proof input
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        worker_tag: Option<String>,
+    },
+    /// Withdraw rewards the contract holds for the sender (contract v2)
+    #[cfg(feature = "contract-v2")]
+    ClaimRewards {},
+    /// Register a worker tag under the sender's address (contract v2)
+    #[cfg(feature = "contract-v2")]
+    RegisterWorker {
+        /// Hex worker tag, as sent with `submit_proof`
+        worker_tag: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
     },
 }
 
@@ -92,10 +551,22 @@ pub enum QueryMsg {
     Config {},
     Seed {},
     Difficulty {},
+    MinerStats {
+        address: String,
+    },
+    /// Miners ranked by accepted proofs (contract v2)
+    #[cfg(feature = "contract-v2")]
+    Leaderboard {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
+        /// Address to continue after, from the previous page
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start_after: Option<String>,
+    },
 }
 
-/// Config response from contract
-/// Supports both production (epoch_duration) and test (period_duration) field names.
+/// Config response from contract, in the newest [`ContractSchema`]
+/// (`epoch_duration` is still accepted for replies parsed directly)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigResponse {
     pub token_denom: String,
@@ -106,6 +577,10 @@ pub struct ConfigResponse {
     pub period_duration: u64,
     #[serde(default)]
     pub target_proofs_per_window: Option<u64>,
+    /// Longest worker tag the contract hashes after the miner address, in
+    /// bytes (`None`: the contract accepts no worker tags)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_worker_tag_len: Option<u32>,
     pub admin: String,
     pub paused: bool,
 }
@@ -115,44 +590,162 @@ pub struct ConfigResponse {
 pub struct SeedResponse {
     pub seed: String,
     pub seed_interval: u64,
+    /// Unix time the seed became active (not reported by older contracts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+}
+
+/// The current mining seed and when it rotates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedInfo {
+    pub seed: [u8; 32],
+    /// Seconds between seed rotations
+    pub seed_interval: u64,
+    /// Unix time the seed became active, if the contract reports it
+    pub created_at: Option<u64>,
+    /// Unix time the seed is due to rotate, if known
+    pub expires_at: Option<u64>,
+}
+
+impl SeedInfo {
+    /// A seed with no known rotation time
+    pub fn new(seed: [u8; 32], seed_interval: u64) -> Self {
+        Self {
+            seed,
+            seed_interval,
+            created_at: None,
+            expires_at: None,
+        }
+    }
+
+    /// Time left until rotation at unix time `now` (zero once overdue)
+    pub fn expires_in(&self, now: u64) -> Option<std::time::Duration> {
+        self.expires_at
+            .map(|at| std::time::Duration::from_secs(at.saturating_sub(now)))
+    }
 }
 
 /// Difficulty response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DifficultyResponse {
     pub current: u32,
+    #[serde(default)]
     pub min_profitable: u32,
 }
 
+/// Miner stats response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinerStatsResponse {
+    pub address: String,
+    pub proofs_submitted: u64,
+    /// Unix time of the last accepted proof
+    #[serde(default)]
+    pub last_proof_time: Option<u64>,
+    /// Sum of rewards paid, in the smallest LI unit
+    pub total_rewards: String,
+}
+
+/// Leaderboard response (contract v2)
+#[cfg(feature = "contract-v2")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardResponse {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// One miner's standing in [`LeaderboardResponse`]
+#[cfg(feature = "contract-v2")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub address: String,
+    pub proofs_submitted: u64,
+    /// Sum of rewards paid, in the smallest LI unit
+    pub total_rewards: String,
+}
+
+/// A committed `submit_proof` execution with the proof as submitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmittedProof {
+    pub tx_hash: String,
+    pub height: u64,
+    /// Unix time of the block
+    pub time: u64,
+    /// Address the proof is for (`miner_address`, else the sender)
+    pub miner: String,
+    /// Hex hash
+    pub hash: String,
+    pub nonce: u64,
+    /// Proof timestamp (Unix seconds)
+    pub timestamp: u64,
+    /// Difficulty the contract reports accepting it at, if it does
+    pub difficulty: Option<u32>,
+    /// Hex worker tag the proof was mined with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_tag: Option<String>,
+}
+
+/// One page of [`RpcClient::proof_submissions`]
+#[derive(Debug, Clone, Default)]
+pub struct ProofPage {
+    /// Proofs of successful transactions, oldest first
+    pub proofs: Vec<SubmittedProof>,
+    /// Whether a further page may hold more
+    pub more: bool,
+}
+
 /// RPC client for interacting with Bostrom
 pub struct RpcClient {
     config: RpcConfig,
     http_client: reqwest::Client,
-    /// Cached account number (set after first query)
-    cached_account_number: std::cell::Cell<Option<u64>>,
-    /// Local sequence counter (incremented after each successful broadcast)
-    local_sequence: std::cell::Cell<Option<u64>>,
+    /// LCD endpoints with the one currently in use
+    endpoints: EndpointPool,
+    /// Account number and next sequence (advanced after each successful broadcast)
+    sequence: SequenceManager,
+    /// gRPC connection, when the configured transport is gRPC
+    #[cfg(feature = "grpc")]
+    grpc: Option<grpc::GrpcClient>,
+    /// Called before each backoff retry
+    warning_handler: Option<WarningHandler>,
+    /// Backoff retries left per endpoint, shared by all requests
+    retry_budget: RetryBudget,
+    /// Contract schema detected from the first config reply
+    schema: std::sync::OnceLock<ContractSchema>,
+    /// Recent contract query responses
+    cache: QueryCache,
+    /// Recently submitted proofs, never sent twice
+    submitted: SubmittedProofs,
 }
 
+type WarningHandler = Box<dyn Fn(&BackoffWarning) + Send + Sync>;
+
 impl RpcClient {
     /// Create a new RPC client with default configuration
     pub fn new() -> Self {
-        Self {
-            config: RpcConfig::default(),
-            http_client: reqwest::Client::new(),
-            cached_account_number: std::cell::Cell::new(None),
-            local_sequence: std::cell::Cell::new(None),
-        }
+        // No proxy and no certificates to read, so nothing can fail
+        Self::try_with_config(RpcConfig::default()).expect("default RPC client configuration")
     }
 
-    /// Create a new RPC client with custom configuration
-    pub fn with_config(config: RpcConfig) -> Self {
-        Self {
+    /// Create a new RPC client, failing on an invalid proxy URL or unreadable certificate
+    pub fn try_with_config(config: RpcConfig) -> Result<Self> {
+        Ok(Self {
+            http_client: http::build_client(&config)?,
+            endpoints: EndpointPool::new(config.lcd_urls()),
+            #[cfg(feature = "grpc")]
+            grpc: (config.transport == Transport::Grpc)
+                .then(|| grpc::GrpcClient::new(&config.grpc_url)),
+            cache: QueryCache::new(std::time::Duration::from_secs(config.cache_ttl_secs)),
+            submitted: SubmittedProofs::new(SUBMITTED_PROOFS_CAPACITY),
             config,
-            http_client: reqwest::Client::new(),
-            cached_account_number: std::cell::Cell::new(None),
-            local_sequence: std::cell::Cell::new(None),
-        }
+            sequence: SequenceManager::new(),
+            warning_handler: None,
+            retry_budget: RetryBudget::default(),
+            schema: std::sync::OnceLock::new(),
+        })
+    }
+
+    /// Report retries of rate-limited or failing requests to `handler`
+    pub fn on_warning(mut self, handler: impl Fn(&BackoffWarning) + Send + Sync + 'static) -> Self {
+        self.warning_handler = Some(Box::new(handler));
+        self
     }
 
     /// Get the current configuration
@@ -160,240 +753,940 @@ impl RpcClient {
         &self.config
     }
 
-    /// Query account info (sequence and account number)
-    pub async fn get_account_info(&self, address: &str) -> Result<(u64, u64)> {
-        let url = format!(
-            "{}/cosmos/auth/v1beta1/accounts/{}",
-            self.config.lcd_url, address
+    /// Local account sequence tracker used by [`RpcClient::submit_proof`]
+    pub fn sequence(&self) -> &SequenceManager {
+        &self.sequence
+    }
+
+    /// LCD endpoint requests are currently sent to
+    pub fn active_endpoint(&self) -> String {
+        self.endpoints.active().1
+    }
+
+    /// LCD endpoints requests fail over between, in order
+    pub fn lcd_endpoints(&self) -> Vec<String> {
+        self.endpoints.urls()
+    }
+
+    /// Send requests to `urls` from now on, starting with the first
+    ///
+    /// Lets a long-running miner pick up edited endpoints without restarting.
+    /// [`RpcClient::config`] keeps reporting the endpoints it was built with.
+    pub fn set_lcd_endpoints(&self, urls: Vec<String>) -> Result<()> {
+        if urls.is_empty() {
+            return Err(RpcError::Config("no LCD endpoints configured".to_string()));
+        }
+        for url in &urls {
+            validate_endpoint(url)?;
+        }
+        self.endpoints.replace(
+            urls.into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
         );
+        Ok(())
+    }
 
-        let resp: serde_json::Value = self.http_client.get(&url).send().await?.json().await?;
+    /// Health-check every configured endpoint
+    pub async fn probe_endpoints(&self) -> Vec<EndpointStatus> {
+        let (active, _) = self.endpoints.active();
+        let mut statuses = Vec::with_capacity(self.endpoints.len());
+        for (i, url) in self.endpoints.urls().iter().enumerate() {
+            let mut status = endpoints::probe(&self.http_client, url).await;
+            status.active = i == active;
+            statuses.push(status);
+        }
+        statuses
+    }
 
-        let account = &resp["account"];
-        let sequence: u64 = account["sequence"]
-            .as_str()
-            .unwrap_or("0")
-            .parse()
-            .unwrap_or(0);
-        let account_number: u64 = account["account_number"]
-            .as_str()
-            .unwrap_or("0")
-            .parse()
-            .unwrap_or(0);
+    /// Probe every endpoint and switch to the fastest healthy one
+    pub async fn select_fastest_endpoint(&self) -> Vec<EndpointStatus> {
+        let mut statuses = self.probe_endpoints().await;
+        if let Some(fastest) = endpoints::fastest(&statuses) {
+            self.endpoints.set_active(fastest);
+            for (i, status) in statuses.iter_mut().enumerate() {
+                status.active = i == fastest;
+            }
+        }
+        statuses
+    }
+
+    /// Run a request against the active endpoint, failing over to the next one
+    /// on transient errors (connection failures, timeouts, non-JSON replies)
+    ///
+    /// HTTP 429 and 5xx replies are first retried on the same endpoint with
+    /// backoff, up to [`RATE_LIMIT_RETRIES`] times and while the endpoint's
+    /// shared retry budget lasts. Once every endpoint has
+    /// failed, the last error is returned as [`RpcError::EndpointsExhausted`].
+    async fn with_failover<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error = None;
+        for _ in 0..self.endpoints.len() {
+            let (index, base) = self.endpoints.active();
+            let mut attempt = 0;
+            let error = loop {
+                let e = match request(base.to_string()).await {
+                    Ok(value) => return Ok(value),
+                    Err(e) => e,
+                };
+                if let RpcError::HttpStatus(status) = &e {
+                    if attempt < RATE_LIMIT_RETRIES && self.retry_budget.try_take(&base) {
+                        let delay = backoff::delay(attempt, status.retry_after);
+                        attempt += 1;
+                        if let Some(handler) = &self.warning_handler {
+                            handler(&BackoffWarning {
+                                endpoint: base.to_string(),
+                                status: status.status,
+                                attempt,
+                                delay,
+                            });
+                        }
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                    break e;
+                }
+                if e.is_transient() {
+                    break e;
+                }
+                return Err(e);
+            };
+            self.endpoints.fail_over(index);
+            last_error = Some(error);
+        }
+        match last_error {
+            Some(e) => Err(RpcError::EndpointsExhausted { last: Box::new(e) }),
+            None => Err(RpcError::Config("no LCD endpoints configured".to_string())),
+        }
+    }
+
+    /// GET a JSON document from the LCD
+    async fn get_json(&self, path: &str) -> Result<serde_json::Value> {
+        self.with_failover(|base| async move {
+            let url = format!("{}{}", base, path);
+            let resp = HttpStatusError::check(debug::send(self.http_client.get(&url)).await?)?;
+            Ok(resp.json().await?)
+        })
+        .await
+    }
+
+    /// POST a JSON body to the LCD and parse the JSON reply
+    async fn post_json(&self, path: &str, body: &serde_json::Value) -> Result<serde_json::Value> {
+        self.with_failover(|base| async move {
+            let url = format!("{}{}", base, path);
+            let resp =
+                HttpStatusError::check(debug::send(self.http_client.post(&url).json(body)).await?)?;
+            Ok(resp.json().await?)
+        })
+        .await
+    }
 
-        Ok((account_number, sequence))
+    /// Forget cached contract query responses so the next queries hit the chain
+    pub fn invalidate_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Run a smart query against the configured contract and parse its reply
+    ///
+    /// Replies are cached for `cache_ttl_secs`; a changed seed drops every
+    /// other cached reply.
+    pub async fn query_smart<T: DeserializeOwned>(&self, msg: &impl Serialize) -> Result<T> {
+        let query = serde_json::to_string(msg)?;
+        let resp = self.query_contract(&query).await?;
+        serde_json::from_value(resp["data"].clone()).map_err(|e| {
+            RpcError::InvalidResponse(format!(
+                "unexpected reply to smart query {} ({}): {}",
+                query,
+                e,
+                lcd::snippet(&resp)
+            ))
+        })
+    }
+
+    /// Read a key of the configured contract's storage, `None` if it is unset
+    pub async fn query_raw(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = &self.grpc {
+            let data = grpc.raw_query(&self.config.contract_address, key).await?;
+            return Ok((!data.is_empty()).then_some(data));
+        }
+
+        let engine = &base64::engine::general_purpose::STANDARD;
+        let resp = self
+            .get_json(&format!(
+                "/cosmwasm/wasm/v1/contract/{}/raw/{}",
+                self.config.contract_address,
+                base64::Engine::encode(engine, key)
+            ))
+            .await?;
+        match resp["data"].as_str() {
+            None | Some("") => Ok(None),
+            Some(data) => base64::Engine::decode(engine, data)
+                .map(Some)
+                .map_err(|e| RpcError::Decode(format!("raw state is not base64: {}", e))),
+        }
+    }
+
+    /// Smart query with a cached reply; `query` is the JSON query message
+    ///
+    /// Returns the LCD envelope `{"data": <contract reply>}` for either transport.
+    async fn query_contract(&self, query: &str) -> Result<serde_json::Value> {
+        if let Some(cached) = self.cache.get(query) {
+            return Ok(cached);
+        }
+        let resp = self.query_contract_uncached(query).await?;
+        let previous = self.cache.insert(query, resp.clone());
+        if query == serde_json::to_string(&QueryMsg::Seed {})?
+            && previous.is_some_and(|p| p != resp)
+        {
+            // A new seed may come with a retarget: refetch everything else
+            self.cache.retain_only(query);
+        }
+        Ok(resp)
+    }
+
+    async fn query_contract_uncached(&self, query: &str) -> Result<serde_json::Value> {
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = &self.grpc {
+            let data = grpc
+                .smart_query(&self.config.contract_address, query.as_bytes())
+                .await?;
+            return Ok(serde_json::json!({ "data": data }));
+        }
+
+        let query_b64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, query);
+        let resp = self
+            .get_json(&format!(
+                "/cosmwasm/wasm/v1/contract/{}/smart/{}",
+                self.config.contract_address, query_b64
+            ))
+            .await?;
+        if resp.get("data").is_none() {
+            // The LCD reports contract errors as {"code": .., "message": ..}
+            if let Some(message) = resp["message"].as_str() {
+                return Err(RpcError::ContractQuery(message.to_string()));
+            }
+        }
+        Ok(resp)
+    }
+
+    /// Query account info (account number and sequence)
+    ///
+    /// Fails with [`RpcError::AccountNotFound`] for an account the chain does
+    /// not know yet, which cannot sign transactions.
+    pub async fn get_account_info(&self, address: &str) -> Result<(u64, u64)> {
+        self.get_account(address)
+            .await?
+            .ok_or_else(|| RpcError::AccountNotFound {
+                address: address.to_string(),
+                chain_id: self.config.chain_id.clone(),
+            })
+    }
+
+    /// Query `(account_number, sequence)`, `None` if the account does not exist
+    pub async fn get_account(&self, address: &str) -> Result<Option<(u64, u64)>> {
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = &self.grpc {
+            return grpc.account_info(address).await;
+        }
+
+        let path = format!("/cosmos/auth/v1beta1/accounts/{}", address);
+        let resp = self.get_json(&path).await?;
+        match lcd::parse::<lcd::AccountReply>(&path, resp.clone())? {
+            lcd::Reply::Ok(reply) => reply.account.numbers().map(Some).ok_or_else(|| {
+                lcd::unexpected(&path, &"account has no account_number/sequence", &resp)
+            }),
+            // Accounts appear on chain with their first incoming transfer
+            lcd::Reply::Error(e) if e.is_not_found() => Ok(None),
+            lcd::Reply::Error(e) => Err(RpcError::InvalidResponse(format!(
+                "account query for {} failed (code {}): {}",
+                address, e.code, e.message
+            ))),
+        }
+    }
+
+    /// Query the `denom` balance of `address` (over the LCD for either transport)
+    pub async fn get_balance(&self, address: &str, denom: &str) -> Result<u128> {
+        let path = format!(
+            "/cosmos/bank/v1beta1/balances/{}/by_denom?denom={}",
+            address, denom
+        );
+        let resp = self.get_json(&path).await?;
+        match lcd::parse::<lcd::BalanceReply>(&path, resp.clone())? {
+            lcd::Reply::Ok(reply) => reply
+                .balance
+                .amount
+                .parse()
+                .map_err(|e| lcd::unexpected(&path, &e, &resp)),
+            lcd::Reply::Error(e) => Err(RpcError::InvalidResponse(format!(
+                "balance query for {} failed (code {}): {}",
+                address, e.code, e.message
+            ))),
+        }
+    }
+
+    /// Whether `granter` has granted `grantee` a fee allowance
+    pub async fn has_fee_allowance(&self, granter: &str, grantee: &str) -> Result<bool> {
+        let path = format!("/cosmos/feegrant/v1beta1/allowance/{}/{}", granter, grantee);
+        let resp = self.get_json(&path).await?;
+        match lcd::parse::<lcd::AllowanceReply>(&path, resp)? {
+            lcd::Reply::Ok(reply) => Ok(!reply.allowance.is_null()),
+            lcd::Reply::Error(e) if e.is_not_found() => Ok(false),
+            lcd::Reply::Error(e) => Err(RpcError::InvalidResponse(format!(
+                "fee allowance query failed (code {}): {}",
+                e.code, e.message
+            ))),
+        }
     }
 
     /// Broadcast a signed transaction
     pub async fn broadcast_tx(&self, tx_bytes: Vec<u8>) -> Result<String> {
-        let url = format!("{}/cosmos/tx/v1beta1/txs", self.config.lcd_url);
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = &self.grpc {
+            return grpc.broadcast(tx_bytes, self.config.broadcast_mode).await;
+        }
 
         let body = serde_json::json!({
             "tx_bytes": base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &tx_bytes),
-            "mode": "BROADCAST_MODE_SYNC"
+            "mode": self.config.broadcast_mode.as_proto()
         });
 
-        let resp: serde_json::Value = self
-            .http_client
-            .post(&url)
-            .json(&body)
-            .send()
-            .await?
-            .json()
-            .await?;
-
-        if let Some(code) = resp["tx_response"]["code"].as_u64() {
-            if code != 0 {
-                let raw_log = resp["tx_response"]["raw_log"]
-                    .as_str()
-                    .unwrap_or("Unknown error");
-                anyhow::bail!("Transaction failed with code {}: {}", code, raw_log);
+        let path = "/cosmos/tx/v1beta1/txs";
+        let resp = self.post_json(path, &body).await?;
+        match lcd::parse::<lcd::BroadcastReply>(path, resp.clone())? {
+            lcd::Reply::Ok(reply) if reply.tx_response.code != 0 => Err(RpcError::TxRejected {
+                code: reply.tx_response.code,
+                raw_log: reply.tx_response.raw_log,
+            }),
+            lcd::Reply::Ok(reply) if reply.tx_response.txhash.is_empty() => {
+                Err(lcd::unexpected(path, &"empty txhash", &resp))
             }
+            lcd::Reply::Ok(reply) => Ok(reply.tx_response.txhash),
+            // The node refused the request before CheckTx (undecodable tx)
+            lcd::Reply::Error(e) => Err(RpcError::TxRejected {
+                code: e.code,
+                raw_log: e.message,
+            }),
         }
+    }
 
-        let tx_hash = resp["tx_response"]["txhash"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
+    /// Simulate a signed transaction and return the gas it would use
+    pub async fn simulate_gas(&self, tx_bytes: &[u8]) -> Result<u64> {
+        let body = serde_json::json!({
+            "tx_bytes": base64::Engine::encode(&base64::engine::general_purpose::STANDARD, tx_bytes),
+        });
+
+        let path = "/cosmos/tx/v1beta1/simulate";
+        let resp = self.post_json(path, &body).await?;
+        match lcd::parse::<lcd::SimulateReply>(path, resp)? {
+            lcd::Reply::Ok(reply) => Ok(reply.gas_info.gas_used),
+            // The ante handler checks the sequence in simulation too
+            lcd::Reply::Error(e) if e.message.contains("account sequence mismatch") => {
+                Err(RpcError::TxRejected {
+                    code: SEQUENCE_MISMATCH_CODE,
+                    raw_log: e.message,
+                })
+            }
+            lcd::Reply::Error(e) => Err(RpcError::InvalidTx(format!(
+                "simulation failed: {}",
+                e.message
+            ))),
+        }
+    }
 
-        Ok(tx_hash)
+    /// Sign a proof transaction and simulate it without broadcasting
+    ///
+    /// A proof the contract rejects (bad hash, expired, duplicate) comes back
+    /// as `accepted: false` with the chain's message; network and account
+    /// errors are returned as errors. The sequence cache is left untouched.
+    pub async fn simulate_proof(
+        &self,
+        proof: &ProofSubmission,
+        signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    ) -> Result<ProofSimulation> {
+        let miner: cosmrs::AccountId = proof
+            .miner_address
+            .parse()
+            .map_err(|e| RpcError::InvalidTx(format!("Invalid miner address: {}", e)))?;
+        let signer = signer_address(signing_key, miner.prefix())?.to_string();
+        let (account_number, sequence) = self.get_account_info(&signer).await?;
+        let signed = sign_proof_tx(&self.config, proof, signing_key, account_number, sequence)?;
+        match self.simulate_gas(&signed.decode_tx_bytes()?).await {
+            Ok(gas_used) => Ok(ProofSimulation {
+                accepted: true,
+                gas_used: Some(gas_used),
+                gas_limit: Some(self.config.gas_limit.unwrap_or_else(|| {
+                    (gas_used as f64 * self.config.gas_adjustment).ceil() as u64
+                })),
+                error: None,
+            }),
+            Err(RpcError::InvalidTx(message)) => Ok(ProofSimulation {
+                accepted: false,
+                gas_used: None,
+                gas_limit: None,
+                error: Some(message),
+            }),
+            Err(e) => Err(e),
+        }
     }
 
     /// Submit a proof to the chain
+    ///
+    /// A key other than the miner's submits on the miner's behalf, as a relay does.
+    /// A proof this client already sent fails with [`RpcError::DuplicateProof`]
+    /// instead of paying for a transaction the contract would reject.
     pub async fn submit_proof(
         &self,
         proof: ProofSubmission,
         signing_key: &cosmrs::crypto::secp256k1::SigningKey,
     ) -> Result<SubmitResult> {
-        use cosmrs::cosmwasm::MsgExecuteContract;
-        use cosmrs::tx::{Body, Fee, Msg, SignDoc, SignerInfo};
-        use cosmrs::{AccountId, Coin};
-
-        // Get account info — use cached sequence to avoid stale on-chain reads
-        let (account_number, sequence) = if let (Some(acc), Some(seq)) =
-            (self.cached_account_number.get(), self.local_sequence.get())
-        {
-            (acc, seq)
-        } else {
-            let (acc, seq) = self.get_account_info(&proof.miner_address).await?;
-            self.cached_account_number.set(Some(acc));
-            self.local_sequence.set(Some(seq));
-            (acc, seq)
-        };
+        self.submit_proofs(std::slice::from_ref(&proof), signing_key)
+            .await
+    }
 
-        // Build execute message (miner_address=None: sender is the miner)
-        let execute_msg = ExecuteMsg::SubmitProof {
-            hash: proof.hash.clone(),
-            nonce: proof.nonce,
-            timestamp: proof.timestamp,
-            miner_address: None,
-        };
-        let msg_bytes = serde_json::to_vec(&execute_msg)?;
+    /// Submit several proofs in one transaction, a `submit_proof` message each
+    ///
+    /// Saves a signature, a fee and a sequence number per extra proof. The
+    /// messages execute atomically: the result covers the whole batch, and
+    /// its reward is the sum paid for every proof in it. Gas is the
+    /// configured limit per proof, or simulated for the whole transaction.
+    pub async fn submit_proofs(
+        &self,
+        proofs: &[ProofSubmission],
+        signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    ) -> Result<SubmitResult> {
+        for (claimed, proof) in proofs.iter().enumerate() {
+            if let Err(tx_hash) = self.submitted.claim(proof) {
+                for proof in &proofs[..claimed] {
+                    self.submitted.release(proof);
+                }
+                return Err(RpcError::DuplicateProof { tx_hash });
+            }
+        }
+        let result = self.send_proofs(proofs, signing_key).await;
+        if result.is_err() {
+            // Unless they were broadcast, the proofs may be sent again
+            for proof in proofs {
+                self.submitted.release(proof);
+            }
+        }
+        result
+    }
 
-        // Parse addresses
-        let sender: AccountId = proof
+    /// Sign and broadcast proofs, re-signing on a stale sequence
+    async fn send_proofs(
+        &self,
+        proofs: &[ProofSubmission],
+        signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    ) -> Result<SubmitResult> {
+        let first = proofs
+            .first()
+            .ok_or_else(|| RpcError::InvalidTx("No proofs to submit".to_string()))?;
+        let miner: cosmrs::AccountId = first
             .miner_address
             .parse()
-            .map_err(|e| anyhow::anyhow!("Invalid sender address: {}", e))?;
-        let contract: AccountId = self
-            .config
-            .contract_address
-            .parse()
-            .map_err(|e| anyhow::anyhow!("Invalid contract address: {}", e))?;
-
-        // Build MsgExecuteContract
-        let msg = MsgExecuteContract {
-            sender,
-            contract,
-            msg: msg_bytes,
-            funds: vec![],
+            .map_err(|e| RpcError::InvalidTx(format!("Invalid miner address: {}", e)))?;
+        let signer = signer_address(signing_key, miner.prefix())?.to_string();
+        let mut retries = 0;
+        loop {
+            // Get account info — use cached sequence to avoid stale on-chain reads
+            let (account_number, sequence) = match self.sequence.current() {
+                Some(current) => current,
+                None => {
+                    let (acc, seq) = self.get_account_info(&signer).await?;
+                    self.sequence.set(acc, seq);
+                    (acc, seq)
+                }
+            };
+
+            let tx_bytes = match self
+                .sign_submission(proofs, signing_key, account_number, sequence)
+                .await
+            {
+                Ok(tx_bytes) => tx_bytes,
+                Err(e) if e.is_sequence_mismatch() && retries < MAX_SEQUENCE_RETRIES => {
+                    // Gas simulation saw the sequence taken: resync and re-sign
+                    retries += 1;
+                    self.sequence.resync(e.expected_sequence());
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            // Broadcast
+            let tx_hash = match self.broadcast_tx(tx_bytes).await {
+                Ok(tx_hash) => tx_hash,
+                Err(e) if e.is_sequence_mismatch() && retries < MAX_SEQUENCE_RETRIES => {
+                    // Another transaction used this sequence: resync and re-sign
+                    retries += 1;
+                    self.sequence.resync(e.expected_sequence());
+                    continue;
+                }
+                Err(e) => {
+                    // The tx may or may not have reached the mempool: re-query next time
+                    self.sequence.invalidate();
+                    return Err(e);
+                }
+            };
+
+            // Increment local sequence for next TX (a committed failure also consumes it)
+            self.sequence.advance(sequence);
+            for proof in proofs {
+                self.submitted.sent(proof, &tx_hash);
+            }
+
+            return self.finish_broadcast(tx_hash).await;
+        }
+    }
+
+    /// Sign a proof transaction, simulating gas first when configured
+    async fn sign_submission(
+        &self,
+        proofs: &[ProofSubmission],
+        signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+        account_number: u64,
+        sequence: u64,
+    ) -> Result<Vec<u8>> {
+        let sign = |gas_limit| {
+            sign_batch_tx_with_gas(
+                &self.config,
+                proofs,
+                signing_key,
+                account_number,
+                sequence,
+                gas_limit,
+            )
         };
+        let tx_bytes = sign(batch_gas_limit(&self.config, proofs.len()))?;
+        if self.config.gas_limit.is_some() {
+            return Ok(tx_bytes);
+        }
+        // Simulate with the default limit, then re-sign with the measured usage
+        let gas_used = self.simulate_gas(&tx_bytes).await?;
+        sign((gas_used as f64 * self.config.gas_adjustment).ceil() as u64)
+    }
 
-        // Convert to Any
-        let msg_any = msg
-            .to_any()
-            .map_err(|e| anyhow::anyhow!("Failed to convert message: {}", e))?;
+    /// Build the submission result, waiting for the commit in `block` mode
+    async fn finish_broadcast(&self, tx_hash: String) -> Result<SubmitResult> {
+        if self.config.broadcast_mode != BroadcastMode::Block {
+            return Ok(SubmitResult {
+                tx_hash,
+                accepted: true,
+                reward: None, // Only known once committed (`block` mode)
+                error: None,
+                height: None,
+                reward_denom: None,
+                gas_used: None,
+            });
+        }
 
-        // Build transaction body
-        let body = Body::new(vec![msg_any], "", 0u32);
+        let confirmation = self.wait_for_tx(&tx_hash).await?;
+        let contract = &self.config.contract_address;
+        let reward = batch_reward(&confirmation.events, contract);
+        // Contracts that do not name the denom pay in their configured token
+        let reward_denom = match proof_reward_denom(&confirmation.events, contract) {
+            Some(denom) => Some(denom),
+            None if reward.is_some() => self.get_config().await.ok().map(|c| c.token_denom),
+            None => None,
+        };
+        Ok(SubmitResult {
+            tx_hash,
+            accepted: confirmation.is_success(),
+            reward,
+            error: (!confirmation.is_success()).then(|| {
+                format!(
+                    "Transaction failed with code {}: {}",
+                    confirmation.code, confirmation.raw_log
+                )
+            }),
+            height: Some(confirmation.height),
+            reward_denom,
+            gas_used: Some(confirmation.gas_used),
+        })
+    }
 
-        // Build auth info with fee (default 0 for Bostrom zero-fee transactions)
-        let denom: cosmrs::Denom = "boot"
-            .parse()
-            .map_err(|e| anyhow::anyhow!("Invalid denom: {}", e))?;
-        let fee = Fee::from_amount_and_gas(
-            Coin {
-                denom,
-                amount: self.config.fee_amount,
-            },
-            1600000u64,
-        );
+    /// Fetch a transaction by hash, returning `None` if it is not (yet) indexed
+    pub async fn get_tx_confirmation(&self, tx_hash: &str) -> Result<Option<TxInfo>> {
+        let path = format!("/cosmos/tx/v1beta1/txs/{}", tx_hash);
+        let json = self
+            .with_failover(|base| {
+                let url = format!("{}{}", base, path);
+                async move {
+                    let resp =
+                        HttpStatusError::check(debug::send(self.http_client.get(&url)).await?)?;
+                    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+                        return Ok(None);
+                    }
+                    Ok(Some(resp.json::<serde_json::Value>().await?))
+                }
+            })
+            .await?;
+        let Some(json) = json else {
+            return Ok(None);
+        };
 
-        let signer_info = SignerInfo::single_direct(Some(signing_key.public_key()), sequence);
-        let auth_info = signer_info.auth_info(fee);
+        let tx_response = &json["tx_response"];
+        if tx_response.is_null() {
+            // Not found is reported as {"code": 5, "message": "tx not found"} by some nodes
+            if json["code"].as_u64() == Some(5) {
+                return Ok(None);
+            }
+            let message = json["message"].as_str().unwrap_or("Unknown error");
+            return Err(RpcError::InvalidResponse(format!(
+                "failed to query transaction: {}",
+                message
+            )));
+        }
 
-        // Build sign doc
-        let chain_id: cosmrs::tendermint::chain::Id = self
-            .config
-            .chain_id
-            .parse()
-            .map_err(|e| anyhow::anyhow!("Invalid chain ID: {}", e))?;
-        let sign_doc = SignDoc::new(&body, &auth_info, &chain_id, account_number)
-            .map_err(|e| anyhow::anyhow!("Failed to create sign doc: {}", e))?;
+        let number = |field: &str| -> u64 {
+            tx_response[field]
+                .as_str()
+                .and_then(|v| v.parse().ok())
+                .or_else(|| tx_response[field].as_u64())
+                .unwrap_or(0)
+        };
+        Ok(Some(TxInfo {
+            tx_hash: tx_hash.to_string(),
+            height: number("height"),
+            code: tx_response["code"].as_u64().unwrap_or(0) as u32,
+            raw_log: tx_response["raw_log"].as_str().unwrap_or("").to_string(),
+            gas_wanted: number("gas_wanted"),
+            gas_used: number("gas_used"),
+            events: serde_json::from_value(tx_response["events"].clone()).unwrap_or_default(),
+        }))
+    }
 
-        // Sign
-        let tx_signed = sign_doc
-            .sign(signing_key)
-            .map_err(|e| anyhow::anyhow!("Failed to sign transaction: {}", e))?;
-        let tx_bytes = tx_signed
-            .to_bytes()
-            .map_err(|e| anyhow::anyhow!("Failed to serialize transaction: {}", e))?;
+    /// Fetch a committed transaction by hash
+    pub async fn get_tx(&self, tx_hash: &str) -> Result<TxInfo> {
+        self.get_tx_confirmation(tx_hash)
+            .await?
+            .ok_or_else(|| RpcError::TxNotFound(tx_hash.to_string()))
+    }
+
+    /// Poll until a transaction is committed or `confirm_timeout_secs` elapses
+    pub async fn wait_for_tx(&self, tx_hash: &str) -> Result<TxInfo> {
+        let deadline = std::time::Instant::now()
+            + std::time::Duration::from_secs(self.config.confirm_timeout_secs);
+        loop {
+            if let Some(confirmation) = self.get_tx_confirmation(tx_hash).await? {
+                return Ok(confirmation);
+            }
+            if std::time::Instant::now() >= deadline {
+                return Err(RpcError::ConfirmTimeout {
+                    tx_hash: tx_hash.to_string(),
+                    timeout_secs: self.config.confirm_timeout_secs,
+                });
+            }
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+        }
+    }
 
-        // Broadcast
-        let tx_hash = self.broadcast_tx(tx_bytes).await?;
+    /// Height of the latest block
+    pub async fn latest_height(&self) -> Result<u64> {
+        let json = self.get_json(HEALTH_PATH).await?;
+        json["block"]["header"]["height"]
+            .as_str()
+            .and_then(|height| height.parse().ok())
+            .ok_or_else(|| lcd::unexpected(HEALTH_PATH, &"no block height", &json))
+    }
 
-        // Increment local sequence for next TX
-        self.local_sequence.set(Some(sequence + 1));
+    /// Let proofs whose transaction was dropped (see [`TxTracker`]) be
+    /// submitted again
+    ///
+    /// The dropped transaction's sequence is free again, so the account is
+    /// queried before the next one is signed.
+    pub fn forget_submission(&self, proofs: &[ProofSubmission]) {
+        for proof in proofs {
+            self.submitted.forget(proof);
+        }
+        self.sequence.invalidate();
+    }
 
-        Ok(SubmitResult {
-            tx_hash,
-            accepted: true,
-            reward: None, // Will be in events
-            error: None,
-        })
+    /// Broadcast a transaction produced offline by [`sign_proof_tx`]
+    pub async fn broadcast_signed(&self, signed: &SignedTx) -> Result<SubmitResult> {
+        if signed.chain_id != self.config.chain_id {
+            return Err(RpcError::InvalidTx(format!(
+                "signed for chain '{}', but the client is configured for '{}'",
+                signed.chain_id, self.config.chain_id
+            )));
+        }
+        let tx_hash = self.broadcast_tx(signed.decode_tx_bytes()?).await?;
+        self.finish_broadcast(tx_hash).await
     }
 
     /// Query the current mining seed from the contract
-    pub async fn get_seed(&self) -> Result<[u8; 32]> {
-        let query = QueryMsg::Seed {};
+    pub async fn get_seed(&self) -> Result<SeedInfo> {
+        let resp: SeedResponse = self.query_smart(&QueryMsg::Seed {}).await?;
+
+        let seed = hex::decode(&resp.seed)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                RpcError::InvalidResponse(format!(
+                    "seed '{}' is not 32 hex-encoded bytes",
+                    resp.seed
+                ))
+            })?;
+        Ok(SeedInfo {
+            seed,
+            seed_interval: resp.seed_interval,
+            created_at: resp.created_at,
+            expires_at: resp
+                .created_at
+                .map(|created| created.saturating_add(resp.seed_interval)),
+        })
+    }
+
+    /// Smart query against the contract state as of block `height`
+    ///
+    /// Sent to the LCD uncached with the `x-cosmos-block-height` header; nodes
+    /// that pruned that height answer with an error.
+    pub async fn query_smart_at<T: DeserializeOwned>(
+        &self,
+        msg: &impl Serialize,
+        height: u64,
+    ) -> Result<T> {
         let query_b64 = base64::Engine::encode(
             &base64::engine::general_purpose::STANDARD,
-            serde_json::to_vec(&query)?,
+            serde_json::to_string(msg)?,
         );
-
-        let url = format!(
-            "{}/cosmwasm/wasm/v1/contract/{}/smart/{}",
-            self.config.lcd_url, self.config.contract_address, query_b64
+        let path = format!(
+            "/cosmwasm/wasm/v1/contract/{}/smart/{}",
+            self.config.contract_address, query_b64
         );
+        let resp: serde_json::Value = self
+            .with_failover(|base| {
+                let request = self
+                    .http_client
+                    .get(format!("{}{}", base, path))
+                    .header(BLOCK_HEIGHT_HEADER, height.to_string());
+                async move {
+                    let resp = HttpStatusError::check(debug::send(request).await?)?;
+                    Ok(resp.json().await?)
+                }
+            })
+            .await?;
+        if resp.get("data").is_none() {
+            let message = resp["message"].as_str().unwrap_or("Unknown error");
+            return Err(RpcError::ContractQuery(message.to_string()));
+        }
+        serde_json::from_value(resp["data"].clone())
+            .map_err(|e| RpcError::InvalidResponse(format!("unexpected smart query reply: {}", e)))
+    }
 
-        let resp: serde_json::Value = self.http_client.get(&url).send().await?.json().await?;
-
-        let seed_hex = resp["data"]["seed"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Invalid seed response"))?;
+    /// Query the current difficulty target
+    pub async fn get_difficulty(&self) -> Result<u32> {
+        let resp: DifficultyResponse = self.query_smart(&QueryMsg::Difficulty {}).await?;
+        Ok(resp.current)
+    }
 
-        let seed_bytes = hex::decode(seed_hex)?;
-        let mut seed = [0u8; 32];
-        seed.copy_from_slice(&seed_bytes);
+    /// Query proofs submitted and rewards earned by `address`
+    pub async fn get_miner_stats(&self, address: &str) -> Result<MinerStatsResponse> {
+        self.query_smart(&QueryMsg::MinerStats {
+            address: address.to_string(),
+        })
+        .await
+    }
 
-        Ok(seed)
+    /// Query up to `limit` miners ranked by accepted proofs, after `start_after`
+    ///
+    /// Needs a v2 contract; v1 contracts reject the query as an unknown variant.
+    #[cfg(feature = "contract-v2")]
+    pub async fn get_leaderboard(
+        &self,
+        limit: Option<u32>,
+        start_after: Option<&str>,
+    ) -> Result<LeaderboardResponse> {
+        self.query_smart(&QueryMsg::Leaderboard {
+            limit,
+            start_after: start_after.map(str::to_string),
+        })
+        .await
     }
 
-    /// Query the current difficulty target
-    pub async fn get_difficulty(&self) -> Result<u32> {
-        let query = QueryMsg::Difficulty {};
-        let query_b64 = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            serde_json::to_vec(&query)?,
-        );
+    /// The last `limit` proofs accepted by the contract, newest first
+    ///
+    /// Searches the LCD's transaction index for `submit_proof` executions,
+    /// [`TX_SEARCH_PAGE`] transactions per request.
+    pub async fn recent_proofs(&self, limit: usize) -> Result<Vec<AcceptedProof>> {
+        let contract = &self.config.contract_address;
+        let conditions = [
+            format!("wasm._contract_address='{}'", contract),
+            "wasm.action='submit_proof'".to_string(),
+        ];
+        let mut proofs = Vec::new();
+        let mut page = 1;
+        while proofs.len() < limit {
+            let json = self.search_txs(&conditions, page, "ORDER_BY_DESC").await?;
+            let responses = tx_responses(&json)?;
+            for tx in responses.iter().filter(|tx| tx["code"].as_u64() == Some(0)) {
+                let (Some(tx_hash), Some(height), Some(time)) = (
+                    tx["txhash"].as_str(),
+                    tx["height"].as_str().and_then(|h| h.parse().ok()),
+                    tx["timestamp"].as_str().and_then(events::parse_block_time),
+                ) else {
+                    continue;
+                };
+                let events: Vec<TxEvent> =
+                    serde_json::from_value(tx["events"].clone()).unwrap_or_default();
+                proofs.extend(
+                    proof_events(&events, contract, tx_hash, height)
+                        .into_iter()
+                        .map(|event| AcceptedProof { event, time }),
+                );
+            }
+            if responses.len() < TX_SEARCH_PAGE {
+                break;
+            }
+            page += 1;
+        }
+        proofs.truncate(limit);
+        Ok(proofs)
+    }
 
-        let url = format!(
-            "{}/cosmwasm/wasm/v1/contract/{}/smart/{}",
-            self.config.lcd_url, self.config.contract_address, query_b64
-        );
+    /// Page `page` (1-based) of the contract's `submit_proof` transactions
+    /// from `from_height` on, oldest first
+    ///
+    /// Each proof comes with the inputs it was submitted with, as decoded by
+    /// the LCD, so it can be re-verified.
+    pub async fn proof_submissions(
+        &self,
+        from_height: u64,
+        to_height: Option<u64>,
+        page: usize,
+    ) -> Result<ProofPage> {
+        let contract = &self.config.contract_address;
+        let mut conditions = vec![
+            format!("wasm._contract_address='{}'", contract),
+            "wasm.action='submit_proof'".to_string(),
+            format!("tx.height>={}", from_height),
+        ];
+        if let Some(to_height) = to_height {
+            conditions.push(format!("tx.height<={}", to_height));
+        }
+        let json = self.search_txs(&conditions, page, "ORDER_BY_ASC").await?;
+        let responses = tx_responses(&json)?;
+
+        let mut proofs = Vec::new();
+        for tx in responses.iter().filter(|tx| tx["code"].as_u64() == Some(0)) {
+            let (Some(tx_hash), Some(height), Some(time)) = (
+                tx["txhash"].as_str(),
+                tx["height"].as_str().and_then(|h| h.parse().ok()),
+                tx["timestamp"].as_str().and_then(events::parse_block_time),
+            ) else {
+                continue;
+            };
+            let events: Vec<TxEvent> =
+                serde_json::from_value(tx["events"].clone()).unwrap_or_default();
+            let accepted = proof_events(&events, contract, tx_hash, height);
+            let messages = tx["tx"]["body"]["messages"]
+                .as_array()
+                .into_iter()
+                .flatten();
+            let submitted = messages.filter(|m| m["contract"].as_str() == Some(contract));
+            for (i, message) in submitted.enumerate() {
+                let Ok(ExecuteMsg::SubmitProof {
+                    hash,
+                    nonce,
+                    timestamp,
+                    miner_address,
+                    worker_tag,
+                }) = serde_json::from_value(message["msg"].clone())
+                else {
+                    continue;
+                };
+                let sender = message["sender"].as_str().unwrap_or_default();
+                proofs.push(SubmittedProof {
+                    tx_hash: tx_hash.to_string(),
+                    height,
+                    time,
+                    miner: miner_address.unwrap_or_else(|| sender.to_string()),
+                    hash,
+                    nonce,
+                    timestamp,
+                    difficulty: accepted.get(i).and_then(|event| event.difficulty),
+                    worker_tag,
+                });
+            }
+        }
+        Ok(ProofPage {
+            proofs,
+            more: responses.len() == TX_SEARCH_PAGE,
+        })
+    }
 
-        let resp: serde_json::Value = self.http_client.get(&url).send().await?.json().await?;
+    /// One page of committed transactions matching all `conditions`, in `order_by` order
+    ///
+    /// Cosmos SDK 0.47 takes the conditions as repeated `events` parameters and
+    /// 0.50 as a single `query`; the latter is tried when a node rejects the former.
+    async fn search_txs(
+        &self,
+        conditions: &[String],
+        page: usize,
+        order_by: &str,
+    ) -> Result<serde_json::Value> {
+        let mut params = vec![
+            ("order_by", order_by.to_string()),
+            ("page", page.to_string()),
+            ("limit", TX_SEARCH_PAGE.to_string()),
+        ];
+        let search = |params: Vec<(&'static str, String)>| {
+            self.with_failover(move |base| {
+                let url = format!("{}/cosmos/tx/v1beta1/txs", base);
+                let request = self.http_client.get(&url).query(&params);
+                async move {
+                    let resp = HttpStatusError::check(debug::send(request).await?)?;
+                    Ok(resp.json::<serde_json::Value>().await?)
+                }
+            })
+        };
 
-        let difficulty = resp["data"]["current"]
-            .as_u64()
-            .ok_or_else(|| anyhow::anyhow!("Invalid difficulty response"))?
-            as u32;
+        let mut events = params.clone();
+        events.extend(conditions.iter().map(|c| ("events", c.clone())));
+        let json = search(events).await?;
+        if json["tx_responses"].is_array() {
+            return Ok(json);
+        }
+        params.push(("query", conditions.join(" AND ")));
+        search(params).await
+    }
 
-        Ok(difficulty)
+    /// Subscribe to proofs accepted by the contract, from every miner
+    ///
+    /// Connects to the websocket of `rpc_url`; LCD failover does not apply.
+    #[cfg(feature = "websocket")]
+    pub async fn subscribe_proofs(&self) -> Result<ProofSubscription> {
+        let connect =
+            ProofSubscription::connect(&self.config.rpc_url, &self.config.contract_address);
+        match self.config.connect_timeout_secs {
+            0 => connect.await,
+            secs => tokio::time::timeout(std::time::Duration::from_secs(secs), connect)
+                .await
+                .map_err(|_| {
+                    RpcError::Network(format!("timed out connecting to {}", self.config.rpc_url))
+                })?,
+        }
     }
 
     /// Check if an account exists on-chain
     pub async fn account_exists(&self, address: &str) -> bool {
-        let url = format!(
-            "{}/cosmos/auth/v1beta1/accounts/{}",
-            self.config.lcd_url, address
-        );
-
-        let Ok(resp) = self.http_client.get(&url).send().await else {
-            return false;
-        };
-        let Ok(json) = resp.json::<serde_json::Value>().await else {
-            return false;
-        };
-        // Non-existent accounts return {"code":5, ...} without "account" field
-        json.get("account").is_some()
+        matches!(self.get_account(address).await, Ok(Some(_)))
     }
 
     /// Relay a proof via the relay service (submits on behalf of the miner).
     /// Used for new accounts that don't exist on-chain yet.
     /// Returns the transaction hash on success.
     pub async fn relay_proof(&self, proof: &ProofSubmission) -> Result<String> {
-        let url = "https://bostrom.cybernode.ai/relay";
+        if self.config.relay_url.is_empty() {
+            return Err(RpcError::Config(format!(
+                "no relay service configured for chain '{}'",
+                self.config.chain_id
+            )));
+        }
+        let url = &self.config.relay_url;
         let body = serde_json::json!({
             "hash": proof.hash,
             "nonce": proof.nonce,
@@ -401,11 +1694,7 @@ impl RpcClient {
             "miner_address": proof.miner_address,
         });
 
-        let resp: serde_json::Value = self
-            .http_client
-            .post(url)
-            .json(&body)
-            .send()
+        let resp: serde_json::Value = debug::send(self.http_client.post(url).json(&body))
             .await?
             .json()
             .await?;
@@ -414,31 +1703,80 @@ impl RpcClient {
             Ok(resp["tx_hash"].as_str().unwrap_or("").to_string())
         } else {
             let error = resp["error"].as_str().unwrap_or("unknown error");
-            anyhow::bail!("Relay failed: {}", error)
+            Err(RpcError::Relay(error.to_string()))
         }
     }
 
-    /// Query the minimum profitable difficulty
-    pub async fn get_min_profitable_difficulty(&self) -> Result<u32> {
-        let query = QueryMsg::Difficulty {};
-        let query_b64 = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            serde_json::to_vec(&query)?,
-        );
+    /// Ask the configured faucet to fund `address`, creating its account
+    ///
+    /// Sends `{"address", "denom"}` as the CosmJS faucet's `/credit` does; the
+    /// account appears once the faucet's transfer is committed (see
+    /// [`RpcClient::wait_for_account`]).
+    pub async fn request_faucet(&self, address: &str) -> Result<()> {
+        if self.config.faucet_url.is_empty() {
+            return Err(RpcError::Config(format!(
+                "no faucet configured for chain '{}' (set chain.faucet_url)",
+                self.config.chain_id
+            )));
+        }
+        let body = serde_json::json!({
+            "address": address,
+            "denom": self.config.fee_denom,
+        });
+        let resp = debug::send(self.http_client.post(&self.config.faucet_url).json(&body)).await?;
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let text = resp.text().await.unwrap_or_default();
+        Err(RpcError::Faucet(format!("{} ({})", text.trim(), status)))
+    }
 
-        let url = format!(
-            "{}/cosmwasm/wasm/v1/contract/{}/smart/{}",
-            self.config.lcd_url, self.config.contract_address, query_b64
-        );
+    /// Poll until `address` exists on chain, for up to `timeout`
+    pub async fn wait_for_account(
+        &self,
+        address: &str,
+        timeout: std::time::Duration,
+    ) -> Result<(u64, u64)> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(account) = self.get_account(address).await? {
+                return Ok(account);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(RpcError::AccountNotFound {
+                    address: address.to_string(),
+                    chain_id: self.config.chain_id.clone(),
+                });
+            }
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+        }
+    }
 
-        let resp: serde_json::Value = self.http_client.get(&url).send().await?.json().await?;
+    /// Query the contract configuration
+    pub async fn get_config(&self) -> Result<ConfigResponse> {
+        let raw: serde_json::Value = self.query_smart(&QueryMsg::Config {}).await?;
+        let schema = self
+            .config
+            .contract_schema
+            .unwrap_or_else(|| *self.schema.get_or_init(|| ContractSchema::detect(&raw)));
+        schema.parse_config(raw)
+    }
 
-        let min_profitable = resp["data"]["min_profitable"]
-            .as_u64()
-            .ok_or_else(|| anyhow::anyhow!("Invalid min_profitable response"))?
-            as u32;
+    /// Response shapes of the configured contract, detected from its config
+    /// on first use unless set in [`RpcConfig::contract_schema`]
+    pub async fn contract_schema(&self) -> Result<ContractSchema> {
+        if let Some(schema) = self.config.contract_schema.or(self.schema.get().copied()) {
+            return Ok(schema);
+        }
+        self.get_config().await?;
+        Ok(*self.schema.get_or_init(|| ContractSchema::LATEST))
+    }
 
-        Ok(min_profitable)
+    /// Query the minimum profitable difficulty
+    pub async fn get_min_profitable_difficulty(&self) -> Result<u32> {
+        let resp: DifficultyResponse = self.query_smart(&QueryMsg::Difficulty {}).await?;
+        Ok(resp.min_profitable)
     }
 }
 
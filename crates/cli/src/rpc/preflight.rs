@@ -0,0 +1,234 @@
+//! Checks run before mining starts
+//!
+//! A miner pointed at the wrong chain, a contract address with a typo or an
+//! account that cannot pay for its transactions only finds out when the
+//! first proof is submitted, possibly hours later. [`preflight`] asks the
+//! chain up front: an LCD endpoint answers, it serves the configured chain
+//! ID, the contract exists and takes proofs, and the signing account exists
+//! and can pay the fee (or has a fee grant).
+
+use serde::Serialize;
+
+use super::{fee_for_gas, EndpointStatus, RpcClient, RpcError, SUBMIT_PROOF_GAS};
+
+/// Outcome of one preflight check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passed,
+    /// Mining can go ahead, but something may need attention
+    Warning,
+    /// Proofs would not be accepted
+    Failed,
+}
+
+/// One preflight check and what it found
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    /// `lcd`, `chain_id`, `contract` or `account`
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// What was found, and for failures what to change
+    pub detail: String,
+}
+
+/// Results of [`preflight`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Preflight {
+    /// Health of every LCD endpoint; the client is left on the fastest one
+    pub endpoints: Vec<EndpointStatus>,
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl Preflight {
+    /// Checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Failed)
+    }
+
+    /// Whether every check passed or only warned
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    fn push(&mut self, name: &'static str, status: CheckStatus, detail: String) {
+        self.checks.push(PreflightCheck {
+            name,
+            status,
+            detail,
+        });
+    }
+}
+
+/// Check the connection, chain, contract and, if `signer` is given, the
+/// account that will sign proof transactions
+///
+/// Switches the client to the fastest healthy endpoint. Later checks are
+/// skipped when no endpoint answers.
+pub async fn preflight(client: &RpcClient, signer: Option<&str>) -> Preflight {
+    let config = client.config();
+    let mut report = Preflight {
+        endpoints: client.select_fastest_endpoint().await,
+        checks: Vec::new(),
+    };
+
+    let Some(active) = report.endpoints.iter().find(|s| s.active && s.is_healthy()) else {
+        let errors = report
+            .endpoints
+            .iter()
+            .map(|s| format!("{}: {}", s.url, s.error.as_deref().unwrap_or("no response")))
+            .collect::<Vec<_>>()
+            .join("; ");
+        report.push(
+            "lcd",
+            CheckStatus::Failed,
+            format!(
+                "No LCD endpoint is reachable ({}); check --lcd / network.lcd",
+                errors
+            ),
+        );
+        return report;
+    };
+    let detail = match active.height {
+        Some(height) => format!("{} at height {}", active.url, height),
+        None => active.url.clone(),
+    };
+    let chain_id = active.chain_id.clone();
+    report.push("lcd", CheckStatus::Passed, detail);
+
+    match chain_id {
+        Some(id) if id == config.chain_id => report.push("chain_id", CheckStatus::Passed, id),
+        Some(id) => report.push(
+            "chain_id",
+            CheckStatus::Failed,
+            format!(
+                "{} serves chain '{}' but transactions are signed for '{}'; \
+                 set chain.chain_id or use an endpoint of '{}'",
+                client.active_endpoint(),
+                id,
+                config.chain_id,
+                config.chain_id
+            ),
+        ),
+        None => report.push(
+            "chain_id",
+            CheckStatus::Warning,
+            "The endpoint did not report its chain ID".to_string(),
+        ),
+    }
+
+    match client.get_config().await {
+        Ok(contract) if contract.paused => report.push(
+            "contract",
+            CheckStatus::Failed,
+            format!(
+                "Contract {} is paused and rejects proofs",
+                config.contract_address
+            ),
+        ),
+        Ok(_) => report.push(
+            "contract",
+            CheckStatus::Passed,
+            config.contract_address.clone(),
+        ),
+        Err(RpcError::ContractQuery(message)) => report.push(
+            "contract",
+            CheckStatus::Failed,
+            format!(
+                "Contract {} did not answer the verifier's config query ({}); check --contract",
+                config.contract_address, message
+            ),
+        ),
+        Err(e) => report.push(
+            "contract",
+            CheckStatus::Warning,
+            format!("Could not query the contract config ({})", e),
+        ),
+    }
+
+    if let Some(signer) = signer {
+        let (status, detail) = check_account(client, signer).await;
+        report.push("account", status, detail);
+    }
+    report
+}
+
+/// Whether `signer` exists and can pay for a proof transaction
+async fn check_account(client: &RpcClient, signer: &str) -> (CheckStatus, String) {
+    let config = client.config();
+    match client.get_account(signer).await {
+        Ok(Some(_)) => {}
+        Ok(None) if !config.relay_url.is_empty() => {
+            return (
+                CheckStatus::Warning,
+                format!(
+                    "Account {} does not exist on chain yet; \
+                     its first proof goes through the relay",
+                    signer
+                ),
+            )
+        }
+        Ok(None) => {
+            return (
+                CheckStatus::Failed,
+                format!(
+                    "Account {} does not exist on {}: send it some {} first \
+                     (`uhash faucet` if chain.faucet_url is set), \
+                     or set chain.relay_url to a relay for first proofs",
+                    signer, config.chain_id, config.fee_denom
+                ),
+            )
+        }
+        Err(e) => {
+            return (
+                CheckStatus::Warning,
+                format!("Could not query account {} ({})", signer, e),
+            )
+        }
+    }
+
+    let fee = fee_for_gas(config, config.gas_limit.unwrap_or(SUBMIT_PROOF_GAS));
+    if fee == 0 {
+        return (CheckStatus::Passed, format!("{} (zero-fee)", signer));
+    }
+    if let Some(granter) = &config.fee_granter {
+        return match client.has_fee_allowance(granter, signer).await {
+            Ok(true) => (
+                CheckStatus::Passed,
+                format!("{} (fees granted by {})", signer, granter),
+            ),
+            Ok(false) => (
+                CheckStatus::Failed,
+                format!(
+                    "{} has granted {} no fee allowance; ask the granter for a grant \
+                     or drop --fee-granter",
+                    granter, signer
+                ),
+            ),
+            Err(e) => (
+                CheckStatus::Warning,
+                format!("Could not query the fee allowance from {} ({})", granter, e),
+            ),
+        };
+    }
+    match client.get_balance(signer, &config.fee_denom).await {
+        Ok(balance) if balance >= fee => (
+            CheckStatus::Passed,
+            format!("{} ({}{})", signer, balance, config.fee_denom),
+        ),
+        Ok(balance) => (
+            CheckStatus::Failed,
+            format!(
+                "{} holds {}{} but a proof transaction costs {}{}; \
+                 fund the account or use --fee-granter",
+                signer, balance, config.fee_denom, fee, config.fee_denom
+            ),
+        ),
+        Err(e) => (
+            CheckStatus::Warning,
+            format!("Could not query the {} balance ({})", config.fee_denom, e),
+        ),
+    }
+}
@@ -0,0 +1,96 @@
+//! Versions of the verifier contract's query responses
+//!
+//! Deployed contracts differ in field names; the mainnet contract reports
+//! `epoch_duration` where newer builds report `period_duration`. Rather than
+//! scattering serde aliases, the client detects the version once from the
+//! config reply and normalizes responses to the newest shape. The
+//! `submit_proof` message is the same in every version so far.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ConfigResponse, Result, RpcError};
+
+/// Response shapes of a verifier contract release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractSchema {
+    /// Mainnet release: `epoch_duration` in the config
+    V1,
+    /// `period_duration` and `target_proofs_per_window` in the config,
+    /// `created_at` in the seed reply
+    V2,
+}
+
+impl ContractSchema {
+    /// Newest schema this client understands, assumed for unrecognized replies
+    pub const LATEST: ContractSchema = ContractSchema::V2;
+
+    /// Identify the schema from the contract's config reply
+    pub fn detect(config: &Value) -> Self {
+        if config.get("epoch_duration").is_some() && config.get("period_duration").is_none() {
+            ContractSchema::V1
+        } else {
+            Self::LATEST
+        }
+    }
+
+    /// Parse a config reply, renaming fields to the newest shape first
+    pub fn parse_config(&self, mut config: Value) -> Result<ConfigResponse> {
+        if *self == ContractSchema::V1 {
+            if let Some(fields) = config.as_object_mut() {
+                if let Some(duration) = fields.remove("epoch_duration") {
+                    fields.insert("period_duration".to_string(), duration);
+                }
+            }
+        }
+        serde_json::from_value(config)
+            .map_err(|e| RpcError::InvalidResponse(format!("config response: {}", e)))
+    }
+}
+
+impl std::fmt::Display for ContractSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContractSchema::V1 => f.write_str("v1"),
+            ContractSchema::V2 => f.write_str("v2"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(duration_field: &str) -> Value {
+        json!({
+            "token_denom": "factory/bostrom1contract/li",
+            "difficulty": 16,
+            "base_reward": "1000000",
+            "max_proof_age": 3600,
+            duration_field: 600,
+            "admin": "bostrom1admin",
+            "paused": false,
+        })
+    }
+
+    #[test]
+    fn test_detect_and_normalize_v1() {
+        let raw = config("epoch_duration");
+        let schema = ContractSchema::detect(&raw);
+        assert_eq!(schema, ContractSchema::V1);
+        let parsed = schema.parse_config(raw).unwrap();
+        assert_eq!(parsed.period_duration, 600);
+        assert_eq!(parsed.target_proofs_per_window, None);
+    }
+
+    #[test]
+    fn test_unknown_shape_assumes_latest() {
+        assert_eq!(
+            ContractSchema::detect(&config("period_duration")),
+            ContractSchema::V2
+        );
+        assert_eq!(ContractSchema::detect(&json!({})), ContractSchema::LATEST);
+    }
+}
@@ -0,0 +1,93 @@
+//! In-process account sequence tracking
+//!
+//! The LCD reports the committed sequence, which lags behind transactions still
+//! in the mempool. Tracking the next sequence locally lets back-to-back proofs
+//! be signed without an account query each time and without reusing a sequence.
+
+use std::sync::Mutex;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct State {
+    account_number: Option<u64>,
+    next_sequence: Option<u64>,
+}
+
+/// Thread-safe tracker of an account's number and next unused sequence
+#[derive(Debug, Default)]
+pub struct SequenceManager {
+    state: Mutex<State>,
+}
+
+impl SequenceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // The state is two plain integers, so a poisoned lock is still consistent
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Account number and next sequence, if both are known
+    pub fn current(&self) -> Option<(u64, u64)> {
+        let state = self.lock();
+        state.account_number.zip(state.next_sequence)
+    }
+
+    /// Seed the tracker from an on-chain account query
+    pub fn set(&self, account_number: u64, sequence: u64) {
+        let mut state = self.lock();
+        state.account_number = Some(account_number);
+        state.next_sequence = Some(sequence);
+    }
+
+    /// Record that `sequence` was consumed by a broadcast transaction
+    ///
+    /// Never moves the counter backwards, so a late confirmation of an older
+    /// transaction cannot undo a newer one.
+    pub fn advance(&self, sequence: u64) {
+        let mut state = self.lock();
+        let next = sequence + 1;
+        if state.next_sequence.is_none_or(|current| current < next) {
+            state.next_sequence = Some(next);
+        }
+    }
+
+    /// Adopt the sequence the node reported, or forget it if unknown
+    pub fn resync(&self, expected: Option<u64>) {
+        self.lock().next_sequence = expected;
+    }
+
+    /// Forget the sequence so the next submission re-queries the account
+    pub fn invalidate(&self) {
+        self.lock().next_sequence = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_manager_lifecycle() {
+        let manager = SequenceManager::new();
+        assert_eq!(manager.current(), None);
+
+        manager.set(42, 7);
+        assert_eq!(manager.current(), Some((42, 7)));
+
+        manager.advance(7);
+        manager.advance(8);
+        assert_eq!(manager.current(), Some((42, 9)));
+
+        // An older broadcast finishing late does not rewind the counter
+        manager.advance(7);
+        assert_eq!(manager.current(), Some((42, 9)));
+
+        manager.resync(Some(5));
+        assert_eq!(manager.current(), Some((42, 5)));
+
+        manager.invalidate();
+        assert_eq!(manager.current(), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! Live feed of proofs accepted by the verifier contract
+//!
+//! Subscribes on the Tendermint RPC websocket (`/websocket` on the RPC
+//! endpoint, not the LCD) to transactions with a `wasm` event from the
+//! contract, and yields a [`ProofEvent`] for every accepted proof on the
+//! network. Events are read from the notification's `TxResult`, which CometBFT
+//! 0.37+ sends with plain-text attributes.
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::{proof_events, ProofEvent, Result, RpcError, TxEvent};
+
+/// JSON-RPC id of the subscribe request
+const SUBSCRIBE_ID: u64 = 1;
+
+/// Websocket URL of a Tendermint RPC endpoint
+pub fn websocket_url(rpc_url: &str) -> Result<String> {
+    let mut url = reqwest::Url::parse(rpc_url)
+        .map_err(|e| RpcError::Config(format!("invalid RPC URL '{}': {}", rpc_url, e)))?;
+    let scheme = match url.scheme() {
+        "http" | "ws" => "ws",
+        "https" | "wss" => "wss",
+        other => {
+            return Err(RpcError::Config(format!(
+                "unsupported RPC URL scheme '{}' in '{}'",
+                other, rpc_url
+            )))
+        }
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| RpcError::Config(format!("invalid RPC URL '{}'", rpc_url)))?;
+    if !url.path().ends_with("/websocket") {
+        let path = format!("{}/websocket", url.path().trim_end_matches('/'));
+        url.set_path(&path);
+    }
+    Ok(url.to_string())
+}
+
+/// Stream of proofs accepted by a contract, as they are committed
+///
+/// Ends when the node closes the connection; subscribe again to resume.
+/// Proofs committed while disconnected are not replayed.
+pub struct ProofSubscription {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    contract: String,
+    pending: VecDeque<ProofEvent>,
+}
+
+impl ProofSubscription {
+    /// Connect to `rpc_url` and subscribe to proofs accepted by `contract`
+    pub async fn connect(rpc_url: &str, contract: &str) -> Result<Self> {
+        let url = websocket_url(rpc_url)?;
+        let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| RpcError::Network(format!("failed to connect to {}: {}", url, e)))?;
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": "subscribe",
+            "id": SUBSCRIBE_ID,
+            "params": {
+                "query": format!("tm.event='Tx' AND wasm._contract_address='{}'", contract),
+            },
+        });
+        socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(|e| RpcError::Network(e.to_string()))?;
+
+        // The node acknowledges with an empty result before any notification
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    parse_notification(&text, contract)?;
+                    break;
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err(RpcError::Network(
+                        "connection closed before the subscription was confirmed".to_string(),
+                    ))
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(RpcError::Network(e.to_string())),
+            }
+        }
+
+        Ok(Self {
+            socket,
+            contract: contract.to_string(),
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+impl Stream for ProofSubscription {
+    type Item = Result<ProofEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            let text = match self.socket.poll_next_unpin(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) | Poll::Ready(Some(Ok(Message::Close(_)))) => {
+                    return Poll::Ready(None)
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    return Poll::Ready(Some(Err(RpcError::Network(e.to_string()))))
+                }
+                Poll::Ready(Some(Ok(Message::Text(text)))) => text,
+                Poll::Ready(Some(Ok(_))) => continue,
+            };
+            match parse_notification(&text, &self.contract) {
+                Ok(events) => self.pending.extend(events),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+    }
+}
+
+/// Proofs in a JSON-RPC message from the websocket (none for the ack)
+fn parse_notification(text: &str, contract: &str) -> Result<Vec<ProofEvent>> {
+    let message: Value = serde_json::from_str(text)?;
+    if let Some(error) = message.get("error") {
+        let detail = error
+            .get("data")
+            .or_else(|| error.get("message"))
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| v.to_string())
+            })
+            .unwrap_or_else(|| error.to_string());
+        return Err(RpcError::InvalidResponse(format!(
+            "subscription failed: {}",
+            detail
+        )));
+    }
+
+    let result = &message["result"];
+    let tx_result = &result["data"]["value"]["TxResult"];
+    if tx_result.is_null() {
+        return Ok(Vec::new());
+    }
+    let height = match &tx_result["height"] {
+        Value::String(s) => s.parse().ok(),
+        other => other.as_u64(),
+    }
+    .ok_or_else(|| RpcError::InvalidResponse("tx notification without height".to_string()))?;
+    let tx_hash = result["events"]["tx.hash"][0]
+        .as_str()
+        .ok_or_else(|| RpcError::InvalidResponse("tx notification without tx.hash".to_string()))?;
+    let events: Vec<TxEvent> = match tx_result["result"].get("events") {
+        Some(events) => serde_json::from_value(events.clone())?,
+        None => Vec::new(),
+    };
+    Ok(proof_events(&events, contract, tx_hash, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "bostrom1qwys5wj3r4lry7dl74ukn5unhdpa6t397h097q36dqvrp5qgvjxqverdlf";
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("https://rpc.bostrom.cybernode.ai").unwrap(),
+            "wss://rpc.bostrom.cybernode.ai/websocket"
+        );
+        assert_eq!(
+            websocket_url("http://127.0.0.1:26657/").unwrap(),
+            "ws://127.0.0.1:26657/websocket"
+        );
+        assert_eq!(
+            websocket_url("wss://node.example/rpc/websocket").unwrap(),
+            "wss://node.example/rpc/websocket"
+        );
+        assert!(matches!(
+            websocket_url("ftp://node.example"),
+            Err(RpcError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_notification() {
+        assert!(
+            parse_notification(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#, CONTRACT)
+                .unwrap()
+                .is_empty()
+        );
+
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "query": "tm.event='Tx'",
+                "data": {
+                    "type": "tendermint/event/Tx",
+                    "value": { "TxResult": {
+                        "height": "1234",
+                        "tx": "",
+                        "result": { "events": [
+                            { "type": "wasm", "attributes": [
+                                { "key": "_contract_address", "value": CONTRACT, "index": true },
+                                { "key": "action", "value": "submit_proof", "index": true },
+                                { "key": "miner", "value": "bostrom1miner", "index": true },
+                                { "key": "reward", "value": "1000000", "index": true }
+                            ]}
+                        ]}
+                    }}
+                },
+                "events": { "tx.hash": ["ABC123"], "tx.height": ["1234"] }
+            }
+        });
+        let proofs = parse_notification(&notification.to_string(), CONTRACT).unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].tx_hash, "ABC123");
+        assert_eq!(proofs[0].height, 1234);
+        assert_eq!(proofs[0].miner, "bostrom1miner");
+        assert_eq!(proofs[0].reward.as_deref(), Some("1000000"));
+    }
+
+    #[test]
+    fn test_subscription_error() {
+        let reply = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"Internal error","data":"max_subscriptions_per_client 5 reached"}}"#;
+        let err = parse_notification(reply, CONTRACT).unwrap_err();
+        assert!(err.to_string().contains("max_subscriptions_per_client"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Following broadcast proof transactions until they are final
+//!
+//! A `sync` or `async` broadcast returns once a node's mempool took the
+//! transaction. It can still be evicted before it reaches a block (a full
+//! mempool, a node restart, a conflicting sequence), and a block it landed in
+//! can be dropped by a reorg or turn out to exist only on an endpoint the
+//! client failed over from. A [`TxTracker`] looks each transaction up until
+//! it is `finality_depth` blocks deep and hands back the proofs of one that
+//! vanished, so they can be submitted again while the contract still takes
+//! them.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use super::{ProofSubmission, Result, RpcClient, TxInfo};
+
+/// Blocks built on top of a transaction's block before it counts as final,
+/// by default: CometBFT blocks are final once committed
+pub const DEFAULT_FINALITY_DEPTH: u64 = 0;
+
+/// How often tracked transactions are looked up
+pub const TRACK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A broadcast transaction not yet final
+#[derive(Debug, Clone)]
+struct TrackedTx {
+    tx_hash: String,
+    /// Proofs the transaction carries
+    proofs: Vec<ProofSubmission>,
+    broadcast_at: Instant,
+    /// Block it was last seen in
+    height: Option<u64>,
+}
+
+/// What became of a tracked transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TxStatus {
+    /// In a block, not yet final
+    Included { height: u64 },
+    /// Deep enough to stay; `success` is false if the contract rejected it
+    Finalized { height: u64, success: bool },
+    /// Never reached a block within the eviction timeout
+    Evicted,
+    /// Was in block `height`, which is no longer part of the chain
+    Reorged { height: u64 },
+}
+
+impl TxStatus {
+    /// Whether the transaction's proofs were dropped and may be sent again
+    pub fn is_dropped(&self) -> bool {
+        matches!(self, TxStatus::Evicted | TxStatus::Reorged { .. })
+    }
+}
+
+/// A change in a tracked transaction's status
+#[derive(Debug, Clone)]
+pub struct TxUpdate {
+    pub tx_hash: String,
+    pub status: TxStatus,
+    /// Proofs the transaction carries
+    pub proofs: Vec<ProofSubmission>,
+}
+
+/// Broadcast transactions waiting to be final
+#[derive(Debug)]
+pub struct TxTracker {
+    pending: Vec<TrackedTx>,
+    /// How long a transaction may take to reach a block
+    evict_after: Duration,
+    finality_depth: u64,
+}
+
+impl TxTracker {
+    /// A tracker that gives up on a transaction not in a block after
+    /// `evict_after`, and lets one go once `finality_depth` blocks are on top
+    pub fn new(evict_after: Duration, finality_depth: u64) -> Self {
+        Self {
+            pending: Vec::new(),
+            evict_after,
+            finality_depth,
+        }
+    }
+
+    /// Follow a transaction that was just broadcast
+    pub fn track(&mut self, tx_hash: &str, proofs: Vec<ProofSubmission>) {
+        self.pending.push(TrackedTx {
+            tx_hash: tx_hash.to_string(),
+            proofs,
+            broadcast_at: Instant::now(),
+            height: None,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Time by which every tracked transaction should have reached a block
+    /// and, allowing as long again, become final; `None` if none is tracked
+    pub fn settle_by(&self) -> Option<Instant> {
+        self.pending
+            .iter()
+            .map(|tracked| tracked.broadcast_at + self.evict_after * 2)
+            .max()
+    }
+
+    /// Look up every tracked transaction, returning the ones whose status
+    /// changed; final and dropped ones are no longer tracked
+    pub async fn poll(&mut self, client: &RpcClient) -> Result<Vec<TxUpdate>> {
+        if self.pending.is_empty() {
+            return Ok(Vec::new());
+        }
+        let latest = client.latest_height().await?;
+        let mut updates = Vec::new();
+        let mut kept = Vec::new();
+        let mut remaining = std::mem::take(&mut self.pending).into_iter();
+        while let Some(mut tracked) = remaining.next() {
+            let confirmation = match client.get_tx_confirmation(&tracked.tx_hash).await {
+                Ok(confirmation) => confirmation,
+                Err(e) => {
+                    // Keep what is left for the next poll
+                    kept.push(tracked);
+                    kept.extend(remaining);
+                    self.pending = kept;
+                    return Err(e);
+                }
+            };
+            let status = self.step(&mut tracked, confirmation.as_ref(), latest, Instant::now());
+            let done = matches!(
+                status,
+                Some(TxStatus::Finalized { .. } | TxStatus::Evicted | TxStatus::Reorged { .. })
+            );
+            if let Some(status) = status {
+                updates.push(TxUpdate {
+                    tx_hash: tracked.tx_hash.clone(),
+                    status,
+                    proofs: tracked.proofs.clone(),
+                });
+            }
+            if !done {
+                kept.push(tracked);
+            }
+        }
+        self.pending = kept;
+        Ok(updates)
+    }
+
+    /// Status change of `tracked` given its lookup and the latest height
+    fn step(
+        &self,
+        tracked: &mut TrackedTx,
+        confirmation: Option<&TxInfo>,
+        latest: u64,
+        now: Instant,
+    ) -> Option<TxStatus> {
+        match (confirmation, tracked.height) {
+            (Some(info), seen) => {
+                tracked.height = Some(info.height);
+                if latest >= info.height + self.finality_depth {
+                    Some(TxStatus::Finalized {
+                        height: info.height,
+                        success: info.is_success(),
+                    })
+                } else if seen != Some(info.height) {
+                    Some(TxStatus::Included {
+                        height: info.height,
+                    })
+                } else {
+                    None
+                }
+            }
+            (None, Some(height)) => Some(TxStatus::Reorged { height }),
+            (None, None) if now.duration_since(tracked.broadcast_at) >= self.evict_after => {
+                Some(TxStatus::Evicted)
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(height: u64, code: u32) -> TxInfo {
+        TxInfo {
+            tx_hash: "AB".to_string(),
+            height,
+            code,
+            raw_log: String::new(),
+            gas_wanted: 0,
+            gas_used: 0,
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_included_then_final_after_depth() {
+        let tracker = TxTracker::new(Duration::from_secs(60), 2);
+        let mut tx = TrackedTx {
+            tx_hash: "AB".to_string(),
+            proofs: Vec::new(),
+            broadcast_at: Instant::now(),
+            height: None,
+        };
+        let now = Instant::now();
+
+        assert_eq!(tracker.step(&mut tx, None, 9, now), None);
+        assert_eq!(
+            tracker.step(&mut tx, Some(&info(10, 0)), 10, now),
+            Some(TxStatus::Included { height: 10 })
+        );
+        assert_eq!(tracker.step(&mut tx, Some(&info(10, 0)), 11, now), None);
+        assert_eq!(
+            tracker.step(&mut tx, Some(&info(10, 5)), 12, now),
+            Some(TxStatus::Finalized {
+                height: 10,
+                success: false
+            })
+        );
+    }
+
+    #[test]
+    fn test_evicted_and_reorged() {
+        let tracker = TxTracker::new(Duration::from_secs(30), 2);
+        let broadcast_at = Instant::now();
+        let mut tx = TrackedTx {
+            tx_hash: "AB".to_string(),
+            proofs: Vec::new(),
+            broadcast_at,
+            height: None,
+        };
+
+        assert_eq!(tracker.step(&mut tx, None, 10, broadcast_at), None);
+        let later = broadcast_at + Duration::from_secs(30);
+        assert_eq!(
+            tracker.step(&mut tx, None, 16, later),
+            Some(TxStatus::Evicted)
+        );
+
+        // Seen in a block, then gone
+        tracker.step(&mut tx, Some(&info(17, 0)), 17, later);
+        let status = tracker.step(&mut tx, None, 18, later).unwrap();
+        assert_eq!(status, TxStatus::Reorged { height: 17 });
+        assert!(status.is_dropped());
+    }
+}
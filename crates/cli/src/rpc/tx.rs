@@ -0,0 +1,463 @@
+//! Transaction construction and the offline signing file format
+//!
+//! [`sign_proof_tx`] needs no network access: given the account number and
+//! sequence it produces fully signed `tx_bytes`. A [`SignedTx`] wraps those
+//! bytes with enough context to be reviewed and broadcast from another machine
+//! (`uhash sign-proof --output tx.json` then `uhash broadcast tx.json`).
+
+use super::{Result, RpcError};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::{ExecuteMsg, ProofSubmission, RpcConfig};
+
+/// Default gas limit for a `submit_proof` transaction
+pub const SUBMIT_PROOF_GAS: u64 = 1_600_000;
+
+/// Default multiplier applied to simulated gas usage
+pub const DEFAULT_GAS_ADJUSTMENT: f64 = 1.3;
+
+/// Fee denom on Bostrom (other chains set `RpcConfig::fee_denom`)
+pub const FEE_DENOM: &str = "boot";
+
+/// Maximum memo length accepted by the chain (`x/auth` `MaxMemoCharacters`)
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// `ErrWrongSequence` code from the Cosmos SDK `sdkerrors` codespace
+pub const SEQUENCE_MISMATCH_CODE: u32 = 32;
+
+/// A signed proof transaction ready to broadcast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTx {
+    /// Chain the transaction was signed for
+    pub chain_id: String,
+    /// Contract the proof is submitted to
+    pub contract_address: String,
+    /// Signer account number used in the sign doc
+    pub account_number: u64,
+    /// Signer sequence used in the sign doc
+    pub sequence: u64,
+    /// The proof carried by the transaction
+    pub proof: ProofSubmission,
+    /// Base64-encoded protobuf `TxRaw`
+    pub tx_bytes: String,
+}
+
+impl SignedTx {
+    /// Decode the raw transaction bytes
+    pub fn decode_tx_bytes(&self) -> Result<Vec<u8>> {
+        STANDARD
+            .decode(&self.tx_bytes)
+            .map_err(|e| RpcError::InvalidTx(format!("Invalid tx_bytes: {}", e)))
+    }
+}
+
+/// Parse a `--gas` value: `auto` (simulate) or a fixed gas limit
+pub fn parse_gas(value: &str) -> Result<Option<u64>> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(None);
+    }
+    value.parse().map(Some).map_err(|_| {
+        RpcError::Config(format!(
+            "Invalid gas '{}': expected 'auto' or a number",
+            value
+        ))
+    })
+}
+
+/// Parse a `--gas-prices` value such as `0.01boot` (the denom suffix is optional)
+pub fn parse_gas_price(value: &str, denom: &str) -> Result<f64> {
+    let amount = value.strip_suffix(denom).unwrap_or(value);
+    match amount.parse::<f64>() {
+        Ok(price) if price.is_finite() && price >= 0.0 => Ok(price),
+        _ => Err(RpcError::Config(format!(
+            "Invalid gas price '{}': expected an amount in {} such as 0.01{}",
+            value, denom, denom
+        ))),
+    }
+}
+
+/// Fee for a gas limit: `gas * gas_price` rounded up, or the flat `fee_amount`
+pub fn fee_for_gas(config: &RpcConfig, gas_limit: u64) -> u128 {
+    match config.gas_price {
+        Some(price) => (gas_limit as f64 * price).ceil() as u128,
+        None => config.fee_amount,
+    }
+}
+
+/// Build and sign a `submit_proof` transaction without touching the network
+///
+/// Uses the configured gas limit, or [`SUBMIT_PROOF_GAS`] when the config asks
+/// for simulation (which needs a node, see `RpcClient::simulate_gas`).
+pub fn sign_proof_tx(
+    config: &RpcConfig,
+    proof: &ProofSubmission,
+    signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    account_number: u64,
+    sequence: u64,
+) -> Result<SignedTx> {
+    let gas_limit = config.gas_limit.unwrap_or(SUBMIT_PROOF_GAS);
+    sign_proof_tx_with_gas(
+        config,
+        proof,
+        signing_key,
+        account_number,
+        sequence,
+        gas_limit,
+    )
+}
+
+/// Build and sign a `submit_proof` transaction with an explicit gas limit
+pub fn sign_proof_tx_with_gas(
+    config: &RpcConfig,
+    proof: &ProofSubmission,
+    signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    account_number: u64,
+    sequence: u64,
+    gas_limit: u64,
+) -> Result<SignedTx> {
+    let tx_bytes = sign_batch_tx_with_gas(
+        config,
+        std::slice::from_ref(proof),
+        signing_key,
+        account_number,
+        sequence,
+        gas_limit,
+    )?;
+    Ok(SignedTx {
+        chain_id: config.chain_id.clone(),
+        contract_address: config.contract_address.clone(),
+        account_number,
+        sequence,
+        proof: proof.clone(),
+        tx_bytes: STANDARD.encode(tx_bytes),
+    })
+}
+
+/// Gas limit for a transaction carrying `proofs` proofs: the configured limit,
+/// or [`SUBMIT_PROOF_GAS`] when simulating, per proof
+pub fn batch_gas_limit(config: &RpcConfig, proofs: usize) -> u64 {
+    config
+        .gas_limit
+        .unwrap_or(SUBMIT_PROOF_GAS)
+        .saturating_mul(proofs.max(1) as u64)
+}
+
+/// Build and sign one transaction with a `submit_proof` message per proof,
+/// returning the raw `TxRaw` bytes
+///
+/// The messages execute atomically: if the contract rejects one proof, none
+/// of them is accepted.
+pub fn sign_batch_tx_with_gas(
+    config: &RpcConfig,
+    proofs: &[ProofSubmission],
+    signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    account_number: u64,
+    sequence: u64,
+    gas_limit: u64,
+) -> Result<Vec<u8>> {
+    use cosmrs::cosmwasm::MsgExecuteContract;
+    use cosmrs::tx::{Body, Fee, Msg, SignDoc, SignerInfo};
+    use cosmrs::{AccountId, Coin};
+
+    if proofs.is_empty() {
+        return Err(RpcError::InvalidTx("No proofs to submit".to_string()));
+    }
+    if config.memo.chars().count() > MAX_MEMO_LEN {
+        return Err(RpcError::InvalidTx(format!(
+            "Memo is longer than {} characters",
+            MAX_MEMO_LEN
+        )));
+    }
+
+    let contract: AccountId = config
+        .contract_address
+        .parse()
+        .map_err(|e| RpcError::InvalidTx(format!("Invalid contract address: {}", e)))?;
+
+    let mut messages = Vec::with_capacity(proofs.len());
+    for proof in proofs {
+        // Parse addresses: the signer sends, on its own behalf or for another miner
+        let miner: AccountId = proof
+            .miner_address
+            .parse()
+            .map_err(|e| RpcError::InvalidTx(format!("Invalid miner address: {}", e)))?;
+        let sender = signer_address(signing_key, miner.prefix())?;
+
+        // Build execute message (miner_address=None: sender is the miner)
+        let execute_msg = ExecuteMsg::SubmitProof {
+            hash: proof.hash.clone(),
+            nonce: proof.nonce,
+            timestamp: proof.timestamp,
+            miner_address: (sender != miner).then(|| miner.to_string()),
+            worker_tag: proof.worker_tag.clone(),
+        };
+
+        // Build MsgExecuteContract and convert to Any
+        let msg = MsgExecuteContract {
+            sender,
+            contract: contract.clone(),
+            msg: serde_json::to_vec(&execute_msg)?,
+            funds: vec![],
+        };
+        messages.push(
+            msg.to_any()
+                .map_err(|e| RpcError::InvalidTx(format!("Failed to convert message: {}", e)))?,
+        );
+    }
+
+    // Build transaction body
+    let body = Body::new(messages, config.memo.as_str(), 0u32);
+
+    // Build auth info with fee (default 0 for Bostrom zero-fee transactions)
+    let denom: cosmrs::Denom = config
+        .fee_denom
+        .parse()
+        .map_err(|e| RpcError::InvalidTx(format!("Invalid denom: {}", e)))?;
+    let mut fee = Fee::from_amount_and_gas(
+        Coin {
+            denom,
+            amount: fee_for_gas(config, gas_limit),
+        },
+        gas_limit,
+    );
+    fee.granter = parse_optional_account(config.fee_granter.as_deref(), "fee granter")?;
+    fee.payer = parse_optional_account(config.fee_payer.as_deref(), "fee payer")?;
+    // The transaction carries one signature, and the chain wants one from the payer too
+    if let Some(payer) = &fee.payer {
+        let signer = signer_address(signing_key, payer.prefix())?;
+        if *payer != signer {
+            return Err(RpcError::InvalidTx(format!(
+                "Fee payer {} is not the signer {}; another account can pay through a fee grant",
+                payer, signer
+            )));
+        }
+    }
+
+    let signer_info = SignerInfo::single_direct(Some(signing_key.public_key()), sequence);
+    let auth_info = signer_info.auth_info(fee);
+
+    // Build sign doc
+    let chain_id: cosmrs::tendermint::chain::Id = config
+        .chain_id
+        .parse()
+        .map_err(|e| RpcError::InvalidTx(format!("Invalid chain ID: {}", e)))?;
+    let sign_doc = SignDoc::new(&body, &auth_info, &chain_id, account_number)
+        .map_err(|e| RpcError::InvalidTx(format!("Failed to create sign doc: {}", e)))?;
+
+    // Sign
+    let tx_signed = sign_doc
+        .sign(signing_key)
+        .map_err(|e| RpcError::InvalidTx(format!("Failed to sign transaction: {}", e)))?;
+    tx_signed
+        .to_bytes()
+        .map_err(|e| RpcError::InvalidTx(format!("Failed to serialize transaction: {}", e)))
+}
+
+/// Address of the account `signing_key` signs for, under `prefix`
+pub fn signer_address(
+    signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    prefix: &str,
+) -> Result<cosmrs::AccountId> {
+    signing_key
+        .public_key()
+        .account_id(prefix)
+        .map_err(|e| RpcError::InvalidTx(format!("Invalid signer address: {}", e)))
+}
+
+fn parse_optional_account(address: Option<&str>, role: &str) -> Result<Option<cosmrs::AccountId>> {
+    address
+        .map(|a| {
+            a.parse()
+                .map_err(|e| RpcError::InvalidTx(format!("Invalid {} address: {}", role, e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn test_sign_proof_tx_offline() {
+        let wallet = Wallet::new().unwrap();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let proof = ProofSubmission {
+            hash: "00".repeat(32),
+            nonce: 42,
+            timestamp: 1_700_000_000,
+            miner_address: wallet.address_str(),
+            worker_tag: None,
+        };
+
+        let signed = sign_proof_tx(&RpcConfig::default(), &proof, &signing_key, 7, 3).unwrap();
+        assert_eq!(signed.account_number, 7);
+        assert_eq!(signed.sequence, 3);
+
+        // Survives the JSON file roundtrip and decodes to a parseable transaction
+        let file = serde_json::to_string_pretty(&signed).unwrap();
+        let restored: SignedTx = serde_json::from_str(&file).unwrap();
+        let tx = cosmrs::Tx::from_bytes(&restored.decode_tx_bytes().unwrap()).unwrap();
+        assert_eq!(tx.auth_info.signer_infos[0].sequence, 3);
+        assert_eq!(tx.body.messages.len(), 1);
+        assert!(tx.auth_info.fee.granter.is_none());
+    }
+
+    #[test]
+    fn test_sign_proof_tx_for_another_miner() {
+        let relayer = Wallet::new().unwrap();
+        let miner = Wallet::new().unwrap().address_str();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &relayer.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let proof = ProofSubmission {
+            hash: "00".repeat(32),
+            nonce: 9,
+            timestamp: 1_700_000_000,
+            miner_address: miner.clone(),
+            worker_tag: None,
+        };
+
+        let signed = sign_proof_tx(&RpcConfig::default(), &proof, &signing_key, 0, 0).unwrap();
+        let tx = cosmrs::Tx::from_bytes(&signed.decode_tx_bytes().unwrap()).unwrap();
+        let msg = <cosmrs::cosmwasm::MsgExecuteContract as cosmrs::tx::Msg>::from_any(
+            &tx.body.messages[0],
+        )
+        .unwrap();
+        assert_eq!(msg.sender.to_string(), relayer.address_str());
+        let execute: serde_json::Value = serde_json::from_slice(&msg.msg).unwrap();
+        assert_eq!(execute["submit_proof"]["miner_address"], miner);
+    }
+
+    #[test]
+    fn test_sign_batch_tx_one_message_per_proof() {
+        let wallet = Wallet::new().unwrap();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let proofs: Vec<ProofSubmission> = (0..3)
+            .map(|nonce| ProofSubmission {
+                hash: "00".repeat(32),
+                nonce,
+                timestamp: 1_700_000_000,
+                miner_address: wallet.address_str(),
+                worker_tag: None,
+            })
+            .collect();
+        let config = RpcConfig::default();
+
+        let gas_limit = batch_gas_limit(&config, proofs.len());
+        assert_eq!(gas_limit, 3 * config.gas_limit.unwrap());
+        let tx_bytes =
+            sign_batch_tx_with_gas(&config, &proofs, &signing_key, 7, 3, gas_limit).unwrap();
+        let tx = cosmrs::Tx::from_bytes(&tx_bytes).unwrap();
+        assert_eq!(tx.body.messages.len(), 3);
+        assert_eq!(tx.auth_info.fee.gas_limit, gas_limit);
+        let nonces: Vec<u64> = tx
+            .body
+            .messages
+            .iter()
+            .map(|any| {
+                let msg = <cosmrs::cosmwasm::MsgExecuteContract as cosmrs::tx::Msg>::from_any(any)
+                    .unwrap();
+                let execute: serde_json::Value = serde_json::from_slice(&msg.msg).unwrap();
+                execute["submit_proof"]["nonce"].as_u64().unwrap()
+            })
+            .collect();
+        assert_eq!(nonces, vec![0, 1, 2]);
+
+        assert!(sign_batch_tx_with_gas(&config, &[], &signing_key, 7, 3, gas_limit).is_err());
+    }
+
+    #[test]
+    fn test_parse_gas_settings() {
+        assert_eq!(parse_gas("auto").unwrap(), None);
+        assert_eq!(parse_gas("250000").unwrap(), Some(250_000));
+        assert!(parse_gas("lots").is_err());
+
+        assert_eq!(parse_gas_price("0.01boot", "boot").unwrap(), 0.01);
+        assert_eq!(parse_gas_price("0.5", "boot").unwrap(), 0.5);
+        assert_eq!(parse_gas_price("0.025uatom", "uatom").unwrap(), 0.025);
+        assert!(parse_gas_price("0.01uatom", "boot").is_err());
+        assert!(parse_gas_price("-1", "boot").is_err());
+
+        let config = RpcConfig {
+            gas_price: Some(0.01),
+            ..RpcConfig::default()
+        };
+        assert_eq!(fee_for_gas(&config, 250_001), 2501);
+        assert_eq!(fee_for_gas(&RpcConfig::default(), 250_001), 0);
+    }
+
+    #[test]
+    fn test_sign_proof_tx_with_fee_granter() {
+        let wallet = Wallet::new().unwrap();
+        let sponsor = Wallet::new().unwrap().address_str();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let proof = ProofSubmission {
+            hash: "00".repeat(32),
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            miner_address: wallet.address_str(),
+            worker_tag: None,
+        };
+        let config = RpcConfig {
+            fee_granter: Some(sponsor.clone()),
+            memo: "worker-07".to_string(),
+            ..RpcConfig::default()
+        };
+
+        let signed = sign_proof_tx(&config, &proof, &signing_key, 0, 0).unwrap();
+        let tx = cosmrs::Tx::from_bytes(&signed.decode_tx_bytes().unwrap()).unwrap();
+        assert_eq!(tx.auth_info.fee.granter.unwrap().to_string(), sponsor);
+        assert!(tx.auth_info.fee.payer.is_none());
+        assert_eq!(tx.body.memo, "worker-07");
+    }
+
+    #[test]
+    fn test_sign_proof_tx_fee_payer_must_be_signer() {
+        let wallet = Wallet::new().unwrap();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let proof = ProofSubmission {
+            hash: "00".repeat(32),
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            miner_address: wallet.address_str(),
+            worker_tag: None,
+        };
+
+        let config = RpcConfig {
+            fee_payer: Some(wallet.address_str()),
+            ..RpcConfig::default()
+        };
+        let signed = sign_proof_tx(&config, &proof, &signing_key, 0, 0).unwrap();
+        let tx = cosmrs::Tx::from_bytes(&signed.decode_tx_bytes().unwrap()).unwrap();
+        assert_eq!(
+            tx.auth_info.fee.payer.unwrap().to_string(),
+            wallet.address_str()
+        );
+
+        // Its signature would be missing, so the chain would reject the transaction
+        let config = RpcConfig {
+            fee_payer: Some(Wallet::new().unwrap().address_str()),
+            ..RpcConfig::default()
+        };
+        assert!(matches!(
+            sign_proof_tx(&config, &proof, &signing_key, 0, 0),
+            Err(RpcError::InvalidTx(_))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+//! Listening servers shared by the devnet, dashboard, API, relay, fleet
+//! controller and proxy
+//!
+//! Each of them can serve on a listener the caller bound (to hand a socket
+//! over, or to bind before dropping privileges) or bind and serve in a
+//! background task. Binding happens before the task starts, so a port that is
+//! taken fails the call instead of the task, and binding port 0 reports the
+//! port the OS picked.
+
+use std::future::Future;
+use std::net::{SocketAddr, TcpListener};
+
+#[cfg(any(
+    feature = "api",
+    feature = "dashboard",
+    feature = "devnet",
+    feature = "relay"
+))]
+use axum::Router;
+
+/// Bind `addr` and run `serve` on the listener in a background task,
+/// returning the bound address
+pub(crate) fn spawn_listener<F, Fut, E>(addr: SocketAddr, serve: F) -> std::io::Result<SocketAddr>
+where
+    F: FnOnce(TcpListener) -> Fut,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Send + 'static,
+{
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    tokio::spawn(serve(listener));
+    Ok(local_addr)
+}
+
+/// Serve `router` on an already bound listener until the server fails
+#[cfg(any(
+    feature = "api",
+    feature = "dashboard",
+    feature = "devnet",
+    feature = "relay"
+))]
+pub(crate) async fn serve_router(listener: TcpListener, router: Router) -> anyhow::Result<()> {
+    listener.set_nonblocking(true)?;
+    axum::Server::from_tcp(listener)?
+        .serve(router.into_make_service())
+        .await?;
+    Ok(())
+}
+
+/// Bind `addr` and serve `router` in a background task, returning the bound
+/// address
+#[cfg(any(
+    feature = "api",
+    feature = "dashboard",
+    feature = "devnet",
+    feature = "relay"
+))]
+pub(crate) fn spawn_server(addr: SocketAddr, router: Router) -> anyhow::Result<SocketAddr> {
+    Ok(spawn_listener(addr, |listener| {
+        serve_router(listener, router)
+    })?)
+}
+
+/// Accept connections on an already bound listener, running `handle` on each
+/// in its own task, until accepting fails
+#[cfg(any(feature = "fleet", feature = "proxy"))]
+pub(crate) async fn accept_loop<F, Fut>(listener: TcpListener, handle: F) -> std::io::Result<()>
+where
+    F: Fn(tokio::net::TcpStream, SocketAddr) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        tokio::spawn(handle(stream, peer));
+    }
+}
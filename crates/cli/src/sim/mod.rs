@@ -0,0 +1,339 @@
+//! Difficulty retarget simulator
+//!
+//! Models the contract's retarget rule — one bit harder after a window with
+//! more than `target_proofs_per_window` proofs, one bit easier after a window
+//! with fewer than half of them — against a network hashrate scenario.
+//! Each window's proof count is drawn from the Poisson distribution a hashrate
+//! and difficulty imply, over many seeded runs, so `uhash simulate-difficulty`
+//! shows how quickly difficulty follows the network and how steady the proof
+//! cadence is for a given target.
+
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SimError {
+    #[error("Invalid hashrate '{0}' (expected a positive number of H/s)")]
+    InvalidHashrate(String),
+
+    #[error("The scenario needs at least one hashrate and one window")]
+    EmptyScenario,
+
+    #[error("At least one run is needed")]
+    NoRuns,
+}
+
+/// Difficulty after a window that accepted `proofs`, by the contract's rule
+pub fn retarget(difficulty: u32, proofs: u64, target_proofs: u64, min_difficulty: u32) -> u32 {
+    if proofs > target_proofs {
+        difficulty + 1
+    } else if proofs < target_proofs / 2 {
+        difficulty.saturating_sub(1).max(min_difficulty)
+    } else {
+        difficulty
+    }
+}
+
+/// Proofs a network is expected to find in one window
+pub fn expected_proofs(hashrate: f64, window_secs: u64, difficulty: u32) -> f64 {
+    hashrate * window_secs as f64 / 2f64.powi(difficulty as i32)
+}
+
+/// The difficulty the rule hovers around for a steady `hashrate`
+///
+/// The lowest difficulty (at least `min_difficulty`) that expects no more
+/// than the target, so an increase is no longer the likely outcome of a
+/// window. Random windows keep difficulty moving a bit or two around it.
+pub fn equilibrium_difficulty(
+    hashrate: f64,
+    window_secs: u64,
+    target_proofs: u64,
+    min_difficulty: u32,
+) -> u32 {
+    let mut difficulty = min_difficulty;
+    while difficulty < 256
+        && expected_proofs(hashrate, window_secs, difficulty) > target_proofs as f64
+    {
+        difficulty += 1;
+    }
+    difficulty
+}
+
+/// Contract parameters the simulation runs with
+#[derive(Debug, Clone, Serialize)]
+pub struct SimParams {
+    /// Difficulty of the first window
+    pub start_difficulty: u32,
+    pub min_difficulty: u32,
+    pub target_proofs_per_window: u64,
+    /// Seconds between seed rotations (and retargets)
+    pub window_secs: u64,
+    /// Monte Carlo runs to average over
+    pub runs: u32,
+    /// Seed of the random number generator, for repeatable results
+    pub seed: u64,
+}
+
+impl Default for SimParams {
+    fn default() -> Self {
+        Self {
+            start_difficulty: 8,
+            min_difficulty: 1,
+            target_proofs_per_window: 10,
+            window_secs: 600,
+            runs: 100,
+            seed: 1,
+        }
+    }
+}
+
+/// Network hashrate over the simulated windows
+///
+/// The hashrates are phases of equal length; with `ramp` the hashrate moves
+/// linearly from each one to the next instead of stepping.
+#[derive(Debug, Clone, Serialize)]
+pub struct Scenario {
+    /// H/s
+    pub hashrates: Vec<f64>,
+    pub ramp: bool,
+    pub windows: usize,
+}
+
+impl Scenario {
+    /// Parse comma-separated hashrates such as `1000,5k,2.5M`
+    pub fn parse(hashrates: &str, windows: usize, ramp: bool) -> Result<Self, SimError> {
+        let hashrates = hashrates
+            .split(',')
+            .map(|h| parse_hashrate(h.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if hashrates.is_empty() || windows == 0 {
+            return Err(SimError::EmptyScenario);
+        }
+        Ok(Self {
+            hashrates,
+            ramp,
+            windows,
+        })
+    }
+
+    /// Network hashrate during `window`
+    pub fn hashrate_at(&self, window: usize) -> f64 {
+        let phases = self.hashrates.len();
+        if phases == 1 {
+            return self.hashrates[0];
+        }
+        if self.ramp {
+            let last = self.windows.saturating_sub(1).max(1) as f64;
+            let position = window as f64 / last * (phases - 1) as f64;
+            let phase = (position.floor() as usize).min(phases - 2);
+            let fraction = position - phase as f64;
+            self.hashrates[phase] + (self.hashrates[phase + 1] - self.hashrates[phase]) * fraction
+        } else {
+            let phase = window * phases / self.windows;
+            self.hashrates[phase.min(phases - 1)]
+        }
+    }
+}
+
+fn parse_hashrate(text: &str) -> Result<f64, SimError> {
+    let invalid = || SimError::InvalidHashrate(text.to_string());
+    let (number, scale) = match text.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&text[..i], 1e3),
+        Some((i, 'M')) => (&text[..i], 1e6),
+        Some((i, 'G')) => (&text[..i], 1e9),
+        _ => (text, 1.0),
+    };
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(invalid());
+    }
+    Ok(value * scale)
+}
+
+/// One window across all runs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowStats {
+    pub window: usize,
+    /// Network H/s
+    pub hashrate: f64,
+    pub difficulty_mean: f64,
+    pub difficulty_min: u32,
+    pub difficulty_max: u32,
+    pub proofs_mean: f64,
+}
+
+/// Outcome of a simulation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimReport {
+    pub windows: Vec<WindowStats>,
+    /// Proofs per window over all windows and runs
+    pub proofs_mean: f64,
+    /// Windows that left difficulty unchanged (between half the target and the target)
+    pub on_target_fraction: f64,
+    /// Windows after which difficulty moved, per window
+    pub retarget_fraction: f64,
+    /// Equilibrium for the last window's hashrate
+    pub final_equilibrium: u32,
+}
+
+/// Run `params.runs` simulations of `scenario`
+pub fn simulate(params: &SimParams, scenario: &Scenario) -> Result<SimReport, SimError> {
+    if params.runs == 0 {
+        return Err(SimError::NoRuns);
+    }
+    if scenario.hashrates.is_empty() || scenario.windows == 0 {
+        return Err(SimError::EmptyScenario);
+    }
+
+    let runs = params.runs as usize;
+    let mut rng = Rng::new(params.seed);
+    let mut difficulties = vec![params.start_difficulty; runs];
+    let mut windows = Vec::with_capacity(scenario.windows);
+    let (mut total_proofs, mut on_target, mut retargets) = (0u64, 0u64, 0u64);
+    let target = params.target_proofs_per_window;
+
+    for window in 0..scenario.windows {
+        let hashrate = scenario.hashrate_at(window);
+        let mut stats = WindowStats {
+            window,
+            hashrate,
+            difficulty_mean: 0.0,
+            difficulty_min: u32::MAX,
+            difficulty_max: 0,
+            proofs_mean: 0.0,
+        };
+        let mut window_proofs = 0u64;
+        for difficulty in difficulties.iter_mut() {
+            stats.difficulty_mean += *difficulty as f64;
+            stats.difficulty_min = stats.difficulty_min.min(*difficulty);
+            stats.difficulty_max = stats.difficulty_max.max(*difficulty);
+
+            let proofs = rng.poisson(expected_proofs(hashrate, params.window_secs, *difficulty));
+            window_proofs += proofs;
+            let next = retarget(*difficulty, proofs, target, params.min_difficulty);
+            if next == *difficulty {
+                on_target += u64::from(proofs >= target / 2 && proofs <= target);
+            } else {
+                retargets += 1;
+            }
+            *difficulty = next;
+        }
+        stats.difficulty_mean /= runs as f64;
+        stats.proofs_mean = window_proofs as f64 / runs as f64;
+        total_proofs += window_proofs;
+        windows.push(stats);
+    }
+
+    let samples = (runs * scenario.windows) as f64;
+    let last_hashrate = scenario.hashrate_at(scenario.windows - 1);
+    Ok(SimReport {
+        windows,
+        proofs_mean: total_proofs as f64 / samples,
+        on_target_fraction: on_target as f64 / samples,
+        retarget_fraction: retargets as f64 / samples,
+        final_equilibrium: equilibrium_difficulty(
+            last_hashrate,
+            params.window_secs,
+            target,
+            params.min_difficulty,
+        ),
+    })
+}
+
+/// SplitMix64: small, seedable and good enough for sampling
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in (0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// Poisson sample: Knuth's method for small means, a normal approximation above
+    fn poisson(&mut self, mean: f64) -> u64 {
+        if mean <= 0.0 {
+            return 0;
+        }
+        if mean > 30.0 {
+            let normal = (-2.0 * self.next_f64().ln()).sqrt()
+                * (std::f64::consts::TAU * self.next_f64()).cos();
+            return (mean + mean.sqrt() * normal).round().max(0.0) as u64;
+        }
+        let limit = (-mean).exp();
+        let mut count = 0;
+        let mut product = self.next_f64();
+        while product > limit {
+            count += 1;
+            product *= self.next_f64();
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retarget_rule() {
+        assert_eq!(retarget(8, 11, 10, 1), 9);
+        assert_eq!(retarget(8, 10, 10, 1), 8);
+        assert_eq!(retarget(8, 5, 10, 1), 8);
+        assert_eq!(retarget(8, 4, 10, 1), 7);
+        assert_eq!(retarget(1, 0, 10, 1), 1);
+    }
+
+    #[test]
+    fn test_scenario_phases_and_ramp() {
+        let steps = Scenario::parse("1k, 4k", 10, false).unwrap();
+        assert_eq!(steps.hashrate_at(0), 1000.0);
+        assert_eq!(steps.hashrate_at(4), 1000.0);
+        assert_eq!(steps.hashrate_at(5), 4000.0);
+        assert_eq!(steps.hashrate_at(9), 4000.0);
+
+        let ramp = Scenario::parse("1000,2000,4000", 5, true).unwrap();
+        assert_eq!(ramp.hashrate_at(0), 1000.0);
+        assert_eq!(ramp.hashrate_at(1), 1500.0);
+        assert_eq!(ramp.hashrate_at(2), 2000.0);
+        assert_eq!(ramp.hashrate_at(4), 4000.0);
+
+        assert_eq!(
+            Scenario::parse("2.5M", 1, false).unwrap().hashrates,
+            [2.5e6]
+        );
+        for bad in ["", "fast", "-5", "0", "1k,,2k"] {
+            assert!(Scenario::parse(bad, 10, false).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_simulation_settles_at_equilibrium() {
+        // 1.6 kH/s over 600 s windows expects 10 proofs at 2^-17
+        let params = SimParams {
+            start_difficulty: 8,
+            ..SimParams::default()
+        };
+        assert_eq!(equilibrium_difficulty(1600.0, 600, 10, 1), 17);
+        let scenario = Scenario::parse("1600", 60, false).unwrap();
+        let report = simulate(&params, &scenario).unwrap();
+        assert_eq!(report.final_equilibrium, 17);
+        let last = report.windows.last().unwrap();
+        assert!((16.0..=18.0).contains(&last.difficulty_mean), "{:?}", last);
+        assert!(report.windows[0].proofs_mean > 1000.0);
+
+        // The same seed gives the same trajectory
+        assert_eq!(simulate(&params, &scenario).unwrap(), report);
+    }
+}
@@ -0,0 +1,637 @@
+//! Where found proofs go
+//!
+//! The mine loop hands every proof it finds to a [`ProofSink`], chosen by the
+//! `submit.sink` setting:
+//!
+//! - `contract` (default): submit to the verifier contract, relaying the first
+//!   proof of an account the chain does not know yet
+//! - `pool`: POST to a pool or relayer that takes the relay service's format
+//! - `relay`: POST to a `uhash-relay` with an ADR-036 authorization from the
+//!   miner, so the relayer pays for the transaction
+//! - `webhook`: POST a `proof_found` event to any HTTP endpoint
+//! - `file`: append one JSON line per proof to a file
+//! - `stdout`: print one JSON line per proof
+//!
+//! New destinations implement [`ProofSink`]; the mining code does not change.
+
+use async_trait::async_trait;
+use cosmrs::crypto::secp256k1::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::rpc::{signer_address, ProofSubmission, RpcClient, RpcError};
+use crate::wallet::{sign_arbitrary, StdSignature};
+
+/// Limit on a pool or webhook request
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a relayed first proof is given to create the account
+const RELAY_SETTLE_TIME: Duration = Duration::from_secs(7);
+
+#[derive(Error, Debug)]
+pub enum SinkError {
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+
+    #[error("{reason} (tx {tx_hash})")]
+    Rejected { tx_hash: String, reason: String },
+
+    #[error("Relay failed: {0}")]
+    Relay(String),
+
+    #[error("Could not write proof: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Request to {url} failed: {reason}")]
+    Http { url: String, reason: String },
+
+    #[error("Invalid submit settings: {0}")]
+    Config(String),
+
+    /// The transaction carrying this proof and others failed
+    #[error("{0}")]
+    Batch(String),
+}
+
+/// Destination of found proofs, as set by `submit.sink`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    #[default]
+    Contract,
+    Pool,
+    Relay,
+    Webhook,
+    File,
+    Stdout,
+}
+
+/// What became of a proof a sink accepted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    /// Accepted by the contract
+    Committed {
+        tx_hash: String,
+        reward: Option<String>,
+        reward_denom: Option<String>,
+        gas_used: Option<u64>,
+        /// Block the transaction landed in, once confirmed
+        height: Option<u64>,
+    },
+    /// Submitted on the miner's behalf by a relay or pool
+    Relayed { tx_hash: String },
+    /// Handed to an off-chain destination
+    Forwarded { destination: String },
+    /// Needs signing offline: the wallet is watch-only
+    Unsigned,
+}
+
+/// A destination for found proofs
+#[async_trait]
+pub trait ProofSink: Send + Sync {
+    /// Where proofs go, for messages (`contract`, a URL, a path)
+    fn destination(&self) -> String;
+
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError>;
+
+    /// Submit several proofs, returning what became of each in order
+    ///
+    /// One at a time unless the sink can do better, as the contract sink does
+    /// by putting them all in one transaction.
+    async fn submit_batch(&self, proofs: &[ProofSubmission]) -> Vec<Result<Delivery, SinkError>> {
+        submit_each(self, proofs).await
+    }
+}
+
+/// Hand `proofs` to `sink` one at a time
+async fn submit_each<S: ProofSink + ?Sized>(
+    sink: &S,
+    proofs: &[ProofSubmission],
+) -> Vec<Result<Delivery, SinkError>> {
+    let mut results = Vec::with_capacity(proofs.len());
+    for proof in proofs {
+        results.push(sink.submit(proof).await);
+    }
+    results
+}
+
+/// Submits proofs to the verifier contract
+pub struct ContractSink<'a> {
+    client: &'a RpcClient,
+    /// `None` for a watch-only wallet
+    signing_key: Option<SigningKey>,
+}
+
+impl<'a> ContractSink<'a> {
+    pub fn new(client: &'a RpcClient, signing_key: Option<SigningKey>) -> Self {
+        Self {
+            client,
+            signing_key,
+        }
+    }
+}
+
+#[async_trait]
+impl ProofSink for ContractSink<'_> {
+    fn destination(&self) -> String {
+        "contract".to_string()
+    }
+
+    /// A first proof from an account the chain does not know yet goes through
+    /// the relay service, since the account cannot pay for its own transaction
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError> {
+        if !self.client.account_exists(&proof.miner_address).await {
+            let tx_hash = self
+                .client
+                .relay_proof(proof)
+                .await
+                .map_err(|e| SinkError::Relay(e.to_string()))?;
+            // Let the relay transaction land so the account exists for the next proof
+            tokio::time::sleep(RELAY_SETTLE_TIME).await;
+            return Ok(Delivery::Relayed { tx_hash });
+        }
+        let Some(signing_key) = &self.signing_key else {
+            return Ok(Delivery::Unsigned);
+        };
+        let result = self.client.submit_proof(proof.clone(), signing_key).await?;
+        if !result.accepted {
+            return Err(SinkError::Rejected {
+                tx_hash: result.tx_hash,
+                reason: result
+                    .error
+                    .unwrap_or_else(|| "Transaction rejected".to_string()),
+            });
+        }
+        Ok(Delivery::Committed {
+            tx_hash: result.tx_hash,
+            reward: result.reward,
+            reward_denom: result.reward_denom,
+            gas_used: result.gas_used,
+            height: result.height,
+        })
+    }
+
+    /// Proofs of an account that exists go in one transaction, which
+    /// succeeds or fails as a whole; the first proof's delivery carries the
+    /// reward and gas of the batch
+    async fn submit_batch(&self, proofs: &[ProofSubmission]) -> Vec<Result<Delivery, SinkError>> {
+        let (Some(signing_key), [first, _, ..]) = (&self.signing_key, proofs) else {
+            return submit_each(self, proofs).await;
+        };
+        if proofs
+            .iter()
+            .any(|proof| proof.miner_address != first.miner_address)
+            || !self.client.account_exists(&first.miner_address).await
+        {
+            return submit_each(self, proofs).await;
+        }
+
+        let failed = |reason: String| {
+            proofs
+                .iter()
+                .map(|_| Err(SinkError::Batch(reason.clone())))
+                .collect()
+        };
+        let result = match self.client.submit_proofs(proofs, signing_key).await {
+            Ok(result) => result,
+            Err(e) => return failed(e.to_string()),
+        };
+        if !result.accepted {
+            let reason = result
+                .error
+                .unwrap_or_else(|| "Transaction rejected".to_string());
+            return failed(format!("{} (tx {})", reason, result.tx_hash));
+        }
+        (0..proofs.len())
+            .map(|i| {
+                Ok(Delivery::Committed {
+                    tx_hash: result.tx_hash.clone(),
+                    reward: result.reward.clone().filter(|_| i == 0),
+                    reward_denom: result.reward_denom.clone(),
+                    gas_used: result.gas_used.filter(|_| i == 0),
+                    height: result.height,
+                })
+            })
+            .collect()
+    }
+}
+
+/// POSTs proofs to a pool or relayer in the relay service's format
+///
+/// The body is `{hash, nonce, timestamp, miner_address}`; the reply is
+/// `{"ok": true, "tx_hash": ...}` or `{"ok": false, "error": ...}`.
+pub struct PoolSink {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl PoolSink {
+    pub fn new(url: &str) -> Result<Self, SinkError> {
+        Ok(Self {
+            url: url.to_string(),
+            http: http_client(url)?,
+        })
+    }
+}
+
+#[async_trait]
+impl ProofSink for PoolSink {
+    fn destination(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError> {
+        post_relay_format(&self.http, &self.url, proof).await
+    }
+}
+
+/// Body of a `uhash-relay` request: the proof and the miner's authorization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayRequest {
+    #[serde(flatten)]
+    pub proof: ProofSubmission,
+    /// ADR-036 signature by the miner over [`authorization_data`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<StdSignature>,
+}
+
+/// The data a miner signs to have a relayer submit `proof`
+///
+/// Compact JSON with sorted keys, so a browser can build the same bytes and
+/// sign them with a wallet's `signArbitrary`. For a lowercase hash these are
+/// the canonical JSON that [`crate::proof::Proof::sign`] signs.
+pub fn authorization_data(proof: &ProofSubmission) -> Vec<u8> {
+    // serde_json::json! sorts object keys
+    serde_json::json!({
+        "hash": proof.hash,
+        "miner_address": proof.miner_address,
+        "nonce": proof.nonce,
+        "timestamp": proof.timestamp,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// POSTs proofs to a `uhash-relay`, signed by the miner
+///
+/// The relayer checks the authorization and submits from its own account, so
+/// the miner needs no BOOT and no route to the chain for submission.
+pub struct RelaySink {
+    url: String,
+    http: reqwest::Client,
+    signing_key: SigningKey,
+}
+
+impl RelaySink {
+    /// Submit through the relay at `base_url` (its `/relay` endpoint)
+    pub fn new(base_url: &str, signing_key: SigningKey) -> Result<Self, SinkError> {
+        Ok(Self {
+            url: format!("{}/relay", base_url.trim_end_matches('/')),
+            http: http_client(base_url)?,
+            signing_key,
+        })
+    }
+
+    /// The request for `proof`, authorized with the miner's key
+    pub fn request(&self, proof: &ProofSubmission) -> Result<RelayRequest, SinkError> {
+        let prefix = proof
+            .miner_address
+            .split_once('1')
+            .map_or("", |(prefix, _)| prefix);
+        let signer = signer_address(&self.signing_key, prefix)?.to_string();
+        if signer != proof.miner_address {
+            return Err(SinkError::Config(format!(
+                "the relay sink signs for {}, not {}",
+                signer, proof.miner_address
+            )));
+        }
+        let authorization = sign_arbitrary(&self.signing_key, &signer, &authorization_data(proof))
+            .map_err(|e| SinkError::Relay(e.to_string()))?;
+        Ok(RelayRequest {
+            proof: proof.clone(),
+            authorization: Some(authorization),
+        })
+    }
+}
+
+#[async_trait]
+impl ProofSink for RelaySink {
+    fn destination(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError> {
+        post_relay_format(&self.http, &self.url, &self.request(proof)?).await
+    }
+}
+
+/// POST `body` to `url` and read a relay service reply
+async fn post_relay_format(
+    http: &reqwest::Client,
+    url: &str,
+    body: &impl Serialize,
+) -> Result<Delivery, SinkError> {
+    let failed = |reason: String| SinkError::Http {
+        url: url.to_string(),
+        reason,
+    };
+    let resp: serde_json::Value = http
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| failed(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| failed(format!("invalid reply: {}", e)))?;
+    if resp["ok"].as_bool() != Some(true) {
+        let error = resp["error"].as_str().unwrap_or("unknown error");
+        return Err(SinkError::Relay(error.to_string()));
+    }
+    Ok(match resp["tx_hash"].as_str() {
+        Some(tx_hash) if !tx_hash.is_empty() => Delivery::Relayed {
+            tx_hash: tx_hash.to_string(),
+        },
+        _ => Delivery::Forwarded {
+            destination: url.to_string(),
+        },
+    })
+}
+
+/// Body of a webhook request
+#[derive(Serialize)]
+struct WebhookEvent<'a> {
+    event: &'static str,
+    #[serde(flatten)]
+    proof: &'a ProofSubmission,
+}
+
+/// POSTs a `proof_found` event per proof; any 2xx reply accepts it
+pub struct WebhookSink {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str) -> Result<Self, SinkError> {
+        Ok(Self {
+            url: url.to_string(),
+            http: http_client(url)?,
+        })
+    }
+}
+
+#[async_trait]
+impl ProofSink for WebhookSink {
+    fn destination(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError> {
+        let event = WebhookEvent {
+            event: "proof_found",
+            proof,
+        };
+        let resp = self
+            .http
+            .post(&self.url)
+            .json(&event)
+            .send()
+            .await
+            .map_err(|e| SinkError::Http {
+                url: self.url.clone(),
+                reason: e.to_string(),
+            })?;
+        if !resp.status().is_success() {
+            return Err(SinkError::Http {
+                url: self.url.clone(),
+                reason: format!("HTTP {}", resp.status()),
+            });
+        }
+        Ok(Delivery::Forwarded {
+            destination: self.url.clone(),
+        })
+    }
+}
+
+/// Appends each proof as a JSON line to a file
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl ProofSink for FileSink {
+    fn destination(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError> {
+        let mut line = serde_json::to_vec(proof).map_err(std::io::Error::from)?;
+        line.push(b'\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)?;
+        Ok(Delivery::Forwarded {
+            destination: self.destination(),
+        })
+    }
+}
+
+/// Prints each proof as a JSON line, for piping into other tools
+pub struct StdoutSink;
+
+#[async_trait]
+impl ProofSink for StdoutSink {
+    fn destination(&self) -> String {
+        "stdout".to_string()
+    }
+
+    async fn submit(&self, proof: &ProofSubmission) -> Result<Delivery, SinkError> {
+        let line = serde_json::to_string(proof).map_err(std::io::Error::from)?;
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+        Ok(Delivery::Forwarded {
+            destination: self.destination(),
+        })
+    }
+}
+
+/// Build the sink `kind` names
+///
+/// `url` is needed by `pool`, `relay` and `webhook`, `path` by `file`; the
+/// contract sink submits with `client` and `signing_key`, and the relay sink
+/// signs its authorizations with `signing_key`.
+pub fn from_settings<'a>(
+    kind: SinkKind,
+    url: &str,
+    path: &str,
+    client: &'a RpcClient,
+    signing_key: Option<SigningKey>,
+) -> Result<Box<dyn ProofSink + 'a>, SinkError> {
+    let required = |value: &str, key: &str| {
+        if value.is_empty() {
+            Err(SinkError::Config(format!(
+                "submit.{} is required for the {:?} sink",
+                key, kind
+            )))
+        } else {
+            Ok(())
+        }
+    };
+    Ok(match kind {
+        SinkKind::Contract => Box::new(ContractSink::new(client, signing_key)),
+        SinkKind::Pool => {
+            required(url, "url")?;
+            Box::new(PoolSink::new(url)?)
+        }
+        SinkKind::Relay => {
+            required(url, "url")?;
+            let signing_key = signing_key.ok_or_else(|| {
+                SinkError::Config("the relay sink needs a wallet that can sign".to_string())
+            })?;
+            Box::new(RelaySink::new(url, signing_key)?)
+        }
+        SinkKind::Webhook => {
+            required(url, "url")?;
+            Box::new(WebhookSink::new(url)?)
+        }
+        SinkKind::File => {
+            required(path, "path")?;
+            Box::new(FileSink::new(path))
+        }
+        SinkKind::Stdout => Box::new(StdoutSink),
+    })
+}
+
+fn http_client(url: &str) -> Result<reqwest::Client, SinkError> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(SinkError::Config(format!(
+            "'{}' is not an http(s) URL",
+            url
+        )));
+    }
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| SinkError::Config(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof() -> ProofSubmission {
+        ProofSubmission {
+            hash: "00ab".repeat(16),
+            nonce: 42,
+            timestamp: 1_700_000_000,
+            miner_address: "bostrom1miner".to_string(),
+            worker_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("uhash-sink-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let sink = FileSink::new(&path);
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                rt.block_on(sink.submit(&proof())).unwrap(),
+                Delivery::Forwarded {
+                    destination: path.display().to_string()
+                }
+            );
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<ProofSubmission> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].nonce, 42);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_settings_need_a_destination() {
+        let client = RpcClient::new();
+        for (kind, url, path) in [
+            (SinkKind::Pool, "", ""),
+            (SinkKind::Webhook, "", ""),
+            (SinkKind::Webhook, "ftp://example.com", ""),
+            (SinkKind::File, "", ""),
+            (SinkKind::Relay, "", ""),
+            // Watch-only: nothing to authorize relayed proofs with
+            (SinkKind::Relay, "https://relay.example", ""),
+        ] {
+            assert!(
+                matches!(
+                    from_settings(kind, url, path, &client, None),
+                    Err(SinkError::Config(_))
+                ),
+                "{:?}",
+                kind
+            );
+        }
+        let sink = from_settings(SinkKind::Contract, "", "", &client, None).unwrap();
+        assert_eq!(sink.destination(), "contract");
+        let sink = from_settings(SinkKind::File, "", "proofs.jsonl", &client, None).unwrap();
+        assert_eq!(sink.destination(), "proofs.jsonl");
+    }
+
+    #[test]
+    fn test_relay_requests_carry_the_miners_authorization() {
+        let wallet = crate::wallet::Wallet::new().unwrap();
+        let signing_key =
+            SigningKey::from_slice(&wallet.signing_key().unwrap().to_bytes()).unwrap();
+        let sink = RelaySink::new("https://relay.example/", signing_key).unwrap();
+        assert_eq!(sink.destination(), "https://relay.example/relay");
+
+        let foreign = proof();
+        let proof = ProofSubmission {
+            miner_address: wallet.address_str(),
+            ..foreign.clone()
+        };
+        let request = sink.request(&proof).unwrap();
+        let authorization = request.authorization.as_ref().unwrap();
+        assert!(crate::wallet::verify_arbitrary(
+            &proof.miner_address,
+            &authorization_data(&proof),
+            authorization
+        )
+        .unwrap());
+        assert_eq!(
+            String::from_utf8(authorization_data(&proof)).unwrap(),
+            format!(
+                r#"{{"hash":"{}","miner_address":"{}","nonce":42,"timestamp":1700000000}}"#,
+                proof.hash, proof.miner_address
+            )
+        );
+
+        // The proof fields sit at the top level, as in the plain relay format
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["nonce"], 42);
+        assert!(body["authorization"]["signature"].is_string());
+
+        // Only the wallet's own proofs can be authorized
+        assert!(matches!(sink.request(&foreign), Err(SinkError::Config(_))));
+    }
+}
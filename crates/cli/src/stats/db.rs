@@ -0,0 +1,502 @@
+//! SQLite storage of mining rounds and proofs (`~/.uhash/stats.db` by default)
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+use super::{ProofEntry, ProofRecord, Report, RoundEntry, RoundOutcome, RoundRecord, StatsError};
+use crate::pool::{PayoutReport, PayoutScheme, ShareRecord};
+
+/// Schema version stored in SQLite's `user_version`
+const SCHEMA_VERSION: i64 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE rounds (
+    id INTEGER PRIMARY KEY,
+    started_at INTEGER NOT NULL,
+    duration_secs REAL NOT NULL,
+    hashes INTEGER NOT NULL,
+    threads INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    outcome TEXT NOT NULL
+);
+CREATE INDEX rounds_started_at ON rounds (started_at);
+
+CREATE TABLE proofs (
+    id INTEGER PRIMARY KEY,
+    round_id INTEGER REFERENCES rounds (id),
+    found_at INTEGER NOT NULL,
+    address TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    nonce INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    difficulty INTEGER NOT NULL,
+    tx_hash TEXT,
+    reward TEXT
+);
+CREATE INDEX proofs_found_at ON proofs (found_at);
+";
+
+/// Pool shares and payouts, added in version 2
+const POOL_SCHEMA: &str = "
+CREATE TABLE pool_shares (
+    id INTEGER PRIMARY KEY,
+    found_at INTEGER NOT NULL,
+    address TEXT NOT NULL,
+    difficulty INTEGER NOT NULL
+);
+
+CREATE TABLE pool_rewards (
+    id INTEGER PRIMARY KEY,
+    paid_at INTEGER NOT NULL,
+    proof_hash TEXT NOT NULL,
+    reward TEXT NOT NULL,
+    scheme TEXT NOT NULL,
+    fee TEXT NOT NULL,
+    last_share_id INTEGER
+);
+
+CREATE TABLE pool_payouts (
+    reward_id INTEGER NOT NULL REFERENCES pool_rewards (id),
+    address TEXT NOT NULL,
+    shares INTEGER NOT NULL,
+    amount TEXT NOT NULL
+);
+";
+
+impl RoundOutcome {
+    fn from_column(value: &str) -> Self {
+        match value {
+            "proof" => RoundOutcome::Proof,
+            "seed_rotated" => RoundOutcome::SeedRotated,
+            "exhausted" => RoundOutcome::Exhausted,
+            _ => RoundOutcome::Interrupted,
+        }
+    }
+}
+
+/// Mining history in a SQLite file
+pub struct StatsDb {
+    conn: Connection,
+}
+
+impl StatsDb {
+    /// Open or create the database at `path`
+    pub fn open(path: &Path) -> Result<Self, StatsError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        Self::init(conn, path)
+    }
+
+    /// A database that lives only as long as the value, for tests
+    pub fn open_in_memory() -> Result<Self, StatsError> {
+        Self::init(Connection::open_in_memory()?, Path::new(":memory:"))
+    }
+
+    fn init(conn: Connection, path: &Path) -> Result<Self, StatsError> {
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        match version {
+            0 => {
+                conn.execute_batch(SCHEMA)?;
+                conn.execute_batch(POOL_SCHEMA)?;
+                conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            1 => {
+                conn.execute_batch(POOL_SCHEMA)?;
+                conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            SCHEMA_VERSION => {}
+            found => {
+                return Err(StatsError::UnsupportedSchema {
+                    path: path.display().to_string(),
+                    found,
+                })
+            }
+        }
+        Ok(Self { conn })
+    }
+
+    /// Record a finished round, returning its id
+    pub fn record_round(&self, round: &RoundRecord) -> Result<i64, StatsError> {
+        self.conn.execute(
+            "INSERT INTO rounds (started_at, duration_secs, hashes, threads, difficulty, outcome)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                round.started_at as i64,
+                round.duration_secs,
+                round.hashes as i64,
+                round.threads as i64,
+                round.difficulty,
+                round.outcome.as_str(),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Record a found proof, returning its id for [`StatsDb::record_submission`]
+    pub fn record_proof(&self, proof: &ProofRecord) -> Result<i64, StatsError> {
+        self.conn.execute(
+            "INSERT INTO proofs (round_id, found_at, address, hash, nonce, timestamp, difficulty)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                proof.round_id,
+                proof.found_at as i64,
+                proof.address,
+                proof.hash,
+                // Stored bit-for-bit; nonces above i64::MAX read back unchanged
+                proof.nonce as i64,
+                proof.timestamp as i64,
+                proof.difficulty,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Attach the transaction, and the reward if known, to a recorded proof
+    pub fn record_submission(
+        &self,
+        proof_id: i64,
+        tx_hash: &str,
+        reward: Option<&str>,
+    ) -> Result<(), StatsError> {
+        self.conn.execute(
+            "UPDATE proofs SET tx_hash = ?2, reward = ?3 WHERE id = ?1",
+            params![proof_id, tx_hash, reward],
+        )?;
+        Ok(())
+    }
+
+    /// Summarize rounds started and proofs found at or after `since`
+    pub fn report(&self, since: u64) -> Result<Report, StatsError> {
+        let since_param = since as i64;
+        let (rounds, mining_secs, hashes): (i64, f64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(duration_secs), 0), COALESCE(SUM(hashes), 0)
+             FROM rounds WHERE started_at >= ?1",
+            [since_param],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+
+        let mut report = Report {
+            since,
+            rounds: rounds as u64,
+            mining_secs,
+            hashes: hashes as u64,
+            hashrate: if mining_secs > 0.0 {
+                hashes as f64 / mining_secs
+            } else {
+                0.0
+            },
+            ..Report::default()
+        };
+
+        let mut rewards: u128 = 0;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tx_hash, reward FROM proofs WHERE found_at >= ?1")?;
+        let rows = stmt.query_map([since_param], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+            ))
+        })?;
+        for row in rows {
+            let (tx_hash, reward) = row?;
+            report.proofs_found += 1;
+            if tx_hash.is_none() {
+                continue;
+            }
+            report.proofs_submitted += 1;
+            match reward.and_then(|r| r.parse::<u128>().ok()) {
+                Some(reward) => rewards += reward,
+                None => report.unknown_rewards += 1,
+            }
+        }
+        report.rewards = rewards.to_string();
+        if report.proofs_found > 0 {
+            report.hashes_per_proof = Some(report.hashes as f64 / report.proofs_found as f64);
+        }
+        Ok(report)
+    }
+
+    /// Rounds started at or after `since`, oldest first
+    pub fn rounds(&self, since: u64) -> Result<Vec<RoundEntry>, StatsError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, started_at, duration_secs, hashes, threads, difficulty, outcome
+             FROM rounds WHERE started_at >= ?1 ORDER BY started_at, id",
+        )?;
+        let rows = stmt.query_map([since as i64], |row| {
+            let duration_secs: f64 = row.get(2)?;
+            let hashes = row.get::<_, i64>(3)? as u64;
+            Ok(RoundEntry {
+                id: row.get(0)?,
+                started_at: row.get::<_, i64>(1)? as u64,
+                duration_secs,
+                hashes,
+                hashrate: if duration_secs > 0.0 {
+                    hashes as f64 / duration_secs
+                } else {
+                    0.0
+                },
+                threads: row.get(4)?,
+                difficulty: row.get(5)?,
+                outcome: RoundOutcome::from_column(&row.get::<_, String>(6)?),
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Proofs found at or after `since`, oldest first
+    pub fn proofs(&self, since: u64) -> Result<Vec<ProofEntry>, StatsError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, round_id, found_at, address, hash, nonce, timestamp, difficulty, tx_hash, reward
+             FROM proofs WHERE found_at >= ?1 ORDER BY found_at, id",
+        )?;
+        let rows = stmt.query_map([since as i64], |row| {
+            Ok(ProofEntry {
+                id: row.get(0)?,
+                round_id: row.get(1)?,
+                found_at: row.get::<_, i64>(2)? as u64,
+                address: row.get(3)?,
+                hash: row.get(4)?,
+                nonce: row.get::<_, i64>(5)? as u64,
+                timestamp: row.get::<_, i64>(6)? as u64,
+                difficulty: row.get(7)?,
+                tx_hash: row.get(8)?,
+                reward: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Record a pool share, returning its id
+    pub fn record_share(&self, share: &ShareRecord) -> Result<i64, StatsError> {
+        self.conn.execute(
+            "INSERT INTO pool_shares (found_at, address, difficulty) VALUES (?1, ?2, ?3)",
+            params![share.found_at as i64, share.address, share.difficulty],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Shares `scheme` splits the next reward over, oldest first
+    pub fn share_window(&self, scheme: PayoutScheme) -> Result<Vec<ShareRecord>, StatsError> {
+        let row_to_share = |row: &rusqlite::Row| {
+            Ok(ShareRecord {
+                id: Some(row.get(0)?),
+                found_at: row.get::<_, i64>(1)? as u64,
+                address: row.get(2)?,
+                difficulty: row.get(3)?,
+            })
+        };
+        let mut shares = match scheme {
+            PayoutScheme::Pplns { window } => {
+                let mut stmt = self.conn.prepare(
+                    "SELECT id, found_at, address, difficulty FROM pool_shares
+                     ORDER BY id DESC LIMIT ?1",
+                )?;
+                let rows = stmt.query_map([window as i64], row_to_share)?;
+                rows.collect::<Result<Vec<_>, _>>()?
+            }
+            PayoutScheme::Proportional => {
+                let mut stmt = self.conn.prepare(
+                    "SELECT id, found_at, address, difficulty FROM pool_shares
+                     WHERE id > (SELECT COALESCE(MAX(last_share_id), 0) FROM pool_rewards)
+                     ORDER BY id DESC",
+                )?;
+                let rows = stmt.query_map([], row_to_share)?;
+                rows.collect::<Result<Vec<_>, _>>()?
+            }
+        };
+        shares.reverse();
+        Ok(shares)
+    }
+
+    /// Record a reward split at `paid_at` (Unix seconds), returning its id
+    pub fn record_payout(&self, report: &PayoutReport, paid_at: u64) -> Result<i64, StatsError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let scheme = match report.scheme {
+            PayoutScheme::Pplns { window } => format!("pplns:{}", window),
+            PayoutScheme::Proportional => "proportional".to_string(),
+        };
+        tx.execute(
+            "INSERT INTO pool_rewards (paid_at, proof_hash, reward, scheme, fee, last_share_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                paid_at as i64,
+                report.proof_hash,
+                report.reward.to_string(),
+                scheme,
+                report.fee.to_string(),
+                report.last_share_id,
+            ],
+        )?;
+        let reward_id = tx.last_insert_rowid();
+        for payout in &report.payouts {
+            tx.execute(
+                "INSERT INTO pool_payouts (reward_id, address, shares, amount)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    reward_id,
+                    payout.address,
+                    payout.shares as i64,
+                    payout.amount.to_string()
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(reward_id)
+    }
+
+    /// Start of the oldest recorded round (Unix seconds), if any
+    pub fn first_round_at(&self) -> Result<Option<u64>, StatsError> {
+        let first: Option<i64> = self
+            .conn
+            .query_row("SELECT MIN(started_at) FROM rounds", [], |row| row.get(0))
+            .optional()?
+            .flatten();
+        Ok(first.map(|t| t as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(started_at: u64, hashes: u64, outcome: RoundOutcome) -> RoundRecord {
+        RoundRecord {
+            started_at,
+            duration_secs: 10.0,
+            hashes,
+            threads: 4,
+            difficulty: 16,
+            outcome,
+        }
+    }
+
+    fn proof(round_id: i64, found_at: u64, nonce: u64) -> ProofRecord {
+        ProofRecord {
+            round_id: Some(round_id),
+            found_at,
+            address: "bostrom1miner".to_string(),
+            hash: "00".repeat(32),
+            nonce,
+            timestamp: found_at,
+            difficulty: 16,
+        }
+    }
+
+    #[test]
+    fn test_report_summarizes_period() {
+        let db = StatsDb::open_in_memory().unwrap();
+        // Outside the period
+        let old = db
+            .record_round(&round(100, 5000, RoundOutcome::Proof))
+            .unwrap();
+        db.record_proof(&proof(old, 105, 1)).unwrap();
+
+        let r1 = db
+            .record_round(&round(1000, 1000, RoundOutcome::Proof))
+            .unwrap();
+        let p1 = db.record_proof(&proof(r1, 1010, 2)).unwrap();
+        db.record_submission(p1, "AA", Some("1000000")).unwrap();
+        let r2 = db
+            .record_round(&round(1020, 1000, RoundOutcome::Proof))
+            .unwrap();
+        let p2 = db.record_proof(&proof(r2, 1030, u64::MAX)).unwrap();
+        db.record_submission(p2, "BB", None).unwrap();
+        let r3 = db
+            .record_round(&round(1040, 1000, RoundOutcome::Proof))
+            .unwrap();
+        db.record_proof(&proof(r3, 1050, 3)).unwrap();
+        db.record_round(&round(1060, 1000, RoundOutcome::SeedRotated))
+            .unwrap();
+
+        let report = db.report(1000).unwrap();
+        assert_eq!(report.rounds, 4);
+        assert_eq!(report.mining_secs, 40.0);
+        assert_eq!(report.hashes, 4000);
+        assert_eq!(report.hashrate, 100.0);
+        assert_eq!(report.proofs_found, 3);
+        assert_eq!(report.proofs_submitted, 2);
+        assert_eq!(report.rewards, "1000000");
+        assert_eq!(report.unknown_rewards, 1);
+        assert_eq!(report.hashes_per_proof, Some(4000.0 / 3.0));
+        assert_eq!(db.first_round_at().unwrap(), Some(100));
+
+        let nonce: i64 = db
+            .conn
+            .query_row("SELECT nonce FROM proofs WHERE id = ?1", [p2], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(nonce as u64, u64::MAX);
+
+        let rounds = db.rounds(1000).unwrap();
+        assert_eq!(rounds.len(), 4);
+        assert_eq!(rounds[0].hashrate, 100.0);
+        assert_eq!(rounds[3].outcome, RoundOutcome::SeedRotated);
+        let proofs = db.proofs(1000).unwrap();
+        assert_eq!(proofs.len(), 3);
+        assert_eq!(proofs[0].reward.as_deref(), Some("1000000"));
+        assert_eq!(proofs[1].nonce, u64::MAX);
+        assert_eq!(proofs[2].tx_hash, None);
+    }
+
+    #[test]
+    fn test_pool_share_windows() {
+        let db = StatsDb::open_in_memory().unwrap();
+        for (i, address) in ["a", "b", "a", "c"].iter().enumerate() {
+            db.record_share(&ShareRecord {
+                id: None,
+                found_at: 1000 + i as u64,
+                address: address.to_string(),
+                difficulty: 10,
+            })
+            .unwrap();
+        }
+        let window = db.share_window(PayoutScheme::Pplns { window: 3 }).unwrap();
+        let addresses: Vec<&str> = window.iter().map(|s| s.address.as_str()).collect();
+        assert_eq!(addresses, ["b", "a", "c"]);
+
+        let round = db.share_window(PayoutScheme::Proportional).unwrap();
+        assert_eq!(round.len(), 4);
+        let report =
+            crate::pool::split_reward("ff", 100, PayoutScheme::Proportional, 0, &round).unwrap();
+        db.record_payout(&report, 2000).unwrap();
+        // The next proportional round starts after the paid shares
+        assert!(db
+            .share_window(PayoutScheme::Proportional)
+            .unwrap()
+            .is_empty());
+        assert_eq!(db.share_window(PayoutScheme::default()).unwrap().len(), 4);
+        let paid: i64 = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM pool_payouts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(paid, 3);
+    }
+
+    #[test]
+    fn test_version_1_database_is_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        let db = StatsDb::init(conn, Path::new(":memory:")).unwrap();
+        assert!(db
+            .share_window(PayoutScheme::Proportional)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_reopen_keeps_history() {
+        let path = std::env::temp_dir().join(format!("uhash-stats-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        StatsDb::open(&path)
+            .unwrap()
+            .record_round(&round(1, 10, RoundOutcome::Interrupted))
+            .unwrap();
+        assert_eq!(StatsDb::open(&path).unwrap().report(0).unwrap().rounds, 1);
+        std::fs::remove_file(&path).ok();
+    }
+}
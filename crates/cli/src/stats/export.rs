@@ -0,0 +1,322 @@
+//! Per-round and per-proof datasets for spreadsheets and dashboards
+//!
+//! An export writes `rounds.<ext>` and `proofs.<ext>` into a directory, one row
+//! per [`RoundEntry`](super::RoundEntry) and [`ProofEntry`](super::ProofEntry).
+//! CSV is always available with the database; Parquet needs the `parquet`
+//! feature.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use super::{StatsDb, StatsError};
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    /// Whether this build can write the format
+    pub fn is_supported(&self) -> bool {
+        match self {
+            ExportFormat::Csv => true,
+            ExportFormat::Parquet => cfg!(feature = "parquet"),
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = StatsError;
+
+    fn from_str(s: &str) -> Result<Self, StatsError> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(StatsError::InvalidExportFormat(s.to_string())),
+        }
+    }
+}
+
+/// Write the rounds and proofs recorded since `since` into `dir`, returning the
+/// paths written
+pub fn export(
+    db: &StatsDb,
+    since: u64,
+    format: ExportFormat,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, StatsError> {
+    if !format.is_supported() {
+        return Err(StatsError::ExportUnavailable(format.extension()));
+    }
+    let rounds = db.rounds(since)?;
+    let proofs = db.proofs(since)?;
+
+    fs::create_dir_all(dir)?;
+    let rounds_path = dir.join(format!("rounds.{}", format.extension()));
+    let proofs_path = dir.join(format!("proofs.{}", format.extension()));
+    match format {
+        ExportFormat::Csv => {
+            write_csv(&rounds_path, &rounds)?;
+            write_csv(&proofs_path, &proofs)?;
+        }
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet => {
+            parquet_file::write_rounds(&rounds_path, &rounds)?;
+            parquet_file::write_proofs(&proofs_path, &proofs)?;
+        }
+        #[cfg(not(feature = "parquet"))]
+        ExportFormat::Parquet => unreachable!("rejected by is_supported"),
+    }
+    Ok(vec![rounds_path, proofs_path])
+}
+
+fn write_csv<T: serde::Serialize>(path: &Path, rows: &[T]) -> Result<(), StatsError> {
+    let mut writer = csv::Writer::from_writer(File::create(path)?);
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_file {
+    use parquet::basic::Compression;
+    use parquet::data_type::{
+        ByteArray, ByteArrayType, DataType, DoubleType, Int32Type, Int64Type,
+    };
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+    use parquet::schema::parser::parse_message_type;
+    use std::fs::File;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use super::super::{ProofEntry, RoundEntry, StatsError};
+
+    const ROUNDS_SCHEMA: &str = "
+        message round {
+            REQUIRED INT64 id;
+            REQUIRED INT64 started_at;
+            REQUIRED DOUBLE duration_secs;
+            REQUIRED INT64 hashes;
+            REQUIRED DOUBLE hashrate;
+            REQUIRED INT32 threads;
+            REQUIRED INT32 difficulty;
+            REQUIRED BYTE_ARRAY outcome (STRING);
+        }
+    ";
+
+    const PROOFS_SCHEMA: &str = "
+        message proof {
+            REQUIRED INT64 id;
+            OPTIONAL INT64 round_id;
+            REQUIRED INT64 found_at;
+            REQUIRED BYTE_ARRAY address (STRING);
+            REQUIRED BYTE_ARRAY hash (STRING);
+            REQUIRED INT64 nonce (INTEGER(64, false));
+            REQUIRED INT64 timestamp;
+            REQUIRED INT32 difficulty;
+            OPTIONAL BYTE_ARRAY tx_hash (STRING);
+            OPTIONAL BYTE_ARRAY reward (STRING);
+        }
+    ";
+
+    /// Values of one column, `None` for nulls in optional columns
+    enum Column {
+        Int64(Vec<Option<i64>>),
+        Int32(Vec<Option<i32>>),
+        Double(Vec<Option<f64>>),
+        Text(Vec<Option<String>>),
+    }
+
+    fn int64<T>(rows: &[T], value: impl Fn(&T) -> Option<i64>) -> Column {
+        Column::Int64(rows.iter().map(value).collect())
+    }
+
+    fn text<T>(rows: &[T], value: impl Fn(&T) -> Option<String>) -> Column {
+        Column::Text(rows.iter().map(value).collect())
+    }
+
+    pub(super) fn write_rounds(path: &Path, rounds: &[RoundEntry]) -> Result<(), StatsError> {
+        write(
+            path,
+            ROUNDS_SCHEMA,
+            vec![
+                int64(rounds, |r| Some(r.id)),
+                int64(rounds, |r| Some(r.started_at as i64)),
+                Column::Double(rounds.iter().map(|r| Some(r.duration_secs)).collect()),
+                int64(rounds, |r| Some(r.hashes as i64)),
+                Column::Double(rounds.iter().map(|r| Some(r.hashrate)).collect()),
+                Column::Int32(rounds.iter().map(|r| Some(r.threads as i32)).collect()),
+                Column::Int32(rounds.iter().map(|r| Some(r.difficulty as i32)).collect()),
+                text(rounds, |r| Some(r.outcome.as_str().to_string())),
+            ],
+        )
+    }
+
+    pub(super) fn write_proofs(path: &Path, proofs: &[ProofEntry]) -> Result<(), StatsError> {
+        write(
+            path,
+            PROOFS_SCHEMA,
+            vec![
+                int64(proofs, |p| Some(p.id)),
+                int64(proofs, |p| p.round_id),
+                int64(proofs, |p| Some(p.found_at as i64)),
+                text(proofs, |p| Some(p.address.clone())),
+                text(proofs, |p| Some(p.hash.clone())),
+                // Unsigned by the schema annotation, so stored bit-for-bit
+                int64(proofs, |p| Some(p.nonce as i64)),
+                int64(proofs, |p| Some(p.timestamp as i64)),
+                Column::Int32(proofs.iter().map(|p| Some(p.difficulty as i32)).collect()),
+                text(proofs, |p| p.tx_hash.clone()),
+                text(proofs, |p| p.reward.clone()),
+            ],
+        )
+    }
+
+    /// Write `columns`, in schema order, as a single row group
+    fn write(path: &Path, schema: &str, columns: Vec<Column>) -> Result<(), StatsError> {
+        let schema = Arc::new(parse_message_type(schema)?);
+        let props = Arc::new(
+            WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build(),
+        );
+        let mut writer = SerializedFileWriter::new(File::create(path)?, schema, props)?;
+        let mut group = writer.next_row_group()?;
+        for column in columns {
+            let mut col = group.next_column()?.ok_or_else(|| {
+                parquet::errors::ParquetError::General("more columns than the schema".into())
+            })?;
+            match column {
+                Column::Int64(values) => write_column::<Int64Type>(&mut col, values)?,
+                Column::Int32(values) => write_column::<Int32Type>(&mut col, values)?,
+                Column::Double(values) => write_column::<DoubleType>(&mut col, values)?,
+                Column::Text(values) => write_column::<ByteArrayType>(
+                    &mut col,
+                    values
+                        .into_iter()
+                        .map(|v| v.map(|s| ByteArray::from(s.into_bytes())))
+                        .collect(),
+                )?,
+            }
+            col.close()?;
+        }
+        group.close()?;
+        writer.close()?;
+        Ok(())
+    }
+
+    fn write_column<T: DataType>(
+        col: &mut SerializedColumnWriter<'_>,
+        values: Vec<Option<T::T>>,
+    ) -> Result<(), StatsError> {
+        let writer = col.typed::<T>();
+        let def_levels: Vec<i16> = values.iter().map(|v| i16::from(v.is_some())).collect();
+        let optional = writer.get_descriptor().max_def_level() > 0;
+        let present: Vec<T::T> = values.into_iter().flatten().collect();
+        writer.write_batch(&present, optional.then_some(&def_levels[..]), None)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{ProofRecord, RoundOutcome, RoundRecord};
+
+    fn sample_db() -> StatsDb {
+        let db = StatsDb::open_in_memory().unwrap();
+        let round = db
+            .record_round(&RoundRecord {
+                started_at: 1000,
+                duration_secs: 4.0,
+                hashes: 400,
+                threads: 2,
+                difficulty: 12,
+                outcome: RoundOutcome::Proof,
+            })
+            .unwrap();
+        let proof = db
+            .record_proof(&ProofRecord {
+                round_id: Some(round),
+                found_at: 1004,
+                address: "bostrom1miner".to_string(),
+                hash: "00ab".to_string(),
+                nonce: u64::MAX,
+                timestamp: 1000,
+                difficulty: 12,
+            })
+            .unwrap();
+        db.record_submission(proof, "TX1", Some("250")).unwrap();
+        db
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("uhash-export-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let dir = scratch_dir("csv");
+        let paths = export(&sample_db(), 0, ExportFormat::Csv, &dir).unwrap();
+        let rounds = fs::read_to_string(&paths[0]).unwrap();
+        assert_eq!(
+            rounds,
+            "id,started_at,duration_secs,hashes,hashrate,threads,difficulty,outcome\n\
+             1,1000,4.0,400,100.0,2,12,proof\n"
+        );
+        let proofs = fs::read_to_string(&paths[1]).unwrap();
+        assert_eq!(
+            proofs,
+            "id,round_id,found_at,address,hash,nonce,timestamp,difficulty,tx_hash,reward\n\
+             1,1,1004,bostrom1miner,00ab,18446744073709551615,1000,12,TX1,250\n"
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_export_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let dir = scratch_dir("parquet");
+        let paths = export(&sample_db(), 0, ExportFormat::Parquet, &dir).unwrap();
+        for path in &paths {
+            let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+            assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+        }
+        let proofs = SerializedFileReader::new(File::open(&paths[1]).unwrap()).unwrap();
+        let row = proofs.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert_eq!(
+            row.to_string(),
+            "{id: 1, round_id: 1, found_at: 1004, address: \"bostrom1miner\", hash: \"00ab\", \
+             nonce: 18446744073709551615, timestamp: 1000, difficulty: 12, tx_hash: \"TX1\", \
+             reward: \"250\"}"
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_export_format() {
+        assert_eq!("CSV".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert_eq!(
+            "parquet".parse::<ExportFormat>().unwrap(),
+            ExportFormat::Parquet
+        );
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Local mining statistics
+//!
+//! `mine` records every round (duration, hashes, threads, difficulty and how it
+//! ended) and every proof it finds (hash, nonce, difficulty, transaction and
+//! reward) so the history survives restarts; `uhash report` summarizes a
+//! period or exports it for spreadsheets. The records are plain types;
+//! [`StatsDb`] stores them in a SQLite file and needs the `stats` feature.
+
+use serde::Serialize;
+use thiserror::Error;
+
+#[cfg(feature = "stats")]
+mod db;
+#[cfg(feature = "stats")]
+mod export;
+
+#[cfg(feature = "stats")]
+pub use db::StatsDb;
+#[cfg(feature = "stats")]
+pub use export::{export, ExportFormat};
+
+#[derive(Error, Debug)]
+pub enum StatsError {
+    #[cfg(feature = "stats")]
+    #[error("Stats database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[cfg(feature = "stats")]
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error("File I/O error: {0}")]
+    FileError(#[from] std::io::Error),
+
+    #[error("Stats database {path} has schema version {found}, newer than this release supports")]
+    UnsupportedSchema { path: String, found: i64 },
+
+    #[error("Invalid period '{0}' (expected e.g. 12h, 7d or 4w)")]
+    InvalidPeriod(String),
+
+    #[error("Invalid export format '{0}' (expected csv or parquet)")]
+    InvalidExportFormat(String),
+
+    #[error("{0} export is not available in this build")]
+    ExportUnavailable(&'static str),
+}
+
+/// How a mining round ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundOutcome {
+    /// A thread found a proof
+    Proof,
+    /// The seed rotated and the round restarted
+    SeedRotated,
+    /// Mining stopped before a proof was found
+    Interrupted,
+    /// Every nonce of the range was hashed without a proof
+    Exhausted,
+}
+
+impl RoundOutcome {
+    /// Name used in the database and exports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoundOutcome::Proof => "proof",
+            RoundOutcome::SeedRotated => "seed_rotated",
+            RoundOutcome::Interrupted => "interrupted",
+            RoundOutcome::Exhausted => "exhausted",
+        }
+    }
+}
+
+/// One round of mining, from spawning the threads to joining them
+#[derive(Debug, Clone)]
+pub struct RoundRecord {
+    /// Unix seconds
+    pub started_at: u64,
+    pub duration_secs: f64,
+    pub hashes: u64,
+    pub threads: usize,
+    pub difficulty: u32,
+    pub outcome: RoundOutcome,
+}
+
+/// A proof found in a round
+#[derive(Debug, Clone)]
+pub struct ProofRecord {
+    /// Round the proof was found in, as returned by `StatsDb::record_round`
+    pub round_id: Option<i64>,
+    /// Unix seconds
+    pub found_at: u64,
+    pub address: String,
+    pub hash: String,
+    pub nonce: u64,
+    /// Timestamp hashed into the proof
+    pub timestamp: u64,
+    pub difficulty: u32,
+}
+
+/// A recorded round, one row of the rounds dataset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoundEntry {
+    pub id: i64,
+    /// Unix seconds
+    pub started_at: u64,
+    pub duration_secs: f64,
+    pub hashes: u64,
+    /// Hashes per second over the round
+    pub hashrate: f64,
+    pub threads: u32,
+    pub difficulty: u32,
+    pub outcome: RoundOutcome,
+}
+
+/// A recorded proof, one row of the proofs dataset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProofEntry {
+    pub id: i64,
+    pub round_id: Option<i64>,
+    /// Unix seconds
+    pub found_at: u64,
+    pub address: String,
+    pub hash: String,
+    pub nonce: u64,
+    pub timestamp: u64,
+    pub difficulty: u32,
+    /// Set once the proof was submitted or relayed
+    pub tx_hash: Option<String>,
+    /// uLI, when the broadcast reported it
+    pub reward: Option<String>,
+}
+
+/// Summary of the rounds and proofs in a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Report {
+    /// Start of the period (Unix seconds)
+    pub since: u64,
+    pub rounds: u64,
+    /// Time spent hashing
+    pub mining_secs: f64,
+    pub hashes: u64,
+    /// Hashes per second of mining time
+    pub hashrate: f64,
+    pub proofs_found: u64,
+    /// Proofs with a transaction hash (submitted or relayed)
+    pub proofs_submitted: u64,
+    /// Hashes computed per proof found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashes_per_proof: Option<f64>,
+    /// Sum of known rewards in uLI
+    pub rewards: String,
+    /// Submitted proofs whose reward was not reported (sync broadcasts)
+    pub unknown_rewards: u64,
+}
+
+/// Parse a period such as `90m`, `12h`, `7d` or `4w` into seconds
+pub fn parse_period(period: &str) -> Result<u64, StatsError> {
+    let invalid = || StatsError::InvalidPeriod(period.to_string());
+    let trimmed = period.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (count, unit) = trimmed.split_at(split);
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return Err(invalid()),
+    };
+    count.checked_mul(unit_secs).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("7d").unwrap(), 7 * 86_400);
+        assert_eq!(parse_period("12h").unwrap(), 12 * 3600);
+        assert_eq!(parse_period("90m").unwrap(), 5400);
+        assert_eq!(parse_period("2w").unwrap(), 2 * 604_800);
+        for bad in ["", "7", "d", "7y", "-1d", "1.5d"] {
+            assert!(parse_period(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Live state of a running miner
+//!
+//! `mine` keeps a [`MinerStatus`] up to date and writes it to a small JSON
+//! file next to the config (`~/.uhash/miner-status.json` by default) every few
+//! seconds, so other processes — `uhash dashboard`, scripts, monitoring
+//! agents — can watch a miner without talking to it. The file is replaced
+//! atomically; a reader never sees a half-written status.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+/// Proofs kept in [`MinerStatus::proofs`]
+pub const PROOF_LOG_LEN: usize = 50;
+
+/// Errors kept in [`MinerStatus::errors`]
+pub const ERROR_LOG_LEN: usize = 20;
+
+/// File name of the status file, in the config directory
+pub const STATUS_FILE_NAME: &str = "miner-status.json";
+
+/// Snapshot of a miner, as written to the status file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MinerStatus {
+    /// Process id of the miner
+    pub pid: u32,
+    /// Unix seconds the miner started
+    pub started_at: u64,
+    /// Unix seconds of the last update
+    pub updated_at: u64,
+    /// Unix seconds the miner exited, if it exited cleanly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<u64>,
+    pub address: String,
+    pub contract: String,
+    pub difficulty: u32,
+    /// Hex seed of the current round
+    pub seed: String,
+    /// Unix seconds the seed rotates, if the contract reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_expires_at: Option<u64>,
+    pub threads: usize,
+    /// Hashing is paused through the management API
+    #[serde(default)]
+    pub paused: bool,
+    /// Hashes per second over the current round
+    pub hashrate: f64,
+    /// Hashes per second of each thread over the current round
+    pub thread_hashrates: Vec<f64>,
+    /// Unix seconds the current round started
+    pub round_started_at: u64,
+    pub round_hashes: u64,
+    /// Hashes in the rounds finished since the miner started
+    pub total_hashes: u64,
+    pub proofs_found: u64,
+    pub proofs_submitted: u64,
+    /// Most recent proofs, oldest first
+    pub proofs: VecDeque<ProofLogEntry>,
+    /// Most recent submission and chain errors, oldest first
+    pub errors: VecDeque<ErrorLogEntry>,
+}
+
+/// A found proof and what became of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofLogEntry {
+    /// Unix seconds
+    pub found_at: u64,
+    pub hash: String,
+    pub nonce: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// uLI, when the broadcast reported it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward: Option<String>,
+    /// Why submission failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// An error the miner recovered from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorLogEntry {
+    /// Unix seconds
+    pub at: u64,
+    pub message: String,
+}
+
+impl MinerStatus {
+    /// Start a new round at `now`, adding the last round to the total
+    pub fn start_round(&mut self, now: u64) {
+        self.total_hashes += self.round_hashes;
+        self.round_started_at = now;
+        self.round_hashes = 0;
+        self.hashrate = 0.0;
+        self.thread_hashrates = vec![0.0; self.threads];
+    }
+
+    /// Update the round's hash counts from per-thread totals after `elapsed_secs`
+    pub fn update_hashes(&mut self, thread_hashes: &[u64], elapsed_secs: f64) {
+        let rate = |hashes: u64| {
+            if elapsed_secs > 0.0 {
+                hashes as f64 / elapsed_secs
+            } else {
+                0.0
+            }
+        };
+        self.round_hashes = thread_hashes.iter().sum();
+        self.hashrate = rate(self.round_hashes);
+        self.thread_hashrates = thread_hashes.iter().map(|&h| rate(h)).collect();
+    }
+
+    /// Log a found proof, dropping the oldest beyond [`PROOF_LOG_LEN`]
+    pub fn push_proof(&mut self, entry: ProofLogEntry) {
+        self.proofs_found += 1;
+        self.proofs.push_back(entry);
+        while self.proofs.len() > PROOF_LOG_LEN {
+            self.proofs.pop_front();
+        }
+    }
+
+    /// The most recently logged proof, to record its submission
+    pub fn last_proof_mut(&mut self) -> Option<&mut ProofLogEntry> {
+        self.proofs.back_mut()
+    }
+
+    /// Record that the last logged proof was submitted
+    pub fn proof_submitted(&mut self, tx_hash: &str, reward: Option<&str>) {
+        self.proofs_submitted += 1;
+        if let Some(proof) = self.last_proof_mut() {
+            proof.tx_hash = Some(tx_hash.to_string());
+            proof.reward = reward.map(str::to_string);
+        }
+    }
+
+    /// Record that submitting the last logged proof failed
+    pub fn proof_failed(&mut self, at: u64, message: String) {
+        if let Some(proof) = self.last_proof_mut() {
+            proof.error = Some(message.clone());
+        }
+        self.push_error(at, message);
+    }
+
+    /// Log an error, dropping the oldest beyond [`ERROR_LOG_LEN`]
+    pub fn push_error(&mut self, at: u64, message: impl Into<String>) {
+        self.errors.push_back(ErrorLogEntry {
+            at,
+            message: message.into(),
+        });
+        while self.errors.len() > ERROR_LOG_LEN {
+            self.errors.pop_front();
+        }
+    }
+
+    /// Read a status file, `None` if it does not exist
+    pub fn read(path: &Path) -> std::io::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replace the status file at `path`
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let text = serde_json::to_vec(self)?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_logs_and_roundtrip() {
+        let mut status = MinerStatus {
+            threads: 2,
+            ..MinerStatus::default()
+        };
+        status.start_round(100);
+        status.update_hashes(&[30, 10], 2.0);
+        assert_eq!(status.round_hashes, 40);
+        assert_eq!(status.hashrate, 20.0);
+        assert_eq!(status.thread_hashrates, vec![15.0, 5.0]);
+        status.start_round(110);
+        assert_eq!(status.total_hashes, 40);
+        assert_eq!(status.round_hashes, 0);
+
+        for nonce in 0..(PROOF_LOG_LEN as u64 + 5) {
+            status.push_proof(ProofLogEntry {
+                found_at: 100 + nonce,
+                hash: "00".repeat(32),
+                nonce,
+                tx_hash: None,
+                reward: None,
+                error: None,
+            });
+        }
+        status.proof_submitted("AB", Some("100"));
+        assert_eq!(status.proofs_submitted, 1);
+        assert_eq!(status.proofs.back().unwrap().reward.as_deref(), Some("100"));
+        status.proof_failed(120, "Submit failed".to_string());
+        assert_eq!(status.errors.len(), 1);
+        assert_eq!(status.proofs_found, PROOF_LOG_LEN as u64 + 5);
+        assert_eq!(status.proofs.len(), PROOF_LOG_LEN);
+        assert_eq!(status.proofs.front().unwrap().nonce, 5);
+
+        let path = std::env::temp_dir().join(format!("uhash-status-{}.json", std::process::id()));
+        assert!(MinerStatus::read(&path).unwrap().is_none());
+        status.write(&path).unwrap();
+        assert_eq!(MinerStatus::read(&path).unwrap().unwrap(), status);
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,691 @@
+//! Stratum V1 shim in front of a uhash pool
+//!
+//! `uhash-stratum` accepts Stratum V1 miners and management tools
+//! (newline-delimited JSON-RPC over TCP) and talks the uhash pool protocol
+//! upstream, one pool connection per Stratum connection. [`Session`] does the
+//! translation:
+//!
+//! | Stratum | uhash pool |
+//! |---------|------------|
+//! | `mining.subscribe` | answered by the shim; `params[0]` becomes `hello`'s agent |
+//! | `mining.authorize ["ADDRESS.WORKER", _]` | `hello`, answered on `welcome` or `error` |
+//! | `mining.submit [worker, job_id, extranonce2, ntime, nonce, hash?]` | `share`, answered on `accepted` or `rejected` |
+//! | `mining.notify` | `job` and `job_update` |
+//! | `mining.set_difficulty` | `welcome` and `set_difficulty`, in leading zero bits |
+//! | `client.show_message` | `error`, then the connection closes |
+//!
+//! `mining.notify` keeps Stratum's nine positions so existing tools parse it:
+//! `[job_id, seed, address, "", [], version, nbits, ntime, true]`, with the
+//! session's protocol version, the network difficulty and the job timestamp
+//! as 8-digit hex. Since the timestamp is part of each job, `ntime` in
+//! submits is ignored.
+//!
+//! The 64-bit uhash nonce is `extranonce2 || nonce`, with an empty
+//! extranonce1 and a 4-byte extranonce2; a submit may instead carry all 16
+//! hex digits in `nonce`. Stratum submits carry no hash, so the shim hashes
+//! the share itself unless a sixth parameter gives it.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use uhash_pool_proto::{
+    ClientMessage, Job, ProtoError, RejectReason, ServerMessage, KEEPALIVE_INTERVAL_SECS,
+    SUPPORTED_VERSIONS,
+};
+
+use crate::proof::proof_input;
+use crate::UniversalHash;
+
+/// Bytes of the nonce a miner rolls as extranonce2
+pub const EXTRANONCE2_SIZE: usize = 4;
+
+/// Jobs kept to hash late submits for
+const KEPT_JOBS: usize = 2;
+
+/// Stratum error codes
+const ERROR_OTHER: i64 = 20;
+const ERROR_JOB_NOT_FOUND: i64 = 21;
+const ERROR_DUPLICATE: i64 = 22;
+const ERROR_LOW_DIFFICULTY: i64 = 23;
+const ERROR_UNAUTHORIZED: i64 = 24;
+
+#[derive(Error, Debug)]
+pub enum StratumError {
+    #[error("Pool URL must look like tcp://host:port, got {0}")]
+    InvalidPoolUrl(String),
+
+    #[error("Pool sent an invalid message: {0}")]
+    Proto(#[from] ProtoError),
+
+    #[error("Connection failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A JSON-RPC call from a Stratum client
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+/// What one message to a [`Session`] produced
+#[derive(Debug, Default, PartialEq)]
+pub struct Output {
+    /// Messages for the pool
+    pub to_pool: Vec<ClientMessage>,
+    /// JSON-RPC lines for the Stratum client, without newlines
+    pub to_miner: Vec<String>,
+    /// The connection is over once these are sent
+    pub close: bool,
+}
+
+/// Translation state of one Stratum connection
+pub struct Session {
+    id: String,
+    agent: Option<String>,
+    /// Stratum username once `hello` went out
+    user: Option<String>,
+    authorize: Option<Value>,
+    authorized: bool,
+    version: u32,
+    /// Newest last
+    jobs: Vec<Job>,
+    /// Submits awaiting the pool's answer, by job and nonce
+    submits: HashMap<(String, u64), Value>,
+    hasher: Option<UniversalHash>,
+}
+
+impl Session {
+    /// A session identified to its client as `id`
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            agent: None,
+            user: None,
+            authorize: None,
+            authorized: false,
+            version: 0,
+            jobs: Vec::new(),
+            submits: HashMap::new(),
+            hasher: None,
+        }
+    }
+
+    /// Handle a line from the Stratum client
+    pub fn from_miner(&mut self, line: &str) -> Output {
+        let mut out = Output::default();
+        let request: Request = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                out.to_miner.push(error(
+                    Value::Null,
+                    ERROR_OTHER,
+                    &format!("Parse error: {}", e),
+                ));
+                return out;
+            }
+        };
+        let id = request.id;
+        let params = request.params;
+        match request.method.as_str() {
+            "mining.subscribe" => {
+                self.agent = params.first().and_then(Value::as_str).map(str::to_string);
+                let subscriptions = json!([
+                    ["mining.set_difficulty", self.id],
+                    ["mining.notify", self.id]
+                ]);
+                out.to_miner
+                    .push(result(id, json!([subscriptions, "", EXTRANONCE2_SIZE])));
+            }
+            "mining.authorize" => {
+                let user = params.first().and_then(Value::as_str).unwrap_or_default();
+                match &self.user {
+                    Some(known) if known == user && self.authorized => {
+                        out.to_miner.push(result(id, json!(true)));
+                    }
+                    Some(known) if known == user => {
+                        out.to_miner
+                            .push(error(id, ERROR_OTHER, "Authorization in progress"));
+                    }
+                    Some(_) => out.to_miner.push(error(
+                        id,
+                        ERROR_UNAUTHORIZED,
+                        "One worker per connection",
+                    )),
+                    None if user.is_empty() => out.to_miner.push(error(
+                        id,
+                        ERROR_UNAUTHORIZED,
+                        "Username must be ADDRESS.WORKER",
+                    )),
+                    None => {
+                        let (address, worker) = match user.split_once('.') {
+                            Some((address, worker)) => (address, Some(worker.to_string())),
+                            None => (user, None),
+                        };
+                        out.to_pool.push(ClientMessage::Hello {
+                            versions: SUPPORTED_VERSIONS.to_vec(),
+                            address: address.to_string(),
+                            worker,
+                            agent: self.agent.clone(),
+                            binary: false,
+                        });
+                        self.user = Some(user.to_string());
+                        self.authorize = Some(id);
+                    }
+                }
+            }
+            "mining.submit" => match self.submit(&params) {
+                Ok(share) => {
+                    if let ClientMessage::Share { job_id, nonce, .. } = &share {
+                        self.submits.insert((job_id.clone(), *nonce), id);
+                    }
+                    out.to_pool.push(share);
+                }
+                Err((code, message)) => out.to_miner.push(error(id, code, &message)),
+            },
+            "mining.extranonce.subscribe" => out.to_miner.push(result(id, json!(false))),
+            method => out.to_miner.push(error(
+                id,
+                ERROR_OTHER,
+                &format!("Unknown method {}", method),
+            )),
+        }
+        out
+    }
+
+    /// Handle a message from the pool
+    pub fn from_pool(&mut self, message: ServerMessage) -> Output {
+        let mut out = Output::default();
+        match message {
+            ServerMessage::Welcome {
+                version,
+                difficulty,
+                ..
+            } => {
+                self.version = version;
+                self.authorized = true;
+                if let Some(id) = self.authorize.take() {
+                    out.to_miner.push(result(id, json!(true)));
+                }
+                out.to_miner.push(set_difficulty(difficulty));
+            }
+            ServerMessage::Job(job) => out.to_miner.push(self.notify(job)),
+            ServerMessage::JobUpdate(update) => {
+                // An update with no job to apply it to is the pool's mistake
+                if let Some(last) = self.jobs.last() {
+                    let job = last.apply(&update);
+                    out.to_miner.push(self.notify(job));
+                }
+            }
+            ServerMessage::SetDifficulty { difficulty } => {
+                out.to_miner.push(set_difficulty(difficulty));
+            }
+            ServerMessage::Accepted { job_id, nonce, .. } => {
+                if let Some(id) = self.submits.remove(&(job_id, nonce)) {
+                    out.to_miner.push(result(id, json!(true)));
+                }
+            }
+            ServerMessage::Rejected {
+                job_id,
+                nonce,
+                reason,
+            } => {
+                if let Some(id) = self.submits.remove(&(job_id, nonce)) {
+                    let (code, message) = match reason {
+                        RejectReason::UnknownJob | RejectReason::StaleJob => {
+                            (ERROR_JOB_NOT_FOUND, "Job not found")
+                        }
+                        RejectReason::Duplicate => (ERROR_DUPLICATE, "Duplicate share"),
+                        RejectReason::LowDifficulty => {
+                            (ERROR_LOW_DIFFICULTY, "Low difficulty share")
+                        }
+                        RejectReason::InvalidHash => (ERROR_OTHER, "Invalid hash"),
+                        RejectReason::Other => (ERROR_OTHER, "Rejected"),
+                    };
+                    out.to_miner.push(error(id, code, message));
+                }
+            }
+            ServerMessage::Error { message } => {
+                match self.authorize.take() {
+                    Some(id) => out.to_miner.push(error(id, ERROR_UNAUTHORIZED, &message)),
+                    None => out
+                        .to_miner
+                        .push(notification("client.show_message", json!([message]))),
+                }
+                out.close = true;
+            }
+            ServerMessage::Keepalive | ServerMessage::Unknown => {}
+        }
+        out
+    }
+
+    fn notify(&mut self, job: Job) -> String {
+        let params = json!([
+            job.job_id,
+            job.seed,
+            job.address,
+            "",
+            [],
+            format!("{:08x}", self.version),
+            format!("{:08x}", job.network_difficulty),
+            format!("{:08x}", job.timestamp),
+            true
+        ]);
+        self.jobs.push(job);
+        if self.jobs.len() > KEPT_JOBS {
+            self.jobs.remove(0);
+        }
+        notification("mining.notify", params)
+    }
+
+    /// The share a `mining.submit` stands for
+    fn submit(&mut self, params: &[Value]) -> Result<ClientMessage, (i64, String)> {
+        if !self.authorized {
+            return Err((ERROR_UNAUTHORIZED, "Unauthorized worker".to_string()));
+        }
+        let param = |i: usize| params.get(i).and_then(Value::as_str).unwrap_or_default();
+        let job_id = param(1).to_string();
+        let nonce = parse_nonce(param(2), param(4))
+            .ok_or_else(|| (ERROR_OTHER, "Invalid nonce".to_string()))?;
+        let hash = match params.get(5).and_then(Value::as_str) {
+            Some(hash) => hash.to_string(),
+            None => self.hash(&job_id, nonce)?,
+        };
+        Ok(ClientMessage::Share {
+            job_id,
+            nonce,
+            hash,
+        })
+    }
+
+    /// Hash `nonce` of a kept job, for submits without a hash
+    fn hash(&mut self, job_id: &str, nonce: u64) -> Result<String, (i64, String)> {
+        let not_found = || (ERROR_JOB_NOT_FOUND, "Job not found".to_string());
+        let job = self
+            .jobs
+            .iter()
+            .find(|job| job.job_id == job_id)
+            .ok_or_else(not_found)?;
+        let mut seed = [0u8; 32];
+        hex::decode_to_slice(&job.seed, &mut seed).map_err(|_| not_found())?;
+        let input = proof_input(&seed, &job.address, &[], job.timestamp, nonce);
+        let hasher = self.hasher.get_or_insert_with(UniversalHash::new);
+        Ok(hex::encode(hasher.hash(&input)))
+    }
+}
+
+/// `extranonce2 || nonce`, or a 16-digit `nonce` alone
+fn parse_nonce(extranonce2: &str, nonce: &str) -> Option<u64> {
+    let hex = match (extranonce2.len(), nonce.len()) {
+        (_, 16) => nonce.to_string(),
+        (8, 8) => format!("{}{}", extranonce2, nonce),
+        _ => return None,
+    };
+    u64::from_str_radix(&hex, 16).ok()
+}
+
+fn result(id: Value, result: Value) -> String {
+    json!({ "id": id, "result": result, "error": null }).to_string()
+}
+
+fn error(id: Value, code: i64, message: &str) -> String {
+    json!({ "id": id, "result": null, "error": [code, message, null] }).to_string()
+}
+
+fn notification(method: &str, params: Value) -> String {
+    json!({ "id": null, "method": method, "params": params }).to_string()
+}
+
+fn set_difficulty(difficulty: u32) -> String {
+    notification("mining.set_difficulty", json!([difficulty]))
+}
+
+/// Parse `tcp://host:port` into `host:port`
+pub fn parse_pool_url(url: &str) -> Result<String, StratumError> {
+    url.strip_prefix("tcp://")
+        .filter(|addr| {
+            addr.rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+        })
+        .map(str::to_string)
+        .ok_or_else(|| StratumError::InvalidPoolUrl(url.to_string()))
+}
+
+/// Accept Stratum clients on `listener` and bridge each to the pool at
+/// `pool` (`host:port`) until accepting fails
+pub async fn serve(listener: TcpListener, pool: String) -> Result<(), StratumError> {
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    let mut sessions: u64 = 0;
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        sessions += 1;
+        let pool = pool.clone();
+        let session = Session::new(format!("{:08x}", sessions));
+        tokio::spawn(async move {
+            if let Err(e) = bridge(stream, &pool, session).await {
+                eprintln!("Warning: Stratum connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Relay one Stratum connection through `session` until either side closes
+pub async fn bridge(
+    miner: TcpStream,
+    pool: &str,
+    mut session: Session,
+) -> Result<(), StratumError> {
+    let upstream = TcpStream::connect(pool).await?;
+    upstream.set_nodelay(true)?;
+    let (miner_read, mut miner_write) = miner.into_split();
+    let (pool_read, mut pool_write) = upstream.into_split();
+    let mut miner_lines = BufReader::new(miner_read).lines();
+    let mut pool_lines = BufReader::new(pool_read).lines();
+    let mut keepalive = tokio::time::interval(Duration::from_secs(KEEPALIVE_INTERVAL_SECS));
+    keepalive.tick().await;
+
+    loop {
+        let out = tokio::select! {
+            line = miner_lines.next_line() => match line? {
+                Some(line) if line.trim().is_empty() => continue,
+                Some(line) => session.from_miner(&line),
+                None => return Ok(()),
+            },
+            line = pool_lines.next_line() => match line? {
+                Some(line) => session.from_pool(ServerMessage::from_frame(&line)?),
+                None => return Ok(()),
+            },
+            _ = keepalive.tick() => Output {
+                to_pool: vec![ClientMessage::Keepalive],
+                ..Output::default()
+            },
+        };
+        for message in &out.to_pool {
+            pool_write
+                .write_all(format!("{}\n", message.to_frame()).as_bytes())
+                .await?;
+        }
+        for line in &out.to_miner {
+            miner_write
+                .write_all(format!("{}\n", line).as_bytes())
+                .await?;
+        }
+        if out.close {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str) -> Job {
+        Job {
+            job_id: id.to_string(),
+            seed: "11".repeat(32),
+            address: "bostrom1pool".to_string(),
+            timestamp: 1_700_000_000,
+            network_difficulty: 20,
+        }
+    }
+
+    fn line(value: Value) -> String {
+        value.to_string()
+    }
+
+    fn parsed(line: &str) -> Value {
+        serde_json::from_str(line).unwrap()
+    }
+
+    /// A session that subscribed and authorized as `bostrom1miner.rig`
+    fn signed_in() -> Session {
+        let mut session = Session::new("s1");
+        session.from_miner(&line(
+            json!({"id": 1, "method": "mining.subscribe", "params": ["miner/1.0"]}),
+        ));
+        session.from_miner(&line(
+            json!({"id": 2, "method": "mining.authorize", "params": ["bostrom1miner.rig", "x"]}),
+        ));
+        session.from_pool(ServerMessage::Welcome {
+            version: 2,
+            session: "p1".to_string(),
+            difficulty: 12,
+            binary: false,
+        });
+        session
+    }
+
+    #[test]
+    fn test_subscribe_and_authorize() {
+        let mut session = Session::new("s1");
+        let out = session.from_miner(&line(
+            json!({"id": 1, "method": "mining.subscribe", "params": ["miner/1.0"]}),
+        ));
+        assert_eq!(
+            parsed(&out.to_miner[0]),
+            json!({"id": 1, "result": [[["mining.set_difficulty", "s1"], ["mining.notify", "s1"]], "", 4], "error": null})
+        );
+
+        // Shares before authorizing go nowhere
+        let out = session.from_miner(&line(json!({"id": 9, "method": "mining.submit", "params": ["w", "j1", "00000000", "0", "00000001"]})));
+        assert_eq!(parsed(&out.to_miner[0])["error"][0], json!(24));
+
+        let out = session.from_miner(&line(
+            json!({"id": 2, "method": "mining.authorize", "params": ["bostrom1miner.rig", "x"]}),
+        ));
+        assert!(out.to_miner.is_empty());
+        assert_eq!(
+            out.to_pool,
+            vec![ClientMessage::Hello {
+                versions: SUPPORTED_VERSIONS.to_vec(),
+                address: "bostrom1miner".to_string(),
+                worker: Some("rig".to_string()),
+                agent: Some("miner/1.0".to_string()),
+                binary: false,
+            }]
+        );
+        let out = session.from_pool(ServerMessage::Welcome {
+            version: 2,
+            session: "p1".to_string(),
+            difficulty: 12,
+            binary: false,
+        });
+        assert_eq!(
+            parsed(&out.to_miner[0]),
+            json!({"id": 2, "result": true, "error": null})
+        );
+        assert_eq!(
+            parsed(&out.to_miner[1]),
+            json!({"id": null, "method": "mining.set_difficulty", "params": [12]})
+        );
+
+        let out = session.from_miner(&line(
+            json!({"id": 3, "method": "mining.authorize", "params": ["bostrom1other", "x"]}),
+        ));
+        assert_eq!(parsed(&out.to_miner[0])["error"][0], json!(24));
+        let out = session.from_miner(&line(
+            json!({"id": 4, "method": "mining.configure", "params": []}),
+        ));
+        assert_eq!(parsed(&out.to_miner[0])["error"][0], json!(20));
+        let out = session.from_miner("not json");
+        assert_eq!(parsed(&out.to_miner[0])["id"], Value::Null);
+    }
+
+    #[test]
+    fn test_refused_authorize_closes() {
+        let mut session = Session::new("s1");
+        session.from_miner(&line(
+            json!({"id": 7, "method": "mining.authorize", "params": ["nobody", ""]}),
+        ));
+        let out = session.from_pool(ServerMessage::Error {
+            message: "invalid address".to_string(),
+        });
+        assert!(out.close);
+        assert_eq!(
+            parsed(&out.to_miner[0]),
+            json!({"id": 7, "result": null, "error": [24, "invalid address", null]})
+        );
+    }
+
+    #[test]
+    fn test_jobs_and_shares() {
+        let mut session = signed_in();
+        let out = session.from_pool(ServerMessage::Job(job("j1")));
+        assert_eq!(
+            parsed(&out.to_miner[0]),
+            json!({"id": null, "method": "mining.notify", "params": [
+                "j1", "11".repeat(32), "bostrom1pool", "", [], "00000002", "00000014", "6553f100", true
+            ]})
+        );
+        // Updates are sent on as whole jobs
+        let out = session.from_pool(ServerMessage::JobUpdate(uhash_pool_proto::JobUpdate {
+            job_id: "j2".to_string(),
+            seed: None,
+            timestamp: None,
+            network_difficulty: Some(21),
+        }));
+        let params = &parsed(&out.to_miner[0])["params"];
+        assert_eq!(
+            (&params[0], &params[1], &params[6]),
+            (&json!("j2"), &json!("11".repeat(32)), &json!("00000015"))
+        );
+
+        // extranonce2 || nonce, hashed by the shim
+        let out = session.from_miner(&line(json!({"id": 10, "method": "mining.submit", "params": ["bostrom1miner.rig", "j1", "00000001", "6553f100", "00000002"]})));
+        let ClientMessage::Share {
+            job_id,
+            nonce,
+            hash,
+        } = &out.to_pool[0]
+        else {
+            panic!("expected a share");
+        };
+        assert_eq!((job_id.as_str(), *nonce), ("j1", 0x1_0000_0002));
+        let mut input = [0x11u8; 32].to_vec();
+        input.extend_from_slice(b"bostrom1pool");
+        input.extend_from_slice(&1_700_000_000u64.to_le_bytes());
+        input.extend_from_slice(&nonce.to_le_bytes());
+        assert_eq!(*hash, hex::encode(UniversalHash::new().hash(&input)));
+
+        // A full nonce with its hash, for uhash-aware miners
+        let out = session.from_miner(&line(json!({"id": 11, "method": "mining.submit", "params": ["w", "j2", "", "0", "0000000000000007", "ab"]})));
+        assert_eq!(
+            out.to_pool,
+            vec![ClientMessage::Share {
+                job_id: "j2".to_string(),
+                nonce: 7,
+                hash: "ab".to_string(),
+            }]
+        );
+        let out = session.from_miner(&line(json!({"id": 12, "method": "mining.submit", "params": ["w", "gone", "00000000", "0", "00000001"]})));
+        assert_eq!(parsed(&out.to_miner[0])["error"][0], json!(21));
+        let out = session.from_miner(&line(
+            json!({"id": 13, "method": "mining.submit", "params": ["w", "j1", "zz", "0", "1"]}),
+        ));
+        assert_eq!(parsed(&out.to_miner[0])["error"][1], json!("Invalid nonce"));
+
+        // Answers go to the submit they belong to
+        let out = session.from_pool(ServerMessage::Rejected {
+            job_id: "j2".to_string(),
+            nonce: 7,
+            reason: RejectReason::Duplicate,
+        });
+        assert_eq!(
+            parsed(&out.to_miner[0]),
+            json!({"id": 11, "result": null, "error": [22, "Duplicate share", null]})
+        );
+        let out = session.from_pool(ServerMessage::Accepted {
+            job_id: "j1".to_string(),
+            nonce: 0x1_0000_0002,
+            proof: false,
+        });
+        assert_eq!(
+            parsed(&out.to_miner[0]),
+            json!({"id": 10, "result": true, "error": null})
+        );
+        assert_eq!(
+            session.from_pool(ServerMessage::Keepalive),
+            Output::default()
+        );
+    }
+
+    #[test]
+    fn test_bridge() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            // A pool that welcomes anyone and sends one job
+            let pool = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let pool_addr = pool.local_addr().unwrap().to_string();
+            tokio::spawn(async move {
+                let (stream, _) = pool.accept().await.unwrap();
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let hello = lines.next_line().await.unwrap().unwrap();
+                assert!(matches!(
+                    ClientMessage::from_frame(&hello),
+                    Ok(ClientMessage::Hello { .. })
+                ));
+                for message in [
+                    ServerMessage::Welcome {
+                        version: 2,
+                        session: "p1".to_string(),
+                        difficulty: 8,
+                        binary: false,
+                    },
+                    ServerMessage::Job(job("j1")),
+                ] {
+                    let frame = format!("{}\n", message.to_frame());
+                    write.write_all(frame.as_bytes()).await.unwrap();
+                }
+                // Hold the connection until the shim hangs up
+                while let Ok(Some(_)) = lines.next_line().await {}
+            });
+
+            let shim = TcpListener::bind("127.0.0.1:0").unwrap();
+            let shim_addr = shim.local_addr().unwrap();
+            tokio::spawn(serve(shim, pool_addr));
+
+            let miner = TcpStream::connect(shim_addr).await.unwrap();
+            let (read, mut write) = miner.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let requests = [
+                json!({"id": 1, "method": "mining.subscribe", "params": []}),
+                json!({"id": 2, "method": "mining.authorize", "params": ["bostrom1miner", ""]}),
+            ];
+            for request in requests {
+                write
+                    .write_all(format!("{}\n", request).as_bytes())
+                    .await
+                    .unwrap();
+            }
+            let mut answers = Vec::new();
+            for _ in 0..4 {
+                answers.push(parsed(&lines.next_line().await.unwrap().unwrap()));
+            }
+            assert_eq!(answers[0]["id"], json!(1));
+            assert_eq!(answers[1], json!({"id": 2, "result": true, "error": null}));
+            assert_eq!(answers[2]["method"], json!("mining.set_difficulty"));
+            assert_eq!(answers[3]["params"][0], json!("j1"));
+        });
+    }
+
+    #[test]
+    fn test_parse_pool_url() {
+        assert_eq!(
+            parse_pool_url("tcp://pool.example:3334").unwrap(),
+            "pool.example:3334"
+        );
+        assert!(parse_pool_url("pool.example:3334").is_err());
+        assert!(parse_pool_url("tcp://pool.example").is_err());
+    }
+}
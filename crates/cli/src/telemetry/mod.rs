@@ -0,0 +1,371 @@
+//! Opt-in benchmark sharing
+//!
+//! `uhash benchmark --share` uploads a [`BenchmarkReport`] to the community
+//! endpoint in `telemetry.url`, and `uhash devices` downloads the aggregated
+//! [`DeviceTable`], so the phone-to-desktop ratio rests on measured devices.
+//! Nothing is sent unless `--share` is given, and a report identifies the
+//! hardware, not the miner: no address, host name or mining history.
+//!
+//! The endpoint serves:
+//!
+//! - `POST /benchmarks` with a [`BenchmarkReport`]
+//! - `GET /devices` — the [`DeviceTable`] built by [`aggregate`]
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Limit on a request to the benchmark endpoint
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Logical CPUs at which an unknown machine counts as a server
+const SERVER_THREADS: usize = 32;
+
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("No benchmark endpoint configured (set telemetry.url)")]
+    NoEndpoint,
+
+    #[error("Request to {url} failed: {reason}")]
+    Http { url: String, reason: String },
+
+    #[error("Invalid device class '{0}': expected phone, laptop, desktop or server")]
+    InvalidDeviceClass(String),
+}
+
+/// Kind of machine a benchmark ran on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceClass {
+    Phone,
+    Laptop,
+    Desktop,
+    Server,
+}
+
+impl DeviceClass {
+    /// Best guess for this machine: phones by OS, laptops by a battery
+    /// (Linux only), servers by core count; anything else is a desktop
+    pub fn detect() -> Self {
+        if cfg!(any(target_os = "android", target_os = "ios")) {
+            return Self::Phone;
+        }
+        if has_battery() {
+            return Self::Laptop;
+        }
+        if logical_cpus() >= SERVER_THREADS {
+            return Self::Server;
+        }
+        Self::Desktop
+    }
+}
+
+impl std::fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Phone => "phone",
+            Self::Laptop => "laptop",
+            Self::Desktop => "desktop",
+            Self::Server => "server",
+        })
+    }
+}
+
+impl std::str::FromStr for DeviceClass {
+    type Err = TelemetryError;
+
+    fn from_str(s: &str) -> Result<Self, TelemetryError> {
+        match s.to_ascii_lowercase().as_str() {
+            "phone" => Ok(Self::Phone),
+            "laptop" => Ok(Self::Laptop),
+            "desktop" => Ok(Self::Desktop),
+            "server" => Ok(Self::Server),
+            _ => Err(TelemetryError::InvalidDeviceClass(s.to_string())),
+        }
+    }
+}
+
+/// One shared benchmark result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub device_class: DeviceClass,
+    /// CPU model as the OS reports it, e.g. `Apple M2`
+    pub cpu_model: String,
+    /// Logical CPUs of the device
+    pub threads: usize,
+    /// Single-thread H/s
+    pub hashrate: f64,
+    pub os: String,
+    pub arch: String,
+    /// Version of the miner that ran the benchmark
+    pub version: String,
+}
+
+impl BenchmarkReport {
+    /// A report for this machine
+    pub fn new(device_class: DeviceClass, hashrate: f64) -> Self {
+        Self {
+            device_class,
+            cpu_model: cpu_model(),
+            threads: logical_cpus(),
+            hashrate,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Reports of one CPU model in one device class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceStats {
+    pub device_class: DeviceClass,
+    pub cpu_model: String,
+    pub samples: usize,
+    /// Median logical CPUs
+    pub threads: usize,
+    /// Median single-thread H/s
+    pub median_hashrate: f64,
+}
+
+/// Reports of one device class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassStats {
+    pub device_class: DeviceClass,
+    pub samples: usize,
+    /// Median single-thread H/s
+    pub median_hashrate: f64,
+}
+
+/// Aggregated benchmark results, as `GET /devices` returns them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTable {
+    /// Fastest first
+    pub devices: Vec<DeviceStats>,
+    /// In [`DeviceClass`] order
+    pub classes: Vec<ClassStats>,
+}
+
+impl DeviceTable {
+    /// Median hashrate of `class` relative to desktops, if both have reports
+    pub fn ratio_to_desktop(&self, class: DeviceClass) -> Option<f64> {
+        let median = |class| {
+            self.classes
+                .iter()
+                .find(|c| c.device_class == class)
+                .map(|c| c.median_hashrate)
+                .filter(|&m| m > 0.0)
+        };
+        Some(median(class)? / median(DeviceClass::Desktop)?)
+    }
+}
+
+/// Group `reports` by device class and CPU model, taking medians
+///
+/// Medians keep a few throttled or overclocked machines from skewing a row.
+pub fn aggregate(reports: &[BenchmarkReport]) -> DeviceTable {
+    let mut devices: BTreeMap<(DeviceClass, &str), Vec<&BenchmarkReport>> = BTreeMap::new();
+    let mut classes: BTreeMap<DeviceClass, Vec<f64>> = BTreeMap::new();
+    for report in reports.iter().filter(|r| r.hashrate.is_finite()) {
+        devices
+            .entry((report.device_class, report.cpu_model.as_str()))
+            .or_default()
+            .push(report);
+        classes
+            .entry(report.device_class)
+            .or_default()
+            .push(report.hashrate);
+    }
+
+    let mut devices: Vec<DeviceStats> = devices
+        .into_iter()
+        .map(|((device_class, cpu_model), reports)| DeviceStats {
+            device_class,
+            cpu_model: cpu_model.to_string(),
+            samples: reports.len(),
+            threads: median(reports.iter().map(|r| r.threads as f64).collect()) as usize,
+            median_hashrate: median(reports.iter().map(|r| r.hashrate).collect()),
+        })
+        .collect();
+    devices.sort_by(|a, b| b.median_hashrate.total_cmp(&a.median_hashrate));
+    let classes = classes
+        .into_iter()
+        .map(|(device_class, hashrates)| ClassStats {
+            device_class,
+            samples: hashrates.len(),
+            median_hashrate: median(hashrates),
+        })
+        .collect();
+    DeviceTable { devices, classes }
+}
+
+/// Upload `report` to the endpoint at `url`
+pub async fn share(url: &str, report: &BenchmarkReport) -> Result<(), TelemetryError> {
+    let endpoint = endpoint(url, "benchmarks")?;
+    let failed = |reason: String| TelemetryError::Http {
+        url: endpoint.clone(),
+        reason,
+    };
+    let resp = http_client()
+        .post(&endpoint)
+        .json(report)
+        .send()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(failed(format!("HTTP {}", resp.status())));
+    }
+    Ok(())
+}
+
+/// Download the aggregated table from the endpoint at `url`
+pub async fn fetch_devices(url: &str) -> Result<DeviceTable, TelemetryError> {
+    let endpoint = endpoint(url, "devices")?;
+    let failed = |reason: String| TelemetryError::Http {
+        url: endpoint.clone(),
+        reason,
+    };
+    let resp = http_client()
+        .get(&endpoint)
+        .send()
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(failed(format!("HTTP {}", resp.status())));
+    }
+    resp.json()
+        .await
+        .map_err(|e| failed(format!("invalid reply: {}", e)))
+}
+
+fn endpoint(url: &str, path: &str) -> Result<String, TelemetryError> {
+    if url.is_empty() {
+        return Err(TelemetryError::NoEndpoint);
+    }
+    Ok(format!("{}/{}", url.trim_end_matches('/'), path))
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn logical_cpus() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn has_battery() -> bool {
+    std::fs::read_dir("/sys/class/power_supply")
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+        })
+        .unwrap_or(false)
+}
+
+/// The CPU model the OS reports, or `unknown`
+pub fn cpu_model() -> String {
+    let model = if cfg!(target_os = "macos") {
+        std::process::Command::new("sysctl")
+            .args(["-n", "machdep.cpu.brand_string"])
+            .output()
+            .ok()
+            .and_then(|out| String::from_utf8(out.stdout).ok())
+    } else if cfg!(windows) {
+        std::env::var("PROCESSOR_IDENTIFIER").ok()
+    } else {
+        std::fs::read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|cpuinfo| cpuinfo_model(&cpuinfo))
+    };
+    model
+        .map(|m| m.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// `model name` on x86, `Hardware` or `Model` on ARM boards and phones
+fn cpuinfo_model(cpuinfo: &str) -> Option<String> {
+    ["model name", "Hardware", "Model"].iter().find_map(|key| {
+        cpuinfo.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == *key).then(|| value.trim().to_string())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(device_class: DeviceClass, cpu_model: &str, hashrate: f64) -> BenchmarkReport {
+        BenchmarkReport {
+            device_class,
+            cpu_model: cpu_model.to_string(),
+            threads: 8,
+            hashrate,
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            version: "0.0.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_takes_medians_per_device_and_class() {
+        let table = aggregate(&[
+            report(DeviceClass::Desktop, "Ryzen 9", 900.0),
+            report(DeviceClass::Desktop, "Ryzen 9", 1000.0),
+            report(DeviceClass::Desktop, "Ryzen 9", 5000.0),
+            report(DeviceClass::Desktop, "Core i5", 600.0),
+            report(DeviceClass::Phone, "Snapdragon 8", 250.0),
+            report(DeviceClass::Phone, "Snapdragon 8", f64::NAN),
+        ]);
+
+        assert_eq!(table.devices.len(), 3);
+        assert_eq!(table.devices[0].cpu_model, "Ryzen 9");
+        assert_eq!(table.devices[0].samples, 3);
+        assert_eq!(table.devices[0].median_hashrate, 1000.0);
+        assert_eq!(table.devices[2].samples, 1);
+
+        assert_eq!(table.classes[0].device_class, DeviceClass::Phone);
+        assert_eq!(table.classes[1].median_hashrate, 950.0);
+        assert_eq!(
+            table.ratio_to_desktop(DeviceClass::Phone),
+            Some(250.0 / 950.0)
+        );
+        assert_eq!(table.ratio_to_desktop(DeviceClass::Server), None);
+    }
+
+    #[test]
+    fn test_cpuinfo_model() {
+        let x86 = "processor\t: 0\nmodel name\t: AMD Ryzen 9 7950X 16-Core Processor\n";
+        assert_eq!(
+            cpuinfo_model(x86).as_deref(),
+            Some("AMD Ryzen 9 7950X 16-Core Processor")
+        );
+        let arm =
+            "processor\t: 0\nBogoMIPS\t: 108.00\n\nHardware\t: Qualcomm Technologies, Inc SM8550\n";
+        assert_eq!(
+            cpuinfo_model(arm).as_deref(),
+            Some("Qualcomm Technologies, Inc SM8550")
+        );
+        assert_eq!(cpuinfo_model("processor\t: 0\n"), None);
+    }
+}
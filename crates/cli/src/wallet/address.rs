@@ -0,0 +1,126 @@
+//! Bech32 address validation
+//!
+//! Catches typos in user-supplied addresses (wrong prefix, bad checksum,
+//! truncated copy/paste) before they end up in a transaction.
+
+use cosmrs::AccountId;
+use std::str::FromStr;
+
+use super::{WalletError, BOSTROM_PREFIX};
+
+/// Payload length of an account address (RIPEMD160 of the public key)
+const ACCOUNT_ADDRESS_LEN: usize = 20;
+
+/// Payload length of a contract or module address (SHA256-derived)
+const CONTRACT_ADDRESS_LEN: usize = 32;
+
+/// What a valid address points at, judged by its payload length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    /// 20-byte externally owned account
+    Account,
+    /// 32-byte contract or module account
+    Contract,
+}
+
+impl AddressKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressKind::Account => "account",
+            AddressKind::Contract => "contract",
+        }
+    }
+}
+
+fn invalid(address: &str, reason: impl Into<String>) -> WalletError {
+    WalletError::InvalidAddress {
+        address: address.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Validate a bostrom address (bech32 checksum, `bostrom` prefix, payload length)
+pub fn validate_address(address: &str) -> Result<(AccountId, AddressKind), WalletError> {
+    validate_address_with_prefix(address, BOSTROM_PREFIX)
+}
+
+/// Validate a bech32 address against an expected human-readable prefix
+pub fn validate_address_with_prefix(
+    address: &str,
+    prefix: &str,
+) -> Result<(AccountId, AddressKind), WalletError> {
+    if address != address.to_lowercase() && address != address.to_uppercase() {
+        return Err(invalid(
+            address,
+            "mixed-case bech32 strings are not allowed",
+        ));
+    }
+    let normalized = address.to_lowercase();
+
+    let (hrp, _) = normalized
+        .rsplit_once('1')
+        .ok_or_else(|| invalid(address, "missing bech32 separator '1'"))?;
+    if hrp != prefix {
+        return Err(invalid(
+            address,
+            format!("expected prefix '{}', found '{}'", prefix, hrp),
+        ));
+    }
+
+    let account = AccountId::from_str(&normalized)
+        .map_err(|_| invalid(address, "bad bech32 checksum or encoding (check for typos)"))?;
+    let kind = match account.to_bytes().len() {
+        ACCOUNT_ADDRESS_LEN => AddressKind::Account,
+        CONTRACT_ADDRESS_LEN => AddressKind::Contract,
+        len => {
+            return Err(invalid(
+                address,
+                format!(
+                    "unexpected payload length {} (expected {} or {} bytes)",
+                    len, ACCOUNT_ADDRESS_LEN, CONTRACT_ADDRESS_LEN
+                ),
+            ))
+        }
+    };
+    Ok((account, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn test_validate_wallet_address() {
+        let wallet = Wallet::new().unwrap();
+        let (account, kind) = validate_address(&wallet.address_str()).unwrap();
+        assert_eq!(account, *wallet.address());
+        assert_eq!(kind, AddressKind::Account);
+
+        // Uppercase bech32 is valid
+        assert!(validate_address(&wallet.address_str().to_uppercase()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_address_rejects_typos() {
+        let address = Wallet::new().unwrap().address_str();
+
+        // Flip the last character to break the checksum
+        let mut typo = address.clone();
+        let last = typo.pop().unwrap();
+        typo.push(if last == 'q' { 'p' } else { 'q' });
+        assert!(matches!(
+            validate_address(&typo),
+            Err(WalletError::InvalidAddress { .. })
+        ));
+
+        let truncated = &address[..address.len() - 1];
+        assert!(validate_address(truncated).is_err());
+
+        let cosmos = address.replacen("bostrom", "cosmos", 1);
+        let err = validate_address(&cosmos).unwrap_err().to_string();
+        assert!(err.contains("expected prefix 'bostrom'"));
+
+        assert!(validate_address("bostrom").is_err());
+    }
+}
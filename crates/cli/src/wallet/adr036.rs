@@ -0,0 +1,204 @@
+//! ADR-036 off-chain message signing
+//!
+//! Signs arbitrary data as a Cosmos `sign/MsgSignData` amino sign doc with
+//! zeroed chain-id, account number, sequence and fee, matching Keplr's
+//! `signArbitrary`, so signatures can be checked by any ADR-036 verifier.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bip32::secp256k1::ecdsa::signature::{Signer, Verifier};
+use bip32::secp256k1::ecdsa::{Signature, VerifyingKey};
+use cosmrs::crypto::PublicKey;
+use cosmrs::AccountId;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use super::{Wallet, WalletError};
+
+/// Amino type of a compressed secp256k1 public key
+pub const PUBKEY_TYPE: &str = "tendermint/PubKeySecp256k1";
+
+/// Amino-JSON public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StdPubKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    /// Base64 compressed public key
+    pub value: String,
+}
+
+/// Detached signature in the Cosmos `StdSignature` JSON shape
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StdSignature {
+    pub pub_key: StdPubKey,
+    /// Base64 64-byte `r || s` signature
+    pub signature: String,
+}
+
+/// Canonical ADR-036 sign doc bytes (sorted keys, no whitespace)
+pub fn sign_doc_bytes(signer: &str, data: &[u8]) -> Vec<u8> {
+    // serde_json::json! sorts object keys, giving the canonical amino JSON ordering
+    let doc = serde_json::json!({
+        "account_number": "0",
+        "chain_id": "",
+        "fee": { "amount": [], "gas": "0" },
+        "memo": "",
+        "msgs": [{
+            "type": "sign/MsgSignData",
+            "value": { "data": STANDARD.encode(data), "signer": signer },
+        }],
+        "sequence": "0",
+    });
+    doc.to_string().into_bytes()
+}
+
+impl Wallet {
+    /// Sign arbitrary data with the wallet key per ADR-036
+    pub fn sign_arbitrary(&self, data: &[u8]) -> Result<StdSignature, WalletError> {
+        let signing_key = self.signing_key()?;
+        let doc = sign_doc_bytes(&self.address_str(), data);
+        let signature: Signature = signing_key.sign(&doc);
+        let public_key = signing_key.verifying_key().to_sec1_bytes();
+
+        Ok(StdSignature {
+            pub_key: StdPubKey {
+                key_type: PUBKEY_TYPE.to_string(),
+                value: STANDARD.encode(public_key),
+            },
+            signature: STANDARD.encode(signature.to_bytes()),
+        })
+    }
+}
+
+/// Sign arbitrary data per ADR-036 with a transaction signing key
+///
+/// For callers that hold the key used for proof transactions rather than a
+/// [`Wallet`]; `signer` must be the key's address.
+pub fn sign_arbitrary(
+    signing_key: &cosmrs::crypto::secp256k1::SigningKey,
+    signer: &str,
+    data: &[u8],
+) -> Result<StdSignature, WalletError> {
+    let signature = signing_key
+        .sign(&sign_doc_bytes(signer, data))
+        .map_err(|e| WalletError::InvalidSignature(e.to_string()))?;
+
+    Ok(StdSignature {
+        pub_key: StdPubKey {
+            key_type: PUBKEY_TYPE.to_string(),
+            value: STANDARD.encode(signing_key.public_key().to_bytes()),
+        },
+        signature: STANDARD.encode(signature.to_bytes()),
+    })
+}
+
+/// Verify an ADR-036 signature over `data` by `signer`
+///
+/// Checks both that the public key belongs to `signer` and that the
+/// signature is valid. Returns `Ok(false)` for a well-formed but wrong
+/// signature, and an error for malformed inputs.
+pub fn verify_arbitrary(
+    signer: &str,
+    data: &[u8],
+    signature: &StdSignature,
+) -> Result<bool, WalletError> {
+    if signature.pub_key.key_type != PUBKEY_TYPE {
+        return Err(WalletError::InvalidSignature(format!(
+            "unsupported public key type: {}",
+            signature.pub_key.key_type
+        )));
+    }
+    let key_bytes = STANDARD
+        .decode(&signature.pub_key.value)
+        .map_err(|e| WalletError::InvalidSignature(format!("public key: {}", e)))?;
+    let verifying_key = VerifyingKey::from_sec1_bytes(&key_bytes)
+        .map_err(|e| WalletError::InvalidSignature(format!("public key: {}", e)))?;
+    let sig_bytes = STANDARD
+        .decode(&signature.signature)
+        .map_err(|e| WalletError::InvalidSignature(format!("signature: {}", e)))?;
+    let sig = Signature::from_slice(&sig_bytes)
+        .map_err(|e| WalletError::InvalidSignature(format!("signature: {}", e)))?;
+
+    // The public key must hash to the claimed signer address (any bech32 prefix)
+    let account = AccountId::from_str(signer).map_err(|e| {
+        WalletError::InvalidSignature(format!("invalid signer address {}: {}", signer, e))
+    })?;
+    let derived = PublicKey::from(&verifying_key)
+        .account_id(account.prefix())
+        .map_err(|e| WalletError::InvalidSignature(e.to_string()))?;
+    if derived.as_ref() != signer {
+        return Ok(false);
+    }
+
+    Ok(verifying_key
+        .verify(&sign_doc_bytes(signer, data), &sig)
+        .is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_sign_doc_is_canonical() {
+        let doc = sign_doc_bytes("bostrom1abc", b"hello");
+        assert_eq!(
+            String::from_utf8(doc).unwrap(),
+            r#"{"account_number":"0","chain_id":"","fee":{"amount":[],"gas":"0"},"memo":"","msgs":[{"type":"sign/MsgSignData","value":{"data":"aGVsbG8=","signer":"bostrom1abc"}}],"sequence":"0"}"#
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let wallet = Wallet::from_phrase(PHRASE).unwrap();
+        let address = wallet.address_str();
+        let sig = wallet.sign_arbitrary(b"I own this address").unwrap();
+
+        assert!(verify_arbitrary(&address, b"I own this address", &sig).unwrap());
+        assert!(!verify_arbitrary(&address, b"tampered", &sig).unwrap());
+
+        // A valid signature presented for a different address is rejected
+        let other = Wallet::new().unwrap();
+        assert!(!verify_arbitrary(&other.address_str(), b"I own this address", &sig).unwrap());
+    }
+
+    #[test]
+    fn test_transaction_key_signs_like_the_wallet() {
+        let wallet = Wallet::from_phrase(PHRASE).unwrap();
+        let address = wallet.address_str();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let sig = sign_arbitrary(&signing_key, &address, b"relay this").unwrap();
+
+        assert_eq!(
+            sig.pub_key,
+            wallet.sign_arbitrary(b"relay this").unwrap().pub_key
+        );
+        assert!(verify_arbitrary(&address, b"relay this", &sig).unwrap());
+    }
+
+    #[test]
+    fn test_prefix_containing_separator() {
+        // Bech32 splits at the last '1', so the prefix here is "test1net"
+        let wallet = Wallet::from_phrase(PHRASE).unwrap();
+        let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+            &wallet.signing_key().unwrap().to_bytes(),
+        )
+        .unwrap();
+        let address = signing_key
+            .public_key()
+            .account_id("test1net")
+            .unwrap()
+            .to_string();
+        assert!(address.starts_with("test1net1"));
+        let sig = sign_arbitrary(&signing_key, &address, b"hello").unwrap();
+
+        assert!(verify_arbitrary(&address, b"hello", &sig).unwrap());
+        assert!(!verify_arbitrary(&address, b"bye", &sig).unwrap());
+        assert!(verify_arbitrary("test1net", b"hello", &sig).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Versioned wallet file format
+//!
+//! Wallet files are small TOML documents: a [`WalletHeader`] (format version,
+//! backend, creation time and the wallet's address) followed by the secret,
+//! which is the mnemonic for the `file` backend and the armored block for
+//! `encrypted`. The address is readable without the passphrase.
+//!
+//! Files written before the header existed hold only the secret. They still
+//! load, and the file stores rewrite them in the current format on first load.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{write_private_file, Wallet, WalletError};
+use crate::config::WalletBackend;
+
+/// Format version written by this release
+pub const WALLET_FILE_VERSION: u32 = 1;
+
+/// Wallet file metadata, stored in the clear
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletHeader {
+    pub version: u32,
+    /// How the secret below the header is stored (`file` or `encrypted`)
+    pub backend: WalletBackend,
+    /// Unix seconds the wallet was saved first; for migrated files, when the
+    /// old file was last modified
+    pub created_at: u64,
+    /// Address of the wallet as it was saved
+    pub address: String,
+    /// Account index `address` was derived for
+    #[serde(default)]
+    pub account: u32,
+}
+
+/// A parsed wallet file: header plus secret
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WalletFile {
+    #[serde(flatten)]
+    pub header: WalletHeader,
+    secret: String,
+}
+
+impl Drop for WalletFile {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Contents of a wallet file on disk
+pub(crate) enum Contents {
+    Current(WalletFile),
+    /// Bare secret written before the format was versioned
+    Legacy(Zeroizing<String>),
+}
+
+impl WalletFile {
+    pub fn new(backend: WalletBackend, created_at: u64, wallet: &Wallet, secret: &str) -> Self {
+        Self {
+            header: WalletHeader {
+                version: WALLET_FILE_VERSION,
+                backend,
+                created_at,
+                address: wallet.address_str(),
+                account: wallet.account(),
+            },
+            secret: secret.to_string(),
+        }
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// The wallet in `secret` (this file's secret, decrypted if need be) on
+    /// the account the header was saved for
+    pub fn wallet(&self, secret: &str) -> Result<Wallet, WalletError> {
+        let wallet = Wallet::from_secret(secret)?;
+        match self.header.account {
+            0 => Ok(wallet),
+            account => wallet.derive_account(account),
+        }
+    }
+
+    /// Parse a wallet file in either format
+    pub fn parse(text: &str) -> Result<Contents, WalletError> {
+        // Legacy secrets (phrases, hex keys, armor) never parse as a TOML table with a version
+        let version = match toml::from_str::<toml::Table>(text) {
+            Ok(table) => match table.get("version") {
+                Some(version) => version.as_integer().ok_or(WalletError::InvalidFormat)?,
+                None => return Ok(Contents::Legacy(Zeroizing::new(text.to_string()))),
+            },
+            Err(_) => return Ok(Contents::Legacy(Zeroizing::new(text.to_string()))),
+        };
+        if version != i64::from(WALLET_FILE_VERSION) {
+            return Err(WalletError::UnsupportedFileVersion(version));
+        }
+        toml::from_str(text)
+            .map(Contents::Current)
+            .map_err(|_| WalletError::InvalidFormat)
+    }
+
+    /// Read `path`, `None` if it does not exist
+    pub fn read(path: &Path) -> Result<Option<Contents>, WalletError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = Zeroizing::new(fs::read_to_string(path)?);
+        Self::parse(&text).map(Some)
+    }
+
+    /// Write to `path` owner-only, through a temporary file so an interrupted
+    /// write never leaves a truncated wallet
+    pub fn write(&self, path: &Path) -> Result<(), WalletError> {
+        let text = Zeroizing::new(
+            toml::to_string(self).map_err(|e| WalletError::Keystore(e.to_string()))?,
+        );
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = Path::new(&tmp);
+        write_private_file(tmp, text.as_bytes())?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Fail unless the file was written by the store for `backend`
+    pub fn expect_backend(&self, backend: WalletBackend, path: &Path) -> Result<(), WalletError> {
+        if self.header.backend == backend {
+            return Ok(());
+        }
+        Err(WalletError::BackendMismatch {
+            path: path.display().to_string(),
+            found: backend_name(self.header.backend).to_string(),
+        })
+    }
+}
+
+/// Read the header of a wallet file without touching its secret
+///
+/// `None` if the file is missing or predates the versioned format.
+pub fn read_header(path: &Path) -> Result<Option<WalletHeader>, WalletError> {
+    Ok(match WalletFile::read(path)? {
+        Some(Contents::Current(file)) => Some(file.header.clone()),
+        _ => None,
+    })
+}
+
+/// Modification time of a file in Unix seconds, the best guess at when a
+/// legacy wallet was created
+pub(crate) fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Current Unix time in seconds
+pub(crate) fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn backend_name(backend: WalletBackend) -> &'static str {
+    match backend {
+        WalletBackend::File => "file",
+        WalletBackend::Keychain => "keychain",
+        WalletBackend::Encrypted => "encrypted",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_current_and_legacy() {
+        let wallet = Wallet::watch_only("bostrom19rl4cm2hmr8afy4kldpxz3fka4jguq0alnewpj").unwrap();
+        let file = WalletFile::new(
+            WalletBackend::Encrypted,
+            1_700_000_000,
+            &wallet,
+            "-----BEGIN UHASH WALLET-----\nkdf: bcrypt\n\nAAAA\n-----END UHASH WALLET-----\n",
+        );
+        let text = toml::to_string(&file).unwrap();
+        match WalletFile::parse(&text).unwrap() {
+            Contents::Current(parsed) => {
+                assert_eq!(parsed.header, file.header);
+                assert_eq!(parsed.secret(), file.secret());
+            }
+            Contents::Legacy(_) => panic!("versioned file parsed as legacy"),
+        }
+
+        for legacy in [
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about\n",
+            &"07".repeat(32),
+            file.secret(),
+        ] {
+            assert!(matches!(
+                WalletFile::parse(legacy).unwrap(),
+                Contents::Legacy(secret) if secret.as_str() == legacy
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rejects_newer_versions() {
+        let text =
+            "version = 2\nbackend = \"file\"\ncreated_at = 0\naddress = \"\"\nsecret = \"\"\n";
+        assert!(matches!(
+            WalletFile::parse(text),
+            Err(WalletError::UnsupportedFileVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+//! OS credential store backend for the wallet mnemonic
+//!
+//! Uses the `keyring` crate: macOS Keychain, Windows Credential Manager and
+//! the Linux Secret Service (GNOME Keyring, KWallet), which keeps entries
+//! across logouts and reboots. Without the `keychain` feature every
+//! operation fails with [`WalletError::Keychain`].
+
+use zeroize::Zeroizing;
+
+use super::WalletError;
+
+/// Service name for uhash keychain entries
+pub const KEYCHAIN_SERVICE: &str = "uhash";
+
+/// Account name under which the mnemonic is stored
+pub const KEYCHAIN_ACCOUNT: &str = "wallet";
+
+#[cfg(feature = "keychain")]
+fn entry() -> Result<keyring::Entry, WalletError> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| WalletError::Keychain(e.to_string()))
+}
+
+/// Store the mnemonic, replacing any existing entry
+#[cfg(feature = "keychain")]
+pub(crate) fn store(mnemonic: &str) -> Result<(), WalletError> {
+    entry()?
+        .set_password(mnemonic)
+        .map_err(|e| WalletError::Keychain(e.to_string()))
+}
+
+/// Load the mnemonic, returning `None` if no entry exists
+#[cfg(feature = "keychain")]
+pub(crate) fn load() -> Result<Option<Zeroizing<String>>, WalletError> {
+    match entry()?.get_password() {
+        Ok(phrase) => Ok(Some(Zeroizing::new(phrase))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(WalletError::Keychain(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "keychain"))]
+fn unavailable() -> WalletError {
+    WalletError::Keychain(
+        "uhash was built without the `keychain` feature (rebuild with `--features keychain`)"
+            .to_string(),
+    )
+}
+
+#[cfg(not(feature = "keychain"))]
+pub(crate) fn store(_mnemonic: &str) -> Result<(), WalletError> {
+    Err(unavailable())
+}
+
+#[cfg(not(feature = "keychain"))]
+pub(crate) fn load() -> Result<Option<Zeroizing<String>>, WalletError> {
+    Err(unavailable())
+}
@@ -0,0 +1,148 @@
+//! Mnemonic parsing with actionable diagnostics
+//!
+//! Distinguishes a bad word count, a word missing from the wordlist (with
+//! its position and the closest BIP39 words) and a checksum failure, where
+//! every word is valid but the phrase as a whole is not.
+
+use bip39::{Language, Mnemonic};
+
+use super::{WalletError, LANGUAGE_NAMES, WORD_COUNTS};
+
+/// Maximum edit distance for a wordlist entry to be suggested
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Maximum number of suggestions per unknown word
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Parse a phrase, detecting the wordlist when `language` is `None`
+pub(crate) fn parse(phrase: &str, language: Option<Language>) -> Result<Mnemonic, WalletError> {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    if !WORD_COUNTS.contains(&words.len()) {
+        return Err(WalletError::InvalidWordCount(words.len()));
+    }
+
+    let language = language.unwrap_or_else(|| detect_language(&words));
+    Mnemonic::parse_in(language, phrase).map_err(|e| match e {
+        bip39::Error::UnknownWord(index) => {
+            let word = words.get(index).copied().unwrap_or_default();
+            WalletError::UnknownWord {
+                index: index + 1,
+                word: word.to_string(),
+                language: language_name(language).to_string(),
+                suggestions: suggest(language, word),
+            }
+        }
+        bip39::Error::InvalidChecksum => WalletError::InvalidChecksum,
+        bip39::Error::BadWordCount(n) => WalletError::InvalidWordCount(n),
+        other => WalletError::InvalidMnemonic(other.to_string()),
+    })
+}
+
+/// Pick the wordlist containing the most words of the phrase (English wins ties)
+fn detect_language(words: &[&str]) -> Language {
+    let mut best = (Language::English, 0);
+    for &language in Language::ALL {
+        let hits = words
+            .iter()
+            .filter(|w| language.find_word(&w.to_lowercase()).is_some())
+            .count();
+        if hits > best.1 {
+            best = (language, hits);
+        }
+    }
+    best.0
+}
+
+/// Lowercase CLI name of a wordlist, as accepted by [`super::parse_language`]
+pub(crate) fn language_name(language: Language) -> &'static str {
+    Language::ALL
+        .iter()
+        .position(|l| *l == language)
+        .map(|i| LANGUAGE_NAMES[i])
+        .unwrap_or("unknown")
+}
+
+/// Closest wordlist entries: shared 4-letter prefix first, then by edit distance
+fn suggest(language: Language, word: &str) -> Vec<String> {
+    let word = word.to_lowercase();
+
+    // BIP39 English words are unique in their first four letters
+    let prefix: String = word.chars().take(4).collect();
+    if prefix.chars().count() == 4 {
+        let by_prefix = language.words_by_prefix(&prefix);
+        if !by_prefix.is_empty() {
+            return by_prefix
+                .iter()
+                .take(MAX_SUGGESTIONS)
+                .map(|w| w.to_string())
+                .collect();
+        }
+    }
+
+    let mut candidates: Vec<(usize, &str)> = language
+        .word_list()
+        .iter()
+        .map(|w| (edit_distance(&word, w), *w))
+        .filter(|(d, _)| *d <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, w)| w.to_string())
+        .collect()
+}
+
+/// Levenshtein distance over Unicode scalar values
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_unknown_word_reports_index_and_suggestions() {
+        let phrase = VALID.replacen("about", "abuot", 1);
+        match parse(&phrase, None) {
+            Err(WalletError::UnknownWord {
+                index,
+                word,
+                suggestions,
+                ..
+            }) => {
+                assert_eq!(index, 12);
+                assert_eq!(word, "abuot");
+                assert!(suggestions.contains(&"about".to_string()));
+            }
+            other => panic!("expected UnknownWord, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_checksum_and_word_count_errors() {
+        let swapped = VALID.replacen("about", "abandon", 1);
+        assert!(matches!(
+            parse(&swapped, None),
+            Err(WalletError::InvalidChecksum)
+        ));
+        assert!(matches!(
+            parse("abandon about", None),
+            Err(WalletError::InvalidWordCount(2))
+        ));
+        assert!(parse(VALID, None).is_ok());
+    }
+}
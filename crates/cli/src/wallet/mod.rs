@@ -1,18 +1,48 @@
 //! Wallet management for UniversalHash miner
 //!
 //! Handles mnemonic generation, import/export, and transaction signing.
+//! Persistence goes through the [`WalletStore`] backends.
+//!
+//! A [`Wallet::watch_only`] wallet holds only an address: it can mine and
+//! query balances, while proofs are signed elsewhere with `sign-proof`.
+//!
+//! Secrets are wiped from memory when dropped: the mnemonic and signing key
+//! zeroize themselves, and seeds, entropy and exported secrets are wrapped in
+//! [`Zeroizing`].
 
 use bip32::secp256k1::ecdsa::SigningKey;
 use bip32::{DerivationPath, XPrv};
-use bip39::{Language, Mnemonic};
+use bip39::Mnemonic;
 use cosmrs::crypto::secp256k1;
 use cosmrs::AccountId;
-use std::fs;
+use std::path::Path;
+#[cfg(feature = "cli")]
 use std::path::PathBuf;
 use thiserror::Error;
-
-/// Default derivation path for Cosmos SDK chains
-const DERIVATION_PATH: &str = "m/44'/118'/0'/0/0";
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::chain::{ChainProfile, COSMOS_COIN_TYPE};
+
+mod address;
+mod adr036;
+mod armor;
+mod format;
+mod keychain;
+mod mnemonic;
+mod permissions;
+mod store;
+
+pub use bip39::Language;
+
+pub use address::{validate_address, validate_address_with_prefix, AddressKind};
+pub use adr036::{
+    sign_arbitrary, sign_doc_bytes, verify_arbitrary, StdPubKey, StdSignature, PUBKEY_TYPE,
+};
+pub use armor::{encrypt_armor_priv_key, unarmor_decrypt_priv_key};
+pub use format::{read_header, WalletHeader, WALLET_FILE_VERSION};
+pub use keychain::{KEYCHAIN_ACCOUNT, KEYCHAIN_SERVICE};
+pub use permissions::{check_file_permissions, create_private_dir, write_private_file};
+pub use store::{EncryptedFileStore, FileStore, KeychainStore, MemoryStore, WalletStore};
 
 /// Bostrom address prefix
 const BOSTROM_PREFIX: &str = "bostrom";
@@ -25,6 +55,23 @@ pub enum WalletError {
     #[error("Invalid mnemonic phrase: {0}")]
     InvalidMnemonic(String),
 
+    #[error(
+        "Invalid mnemonic phrase: word {index} ('{word}') is not in the {language} wordlist{hint}",
+        hint = suggestion_hint(suggestions)
+    )]
+    UnknownWord {
+        /// 1-based position of the word in the phrase
+        index: usize,
+        word: String,
+        language: String,
+        suggestions: Vec<String>,
+    },
+
+    #[error(
+        "Invalid mnemonic phrase: checksum mismatch. Every word is valid, so check the word order and look for a typo that produced another valid word"
+    )]
+    InvalidChecksum,
+
     #[error("Derivation error: {0}")]
     Derivation(String),
 
@@ -33,72 +80,309 @@ pub enum WalletError {
 
     #[error("Invalid wallet file format")]
     InvalidFormat,
+
+    #[error("Keychain error: {0}")]
+    Keychain(String),
+
+    #[error("Keystore error: {0}")]
+    Keystore(String),
+
+    #[error("Invalid private key: {0}")]
+    InvalidPrivateKey(String),
+
+    #[error("Unsupported mnemonic language: {0} (expected one of: {langs})", langs = LANGUAGE_NAMES.join(", "))]
+    UnsupportedLanguage(String),
+
+    #[error(
+        "Wallet file {path} is accessible by other users (mode {mode:o}). Run 'chmod 600 {path}' or pass --insecure-ok"
+    )]
+    InsecurePermissions { path: String, mode: u32 },
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Invalid word count: {0} (expected 12, 15, 18, 21 or 24)")]
+    InvalidWordCount(usize),
+
+    #[error("Invalid address '{address}': {reason}")]
+    InvalidAddress { address: String, reason: String },
+
+    #[error("Unsupported wallet file version {0} (this release reads version {v}); upgrade uhash", v = format::WALLET_FILE_VERSION)]
+    UnsupportedFileVersion(i64),
+
+    #[error("Wallet file {path} is stored by the {found} backend. Run 'uhash config set wallet.backend {found}'")]
+    BackendMismatch { path: String, found: String },
+
+    #[error("Wallet has no mnemonic; only mnemonic wallets derive other accounts")]
+    NoMnemonic,
+
+    #[error("Wallet {0} is watch-only and cannot sign. Sign on the machine holding its key with 'uhash sign-proof'")]
+    WatchOnly(String),
 }
 
-/// A wallet containing a mnemonic and derived keys
+fn suggestion_hint(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean: {}?)", suggestions.join(", "))
+    }
+}
+
+/// Mnemonic word counts accepted by BIP39
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// BIP39 wordlist names accepted by [`parse_language`]
+pub const LANGUAGE_NAMES: [&str; 10] = [
+    "english",
+    "chinese-simplified",
+    "chinese-traditional",
+    "czech",
+    "french",
+    "italian",
+    "japanese",
+    "korean",
+    "portuguese",
+    "spanish",
+];
+
+/// Parse a BIP39 wordlist name (e.g. `english`, `japanese`, `chinese-simplified`)
+pub fn parse_language(name: &str) -> Result<Language, WalletError> {
+    Ok(match name.to_ascii_lowercase().replace('_', "-").as_str() {
+        "english" | "en" => Language::English,
+        "chinese-simplified" | "zh-hans" => Language::SimplifiedChinese,
+        "chinese-traditional" | "zh-hant" => Language::TraditionalChinese,
+        "czech" | "cs" => Language::Czech,
+        "french" | "fr" => Language::French,
+        "italian" | "it" => Language::Italian,
+        "japanese" | "ja" => Language::Japanese,
+        "korean" | "ko" => Language::Korean,
+        "portuguese" | "pt" => Language::Portuguese,
+        "spanish" | "es" => Language::Spanish,
+        _ => return Err(WalletError::UnsupportedLanguage(name.to_string())),
+    })
+}
+
+/// A wallet containing a signing key and, unless imported from a raw key, its mnemonic
+///
+/// Watch-only wallets have neither and fail every signing operation with
+/// [`WalletError::WatchOnly`]. Mnemonic wallets start on account 0; see
+/// [`Wallet::derive_account`].
 pub struct Wallet {
-    mnemonic: Mnemonic,
-    signing_key: SigningKey,
+    mnemonic: Option<Mnemonic>,
+    signing_key: Option<SigningKey>,
     address: AccountId,
+    /// Coin type the key was derived with
+    coin_type: u32,
+    /// Hardened account index of the derivation path
+    account: u32,
 }
 
 impl Wallet {
-    /// Create a new wallet with a random mnemonic
+    /// Create a new wallet with a random 24-word English mnemonic
     pub fn new() -> Result<Self, WalletError> {
-        // Generate 32 bytes of entropy for 24-word mnemonic
-        let mut entropy = [0u8; 32];
-        getrandom::getrandom(&mut entropy)
+        Self::generate(24, Language::English)
+    }
+
+    /// Create a new wallet with a random mnemonic of the given length and wordlist
+    pub fn generate(word_count: usize, language: Language) -> Result<Self, WalletError> {
+        if !WORD_COUNTS.contains(&word_count) {
+            return Err(WalletError::InvalidWordCount(word_count));
+        }
+
+        // 32 bits of entropy per 3 words (12 words = 16 bytes, 24 words = 32 bytes)
+        let mut entropy = Zeroizing::new([0u8; 32]);
+        let entropy = &mut entropy[..word_count / 3 * 4];
+        getrandom::getrandom(entropy)
             .map_err(|e| WalletError::MnemonicGeneration(e.to_string()))?;
 
-        let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)
+        let mnemonic = Mnemonic::from_entropy_in(language, entropy)
             .map_err(|e| WalletError::MnemonicGeneration(e.to_string()))?;
 
         Self::from_mnemonic(mnemonic)
     }
 
     /// Create a wallet from an existing mnemonic phrase
+    ///
+    /// The wordlist is detected automatically (English wins ties).
     pub fn from_phrase(phrase: &str) -> Result<Self, WalletError> {
-        let mnemonic = Mnemonic::parse_in(Language::English, phrase)
-            .map_err(|e| WalletError::InvalidMnemonic(e.to_string()))?;
+        Self::from_mnemonic(mnemonic::parse(phrase, None)?)
+    }
 
-        Self::from_mnemonic(mnemonic)
+    /// Create a wallet from a mnemonic phrase in a specific wordlist
+    pub fn from_phrase_in(language: Language, phrase: &str) -> Result<Self, WalletError> {
+        Self::from_mnemonic(mnemonic::parse(phrase, Some(language))?)
     }
 
-    /// Create a wallet from a Mnemonic
+    /// Create a wallet from a Mnemonic (Bostrom derivation and prefix)
     fn from_mnemonic(mnemonic: Mnemonic) -> Result<Self, WalletError> {
-        let seed = mnemonic.to_seed("");
+        let profile = ChainProfile::bostrom();
+        let signing_key = derive_signing_key(&mnemonic, &profile, 0)?;
+        Self::from_signing_key(Some(mnemonic), signing_key, &profile.bech32_prefix)
+    }
 
-        let path: DerivationPath = DERIVATION_PATH
-            .parse()
-            .map_err(|e: bip32::Error| WalletError::Derivation(e.to_string()))?;
+    /// Re-derive the key and address for another chain
+    ///
+    /// Mnemonic wallets are re-derived with the profile's coin type, keeping
+    /// their account; key-only wallets keep their key and only change the
+    /// address prefix.
+    pub fn for_chain(self, profile: &ChainProfile) -> Result<Self, WalletError> {
+        let signing_key = match (&self.mnemonic, &self.signing_key) {
+            (Some(mnemonic), _) => derive_signing_key(mnemonic, profile, self.account)?,
+            (None, Some(key)) => key.clone(),
+            (None, None) => {
+                let address = AccountId::new(&profile.bech32_prefix, &self.address.to_bytes())
+                    .map_err(|e| WalletError::Derivation(e.to_string()))?;
+                return Ok(Self { address, ..self });
+            }
+        };
+        let account = self.account;
+        Ok(Self {
+            coin_type: profile.coin_type,
+            account,
+            ..Self::from_signing_key(self.mnemonic, signing_key, &profile.bech32_prefix)?
+        })
+    }
 
-        let xprv = XPrv::derive_from_path(seed, &path)
-            .map_err(|e| WalletError::Derivation(e.to_string()))?;
+    /// Derive another account from the wallet's mnemonic
+    ///
+    /// Account `n` uses the path `m/44'/<coin_type>'/<n>'/0/0` on the same
+    /// chain as this wallet, matching `cyber keys add --account n`, so the
+    /// same key signs wherever the account is used.
+    pub fn derive_account(&self, account: u32) -> Result<Self, WalletError> {
+        let mnemonic = self.mnemonic.as_ref().ok_or(WalletError::NoMnemonic)?;
+        let profile = ChainProfile {
+            coin_type: self.coin_type,
+            ..ChainProfile::bostrom()
+        };
+        let signing_key = derive_signing_key(mnemonic, &profile, account)?;
+        Ok(Self {
+            coin_type: self.coin_type,
+            account,
+            ..Self::from_signing_key(Some(mnemonic.clone()), signing_key, self.address.prefix())?
+        })
+    }
+
+    /// Account index the key was derived for (0 for key-only wallets)
+    pub fn account(&self) -> u32 {
+        self.account
+    }
 
-        let signing_key = xprv.private_key();
+    /// Create a wallet that knows only its account address
+    ///
+    /// It supports everything but signing: mining, balance and stats queries,
+    /// and relaying. Found proofs are signed offline with `sign-proof` and
+    /// broadcast from here with `broadcast`.
+    pub fn watch_only(address: &str) -> Result<Self, WalletError> {
+        let prefix = address
+            .rsplit_once('1')
+            .map(|(hrp, _)| hrp.to_lowercase())
+            .unwrap_or_default();
+        let (address, kind) = validate_address_with_prefix(address, &prefix)?;
+        if kind != AddressKind::Account {
+            return Err(WalletError::InvalidAddress {
+                address: address.to_string(),
+                reason: "expected an account address, found a contract address".to_string(),
+            });
+        }
+        Ok(Self {
+            mnemonic: None,
+            signing_key: None,
+            address,
+            coin_type: COSMOS_COIN_TYPE,
+            account: 0,
+        })
+    }
+
+    /// Whether the wallet has no key and cannot sign
+    pub fn is_watch_only(&self) -> bool {
+        self.signing_key.is_none()
+    }
+
+    /// Create a wallet from a raw 32-byte secp256k1 private key (no mnemonic)
+    pub fn from_private_key(key: &[u8]) -> Result<Self, WalletError> {
+        let signing_key = SigningKey::from_slice(key)
+            .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
+        Self::from_signing_key(None, signing_key, BOSTROM_PREFIX)
+    }
 
+    /// Import a wallet from a Cosmos SDK armored private key (`cyber keys export`)
+    pub fn from_armored(armored: &str, passphrase: &str) -> Result<Self, WalletError> {
+        let key = unarmor_decrypt_priv_key(armored, passphrase)?;
+        Self::from_private_key(&*key)
+    }
+
+    /// Export the private key in the Cosmos SDK armored format (`cyber keys import`)
+    pub fn to_armored(&self, passphrase: &str) -> Result<String, WalletError> {
+        encrypt_armor_priv_key(&*self.private_key_bytes()?, passphrase)
+    }
+
+    fn from_signing_key(
+        mnemonic: Option<Mnemonic>,
+        signing_key: SigningKey,
+        prefix: &str,
+    ) -> Result<Self, WalletError> {
         // Derive address from public key
         let public_key = secp256k1::SigningKey::from_slice(&signing_key.to_bytes())
             .map_err(|e| WalletError::Derivation(e.to_string()))?
             .public_key();
 
         let address = public_key
-            .account_id(BOSTROM_PREFIX)
+            .account_id(prefix)
             .map_err(|e| WalletError::Derivation(e.to_string()))?;
 
         Ok(Self {
             mnemonic,
-            signing_key: signing_key.clone(),
+            signing_key: Some(signing_key),
             address,
+            coin_type: COSMOS_COIN_TYPE,
+            account: 0,
         })
     }
 
-    /// Get the mnemonic phrase
-    pub fn mnemonic(&self) -> String {
-        self.mnemonic.to_string()
+    /// Parse a stored secret: a mnemonic phrase, a hex-encoded private key,
+    /// or the bech32 address of a watch-only wallet
+    fn from_secret(secret: &str) -> Result<Self, WalletError> {
+        let secret = secret.trim();
+        if secret.len() == 64 && secret.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let key = Zeroizing::new(hex::decode(secret).map_err(|_| WalletError::InvalidFormat)?);
+            Self::from_private_key(&key)
+        } else if !secret.contains(char::is_whitespace) && secret.contains('1') {
+            Self::watch_only(secret)
+        } else {
+            Self::from_phrase(secret)
+        }
+    }
+
+    /// The secret persisted by file and keychain backends
+    fn secret(&self) -> Zeroizing<String> {
+        match (self.mnemonic(), self.private_key_hex()) {
+            (Some(phrase), _) => phrase,
+            (None, Ok(key)) => key,
+            (None, Err(_)) => Zeroizing::new(self.address_str()),
+        }
+    }
+
+    /// The raw private key, wiped when dropped
+    fn private_key_bytes(&self) -> Result<Zeroizing<[u8; 32]>, WalletError> {
+        let mut bytes = self.signing_key()?.to_bytes();
+        let key = Zeroizing::new(bytes.into());
+        bytes.zeroize();
+        Ok(key)
+    }
+
+    /// Get the raw secp256k1 private key as hex (importable by Keplr and `cyber keys import-hex`)
+    pub fn private_key_hex(&self) -> Result<Zeroizing<String>, WalletError> {
+        Ok(Zeroizing::new(hex::encode(*self.private_key_bytes()?)))
+    }
+
+    /// Get the mnemonic phrase, if the wallet was created from one
+    pub fn mnemonic(&self) -> Option<Zeroizing<String>> {
+        self.mnemonic
+            .as_ref()
+            .map(|m| Zeroizing::new(m.to_string()))
     }
 
-    /// Get the Bostrom address
+    /// Get the account address (Bostrom unless re-derived with [`Wallet::for_chain`])
     pub fn address(&self) -> &AccountId {
         &self.address
     }
@@ -109,25 +393,58 @@ impl Wallet {
     }
 
     /// Get the signing key for transaction signing
-    pub fn signing_key(&self) -> &SigningKey {
-        &self.signing_key
+    pub fn signing_key(&self) -> Result<&SigningKey, WalletError> {
+        self.signing_key
+            .as_ref()
+            .ok_or_else(|| WalletError::WatchOnly(self.address_str()))
+    }
+
+    /// Save the wallet mnemonic to a plaintext file (see [`FileStore`])
+    ///
+    /// The file is created owner-only (`0600`) on Unix. Use
+    /// [`EncryptedFileStore`] to keep it under a passphrase instead.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), WalletError> {
+        FileStore::new(path).save(self)
     }
 
-    /// Save wallet mnemonic to a file (encrypted with password in future)
-    pub fn save_to_file(&self, path: &PathBuf) -> Result<(), WalletError> {
-        // For now, save as plaintext (TODO: add encryption)
-        fs::write(path, self.mnemonic())?;
-        Ok(())
+    /// Load a wallet from a plaintext file, whatever its permissions
+    pub fn load_from_file(path: &Path) -> Result<Self, WalletError> {
+        FileStore::new(path)
+            .allow_insecure(true)
+            .load()?
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound).into())
     }
 
-    /// Load wallet from a file
-    pub fn load_from_file(path: &PathBuf) -> Result<Self, WalletError> {
-        let content = fs::read_to_string(path)?;
-        let phrase = content.trim();
-        Self::from_phrase(phrase)
+    /// Save wallet mnemonic to the OS keychain
+    pub fn save_to_keychain(&self) -> Result<(), WalletError> {
+        KeychainStore.save(self)
+    }
+
+    /// Load wallet from the OS keychain, returning `None` if no wallet is stored
+    pub fn load_from_keychain() -> Result<Option<Self>, WalletError> {
+        KeychainStore.load()
     }
 }
 
+/// Derive an account's key from a mnemonic using the profile's coin type
+fn derive_signing_key(
+    mnemonic: &Mnemonic,
+    profile: &ChainProfile,
+    account: u32,
+) -> Result<SigningKey, WalletError> {
+    let seed = Zeroizing::new(mnemonic.to_seed(""));
+
+    let path: DerivationPath = profile
+        .account_path(account)
+        .parse()
+        .map_err(|e: bip32::Error| WalletError::Derivation(e.to_string()))?;
+
+    let xprv = XPrv::derive_from_path(&seed[..], &path)
+        .map_err(|e| WalletError::Derivation(e.to_string()))?;
+
+    Ok(xprv.private_key().clone())
+}
+
 impl Default for Wallet {
     fn default() -> Self {
         Self::new().expect("Failed to create wallet")
@@ -146,7 +463,7 @@ pub fn default_wallet_path() -> PathBuf {
 pub fn ensure_wallet_dir() -> Result<PathBuf, WalletError> {
     let wallet_path = default_wallet_path();
     if let Some(parent) = wallet_path.parent() {
-        fs::create_dir_all(parent)?;
+        create_private_dir(parent)?;
     }
     Ok(wallet_path)
 }
@@ -158,7 +475,7 @@ mod tests {
     #[test]
     fn test_new_wallet() {
         let wallet = Wallet::new().unwrap();
-        let phrase = wallet.mnemonic();
+        let phrase = wallet.mnemonic().unwrap();
 
         // Should have 24 words
         assert_eq!(phrase.split_whitespace().count(), 24);
@@ -170,7 +487,7 @@ mod tests {
     #[test]
     fn test_wallet_from_phrase() {
         let wallet1 = Wallet::new().unwrap();
-        let phrase = wallet1.mnemonic();
+        let phrase = wallet1.mnemonic().unwrap();
 
         let wallet2 = Wallet::from_phrase(&phrase).unwrap();
 
@@ -188,4 +505,168 @@ mod tests {
 
         assert_eq!(wallet1.address_str(), wallet2.address_str());
     }
+
+    #[test]
+    fn test_generate_word_counts_and_languages() {
+        for words in WORD_COUNTS {
+            let wallet = Wallet::generate(words, Language::English).unwrap();
+            assert_eq!(wallet.mnemonic().unwrap().split_whitespace().count(), words);
+        }
+        assert!(matches!(
+            Wallet::generate(13, Language::English),
+            Err(WalletError::InvalidWordCount(13))
+        ));
+
+        // Non-English phrases are detected on import without naming the language
+        let spanish = Wallet::generate(12, parse_language("spanish").unwrap()).unwrap();
+        let phrase = spanish.mnemonic().unwrap();
+        let imported = Wallet::from_phrase(&phrase).unwrap();
+        assert_eq!(imported.address_str(), spanish.address_str());
+    }
+
+    #[test]
+    fn test_private_key_wallet_roundtrip() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let wallet = Wallet::from_phrase(phrase).unwrap();
+
+        let armored = wallet.to_armored("secret").unwrap();
+        let imported = Wallet::from_armored(&armored, "secret").unwrap();
+        assert_eq!(imported.address_str(), wallet.address_str());
+        assert!(imported.mnemonic().is_none());
+
+        // Key-only wallets persist as hex and reload to the same address
+        let reloaded = Wallet::from_secret(&imported.secret()).unwrap();
+        assert_eq!(reloaded.address_str(), wallet.address_str());
+    }
+
+    #[test]
+    fn test_key_material_zeroizes_on_drop() {
+        fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<Mnemonic>();
+        assert_zeroize_on_drop::<SigningKey>();
+
+        let wallet = Wallet::from_private_key(&[7u8; 32]).unwrap();
+        assert_eq!(*wallet.private_key_bytes().unwrap(), [7u8; 32]);
+        assert_eq!(wallet.private_key_hex().unwrap().as_str(), "07".repeat(32));
+    }
+
+    #[test]
+    fn test_derive_account() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let wallet = Wallet::from_phrase(phrase).unwrap();
+        assert_eq!(wallet.account(), 0);
+        assert_eq!(
+            wallet.derive_account(0).unwrap().address_str(),
+            wallet.address_str()
+        );
+
+        let second = wallet.derive_account(1).unwrap();
+        assert_eq!(second.account(), 1);
+        assert_ne!(second.address_str(), wallet.address_str());
+        let seed = mnemonic::parse(phrase, None).unwrap().to_seed("");
+        let path: DerivationPath = "m/44'/118'/1'/0/0".parse().unwrap();
+        let expected = XPrv::derive_from_path(seed, &path).unwrap();
+        assert_eq!(
+            second.signing_key().unwrap().to_bytes(),
+            expected.private_key().to_bytes()
+        );
+
+        // The account survives re-derivation for another chain
+        let other_coin = ChainProfile {
+            coin_type: 60,
+            ..ChainProfile::bostrom()
+        };
+        let moved = second.for_chain(&other_coin).unwrap();
+        assert_eq!(moved.account(), 1);
+        assert_eq!(
+            moved.address_str(),
+            Wallet::from_phrase(phrase)
+                .unwrap()
+                .for_chain(&other_coin)
+                .unwrap()
+                .derive_account(1)
+                .unwrap()
+                .address_str()
+        );
+
+        let key_only = Wallet::from_private_key(&[7u8; 32]).unwrap();
+        assert!(matches!(
+            key_only.derive_account(1),
+            Err(WalletError::NoMnemonic)
+        ));
+    }
+
+    #[test]
+    fn test_watch_only_wallet() {
+        let signer = Wallet::new().unwrap();
+        let wallet = Wallet::watch_only(&signer.address_str()).unwrap();
+        assert!(wallet.is_watch_only());
+        assert!(!signer.is_watch_only());
+        assert_eq!(wallet.address_str(), signer.address_str());
+        assert!(wallet.mnemonic().is_none());
+        assert!(matches!(
+            wallet.signing_key(),
+            Err(WalletError::WatchOnly(_))
+        ));
+        assert!(matches!(
+            wallet.private_key_hex(),
+            Err(WalletError::WatchOnly(_))
+        ));
+        assert!(matches!(
+            wallet.to_armored("secret"),
+            Err(WalletError::WatchOnly(_))
+        ));
+        assert!(wallet.sign_arbitrary(b"hello").is_err());
+
+        // Stored as its address, and re-prefixed rather than re-derived
+        let reloaded = Wallet::from_secret(&wallet.secret()).unwrap();
+        assert!(reloaded.is_watch_only());
+        assert_eq!(reloaded.address_str(), signer.address_str());
+        let cosmos = ChainProfile {
+            bech32_prefix: "cosmos".to_string(),
+            ..ChainProfile::bostrom()
+        };
+        let rewrapped = reloaded.for_chain(&cosmos).unwrap();
+        assert!(rewrapped.address_str().starts_with("cosmos1"));
+        assert_eq!(rewrapped.address().to_bytes(), signer.address().to_bytes());
+
+        assert!(matches!(
+            Wallet::watch_only("bostrom1notanaddress"),
+            Err(WalletError::InvalidAddress { .. })
+        ));
+        let contract = "bostrom1qwys5wj3r4lry7dl74ukn5unhdpa6t397h097q36dqvrp5qgvjxqverdlf";
+        assert!(Wallet::watch_only(contract).is_err());
+    }
+
+    #[test]
+    fn test_for_chain_rederives_address() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let wallet = Wallet::from_phrase(phrase).unwrap();
+
+        let cosmos = ChainProfile {
+            bech32_prefix: "cosmos".to_string(),
+            ..ChainProfile::bostrom()
+        };
+        let rewrapped = Wallet::from_phrase(phrase)
+            .unwrap()
+            .for_chain(&cosmos)
+            .unwrap();
+        // Well-known Cosmos Hub address of the BIP39 test mnemonic
+        assert_eq!(
+            rewrapped.address_str(),
+            "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"
+        );
+        assert_eq!(rewrapped.address().to_bytes(), wallet.address().to_bytes());
+
+        // A different coin type derives a different key
+        let other_coin = ChainProfile {
+            coin_type: 60,
+            ..ChainProfile::bostrom()
+        };
+        let rederived = Wallet::from_phrase(phrase)
+            .unwrap()
+            .for_chain(&other_coin)
+            .unwrap();
+        assert_ne!(rederived.address_str(), wallet.address_str());
+    }
 }
@@ -0,0 +1,98 @@
+//! Filesystem permission hygiene for plaintext wallet files
+//!
+//! On Unix, wallet files are written `0600` inside a `0700` directory, and
+//! files readable by group or others are reported as insecure. Other
+//! platforms rely on the default ACLs and every check passes.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use super::WalletError;
+
+/// Permission bits that must not be set on a wallet file (group/other access)
+#[cfg(unix)]
+const INSECURE_BITS: u32 = 0o077;
+
+/// Write a file readable and writable only by the owner
+pub fn write_private_file(path: &Path, contents: &[u8]) -> Result<(), WalletError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // `mode` only applies on creation; tighten files that already existed
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    Ok(())
+}
+
+/// Create a directory (and parents) accessible only by the owner
+pub fn create_private_dir(path: &Path) -> Result<(), WalletError> {
+    fs::create_dir_all(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Fail with [`WalletError::InsecurePermissions`] if group or others can access the file
+pub fn check_file_permissions(path: &Path) -> Result<(), WalletError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+        if mode & INSECURE_BITS != 0 {
+            return Err(WalletError::InsecurePermissions {
+                path: path.display().to_string(),
+                mode,
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_private_file_permissions() {
+        let dir = std::env::temp_dir().join(format!("uhash-perms-{}", std::process::id()));
+        create_private_dir(&dir).unwrap();
+        let path = dir.join("wallet.txt");
+
+        // Pre-existing world-readable file is tightened on write
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(matches!(
+            check_file_permissions(&path),
+            Err(WalletError::InsecurePermissions { mode: 0o644, .. })
+        ));
+
+        write_private_file(&path, b"new").unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(
+            fs::metadata(&dir).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        check_file_permissions(&path).unwrap();
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,388 @@
+//! Wallet persistence behind one trait
+//!
+//! The CLI, the desktop app and servers load and save wallets through a
+//! [`WalletStore`] rather than writing files themselves. Every store keeps the
+//! same secret (the mnemonic, or the hex private key of key-only wallets), so a
+//! wallet moves between stores unchanged. Loaded wallets use the Bostrom
+//! derivation; re-derive them with [`Wallet::for_chain`] for other chains.
+//!
+//! The file stores write the versioned format of the `format` module and
+//! migrate bare-secret files from older releases when they load them.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+use crate::config::WalletBackend;
+
+use super::armor::{encrypt_armor_secret, unarmor_decrypt_secret};
+use super::format::{modified_secs, now_secs, Contents, WalletFile};
+use super::{check_file_permissions, create_private_dir, keychain, Wallet, WalletError};
+
+/// Somewhere a wallet can be saved and loaded
+pub trait WalletStore: Send + Sync {
+    /// Where the wallet is kept, for messages such as "Saved to: ..."
+    fn location(&self) -> String;
+
+    /// Load the stored wallet, `None` if nothing is stored
+    fn load(&self) -> Result<Option<Wallet>, WalletError>;
+
+    /// Store `wallet`, replacing any stored one
+    fn save(&self, wallet: &Wallet) -> Result<(), WalletError>;
+
+    /// Whether a wallet is stored
+    fn exists(&self) -> Result<bool, WalletError> {
+        Ok(self.load()?.is_some())
+    }
+}
+
+/// Rewrite a legacy wallet file in the versioned format
+///
+/// Best effort: a wallet on a read-only disk still loads, and is migrated by
+/// the first load that can write.
+fn migrate(path: &Path, backend: WalletBackend, wallet: &Wallet, secret: &str) {
+    let file = WalletFile::new(backend, modified_secs(path), wallet, secret.trim());
+    let _ = file.write(path);
+}
+
+/// Create the parent directory of a wallet file owner-only if it is missing
+fn ensure_parent(path: &Path) -> Result<(), WalletError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.exists() => {
+            create_private_dir(parent)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Plaintext secret in an owner-only file
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+    allow_insecure: bool,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            allow_insecure: false,
+        }
+    }
+
+    /// Load files other users can read instead of failing with
+    /// [`WalletError::InsecurePermissions`]
+    pub fn allow_insecure(self, allow: bool) -> Self {
+        Self {
+            allow_insecure: allow,
+            ..self
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl WalletStore for FileStore {
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> Result<Option<Wallet>, WalletError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        if !self.allow_insecure {
+            check_file_permissions(&self.path)?;
+        }
+        match WalletFile::read(&self.path)? {
+            None => Ok(None),
+            Some(Contents::Current(file)) => {
+                file.expect_backend(WalletBackend::File, &self.path)?;
+                file.wallet(file.secret()).map(Some)
+            }
+            Some(Contents::Legacy(secret)) => {
+                let wallet = Wallet::from_secret(&secret)?;
+                migrate(&self.path, WalletBackend::File, &wallet, &secret);
+                Ok(Some(wallet))
+            }
+        }
+    }
+
+    fn save(&self, wallet: &Wallet) -> Result<(), WalletError> {
+        ensure_parent(&self.path)?;
+        let file = WalletFile::new(WalletBackend::File, now_secs(), wallet, &wallet.secret());
+        file.write(&self.path)
+    }
+
+    fn exists(&self) -> Result<bool, WalletError> {
+        Ok(self.path.exists())
+    }
+}
+
+/// Secret sealed under a passphrase (bcrypt + xsalsa20-poly1305, armored)
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    passphrase: Zeroizing<String>,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: impl Into<PathBuf>, passphrase: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            passphrase: Zeroizing::new(passphrase.into()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl std::fmt::Debug for EncryptedFileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedFileStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WalletStore for EncryptedFileStore {
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> Result<Option<Wallet>, WalletError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        match WalletFile::read(&self.path)? {
+            None => Ok(None),
+            Some(Contents::Current(file)) => {
+                file.expect_backend(WalletBackend::Encrypted, &self.path)?;
+                let secret = unarmor_decrypt_secret(file.secret(), &self.passphrase)?;
+                file.wallet(&secret).map(Some)
+            }
+            Some(Contents::Legacy(armored)) => {
+                let secret = unarmor_decrypt_secret(&armored, &self.passphrase)?;
+                let wallet = Wallet::from_secret(&secret)?;
+                migrate(&self.path, WalletBackend::Encrypted, &wallet, &armored);
+                Ok(Some(wallet))
+            }
+        }
+    }
+
+    fn save(&self, wallet: &Wallet) -> Result<(), WalletError> {
+        if self.passphrase.is_empty() {
+            return Err(WalletError::Keystore(
+                "passphrase must not be empty".to_string(),
+            ));
+        }
+        let armored = encrypt_armor_secret(&wallet.secret(), &self.passphrase)?;
+        ensure_parent(&self.path)?;
+        WalletFile::new(WalletBackend::Encrypted, now_secs(), wallet, &armored).write(&self.path)
+    }
+
+    fn exists(&self) -> Result<bool, WalletError> {
+        Ok(self.path.exists())
+    }
+}
+
+/// OS credential store entry (requires the `keychain` feature)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeychainStore;
+
+impl WalletStore for KeychainStore {
+    fn location(&self) -> String {
+        format!(
+            "OS keychain ({}/{})",
+            keychain::KEYCHAIN_SERVICE,
+            keychain::KEYCHAIN_ACCOUNT
+        )
+    }
+
+    fn load(&self) -> Result<Option<Wallet>, WalletError> {
+        keychain::load()?
+            .map(|secret| Wallet::from_secret(&secret))
+            .transpose()
+    }
+
+    fn save(&self, wallet: &Wallet) -> Result<(), WalletError> {
+        keychain::store(&wallet.secret())
+    }
+}
+
+/// Process-local store for tests and embedders that persist elsewhere
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    secret: Mutex<Option<Zeroizing<String>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl WalletStore for MemoryStore {
+    fn location(&self) -> String {
+        "memory".to_string()
+    }
+
+    fn load(&self) -> Result<Option<Wallet>, WalletError> {
+        let secret = self.secret.lock().unwrap_or_else(|e| e.into_inner());
+        secret
+            .as_deref()
+            .map(|s| Wallet::from_secret(s))
+            .transpose()
+    }
+
+    fn save(&self, wallet: &Wallet) -> Result<(), WalletError> {
+        *self.secret.lock().unwrap_or_else(|e| e.into_inner()) = Some(wallet.secret());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::read_header;
+    use std::fs;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("uhash-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Save, reload and compare through any store
+    fn roundtrip(store: &dyn WalletStore) {
+        assert!(!store.exists().unwrap());
+        assert!(store.load().unwrap().is_none());
+
+        let wallet = Wallet::from_phrase(PHRASE).unwrap();
+        store.save(&wallet).unwrap();
+        assert!(store.exists().unwrap());
+        let loaded = store.load().unwrap().unwrap();
+        assert_eq!(loaded.address_str(), wallet.address_str());
+        assert_eq!(loaded.mnemonic().unwrap().as_str(), PHRASE);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        roundtrip(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_file_store_creates_private_dir() {
+        let dir = scratch_dir("file");
+        let store = FileStore::new(dir.join("nested").join("wallet.txt"));
+        roundtrip(&store);
+        let header = read_header(store.path()).unwrap().unwrap();
+        assert_eq!(header.backend, WalletBackend::File);
+        assert_eq!(
+            header.address,
+            Wallet::from_phrase(PHRASE).unwrap().address_str()
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_legacy_files_are_migrated() {
+        let dir = scratch_dir("legacy");
+        create_private_dir(&dir).unwrap();
+        let path = dir.join("wallet.txt");
+        crate::wallet::write_private_file(&path, format!("{}\n", PHRASE).as_bytes()).unwrap();
+        assert!(read_header(&path).unwrap().is_none());
+
+        let store = FileStore::new(&path);
+        let wallet = store.load().unwrap().unwrap();
+        assert_eq!(wallet.mnemonic().unwrap().as_str(), PHRASE);
+        let header = read_header(&path).unwrap().unwrap();
+        assert_eq!(header.version, crate::wallet::WALLET_FILE_VERSION);
+        assert_eq!(header.address, wallet.address_str());
+        assert!(header.created_at > 0);
+        assert_eq!(
+            store.load().unwrap().unwrap().address_str(),
+            wallet.address_str()
+        );
+
+        // The encrypted store migrates bare armor without re-encrypting it
+        let enc_path = dir.join("wallet.enc");
+        let armored = encrypt_armor_secret(PHRASE, "pw").unwrap();
+        crate::wallet::write_private_file(&enc_path, armored.as_bytes()).unwrap();
+        let store = EncryptedFileStore::new(&enc_path, "pw");
+        assert_eq!(
+            store.load().unwrap().unwrap().address_str(),
+            wallet.address_str()
+        );
+        assert_eq!(
+            read_header(&enc_path).unwrap().unwrap().backend,
+            WalletBackend::Encrypted
+        );
+        assert!(store.load().unwrap().is_some());
+
+        // Each file store refuses the other's files
+        assert!(matches!(
+            FileStore::new(&enc_path).load(),
+            Err(WalletError::BackendMismatch { .. })
+        ));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_encrypted_file_store() {
+        let dir = scratch_dir("encrypted");
+        let path = dir.join("wallet.enc");
+        roundtrip(&EncryptedFileStore::new(&path, "correct horse"));
+
+        let armored = fs::read_to_string(&path).unwrap();
+        assert!(!armored.contains("abandon"));
+        assert!(matches!(
+            EncryptedFileStore::new(&path, "wrong").load(),
+            Err(WalletError::Keystore(_))
+        ));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_account_survives_round_trip() {
+        let dir = scratch_dir("account");
+        let wallet = Wallet::from_phrase(PHRASE)
+            .unwrap()
+            .derive_account(3)
+            .unwrap();
+        let stores: [Box<dyn WalletStore>; 2] = [
+            Box::new(FileStore::new(dir.join("wallet.txt"))),
+            Box::new(EncryptedFileStore::new(dir.join("wallet.enc"), "pw")),
+        ];
+        for store in stores {
+            store.save(&wallet).unwrap();
+            let loaded = store.load().unwrap().unwrap();
+            assert_eq!(loaded.account(), 3);
+            assert_eq!(loaded.address_str(), wallet.address_str());
+        }
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_store_rejects_readable_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("insecure");
+        let store = FileStore::new(dir.join("wallet.txt"));
+        store.save(&Wallet::from_phrase(PHRASE).unwrap()).unwrap();
+        fs::set_permissions(store.path(), fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(matches!(
+            store.load(),
+            Err(WalletError::InsecurePermissions { .. })
+        ));
+        assert!(store.clone().allow_insecure(true).load().unwrap().is_some());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
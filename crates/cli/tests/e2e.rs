@@ -0,0 +1,1518 @@
+//! End-to-end tests: the `uhash` binary mining against an in-process devnet
+
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use uhash::dashboard::Dashboard;
+use uhash::devnet::{Devnet, DevnetParams, Fault, MOCK_GAS_USED};
+use uhash::status::MinerStatus;
+use uhash::{meets_difficulty, UniversalHash};
+
+/// A devnet served in the background plus a scratch directory for the CLI
+struct Harness {
+    devnet: Devnet,
+    url: String,
+    dir: PathBuf,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Harness {
+    fn new(name: &str) -> Self {
+        Self::with_params(
+            name,
+            DevnetParams {
+                difficulty: 1,
+                ..DevnetParams::default()
+            },
+        )
+    }
+
+    fn with_params(name: &str, params: DevnetParams) -> Self {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let devnet = Devnet::new(params);
+        let addr = {
+            let _guard = runtime.enter();
+            devnet.spawn("127.0.0.1:0".parse().unwrap()).unwrap()
+        };
+
+        let dir = std::env::temp_dir().join(format!("uhash-e2e-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let harness = Self {
+            devnet,
+            url: format!("http://{}", addr),
+            dir,
+            runtime,
+        };
+        let chain_id = harness.devnet.chain().params().chain_id.clone();
+        let (status, _) = harness.uhash(&["config", "set", "chain.chain_id", &chain_id]);
+        assert!(status.success(), "config set failed");
+        let (status, _) = harness.uhash(&["new-wallet"]);
+        assert!(status.success(), "new-wallet failed");
+        harness
+    }
+
+    /// `uhash --json <args>` pointed at the devnet
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_uhash"));
+        command
+            .arg("--config")
+            .arg(self.dir.join("config.toml"))
+            .arg("--wallet")
+            .arg(self.dir.join("wallet.txt"))
+            .args(["--rpc", &self.url, "--lcd", &self.url, "--json"])
+            .args(args);
+        command
+    }
+
+    /// Run `uhash --json <args>` against the devnet, returning the NDJSON lines
+    fn uhash(&self, args: &[&str]) -> (std::process::ExitStatus, Vec<Value>) {
+        let output = self.command(args).output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines = stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|_| panic!("not JSON: {}", line)))
+            .collect();
+        (output.status, lines)
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn events<'a>(lines: &'a [Value], event: &str) -> Vec<&'a Value> {
+    lines.iter().filter(|l| l["event"] == event).collect()
+}
+
+fn is_round_event(line: &Value) -> bool {
+    line["event"] == "round_started" || line["event"] == "round_ended"
+}
+
+#[test]
+fn test_mine_finds_and_submits_proofs() {
+    let harness = Harness::new("mine");
+    let (status, lines) = harness.uhash(&[
+        "--broadcast-mode",
+        "block",
+        "mine",
+        "--threads",
+        "2",
+        "--max-proofs",
+        "2",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+
+    let started = events(&lines, "mine_started");
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0]["difficulty"], 1);
+    assert_eq!(started[0]["contract_schema"], "v2");
+    let checks = started[0]["preflight"].as_array().unwrap();
+    assert_eq!(checks.len(), 4);
+    assert!(
+        checks.iter().all(|c| c["status"] == "passed"),
+        "{:?}",
+        checks
+    );
+    let found = events(&lines, "proof_found");
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0]["thread_hashrates"].as_array().unwrap().len(), 2);
+
+    let round_started = events(&lines, "round_started");
+    let round_ended = events(&lines, "round_ended");
+    assert_eq!(round_started.len(), 2);
+    assert_eq!(round_ended.len(), 2);
+    for (round, ended) in round_started.iter().zip(&round_ended) {
+        assert_eq!(round["seed"], started[0]["seed"]);
+        assert_eq!(round["threads"], 2);
+        assert_eq!(ended["seed"], round["seed"]);
+        assert_eq!(ended["timestamp"], round["timestamp"]);
+        assert_eq!(ended["difficulty"], 1);
+        assert_eq!(ended["outcome"], "proof");
+        assert!(ended["hashes"].as_u64().unwrap() > 0);
+        assert!(ended["duration_s"].as_f64().unwrap() > 0.0);
+    }
+
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 2);
+    for (i, event) in submitted.iter().enumerate() {
+        assert_eq!(event["success"], true);
+        assert_eq!(event["proofs_submitted"], i as u64 + 1);
+        assert_eq!(event["reward"], "1000000");
+        assert!(event["reward_denom"].as_str().unwrap().ends_with("/li"));
+        assert_eq!(event["gas_used"], MOCK_GAS_USED);
+        assert!(event["height"].as_u64().unwrap() > 1);
+    }
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 2);
+
+    let (status, lines) = harness.uhash(&["stats", "--mine"]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(lines[0]["proofs_submitted"], 2);
+    assert_eq!(lines[0]["total_rewards"], "2000000");
+
+    let miner = MinerStatus::read(&harness.dir.join("miner-status.json"))
+        .unwrap()
+        .expect("mine writes a status file");
+    assert!(miner.stopped_at.is_some());
+    assert_eq!(miner.threads, 2);
+    assert_eq!(miner.proofs_found, 2);
+    assert_eq!(miner.proofs_submitted, 2);
+    assert!(miner
+        .proofs
+        .iter()
+        .all(|p| p.reward.as_deref() == Some("1000000")));
+}
+
+#[test]
+fn test_mine_reports_exact_luck() {
+    // Every hash meets difficulty 0, so each proof comes from the first hash
+    // of its round on the single thread: exactly the one hash expected
+    let harness = Harness::with_params(
+        "luck",
+        DevnetParams {
+            difficulty: 0,
+            ..DevnetParams::default()
+        },
+    );
+    let (status, lines) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "2"]);
+    assert!(status.success(), "{:?}", lines);
+
+    let found = events(&lines, "proof_found");
+    assert_eq!(found.len(), 2);
+    for proof in &found {
+        assert_eq!(proof["hashes_computed"], 1);
+        assert_eq!(proof["effort"], 1.0);
+        assert_eq!(proof["luck"], 1.0);
+        assert_eq!(proof["luck_probability"], (-1.0f64).exp());
+    }
+}
+
+#[test]
+fn test_mine_takes_turns_at_addresses() {
+    let harness = Harness::new("addresses");
+    let alice = "bostrom1qyqszqgpqyqszqgpqyqszqgpqyqszqgp3q4dnm";
+    let bob = "bostrom1qgpqyqszqgpqyqszqgpqyqszqgpqyqszqyngcd";
+    let book = harness.dir.join("addresses.txt");
+    std::fs::write(&book, format!("# family\n{}\n{}\n", alice, bob)).unwrap();
+    let (status, lines) = harness.uhash(&[
+        "--broadcast-mode",
+        "block",
+        "mine",
+        "--threads",
+        "1",
+        "--max-proofs",
+        "2",
+        "--addresses",
+        book.to_str().unwrap(),
+    ]);
+    assert!(status.success(), "{:?}", lines);
+
+    let started = events(&lines, "mine_started");
+    assert_eq!(started[0]["addresses"], serde_json::json!([alice, bob]));
+    // One round per proof at difficulty 1, so each address gets one
+    let rounds: Vec<&Value> = events(&lines, "round_started")
+        .iter()
+        .map(|round| &round["address"])
+        .collect();
+    assert_eq!(rounds, [alice, bob]);
+    let stopped = &events(&lines, "mine_stopped")[0];
+    for (entry, address) in stopped["addresses"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip([alice, bob])
+    {
+        assert_eq!(entry["address"], address);
+        assert_eq!(entry["rounds"], 1);
+        assert_eq!(entry["proofs_found"], 1);
+        assert_eq!(entry["proofs_submitted"], 1);
+    }
+
+    let (status, _) = harness.uhash(&["mine", "--addresses", "missing.txt"]);
+    assert!(!status.success());
+}
+
+#[test]
+fn test_mine_preflight_fails_fast() {
+    let harness = Harness::new("preflight");
+    let (status, _) = harness.uhash(&["config", "set", "chain.chain_id", "bostrom"]);
+    assert!(status.success());
+
+    let (status, lines) = harness.uhash(&["mine", "--max-proofs", "1"]);
+    assert!(!status.success());
+    let error = lines[0]["error"].as_str().unwrap();
+    assert!(error.starts_with("Preflight checks failed"), "{}", error);
+    assert!(error.contains("serves chain 'uhash-devnet-1'"), "{}", error);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 0);
+
+    // Without submissions the mismatch is only a warning
+    let (status, lines) = harness.uhash(&["mine", "--no-submit"]);
+    assert!(status.success(), "{:?}", lines);
+    let started = events(&lines, "mine_started");
+    assert_eq!(started[0]["preflight"][1]["name"], "chain_id");
+    assert_eq!(started[0]["preflight"][1]["status"], "failed");
+}
+
+#[test]
+fn test_mine_with_worker_tag() {
+    let harness = Harness::new("untagged");
+    let (status, lines) = harness.uhash(&["mine", "--worker-tag", "rig-7"]);
+    assert!(!status.success());
+    assert_eq!(
+        lines[0]["error"],
+        "The contract does not accept worker tags"
+    );
+
+    let harness = Harness::with_params(
+        "tagged",
+        DevnetParams {
+            difficulty: 1,
+            max_worker_tag_len: Some(16),
+            ..DevnetParams::default()
+        },
+    );
+    let (status, lines) = harness.uhash(&[
+        "--broadcast-mode",
+        "block",
+        "mine",
+        "--max-proofs",
+        "1",
+        "--worker-tag",
+        "rig-7",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(events(&lines, "mine_started")[0]["worker_tag"], "rig-7");
+    // The devnet only accepts the proof if its hash covers the tag
+    assert_eq!(events(&lines, "proof_submitted")[0]["success"], true);
+}
+
+#[test]
+fn test_dashboard_serves_miner_status() {
+    let harness = Harness::new("dashboard");
+    let dashboard = Dashboard::new(harness.dir.join("miner-status.json"));
+    let addr = {
+        let _guard = harness.runtime.enter();
+        dashboard.spawn("127.0.0.1:0".parse().unwrap()).unwrap()
+    };
+    let get = |path: &str| -> (u16, String) {
+        harness.runtime.block_on(async {
+            let response = reqwest::get(format!("http://{}{}", addr, path))
+                .await
+                .unwrap();
+            (response.status().as_u16(), response.text().await.unwrap())
+        })
+    };
+
+    let (code, body) = get("/api/status");
+    assert_eq!(code, 200);
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["running"], false);
+    assert!(body["status"].is_null());
+
+    let (status, lines) = harness.uhash(&["mine", "--max-proofs", "1"]);
+    assert!(status.success(), "{:?}", lines);
+    let (_, body) = get("/api/status");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["running"], false, "the miner exited");
+    assert_eq!(body["status"]["proofs_found"], 1);
+    assert_eq!(body["status"]["proofs"].as_array().unwrap().len(), 1);
+
+    let (code, metrics) = get("/metrics");
+    assert_eq!(code, 200);
+    assert!(metrics.contains("uhash_up 0\n"));
+    assert!(metrics.contains("uhash_proofs_found 1\n"));
+    let threads = MinerStatus::read(&harness.dir.join("miner-status.json"))
+        .unwrap()
+        .unwrap()
+        .threads;
+    for thread in 0..threads {
+        assert!(metrics.contains(&format!("uhash_thread_hashrate{{thread=\"{}\"}}", thread)));
+    }
+
+    let (code, page) = get("/");
+    assert_eq!(code, 200);
+    assert!(page.contains("api/status"));
+}
+
+#[test]
+fn test_mine_retries_injected_failures() {
+    let harness = Harness::new("faults");
+    harness.devnet.inject(Fault::Unavailable);
+    harness.devnet.inject(Fault::RateLimited);
+    harness.devnet.inject(Fault::SequenceMismatch);
+
+    let (status, lines) = harness.uhash(&[
+        "--broadcast-mode",
+        "block",
+        "mine",
+        "--threads",
+        "1",
+        "--max-proofs",
+        "1",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0]["success"], true);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 1);
+}
+
+#[test]
+fn test_gas_simulation_retries_sequence_mismatch() {
+    let harness = Harness::new("simulate-sequence");
+    // Taken by the simulation, which comes before the broadcast with --gas auto
+    harness.devnet.inject(Fault::SequenceMismatch);
+
+    let (status, lines) = harness.uhash(&[
+        "--gas",
+        "auto",
+        "--broadcast-mode",
+        "block",
+        "mine",
+        "--threads",
+        "1",
+        "--max-proofs",
+        "1",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0]["success"], true);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 1);
+}
+
+#[test]
+fn test_mine_resubmits_evicted_proof() {
+    let harness = Harness::new("evict");
+    harness.devnet.inject(Fault::Evicted);
+
+    let (status, lines) = harness.uhash(&[
+        "--confirm-timeout",
+        "2",
+        "mine",
+        "--threads",
+        "1",
+        "--max-proofs",
+        "1",
+    ]);
+    assert!(status.success());
+
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 2);
+    let updates = events(&lines, "tx_status");
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0]["status"], "evicted");
+    assert_eq!(updates[0]["tx_hash"], submitted[0]["tx_hash"]);
+    assert_eq!(updates[0]["resubmitted"], 1);
+    assert_eq!(updates[1]["status"], "finalized");
+    assert_eq!(updates[1]["tx_hash"], submitted[1]["tx_hash"]);
+    assert_eq!(updates[1]["success"], true);
+    assert_eq!(events(&lines, "mine_stopped")[0]["proofs_submitted"], 1);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 1);
+}
+
+#[test]
+fn test_rejected_proof_reports_error() {
+    let harness = Harness::new("reject");
+    let (status, lines) = harness.uhash(&[
+        "--broadcast-mode",
+        "block",
+        "send",
+        "--hash",
+        &"ab".repeat(32),
+        "--nonce",
+        "1",
+        "--timestamp",
+        &std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string(),
+    ]);
+    assert!(!status.success());
+    assert_eq!(lines.len(), 1);
+    let error = lines[0]["error"].as_str().unwrap();
+    assert!(error.contains("hash does not match"), "{}", error);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 0);
+}
+
+#[test]
+fn test_send_dry_run_simulates_without_broadcasting() {
+    let harness = Harness::new("dryrun");
+    let (_, lines) = harness.uhash(&["address"]);
+    let address = lines[0]["address"].as_str().unwrap().to_string();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let seed = harness.devnet.chain().seed().to_vec();
+    let mut hasher = UniversalHash::new();
+    let (hash, nonce) = (0u64..)
+        .map(|nonce| {
+            let mut input = seed.clone();
+            input.extend_from_slice(address.as_bytes());
+            input.extend_from_slice(&now.to_le_bytes());
+            input.extend_from_slice(&nonce.to_le_bytes());
+            (hasher.hash(&input), nonce)
+        })
+        .find(|(hash, _)| meets_difficulty(hash, 1))
+        .unwrap();
+    let send = |nonce: u64| {
+        harness.uhash(&[
+            "send",
+            "--dry-run",
+            "--hash",
+            &hex::encode(hash),
+            "--nonce",
+            &nonce.to_string(),
+            "--timestamp",
+            &now.to_string(),
+        ])
+    };
+
+    let (status, lines) = send(nonce);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(lines[0]["event"], "simulation");
+    assert_eq!(lines[0]["accepted"], true);
+    assert_eq!(lines[0]["gas_used"], 120000);
+    assert!(lines[0]["gas_limit"].as_u64().unwrap() >= 120000);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 0);
+
+    // The same hash with another nonce fails the contract's check
+    let (status, lines) = send(nonce + 1);
+    assert!(!status.success());
+    assert_eq!(lines[0]["accepted"], false);
+    let error = lines[1]["error"].as_str().unwrap();
+    assert!(error.contains("hash does not match"), "{}", error);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 0);
+}
+
+#[test]
+fn test_tx_reports_committed_proof() {
+    let harness = Harness::new("tx");
+    let (status, lines) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "1"]);
+    assert!(status.success(), "{:?}", lines);
+    let submitted = events(&lines, "proof_submitted");
+    let tx_hash = submitted[0]["tx_hash"].as_str().unwrap();
+
+    let (status, lines) = harness.uhash(&["tx", tx_hash]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(lines[0]["success"], true);
+    assert_eq!(lines[0]["code"], 0);
+    assert_eq!(lines[0]["reward"], "1000000");
+    assert!(lines[0]["height"].as_u64().unwrap() > 0);
+
+    let (status, lines) = harness.uhash(&["tx", &"00".repeat(32)]);
+    assert!(!status.success());
+    assert!(lines[0]["error"].as_str().unwrap().contains("not found"));
+}
+
+#[test]
+fn test_stats_follow_streams_accepted_proofs() {
+    let harness = Harness::new("follow");
+    let mut follow = harness
+        .command(&["stats", "--follow"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(follow.stdout.take().unwrap()).lines();
+    let mut next_line = || -> Value {
+        let line = lines.next().expect("follow exited").unwrap();
+        serde_json::from_str(&line).unwrap()
+    };
+    assert_eq!(next_line()["event"], "subscribed");
+
+    let (status, mined) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "2"]);
+    assert!(status.success(), "{:?}", mined);
+    let submitted = events(&mined, "proof_submitted");
+
+    for event in submitted {
+        let accepted = next_line();
+        assert_eq!(accepted["event"], "proof_accepted");
+        assert_eq!(accepted["tx_hash"], event["tx_hash"]);
+        assert_eq!(accepted["difficulty"], 1);
+        assert_eq!(accepted["reward"], "1000000");
+        assert!(accepted["miner"].as_str().unwrap().starts_with("bostrom1"));
+    }
+    let _ = follow.kill();
+    let _ = follow.wait();
+}
+
+#[test]
+fn test_management_api_pauses_and_resizes_miner() {
+    let harness = Harness::new("api");
+    let mut mine = harness
+        .command(&[
+            "mine",
+            "--threads",
+            "1",
+            // Never finds a proof, so the miner keeps running until killed
+            "--difficulty",
+            "60",
+            "--no-submit",
+            "--api",
+            "127.0.0.1:0",
+        ])
+        .env("UHASH_API_TOKEN", "s3cret")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(mine.stdout.take().unwrap()).lines();
+    let started: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(started["event"], "mine_started");
+    let api = started["api"].as_str().unwrap().to_string();
+
+    let client = reqwest::Client::new();
+    let call = |method: reqwest::Method, path: &str, token: &str, body: Option<Value>| {
+        harness.runtime.block_on(async {
+            let mut request = client
+                .request(method, format!("{}{}", api, path))
+                .bearer_auth(token);
+            if let Some(body) = body {
+                request = request.json(&body);
+            }
+            let response = request.send().await.unwrap();
+            let code = response.status().as_u16();
+            (code, response.json::<Value>().await.unwrap())
+        })
+    };
+    let wait_for = |check: &dyn Fn(&Value) -> bool| {
+        for _ in 0..50 {
+            let (_, status) = call(reqwest::Method::GET, "/status", "s3cret", None);
+            if check(&status) {
+                return status;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        panic!("miner status never matched");
+    };
+
+    let (code, _) = call(reqwest::Method::GET, "/status", "wrong", None);
+    assert_eq!(code, 401);
+    let (code, status) = call(reqwest::Method::GET, "/status", "s3cret", None);
+    assert_eq!(code, 200);
+    assert_eq!(status["threads"], 1);
+
+    let (code, _) = call(reqwest::Method::POST, "/pause", "s3cret", None);
+    assert_eq!(code, 200);
+    wait_for(&|s| s["paused"] == true);
+
+    let threads = |n: u64| Some(serde_json::json!({ "threads": n }));
+    let (code, _) = call(reqwest::Method::POST, "/threads", "s3cret", threads(0));
+    assert_eq!(code, 400);
+    let (code, _) = call(reqwest::Method::POST, "/threads", "s3cret", threads(2));
+    assert_eq!(code, 200);
+    let (code, _) = call(reqwest::Method::POST, "/resume", "s3cret", None);
+    assert_eq!(code, 200);
+    let status = wait_for(&|s| s["paused"] == false && s["threads"] == 2);
+    assert_eq!(status["thread_hashrates"].as_array().unwrap().len(), 2);
+
+    let (code, proofs) = call(reqwest::Method::GET, "/proofs", "s3cret", None);
+    assert_eq!(code, 200);
+    assert_eq!(proofs, serde_json::json!([]));
+
+    let _ = mine.kill();
+    let _ = mine.wait();
+}
+
+#[test]
+fn test_controller_submits_proofs_found_by_workers() {
+    let harness = Harness::new("fleet");
+    let (_, lines) = harness.uhash(&["address"]);
+    let address = lines[0]["address"].as_str().unwrap().to_string();
+
+    let mut controller = harness
+        .command(&["controller", "--listen", "127.0.0.1:0", "--max-proofs", "2"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(controller.stdout.take().unwrap()).lines();
+    let mut next_line = || -> Value {
+        let line = lines.next().expect("controller exited").unwrap();
+        serde_json::from_str(&line).unwrap()
+    };
+    let started = next_line();
+    assert_eq!(started["event"], "controller_started");
+    assert_eq!(started["address"], address.as_str());
+    let url = format!("tcp://{}", started["listen"].as_str().unwrap());
+
+    // Workers need neither the wallet nor the chain
+    let mut worker = Command::new(env!("CARGO_BIN_EXE_uhash"))
+        .args(["--json", "worker", "--controller", &url, "--threads", "1"])
+        .args(["--name", "rig-1"])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut found = 0;
+    let mut submitted = Vec::new();
+    while submitted.len() < 2 {
+        let line = next_line();
+        match line["event"].as_str().unwrap() {
+            "worker_connected" => assert_eq!(line["name"], "rig-1"),
+            "proof_found" => {
+                assert_eq!(line["worker"], "rig-1");
+                found += 1;
+            }
+            "proof_submitted" => submitted.push(line),
+            _ => {}
+        }
+    }
+    assert_eq!(found, 2);
+    assert!(submitted.iter().all(|s| s["success"] == true));
+    assert!(controller.wait().unwrap().success());
+    let _ = worker.kill();
+    let _ = worker.wait();
+
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 2);
+    let (_, lines) = harness.uhash(&["stats", "--mine"]);
+    assert_eq!(lines[0]["proofs_submitted"], 2);
+}
+
+#[test]
+fn test_controller_batches_proofs_into_one_transaction() {
+    let harness = Harness::new("batch");
+    let mut controller = harness
+        .command(&[
+            "--broadcast-mode",
+            "block",
+            "controller",
+            "--listen",
+            "127.0.0.1:0",
+            "--max-proofs",
+            "3",
+            "--batch-size",
+            "3",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(controller.stdout.take().unwrap()).lines();
+    let mut next_line = || -> Value {
+        let line = lines.next().expect("controller exited").unwrap();
+        serde_json::from_str(&line).unwrap()
+    };
+    let started = next_line();
+    let url = format!("tcp://{}", started["listen"].as_str().unwrap());
+    let mut worker = Command::new(env!("CARGO_BIN_EXE_uhash"))
+        .args(["--json", "worker", "--controller", &url, "--threads", "1"])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut submitted = Vec::new();
+    while submitted.len() < 3 {
+        let line = next_line();
+        if line["event"] == "proof_submitted" {
+            submitted.push(line);
+        }
+    }
+    assert!(controller.wait().unwrap().success());
+    let _ = worker.kill();
+    let _ = worker.wait();
+
+    // One transaction, whose reward is reported once for all three proofs
+    assert!(submitted.iter().all(|s| s["success"] == true));
+    assert!(submitted
+        .iter()
+        .all(|s| s["tx_hash"] == submitted[0]["tx_hash"]));
+    let chain = harness.devnet.chain();
+    assert_eq!(chain.proofs_accepted(), 3);
+    let tx = chain.tx(submitted[0]["tx_hash"].as_str().unwrap()).unwrap();
+    assert_eq!(tx.messages.len(), 3);
+    let reward: u128 = submitted[0]["reward"].as_str().unwrap().parse().unwrap();
+    assert_eq!(reward, 3 * chain.params().base_reward);
+    assert!(submitted[1].get("reward").is_none());
+}
+
+#[test]
+fn test_watch_only_mining_hands_off_for_offline_signing() {
+    let harness = Harness::new("watch");
+    let (_, lines) = harness.uhash(&["address"]);
+    let address = lines[0]["address"].as_str().unwrap().to_string();
+
+    // Keep the key aside and mine with only the address
+    let wallet = harness.dir.join("wallet.txt");
+    let signer = harness.dir.join("signer.txt");
+    std::fs::rename(&wallet, &signer).unwrap();
+    let (status, lines) = harness.uhash(&["import-address", &address]);
+    assert!(status.success(), "{:?}", lines);
+    let (status, lines) = harness.uhash(&["sign-message", "hello"]);
+    assert!(!status.success());
+    assert!(lines[0]["error"].as_str().unwrap().contains("watch-only"));
+
+    let (status, mined) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "1"]);
+    assert!(status.success(), "{:?}", mined);
+    assert_eq!(events(&mined, "mine_started")[0]["watch_only"], true);
+    assert!(events(&mined, "proof_submitted").is_empty());
+    let unsigned = events(&mined, "proof_unsigned");
+    assert_eq!(unsigned.len(), 1);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 0);
+
+    // Sign where the key is, broadcast from the mining host
+    std::fs::rename(&signer, &wallet).unwrap();
+    let tx = harness.dir.join("proof.json");
+    let proof = unsigned[0];
+    let (status, lines) = harness.uhash(&[
+        "sign-proof",
+        "--proof",
+        proof["proof"].as_str().unwrap(),
+        "--account-number",
+        &proof["account_number"].to_string(),
+        "--sequence",
+        &proof["sequence"].to_string(),
+        "-o",
+        tx.to_str().unwrap(),
+    ]);
+    assert!(status.success(), "{:?}", lines);
+    let (status, lines) = harness.uhash(&["broadcast", tx.to_str().unwrap()]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 1);
+}
+
+#[test]
+fn test_network_hashrate_samples_accepted_proofs() {
+    let harness = Harness::new("hashrate");
+    let (status, lines) = harness.uhash(&["network-hashrate"]);
+    assert!(!status.success());
+    assert!(lines[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("two accepted proofs"));
+
+    // Relay three difficulty-1 proofs a minute apart
+    let start = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        - 120;
+    let mut hasher = UniversalHash::new();
+    for i in 0..3 {
+        let mut chain = harness.devnet.chain();
+        let (miner, now) = (format!("bostrom1miner{}", i), start + i * 60);
+        let (hash, nonce) = (0u64..)
+            .map(|nonce| {
+                let mut input = chain.seed().to_vec();
+                input.extend_from_slice(miner.as_bytes());
+                input.extend_from_slice(&now.to_le_bytes());
+                input.extend_from_slice(&nonce.to_le_bytes());
+                (hasher.hash(&input), nonce)
+            })
+            .find(|(hash, _)| meets_difficulty(hash, 1))
+            .unwrap();
+        chain
+            .relay(&miner, &hex::encode(hash), nonce, now, now)
+            .unwrap();
+    }
+
+    let (status, lines) = harness.uhash(&["network-hashrate", "--samples", "10"]);
+    assert!(status.success(), "{:?}", lines);
+    let estimate = &lines[0];
+    assert_eq!(estimate["proofs"], 3);
+    assert_eq!(estimate["span_secs"], 120);
+    assert_eq!(estimate["difficulty"], 1);
+    // Two arrivals of 2 hashes each in 120 s
+    assert!((estimate["hashrate"].as_f64().unwrap() - 4.0 / 120.0).abs() < 1e-9);
+    assert!(estimate["low"].as_f64().unwrap() < estimate["hashrate"].as_f64().unwrap());
+    assert!(estimate["high"].as_f64().unwrap() > estimate["hashrate"].as_f64().unwrap());
+    assert!(estimate["from_height"].as_u64().unwrap() < estimate["to_height"].as_u64().unwrap());
+}
+
+#[test]
+fn test_audit_reverifies_accepted_proofs() {
+    let harness = Harness::new("audit");
+    let (status, lines) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "2"]);
+    assert!(status.success(), "{:?}", lines);
+
+    let (status, lines) = harness.uhash(&["audit", "--from-height", "1"]);
+    assert!(status.success(), "{:?}", lines);
+    assert!(events(&lines, "discrepancy").is_empty());
+    let finished = events(&lines, "audit_finished");
+    assert_eq!(finished[0]["proofs"], 2);
+    assert_eq!(finished[0]["verified"], 2);
+
+    let last = finished[0]["last_height"].as_u64().unwrap();
+    let (status, lines) = harness.uhash(&["audit", "--from-height", &(last + 1).to_string()]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(events(&lines, "audit_finished")[0]["proofs"], 0);
+}
+
+#[test]
+fn test_mine_hands_proofs_to_configured_sink() {
+    let harness = Harness::new("sink");
+    let proofs = harness.dir.join("proofs.jsonl");
+    for (key, value) in [
+        ("submit.sink", "file"),
+        ("submit.path", proofs.to_str().unwrap()),
+    ] {
+        let (status, lines) = harness.uhash(&["config", "set", key, value]);
+        assert!(status.success(), "{:?}", lines);
+    }
+
+    let (status, lines) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "2"]);
+    assert!(status.success(), "{:?}", lines);
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 2);
+    assert_eq!(submitted[1]["destination"], proofs.to_str().unwrap());
+    assert_eq!(submitted[1]["proofs_submitted"], 2);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 0);
+
+    let found = events(&lines, "proof_found");
+    let written: Vec<Value> = std::fs::read_to_string(&proofs)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(written.len(), 2);
+    for (proof, found) in written.iter().zip(found) {
+        assert_eq!(proof["hash"], found["hash"]);
+        assert_eq!(proof["nonce"], found["nonce"]);
+    }
+}
+
+#[test]
+fn test_relay_submits_proofs_for_unfunded_miners() {
+    let harness = Harness::new("relay");
+    let relayer = uhash::wallet::Wallet::new().unwrap();
+    let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+        &relayer.signing_key().unwrap().to_bytes(),
+    )
+    .unwrap();
+    let client = uhash::rpc::RpcClient::try_with_config(uhash::rpc::RpcConfig {
+        rpc_url: harness.url.clone(),
+        lcd_url: harness.url.clone(),
+        cache_ttl_secs: 0,
+        ..uhash::rpc::RpcConfig::default()
+    })
+    .unwrap();
+    let relay = uhash::relay::Relay::new(client, signing_key, "bostrom").unwrap();
+    let addr = {
+        let _guard = harness.runtime.enter();
+        relay.spawn("127.0.0.1:0".parse().unwrap()).unwrap()
+    };
+    let relay_url = format!("http://{}/relay", addr);
+
+    // The miner hands its proofs to the relay through the pool sink
+    for (key, value) in [("submit.sink", "pool"), ("submit.url", relay_url.as_str())] {
+        let (status, lines) = harness.uhash(&["config", "set", key, value]);
+        assert!(status.success(), "{:?}", lines);
+    }
+    let (status, lines) = harness.uhash(&["mine", "--threads", "1", "--max-proofs", "2"]);
+    assert!(status.success(), "{:?}", lines);
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 2);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 2);
+
+    // Signed by the relayer, credited to the miner
+    let tx_hash = submitted[0]["tx_hash"].as_str().unwrap();
+    let proof = {
+        let chain = harness.devnet.chain();
+        let message = &chain.tx(tx_hash).unwrap().messages[0];
+        assert_eq!(message["sender"], relayer.address_str());
+        message["msg"]["submit_proof"].clone()
+    };
+    let (_, address) = harness.uhash(&["address"]);
+    assert_eq!(proof["miner_address"], address[0]["address"]);
+
+    // Replays and forgeries never reach the chain
+    let client = reqwest::Client::new();
+    let post = |body: &Value| {
+        harness.runtime.block_on(async {
+            let response = client.post(&relay_url).json(body).send().await.unwrap();
+            let code = response.status().as_u16();
+            (code, response.json::<Value>().await.unwrap())
+        })
+    };
+    let (code, reply) = post(&proof);
+    assert_eq!(code, 409, "{}", reply);
+    assert_eq!(reply["ok"], false);
+    let mut forged = proof.clone();
+    forged["hash"] = "00".repeat(32).into();
+    let (code, reply) = post(&forged);
+    assert_eq!(code, 422, "{}", reply);
+    assert!(reply["error"].as_str().unwrap().contains("U-Hash"));
+
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 2);
+    let health = relay.health();
+    assert_eq!((health.relayed, health.refused), (2, 2));
+}
+
+#[test]
+fn test_mine_submits_via_relay_with_authorization() {
+    let harness = Harness::new("submit-via");
+    let relayer = uhash::wallet::Wallet::new().unwrap();
+    let signing_key = cosmrs::crypto::secp256k1::SigningKey::from_slice(
+        &relayer.signing_key().unwrap().to_bytes(),
+    )
+    .unwrap();
+    let client = uhash::rpc::RpcClient::try_with_config(uhash::rpc::RpcConfig {
+        rpc_url: harness.url.clone(),
+        lcd_url: harness.url.clone(),
+        cache_ttl_secs: 0,
+        ..uhash::rpc::RpcConfig::default()
+    })
+    .unwrap();
+    let relay = uhash::relay::Relay::new(client, signing_key, "bostrom")
+        .unwrap()
+        .require_authorization(true);
+    let addr = {
+        let _guard = harness.runtime.enter();
+        relay.spawn("127.0.0.1:0".parse().unwrap()).unwrap()
+    };
+    let relay_url = format!("http://{}", addr);
+
+    let (status, lines) = harness.uhash(&[
+        "mine",
+        "--threads",
+        "1",
+        "--max-proofs",
+        "1",
+        "--submit-via",
+        &relay_url,
+    ]);
+    assert!(status.success(), "{:?}", lines);
+    let submitted = events(&lines, "proof_submitted");
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0]["destination"], format!("{}/relay", relay_url));
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 1);
+    assert_eq!((relay.health().relayed, relay.health().refused), (1, 0));
+
+    let tx_hash = submitted[0]["tx_hash"].as_str().unwrap();
+    let chain = harness.devnet.chain();
+    let message = &chain.tx(tx_hash).unwrap().messages[0];
+    assert_eq!(message["sender"], relayer.address_str());
+}
+
+#[test]
+fn test_benchmark_share_and_devices_table() {
+    let harness = Harness::new("telemetry");
+
+    // Opt-in: a plain benchmark uploads nothing
+    let (status, lines) = harness.uhash(&["benchmark", "--count", "2"]);
+    assert!(status.success(), "{:?}", lines);
+    assert!(lines[0].get("shared").is_none());
+    assert!(lines[0].get("phases").is_none());
+    let (status, lines) = harness.uhash(&["benchmark", "--count", "2", "--detailed"]);
+    assert!(status.success(), "{:?}", lines);
+    assert!(lines[0]["phases"]["rounds_us"].as_f64().unwrap() > 0.0);
+    let (status, _) = harness.uhash(&["benchmark", "--count", "2", "--share"]);
+    assert!(!status.success(), "sharing needs telemetry.url");
+    assert!(harness.devnet.benchmarks().is_empty());
+
+    let (status, _) = harness.uhash(&["config", "set", "telemetry.url", &harness.url]);
+    assert!(status.success());
+    for class in ["phone", "desktop"] {
+        let (status, lines) = harness.uhash(&[
+            "benchmark",
+            "--count",
+            "2",
+            "--share",
+            "--device-class",
+            class,
+        ]);
+        assert!(status.success(), "{:?}", lines);
+        assert_eq!(lines[0]["shared"]["device_class"], class);
+        assert_eq!(lines[0]["shared"]["hashrate"], lines[0]["hashrate"]);
+    }
+    let shared = harness.devnet.benchmarks();
+    assert_eq!(shared.len(), 2);
+    assert!(shared[0].threads > 0);
+
+    let (status, lines) = harness.uhash(&["devices"]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(lines[0]["devices"].as_array().unwrap().len(), 2);
+    let classes = lines[0]["classes"].as_array().unwrap();
+    assert_eq!(classes[0]["device_class"], "phone");
+    assert_eq!(classes[1]["device_class"], "desktop");
+}
+
+#[test]
+fn test_profit_estimate_from_contract_state() {
+    let harness = Harness::new("profit");
+    let (status, lines) = harness.uhash(&[
+        "profit",
+        "--power-watts",
+        "15",
+        "--electricity",
+        "0.20",
+        "--hashrate",
+        "1000",
+        "--difficulty",
+        "20",
+        "--li-price",
+        "0.01",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+    let out = &lines[0];
+    assert_eq!(out["measured"], false);
+    assert_eq!(out["params"]["base_reward"], 1_000_000);
+    assert_eq!(out["params"]["li"], 0.01);
+    let proofs = out["proofs_per_day"].as_f64().unwrap();
+    assert!((proofs - 1000.0 * 86_400.0 / 2f64.powi(20)).abs() < 1e-9);
+    let profit = out["profit_per_day"].as_f64().unwrap();
+    assert!((profit - (proofs * 0.01 - 0.072)).abs() < 1e-9);
+    assert!(out["break_even_difficulty"].as_f64().unwrap() > 20.0);
+
+    // Without prices there is still the LI per day and the break-even price
+    let (status, lines) = harness.uhash(&["profit", "--power-watts", "15", "--hashrate", "10"]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(lines[0]["params"]["difficulty"], 1);
+    assert!(lines[0].get("profit_per_day").is_none());
+    assert_eq!(lines[0]["break_even_li_price"], 0.0);
+}
+
+#[test]
+fn test_mine_submits_pending_proofs_and_stops_on_sigterm() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let harness = Harness::new("signal");
+    let (_, lines) = harness.uhash(&["address"]);
+    let address = lines[0]["address"].as_str().unwrap().to_string();
+
+    // A proof an earlier run saved when it was stopped
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let seed = harness.devnet.chain().seed().to_vec();
+    let mut hasher = UniversalHash::new();
+    let (hash, nonce) = (0u64..)
+        .map(|nonce| {
+            let mut input = seed.clone();
+            input.extend_from_slice(address.as_bytes());
+            input.extend_from_slice(&now.to_le_bytes());
+            input.extend_from_slice(&nonce.to_le_bytes());
+            (hasher.hash(&input), nonce)
+        })
+        .find(|(hash, _)| meets_difficulty(hash, 1))
+        .unwrap();
+    let pending = harness.dir.join("pending-proofs.jsonl");
+    let queued = serde_json::json!({
+        "hash": hex::encode(hash),
+        "nonce": nonce,
+        "timestamp": now,
+        "miner_address": address,
+    });
+    std::fs::write(&pending, format!("{}\n", queued)).unwrap();
+
+    let mut mine = harness
+        .command(&[
+            "--broadcast-mode",
+            "block",
+            "mine",
+            "--threads",
+            "1",
+            // Never finds a proof, so only the queued one is submitted
+            "--difficulty",
+            "60",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(mine.stdout.take().unwrap()).lines();
+    // Round events are covered elsewhere
+    let mut next = || -> Value {
+        loop {
+            let line: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+            if !is_round_event(&line) {
+                return line;
+            }
+        }
+    };
+    assert_eq!(next()["event"], "mine_started");
+    let submitted = next();
+    assert_eq!(submitted["event"], "proof_submitted");
+    assert_eq!(submitted["success"], true);
+    assert_eq!(harness.devnet.chain().proofs_accepted(), 1);
+
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let status = Command::new("kill")
+        .args(["-TERM", &mine.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let stopped = next();
+    assert_eq!(stopped["event"], "mine_stopped");
+    assert_eq!(stopped["reason"], "signal");
+    assert_eq!(stopped["signal"], "SIGTERM");
+    assert_eq!(stopped["proofs_submitted"], 1);
+    assert_eq!(stopped["proofs_pending"], 0);
+    let status = mine.wait().unwrap();
+    assert_eq!(status.code(), Some(143), "{:?}", status.signal());
+    assert!(!pending.exists());
+}
+
+#[test]
+fn test_mine_resumes_interrupted_round() {
+    let harness = Harness::new("resume");
+    let round_path = harness.dir.join("round-state.json");
+    // Never finds a proof, so every run ends on SIGINT mid-round
+    let interrupted_run = |extra: &[&str]| -> (Value, Value) {
+        let mut args = vec![
+            "mine",
+            "--threads",
+            "2",
+            "--difficulty",
+            "60",
+            "--no-submit",
+        ];
+        args.extend_from_slice(extra);
+        let mut mine = harness
+            .command(&args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut lines = BufReader::new(mine.stdout.take().unwrap())
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap())
+            .filter(|line| !is_round_event(line));
+        let started = lines.next().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+        let status = Command::new("kill")
+            .args(["-INT", &mine.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let stopped = lines.next().unwrap();
+        assert_eq!(mine.wait().unwrap().code(), Some(130));
+        (started, stopped)
+    };
+
+    let (started, stopped) = interrupted_run(&[]);
+    assert!(started.get("resumed").is_none());
+    assert_eq!(stopped["event"], "mine_stopped");
+    assert_eq!(stopped["round_saved"], true);
+    let first: Value =
+        serde_json::from_str(&std::fs::read_to_string(&round_path).unwrap()).unwrap();
+    assert_eq!(first["seed"], started["seed"]);
+    assert_eq!(first["cursors"].as_array().unwrap().len(), 2);
+
+    // The next run continues the same round from the saved cursors
+    let (started, _) = interrupted_run(&["--resume"]);
+    assert_eq!(started["resumed"], first["timestamp"]);
+    let second: Value =
+        serde_json::from_str(&std::fs::read_to_string(&round_path).unwrap()).unwrap();
+    assert_eq!(second["timestamp"], first["timestamp"]);
+    for (before, after) in first["cursors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(second["cursors"].as_array().unwrap())
+    {
+        assert!(after.as_u64().unwrap() >= before.as_u64().unwrap());
+    }
+
+    // A saved round for another difficulty is not resumed
+    let (status, lines) = harness.uhash(&[
+        "mine",
+        "--threads",
+        "1",
+        "--difficulty",
+        "1",
+        "--no-submit",
+        "--resume",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+    assert!(events(&lines, "mine_started")[0].get("resumed").is_none());
+    assert!(!round_path.exists());
+}
+
+#[test]
+fn test_mine_partitions_nonces_between_workers() {
+    let harness = Harness::new("partition");
+    let (status, lines) = harness.uhash(&["mine", "--worker-index", "2", "--worker-count", "2"]);
+    assert!(!status.success());
+    assert!(lines[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("--worker-index"));
+
+    let (status, lines) = harness.uhash(&[
+        "mine",
+        "--threads",
+        "2",
+        "--max-proofs",
+        "2",
+        "--nonce-range",
+        "1000",
+        "--worker-index",
+        "1",
+        "--worker-count",
+        "2",
+    ]);
+    assert!(status.success(), "{:?}", lines);
+    let found = events(&lines, "proof_found");
+    assert_eq!(found.len(), 2);
+    for proof in found {
+        assert!((1000..2000).contains(&proof["nonce"].as_u64().unwrap()));
+    }
+
+    let (status, lines) = harness.uhash(&[
+        "mine",
+        "--nonce-random",
+        "--worker-index",
+        "0",
+        "--worker-count",
+        "2",
+    ]);
+    assert!(!status.success(), "{:?}", lines);
+    let (status, lines) = harness.uhash(&["mine", "--max-proofs", "1", "--nonce-random"]);
+    assert!(status.success(), "{:?}", lines);
+    // Practically never near zero when drawn from 2^53 nonces
+    assert!(events(&lines, "proof_found")[0]["nonce"].as_u64().unwrap() > 1 << 20);
+
+    // A range without a proof moves on to a fresh timestamp
+    let mut mine = harness
+        .command(&[
+            "mine",
+            "--threads",
+            "2",
+            "--difficulty",
+            "60",
+            "--no-submit",
+            "--nonce-range",
+            "4",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let ended: Vec<Value> = BufReader::new(mine.stdout.take().unwrap())
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap())
+        .filter(|line| line["event"] == "round_ended")
+        .take(2)
+        .collect();
+    let _ = mine.kill();
+    let _ = mine.wait();
+    assert!(ended.iter().all(|round| round["outcome"] == "exhausted"));
+    assert!(ended.iter().all(|round| round["hashes"] == 4));
+    assert!(ended[1]["timestamp"].as_u64() > ended[0]["timestamp"].as_u64());
+}
+
+#[test]
+fn test_mine_when_idle_needs_an_idle_source() {
+    let harness = Harness::new("idle");
+    // Without a display or a login session there is nothing to ask
+    let output = harness
+        .command(&["mine", "--when-idle", "--no-submit"])
+        .env_remove("DISPLAY")
+        .env_remove("XDG_SESSION_ID")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let error: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(
+        error["error"]
+            .as_str()
+            .unwrap()
+            .contains("Could not detect idleness"),
+        "{:?}",
+        error
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_mine_when_idle_waits_for_the_user_to_leave() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let harness = Harness::new("when-idle");
+    // A stand-in for xprintidle reporting the milliseconds in a file
+    let idle_ms = harness.dir.join("idle-ms");
+    std::fs::write(&idle_ms, "0").unwrap();
+    let bin = harness.dir.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let script = bin.join("xprintidle");
+    std::fs::write(&script, format!("#!/bin/sh\ncat {}\n", idle_ms.display())).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    let mut mine = harness
+        .command(&[
+            "mine",
+            "--when-idle",
+            "1",
+            "--difficulty",
+            "60",
+            "--no-submit",
+        ])
+        .env("PATH", path)
+        .env("DISPLAY", ":99")
+        .env_remove("XDG_SESSION_ID")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(mine.stdout.take().unwrap())
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap())
+        .filter(|line| line["event"] != "hashrate");
+    assert_eq!(lines.next().unwrap()["event"], "mine_started");
+    let waiting = lines.next().unwrap();
+    assert_eq!(waiting["event"], "idle_changed");
+    assert_eq!(waiting["idle"], false);
+
+    // Two minutes without input: mining starts
+    std::fs::write(&idle_ms, "120000").unwrap();
+    let idle = lines.next().unwrap();
+    assert_eq!(idle["event"], "idle_changed");
+    assert_eq!(idle["idle"], true);
+    assert_eq!(lines.next().unwrap()["event"], "round_started");
+
+    // The user is back: the round ends and mining waits again
+    std::fs::write(&idle_ms, "10").unwrap();
+    let back = lines.next().unwrap();
+    assert_eq!(back["event"], "idle_changed");
+    assert_eq!(back["idle"], false);
+    assert_eq!(lines.next().unwrap()["event"], "round_ended");
+
+    let _ = mine.kill();
+    let _ = mine.wait();
+}
+
+#[test]
+fn test_mine_reloads_config() {
+    let harness = Harness::new("reload");
+    let set_threads = |threads: &str| {
+        let (status, lines) = harness.uhash(&["config", "set", "mine.threads", threads]);
+        assert!(status.success(), "{:?}", lines);
+    };
+    set_threads("1");
+
+    let mut mine = harness
+        .command(&["mine", "--difficulty", "60", "--no-submit"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(mine.stdout.take().unwrap())
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap());
+    let mut next = |event: &str| lines.find(|line| line["event"] == event).unwrap();
+    assert_eq!(next("round_started")["threads"], 1);
+
+    // SIGHUP re-reads the file even when it did not change
+    let status = Command::new("kill")
+        .args(["-HUP", &mine.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let reloaded = next("config_reloaded");
+    assert_eq!(reloaded["changed"], serde_json::json!([]));
+    assert!(reloaded.get("error").is_none());
+
+    // An edited file is picked up and the next round uses the new threads
+    set_threads("3");
+    assert_eq!(
+        next("config_reloaded")["changed"],
+        serde_json::json!(["threads=3"])
+    );
+    assert_eq!(next("round_started")["threads"], 3);
+
+    // A broken file is reported and the miner keeps its settings
+    std::fs::write(harness.dir.join("config.toml"), "[mine\n").unwrap();
+    let reloaded = next("config_reloaded");
+    assert!(reloaded["error"].is_string(), "{:?}", reloaded);
+
+    let _ = mine.kill();
+    let _ = mine.wait();
+}
+
+#[test]
+fn test_init_sets_up_wallet_endpoints_and_threads() {
+    let harness = Harness::new("init");
+    let (status, lines) = harness.uhash(&["init"]);
+    assert!(!status.success());
+    assert!(lines[0]["error"].as_str().unwrap().contains("--yes"));
+
+    std::fs::remove_file(harness.dir.join("wallet.txt")).unwrap();
+    let (status, lines) = harness.uhash(&["init", "--yes"]);
+    assert!(status.success(), "{:?}", lines);
+    let init = &lines[0];
+    assert_eq!(init["wallet"], "created");
+    assert_eq!(init["reachable"], true);
+    assert_eq!(init["lcd"][0], harness.url.as_str());
+    let threads = init["threads"].as_u64().unwrap();
+    assert!(threads >= 1);
+    assert!(init["hashrate"].as_f64().unwrap() > 0.0);
+    assert_eq!(init["dry_run"]["difficulty"], 1);
+    assert_eq!(init["dry_run"]["hash"].as_str().unwrap().len(), 64);
+
+    let (_, lines) = harness.uhash(&["address"]);
+    assert_eq!(lines[0]["address"], init["address"]);
+    let (_, lines) = harness.uhash(&["config", "get", "mine.threads"]);
+    assert_eq!(lines[0]["value"], threads.to_string());
+    let (_, lines) = harness.uhash(&["config", "get", "network.lcd"]);
+    assert!(lines[0]["value"].as_str().unwrap().contains(&harness.url));
+
+    // A second run keeps the wallet
+    let (status, lines) = harness.uhash(&["init", "--yes"]);
+    assert!(status.success(), "{:?}", lines);
+    assert_eq!(lines[0]["wallet"], "existing");
+}
+
+#[test]
+fn test_import_mnemonic_from_stdin_without_echo() {
+    use std::io::Write;
+
+    let harness = Harness::new("import");
+    let (_, lines) = harness.uhash(&["export-mnemonic"]);
+    let mnemonic = lines[0]["mnemonic"].as_str().unwrap().to_string();
+    let address = lines[0]["address"].clone();
+
+    let import = |args: &[&str]| {
+        std::fs::remove_file(harness.dir.join("wallet.txt")).unwrap();
+        let mut child = harness
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Split over lines, as a password manager might hand it over
+        let input = mnemonic.replacen(' ', "\n", 3);
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        serde_json::from_str::<Value>(stdout.trim()).unwrap()
+    };
+
+    let imported = import(&["import-mnemonic", "--stdin"]);
+    assert_eq!(imported["address"], address);
+    assert!(imported.get("mnemonic").is_none());
+
+    let imported = import(&["import-mnemonic", "--stdin", "--confirm-display"]);
+    assert_eq!(imported["address"], address);
+    assert_eq!(imported["mnemonic"], mnemonic.as_str());
+}
+
+#[test]
+fn test_wallet_loads_on_its_saved_account() {
+    let harness = Harness::new("account");
+    let (_, lines) = harness.uhash(&["address"]);
+    let first = lines[0]["address"].clone();
+
+    let (_, lines) = harness.uhash(&["--account", "2", "address"]);
+    let second = lines[0]["address"].clone();
+    assert_ne!(second, first);
+
+    // Saved on account 2, the wallet stays there until `--account` says otherwise
+    let (_, lines) = harness.uhash(&["export-mnemonic"]);
+    let mnemonic = lines[0]["mnemonic"].as_str().unwrap().to_string();
+    std::fs::remove_file(harness.dir.join("wallet.txt")).unwrap();
+    let (status, _) = harness.uhash(&["--account", "2", "import-mnemonic", "--phrase", &mnemonic]);
+    assert!(status.success());
+    let (_, lines) = harness.uhash(&["address"]);
+    assert_eq!(lines[0]["address"], second);
+    let (_, lines) = harness.uhash(&["--account", "0", "address"]);
+    assert_eq!(lines[0]["address"], first);
+}
@@ -1,7 +1,8 @@
 //! Benchmark for UniversalHash algorithm
 
-use criterion::{Criterion, black_box, criterion_group, criterion_main};
-use uhash_core::UniversalHash;
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use uhash_core::verify::{Proof, verify_proofs, verify_proofs_parallel};
+use uhash_core::{UniversalHash, hash};
 
 fn bench_hash(c: &mut Criterion) {
     let mut hasher = UniversalHash::new();
@@ -25,5 +26,32 @@ fn bench_hash_varying_input(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_hash, bench_hash_varying_input);
+/// Proofs checked per second: one hasher in turn against the rayon pool
+fn bench_verify(c: &mut Criterion) {
+    const BATCH: u64 = 64;
+    let proofs: Vec<Proof> = (0..BATCH)
+        .map(|nonce| {
+            let mut input = b"benchmark proof header".to_vec();
+            input.extend_from_slice(&nonce.to_le_bytes());
+            Proof {
+                hash: hash(&input),
+                input,
+                difficulty: 0,
+            }
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("verify");
+    group.throughput(Throughput::Elements(BATCH));
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| black_box(verify_proofs(black_box(&proofs))))
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| black_box(verify_proofs_parallel(black_box(&proofs))))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_hash, bench_hash_varying_input, bench_verify);
 criterion_main!(benches);
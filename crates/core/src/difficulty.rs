@@ -0,0 +1,98 @@
+//! Difficulty arithmetic for share mining
+//!
+//! Difficulty is a number of leading zero bits (see [`crate::meets_difficulty`]),
+//! so a hash meets difficulty `d` with probability 2^-d. Pools hand their
+//! miners an easier *share* difficulty, the network difficulty minus an
+//! offset, so that every miner finds shares often enough to be paid for its
+//! work; shares that also meet the network difficulty are proofs.
+//!
+//! ```rust
+//! use uhash_core::difficulty::{share_difficulty, share_offset_for_rate};
+//!
+//! // 1,000 H/s aiming at 6 shares per minute against a 24-bit network
+//! let offset = share_offset_for_rate(24, 1000.0, 6.0);
+//! assert_eq!(offset, 11);
+//! assert_eq!(share_difficulty(24, offset), 13);
+//! ```
+
+use core::f64::consts::SQRT_2;
+
+/// Highest meaningful difficulty: every bit of the 256-bit hash is zero
+pub const MAX_DIFFICULTY: u32 = 256;
+
+/// Difficulty a share must meet: `network` bits minus the pool's `offset`
+pub fn share_difficulty(network: u32, offset: u32) -> u32 {
+    network.saturating_sub(offset)
+}
+
+/// Hashes needed on average to meet `difficulty`: 2^difficulty
+pub fn expected_hashes(difficulty: u32) -> f64 {
+    // The exponent field of an f64 holds any power of two up to 2^1023
+    f64::from_bits(((1023 + difficulty.min(MAX_DIFFICULTY)) as u64) << 52)
+}
+
+/// Shares per minute found at `hashrate` (H/s) against `difficulty`
+pub fn shares_per_minute(hashrate: f64, difficulty: u32) -> f64 {
+    hashrate * 60.0 / expected_hashes(difficulty)
+}
+
+/// Difficulty at which `hashrate` (H/s) finds `shares_per_minute` shares
+///
+/// Difficulties are whole bits, so the result is the one closest to the
+/// target rate on a log scale: the rate found is within a factor of √2.
+/// Rates too high for even difficulty 0 give 0.
+pub fn difficulty_for_rate(hashrate: f64, shares_per_minute: f64) -> u32 {
+    let hashes_per_share = hashrate * 60.0 / shares_per_minute;
+    // NaN comes from a zero hashrate and a zero rate
+    if hashes_per_share.is_nan() || hashes_per_share < 1.0 {
+        return 0;
+    }
+    let bound = hashes_per_share * SQRT_2;
+    let mut difficulty = 0;
+    while difficulty < MAX_DIFFICULTY && expected_hashes(difficulty + 1) <= bound {
+        difficulty += 1;
+    }
+    difficulty
+}
+
+/// How the work behind proofs compares to the expected work
+///
+/// Effort counts hashes in expected proofs: a proof found after 2^d hashes
+/// at difficulty `d` took an effort of 1.0, whatever `d` is, so rounds at
+/// different difficulties add up. Luck is the inverse: 2.0 is a proof found
+/// in half the expected hashes. Efforts are exponentially distributed, so a
+/// run of proofs that chance would rarely give (probabilities all near 0, or
+/// all near 1) points at a miscomputed difficulty or a broken nonce source.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Luck {
+    effort: f64,
+}
+
+impl Luck {
+    /// Count `hashes` hashed at `difficulty`
+    pub fn add(&mut self, hashes: u64, difficulty: u32) {
+        self.effort += hashes as f64 / expected_hashes(difficulty);
+    }
+
+    /// Work so far, in expected proofs
+    pub fn effort(&self) -> f64 {
+        self.effort
+    }
+
+    /// Expected work over actual work; infinite before any hash
+    pub fn luck(&self) -> f64 {
+        1.0 / self.effort
+    }
+
+    /// Chance that a proof takes at least this much work: e^-effort
+    #[cfg(feature = "std")]
+    pub fn probability(&self) -> f64 {
+        (-self.effort).exp()
+    }
+}
+
+/// Offset below `network` that gives `hashrate` (H/s) about
+/// `shares_per_minute` shares; 0 when network proofs already come that often
+pub fn share_offset_for_rate(network: u32, hashrate: f64, shares_per_minute: f64) -> u32 {
+    network.saturating_sub(difficulty_for_rate(hashrate, shares_per_minute))
+}
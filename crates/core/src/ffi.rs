@@ -1,6 +1,7 @@
 //! C FFI bindings for mobile platforms
 
 use crate::UniversalHash;
+use crate::params::{BLOCK_SIZE, CHAINS, ROUNDS, SCRATCHPAD_SIZE, TOTAL_MEMORY, VERSION};
 use core::slice;
 
 /// Opaque hasher handle for FFI
@@ -81,3 +82,42 @@ pub extern "C" fn uhash_hashrate(iterations: u32, microseconds: u64) -> f64 {
     }
     (iterations as f64) / (microseconds as f64 / 1_000_000.0)
 }
+
+/// Algorithm parameters, as filled in by uhash_get_params()
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UHashParams {
+    /// Number of parallel computation chains
+    pub chains: u32,
+    /// Scratchpad size per chain in bytes
+    pub scratchpad_size: u32,
+    /// Memory one hasher needs for all scratchpads, in bytes
+    pub total_memory: u32,
+    /// Rounds per chain
+    pub rounds: u32,
+    /// Block size in bytes for memory operations
+    pub block_size: u32,
+    /// Algorithm version
+    pub version: u32,
+}
+
+/// Fill in the algorithm parameters of this build
+/// - out: pointer to a UHashParams to write
+#[unsafe(no_mangle)]
+pub extern "C" fn uhash_get_params(out: *mut UHashParams) {
+    if out.is_null() {
+        return;
+    }
+
+    let params = UHashParams {
+        chains: CHAINS as u32,
+        scratchpad_size: SCRATCHPAD_SIZE as u32,
+        total_memory: TOTAL_MEMORY as u32,
+        rounds: ROUNDS as u32,
+        block_size: BLOCK_SIZE as u32,
+        version: VERSION as u32,
+    };
+    unsafe {
+        out.write(params);
+    }
+}
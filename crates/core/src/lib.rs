@@ -58,15 +58,40 @@
 //! [dependencies]
 //! uhash-core = { version = "0.2", default-features = false }
 //! ```
+//!
+//! ## Share Difficulty
+//!
+//! The [`difficulty`] module converts between difficulties, expected hashes
+//! and share rates, for pools that accept shares below the network difficulty.
+//!
+//! ## Test Vectors
+//!
+//! [`vectors::TEST_VECTORS`] are the official input/hash pairs; a build that
+//! fails [`vectors::self_test`] must not mine.
+//!
+//! ## Batch Verification
+//!
+//! The [`verify`] module checks claimed hashes against their inputs and
+//! difficulties; with the `parallel` feature, [`verify::verify_proofs_parallel`]
+//! checks a batch on the rayon thread pool with reused hashers.
+//!
+//! ## Phase Timing
+//!
+//! With the `timing` feature, [`UniversalHash::hash_timed`] also returns how
+//! long scratchpad initialization, the mixing rounds and finalization took
+//! ([`HashTiming`]).
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+pub mod difficulty;
 mod params;
 mod primitives;
 mod uhash;
+pub mod vectors;
+pub mod verify;
 
 #[cfg(feature = "std")]
 mod ffi;
@@ -74,5 +99,8 @@ mod ffi;
 pub use params::*;
 pub use uhash::{UniversalHash, hash, meets_difficulty};
 
+#[cfg(feature = "timing")]
+pub use uhash::HashTiming;
+
 #[cfg(test)]
 mod tests;
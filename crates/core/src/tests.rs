@@ -98,6 +98,24 @@ fn test_hasher_reusability() {
     assert_eq!(hash1, hash1_again);
 }
 
+#[cfg(feature = "timing")]
+#[test]
+fn test_hash_timed_matches_hash() {
+    let mut hasher = UniversalHash::new();
+    let (timed, timing) = hasher.hash_timed(b"timed input");
+    assert_eq!(timed, hash(b"timed input"));
+    assert!(timing.rounds > core::time::Duration::ZERO);
+    assert_eq!(
+        timing.total(),
+        timing.scratchpad_init + timing.rounds + timing.finalize
+    );
+
+    for vector in crate::vectors::TEST_VECTORS {
+        let input = vector.input();
+        assert_eq!(hasher.hash_timed(&input).0, hash(&input), "{}", vector.name);
+    }
+}
+
 #[test]
 fn test_empty_input() {
     let result = hash(b"");
@@ -603,3 +621,146 @@ fn timing_breakdown() {
         per_hash.saturating_sub(scratchpad_init_est + rounds_est)
     );
 }
+
+#[test]
+fn test_share_difficulty() {
+    use crate::difficulty::*;
+
+    assert_eq!(share_difficulty(24, 8), 16);
+    assert_eq!(share_difficulty(4, 8), 0);
+    assert_eq!(expected_hashes(0), 1.0);
+    assert_eq!(expected_hashes(16), 65536.0);
+    assert_eq!(shares_per_minute(65536.0, 16), 60.0);
+
+    // 10,000 hashes per share: 2^13 is the nearest power of two
+    assert_eq!(difficulty_for_rate(1000.0, 6.0), 13);
+    // The rounding point between 2^13 and 2^14 is 2^13.5 = 8192 * 1.414...
+    assert_eq!(difficulty_for_rate(8192.0 * 1.4, 60.0), 13);
+    assert_eq!(difficulty_for_rate(8192.0 * 1.5, 60.0), 14);
+    assert_eq!(difficulty_for_rate(0.0, 6.0), 0);
+    assert_eq!(difficulty_for_rate(1000.0, 0.0), MAX_DIFFICULTY);
+
+    assert_eq!(share_offset_for_rate(24, 1000.0, 6.0), 11);
+    // A miner that finds proofs faster than the target needs no offset
+    assert_eq!(share_offset_for_rate(8, 1000.0, 6.0), 0);
+}
+
+#[test]
+fn test_luck() {
+    use crate::difficulty::*;
+
+    let mut luck = Luck::default();
+    assert_eq!(luck.luck(), f64::INFINITY);
+    assert_eq!(luck.probability(), 1.0);
+
+    // Half the expected hashes over two rounds at different difficulties
+    luck.add(4096, 16);
+    luck.add(16384, 18);
+    assert_eq!(luck.effort(), 0.125);
+    luck.add(98304, 17);
+    assert_eq!(luck.effort(), 0.875);
+    luck.add(8192, 16);
+    assert_eq!(luck.effort(), 1.0);
+    assert_eq!(luck.luck(), 1.0);
+    // About 37% of proofs take longer than the expected hashes
+    assert!((luck.probability() - (-1.0f64).exp()).abs() < 1e-12);
+
+    let mut lucky = Luck::default();
+    lucky.add(32768, 16);
+    assert_eq!(lucky.luck(), 2.0);
+    assert!((lucky.probability() - (-0.5f64).exp()).abs() < 1e-12);
+}
+
+#[test]
+fn test_verify_proofs() {
+    use crate::verify::*;
+
+    let proofs: Vec<Proof> = (0u64..6)
+        .map(|nonce| {
+            let mut input = b"verify batch header".to_vec();
+            input.extend_from_slice(&nonce.to_le_bytes());
+            Proof {
+                hash: hash(&input),
+                input,
+                difficulty: 0,
+            }
+        })
+        .collect();
+    let mut tampered = proofs.clone();
+    tampered[1].hash[31] ^= 1;
+    tampered[3].hash = [0xff; 32];
+    tampered[3].difficulty = 1;
+
+    let expected = [
+        VerifyResult::Valid,
+        VerifyResult::HashMismatch,
+        VerifyResult::Valid,
+        VerifyResult::BelowDifficulty,
+        VerifyResult::Valid,
+        VerifyResult::Valid,
+    ];
+    assert_eq!(verify_proofs(&tampered), expected);
+    #[cfg(all(feature = "parallel", feature = "std"))]
+    {
+        assert_eq!(verify_proofs_parallel(&tampered), expected);
+        assert!(
+            verify_proofs_parallel(&proofs)
+                .iter()
+                .all(VerifyResult::is_valid)
+        );
+        assert!(verify_proofs_parallel(&[]).is_empty());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hasher_pool_reuses_hashers() {
+    use crate::verify::HasherPool;
+
+    let pool = HasherPool::new(1);
+    {
+        let (mut first, _second) = (pool.get(), pool.get());
+        assert_eq!(first.hash(b"pooled"), hash(b"pooled"));
+    }
+    // Only `capacity` hashers are kept when more come back
+    assert_eq!(pool.idle_count(), 1);
+    let mut again = pool.get();
+    assert_eq!(again.hash(b"pooled"), hash(b"pooled"));
+    assert_eq!(pool.idle_count(), 0);
+}
+
+#[test]
+fn test_official_vectors() {
+    use crate::vectors::*;
+
+    let mut hasher = UniversalHash::new();
+    for vector in TEST_VECTORS {
+        assert_eq!(hash(&vector.input()), vector.hash(), "{}", vector.name);
+    }
+    assert!(self_test(&mut hasher));
+
+    // A vector with another expected hash reports what was computed
+    let altered = TestVector {
+        hash_hex: "00b37e351ab7b7616e415fd350adb55fea92fb8027f9e9695387b37392bafab4",
+        ..TEST_VECTORS[4]
+    };
+    assert_eq!(altered.mismatch(&mut hasher), Some(TEST_VECTORS[4].hash()));
+    assert_eq!(TEST_VECTORS[4].input().len(), 32 + 46 + 8 + 8);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_ffi_get_params() {
+    use crate::ffi::{UHashParams, uhash_get_params};
+    use crate::params::*;
+
+    let mut params = UHashParams::default();
+    uhash_get_params(&mut params);
+    assert_eq!(params.chains as usize, CHAINS);
+    assert_eq!(params.scratchpad_size, 512 * 1024);
+    assert_eq!(params.total_memory as usize, TOTAL_MEMORY);
+    assert_eq!(params.rounds as usize, ROUNDS);
+    assert_eq!(params.block_size as usize, BLOCK_SIZE);
+    assert_eq!(params.version, 4);
+    uhash_get_params(core::ptr::null_mut());
+}
@@ -21,6 +21,9 @@ use sha2::{Digest, Sha256};
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+#[cfg(feature = "timing")]
+use std::time::{Duration, Instant};
+
 use crate::params::*;
 use crate::primitives::{aes_compress, blake3_compress, sha256_compress};
 
@@ -31,6 +34,35 @@ const ADDRESS_MASK: usize = BLOCKS_PER_SCRATCHPAD - 1;
 /// Golden ratio constant for seed generation (Fibonacci hashing constant)
 const GOLDEN_RATIO: u64 = 0x9E3779B97F4A7C15;
 
+/// Time spent in each phase of a hash, from [`UniversalHash::hash_timed`]
+#[cfg(feature = "timing")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HashTiming {
+    /// Phase 1: chain seeds and scratchpad expansion
+    pub scratchpad_init: Duration,
+    /// Phase 2: the mixing rounds
+    pub rounds: Duration,
+    /// Phase 3: combining the chains into the output
+    pub finalize: Duration,
+}
+
+#[cfg(feature = "timing")]
+impl HashTiming {
+    /// Time spent in all three phases
+    pub fn total(&self) -> Duration {
+        self.scratchpad_init + self.rounds + self.finalize
+    }
+}
+
+#[cfg(feature = "timing")]
+impl core::ops::AddAssign for HashTiming {
+    fn add_assign(&mut self, other: Self) {
+        self.scratchpad_init += other.scratchpad_init;
+        self.rounds += other.rounds;
+        self.finalize += other.finalize;
+    }
+}
+
 /// UniversalHash v4 hasher
 ///
 /// This struct maintains the scratchpads and chain states needed for hashing.
@@ -64,17 +96,50 @@ impl UniversalHash {
     ///
     /// Returns a 32-byte hash.
     pub fn hash(&mut self, input: &[u8]) -> [u8; 32] {
+        self.hash_phases(input, || {})
+    }
+
+    /// Compute the hash like [`UniversalHash::hash`], timing each phase
+    ///
+    /// The clock reads cost a little, so mining uses [`UniversalHash::hash`].
+    #[cfg(feature = "timing")]
+    pub fn hash_timed(&mut self, input: &[u8]) -> ([u8; 32], HashTiming) {
+        let mut laps = [Duration::ZERO; 3];
+        let mut phase = 0;
+        let mut start = Instant::now();
+        let hash = self.hash_phases(input, || {
+            laps[phase] = start.elapsed();
+            phase += 1;
+            start = Instant::now();
+        });
+
+        let [scratchpad_init, rounds, finalize] = laps;
+        let timing = HashTiming {
+            scratchpad_init,
+            rounds,
+            finalize,
+        };
+        (hash, timing)
+    }
+
+    /// Run the three phases of a hash, calling `end_phase` after each one
+    #[inline(always)]
+    fn hash_phases(&mut self, input: &[u8], mut end_phase: impl FnMut()) -> [u8; 32] {
         // Extract effective nonce from last 8 bytes of input (or hash if shorter)
         self.effective_nonce = extract_nonce(input);
 
         // Phase 1: Initialize scratchpads using input (spec-compliant seed generation)
         self.init_scratchpads(input);
+        end_phase();
 
         // Phase 2: Execute main mixing rounds (spec-compliant, no cross-chain mixing)
         self.execute_rounds();
+        end_phase();
 
         // Phase 3: Finalize and produce output
-        self.finalize()
+        let hash = self.finalize();
+        end_phase();
+        hash
     }
 
     /// Initialize all scratchpads from input using expansion
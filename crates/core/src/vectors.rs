@@ -0,0 +1,119 @@
+//! Official test vectors
+//!
+//! Inputs in the mining layout (epoch seed, miner address, timestamp, nonce)
+//! with the hash every build must produce. A build with a broken AES,
+//! SHA-256 or BLAKE3 path (a bad SIMD backend, a miscompiled WASM module)
+//! still hashes, only to the wrong values; checking these before mining
+//! stops it from spending hours on proofs the contract will reject.
+//!
+//! ```rust
+//! use uhash_core::UniversalHash;
+//! use uhash_core::vectors::{TEST_VECTORS, self_test};
+//!
+//! let mut hasher = UniversalHash::new();
+//! assert!(self_test(&mut hasher));
+//! assert_eq!(TEST_VECTORS[0].mismatch(&mut hasher), None);
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::uhash::UniversalHash;
+
+/// An input and the hash it must give, both hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestVector {
+    pub name: &'static str,
+    pub input_hex: &'static str,
+    pub hash_hex: &'static str,
+}
+
+/// The vectors every build must reproduce
+pub const TEST_VECTORS: &[TestVector] = &[
+    TestVector {
+        name: "zero_seed_nonce_0",
+        // Seed 0x00 × 32, address 0x01 × 20, timestamp 0, nonce 0
+        input_hex: "0000000000000000000000000000000000000000000000000000000000000000\
+                    0101010101010101010101010101010101010101\
+                    0000000000000000\
+                    0000000000000000",
+        hash_hex: "4b4d8575471999dcf2c1acb429361718580f4c291c4628957a643c269500b469",
+    },
+    TestVector {
+        name: "zero_seed_nonce_1",
+        input_hex: "0000000000000000000000000000000000000000000000000000000000000000\
+                    0101010101010101010101010101010101010101\
+                    0000000000000000\
+                    0100000000000000",
+        hash_hex: "99d9682f743ad694298e6d57f22e042d3ed92bba2abb020f957039fe4dc383b3",
+    },
+    TestVector {
+        name: "ab_seed_nonce_0",
+        input_hex: "abababababababababababababababababababababababababababababababab\
+                    0101010101010101010101010101010101010101\
+                    0000000000000000\
+                    0000000000000000",
+        hash_hex: "1d39fc8ba89cb3204f5da7e6175352775f1cfc07a00912ef9545a7e8bd7e7b35",
+    },
+    TestVector {
+        name: "cross_platform",
+        // Seed 0xaa × 32, "bostrom1testaddr12345", timestamp 1000, nonce 42
+        input_hex: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\
+                    626f7374726f6d3174657374616464723132333435\
+                    e803000000000000\
+                    2a00000000000000",
+        hash_hex: "33f0dc0c4246aa226a40ed0a1ab53caea59f7577fa87552faa756688f13b2f03",
+    },
+    TestVector {
+        name: "mainnet_proof",
+        // A proof accepted on Bostrom: timestamp 1770986039, nonce 2^63 + 85
+        input_hex: "6ebb4eda559a631b31ec2d5db3a6fddb08ede58462c917d5bff6f0da284c1afc\
+                    626f7374726f6d31733766757934336838763668\
+                    7a6a74756c7839677879703330726c397435637a\
+                    337a35366d6b\
+                    371a8f6900000000\
+                    5500000000000080",
+        hash_hex: "00b37e351ab7b7616e415fd350adb55fea92fb8027f9e9695387b37392bafab5",
+    },
+];
+
+impl TestVector {
+    pub fn input(&self) -> Vec<u8> {
+        decode_hex(self.input_hex)
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        decode_hex(self.hash_hex)
+            .try_into()
+            .expect("vector hashes are 32 bytes")
+    }
+
+    /// The hash `hasher` gives the input, if it is not the expected one
+    pub fn mismatch(&self, hasher: &mut UniversalHash) -> Option<[u8; 32]> {
+        let actual = hasher.hash(&self.input());
+        (actual != self.hash()).then_some(actual)
+    }
+}
+
+/// Whether `hasher` reproduces every vector in [`TEST_VECTORS`]
+pub fn self_test(hasher: &mut UniversalHash) -> bool {
+    TEST_VECTORS
+        .iter()
+        .all(|vector| vector.mismatch(hasher).is_none())
+}
+
+/// Bytes of a vector's lowercase hex
+fn decode_hex(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .bytes()
+        .map(|b| match b {
+            b'0'..=b'9' => b - b'0',
+            b'a'..=b'f' => b - b'a' + 10,
+            _ => panic!("vector hex is lowercase"),
+        })
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect()
+}
@@ -0,0 +1,183 @@
+//! Checking many proofs at once
+//!
+//! A relayer, pool or auditor checks proofs far faster than it could mine
+//! them, but each check is still a full hash: 2 MB of scratchpads and 12,288
+//! rounds per chain. [`verify_proofs_parallel`] spreads a batch over the
+//! rayon thread pool, each thread hashing with a hasher from a
+//! [`HasherPool`] instead of allocating fresh scratchpads per proof. A proof whose claimed
+//! hash does not meet its difficulty is refused before any hashing.
+//!
+//! ```rust
+//! use uhash_core::verify::{Proof, VerifyResult, verify_proof};
+//! use uhash_core::{UniversalHash, hash};
+//!
+//! let input = b"header||nonce...".to_vec();
+//! let proof = Proof { hash: hash(&input), input, difficulty: 0 };
+//! assert_eq!(verify_proof(&mut UniversalHash::new(), &proof), VerifyResult::Valid);
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "parallel", feature = "std"))]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
+use std::ops::{Deref, DerefMut};
+#[cfg(all(feature = "parallel", feature = "std"))]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+use crate::uhash::{UniversalHash, meets_difficulty};
+
+/// A hash claimed for an input at a difficulty
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Proof {
+    /// Hash input, nonce in the last 8 bytes
+    pub input: Vec<u8>,
+    /// The claimed U-Hash of `input`
+    pub hash: [u8; 32],
+    /// Leading zero bits the hash must have
+    pub difficulty: u32,
+}
+
+/// Verdict on one [`Proof`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerifyResult {
+    Valid,
+    /// The claimed hash is not the U-Hash of the input
+    HashMismatch,
+    /// The claimed hash does not meet the difficulty; it was not hashed
+    BelowDifficulty,
+}
+
+impl VerifyResult {
+    pub fn is_valid(&self) -> bool {
+        *self == VerifyResult::Valid
+    }
+}
+
+/// Check one proof with `hasher`
+pub fn verify_proof(hasher: &mut UniversalHash, proof: &Proof) -> VerifyResult {
+    if !meets_difficulty(&proof.hash, proof.difficulty) {
+        return VerifyResult::BelowDifficulty;
+    }
+    if hasher.hash(&proof.input) != proof.hash {
+        return VerifyResult::HashMismatch;
+    }
+    VerifyResult::Valid
+}
+
+/// Check `proofs` one after another with a single hasher, one verdict per
+/// proof in order
+pub fn verify_proofs(proofs: &[Proof]) -> Vec<VerifyResult> {
+    let mut hasher = UniversalHash::new();
+    proofs
+        .iter()
+        .map(|proof| verify_proof(&mut hasher, proof))
+        .collect()
+}
+
+/// Check `proofs` on the rayon thread pool, one verdict per proof in order
+///
+/// Hashers are taken from a process-wide pool and returned after the batch,
+/// so repeated calls allocate scratchpads only once per thread.
+#[cfg(all(feature = "parallel", feature = "std"))]
+pub fn verify_proofs_parallel(proofs: &[Proof]) -> Vec<VerifyResult> {
+    proofs
+        .par_iter()
+        .map_init(
+            || parallel_hashers().get(),
+            |hasher, proof| verify_proof(hasher, proof),
+        )
+        .collect()
+}
+
+/// Hashers kept for reuse; more are made when all are in use
+///
+/// Each hasher holds 2 MB of scratchpads, so a verifier checking proofs on
+/// several threads takes them from a pool instead of allocating per proof.
+#[cfg(feature = "std")]
+pub struct HasherPool {
+    idle: Mutex<Vec<UniversalHash>>,
+    capacity: usize,
+}
+
+/// A hasher taken from a [`HasherPool`], returned to it on drop
+#[cfg(feature = "std")]
+pub struct PooledHasher<'a> {
+    pool: &'a HasherPool,
+    hasher: Option<UniversalHash>,
+}
+
+#[cfg(feature = "std")]
+impl HasherPool {
+    /// A pool keeping up to `capacity` idle hashers, made as they are needed
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            idle: Mutex::new(Vec::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// An idle hasher, or a new one if none is
+    pub fn get(&self) -> PooledHasher<'_> {
+        let hasher = self.idle().pop().unwrap_or_default();
+        PooledHasher {
+            pool: self,
+            hasher: Some(hasher),
+        }
+    }
+
+    /// Hashers waiting to be reused
+    pub fn idle_count(&self) -> usize {
+        self.idle().len()
+    }
+
+    fn idle(&self) -> MutexGuard<'_, Vec<UniversalHash>> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Debug for HasherPool {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HasherPool")
+            .field("idle", &self.idle_count())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Deref for PooledHasher<'_> {
+    type Target = UniversalHash;
+
+    fn deref(&self) -> &UniversalHash {
+        self.hasher.as_ref().expect("hasher is taken only on drop")
+    }
+}
+
+#[cfg(feature = "std")]
+impl DerefMut for PooledHasher<'_> {
+    fn deref_mut(&mut self) -> &mut UniversalHash {
+        self.hasher.as_mut().expect("hasher is taken only on drop")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for PooledHasher<'_> {
+    fn drop(&mut self) {
+        let mut idle = self.pool.idle();
+        if idle.len() < self.pool.capacity {
+            idle.extend(self.hasher.take());
+        }
+    }
+}
+
+/// The process-wide pool of [`verify_proofs_parallel`], one hasher per rayon thread
+#[cfg(all(feature = "parallel", feature = "std"))]
+fn parallel_hashers() -> &'static HasherPool {
+    static HASHERS: OnceLock<HasherPool> = OnceLock::new();
+    HASHERS.get_or_init(|| HasherPool::new(rayon::current_num_threads()))
+}
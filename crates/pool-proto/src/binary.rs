@@ -0,0 +1,359 @@
+//! Binary frames
+//!
+//! The same messages as the JSON frames, packed for metered connections: a
+//! tag byte, then the fields in declaration order. Integers are LEB128
+//! varints, strings a varint length and UTF-8, hex fields (`seed`, `hash`)
+//! a varint length and the raw bytes, options a presence byte. A job is
+//! about a third of its JSON size and a share under half.
+//!
+//! Bytes after the last known field are ignored, so a newer peer can append
+//! fields, and unknown tags decode as `Unknown`.
+
+use crate::{ClientMessage, Job, JobUpdate, ProtoError, RejectReason, ServerMessage};
+
+mod tag {
+    pub const HELLO: u8 = 1;
+    pub const SHARE: u8 = 2;
+    pub const CLIENT_KEEPALIVE: u8 = 3;
+
+    pub const WELCOME: u8 = 1;
+    pub const JOB: u8 = 2;
+    pub const JOB_UPDATE: u8 = 3;
+    pub const SET_DIFFICULTY: u8 = 4;
+    pub const ACCEPTED: u8 = 5;
+    pub const REJECTED: u8 = 6;
+    pub const SERVER_KEEPALIVE: u8 = 7;
+    pub const ERROR: u8 = 8;
+}
+
+pub(crate) fn encode_client(message: &ClientMessage) -> Vec<u8> {
+    let mut w = Writer::default();
+    match message {
+        ClientMessage::Hello {
+            versions,
+            address,
+            worker,
+            agent,
+            binary,
+        } => {
+            w.byte(tag::HELLO);
+            w.varint(versions.len() as u64);
+            for version in versions {
+                w.varint(u64::from(*version));
+            }
+            w.str(address);
+            w.option(worker.as_deref(), Writer::str);
+            w.option(agent.as_deref(), Writer::str);
+            w.byte(u8::from(*binary));
+        }
+        ClientMessage::Share {
+            job_id,
+            nonce,
+            hash,
+        } => {
+            w.byte(tag::SHARE);
+            w.str(job_id);
+            w.varint(*nonce);
+            w.hex(hash);
+        }
+        ClientMessage::Keepalive => w.byte(tag::CLIENT_KEEPALIVE),
+        // Never sent; a peer decodes it as unknown too
+        ClientMessage::Unknown => w.byte(0),
+    }
+    w.0
+}
+
+pub(crate) fn decode_client(frame: &[u8]) -> Result<ClientMessage, ProtoError> {
+    let mut r = Reader(frame);
+    Ok(match r.byte()? {
+        tag::HELLO => {
+            let count = r.varint()?;
+            let versions = (0..count).map(|_| r.u32()).collect::<Result<Vec<_>, _>>()?;
+            ClientMessage::Hello {
+                versions,
+                address: r.string()?,
+                worker: r.option(Reader::string)?,
+                agent: r.option(Reader::string)?,
+                binary: r.byte()? != 0,
+            }
+        }
+        tag::SHARE => ClientMessage::Share {
+            job_id: r.string()?,
+            nonce: r.varint()?,
+            hash: r.hex()?,
+        },
+        tag::CLIENT_KEEPALIVE => ClientMessage::Keepalive,
+        _ => ClientMessage::Unknown,
+    })
+}
+
+pub(crate) fn encode_server(message: &ServerMessage) -> Vec<u8> {
+    let mut w = Writer::default();
+    match message {
+        ServerMessage::Welcome {
+            version,
+            session,
+            difficulty,
+            binary,
+        } => {
+            w.byte(tag::WELCOME);
+            w.varint(u64::from(*version));
+            w.str(session);
+            w.varint(u64::from(*difficulty));
+            w.byte(u8::from(*binary));
+        }
+        ServerMessage::Job(job) => {
+            w.byte(tag::JOB);
+            w.str(&job.job_id);
+            w.hex(&job.seed);
+            w.str(&job.address);
+            w.varint(job.timestamp);
+            w.varint(u64::from(job.network_difficulty));
+        }
+        ServerMessage::JobUpdate(update) => {
+            w.byte(tag::JOB_UPDATE);
+            w.str(&update.job_id);
+            w.option(update.seed.as_deref(), Writer::hex);
+            w.option(update.timestamp, Writer::varint);
+            w.option(update.network_difficulty.map(u64::from), Writer::varint);
+        }
+        ServerMessage::SetDifficulty { difficulty } => {
+            w.byte(tag::SET_DIFFICULTY);
+            w.varint(u64::from(*difficulty));
+        }
+        ServerMessage::Accepted {
+            job_id,
+            nonce,
+            proof,
+        } => {
+            w.byte(tag::ACCEPTED);
+            w.str(job_id);
+            w.varint(*nonce);
+            w.byte(u8::from(*proof));
+        }
+        ServerMessage::Rejected {
+            job_id,
+            nonce,
+            reason,
+        } => {
+            w.byte(tag::REJECTED);
+            w.str(job_id);
+            w.varint(*nonce);
+            w.byte(reason_code(*reason));
+        }
+        ServerMessage::Keepalive => w.byte(tag::SERVER_KEEPALIVE),
+        ServerMessage::Error { message } => {
+            w.byte(tag::ERROR);
+            w.str(message);
+        }
+        ServerMessage::Unknown => w.byte(0),
+    }
+    w.0
+}
+
+pub(crate) fn decode_server(frame: &[u8]) -> Result<ServerMessage, ProtoError> {
+    let mut r = Reader(frame);
+    Ok(match r.byte()? {
+        tag::WELCOME => ServerMessage::Welcome {
+            version: r.u32()?,
+            session: r.string()?,
+            difficulty: r.u32()?,
+            binary: r.byte()? != 0,
+        },
+        tag::JOB => ServerMessage::Job(Job {
+            job_id: r.string()?,
+            seed: r.hex()?,
+            address: r.string()?,
+            timestamp: r.varint()?,
+            network_difficulty: r.u32()?,
+        }),
+        tag::JOB_UPDATE => ServerMessage::JobUpdate(JobUpdate {
+            job_id: r.string()?,
+            seed: r.option(Reader::hex)?,
+            timestamp: r.option(Reader::varint)?,
+            network_difficulty: r.option(Reader::u32)?,
+        }),
+        tag::SET_DIFFICULTY => ServerMessage::SetDifficulty {
+            difficulty: r.u32()?,
+        },
+        tag::ACCEPTED => ServerMessage::Accepted {
+            job_id: r.string()?,
+            nonce: r.varint()?,
+            proof: r.byte()? != 0,
+        },
+        tag::REJECTED => ServerMessage::Rejected {
+            job_id: r.string()?,
+            nonce: r.varint()?,
+            reason: reason_from_code(r.byte()?),
+        },
+        tag::SERVER_KEEPALIVE => ServerMessage::Keepalive,
+        tag::ERROR => ServerMessage::Error {
+            message: r.string()?,
+        },
+        _ => ServerMessage::Unknown,
+    })
+}
+
+fn reason_code(reason: RejectReason) -> u8 {
+    match reason {
+        RejectReason::Other => 0,
+        RejectReason::UnknownJob => 1,
+        RejectReason::StaleJob => 2,
+        RejectReason::Duplicate => 3,
+        RejectReason::LowDifficulty => 4,
+        RejectReason::InvalidHash => 5,
+    }
+}
+
+fn reason_from_code(code: u8) -> RejectReason {
+    match code {
+        1 => RejectReason::UnknownJob,
+        2 => RejectReason::StaleJob,
+        3 => RejectReason::Duplicate,
+        4 => RejectReason::LowDifficulty,
+        5 => RejectReason::InvalidHash,
+        _ => RejectReason::Other,
+    }
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn byte(&mut self, byte: u8) {
+        self.0.push(byte);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.bytes(s.as_bytes());
+    }
+
+    /// Hex as raw bytes; text that is not hex is sent as is, behind a marker
+    /// length no raw field can have
+    fn hex(&mut self, s: &str) {
+        match decode_hex(s) {
+            Some(bytes) => self.bytes(&bytes),
+            None => {
+                self.varint(u64::MAX);
+                self.str(s);
+            }
+        }
+    }
+
+    fn option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(value) => {
+                self.byte(1);
+                write(self, value);
+            }
+            None => self.byte(0),
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, ProtoError> {
+        let (&byte, rest) = self.0.split_first().ok_or_else(truncated)?;
+        self.0 = rest;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, ProtoError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ProtoError::Malformed("varint is too long".to_string()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ProtoError> {
+        u32::try_from(self.varint()?)
+            .map_err(|_| ProtoError::Malformed("integer out of range".to_string()))
+    }
+
+    fn bytes(&mut self) -> Result<&[u8], ProtoError> {
+        let len = self.varint()?;
+        self.take(len)
+    }
+
+    fn take(&mut self, len: u64) -> Result<&[u8], ProtoError> {
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= self.0.len())
+            .ok_or_else(truncated)?;
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn string(&mut self) -> Result<String, ProtoError> {
+        let bytes = self.bytes()?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| ProtoError::Malformed("string is not UTF-8".to_string()))
+    }
+
+    fn hex(&mut self) -> Result<String, ProtoError> {
+        match self.varint()? {
+            u64::MAX => self.string(),
+            len => Ok(encode_hex(self.take(len)?)),
+        }
+    }
+
+    fn option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, ProtoError>,
+    ) -> Result<Option<T>, ProtoError> {
+        match self.byte()? {
+            0 => Ok(None),
+            _ => read(self).map(Some),
+        }
+    }
+}
+
+fn truncated() -> ProtoError {
+    ProtoError::Malformed("frame is truncated".to_string())
+}
+
+/// Lowercase hex only, so decoding gives back the same text
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
+    };
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    bytes
+        .iter()
+        .flat_map(|b| [DIGITS[usize::from(b >> 4)], DIGITS[usize::from(b & 0xf)]])
+        .map(char::from)
+        .collect()
+}
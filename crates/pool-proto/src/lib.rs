@@ -0,0 +1,626 @@
+//! # UniversalHash Pool Protocol
+//!
+//! The messages a mining pool and its miners exchange, shared by the pool
+//! server, the `uhash` pool client and the browser miner.
+//!
+//! ## Framing
+//!
+//! Every message is one JSON object tagged by its `"type"`, sent as one
+//! WebSocket text frame (subprotocol [`WEBSOCKET_SUBPROTOCOL`]), or as one
+//! line over a plain TCP stream. Frames longer than [`MAX_FRAME_LEN`] are
+//! refused. Message types a peer does not know decode as `Unknown` and are
+//! ignored, so either side can add messages without breaking the other.
+//!
+//! From version [`COMPACT_VERSION`], a WebSocket miner on a metered
+//! connection can ask for binary frames (`to_binary` and `from_binary`,
+//! the same messages packed to a fraction of the size) with `binary` in
+//! `hello`; once `welcome` confirms it, both sides send binary frames only.
+//!
+//! ## Session
+//!
+//! 1. The miner sends `hello` with the protocol versions it speaks.
+//! 2. The pool answers `welcome` with the highest version both speak (see
+//!    [`negotiate`]) and the starting share difficulty, or `error` and closes.
+//! 3. The pool sends a `job`; the miner hashes
+//!    `seed || address || timestamp || nonce` and sends a `share` for each
+//!    hash meeting the share difficulty. A new job replaces the previous one.
+//!    From version [`COMPACT_VERSION`], a new job may come as a `job_update`
+//!    carrying only the fields that changed (see [`Job::announce`]).
+//! 4. The pool answers each share with `accepted` or `rejected`.
+//! 5. The pool sends `set_difficulty` whenever it retargets the miner.
+//! 6. Either side sends `keepalive` after [`KEEPALIVE_INTERVAL_SECS`] without
+//!    a message; a peer silent for [`KEEPALIVE_TIMEOUT_SECS`] is gone.
+//!
+//! ```rust
+//! use uhash_pool_proto::{ClientMessage, ServerMessage, PROTOCOL_VERSION};
+//!
+//! let hello = ClientMessage::hello("bostrom1miner", Some("rig-1".to_string()));
+//! let frame = hello.to_frame();
+//! assert!(frame.starts_with(r#"{"type":"hello""#));
+//!
+//! let welcome = ServerMessage::from_frame(
+//!     r#"{"type":"welcome","version":2,"session":"s1","difficulty":12}"#,
+//! )
+//! .unwrap();
+//! assert!(matches!(welcome, ServerMessage::Welcome { version: PROTOCOL_VERSION, .. }));
+//! ```
+
+mod binary;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Protocol version this crate speaks by default
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Every protocol version this crate can speak, oldest first
+pub const SUPPORTED_VERSIONS: &[u32] = &[1, 2];
+
+/// First version with `job_update` and binary frames
+pub const COMPACT_VERSION: u32 = 2;
+
+/// `Sec-WebSocket-Protocol` of pool connections
+pub const WEBSOCKET_SUBPROTOCOL: &str = "uhash-pool";
+
+/// Longest frame a peer accepts, in bytes
+pub const MAX_FRAME_LEN: usize = 16 * 1024;
+
+/// Quiet time after which a peer sends `keepalive`
+pub const KEEPALIVE_INTERVAL_SECS: u64 = 30;
+
+/// Silence after which a peer is taken to be gone
+pub const KEEPALIVE_TIMEOUT_SECS: u64 = 90;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProtoError {
+    #[error("Frame of {len} bytes exceeds the {MAX_FRAME_LEN}-byte limit")]
+    FrameTooLong { len: usize },
+
+    #[error("Malformed message: {0}")]
+    Malformed(String),
+
+    #[error(
+        "No common protocol version: peer speaks {offered:?}, this side {SUPPORTED_VERSIONS:?}"
+    )]
+    NoCommonVersion { offered: Vec<u32> },
+}
+
+/// Messages from a miner to the pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Opens the session
+    Hello {
+        /// Protocol versions the miner speaks
+        versions: Vec<u32>,
+        /// Address the miner's share of the rewards is paid to
+        address: String,
+        /// Worker name, to tell a miner's machines apart
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        worker: Option<String>,
+        /// Software and version, e.g. `uhash/0.2.8`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        agent: Option<String>,
+        /// Binary frames wanted after `welcome`
+        #[serde(default, skip_serializing_if = "is_false")]
+        binary: bool,
+    },
+
+    /// A hash meeting the share difficulty of job `job_id`
+    Share {
+        job_id: String,
+        nonce: u64,
+        /// Hex hash, checked by the pool against its own
+        hash: String,
+    },
+
+    Keepalive,
+
+    /// A message type this side does not know
+    #[serde(other)]
+    Unknown,
+}
+
+/// Messages from the pool to a miner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Answers `hello`
+    Welcome {
+        /// Protocol version of the session
+        version: u32,
+        /// Session id, for the pool's logs
+        session: String,
+        /// Starting share difficulty (leading zero bits)
+        difficulty: u32,
+        /// Both sides send binary frames from now on
+        #[serde(default, skip_serializing_if = "is_false")]
+        binary: bool,
+    },
+
+    /// Work to do, replacing any previous job
+    Job(Job),
+
+    /// The previous job with some fields changed, as a new job
+    JobUpdate(JobUpdate),
+
+    /// Share difficulty from now on
+    SetDifficulty {
+        difficulty: u32,
+    },
+
+    Accepted {
+        job_id: String,
+        nonce: u64,
+        /// The share also met the network difficulty and goes to the chain
+        #[serde(default)]
+        proof: bool,
+    },
+
+    Rejected {
+        job_id: String,
+        nonce: u64,
+        reason: RejectReason,
+    },
+
+    Keepalive,
+
+    /// Fatal; the pool closes the connection after it
+    Error {
+        message: String,
+    },
+
+    /// A message type this side does not know
+    #[serde(other)]
+    Unknown,
+}
+
+/// Why a share was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// No such job on this session
+    UnknownJob,
+    /// The job was replaced before the share arrived
+    StaleJob,
+    /// The share was submitted before
+    Duplicate,
+    /// The hash does not meet the share difficulty
+    LowDifficulty,
+    /// The hash is not the hash of the job and nonce
+    InvalidHash,
+    /// A reason this side does not know
+    #[serde(other)]
+    Other,
+}
+
+/// A pool job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub job_id: String,
+    /// Hex epoch seed
+    pub seed: String,
+    /// Address in the hash input: the pool's, which submits the proofs
+    pub address: String,
+    /// Timestamp in the hash input (unix seconds)
+    pub timestamp: u64,
+    /// Difficulty at which a share is also a proof for the chain
+    pub network_difficulty: u32,
+}
+
+/// The fields of a new job that differ from the previous one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobUpdate {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_difficulty: Option<u32>,
+}
+
+impl Job {
+    /// The message announcing this job to a miner on a session of `version`
+    /// whose last job is `previous`: an update if the session has them and
+    /// only the seed, timestamp or difficulty changed, otherwise the job
+    pub fn announce(&self, previous: Option<&Job>, version: u32) -> ServerMessage {
+        match previous {
+            Some(previous) if version >= COMPACT_VERSION && previous.address == self.address => {
+                fn changed<T: PartialEq>(new: T, old: T) -> Option<T> {
+                    (new != old).then_some(new)
+                }
+                ServerMessage::JobUpdate(JobUpdate {
+                    job_id: self.job_id.clone(),
+                    seed: changed(&self.seed, &previous.seed).cloned(),
+                    timestamp: changed(self.timestamp, previous.timestamp),
+                    network_difficulty: changed(
+                        self.network_difficulty,
+                        previous.network_difficulty,
+                    ),
+                })
+            }
+            _ => ServerMessage::Job(self.clone()),
+        }
+    }
+
+    /// The job `update` makes of this one
+    pub fn apply(&self, update: &JobUpdate) -> Job {
+        Job {
+            job_id: update.job_id.clone(),
+            seed: update.seed.clone().unwrap_or_else(|| self.seed.clone()),
+            address: self.address.clone(),
+            timestamp: update.timestamp.unwrap_or(self.timestamp),
+            network_difficulty: update.network_difficulty.unwrap_or(self.network_difficulty),
+        }
+    }
+}
+
+impl ClientMessage {
+    /// `hello` offering every supported version
+    pub fn hello(address: impl Into<String>, worker: Option<String>) -> Self {
+        Self::Hello {
+            versions: SUPPORTED_VERSIONS.to_vec(),
+            address: address.into(),
+            worker,
+            agent: None,
+            binary: false,
+        }
+    }
+
+    /// The message as a frame
+    pub fn to_frame(&self) -> String {
+        to_frame(self)
+    }
+
+    /// Parse a frame; surrounding whitespace, such as a line's newline, is ignored
+    pub fn from_frame(frame: &str) -> Result<Self, ProtoError> {
+        from_frame(frame)
+    }
+
+    /// The message as a binary frame
+    pub fn to_binary(&self) -> Vec<u8> {
+        binary::encode_client(self)
+    }
+
+    /// Parse a binary frame
+    pub fn from_binary(frame: &[u8]) -> Result<Self, ProtoError> {
+        check_len(frame.len())?;
+        binary::decode_client(frame)
+    }
+}
+
+impl ServerMessage {
+    /// The message as a frame
+    pub fn to_frame(&self) -> String {
+        to_frame(self)
+    }
+
+    /// Parse a frame; surrounding whitespace, such as a line's newline, is ignored
+    pub fn from_frame(frame: &str) -> Result<Self, ProtoError> {
+        from_frame(frame)
+    }
+
+    /// The message as a binary frame
+    pub fn to_binary(&self) -> Vec<u8> {
+        binary::encode_server(self)
+    }
+
+    /// Parse a binary frame
+    pub fn from_binary(frame: &[u8]) -> Result<Self, ProtoError> {
+        check_len(frame.len())?;
+        binary::decode_server(frame)
+    }
+}
+
+/// Version of a session with a peer offering `offered`: the highest both speak
+pub fn negotiate(offered: &[u32]) -> Result<u32, ProtoError> {
+    SUPPORTED_VERSIONS
+        .iter()
+        .rev()
+        .find(|v| offered.contains(v))
+        .copied()
+        .ok_or_else(|| ProtoError::NoCommonVersion {
+            offered: offered.to_vec(),
+        })
+}
+
+fn to_frame<M: Serialize>(message: &M) -> String {
+    serde_json::to_string(message).expect("protocol messages serialize")
+}
+
+fn from_frame<M: DeserializeOwned>(frame: &str) -> Result<M, ProtoError> {
+    check_len(frame.len())?;
+    serde_json::from_str(frame.trim()).map_err(|e| ProtoError::Malformed(e.to_string()))
+}
+
+fn check_len(len: usize) -> Result<(), ProtoError> {
+    if len > MAX_FRAME_LEN {
+        return Err(ProtoError::FrameTooLong { len });
+    }
+    Ok(())
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> Job {
+        Job {
+            job_id: "j1".to_string(),
+            seed: "11".repeat(32),
+            address: "bostrom1pool".to_string(),
+            timestamp: 1_700_000_000,
+            network_difficulty: 20,
+        }
+    }
+
+    #[test]
+    fn test_frames() {
+        let share = ClientMessage::Share {
+            job_id: "j1".to_string(),
+            nonce: 42,
+            hash: "00ab".to_string(),
+        };
+        assert_eq!(
+            share.to_frame(),
+            r#"{"type":"share","job_id":"j1","nonce":42,"hash":"00ab"}"#
+        );
+        assert_eq!(ClientMessage::from_frame(&share.to_frame()), Ok(share));
+        assert_eq!(
+            ClientMessage::Keepalive.to_frame(),
+            r#"{"type":"keepalive"}"#
+        );
+
+        let messages = [
+            ServerMessage::Job(job()),
+            ServerMessage::SetDifficulty { difficulty: 14 },
+            ServerMessage::Accepted {
+                job_id: "j1".to_string(),
+                nonce: 42,
+                proof: true,
+            },
+            ServerMessage::Rejected {
+                job_id: "j1".to_string(),
+                nonce: 42,
+                reason: RejectReason::StaleJob,
+            },
+            ServerMessage::Error {
+                message: "bye".to_string(),
+            },
+        ];
+        for message in messages {
+            let line = format!("{}\n", message.to_frame());
+            assert_eq!(ServerMessage::from_frame(&line), Ok(message));
+        }
+    }
+
+    #[test]
+    fn test_unknown_messages_are_tolerated() {
+        assert_eq!(
+            ServerMessage::from_frame(r#"{"type":"vote","topic":"fees"}"#),
+            Ok(ServerMessage::Unknown)
+        );
+        assert_eq!(
+            ServerMessage::from_frame(
+                r#"{"type":"rejected","job_id":"j","nonce":1,"reason":"banned"}"#
+            ),
+            Ok(ServerMessage::Rejected {
+                job_id: "j".to_string(),
+                nonce: 1,
+                reason: RejectReason::Other,
+            })
+        );
+        assert!(matches!(
+            ClientMessage::from_frame(r#"{"type":"share","nonce":1}"#),
+            Err(ProtoError::Malformed(_))
+        ));
+        let long = format!(
+            r#"{{"type":"keepalive","pad":"{}"}}"#,
+            "x".repeat(MAX_FRAME_LEN)
+        );
+        assert!(matches!(
+            ClientMessage::from_frame(&long),
+            Err(ProtoError::FrameTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&[1, 2, 7]), Ok(PROTOCOL_VERSION));
+        assert_eq!(negotiate(&[1]), Ok(1));
+        assert_eq!(
+            negotiate(&[0, 9]),
+            Err(ProtoError::NoCommonVersion {
+                offered: vec![0, 9]
+            })
+        );
+        let ClientMessage::Hello { versions, .. } = ClientMessage::hello("bostrom1miner", None)
+        else {
+            unreachable!()
+        };
+        assert_eq!(negotiate(&versions), Ok(PROTOCOL_VERSION));
+    }
+
+    #[test]
+    fn test_version_1_frames_are_unchanged() {
+        let job = ServerMessage::Job(job());
+        assert_eq!(
+            job.to_frame(),
+            r#"{"type":"job","job_id":"j1","seed":"1111111111111111111111111111111111111111111111111111111111111111","address":"bostrom1pool","timestamp":1700000000,"network_difficulty":20}"#
+        );
+        assert_eq!(
+            ClientMessage::from_frame(
+                r#"{"type":"hello","versions":[1],"address":"bostrom1miner"}"#
+            ),
+            Ok(ClientMessage::Hello {
+                versions: vec![1],
+                address: "bostrom1miner".to_string(),
+                worker: None,
+                agent: None,
+                binary: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_job_updates() {
+        let first = job();
+        let next = Job {
+            job_id: "j2".to_string(),
+            network_difficulty: 21,
+            ..first.clone()
+        };
+        let update = next.announce(Some(&first), COMPACT_VERSION);
+        assert_eq!(
+            update.to_frame(),
+            r#"{"type":"job_update","job_id":"j2","network_difficulty":21}"#
+        );
+        let ServerMessage::JobUpdate(update) = update else {
+            unreachable!()
+        };
+        assert_eq!(first.apply(&update), next);
+
+        // Full jobs for version 1, the first job and a new address
+        assert_eq!(
+            next.announce(Some(&first), 1),
+            ServerMessage::Job(next.clone())
+        );
+        assert_eq!(
+            next.announce(None, COMPACT_VERSION),
+            ServerMessage::Job(next.clone())
+        );
+        let moved = Job {
+            address: "bostrom1other".to_string(),
+            ..next.clone()
+        };
+        assert_eq!(
+            moved.announce(Some(&first), COMPACT_VERSION),
+            ServerMessage::Job(moved.clone())
+        );
+    }
+
+    #[test]
+    fn test_binary_frames() {
+        let client = [
+            ClientMessage::Hello {
+                versions: vec![1, 2],
+                address: "bostrom1miner".to_string(),
+                worker: Some("phone".to_string()),
+                agent: None,
+                binary: true,
+            },
+            ClientMessage::Share {
+                job_id: "j1".to_string(),
+                nonce: u64::MAX,
+                hash: "00ab".repeat(16),
+            },
+            ClientMessage::Keepalive,
+        ];
+        for message in client {
+            assert_eq!(
+                ClientMessage::from_binary(&message.to_binary()),
+                Ok(message)
+            );
+        }
+
+        let server = [
+            ServerMessage::Welcome {
+                version: 2,
+                session: "s1".to_string(),
+                difficulty: 12,
+                binary: true,
+            },
+            ServerMessage::Job(job()),
+            ServerMessage::JobUpdate(JobUpdate {
+                job_id: "j2".to_string(),
+                seed: Some("22".repeat(32)),
+                timestamp: None,
+                network_difficulty: Some(21),
+            }),
+            ServerMessage::SetDifficulty { difficulty: 14 },
+            ServerMessage::Accepted {
+                job_id: "j1".to_string(),
+                nonce: 42,
+                proof: false,
+            },
+            ServerMessage::Rejected {
+                job_id: "j1".to_string(),
+                nonce: 42,
+                reason: RejectReason::Duplicate,
+            },
+            ServerMessage::Keepalive,
+            ServerMessage::Error {
+                message: "bye".to_string(),
+            },
+        ];
+        for message in server {
+            assert_eq!(
+                ServerMessage::from_binary(&message.to_binary()),
+                Ok(message)
+            );
+        }
+
+        // Well under the JSON size on the messages a miner sees most
+        let job = ServerMessage::Job(job());
+        assert!(job.to_binary().len() * 2 < job.to_frame().len());
+        let share = ClientMessage::Share {
+            job_id: "j1".to_string(),
+            nonce: 1_234_567,
+            hash: "00".repeat(32),
+        };
+        assert!(share.to_binary().len() * 2 < share.to_frame().len());
+
+        // Seeds that are not lowercase hex survive as text
+        let odd = ServerMessage::Job(Job {
+            seed: "ABCD".to_string(),
+            ..self::job()
+        });
+        assert_eq!(ServerMessage::from_binary(&odd.to_binary()), Ok(odd));
+    }
+
+    #[test]
+    fn test_binary_tolerance() {
+        // Unknown tags, unknown reasons and appended fields
+        assert_eq!(
+            ServerMessage::from_binary(&[99, 1, 2]),
+            Ok(ServerMessage::Unknown)
+        );
+        assert_eq!(
+            ServerMessage::from_binary(&[6, 1, b'j', 1, 200]),
+            Ok(ServerMessage::Rejected {
+                job_id: "j".to_string(),
+                nonce: 1,
+                reason: RejectReason::Other,
+            })
+        );
+        let mut frame = ServerMessage::SetDifficulty { difficulty: 3 }.to_binary();
+        frame.extend_from_slice(&[7, 7]);
+        assert_eq!(
+            ServerMessage::from_binary(&frame),
+            Ok(ServerMessage::SetDifficulty { difficulty: 3 })
+        );
+
+        let share = ClientMessage::Share {
+            job_id: "j1".to_string(),
+            nonce: 1,
+            hash: "00".repeat(32),
+        }
+        .to_binary();
+        for len in 0..share.len() {
+            assert!(matches!(
+                ClientMessage::from_binary(&share[..len]),
+                Err(ProtoError::Malformed(_))
+            ));
+        }
+        assert!(matches!(
+            ClientMessage::from_binary(&vec![0; MAX_FRAME_LEN + 1]),
+            Err(ProtoError::FrameTooLong { .. })
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use uhash_core::vectors::TEST_VECTORS;
 use uhash_core::{UniversalHash, meets_difficulty};
 use wasm_bindgen::prelude::*;
 
@@ -61,6 +62,56 @@ pub fn hash_once(input: &[u8]) -> Vec<u8> {
     uhash_core::hash(input).to_vec()
 }
 
+/// Check that this build reproduces the official test vectors.
+/// Returns JSON string:
+/// `{"passed":true,"vectors":N,"failures":[]}`, or with each failing vector as
+/// `{"name":"...","expected":"...","actual":"..."}` in `failures`
+///
+/// Run it once at startup: a build with a broken AES or SIMD path still
+/// hashes, but every proof it finds is rejected by the contract.
+#[wasm_bindgen]
+pub fn self_test() -> String {
+    let mut hasher = UniversalHash::new();
+    let failures: Vec<String> = TEST_VECTORS
+        .iter()
+        .filter_map(|vector| {
+            vector.mismatch(&mut hasher).map(|actual| {
+                format!(
+                    r#"{{"name":"{}","expected":"{}","actual":"{}"}}"#,
+                    vector.name,
+                    vector.hash_hex,
+                    hex::encode(actual)
+                )
+            })
+        })
+        .collect();
+    format!(
+        r#"{{"passed":{},"vectors":{},"failures":[{}]}}"#,
+        failures.is_empty(),
+        TEST_VECTORS.len(),
+        failures.join(",")
+    )
+}
+
+/// The official test vectors as a JSON string:
+/// `[{"name":"...","input":"<hex>","hash":"<hex>"},...]`
+///
+/// JS code hashing the inputs (through `hash_once` or its own implementation)
+/// must get the same hashes as the Rust one.
+#[wasm_bindgen]
+pub fn test_vectors() -> String {
+    let vectors: Vec<String> = TEST_VECTORS
+        .iter()
+        .map(|vector| {
+            format!(
+                r#"{{"name":"{}","input":"{}","hash":"{}"}}"#,
+                vector.name, vector.input_hex, vector.hash_hex
+            )
+        })
+        .collect();
+    format!("[{}]", vectors.join(","))
+}
+
 /// Mining struct for Web Worker usage.
 /// Reuses UniversalHash across batches to avoid 2MB re-allocation per hash.
 #[wasm_bindgen]
@@ -123,4 +174,33 @@ impl Miner {
 
         format!(r#"{{"found":false,"count":{}}}"#, batch_size)
     }
+
+    /// Data the miner signs to have a `uhash-relay` submit a found proof.
+    ///
+    /// Pass it to the wallet's ADR-036 `signArbitrary(chainId, address, data)`
+    /// and the resulting signature to `relay_request`.
+    pub fn authorization(&self, hash: &str, nonce: f64) -> String {
+        format!(
+            r#"{{"hash":"{}","miner_address":"{}","nonce":{},"timestamp":{}}}"#,
+            hash,
+            String::from_utf8_lossy(&self.address_bytes),
+            nonce as u64,
+            u64::from_le_bytes(self.timestamp_bytes)
+        )
+    }
+
+    /// Body to POST to a relay's `/relay` endpoint for a found proof.
+    ///
+    /// `signature_json` is the `{pub_key, signature}` object `signArbitrary`
+    /// returned for `authorization(hash, nonce)`.
+    pub fn relay_request(&self, hash: &str, nonce: f64, signature_json: &str) -> String {
+        format!(
+            r#"{{"hash":"{}","nonce":{},"timestamp":{},"miner_address":"{}","authorization":{}}}"#,
+            hash,
+            nonce as u64,
+            u64::from_le_bytes(self.timestamp_bytes),
+            String::from_utf8_lossy(&self.address_bytes),
+            signature_json
+        )
+    }
 }